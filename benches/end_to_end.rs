@@ -0,0 +1,129 @@
+//! Latency of one `ExchangeData` round trip, from control inputs to decoded
+//! state, against an in-process [MockSimulator] over loopback TCP.
+//!
+//! The `stages` group times each step of the path on its own, without I/O;
+//! the `round_trip` group times the whole path through the sync and async
+//! bridges. Compare runs with `--save-baseline` and `--baseline`:
+//!
+//! ```text
+//! cargo bench --features bench-internals,rt-tokio --bench end_to_end -- --save-baseline main
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use tokio::runtime::Runtime;
+
+use realflight_bridge::mock::MockSimulator;
+use realflight_bridge::{
+    AsyncBridge, AsyncLocalBridge, Configuration, ControlInputs, RealFlightBridge,
+    RealFlightLocalBridge, TcpSoapClient, build_http_request, decode_simulator_state,
+    encode_control_inputs, encode_envelope,
+};
+
+static SIM_STATE_RESPONSE: &str = include_str!("../testdata/responses/return-data-200.xml");
+
+///
+/// Control inputs with every channel set, as a flight controller sends them
+///
+fn control_inputs() -> ControlInputs {
+    let mut inputs = ControlInputs::default();
+    for (i, channel) in inputs.channels.iter_mut().enumerate() {
+        *channel = i as f32 / 11.0;
+    }
+    inputs
+}
+
+///
+/// Mock simulator with the controller already injected, so exchanges succeed
+///
+fn start_simulator() -> MockSimulator {
+    let simulator = MockSimulator::start(0).unwrap();
+    simulator.set_controller_injected(true);
+    simulator
+}
+
+fn bench_stages(c: &mut Criterion) {
+    let simulator = start_simulator();
+    let client = TcpSoapClient::connect(&Configuration {
+        simulator_host: simulator.host(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let inputs = control_inputs();
+    let envelope = encode_envelope("ExchangeData", &encode_control_inputs(&inputs));
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/xml; charset=utf-8\r\nContent-Length: {}\r\nConnection: keep-alive\r\n\r\n{}",
+        SIM_STATE_RESPONSE.len(),
+        SIM_STATE_RESPONSE
+    );
+
+    let mut group = c.benchmark_group("stages");
+    group.bench_function("encode", |b| {
+        b.iter(|| {
+            let body = encode_control_inputs(black_box(&inputs));
+            black_box(encode_envelope("ExchangeData", &body))
+        })
+    });
+    group.bench_function("frame", |b| {
+        b.iter(|| {
+            black_box(build_http_request(
+                "ExchangeData",
+                black_box(&envelope),
+                true,
+            ))
+        })
+    });
+    group.bench_function("parse", |b| {
+        b.iter(|| {
+            black_box(
+                client
+                    .parse_response(black_box(response.as_bytes()))
+                    .unwrap(),
+            )
+        })
+    });
+    group.bench_function("decode", |b| {
+        b.iter(|| black_box(decode_simulator_state(black_box(SIM_STATE_RESPONSE)).unwrap()))
+    });
+    group.finish();
+}
+
+fn bench_round_trip(c: &mut Criterion) {
+    let simulator = start_simulator();
+    let inputs = control_inputs();
+
+    let bridge = RealFlightLocalBridge::with_configuration(&Configuration {
+        simulator_host: simulator.host(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let runtime = Runtime::new().unwrap();
+    let async_bridge = runtime
+        .block_on(
+            AsyncLocalBridge::builder()
+                .addr(simulator.address())
+                .build(),
+        )
+        .unwrap();
+
+    let mut group = c.benchmark_group("round_trip");
+    group.bench_function("sync", |b| {
+        b.iter(|| black_box(bridge.exchange_data(black_box(&inputs)).unwrap()))
+    });
+    group.bench_function("async", |b| {
+        b.iter(|| {
+            black_box(
+                runtime
+                    .block_on(async_bridge.exchange_data(black_box(&inputs)))
+                    .unwrap(),
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_stages, bench_round_trip);
+criterion_main!(benches);
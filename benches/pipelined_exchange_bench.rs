@@ -0,0 +1,85 @@
+use std::hint::black_box;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use realflight_bridge::{
+    Configuration, ControlInputs, RealFlightBridge, RealFlightLocalBridge, TcpSoapClient,
+};
+
+static SIM_STATE_RESPONSE: &str = include_str!("../testdata/responses/return-data-200.xml");
+
+///
+/// Local simulator answering every request on a connection until the client hangs up
+///
+fn start_simulator() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || serve(stream));
+        }
+    });
+    address
+}
+
+fn serve(mut stream: TcpStream) {
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/xml; charset=utf-8\r\nContent-Length: {}\r\nConnection: keep-alive\r\n\r\n{}",
+        SIM_STATE_RESPONSE.len(),
+        SIM_STATE_RESPONSE
+    );
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    loop {
+        let mut content_length = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            if line == "\r\n" {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().ok();
+                }
+            }
+        }
+        let mut body = vec![0; content_length.unwrap_or(0)];
+        if reader.read_exact(&mut body).is_err() {
+            return;
+        }
+        if stream.write_all(response.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+fn bench_pipelined_exchange(c: &mut Criterion) {
+    let configuration = Configuration {
+        simulator_host: start_simulator(),
+        ..Default::default()
+    };
+    let bridge = RealFlightLocalBridge::with_configuration(&configuration).unwrap();
+    let client = TcpSoapClient::connect(&configuration).unwrap();
+    let controls = [ControlInputs::default(), ControlInputs::default()];
+
+    let mut group = c.benchmark_group("exchange_two_frames");
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            for control in &controls {
+                black_box(bridge.exchange_data(black_box(control)).unwrap());
+            }
+        })
+    });
+    group.bench_function("pipelined", |b| {
+        b.iter(|| black_box(client.pipelined_exchange(black_box(&controls)).unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_pipelined_exchange);
+criterion_main!(benches);
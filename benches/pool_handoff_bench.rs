@@ -0,0 +1,91 @@
+use std::hint::black_box;
+use std::sync::Arc;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use tokio::runtime::Runtime;
+use tokio::sync::{Mutex, mpsc};
+
+use realflight_bridge::handoff_channel;
+
+const TASKS: usize = 4;
+const CAPACITY: usize = 4;
+const VALUES: usize = 10_000;
+
+///
+/// Previous pool handoff: requests queue on a mutex around an mpsc receiver
+///
+async fn mutex_receiver() {
+    let (tx, rx) = mpsc::channel(CAPACITY);
+    let rx = Arc::new(Mutex::new(rx));
+
+    let tasks: Vec<_> = (0..TASKS)
+        .map(|_| {
+            let rx = Arc::clone(&rx);
+            tokio::spawn(async move {
+                let mut taken = 0;
+                while let Some(value) = rx.lock().await.recv().await {
+                    black_box(value);
+                    taken += 1;
+                }
+                taken
+            })
+        })
+        .collect();
+    for value in 0..VALUES {
+        tx.send(value).await.unwrap();
+    }
+    drop(tx);
+
+    let mut taken = 0;
+    for task in tasks {
+        taken += task.await.unwrap();
+    }
+    assert_eq!(taken, VALUES);
+}
+
+///
+/// Current pool handoff: requests wait on the receiver directly
+///
+async fn handoff_receiver() {
+    let (tx, rx) = handoff_channel(CAPACITY);
+    let rx = Arc::new(rx);
+
+    let tasks: Vec<_> = (0..TASKS)
+        .map(|_| {
+            let rx = Arc::clone(&rx);
+            tokio::spawn(async move {
+                let mut taken = 0;
+                while let Some(value) = rx.recv().await {
+                    black_box(value);
+                    taken += 1;
+                }
+                taken
+            })
+        })
+        .collect();
+    for value in 0..VALUES {
+        tx.send(value).await.unwrap();
+    }
+    drop(tx);
+
+    let mut taken = 0;
+    for task in tasks {
+        taken += task.await.unwrap();
+    }
+    assert_eq!(taken, VALUES);
+}
+
+fn bench_pool_handoff(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("pool_handoff_4_tasks");
+    group.bench_function("mutex_receiver", |b| {
+        b.iter(|| runtime.block_on(mutex_receiver()))
+    });
+    group.bench_function("handoff_receiver", |b| {
+        b.iter(|| runtime.block_on(handoff_receiver()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_pool_handoff);
+criterion_main!(benches);
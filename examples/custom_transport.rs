@@ -0,0 +1,156 @@
+//! Runs a bridge over a transport supplied by the application.
+//!
+//! `LoopbackTransport` stands in for a link the bridge cannot open itself,
+//! such as SOAP tunnelled over a serial line: instead of writing each request
+//! to the wire it answers from recorded simulator responses. Swap its
+//! `exchange` for real I/O to carry RealFlight Link anywhere.
+
+use std::error::Error;
+use std::sync::Mutex;
+
+use realflight_bridge::{
+    BridgeError, ControlInputs, RealFlightBridge, RealFlightLocalBridge, SoapClient, SoapResponse,
+    Wait,
+};
+
+const RETURN_DATA: &str = include_str!("../testdata/responses/return-data-200.xml");
+const RESET_AIRCRAFT: &str = include_str!("../testdata/responses/reset-aircraft-200.xml");
+const INJECT_CONTROLLER: &str =
+    include_str!("../testdata/responses/inject-uav-controller-interface-200.xml");
+const RESTORE_CONTROLLER: &str =
+    include_str!("../testdata/responses/restore-original-controller-device-200.xml");
+
+/// Answers each SOAP action with a recorded RealFlight response.
+#[derive(Default)]
+struct LoopbackTransport {
+    /// Actions sent so far, in order
+    sent: Mutex<Vec<String>>,
+}
+
+impl LoopbackTransport {
+    /// One request/response exchange over the link.
+    fn exchange(&self, action: &str) -> Result<SoapResponse, BridgeError> {
+        self.sent.lock().unwrap().push(action.to_string());
+        let body = match action {
+            "ExchangeData" => RETURN_DATA,
+            "ResetAircraft" => RESET_AIRCRAFT,
+            "InjectUAVControllerInterface" => INJECT_CONTROLLER,
+            "RestoreOriginalControllerDevice" => RESTORE_CONTROLLER,
+            _ => return Err(BridgeError::Protocol(format!("no response for {}", action))),
+        };
+        Ok(SoapResponse {
+            status_code: 200,
+            body: body.to_string(),
+        })
+    }
+}
+
+impl SoapClient for LoopbackTransport {
+    fn send_action_with(
+        &self,
+        action: &str,
+        _body: &str,
+        _wait: Wait,
+    ) -> Result<SoapResponse, BridgeError> {
+        self.exchange(action)
+    }
+}
+
+#[cfg(feature = "rt-tokio")]
+impl realflight_bridge::AsyncSoapClient for LoopbackTransport {
+    async fn send_action(&self, action: &str, _body: &str) -> Result<SoapResponse, BridgeError> {
+        self.exchange(action)
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let bridge = RealFlightLocalBridge::with_soap_client(Box::new(LoopbackTransport::default()));
+
+    bridge.disable_rc()?;
+    let state = bridge.exchange_data(&ControlInputs::default())?;
+    println!("Airspeed: {:?}", state.airspeed);
+    bridge.enable_rc()?;
+
+    println!("Requests: {}", bridge.statistics().request_count);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Shares one transport between the bridge and the test.
+    struct Shared(Arc<LoopbackTransport>);
+
+    impl SoapClient for Shared {
+        fn send_action_with(
+            &self,
+            action: &str,
+            body: &str,
+            wait: Wait,
+        ) -> Result<SoapResponse, BridgeError> {
+            self.0.send_action_with(action, body, wait)
+        }
+    }
+
+    #[test]
+    fn bridge_runs_over_loopback() {
+        let transport = Arc::new(LoopbackTransport::default());
+        let bridge = RealFlightLocalBridge::with_soap_client(Box::new(Shared(transport.clone())));
+
+        bridge.reset_aircraft().unwrap();
+        let state = bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+        assert_eq!(state.current_physics_speed_multiplier, 1.0);
+        assert_eq!(
+            *transport.sent.lock().unwrap(),
+            vec!["ResetAircraft", "ExchangeData"]
+        );
+    }
+
+    /// A link that is down.
+    struct Unplugged;
+
+    impl SoapClient for Unplugged {
+        fn send_action_with(&self, _: &str, _: &str, _: Wait) -> Result<SoapResponse, BridgeError> {
+            Err(std::io::Error::from(std::io::ErrorKind::NotConnected).into())
+        }
+    }
+
+    #[test]
+    fn bridge_counts_statistics_for_transport() {
+        let bridge =
+            RealFlightLocalBridge::with_soap_client(Box::new(LoopbackTransport::default()));
+        bridge.exchange_data(&ControlInputs::default()).unwrap();
+        assert!(bridge.ping().is_ok());
+
+        let statistics = bridge.statistics();
+        assert_eq!(statistics.request_count, 2);
+        assert_eq!(statistics.error_count, 0);
+
+        let bridge = RealFlightLocalBridge::with_soap_client(Box::new(Unplugged));
+        assert!(bridge.exchange_data(&ControlInputs::default()).is_err());
+
+        let statistics = bridge.statistics();
+        assert_eq!(statistics.request_count, 1);
+        assert_eq!(statistics.error_count, 1);
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    #[tokio::test]
+    async fn async_bridge_runs_over_loopback() {
+        use realflight_bridge::{AsyncBridge, AsyncLocalBridge};
+
+        let bridge = AsyncLocalBridge::with_soap_client(LoopbackTransport::default());
+
+        bridge.disable_rc().await.unwrap();
+        let state = bridge
+            .exchange_data(&ControlInputs::default())
+            .await
+            .unwrap();
+        bridge.shutdown().await.unwrap();
+
+        assert_eq!(state.current_physics_speed_multiplier, 1.0);
+    }
+}
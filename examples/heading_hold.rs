@@ -0,0 +1,65 @@
+//! Taxis the mock simulator's aircraft around the compass with a
+//! `HeadingHold`, crossing 180 on the way, and prints its heading once a
+//! second of physics time.
+//!
+//! ```text
+//! cargo run --example heading_hold --features mock
+//! ```
+
+use std::time::Duration;
+
+use realflight_bridge::control::Pid;
+use realflight_bridge::control::holds::{HeadingHold, heading_error};
+use realflight_bridge::mock::{MockSimulator, TIME_STEP};
+use realflight_bridge::{Configuration, ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let simulator = MockSimulator::start(0)?;
+    let bridge = RealFlightLocalBridge::with_configuration(&Configuration {
+        simulator_host: simulator.host(),
+        ..Default::default()
+    })?;
+    bridge.disable_rc()?;
+
+    let mut hold = HeadingHold::new(
+        Pid::new(2.0, 0.0, 0.0).with_output_limits(-40.0, 40.0),
+        Pid::new(0.01, 1.0, 0.0),
+    );
+    let mut inputs = ControlInputs {
+        channels: [0.5; 12],
+    };
+    // Too slow to take off
+    inputs.channels[2] = 0.15;
+    let dt = Duration::from_secs_f32(TIME_STEP);
+    let steps_per_second = (1.0 / TIME_STEP) as usize;
+
+    for target in [90.0, 170.0, -170.0, -90.0, 0.0] {
+        println!("turning to {:.0}", target);
+        for step in 0..steps_per_second * 6 {
+            let state = bridge.exchange_data(&inputs)?;
+            inputs.merge(&hold.update(target, &state, dt));
+
+            if step % steps_per_second == 0 {
+                let azimuth = azimuth_degrees(&state);
+                println!(
+                    "  heading {:.1}, {:.1} degrees to go",
+                    azimuth,
+                    heading_error(target, azimuth)
+                );
+            }
+        }
+    }
+
+    bridge.enable_rc()?;
+    Ok(())
+}
+
+#[cfg(feature = "uom")]
+fn azimuth_degrees(state: &realflight_bridge::SimulatorState) -> f32 {
+    state.azimuth.get::<uom::si::angle::degree>()
+}
+
+#[cfg(not(feature = "uom"))]
+fn azimuth_degrees(state: &realflight_bridge::SimulatorState) -> f32 {
+    state.azimuth
+}
@@ -0,0 +1,136 @@
+/*
+ * C interface to realflight-bridge, built with the `ffi` feature:
+ *
+ *     cargo rustc --lib --release --features ffi --crate-type cdylib
+ *
+ * Keep in sync with src/ffi.rs; the crate's tests check that every function
+ * is declared here and that RfbState and RfbStatistics list the same fields
+ * in the same order.
+ */
+
+#ifndef REALFLIGHT_BRIDGE_H
+#define REALFLIGHT_BRIDGE_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Returned by a call that succeeded. */
+#define RFB_OK 0
+/* Returned by a call that failed; see rfb_last_error_message. */
+#define RFB_ERROR -1
+
+/* Opaque handle to a bridge. */
+typedef struct RfbBridge RfbBridge;
+
+/*
+ * Simulator state in SI units: angles in radians, angular rates in
+ * radians/second, battery capacity in coulombs and fuel in cubic meters.
+ */
+typedef struct RfbState {
+    float previous_channels[12];
+    float airspeed;
+    float altitude_asl;
+    float altitude_agl;
+    float groundspeed;
+    float pitch_rate;
+    float roll_rate;
+    float yaw_rate;
+    float azimuth;
+    float inclination;
+    float roll;
+    float aircraft_position_x;
+    float aircraft_position_y;
+    float velocity_world_u;
+    float velocity_world_v;
+    float velocity_world_w;
+    float velocity_body_u;
+    float velocity_body_v;
+    float velocity_body_w;
+    float acceleration_world_ax;
+    float acceleration_world_ay;
+    float acceleration_world_az;
+    float acceleration_body_ax;
+    float acceleration_body_ay;
+    float acceleration_body_az;
+    float wind_x;
+    float wind_y;
+    float wind_z;
+    float prop_rpm;
+    float heli_main_rotor_rpm;
+    float battery_voltage;
+    float battery_current_draw;
+    float battery_remaining_capacity;
+    float fuel_remaining;
+    bool is_locked;
+    bool has_lost_components;
+    bool an_engine_is_running;
+    bool is_touching_ground;
+    float current_physics_time;
+    float current_physics_speed_multiplier;
+    float orientation_quaternion_x;
+    float orientation_quaternion_y;
+    float orientation_quaternion_z;
+    float orientation_quaternion_w;
+    bool flight_axis_controller_is_active;
+    bool reset_button_has_been_pressed;
+} RfbState;
+
+/* Request statistics of a bridge, with durations in seconds. */
+typedef struct RfbStatistics {
+    double runtime;
+    uint32_t error_count;
+    float frequency;
+    uint32_t request_count;
+    uint32_t cancelled_count;
+    double pool_wait_total;
+    double io_total;
+    uint32_t in_flight;
+    uint32_t pool_target;
+    float pool_miss_rate;
+    uint32_t pool_starvation_count;
+    uint32_t transcript_dropped_count;
+    uint32_t channel_limit_count;
+} RfbStatistics;
+
+/* Connects to RealFlight at 127.0.0.1:18083. Returns NULL on failure. */
+RfbBridge *rfb_bridge_new(void);
+
+/* Connects to RealFlight at host ("address:port"). Returns NULL on failure. */
+RfbBridge *rfb_bridge_with_host(const char *host);
+
+/* Connects to a RealFlight proxy at address ("address:port"). Returns NULL on failure. */
+RfbBridge *rfb_remote_bridge_new(const char *address);
+
+/* Sends 12 channel values (0.0 to 1.0) and writes the returned state to out. */
+int rfb_exchange_data(const RfbBridge *bridge, const float channels[12], RfbState *out);
+
+/* Hands control to the bridge instead of the RC transmitter. */
+int rfb_disable_rc(const RfbBridge *bridge);
+
+/* Returns control to the RC transmitter. */
+int rfb_enable_rc(const RfbBridge *bridge);
+
+/* Resets the aircraft. */
+int rfb_reset(const RfbBridge *bridge);
+
+/* Writes the bridge's request statistics to out. */
+int rfb_statistics(const RfbBridge *bridge, RfbStatistics *out);
+
+/*
+ * Describes the last failure on the calling thread, or returns NULL. Valid
+ * until the next failure on the same thread.
+ */
+const char *rfb_last_error_message(void);
+
+/* Closes the bridge and releases the handle. NULL is ignored. */
+void rfb_bridge_free(RfbBridge *bridge);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* REALFLIGHT_BRIDGE_H */
@@ -0,0 +1,264 @@
+"""Python bindings for realflight-bridge, for scripted flight tests.
+
+Wraps the C interface of the crate's shared library, built with the
+``ffi`` feature::
+
+    cargo rustc --lib --release --features ffi --crate-type cdylib
+
+The library is looked up in ``REALFLIGHT_BRIDGE_LIB`` if set, then in the
+crate's ``target/release`` and ``target/debug`` directories. Calls into the
+library release the GIL, so other Python threads keep running while a request
+waits on the simulator.
+
+Example::
+
+    from realflight_bridge import RealFlightBridge
+
+    bridge = RealFlightBridge("127.0.0.1:18083")
+    bridge.disable_rc()
+    for _ in range(500):
+        state = bridge.exchange_data([0.5, 0.5, 0.8, 0.5] + [0.0] * 8)
+        print(state["altitude_agl"], state["airspeed"])
+    bridge.enable_rc()
+    print(bridge.statistics()["frequency"])
+
+``exchange_data`` returns a dict of the simulator state in SI units: meters,
+meters/second, radians, radians/second, coulombs and cubic meters.
+Failures raise ``BridgeError``.
+"""
+
+import ctypes
+import os
+import sys
+from pathlib import Path
+
+__all__ = ["BridgeError", "RealFlightBridge", "RealFlightRemoteBridge"]
+
+RFB_OK = 0
+
+
+class BridgeError(RuntimeError):
+    """A bridge call failed; the message is the library's description."""
+
+
+# Fields of RfbState after previous_channels, in declaration order
+_STATE_FIELDS = (
+    "airspeed",
+    "altitude_asl",
+    "altitude_agl",
+    "groundspeed",
+    "pitch_rate",
+    "roll_rate",
+    "yaw_rate",
+    "azimuth",
+    "inclination",
+    "roll",
+    "aircraft_position_x",
+    "aircraft_position_y",
+    "velocity_world_u",
+    "velocity_world_v",
+    "velocity_world_w",
+    "velocity_body_u",
+    "velocity_body_v",
+    "velocity_body_w",
+    "acceleration_world_ax",
+    "acceleration_world_ay",
+    "acceleration_world_az",
+    "acceleration_body_ax",
+    "acceleration_body_ay",
+    "acceleration_body_az",
+    "wind_x",
+    "wind_y",
+    "wind_z",
+    "prop_rpm",
+    "heli_main_rotor_rpm",
+    "battery_voltage",
+    "battery_current_draw",
+    "battery_remaining_capacity",
+    "fuel_remaining",
+    "is_locked",
+    "has_lost_components",
+    "an_engine_is_running",
+    "is_touching_ground",
+    "current_physics_time",
+    "current_physics_speed_multiplier",
+    "orientation_quaternion_x",
+    "orientation_quaternion_y",
+    "orientation_quaternion_z",
+    "orientation_quaternion_w",
+    "flight_axis_controller_is_active",
+    "reset_button_has_been_pressed",
+)
+
+_STATE_FLAGS = {
+    "is_locked",
+    "has_lost_components",
+    "an_engine_is_running",
+    "is_touching_ground",
+    "flight_axis_controller_is_active",
+    "reset_button_has_been_pressed",
+}
+
+
+class _State(ctypes.Structure):
+    _fields_ = [("previous_channels", ctypes.c_float * 12)] + [
+        (name, ctypes.c_bool if name in _STATE_FLAGS else ctypes.c_float)
+        for name in _STATE_FIELDS
+    ]
+
+
+class _Statistics(ctypes.Structure):
+    _fields_ = [
+        ("runtime", ctypes.c_double),
+        ("error_count", ctypes.c_uint32),
+        ("frequency", ctypes.c_float),
+        ("request_count", ctypes.c_uint32),
+        ("cancelled_count", ctypes.c_uint32),
+        ("pool_wait_total", ctypes.c_double),
+        ("io_total", ctypes.c_double),
+        ("in_flight", ctypes.c_uint32),
+        ("pool_target", ctypes.c_uint32),
+        ("pool_miss_rate", ctypes.c_float),
+        ("pool_starvation_count", ctypes.c_uint32),
+        ("transcript_dropped_count", ctypes.c_uint32),
+        ("channel_limit_count", ctypes.c_uint32),
+    ]
+
+
+def _library_path():
+    if "REALFLIGHT_BRIDGE_LIB" in os.environ:
+        return os.environ["REALFLIGHT_BRIDGE_LIB"]
+    if sys.platform == "win32":
+        name = "realflight_bridge.dll"
+    elif sys.platform == "darwin":
+        name = "librealflight_bridge.dylib"
+    else:
+        name = "librealflight_bridge.so"
+    target = Path(__file__).resolve().parent.parent / "target"
+    for profile in ("release", "debug"):
+        if (target / profile / name).exists():
+            return str(target / profile / name)
+    raise BridgeError(
+        f"{name} not found; build it with "
+        "`cargo rustc --lib --features ffi --crate-type cdylib` "
+        "or set REALFLIGHT_BRIDGE_LIB"
+    )
+
+
+def _load():
+    lib = ctypes.CDLL(_library_path())
+    handle = ctypes.c_void_p
+    lib.rfb_bridge_with_host.argtypes = [ctypes.c_char_p]
+    lib.rfb_bridge_with_host.restype = handle
+    lib.rfb_remote_bridge_new.argtypes = [ctypes.c_char_p]
+    lib.rfb_remote_bridge_new.restype = handle
+    lib.rfb_exchange_data.argtypes = [
+        handle,
+        ctypes.POINTER(ctypes.c_float),
+        ctypes.POINTER(_State),
+    ]
+    lib.rfb_exchange_data.restype = ctypes.c_int
+    for name in ("rfb_disable_rc", "rfb_enable_rc", "rfb_reset"):
+        getattr(lib, name).argtypes = [handle]
+        getattr(lib, name).restype = ctypes.c_int
+    lib.rfb_statistics.argtypes = [handle, ctypes.POINTER(_Statistics)]
+    lib.rfb_statistics.restype = ctypes.c_int
+    lib.rfb_last_error_message.argtypes = []
+    lib.rfb_last_error_message.restype = ctypes.c_char_p
+    lib.rfb_bridge_free.argtypes = [handle]
+    lib.rfb_bridge_free.restype = None
+    return lib
+
+
+_lib = None
+
+
+def _library():
+    global _lib
+    if _lib is None:
+        _lib = _load()
+    return _lib
+
+
+def _error():
+    message = _library().rfb_last_error_message()
+    return BridgeError(message.decode() if message else "unknown error")
+
+
+class _Bridge:
+    _handle = None
+
+    def __init__(self, handle):
+        if not handle:
+            raise _error()
+        self._handle = handle
+
+    def exchange_data(self, channels):
+        """Sends 12 channel values, each from 0.0 to 1.0, and returns the
+        simulator state as a dict."""
+        channels = list(channels)
+        if len(channels) != 12:
+            raise ValueError(f"expected 12 channels, got {len(channels)}")
+        values = (ctypes.c_float * 12)(*channels)
+        state = _State()
+        self._check(_library().rfb_exchange_data(self._live(), values, ctypes.byref(state)))
+        result = {name: getattr(state, name) for name, _ in _State._fields_}
+        result["previous_channels"] = list(state.previous_channels)
+        return result
+
+    def disable_rc(self):
+        """Hands control to the bridge instead of the RC transmitter."""
+        self._check(_library().rfb_disable_rc(self._live()))
+
+    def enable_rc(self):
+        """Returns control to the RC transmitter."""
+        self._check(_library().rfb_enable_rc(self._live()))
+
+    def reset_aircraft(self):
+        """Resets the aircraft."""
+        self._check(_library().rfb_reset(self._live()))
+
+    def statistics(self):
+        """Returns request statistics as a dict, with durations in seconds."""
+        statistics = _Statistics()
+        self._check(_library().rfb_statistics(self._live(), ctypes.byref(statistics)))
+        return {name: getattr(statistics, name) for name, _ in _Statistics._fields_}
+
+    def close(self):
+        """Closes the bridge. Further calls raise ``BridgeError``."""
+        if self._handle:
+            _library().rfb_bridge_free(self._handle)
+            self._handle = None
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        self.close()
+
+    def __del__(self):
+        self.close()
+
+    def _live(self):
+        if not self._handle:
+            raise BridgeError("bridge is closed")
+        return self._handle
+
+    @staticmethod
+    def _check(rc):
+        if rc != RFB_OK:
+            raise _error()
+
+
+class RealFlightBridge(_Bridge):
+    """Bridge to RealFlight on this machine or the local network."""
+
+    def __init__(self, host="127.0.0.1:18083"):
+        super().__init__(_library().rfb_bridge_with_host(host.encode()))
+
+
+class RealFlightRemoteBridge(_Bridge):
+    """Bridge to RealFlight through a ``realflight_bridge_proxy``."""
+
+    def __init__(self, address):
+        super().__init__(_library().rfb_remote_bridge_new(address.encode()))
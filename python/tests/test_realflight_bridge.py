@@ -0,0 +1,159 @@
+"""Tests for the Python bindings against a stub RealFlight server.
+
+Build the library first, then run with pytest or unittest from ``python/``::
+
+    cargo rustc --lib --features ffi --crate-type cdylib
+    python -m pytest tests
+"""
+
+import math
+import socketserver
+import sys
+import threading
+import unittest
+from pathlib import Path
+
+sys.path.insert(0, str(Path(__file__).resolve().parent.parent))
+
+from realflight_bridge import BridgeError, RealFlightBridge, RealFlightRemoteBridge  # noqa: E402
+
+RESPONSES = Path(__file__).resolve().parents[2] / "testdata" / "responses"
+
+
+class StubServer(socketserver.ThreadingTCPServer):
+    """Serves canned responses from testdata/responses, one per connection,
+    as RealFlight does."""
+
+    allow_reuse_address = True
+    daemon_threads = True
+
+    def __init__(self, responses):
+        super().__init__(("127.0.0.1", 0), StubHandler)
+        self.responses = list(responses)
+        self.requests = []
+        self.lock = threading.Lock()
+        threading.Thread(target=self.serve_forever, daemon=True).start()
+
+    @property
+    def host(self):
+        return "127.0.0.1:%d" % self.server_address[1]
+
+    def close(self):
+        self.shutdown()
+        self.server_close()
+
+
+class StubHandler(socketserver.StreamRequestHandler):
+    def handle(self):
+        length = 0
+        while True:
+            line = self.rfile.readline()
+            if not line:
+                return
+            if line == b"\r\n":
+                break
+            if line.lower().startswith(b"content-length:"):
+                length = int(line.split(b":")[1])
+        if length == 0:
+            return
+        body = self.rfile.read(length).decode()
+        with self.server.lock:
+            self.server.requests.append(body)
+            key = self.server.responses.pop(0)
+        payload = (RESPONSES / (key + ".xml")).read_bytes()
+        code = key.rsplit("-", 1)[1]
+        self.wfile.write(
+            b"HTTP/1.1 %s OK\r\nContent-Type: text/xml; charset=utf-8\r\n"
+            b"Content-Length: %d\r\nConnection: close\r\n\r\n" % (code.encode(), len(payload))
+        )
+        self.wfile.write(payload)
+
+
+class ExchangeDataTest(unittest.TestCase):
+    def test_returns_state_in_si_units(self):
+        server = StubServer(["return-data-200"])
+        with RealFlightBridge(server.host) as bridge:
+            state = bridge.exchange_data([0.5] * 12)
+        server.close()
+
+        self.assertAlmostEqual(state["altitude_asl"], 1127.371, places=3)
+        self.assertAlmostEqual(state["azimuth"], math.radians(-89.6070556640625), places=5)
+        self.assertAlmostEqual(state["battery_remaining_capacity"], 3999.9907 * 3.6, places=1)
+        self.assertIs(state["an_engine_is_running"], True)
+        self.assertIs(state["is_touching_ground"], False)
+        self.assertEqual(state["previous_channels"], [0.5] * 11 + [0.0])
+
+    def test_sends_channels(self):
+        server = StubServer(["return-data-200"])
+        with RealFlightBridge(server.host) as bridge:
+            bridge.exchange_data([0.0, 0.0, 0.75] + [0.0] * 9)
+        server.close()
+
+        self.assertIn("<item>0.75</item>", server.requests[0])
+
+    def test_rejects_wrong_channel_count(self):
+        server = StubServer([])
+        with RealFlightBridge(server.host) as bridge:
+            with self.assertRaises(ValueError):
+                bridge.exchange_data([0.5] * 4)
+        server.close()
+
+    def test_raises_soap_fault(self):
+        server = StubServer(["return-data-500"])
+        with RealFlightBridge(server.host) as bridge:
+            with self.assertRaises(BridgeError):
+                bridge.exchange_data([0.5] * 12)
+        server.close()
+
+
+class ControlTest(unittest.TestCase):
+    def test_disable_enable_and_reset(self):
+        server = StubServer(
+            [
+                "inject-uav-controller-interface-200",
+                "restore-original-controller-device-200",
+                "reset-aircraft-200",
+            ]
+        )
+        with RealFlightBridge(server.host) as bridge:
+            bridge.disable_rc()
+            bridge.enable_rc()
+            bridge.reset_aircraft()
+        server.close()
+
+        self.assertIn("InjectUAVControllerInterface", server.requests[0])
+        self.assertIn("RestoreOriginalControllerDevice", server.requests[1])
+        self.assertIn("ResetAircraft", server.requests[2])
+
+    def test_statistics(self):
+        server = StubServer(["return-data-200"])
+        with RealFlightBridge(server.host) as bridge:
+            bridge.exchange_data([0.5] * 12)
+            statistics = bridge.statistics()
+        server.close()
+
+        self.assertEqual(statistics["request_count"], 1)
+        self.assertEqual(statistics["error_count"], 0)
+
+    def test_closed_bridge_raises(self):
+        server = StubServer([])
+        bridge = RealFlightBridge(server.host)
+        bridge.close()
+        server.close()
+
+        with self.assertRaises(BridgeError):
+            bridge.reset_aircraft()
+
+
+class ConnectTest(unittest.TestCase):
+    def test_unreachable_simulator_raises(self):
+        with self.assertRaises(BridgeError):
+            RealFlightBridge("not-a-host")
+
+    def test_unreachable_proxy_raises(self):
+        with self.assertRaises(BridgeError):
+            RealFlightRemoteBridge("not-a-host")
+
+
+if __name__ == "__main__":
+    unittest.main()
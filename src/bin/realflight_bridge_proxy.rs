@@ -1,7 +1,8 @@
 use std::error::Error;
+use std::time::Duration;
 
-use clap::Parser;
-use realflight_bridge::AsyncProxyServer;
+use clap::{Parser, ValueEnum};
+use realflight_bridge::{AsyncProxyServer, BridgeError, LinkFailsafe, RateLimit, SimModel};
 use tokio_util::sync::CancellationToken;
 
 /// RealFlight Bridge Proxy server.
@@ -14,6 +15,58 @@ struct Args {
     /// Address to bind the server to
     #[arg(long, default_value = "0.0.0.0:8080")]
     bind_address: String,
+
+    /// Listen on a Unix domain socket at this path instead of TCP
+    #[cfg(unix)]
+    #[arg(long, conflicts_with_all = ["bind_address", "simulate"])]
+    uds: Option<std::path::PathBuf>,
+
+    /// Fly a simulated aircraft instead of forwarding to RealFlight
+    #[arg(long, value_enum)]
+    simulate: Option<Model>,
+
+    /// Require clients to present this token before they can send commands
+    #[arg(long)]
+    auth_token: Option<String>,
+
+    /// Limit each client to this many simulator requests per second
+    #[arg(long)]
+    max_requests_per_second: Option<u32>,
+
+    /// Requests a client may send back to back under the rate limit
+    #[arg(long, requires = "max_requests_per_second")]
+    burst: Option<u32>,
+
+    /// Command a failsafe when a client flying the aircraft is silent for
+    /// this many milliseconds
+    #[arg(long)]
+    link_timeout_ms: Option<u64>,
+}
+
+/// Aircraft for `--simulate`.
+#[derive(Clone, Copy, ValueEnum)]
+enum Model {
+    FixedWing,
+    Hover,
+}
+
+impl From<Model> for SimModel {
+    fn from(model: Model) -> Self {
+        match model {
+            Model::FixedWing => SimModel::FixedWing,
+            Model::Hover => SimModel::Hover,
+        }
+    }
+}
+
+/// Binds the server to the Unix domain socket if one was given, or else to
+/// the TCP address.
+async fn bind(args: &Args) -> Result<AsyncProxyServer, BridgeError> {
+    #[cfg(unix)]
+    if let Some(path) = &args.uds {
+        return AsyncProxyServer::bind_uds(path).await;
+    }
+    AsyncProxyServer::new(&args.bind_address).await
 }
 
 #[tokio::main]
@@ -22,7 +75,28 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let args = Args::parse();
 
-    let server = AsyncProxyServer::new(&args.bind_address).await?;
+    let server = match args.simulate {
+        Some(model) => AsyncProxyServer::new_simulated(&args.bind_address, model.into()).await?,
+        None => bind(&args).await?,
+    };
+    let server = match args.auth_token {
+        Some(token) => server.auth_token(token),
+        None => server,
+    };
+    let server = match args.max_requests_per_second {
+        Some(max_requests_per_second) => server.rate_limit(RateLimit {
+            max_requests_per_second,
+            burst: args.burst.unwrap_or(RateLimit::default().burst),
+        }),
+        None => server,
+    };
+    let server = match args.link_timeout_ms {
+        Some(ms) => server.link_failsafe(LinkFailsafe {
+            link_timeout: Duration::from_millis(ms),
+            ..Default::default()
+        }),
+        None => server,
+    };
     let cancel = CancellationToken::new();
 
     // Set up Ctrl+C handler for graceful shutdown
@@ -1,11 +1,24 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::constants::{RESET_SETTLE_ALTITUDE, RESET_SETTLE_SAMPLES, RESET_SETTLE_VELOCITY};
+use crate::encoders::units;
 use crate::{BridgeError, ControlInputs, SimulatorState};
 
 #[cfg(feature = "rt-tokio")]
 use std::future::Future;
+#[cfg(feature = "rt-tokio")]
+use std::pin::Pin;
 
+pub mod armed;
+pub mod failsafe;
 pub mod local;
+pub mod managed;
+pub mod mavlink;
+pub mod multi;
 pub mod proxy;
 pub mod remote;
+pub mod scheduler;
 
 pub trait RealFlightBridge {
     /// Exchanges flight control data with the RealFlight simulator.
@@ -61,16 +74,105 @@ pub trait RealFlightBridge {
     /// `Ok(())` upon a successful reset. Returns an error if RealFlight rejects the command
     /// or if a network issue prevents delivery.
     fn reset_aircraft(&self) -> Result<(), BridgeError>;
+
+    /// Resets the aircraft and waits for the states that follow to settle.
+    ///
+    /// States returned in the first few hundred milliseconds after a reset are
+    /// unreliable. This exchanges neutral inputs, throttle at 0 and every
+    /// other channel centered, until
+    /// [RESET_SETTLE_SAMPLES]
+    /// successive states agree on `altitude_agl` and the world velocities,
+    /// and returns the last of them.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [BridgeError::Timeout] if the states have not settled
+    /// within `settle`, and with the error of the reset or of an exchange if
+    /// one fails.
+    fn reset_aircraft_and_wait(&self, settle: Duration) -> Result<SimulatorState, BridgeError> {
+        self.reset_aircraft()?;
+
+        let deadline = Instant::now() + settle;
+        let neutral = ControlInputs::neutral();
+        let mut settling = Settling::default();
+        loop {
+            let state = self.exchange_data(&neutral)?;
+            if settling.update(&state) {
+                return Ok(state);
+            }
+            if Instant::now() >= deadline {
+                return Err(BridgeError::Timeout(settle));
+            }
+        }
+    }
+}
+
+/// Counts successive states that agree, for `reset_aircraft_and_wait`.
+#[derive(Default)]
+struct Settling {
+    previous: Option<[f32; 4]>,
+    agreeing: usize,
+}
+
+impl Settling {
+    /// Whether `state` completes a run of settled states.
+    fn update(&mut self, state: &SimulatorState) -> bool {
+        let sample = [
+            units::length(state.altitude_agl),
+            units::velocity(state.velocity_world_u),
+            units::velocity(state.velocity_world_v),
+            units::velocity(state.velocity_world_w),
+        ];
+        let steady = self.previous.is_some_and(|previous| {
+            (sample[0] - previous[0]).abs() <= RESET_SETTLE_ALTITUDE
+                && (1..4).all(|i| (sample[i] - previous[i]).abs() <= RESET_SETTLE_VELOCITY)
+        });
+        self.previous = Some(sample);
+        self.agreeing = if steady { self.agreeing + 1 } else { 1 };
+        self.agreeing >= RESET_SETTLE_SAMPLES
+    }
 }
 
+/// Forwards every [RealFlightBridge] method to the pointee, so references and
+/// smart pointers can be passed wherever `B: RealFlightBridge` is expected.
+macro_rules! forward_bridge {
+    ($($ty:ty),*) => {$(
+        impl<B: RealFlightBridge + ?Sized> RealFlightBridge for $ty {
+            fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+                (**self).exchange_data(control)
+            }
+
+            fn enable_rc(&self) -> Result<(), BridgeError> {
+                (**self).enable_rc()
+            }
+
+            fn disable_rc(&self) -> Result<(), BridgeError> {
+                (**self).disable_rc()
+            }
+
+            fn reset_aircraft(&self) -> Result<(), BridgeError> {
+                (**self).reset_aircraft()
+            }
+
+            fn reset_aircraft_and_wait(
+                &self,
+                settle: Duration,
+            ) -> Result<SimulatorState, BridgeError> {
+                (**self).reset_aircraft_and_wait(settle)
+            }
+        }
+    )*};
+}
+
+forward_bridge!(&B, Arc<B>, Box<B>);
+
 /// Async version of the RealFlight bridge interface.
 ///
 /// # Object Safety
 ///
 /// This trait is **not object-safe** due to RPITIT (return-position impl trait).
-/// You cannot use `dyn AsyncBridge`. If dynamic dispatch is required, either:
-/// - Use an enum over concrete types
-/// - Enable a future `async-trait` feature flag (not yet implemented)
+/// You cannot use `dyn AsyncBridge`. If dynamic dispatch is required, use
+/// [DynAsyncBridge], which every `AsyncBridge` implements.
 ///
 /// For most use cases, generic `impl AsyncBridge` works well.
 #[cfg(feature = "rt-tokio")]
@@ -85,6 +187,24 @@ pub trait AsyncBridge: Send + Sync {
         control: &ControlInputs,
     ) -> impl Future<Output = Result<SimulatorState, BridgeError>> + Send;
 
+    /// Same as [AsyncBridge::exchange_data], failing with [BridgeError::Timeout]
+    /// if no state arrives within `timeout`.
+    ///
+    /// The exchange is dropped when the timeout elapses, so it follows the
+    /// bridge's rules for cancelled requests.
+    fn exchange_data_timeout(
+        &self,
+        control: &ControlInputs,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<SimulatorState, BridgeError>> + Send {
+        async move {
+            match tokio::time::timeout(timeout, self.exchange_data(control)).await {
+                Ok(result) => result,
+                Err(_) => Err(BridgeError::Timeout(timeout)),
+            }
+        }
+    }
+
     /// Reverts the RealFlight simulator to use its original Spektrum (or built-in) RC input.
     fn enable_rc(&self) -> impl Future<Output = Result<(), BridgeError>> + Send;
 
@@ -93,4 +213,168 @@ pub trait AsyncBridge: Send + Sync {
 
     /// Resets the currently loaded aircraft in the RealFlight simulator.
     fn reset_aircraft(&self) -> impl Future<Output = Result<(), BridgeError>> + Send;
+
+    /// Resets the aircraft and waits for the states that follow to settle.
+    ///
+    /// Async equivalent of [RealFlightBridge::reset_aircraft_and_wait].
+    fn reset_aircraft_and_wait(
+        &self,
+        settle: Duration,
+    ) -> impl Future<Output = Result<SimulatorState, BridgeError>> + Send {
+        async move {
+            self.reset_aircraft().await?;
+
+            let deadline = tokio::time::Instant::now() + settle;
+            let neutral = ControlInputs::neutral();
+            let mut settling = Settling::default();
+            loop {
+                let state = self.exchange_data(&neutral).await?;
+                if settling.update(&state) {
+                    return Ok(state);
+                }
+                if tokio::time::Instant::now() >= deadline {
+                    return Err(BridgeError::Timeout(settle));
+                }
+            }
+        }
+    }
+}
+
+/// Same as [forward_bridge], for [AsyncBridge].
+#[cfg(feature = "rt-tokio")]
+macro_rules! forward_async_bridge {
+    ($($ty:ty),*) => {$(
+        impl<B: AsyncBridge + ?Sized> AsyncBridge for $ty {
+            fn exchange_data(
+                &self,
+                control: &ControlInputs,
+            ) -> impl Future<Output = Result<SimulatorState, BridgeError>> + Send {
+                (**self).exchange_data(control)
+            }
+
+            fn exchange_data_timeout(
+                &self,
+                control: &ControlInputs,
+                timeout: Duration,
+            ) -> impl Future<Output = Result<SimulatorState, BridgeError>> + Send {
+                (**self).exchange_data_timeout(control, timeout)
+            }
+
+            fn enable_rc(&self) -> impl Future<Output = Result<(), BridgeError>> + Send {
+                (**self).enable_rc()
+            }
+
+            fn disable_rc(&self) -> impl Future<Output = Result<(), BridgeError>> + Send {
+                (**self).disable_rc()
+            }
+
+            fn reset_aircraft(&self) -> impl Future<Output = Result<(), BridgeError>> + Send {
+                (**self).reset_aircraft()
+            }
+
+            fn reset_aircraft_and_wait(
+                &self,
+                settle: Duration,
+            ) -> impl Future<Output = Result<SimulatorState, BridgeError>> + Send {
+                (**self).reset_aircraft_and_wait(settle)
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "rt-tokio")]
+forward_async_bridge!(&B, Arc<B>, Box<B>);
+
+/// Boxed future returned by [DynAsyncBridge] methods.
+#[cfg(feature = "rt-tokio")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Object-safe form of [AsyncBridge].
+///
+/// Every [AsyncBridge] implements this trait, so bridges of different types can be
+/// stored behind `Box<dyn DynAsyncBridge>` and chosen at runtime. The trait object
+/// implements [AsyncBridge] in turn, so it can be passed to generic code such as
+/// [AsyncProxyServer::run_with_bridge](crate::AsyncProxyServer::run_with_bridge).
+///
+/// Each call allocates one boxed future. Because both traits provide the same method
+/// names, import only one of them where the methods are called, or use fully
+/// qualified syntax.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::{
+///     AsyncLocalBridge, AsyncRemoteBridge, BridgeError, ControlInputs, DynAsyncBridge,
+/// };
+///
+/// async fn connect(remote: Option<&str>) -> Result<Box<dyn DynAsyncBridge>, BridgeError> {
+///     Ok(match remote {
+///         Some(addr) => Box::new(AsyncRemoteBridge::new(addr).await?),
+///         None => Box::new(AsyncLocalBridge::new().await?),
+///     })
+/// }
+///
+/// # async fn example() -> Result<(), BridgeError> {
+/// let bridge = connect(None).await?;
+/// let state = bridge.exchange_data(&ControlInputs::default()).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "rt-tokio")]
+pub trait DynAsyncBridge: Send + Sync {
+    /// See [AsyncBridge::exchange_data].
+    fn exchange_data<'a>(
+        &'a self,
+        control: &'a ControlInputs,
+    ) -> BoxFuture<'a, Result<SimulatorState, BridgeError>>;
+
+    /// See [AsyncBridge::enable_rc].
+    fn enable_rc(&self) -> BoxFuture<'_, Result<(), BridgeError>>;
+
+    /// See [AsyncBridge::disable_rc].
+    fn disable_rc(&self) -> BoxFuture<'_, Result<(), BridgeError>>;
+
+    /// See [AsyncBridge::reset_aircraft].
+    fn reset_aircraft(&self) -> BoxFuture<'_, Result<(), BridgeError>>;
+}
+
+#[cfg(feature = "rt-tokio")]
+impl<B: AsyncBridge> DynAsyncBridge for B {
+    fn exchange_data<'a>(
+        &'a self,
+        control: &'a ControlInputs,
+    ) -> BoxFuture<'a, Result<SimulatorState, BridgeError>> {
+        Box::pin(AsyncBridge::exchange_data(self, control))
+    }
+
+    fn enable_rc(&self) -> BoxFuture<'_, Result<(), BridgeError>> {
+        Box::pin(AsyncBridge::enable_rc(self))
+    }
+
+    fn disable_rc(&self) -> BoxFuture<'_, Result<(), BridgeError>> {
+        Box::pin(AsyncBridge::disable_rc(self))
+    }
+
+    fn reset_aircraft(&self) -> BoxFuture<'_, Result<(), BridgeError>> {
+        Box::pin(AsyncBridge::reset_aircraft(self))
+    }
+}
+
+#[cfg(feature = "rt-tokio")]
+impl AsyncBridge for dyn DynAsyncBridge + '_ {
+    async fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        DynAsyncBridge::exchange_data(self, control).await
+    }
+
+    async fn enable_rc(&self) -> Result<(), BridgeError> {
+        DynAsyncBridge::enable_rc(self).await
+    }
+
+    async fn disable_rc(&self) -> Result<(), BridgeError> {
+        DynAsyncBridge::disable_rc(self).await
+    }
+
+    async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        DynAsyncBridge::reset_aircraft(self).await
+    }
 }
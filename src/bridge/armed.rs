@@ -0,0 +1,339 @@
+//! Arming wrapper that keeps control inputs from reaching the simulator until
+//! explicitly allowed.
+
+use std::sync::RwLock;
+
+use super::RealFlightBridge;
+use crate::{BridgeError, ControlInputs, SimulatorState};
+
+/// What a disarmed [ArmedBridge] does with `exchange_data`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArmPolicy {
+    /// Fail with [BridgeError::InvalidState] without contacting the simulator
+    #[default]
+    RejectWhenDisarmed,
+    /// Send the neutral frame instead of the given inputs, so states keep
+    /// arriving while nothing is commanded
+    NeutralWhenDisarmed,
+}
+
+/// Wraps a bridge and requires an explicit [arm](Self::arm) before any
+/// control is sent.
+///
+/// A new bridge is disarmed. What each call does depends on the arming state:
+///
+/// | Operation        | Disarmed                                   | Armed   |
+/// |------------------|--------------------------------------------|---------|
+/// | `exchange_data`  | rejected, or neutral frame per [ArmPolicy] | allowed |
+/// | `disable_rc`     | `InvalidState`                             | allowed |
+/// | `enable_rc`      | allowed                                    | allowed |
+/// | `reset_aircraft` | allowed                                    | allowed |
+///
+/// [disarm](Self::disarm) always hands the aircraft back to the original RC
+/// device with `enable_rc`. Arming and disarming wait for calls in flight, so
+/// no control is sent once `disarm` has started.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::{
+///     ArmPolicy, ArmedBridge, BridgeError, ControlInputs, RealFlightBridge, RealFlightLocalBridge,
+/// };
+///
+/// fn main() -> Result<(), BridgeError> {
+///     let bridge = ArmedBridge::new(RealFlightLocalBridge::new()?, ArmPolicy::RejectWhenDisarmed);
+///
+///     // Rejected locally until armed
+///     assert!(matches!(bridge.disable_rc(), Err(BridgeError::InvalidState(_))));
+///
+///     bridge.arm();
+///     bridge.disable_rc()?;
+///     bridge.exchange_data(&ControlInputs::default())?;
+///
+///     bridge.disarm()?;
+///     Ok(())
+/// }
+/// ```
+pub struct ArmedBridge<B> {
+    bridge: B,
+    policy: ArmPolicy,
+    neutral: ControlInputs,
+    armed: RwLock<bool>,
+}
+
+impl<B: RealFlightBridge> ArmedBridge<B> {
+    /// Wraps `bridge`, disarmed.
+    pub fn new(bridge: B, policy: ArmPolicy) -> Self {
+        ArmedBridge {
+            bridge,
+            policy,
+            neutral: ControlInputs::neutral(),
+            armed: RwLock::new(false),
+        }
+    }
+
+    /// Sets the frame sent while disarmed under
+    /// [ArmPolicy::NeutralWhenDisarmed]. Defaults to throttle (channel 2) at 0
+    /// and every other channel centered.
+    pub fn with_neutral_frame(mut self, neutral: ControlInputs) -> Self {
+        self.neutral = neutral;
+        self
+    }
+
+    /// Allows control inputs through.
+    pub fn arm(&self) {
+        *self.armed.write().unwrap() = true;
+    }
+
+    /// Stops control inputs and restores the original RC device.
+    ///
+    /// The bridge is disarmed even if `enable_rc` fails; its error is
+    /// returned.
+    pub fn disarm(&self) -> Result<(), BridgeError> {
+        let mut armed = self.armed.write().unwrap();
+        *armed = false;
+        self.bridge.enable_rc()
+    }
+
+    /// Whether control inputs are allowed through.
+    pub fn is_armed(&self) -> bool {
+        *self.armed.read().unwrap()
+    }
+
+    /// Returns the policy for disarmed exchanges.
+    pub fn policy(&self) -> ArmPolicy {
+        self.policy
+    }
+
+    /// Returns the wrapped bridge.
+    pub fn get_ref(&self) -> &B {
+        &self.bridge
+    }
+
+    /// Unwraps the bridge.
+    pub fn into_inner(self) -> B {
+        self.bridge
+    }
+}
+
+impl<B: RealFlightBridge> RealFlightBridge for ArmedBridge<B> {
+    fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        let armed = self.armed.read().unwrap();
+        if *armed {
+            return self.bridge.exchange_data(control);
+        }
+
+        match self.policy {
+            ArmPolicy::RejectWhenDisarmed => Err(BridgeError::InvalidState(
+                "exchange_data requires the bridge to be armed; call arm first".into(),
+            )),
+            ArmPolicy::NeutralWhenDisarmed => self.bridge.exchange_data(&self.neutral),
+        }
+    }
+
+    fn enable_rc(&self) -> Result<(), BridgeError> {
+        self.bridge.enable_rc()
+    }
+
+    fn disable_rc(&self) -> Result<(), BridgeError> {
+        let armed = self.armed.read().unwrap();
+        if !*armed {
+            return Err(BridgeError::InvalidState(
+                "disable_rc requires the bridge to be armed; call arm first".into(),
+            ));
+        }
+        self.bridge.disable_rc()
+    }
+
+    fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        self.bridge.reset_aircraft()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::local::RealFlightLocalBridge;
+    use crate::encode_control_inputs;
+    use crate::soap_client::stub::StubSoapClient;
+
+    fn armed(responses: Vec<&str>, policy: ArmPolicy) -> ArmedBridge<RealFlightLocalBridge> {
+        let responses = responses.iter().map(|r| r.to_string()).collect();
+        ArmedBridge::new(
+            RealFlightLocalBridge::stub(StubSoapClient::new(responses)),
+            policy,
+        )
+    }
+
+    fn assert_invalid_state<T: std::fmt::Debug>(result: Result<T, BridgeError>) {
+        match result {
+            Err(BridgeError::InvalidState(_)) => {}
+            other => panic!("expected InvalidState, got {:?}", other),
+        }
+    }
+
+    fn full_throttle() -> ControlInputs {
+        ControlInputs {
+            channels: [1.0; 12],
+        }
+    }
+
+    #[test]
+    fn starts_disarmed() {
+        let bridge = armed(vec!["reset-aircraft-200"], ArmPolicy::default());
+
+        assert!(!bridge.is_armed());
+        assert_eq!(bridge.policy(), ArmPolicy::RejectWhenDisarmed);
+    }
+
+    #[test]
+    fn exchange_when_armed() {
+        let bridge = armed(vec!["return-data-200"], ArmPolicy::RejectWhenDisarmed);
+        bridge.arm();
+
+        bridge.exchange_data(&full_throttle()).unwrap();
+
+        let requests = bridge.get_ref().requests();
+        assert!(requests[0].contains(&encode_control_inputs(&full_throttle())));
+    }
+
+    #[test]
+    fn exchange_rejected_when_disarmed() {
+        let bridge = armed(vec!["return-data-200"], ArmPolicy::RejectWhenDisarmed);
+
+        assert_invalid_state(bridge.exchange_data(&full_throttle()));
+        assert!(bridge.get_ref().requests().is_empty());
+    }
+
+    #[test]
+    fn exchange_neutral_when_disarmed() {
+        let bridge = armed(vec!["return-data-200"], ArmPolicy::NeutralWhenDisarmed);
+
+        bridge.exchange_data(&full_throttle()).unwrap();
+
+        let mut neutral = ControlInputs {
+            channels: [0.5; 12],
+        };
+        neutral.channels[2] = 0.0;
+        let requests = bridge.get_ref().requests();
+        assert!(requests[0].contains(&encode_control_inputs(&neutral)));
+    }
+
+    #[test]
+    fn custom_neutral_frame() {
+        let neutral = ControlInputs {
+            channels: [0.25; 12],
+        };
+        let bridge = armed(vec!["return-data-200"], ArmPolicy::NeutralWhenDisarmed)
+            .with_neutral_frame(neutral.clone());
+
+        bridge.exchange_data(&full_throttle()).unwrap();
+
+        let requests = bridge.get_ref().requests();
+        assert!(requests[0].contains(&encode_control_inputs(&neutral)));
+    }
+
+    #[test]
+    fn disable_rc_when_armed() {
+        let bridge = armed(
+            vec!["inject-uav-controller-interface-200"],
+            ArmPolicy::RejectWhenDisarmed,
+        );
+        bridge.arm();
+
+        bridge.disable_rc().unwrap();
+        assert_eq!(bridge.get_ref().requests().len(), 1);
+    }
+
+    #[test]
+    fn disable_rc_rejected_when_disarmed() {
+        for policy in [
+            ArmPolicy::RejectWhenDisarmed,
+            ArmPolicy::NeutralWhenDisarmed,
+        ] {
+            let bridge = armed(vec!["inject-uav-controller-interface-200"], policy);
+
+            assert_invalid_state(bridge.disable_rc());
+            assert!(bridge.get_ref().requests().is_empty());
+        }
+    }
+
+    #[test]
+    fn enable_rc_in_either_state() {
+        let bridge = armed(
+            vec!["restore-original-controller-device-200"],
+            ArmPolicy::RejectWhenDisarmed,
+        );
+
+        bridge.enable_rc().unwrap();
+        bridge.arm();
+        bridge.enable_rc().unwrap();
+
+        assert_eq!(bridge.get_ref().requests().len(), 2);
+        assert!(bridge.is_armed());
+    }
+
+    #[test]
+    fn reset_in_either_state() {
+        for arm in [false, true] {
+            let bridge = armed(vec!["reset-aircraft-200"], ArmPolicy::RejectWhenDisarmed);
+            if arm {
+                bridge.arm();
+            }
+
+            bridge.reset_aircraft().unwrap();
+            assert_eq!(bridge.get_ref().requests().len(), 1);
+        }
+    }
+
+    #[test]
+    fn disarm_restores_rc() {
+        let bridge = armed(
+            vec!["restore-original-controller-device-200"],
+            ArmPolicy::RejectWhenDisarmed,
+        );
+        bridge.arm();
+
+        bridge.disarm().unwrap();
+
+        assert!(!bridge.is_armed());
+        let requests = bridge.get_ref().requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("RestoreOriginalControllerDevice"));
+        assert_invalid_state(bridge.exchange_data(&full_throttle()));
+    }
+
+    #[test]
+    fn disarm_when_disarmed_still_restores_rc() {
+        let bridge = armed(
+            vec!["restore-original-controller-device-200"],
+            ArmPolicy::RejectWhenDisarmed,
+        );
+
+        bridge.disarm().unwrap();
+        assert_eq!(bridge.get_ref().requests().len(), 1);
+    }
+
+    #[test]
+    fn disarms_even_if_enable_rc_fails() {
+        let bridge = armed(
+            vec!["restore-original-controller-device-500"],
+            ArmPolicy::RejectWhenDisarmed,
+        );
+        bridge.arm();
+
+        assert!(bridge.disarm().is_err());
+        assert!(!bridge.is_armed());
+    }
+
+    #[test]
+    fn arms_across_threads() {
+        let bridge = armed(vec!["return-data-200"], ArmPolicy::RejectWhenDisarmed);
+
+        std::thread::scope(|s| {
+            s.spawn(|| bridge.arm());
+        });
+
+        assert!(bridge.exchange_data(&full_throttle()).is_ok());
+    }
+}
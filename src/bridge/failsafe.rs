@@ -0,0 +1,989 @@
+//! Wrapper that sends a failsafe frame when its owner stops exchanging.
+
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+
+use super::RealFlightBridge;
+use crate::clock::{Clock, SystemClock};
+use crate::{BridgeError, ControlInputs, SimulatorState};
+
+/// Longest real time the watchdog thread waits between checks.
+///
+/// Bounds how late it notices a [ManualClock](crate::clock::ManualClock)
+/// being advanced.
+const MAX_POLL: Duration = Duration::from_millis(5);
+
+/// Settings for a [FailsafeBridge].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FailsafeConfig {
+    /// Time without an `exchange_data` call from the owner before the
+    /// failsafe takes over. Defaults to 250 ms.
+    pub timeout: Duration,
+    /// Rate of failsafe frames while engaged. Defaults to 10 Hz.
+    pub rate_hz: f32,
+    /// Inputs sent while engaged. Defaults to throttle (channel 2) at 0 and
+    /// every other channel centered.
+    pub frame: ControlInputs,
+    /// Channel the owner must keep toggling, or [kick](FailsafeBridge::kick)
+    /// instead, for its inputs to go through. Defaults to `None`.
+    pub dead_mans_switch: Option<DeadMansSwitch>,
+}
+
+impl Default for FailsafeConfig {
+    fn default() -> Self {
+        FailsafeConfig {
+            timeout: Duration::from_millis(250),
+            rate_hz: 10.0,
+            frame: ControlInputs::neutral(),
+            dead_mans_switch: None,
+        }
+    }
+}
+
+/// A dead-man's switch on one channel of a [FailsafeBridge].
+///
+/// The owner proves it is in control by changing the value of the channel
+/// between exchanges, or by calling [kick](FailsafeBridge::kick), at least
+/// once every period. Otherwise the switch expires: the failsafe frame
+/// replaces the owner's inputs until the next kick. Made with
+/// [new](Self::new), which checks both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeadMansSwitch {
+    channel: usize,
+    period: Duration,
+}
+
+impl DeadMansSwitch {
+    /// Watches `channel`, expiring after `period` without a kick.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not below 12 or `period` is zero.
+    pub fn new(channel: usize, period: Duration) -> Self {
+        assert!(channel < 12, "channel must be below 12, got {}", channel);
+        assert!(!period.is_zero(), "period must not be zero");
+        DeadMansSwitch { channel, period }
+    }
+
+    /// Index of the watched channel (0-11).
+    pub fn channel(&self) -> usize {
+        self.channel
+    }
+
+    /// Longest time allowed between kicks.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+/// An expiry of the [DeadMansSwitch] of a [FailsafeBridge].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeadManExpiry {
+    /// The watched channel
+    pub channel: usize,
+    /// Time since the last kick when the expiry was noticed
+    pub since_kick: Duration,
+}
+
+type ExpiryHook = Box<dyn FnMut(&DeadManExpiry) + Send>;
+
+/// Counters of a [FailsafeBridge].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FailsafeStatistics {
+    /// Number of times the failsafe took over
+    pub engagements: u64,
+    /// Number of failsafe frames sent
+    pub frames_sent: u64,
+    /// Number of failsafe frames the simulator did not accept
+    pub frame_errors: u64,
+    /// Number of times the dead-man's switch expired
+    pub dead_man_expiries: u64,
+}
+
+/// Wraps a bridge and keeps the aircraft safe when its owner stops feeding
+/// inputs.
+///
+/// Without it, a controller that panics or hangs leaves the aircraft on the
+/// last [ControlInputs] it sent, often full throttle. A watchdog thread
+/// tracks the time since the owner last called
+/// [exchange_data](RealFlightBridge::exchange_data). Once that exceeds
+/// [FailsafeConfig::timeout] the failsafe is engaged and the thread sends
+/// [FailsafeConfig::frame] at [FailsafeConfig::rate_hz].
+///
+/// The next `exchange_data` from the owner disengages the failsafe and goes
+/// out as usual. Calls share one lock with the watchdog, so no failsafe frame
+/// follows it. The watchdog is armed by the first `exchange_data` and disarmed
+/// by [enable_rc](RealFlightBridge::enable_rc), when the original RC device
+/// has the aircraft.
+///
+/// With a [DeadMansSwitch] in [FailsafeConfig::dead_mans_switch], an owner
+/// that keeps exchanging is not enough: it must also kick the switch. Once
+/// the switch expires, the watchdog sends failsafe frames, the owner's
+/// exchanges send [FailsafeConfig::frame] in place of their inputs, and hooks
+/// registered with [on_dead_man_expired](Self::on_dead_man_expired) run. The
+/// next kick hands control back.
+///
+/// Time is read from a [Clock], the [SystemClock] unless the bridge is made
+/// with [with_clock](Self::with_clock). Dropping the bridge stops the thread.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::{
+///     ControlInputs, FailsafeBridge, FailsafeConfig, RealFlightBridge, RealFlightLocalBridge,
+/// };
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let bridge = FailsafeBridge::new(RealFlightLocalBridge::new()?, FailsafeConfig::default());
+///     bridge.disable_rc()?;
+///
+///     let mut inputs = ControlInputs::default();
+///     inputs.channels[2] = 1.0;
+///     loop {
+///         // Should this loop panic or stall, the throttle is cut
+///         let state = bridge.exchange_data(&inputs)?;
+///         if state.has_lost_components {
+///             break;
+///         }
+///     }
+///     println!("Failsafe engaged {} times", bridge.statistics().engagements);
+///     Ok(())
+/// }
+/// ```
+pub struct FailsafeBridge<B, C: Clock = SystemClock> {
+    shared: Arc<Shared<B, C>>,
+    watchdog: Watchdog<B, C>,
+}
+
+impl<B: RealFlightBridge + Send + 'static> FailsafeBridge<B> {
+    /// Wraps `bridge` and starts the watchdog thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.timeout` is zero or `config.rate_hz` is not positive
+    /// and finite.
+    pub fn new(bridge: B, config: FailsafeConfig) -> Self {
+        Self::with_clock(bridge, config, SystemClock)
+    }
+}
+
+impl<B: RealFlightBridge + Send + 'static, C: Clock + 'static> FailsafeBridge<B, C> {
+    /// Wraps `bridge` with a watchdog that reads the time from `clock`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.timeout` is zero or `config.rate_hz` is not positive
+    /// and finite.
+    pub fn with_clock(bridge: B, config: FailsafeConfig, clock: C) -> Self {
+        assert!(!config.timeout.is_zero(), "timeout must not be zero");
+        assert!(
+            config.rate_hz > 0.0 && config.rate_hz.is_finite(),
+            "rate_hz must be positive and finite, got {}",
+            config.rate_hz
+        );
+
+        let shared = Arc::new(Shared {
+            bridge: Mutex::new(bridge),
+            period: Duration::from_secs_f64(1.0 / config.rate_hz as f64),
+            config,
+            clock,
+            watch: Mutex::new(Watch::default()),
+            wake: Condvar::new(),
+            expiry_hooks: Mutex::new(Vec::new()),
+        });
+        let thread = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name("realflight-failsafe".into())
+                .spawn(move || shared.run())
+                .expect("failed to spawn the failsafe thread")
+        };
+
+        FailsafeBridge {
+            watchdog: Watchdog {
+                shared: Arc::clone(&shared),
+                thread: Some(thread),
+            },
+            shared,
+        }
+    }
+
+    /// Calls `hook` once per expiry of the dead-man's switch, on the thread
+    /// that noticed it: the watchdog or the owner's `exchange_data`.
+    pub fn on_dead_man_expired(self, hook: impl FnMut(&DeadManExpiry) + Send + 'static) -> Self {
+        self.shared
+            .expiry_hooks
+            .lock()
+            .unwrap()
+            .push(Box::new(hook));
+        self
+    }
+
+    /// Kicks the dead-man's switch, handing control back to the owner if it
+    /// had expired. Does nothing without a [DeadMansSwitch].
+    pub fn kick(&self) {
+        if self.shared.config.dead_mans_switch.is_some() {
+            self.shared.watch().kick(self.shared.clock.now());
+        }
+    }
+
+    /// Whether the failsafe has taken over and the owner has not exchanged
+    /// since, or the dead-man's switch has expired and not been kicked since.
+    pub fn failsafe_engaged(&self) -> bool {
+        let watch = self.shared.watch();
+        watch.engaged || watch.dead_man_expired
+    }
+
+    /// Whether the dead-man's switch has expired and not been kicked since.
+    pub fn dead_man_expired(&self) -> bool {
+        self.shared.watch().dead_man_expired
+    }
+
+    /// Returns the counters so far.
+    pub fn statistics(&self) -> FailsafeStatistics {
+        self.shared.watch().statistics.clone()
+    }
+
+    /// Returns the settings.
+    pub fn config(&self) -> &FailsafeConfig {
+        &self.shared.config
+    }
+
+    /// Returns the clock the watchdog reads.
+    pub fn clock(&self) -> &C {
+        &self.shared.clock
+    }
+
+    /// Stops the watchdog and unwraps the bridge.
+    pub fn into_inner(self) -> B {
+        let FailsafeBridge { shared, watchdog } = self;
+        drop(watchdog);
+        match Arc::try_unwrap(shared) {
+            Ok(shared) => shared.bridge.into_inner().unwrap(),
+            Err(_) => unreachable!("the watchdog has been dropped"),
+        }
+    }
+}
+
+impl<B: RealFlightBridge + Send + 'static, C: Clock + 'static> RealFlightBridge
+    for FailsafeBridge<B, C>
+{
+    fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        let bridge = self.shared.bridge.lock().unwrap();
+        let now = self.shared.clock.now();
+        let (expiry, expired) = {
+            let mut watch = self.shared.watch();
+            watch.last_exchange = Some(now);
+            if watch.engaged {
+                watch.engaged = false;
+                info!("Control inputs resumed, failsafe disengaged");
+            }
+            match &self.shared.config.dead_mans_switch {
+                Some(switch) => {
+                    watch.observe_channel(switch, control, now);
+                    let expiry = watch.check_dead_man(switch, now);
+                    (expiry, watch.dead_man_expired)
+                }
+                None => (None, false),
+            }
+        };
+
+        let result = if expired {
+            let result = bridge.exchange_data(&self.shared.config.frame);
+            self.shared.count_frame(&result);
+            result
+        } else {
+            bridge.exchange_data(control)
+        };
+        drop(bridge);
+
+        if let Some(expiry) = expiry {
+            self.shared.report_expiry(expiry);
+        }
+        result
+    }
+
+    fn enable_rc(&self) -> Result<(), BridgeError> {
+        let bridge = self.shared.bridge.lock().unwrap();
+        {
+            let mut watch = self.shared.watch();
+            watch.last_exchange = None;
+            watch.engaged = false;
+            watch.last_kick = None;
+            watch.switch_value = None;
+            watch.dead_man_expired = false;
+        }
+        bridge.enable_rc()
+    }
+
+    fn disable_rc(&self) -> Result<(), BridgeError> {
+        self.shared.bridge.lock().unwrap().disable_rc()
+    }
+
+    fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        self.shared.bridge.lock().unwrap().reset_aircraft()
+    }
+}
+
+impl<B, C: Clock> std::fmt::Debug for FailsafeBridge<B, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let watch = self.shared.watch();
+        f.debug_struct("FailsafeBridge")
+            .field("config", &self.shared.config)
+            .field("engaged", &watch.engaged)
+            .field("statistics", &watch.statistics)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Stops and joins the watchdog thread when dropped.
+struct Watchdog<B, C> {
+    shared: Arc<Shared<B, C>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<B, C> Drop for Watchdog<B, C> {
+    fn drop(&mut self) {
+        self.shared.stop();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// State shared by a [FailsafeBridge] and its watchdog thread.
+struct Shared<B, C> {
+    /// Locked for every call, so owner and watchdog never interleave
+    bridge: Mutex<B>,
+    config: FailsafeConfig,
+    period: Duration,
+    clock: C,
+    watch: Mutex<Watch>,
+    wake: Condvar,
+    expiry_hooks: Mutex<Vec<ExpiryHook>>,
+}
+
+#[derive(Default)]
+struct Watch {
+    /// When the owner last exchanged, or `None` while disarmed
+    last_exchange: Option<Instant>,
+    engaged: bool,
+    next_frame: Option<Instant>,
+    stopped: bool,
+    statistics: FailsafeStatistics,
+    /// When the dead-man's switch was last kicked, or `None` before the
+    /// first kick or exchange
+    last_kick: Option<Instant>,
+    /// Value of the watched channel in the owner's last exchange
+    switch_value: Option<f32>,
+    dead_man_expired: bool,
+}
+
+impl Watch {
+    fn kick(&mut self, now: Instant) {
+        self.last_kick = Some(now);
+        if self.dead_man_expired {
+            self.dead_man_expired = false;
+            info!("Dead-man's switch kicked, failsafe disengaged");
+        }
+    }
+
+    /// Kicks the switch if the watched channel changed since the owner's
+    /// last exchange. The first exchange starts the period.
+    fn observe_channel(&mut self, switch: &DeadMansSwitch, control: &ControlInputs, now: Instant) {
+        let value = control.channels[switch.channel];
+        let changed = self
+            .switch_value
+            .is_some_and(|last| (value - last).abs() > f32::EPSILON);
+        self.switch_value = Some(value);
+        if changed || self.last_kick.is_none() {
+            self.kick(now);
+        }
+    }
+
+    /// Expires the switch if its period has passed since the last kick.
+    /// Returns the expiry if it is new.
+    fn check_dead_man(&mut self, switch: &DeadMansSwitch, now: Instant) -> Option<DeadManExpiry> {
+        let last_kick = self.last_kick?;
+        let since_kick = now.saturating_duration_since(last_kick);
+        if self.dead_man_expired || since_kick < switch.period {
+            return None;
+        }
+
+        self.dead_man_expired = true;
+        self.next_frame = Some(now);
+        self.statistics.dead_man_expiries += 1;
+        warn!(
+            "Dead-man's switch on channel {} not kicked for {:?}, failsafe engaged",
+            switch.channel, since_kick
+        );
+        Some(DeadManExpiry {
+            channel: switch.channel,
+            since_kick,
+        })
+    }
+}
+
+impl<B, C> Shared<B, C> {
+    fn watch(&self) -> MutexGuard<'_, Watch> {
+        self.watch.lock().unwrap()
+    }
+
+    fn stop(&self) {
+        self.watch().stopped = true;
+        self.wake.notify_all();
+    }
+
+    fn count_frame(&self, result: &Result<SimulatorState, BridgeError>) {
+        let mut watch = self.watch();
+        watch.statistics.frames_sent += 1;
+        if let Err(e) = result {
+            watch.statistics.frame_errors += 1;
+            warn!("Failsafe frame failed: {}", e);
+        }
+    }
+
+    /// Runs the expiry hooks. Called without the bridge lock held.
+    fn report_expiry(&self, expiry: DeadManExpiry) {
+        for hook in self.expiry_hooks.lock().unwrap().iter_mut() {
+            hook(&expiry);
+        }
+    }
+}
+
+impl<B: RealFlightBridge, C: Clock> Shared<B, C> {
+    fn run(&self) {
+        loop {
+            let wait = self.check().min(MAX_POLL);
+            let watch = self.watch();
+            if watch.stopped {
+                return;
+            }
+            let (watch, _) = self.wake.wait_timeout(watch, wait).unwrap();
+            if watch.stopped {
+                return;
+            }
+        }
+    }
+
+    /// Engages the failsafe and sends a frame if due. Returns the time until
+    /// the next check is due.
+    fn check(&self) -> Duration {
+        let bridge = self.bridge.lock().unwrap();
+        let now = self.clock.now();
+        let mut wait = Duration::MAX;
+        let expiry = {
+            let mut watch = self.watch();
+            let Some(last_exchange) = watch.last_exchange else {
+                return MAX_POLL;
+            };
+
+            if !watch.engaged {
+                let idle = now.saturating_duration_since(last_exchange);
+                if idle < self.config.timeout {
+                    wait = self.config.timeout - idle;
+                } else {
+                    watch.engaged = true;
+                    watch.next_frame = Some(now);
+                    watch.statistics.engagements += 1;
+                    warn!(
+                        "No control inputs for {:?}, failsafe engaged",
+                        self.config.timeout
+                    );
+                }
+            }
+
+            let mut expiry = None;
+            if let Some(switch) = &self.config.dead_mans_switch {
+                expiry = watch.check_dead_man(switch, now);
+                if let (false, Some(last_kick)) = (watch.dead_man_expired, watch.last_kick) {
+                    let since_kick = now.saturating_duration_since(last_kick);
+                    wait = wait.min(switch.period.saturating_sub(since_kick));
+                }
+            }
+
+            if !watch.engaged && !watch.dead_man_expired {
+                return wait;
+            }
+            let due = watch.next_frame.unwrap_or(now);
+            if now < due {
+                return due - now;
+            }
+            watch.next_frame = Some(now + self.period);
+            expiry
+        };
+
+        let result = bridge.exchange_data(&self.config.frame);
+        drop(bridge);
+        self.count_frame(&result);
+        if let Some(expiry) = expiry {
+            self.report_expiry(expiry);
+        }
+        self.period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    const TIMEOUT: Duration = Duration::from_millis(100);
+
+    /// Bridge recording the inputs of every exchange.
+    #[derive(Default)]
+    struct StubBridge {
+        sent: Arc<Mutex<Vec<ControlInputs>>>,
+        fails: bool,
+    }
+
+    impl RealFlightBridge for StubBridge {
+        fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+            self.sent.lock().unwrap().push(control.clone());
+            if self.fails {
+                return Err(BridgeError::Timeout(TIMEOUT));
+            }
+            Ok(SimulatorState {
+                previous_inputs: control.clone(),
+                ..Default::default()
+            })
+        }
+
+        fn enable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        fn disable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        fn reset_aircraft(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+    }
+
+    fn config() -> FailsafeConfig {
+        FailsafeConfig {
+            timeout: TIMEOUT,
+            rate_hz: 10.0,
+            ..Default::default()
+        }
+    }
+
+    fn failsafe(
+        stub: StubBridge,
+    ) -> (
+        FailsafeBridge<StubBridge, ManualClock>,
+        Arc<Mutex<Vec<ControlInputs>>>,
+    ) {
+        let sent = Arc::clone(&stub.sent);
+        (
+            FailsafeBridge::with_clock(stub, config(), ManualClock::new()),
+            sent,
+        )
+    }
+
+    fn full_throttle() -> ControlInputs {
+        ControlInputs {
+            channels: [1.0; 12],
+        }
+    }
+
+    /// Waits up to a second of real time for `condition`.
+    fn wait_until(condition: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        condition()
+    }
+
+    /// Gives the watchdog thread time for a few checks.
+    fn settle() {
+        thread::sleep(MAX_POLL * 5);
+    }
+
+    #[test]
+    fn default_frame_cuts_throttle_and_centers_surfaces() {
+        let frame = FailsafeConfig::default().frame;
+
+        assert_eq!(frame.channels[2], 0.0);
+        for (channel, value) in frame.channels.iter().enumerate() {
+            if channel != 2 {
+                assert_eq!(*value, 0.5, "channel {}", channel);
+            }
+        }
+    }
+
+    #[test]
+    fn passes_exchanges_through() {
+        let (bridge, sent) = failsafe(StubBridge::default());
+
+        let state = bridge.exchange_data(&full_throttle()).unwrap();
+
+        assert_eq!(state.previous_inputs, full_throttle());
+        assert_eq!(*sent.lock().unwrap(), vec![full_throttle()]);
+        assert!(!bridge.failsafe_engaged());
+    }
+
+    #[test]
+    fn disarmed_until_first_exchange() {
+        let (bridge, sent) = failsafe(StubBridge::default());
+
+        bridge.clock().advance(TIMEOUT * 10);
+        settle();
+
+        assert!(!bridge.failsafe_engaged());
+        assert!(sent.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn stays_off_within_timeout() {
+        let (bridge, sent) = failsafe(StubBridge::default());
+        bridge.exchange_data(&full_throttle()).unwrap();
+
+        bridge.clock().advance(TIMEOUT - Duration::from_millis(1));
+        settle();
+
+        assert!(!bridge.failsafe_engaged());
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn engages_after_timeout() {
+        let (bridge, sent) = failsafe(StubBridge::default());
+        bridge.exchange_data(&full_throttle()).unwrap();
+
+        bridge.clock().advance(TIMEOUT);
+
+        assert!(wait_until(|| bridge.statistics().frames_sent == 1));
+        assert!(bridge.failsafe_engaged());
+        assert_eq!(sent.lock().unwrap()[1], config().frame);
+        assert_eq!(bridge.statistics().engagements, 1);
+    }
+
+    #[test]
+    fn sends_frames_at_rate() {
+        let (bridge, sent) = failsafe(StubBridge::default());
+        bridge.exchange_data(&full_throttle()).unwrap();
+        bridge.clock().advance(TIMEOUT);
+        assert!(wait_until(|| bridge.statistics().frames_sent == 1));
+
+        // Not due again until a full period at 10 Hz has passed
+        bridge.clock().advance(Duration::from_millis(99));
+        settle();
+        assert_eq!(bridge.statistics().frames_sent, 1);
+
+        bridge.clock().advance(Duration::from_millis(1));
+        assert!(wait_until(|| bridge.statistics().frames_sent == 2));
+        bridge.clock().advance(Duration::from_millis(100));
+        assert!(wait_until(|| bridge.statistics().frames_sent == 3));
+
+        assert!(
+            sent.lock().unwrap()[1..]
+                .iter()
+                .all(|f| *f == config().frame)
+        );
+        assert_eq!(bridge.statistics().engagements, 1);
+    }
+
+    #[test]
+    fn owner_resumes_control() {
+        let (bridge, sent) = failsafe(StubBridge::default());
+        bridge.exchange_data(&full_throttle()).unwrap();
+        bridge.clock().advance(TIMEOUT);
+        assert!(wait_until(|| bridge.failsafe_engaged()));
+
+        bridge.exchange_data(&full_throttle()).unwrap();
+        assert!(!bridge.failsafe_engaged());
+
+        // A full period later, still within the timeout of the new exchange
+        bridge.clock().advance(Duration::from_millis(99));
+        settle();
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.last(), Some(&full_throttle()));
+        assert_eq!(bridge.statistics().frames_sent, 1);
+    }
+
+    #[test]
+    fn engages_again_after_second_stall() {
+        let (bridge, _) = failsafe(StubBridge::default());
+        bridge.exchange_data(&full_throttle()).unwrap();
+        bridge.clock().advance(TIMEOUT);
+        assert!(wait_until(|| bridge.failsafe_engaged()));
+        bridge.exchange_data(&full_throttle()).unwrap();
+
+        bridge.clock().advance(TIMEOUT);
+
+        assert!(wait_until(|| bridge.statistics().engagements == 2));
+        assert!(bridge.failsafe_engaged());
+    }
+
+    #[test]
+    fn enable_rc_disarms() {
+        let (bridge, sent) = failsafe(StubBridge::default());
+        bridge.exchange_data(&full_throttle()).unwrap();
+
+        bridge.enable_rc().unwrap();
+        bridge.clock().advance(TIMEOUT * 10);
+        settle();
+
+        assert!(!bridge.failsafe_engaged());
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn counts_failed_frames() {
+        let (bridge, _) = failsafe(StubBridge {
+            fails: true,
+            ..Default::default()
+        });
+        let _ = bridge.exchange_data(&full_throttle());
+
+        bridge.clock().advance(TIMEOUT);
+
+        assert!(wait_until(|| bridge.statistics().frame_errors == 1));
+        assert_eq!(bridge.statistics().frames_sent, 1);
+        assert!(bridge.failsafe_engaged());
+    }
+
+    #[test]
+    fn into_inner_stops_watchdog() {
+        let (bridge, sent) = failsafe(StubBridge::default());
+        bridge.exchange_data(&full_throttle()).unwrap();
+        let clock = bridge.clock().clone();
+
+        let stub = bridge.into_inner();
+        clock.advance(TIMEOUT * 10);
+        settle();
+
+        assert_eq!(sent.lock().unwrap().len(), 1);
+        assert!(stub.exchange_data(&full_throttle()).is_ok());
+    }
+
+    mod dead_mans_switch {
+        use super::*;
+
+        const PERIOD: Duration = Duration::from_millis(50);
+        const CHANNEL: usize = 7;
+
+        type Expiries = Arc<Mutex<Vec<DeadManExpiry>>>;
+
+        /// Failsafe bridge with a dead-man's switch on [CHANNEL] and a
+        /// record of its expiries.
+        fn switched() -> (
+            FailsafeBridge<StubBridge, ManualClock>,
+            Arc<Mutex<Vec<ControlInputs>>>,
+            Expiries,
+        ) {
+            let stub = StubBridge::default();
+            let sent = Arc::clone(&stub.sent);
+            let expiries = Arc::new(Mutex::new(Vec::new()));
+            let recorder = Arc::clone(&expiries);
+            let config = FailsafeConfig {
+                dead_mans_switch: Some(DeadMansSwitch::new(CHANNEL, PERIOD)),
+                ..config()
+            };
+            let bridge = FailsafeBridge::with_clock(stub, config, ManualClock::new())
+                .on_dead_man_expired(move |expiry| recorder.lock().unwrap().push(expiry.clone()));
+            (bridge, sent, expiries)
+        }
+
+        /// Full throttle with the switch channel at `switch`.
+        fn inputs(switch: f32) -> ControlInputs {
+            let mut inputs = full_throttle();
+            inputs.channels[CHANNEL] = switch;
+            inputs
+        }
+
+        #[test]
+        fn kicks_within_period_never_expire() {
+            let (bridge, sent, expiries) = switched();
+
+            for _ in 0..10 {
+                bridge.exchange_data(&inputs(1.0)).unwrap();
+                bridge.clock().advance(PERIOD - Duration::from_millis(1));
+                bridge.kick();
+            }
+            settle();
+
+            assert!(!bridge.dead_man_expired());
+            assert!(expiries.lock().unwrap().is_empty());
+            assert!(sent.lock().unwrap().iter().all(|f| *f == inputs(1.0)));
+        }
+
+        #[test]
+        fn toggling_channel_kicks() {
+            let (bridge, sent, expiries) = switched();
+
+            for i in 0..10 {
+                let switch = if i % 2 == 0 { 0.0 } else { 1.0 };
+                bridge.exchange_data(&inputs(switch)).unwrap();
+                bridge.clock().advance(PERIOD - Duration::from_millis(1));
+            }
+            settle();
+
+            assert!(!bridge.dead_man_expired());
+            assert!(expiries.lock().unwrap().is_empty());
+            assert_eq!(sent.lock().unwrap().len(), 10);
+            assert_eq!(bridge.statistics().frames_sent, 0);
+        }
+
+        #[test]
+        fn expires_while_owner_keeps_exchanging() {
+            let (bridge, sent, expiries) = switched();
+            bridge.exchange_data(&inputs(1.0)).unwrap();
+
+            // Exchanging the same switch value is not a kick
+            bridge.clock().advance(PERIOD / 2);
+            bridge.exchange_data(&inputs(1.0)).unwrap();
+            bridge.clock().advance(PERIOD / 2);
+            let state = bridge.exchange_data(&inputs(1.0)).unwrap();
+
+            assert_eq!(state.previous_inputs, config().frame);
+            assert!(bridge.dead_man_expired());
+            assert!(bridge.failsafe_engaged());
+            let expiries = expiries.lock().unwrap();
+            assert_eq!(
+                *expiries,
+                vec![DeadManExpiry {
+                    channel: CHANNEL,
+                    since_kick: PERIOD,
+                }]
+            );
+            assert_eq!(bridge.statistics().dead_man_expiries, 1);
+            assert_eq!(sent.lock().unwrap().last(), Some(&config().frame));
+        }
+
+        #[test]
+        fn watchdog_sends_frames_on_expiry() {
+            let (bridge, sent, expiries) = switched();
+            bridge.exchange_data(&inputs(1.0)).unwrap();
+
+            bridge.clock().advance(PERIOD);
+
+            assert!(wait_until(|| bridge.statistics().frames_sent == 1));
+            assert!(bridge.dead_man_expired());
+            assert_eq!(sent.lock().unwrap()[1], config().frame);
+            assert_eq!(expiries.lock().unwrap().len(), 1);
+            // Within the exchange timeout, so only the switch has expired
+            assert_eq!(bridge.statistics().engagements, 0);
+        }
+
+        #[test]
+        fn kick_recovers() {
+            let (bridge, sent, expiries) = switched();
+            bridge.exchange_data(&inputs(1.0)).unwrap();
+            bridge.clock().advance(PERIOD);
+            assert!(wait_until(|| bridge.dead_man_expired()));
+
+            bridge.kick();
+            let state = bridge.exchange_data(&inputs(1.0)).unwrap();
+
+            assert!(!bridge.dead_man_expired());
+            assert!(!bridge.failsafe_engaged());
+            assert_eq!(state.previous_inputs, inputs(1.0));
+            assert_eq!(sent.lock().unwrap().last(), Some(&inputs(1.0)));
+
+            // Expires again once the period passes without another kick
+            bridge.clock().advance(PERIOD);
+            assert!(wait_until(|| bridge.statistics().dead_man_expiries == 2));
+            assert_eq!(expiries.lock().unwrap().len(), 2);
+        }
+
+        #[test]
+        fn toggle_recovers() {
+            let (bridge, _, _) = switched();
+            bridge.exchange_data(&inputs(1.0)).unwrap();
+            bridge.clock().advance(PERIOD);
+            bridge.exchange_data(&inputs(1.0)).unwrap();
+            assert!(bridge.dead_man_expired());
+
+            let state = bridge.exchange_data(&inputs(0.0)).unwrap();
+
+            assert!(!bridge.dead_man_expired());
+            assert_eq!(state.previous_inputs, inputs(0.0));
+        }
+
+        #[test]
+        fn enable_rc_disarms_switch() {
+            let (bridge, sent, expiries) = switched();
+            bridge.exchange_data(&inputs(1.0)).unwrap();
+
+            bridge.enable_rc().unwrap();
+            bridge.clock().advance(PERIOD * 10);
+            settle();
+
+            assert!(!bridge.dead_man_expired());
+            assert!(expiries.lock().unwrap().is_empty());
+            assert_eq!(sent.lock().unwrap().len(), 1);
+        }
+
+        #[test]
+        fn kick_without_switch_is_ignored() {
+            let (bridge, _) = failsafe(StubBridge::default());
+            bridge.exchange_data(&full_throttle()).unwrap();
+
+            bridge.kick();
+            bridge.clock().advance(TIMEOUT);
+
+            assert!(wait_until(|| bridge.failsafe_engaged()));
+            assert!(!bridge.dead_man_expired());
+        }
+
+        #[test]
+        #[should_panic(expected = "channel must be below 12")]
+        fn rejects_out_of_range_channel() {
+            DeadMansSwitch::new(12, PERIOD);
+        }
+
+        #[test]
+        #[should_panic(expected = "channel must be below 12")]
+        fn rejects_channel_far_out_of_range() {
+            DeadMansSwitch::new(usize::MAX, PERIOD);
+        }
+
+        #[test]
+        fn keeps_channel_and_period() {
+            let switch = DeadMansSwitch::new(CHANNEL, PERIOD);
+
+            assert_eq!((switch.channel(), switch.period()), (CHANNEL, PERIOD));
+        }
+
+        #[test]
+        #[should_panic(expected = "period must not be zero")]
+        fn rejects_zero_period() {
+            DeadMansSwitch::new(CHANNEL, Duration::ZERO);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "rate_hz must be positive")]
+    fn rejects_zero_rate() {
+        FailsafeBridge::new(
+            StubBridge::default(),
+            FailsafeConfig {
+                rate_hz: 0.0,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "timeout must not be zero")]
+    fn rejects_zero_timeout() {
+        FailsafeBridge::new(
+            StubBridge::default(),
+            FailsafeConfig {
+                timeout: Duration::ZERO,
+                ..Default::default()
+            },
+        );
+    }
+}
@@ -1,40 +1,120 @@
 //! Async implementation of the local bridge for RealFlight simulator.
 
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use futures_util::Stream;
+use tokio::sync::{mpsc, watch};
+use tokio::time::MissedTickBehavior;
 
 use crate::bridge::AsyncBridge;
-use crate::soap_client::AsyncSoapClient;
+use crate::constants::{
+    DEFAULT_CONNECT_TIMEOUT, DEFAULT_INIT_TIMEOUT, DEFAULT_MAX_IDLE, DEFAULT_MAX_RESPONSE_SIZE,
+    DEFAULT_RESET_DEBOUNCE, DEFAULT_SIMULATOR_HOST,
+};
+use crate::encoders::encode_idle_inputs;
+#[cfg(feature = "custom-transport")]
+use crate::soap_client::custom::{Counted, DynAsyncSoapClient};
 use crate::soap_client::tcp_async::AsyncTcpSoapClient;
+use crate::soap_client::transcript::TranscriptRecorder;
+use crate::soap_client::{AsyncSoapClient, RequestTap, SoapResponse};
+use crate::timing::{AsyncRateLimiter, Tick};
 use crate::{BridgeError, ControlInputs, SimulatorState, Statistics, StatisticsEngine};
 
-use super::encode_control_inputs;
+use super::{
+    ChannelLimits, ConnectionMode, PoolSize, ResetDebounce, SafeStart, encode_control_inputs,
+    limit_inputs, safe_start_state,
+};
 
 const EMPTY_BODY: &str = "";
-const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_millis(5);
-const DEFAULT_INIT_TIMEOUT: Duration = Duration::from_secs(5);
-/// Pool pre-creates next connection to hide latency. Only one connection needed at a time.
-const DEFAULT_POOL_SIZE: usize = 1;
+
+/// How an [AsyncLocalBridge] obtains simulator connections.
+///
+/// RealFlight Link needs a new connection for every request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PoolMode {
+    /// A spawned task opens the next connection while the current request is
+    /// in flight, hiding connection setup from the request latency.
+    #[default]
+    Background,
+    /// Each request opens its own connection, adding the connection setup time
+    /// (typically well under a millisecond on localhost) to every request. No
+    /// task is spawned, which suits `current_thread` runtimes and rigs that
+    /// must control every task they run. `pool_size` is ignored.
+    Inline,
+}
 
 /// Builder for AsyncLocalBridge.
 ///
 /// Configure options synchronously, then call `build()` to connect.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct AsyncLocalBridgeBuilder {
     connect_timeout: Duration,
+    io_timeout: Option<Duration>,
     init_timeout: Duration,
     addr: SocketAddr,
-    pool_size: usize,
+    local_bind: Option<SocketAddr>,
+    pool_size: PoolSize,
+    max_idle: Duration,
+    connection_mode: ConnectionMode,
+    max_response_size: usize,
+    tap: Option<RequestTap>,
+    transcript: Option<PathBuf>,
+    cache_last_state: bool,
+    pool_mode: PoolMode,
+    verify_on_connect: bool,
+    reset_debounce: Duration,
+    safe_start: Option<SafeStart>,
+    channel_limits: Option<ChannelLimits>,
+}
+
+impl std::fmt::Debug for AsyncLocalBridgeBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncLocalBridgeBuilder")
+            .field("connect_timeout", &self.connect_timeout)
+            .field("io_timeout", &self.io_timeout)
+            .field("init_timeout", &self.init_timeout)
+            .field("addr", &self.addr)
+            .field("local_bind", &self.local_bind)
+            .field("pool_size", &self.pool_size)
+            .field("max_idle", &self.max_idle)
+            .field("connection_mode", &self.connection_mode)
+            .field("max_response_size", &self.max_response_size)
+            .field("tap", &self.tap.as_ref().map(|_| ".."))
+            .field("transcript", &self.transcript)
+            .field("cache_last_state", &self.cache_last_state)
+            .field("pool_mode", &self.pool_mode)
+            .field("verify_on_connect", &self.verify_on_connect)
+            .field("reset_debounce", &self.reset_debounce)
+            .field("safe_start", &self.safe_start)
+            .field("channel_limits", &self.channel_limits)
+            .finish()
+    }
 }
 
 impl Default for AsyncLocalBridgeBuilder {
     fn default() -> Self {
         Self {
             connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            io_timeout: None,
             init_timeout: DEFAULT_INIT_TIMEOUT,
-            addr: crate::DEFAULT_SIMULATOR_HOST.parse().unwrap(),
-            pool_size: DEFAULT_POOL_SIZE,
+            addr: DEFAULT_SIMULATOR_HOST.parse().unwrap(),
+            local_bind: None,
+            pool_size: PoolSize::default(),
+            max_idle: DEFAULT_MAX_IDLE,
+            connection_mode: ConnectionMode::default(),
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            tap: None,
+            transcript: None,
+            cache_last_state: false,
+            pool_mode: PoolMode::default(),
+            verify_on_connect: false,
+            reset_debounce: DEFAULT_RESET_DEBOUNCE,
+            safe_start: None,
+            channel_limits: None,
         }
     }
 }
@@ -52,6 +132,15 @@ impl AsyncLocalBridgeBuilder {
         self
     }
 
+    /// Sets the timeout for sending a request and reading its response.
+    ///
+    /// Requests that exceed it fail with [BridgeError::Timeout]. Unset by default.
+    #[must_use]
+    pub fn io_timeout(mut self, timeout: Duration) -> Self {
+        self.io_timeout = Some(timeout);
+        self
+    }
+
     /// Sets the initialization timeout for waiting for the connection pool.
     #[must_use]
     pub fn init_timeout(mut self, timeout: Duration) -> Self {
@@ -66,37 +155,271 @@ impl AsyncLocalBridgeBuilder {
         self
     }
 
-    /// Sets the connection pool size.
+    /// Sets the local address to bind outgoing simulator connections to.
+    ///
+    /// Use port `0` to let the OS pick the source port.
+    #[must_use]
+    pub fn local_bind(mut self, addr: SocketAddr) -> Self {
+        self.local_bind = Some(addr);
+        self
+    }
+
+    /// Sets the connection pool size, either a number of connections or a
+    /// [PoolSize].
+    ///
+    /// See [Configuration::pool_size](crate::Configuration::pool_size).
+    #[must_use]
+    pub fn pool_size(mut self, size: impl Into<PoolSize>) -> Self {
+        self.pool_size = size.into();
+        self
+    }
+
+    /// Sets the age after which a pooled connection is replaced instead of used.
+    ///
+    /// See [Configuration::max_idle](crate::Configuration::max_idle).
+    #[must_use]
+    pub fn max_idle(mut self, max_idle: Duration) -> Self {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Sets whether each request opens a new connection or one is kept open.
+    ///
+    /// See [ConnectionMode].
+    #[must_use]
+    pub fn connection_mode(mut self, mode: ConnectionMode) -> Self {
+        self.connection_mode = mode;
+        self
+    }
+
+    /// Sets the largest simulator response body accepted, in bytes.
+    ///
+    /// See [Configuration::max_response_size](crate::Configuration::max_response_size).
+    #[must_use]
+    pub fn max_response_size(mut self, max_response_size: usize) -> Self {
+        self.max_response_size = max_response_size;
+        self
+    }
+
+    /// Sets how connections are created. Defaults to [PoolMode::Background].
+    #[must_use]
+    pub fn pool_mode(mut self, mode: PoolMode) -> Self {
+        self.pool_mode = mode;
+        self
+    }
+
+    /// Checks during `build()` that the peer is RealFlight Link.
+    ///
+    /// A TCP connection alone does not prove the simulator is listening. When
+    /// enabled, `build()` sends a probe that does not affect the aircraft and
+    /// fails with [BridgeError::Initialization] unless a RealFlight-style SOAP
+    /// response arrives within the init timeout. Disabled by default.
+    #[must_use]
+    pub fn verify_on_connect(mut self, enabled: bool) -> Self {
+        self.verify_on_connect = enabled;
+        self
+    }
+
+    /// Sets a callback invoked after each completed request.
+    ///
+    /// See [Configuration::tap](crate::Configuration::tap).
+    #[must_use]
+    pub fn tap(mut self, tap: RequestTap) -> Self {
+        self.tap = Some(tap);
+        self
+    }
+
+    /// Writes a transcript of the raw simulator traffic to `dir`.
+    ///
+    /// See [Configuration::transcript](crate::Configuration::transcript).
+    #[must_use]
+    pub fn transcript(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.transcript = Some(dir.into());
+        self
+    }
+
+    /// Keeps the most recent state for [AsyncLocalBridge::last_state] and
+    /// [AsyncLocalBridge::watch_state].
+    ///
+    /// See [Configuration::cache_last_state](crate::Configuration::cache_last_state).
+    #[must_use]
+    pub fn cache_last_state(mut self, enabled: bool) -> Self {
+        self.cache_last_state = enabled;
+        self
+    }
+
+    /// Sets the shortest time between two resets sent to the simulator.
+    ///
+    /// See [Configuration::reset_debounce](crate::Configuration::reset_debounce).
+    #[must_use]
+    pub fn reset_debounce(mut self, interval: Duration) -> Self {
+        self.reset_debounce = interval;
+        self
+    }
+
+    /// Refuses to inject the RealFlight Link controller while the aircraft is
+    /// flying.
+    ///
+    /// See [Configuration::safe_start](crate::Configuration::safe_start).
+    #[must_use]
+    pub fn safe_start(mut self, safe_start: SafeStart) -> Self {
+        self.safe_start = Some(safe_start);
+        self
+    }
+
+    /// Applies hard limits to the inputs of every exchange.
+    ///
+    /// See [Configuration::channel_limits](crate::Configuration::channel_limits).
     #[must_use]
-    pub fn pool_size(mut self, size: usize) -> Self {
-        self.pool_size = size;
+    pub fn channel_limits(mut self, limits: ChannelLimits) -> Self {
+        self.channel_limits = Some(limits);
         self
     }
 
     /// Builds the AsyncLocalBridge, connecting to the simulator.
     pub async fn build(self) -> Result<AsyncLocalBridge, BridgeError> {
-        let statistics = Arc::new(StatisticsEngine::new());
-        let soap_client = AsyncTcpSoapClient::new(
-            self.addr,
-            self.connect_timeout,
-            self.pool_size,
-            statistics.clone(),
-        )
-        .await?;
-
-        soap_client
-            .ensure_pool_initialized(self.init_timeout)
-            .await?;
+        let soap_client = self.connect_soap_client(Some(self.init_timeout)).await?;
+        self.into_bridge(soap_client).await
+    }
 
-        Ok(AsyncLocalBridge {
-            statistics,
-            soap_client,
-        })
+    /// Builds the AsyncLocalBridge once the connection pool is ready, however
+    /// long that takes, instead of failing after the init timeout.
+    ///
+    /// Resolves with an error as soon as the pool gives up: when a bind
+    /// fails, or the simulator still refuses connections after
+    /// [INIT_RETRY_WINDOW](crate::constants::INIT_RETRY_WINDOW). Wrap it in
+    /// `tokio::time::timeout` to bound the wait.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{AsyncBridge, AsyncLocalBridge};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let bridge = AsyncLocalBridge::builder().build_when_ready().await?;
+    ///     bridge.reset_aircraft().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn build_when_ready(self) -> Result<AsyncLocalBridge, BridgeError> {
+        let soap_client = self.connect_soap_client(None).await?;
+        self.into_bridge(soap_client).await
+    }
+
+    /// Wraps a connected client in a bridge, probing the peer if asked to.
+    async fn into_bridge(
+        self,
+        soap_client: AsyncTcpSoapClient,
+    ) -> Result<AsyncLocalBridge, BridgeError> {
+        let statistics = soap_client.statistics().clone();
+
+        let bridge = AsyncLocalBridge {
+            inner: Arc::new(Shared {
+                statistics,
+                soap_client: Transport::Tcp(soap_client),
+                rc_disabled: AtomicBool::new(false),
+                addr: self.addr,
+                last_state: self.cache_last_state.then(|| watch::Sender::new(None)),
+                reset_debounce: ResetDebounce::new(self.reset_debounce),
+                safe_start: self.safe_start,
+                channel_limits: self.channel_limits,
+            }),
+        };
+        if self.verify_on_connect {
+            bridge.verify_peer(self.init_timeout).await?;
+        }
+        Ok(bridge)
+    }
+
+    /// Connects a raw SOAP client with these settings instead of a bridge.
+    ///
+    /// The client sends any action through the same connection pool an
+    /// [AsyncLocalBridge] would use. `verify_on_connect` and `cache_last_state`
+    /// only apply to bridges and are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{AsyncLocalBridge, AsyncSoapClient};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AsyncLocalBridge::builder().build_soap_client().await?;
+    ///     let response = client.send_action("ResetAircraft", "").await?;
+    ///     println!("{}: {}", response.status_code, response.body);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "raw-soap")]
+    pub async fn build_soap_client(self) -> Result<AsyncTcpSoapClient, BridgeError> {
+        self.connect_soap_client(Some(self.init_timeout)).await
+    }
+
+    /// Connects the client, waiting at most `init_timeout` for the pool if
+    /// given.
+    async fn connect_soap_client(
+        &self,
+        init_timeout: Option<Duration>,
+    ) -> Result<AsyncTcpSoapClient, BridgeError> {
+        let statistics = Arc::new(StatisticsEngine::new());
+        let transcript = self
+            .transcript
+            .as_deref()
+            .map(|dir| TranscriptRecorder::start(dir, Arc::clone(&statistics)))
+            .transpose()?;
+        let soap_client = match self.pool_mode {
+            PoolMode::Background => {
+                AsyncTcpSoapClient::new(
+                    self.addr,
+                    self.local_bind,
+                    self.connect_timeout,
+                    self.io_timeout,
+                    self.pool_size,
+                    statistics,
+                )
+                .await?
+            }
+            PoolMode::Inline => AsyncTcpSoapClient::inline(
+                self.addr,
+                self.local_bind,
+                self.connect_timeout,
+                self.io_timeout,
+                statistics,
+            ),
+        }
+        .with_tap(self.tap.clone())
+        .with_transcript(transcript)
+        .with_max_idle(self.max_idle)
+        .with_connection_mode(self.connection_mode)
+        .with_max_response_size(self.max_response_size);
+
+        match init_timeout {
+            Some(init_timeout) => soap_client.ensure_pool_initialized(init_timeout).await?,
+            None => soap_client.pool_initialized().await?,
+        }
+        Ok(soap_client)
     }
 }
 
 /// Async client for interacting with RealFlight simulators via RealFlight Link.
 ///
+/// # Cancellation
+///
+/// Requests are cancel-safe and can be raced in `tokio::select!`. A request
+/// dropped before it was sent leaves its connection in the pool; one dropped
+/// after it was sent closes its connection and is counted in
+/// [Statistics::cancelled_count]. The simulator may still apply a request that
+/// was cancelled after sending.
+///
+/// # Sharing
+///
+/// The bridge is cheap to clone, and clones share one connection pool, so a
+/// clone can be moved into each spawned task. Every request takes its own
+/// connection and no lock is held while it is on the wire, so up to
+/// `pool_size` exchanges run concurrently and a response is always read from
+/// the connection its request was written to.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -126,40 +449,102 @@ impl AsyncLocalBridgeBuilder {
 ///     Ok(())
 /// }
 /// ```
+#[derive(Clone)]
 pub struct AsyncLocalBridge {
+    inner: Arc<Shared>,
+}
+
+/// Client an [AsyncLocalBridge] sends its requests through.
+// Held once per bridge, so the space a boxed client leaves unused is no concern
+#[allow(clippy::large_enum_variant)]
+enum Transport {
+    Tcp(AsyncTcpSoapClient),
+    /// Supplied with [AsyncLocalBridge::with_soap_client]
+    #[cfg(feature = "custom-transport")]
+    Custom(Box<dyn DynAsyncSoapClient>),
+}
+
+impl AsyncSoapClient for Transport {
+    async fn send_action(&self, action: &str, body: &str) -> Result<SoapResponse, BridgeError> {
+        match self {
+            Transport::Tcp(client) => client.send_action(action, body).await,
+            #[cfg(feature = "custom-transport")]
+            Transport::Custom(client) => client.send_action(action, body).await,
+        }
+    }
+}
+
+impl Transport {
+    async fn shutdown(self) -> Result<(), BridgeError> {
+        match self {
+            Transport::Tcp(client) => client.shutdown().await,
+            #[cfg(feature = "custom-transport")]
+            Transport::Custom(_) => Ok(()),
+        }
+    }
+}
+
+/// State shared by all clones of an [AsyncLocalBridge].
+struct Shared {
     statistics: Arc<StatisticsEngine>,
-    soap_client: AsyncTcpSoapClient,
+    soap_client: Transport,
+    /// Whether this bridge has injected the RealFlight Link controller
+    rc_disabled: AtomicBool,
+    addr: SocketAddr,
+    /// Most recent state, present when caching is enabled
+    last_state: Option<watch::Sender<Option<SimulatorState>>>,
+    reset_debounce: ResetDebounce,
+    safe_start: Option<SafeStart>,
+    channel_limits: Option<ChannelLimits>,
 }
 
 impl AsyncBridge for AsyncLocalBridge {
     async fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
-        let body = encode_control_inputs(control);
-        let response = self.soap_client.send_action("ExchangeData", &body).await?;
-        match response.status_code {
-            200 => crate::decoders::decode_simulator_state(&response.body),
-            _ => Err(BridgeError::SoapFault(response.fault_message())),
-        }
+        let response = self.send_exchange(control).await?;
+        self.decode_exchange(response)
     }
 
     async fn enable_rc(&self) -> Result<(), BridgeError> {
-        self.soap_client
+        let result: Result<(), BridgeError> = self
+            .inner
+            .soap_client
             .send_action("RestoreOriginalControllerDevice", EMPTY_BODY)
             .await?
-            .into()
+            .into();
+        if result.is_ok() {
+            self.inner.rc_disabled.store(false, Ordering::Relaxed);
+        }
+        result
     }
 
     async fn disable_rc(&self) -> Result<(), BridgeError> {
-        self.soap_client
-            .send_action("InjectUAVControllerInterface", EMPTY_BODY)
-            .await?
-            .into()
+        if let Some(safe_start) = &self.inner.safe_start {
+            let response = self
+                .inner
+                .soap_client
+                .send_action("ExchangeData", &encode_idle_inputs())
+                .await?;
+            if let Some(state) = safe_start_state(response)? {
+                safe_start.check(&state)?;
+            }
+        }
+        self.force_disable_rc().await
     }
 
     async fn reset_aircraft(&self) -> Result<(), BridgeError> {
-        self.soap_client
+        if !self.inner.reset_debounce.admit() {
+            return Ok(());
+        }
+        let result: Result<(), BridgeError> = self
+            .inner
+            .soap_client
             .send_action("ResetAircraft", EMPTY_BODY)
             .await?
-            .into()
+            .into();
+        if result.is_ok() {
+            self.inner.reset_debounce.record();
+        }
+        result
     }
 }
 
@@ -174,9 +559,391 @@ impl AsyncLocalBridge {
         AsyncLocalBridgeBuilder::default()
     }
 
+    /// Creates a bridge that sends its requests over `soap_client` instead of
+    /// a pool of TCP connections to RealFlight.
+    ///
+    /// Async equivalent of
+    /// [RealFlightLocalBridge::with_soap_client](crate::RealFlightLocalBridge::with_soap_client).
+    /// No background task is spawned, and [AsyncLocalBridge::addr] is
+    /// unspecified. The bridge counts the client's requests, errors and
+    /// round-trip times in [AsyncLocalBridge::statistics].
+    #[cfg(feature = "custom-transport")]
+    pub fn with_soap_client(soap_client: impl AsyncSoapClient + 'static) -> Self {
+        let statistics = Arc::new(StatisticsEngine::new());
+        let soap_client = Counted::new(soap_client, Arc::clone(&statistics));
+
+        AsyncLocalBridge {
+            inner: Arc::new(Shared {
+                statistics,
+                soap_client: Transport::Custom(Box::new(soap_client)),
+                rc_disabled: AtomicBool::new(false),
+                addr: SocketAddr::from(([0, 0, 0, 0], 0)),
+                last_state: None,
+                reset_debounce: ResetDebounce::new(DEFAULT_RESET_DEBOUNCE),
+                safe_start: None,
+                channel_limits: None,
+            }),
+        }
+    }
+
     /// Returns a snapshot of current statistics.
     pub fn statistics(&self) -> Statistics {
-        self.statistics.snapshot()
+        self.inner.statistics.snapshot()
+    }
+
+    /// Returns a copy of the most recent state received from the simulator.
+    ///
+    /// Async equivalent of [RealFlightLocalBridge::last_state](crate::RealFlightLocalBridge::last_state).
+    /// Requires [AsyncLocalBridgeBuilder::cache_last_state].
+    pub fn last_state(&self) -> Option<SimulatorState> {
+        self.inner
+            .last_state
+            .as_ref()
+            .and_then(|last_state| last_state.borrow().clone())
+    }
+
+    /// Subscribes to state updates, or `None` unless
+    /// [AsyncLocalBridgeBuilder::cache_last_state] is enabled.
+    ///
+    /// The receiver holds `None` until the first successful exchange.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::AsyncLocalBridge;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let bridge = AsyncLocalBridge::builder()
+    ///         .cache_last_state(true)
+    ///         .build()
+    ///         .await?;
+    ///
+    ///     let mut updates = bridge.watch_state().unwrap();
+    ///     tokio::spawn(async move {
+    ///         while updates.changed().await.is_ok() {
+    ///             if let Some(state) = updates.borrow_and_update().as_ref() {
+    ///                 println!("Airspeed: {:?}", state.airspeed);
+    ///             }
+    ///         }
+    ///     });
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn watch_state(&self) -> Option<watch::Receiver<Option<SimulatorState>>> {
+        self.inner.last_state.as_ref().map(watch::Sender::subscribe)
+    }
+
+    /// Returns the simulator address this bridge was built with.
+    pub fn addr(&self) -> SocketAddr {
+        self.inner.addr
+    }
+
+    /// Checks that RealFlight Link is responding and returns the round-trip time.
+    ///
+    /// Async equivalent of [RealFlightLocalBridge::ping](crate::RealFlightLocalBridge::ping).
+    pub async fn ping(&self) -> Result<Duration, BridgeError> {
+        let start = Instant::now();
+        self.inner
+            .soap_client
+            .send_action("ExchangeData", &encode_idle_inputs())
+            .await?;
+        Ok(start.elapsed())
+    }
+
+    /// Switches to the RealFlight Link controller without the
+    /// [safe start](AsyncLocalBridgeBuilder::safe_start) check.
+    ///
+    /// Async equivalent of
+    /// [RealFlightLocalBridge::force_disable_rc](crate::RealFlightLocalBridge::force_disable_rc).
+    pub async fn force_disable_rc(&self) -> Result<(), BridgeError> {
+        let result: Result<(), BridgeError> = self
+            .inner
+            .soap_client
+            .send_action("InjectUAVControllerInterface", EMPTY_BODY)
+            .await?
+            .into();
+        if result.is_ok() {
+            self.inner.rc_disabled.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Shuts the bridge down, leaving the simulator as it was found.
+    ///
+    /// Async equivalent of [RealFlightLocalBridge::shutdown](crate::RealFlightLocalBridge::shutdown):
+    /// restores the original RC device if this bridge still has it disabled, then
+    /// stops the connection pool's background task. While other clones are alive
+    /// the pool keeps serving them, and stops when the last clone is dropped.
+    pub async fn shutdown(self) -> Result<(), BridgeError> {
+        let restored = if self.inner.rc_disabled.load(Ordering::Relaxed) {
+            self.enable_rc().await
+        } else {
+            Ok(())
+        };
+        let stopped = match Arc::try_unwrap(self.inner) {
+            Ok(inner) => inner.soap_client.shutdown().await,
+            Err(_) => Ok(()),
+        };
+        restored.and(stopped)
+    }
+
+    /// Starts a pipeline that exchanges data on spawned tasks.
+    ///
+    /// Inputs sent on the returned [mpsc::Sender] are exchanged with the simulator
+    /// one at a time, and each result is delivered on the returned
+    /// [mpsc::Receiver]. Decoding a response runs on a separate task, so it overlaps
+    /// the network round trip of the next request. Because each request uses a
+    /// fresh connection, a pool size of 2 keeps the next connection ready.
+    ///
+    /// # Ordering
+    ///
+    /// Exactly one result is produced per input, in submission order. Failed
+    /// exchanges are delivered as `Err` items and the pipeline keeps going.
+    ///
+    /// # Backpressure
+    ///
+    /// Up to `capacity` inputs are queued before `send` waits, and up to
+    /// `capacity` results are buffered before the pipeline stops issuing requests.
+    /// A consumer that stops reading therefore stalls the pipeline rather than
+    /// dropping results.
+    ///
+    /// # Shutdown
+    ///
+    /// Dropping the sender lets queued inputs finish, then closes the receiver.
+    /// Dropping the receiver stops the pipeline after the request in flight.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{AsyncBridge, AsyncLocalBridge, ControlInputs};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let bridge = AsyncLocalBridge::builder().pool_size(2).build().await?;
+    ///     bridge.disable_rc().await?;
+    ///
+    ///     let (inputs, mut states) = bridge.exchange_pipeline(4);
+    ///     inputs.send(ControlInputs::default()).await?;
+    ///     inputs.send(ControlInputs::default()).await?;
+    ///     drop(inputs);
+    ///
+    ///     while let Some(state) = states.recv().await {
+    ///         println!("Airspeed: {:?}", state?.airspeed);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn exchange_pipeline(
+        &self,
+        capacity: usize,
+    ) -> (
+        mpsc::Sender<ControlInputs>,
+        mpsc::Receiver<Result<SimulatorState, BridgeError>>,
+    ) {
+        let (input_tx, mut input_rx) = mpsc::channel::<ControlInputs>(capacity);
+        let (response_tx, mut response_rx) = mpsc::channel(1);
+        let (state_tx, state_rx) = mpsc::channel(capacity);
+
+        // Network stage: keeps one request in flight
+        let bridge = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let control = tokio::select! {
+                    control = input_rx.recv() => match control {
+                        Some(control) => control,
+                        None => break,
+                    },
+                    // Decode stage exited because the receiver was dropped
+                    _ = response_tx.closed() => break,
+                };
+                let response = bridge.send_exchange(&control).await;
+                if response_tx.send(response).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        // Decode stage: works on the previous response while the next is in flight
+        let bridge = self.clone();
+        tokio::spawn(async move {
+            while let Some(response) = response_rx.recv().await {
+                let state = response.and_then(|response| bridge.decode_exchange(response));
+                if state_tx.send(state).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        (input_tx, state_rx)
+    }
+
+    /// Returns a stream that exchanges data at a fixed rate.
+    ///
+    /// On every tick of an [AsyncRateLimiter] the stream reads the latest
+    /// value from `inputs`, exchanges it with the simulator and yields the
+    /// resulting state. The first exchange is made at once. Nothing is sent while the stream is not polled.
+    ///
+    /// When an exchange, or a slow consumer, delays the stream past one or more
+    /// ticks, those ticks are skipped and reported as a single
+    /// [BridgeError::MissedDeadline] item before the next state. Failed exchanges
+    /// are yielded as errors and the stream continues. The stream ends when the
+    /// sender of `inputs` is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate_hz` is not a positive, finite number.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use futures_util::StreamExt;
+    /// use realflight_bridge::{AsyncBridge, AsyncLocalBridge, BridgeError, ControlInputs};
+    /// use tokio::sync::watch;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let bridge = AsyncLocalBridge::new().await?;
+    ///     bridge.disable_rc().await?;
+    ///
+    ///     let (inputs, inputs_rx) = watch::channel(ControlInputs::default());
+    ///     let mut states = std::pin::pin!(bridge.state_stream(100.0, inputs_rx));
+    ///
+    ///     while let Some(item) = states.next().await {
+    ///         match item {
+    ///             Ok(state) => {
+    ///                 let mut next = ControlInputs::default();
+    ///                 next.channels[2] = 1.0; // Full throttle
+    ///                 inputs.send(next)?;
+    ///                 println!("Airspeed: {:?}", state.airspeed);
+    ///             }
+    ///             Err(BridgeError::MissedDeadline(missed)) => eprintln!("missed {} ticks", missed),
+    ///             Err(e) => return Err(e.into()),
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn state_stream(
+        &self,
+        rate_hz: f32,
+        inputs: watch::Receiver<ControlInputs>,
+    ) -> impl Stream<Item = Result<SimulatorState, BridgeError>> + '_ {
+        let limiter =
+            AsyncRateLimiter::new(rate_hz).with_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        let ticker = StreamTicker {
+            limiter,
+            started: false,
+            late_tick: false,
+        };
+        futures_util::stream::unfold((ticker, inputs), move |(mut ticker, inputs)| async move {
+            if let Err(missed) = ticker.next_tick().await {
+                return Some((Err(missed), (ticker, inputs)));
+            }
+
+            // A dropped sender ends the stream
+            inputs.has_changed().ok()?;
+            let control = inputs.borrow().clone();
+            let result = self.exchange_data(&control).await;
+            Some((result, (ticker, inputs)))
+        })
+    }
+
+    /// Sends an idle exchange and checks the reply looks like RealFlight Link.
+    ///
+    /// The simulator answers with a state once its controller is injected and
+    /// with a SOAP fault before that; anything else is some other service.
+    async fn verify_peer(&self, probe_timeout: Duration) -> Result<(), BridgeError> {
+        let not_realflight = |reason: String| {
+            BridgeError::Initialization(format!(
+                "Peer at {} does not look like RealFlight Link: {}",
+                self.inner.addr, reason
+            ))
+        };
+
+        let body = encode_idle_inputs();
+        let probe = self.inner.soap_client.send_action("ExchangeData", &body);
+        let response = match tokio::time::timeout(probe_timeout, probe).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => return Err(not_realflight(e.to_string())),
+            Err(_) => {
+                return Err(not_realflight(format!(
+                    "no response within {:?}",
+                    probe_timeout
+                )));
+            }
+        };
+
+        match response.status_code {
+            200 => crate::decoders::decode_simulator_state(&response.body)
+                .map(|_| ())
+                .map_err(|e| not_realflight(e.to_string())),
+            status => match crate::decoders::extract_element("detail", &response.body) {
+                Some(_) => Ok(()),
+                None => Err(not_realflight(format!(
+                    "HTTP {} without a SOAP fault",
+                    status
+                ))),
+            },
+        }
+    }
+
+    async fn send_exchange(&self, control: &ControlInputs) -> Result<SoapResponse, BridgeError> {
+        let control = limit_inputs(
+            self.inner.channel_limits.as_ref(),
+            control,
+            &self.inner.statistics,
+        );
+        let body = encode_control_inputs(&control);
+        self.inner
+            .soap_client
+            .send_action("ExchangeData", &body)
+            .await
+    }
+
+    fn decode_exchange(&self, response: SoapResponse) -> Result<SimulatorState, BridgeError> {
+        let state = match response.status_code {
+            200 => crate::decoders::decode_simulator_state(&response.body)?,
+            _ => return Err(BridgeError::SoapFault(response.fault_message())),
+        };
+        if let Some(last_state) = &self.inner.last_state {
+            last_state.send_replace(Some(state.clone()));
+        }
+        Ok(state)
+    }
+}
+
+/// Tick source for [AsyncLocalBridge::state_stream] that detects skipped ticks.
+struct StreamTicker {
+    limiter: AsyncRateLimiter,
+    /// Whether the first exchange, which does not wait, has been made
+    started: bool,
+    /// Tick already awaited while reporting missed ticks
+    late_tick: bool,
+}
+
+impl StreamTicker {
+    /// Waits for the next tick, or reports how many ticks were skipped before it.
+    ///
+    /// A late tick still fires with its original deadline, so its lateness
+    /// tells how many later ticks the limiter will skip.
+    async fn next_tick(&mut self) -> Result<(), BridgeError> {
+        if !std::mem::replace(&mut self.started, true) || std::mem::take(&mut self.late_tick) {
+            return Ok(());
+        }
+        if let Tick::Missed { late } = self.limiter.tick().await {
+            let missed = late.as_nanos() / self.limiter.period().as_nanos();
+            if missed > 0 {
+                self.late_tick = true;
+                return Err(BridgeError::MissedDeadline(missed as u64));
+            }
+        }
+        Ok(())
     }
 }
 
@@ -185,6 +952,7 @@ mod tests {
     use super::*;
     use crate::bridge::AsyncBridge;
     use crate::tests::soap_stub::Server;
+    #[cfg(not(feature = "uom"))]
     use approx::assert_relative_eq;
     use std::net::TcpListener;
 
@@ -228,7 +996,7 @@ mod tests {
         #[test]
         fn builder_default_pool_size() {
             let builder = AsyncLocalBridgeBuilder::new();
-            assert_eq!(builder.pool_size, 1);
+            assert_eq!(builder.pool_size, PoolSize::Fixed(1));
         }
 
         #[test]
@@ -238,6 +1006,18 @@ mod tests {
             assert_eq!(builder.connect_timeout, Duration::from_millis(100));
         }
 
+        #[test]
+        fn builder_default_io_timeout_is_unset() {
+            let builder = AsyncLocalBridgeBuilder::new();
+            assert_eq!(builder.io_timeout, None);
+        }
+
+        #[test]
+        fn builder_io_timeout_sets_value() {
+            let builder = AsyncLocalBridgeBuilder::new().io_timeout(Duration::from_millis(50));
+            assert_eq!(builder.io_timeout, Some(Duration::from_millis(50)));
+        }
+
         #[test]
         fn builder_init_timeout_sets_value() {
             let builder = AsyncLocalBridgeBuilder::new().init_timeout(Duration::from_secs(10));
@@ -251,10 +1031,59 @@ mod tests {
             assert_eq!(builder.addr, addr);
         }
 
+        #[test]
+        fn builder_local_bind_sets_value() {
+            let addr: SocketAddr = "192.168.1.10:0".parse().unwrap();
+            let builder = AsyncLocalBridgeBuilder::new().local_bind(addr);
+            assert_eq!(builder.local_bind, Some(addr));
+        }
+
         #[test]
         fn builder_pool_size_sets_value() {
             let builder = AsyncLocalBridgeBuilder::new().pool_size(5);
-            assert_eq!(builder.pool_size, 5);
+            assert_eq!(builder.pool_size, PoolSize::Fixed(5));
+        }
+
+        #[test]
+        fn builder_max_idle_sets_value() {
+            let builder = AsyncLocalBridgeBuilder::new().max_idle(Duration::from_secs(2));
+            assert_eq!(builder.max_idle, Duration::from_secs(2));
+        }
+
+        #[test]
+        fn builder_connection_mode_sets_value() {
+            let builder = AsyncLocalBridgeBuilder::new();
+            assert_eq!(builder.connection_mode, ConnectionMode::Pooled);
+
+            let builder = builder.connection_mode(ConnectionMode::Reuse);
+            assert_eq!(builder.connection_mode, ConnectionMode::Reuse);
+        }
+
+        #[test]
+        fn builder_max_response_size_sets_value() {
+            let builder = AsyncLocalBridgeBuilder::new();
+            assert_eq!(builder.max_response_size, DEFAULT_MAX_RESPONSE_SIZE);
+
+            let builder = builder.max_response_size(1024);
+            assert_eq!(builder.max_response_size, 1024);
+        }
+
+        #[test]
+        fn builder_default_pool_mode_is_background() {
+            let builder = AsyncLocalBridgeBuilder::new();
+            assert_eq!(builder.pool_mode, PoolMode::Background);
+        }
+
+        #[test]
+        fn builder_pool_mode_sets_value() {
+            let builder = AsyncLocalBridgeBuilder::new().pool_mode(PoolMode::Inline);
+            assert_eq!(builder.pool_mode, PoolMode::Inline);
+        }
+
+        #[test]
+        fn builder_default_skips_verification() {
+            let builder = AsyncLocalBridgeBuilder::new();
+            assert!(!builder.verify_on_connect);
         }
 
         #[test]
@@ -358,6 +1187,76 @@ mod tests {
                 other => panic!("expected SoapFault, got {:?}", other),
             }
         }
+
+        async fn create_safe_bridge(port: u16) -> AsyncLocalBridge {
+            AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .safe_start(SafeStart::default())
+                .build()
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn safe_start_injects_when_grounded() {
+            let port = get_available_port();
+            // Served last to first
+            let server = Server::new(
+                port,
+                vec![
+                    "inject-uav-controller-interface-200".to_string(),
+                    "return-data-grounded-200".to_string(),
+                ],
+            );
+            let bridge = create_safe_bridge(port).await;
+
+            bridge.disable_rc().await.unwrap();
+
+            let requests = server.requests();
+            assert_eq!(requests.len(), 2);
+            assert!(requests[0].contains("ExchangeData"));
+            assert!(requests[1].contains("InjectUAVControllerInterface"));
+        }
+
+        #[tokio::test]
+        async fn safe_start_refuses_when_airborne() {
+            let port = get_available_port();
+            let server = Server::new(
+                port,
+                vec![
+                    "inject-uav-controller-interface-200".to_string(),
+                    "return-data-airborne-200".to_string(),
+                ],
+            );
+            let bridge = create_safe_bridge(port).await;
+
+            match bridge.disable_rc().await {
+                Err(BridgeError::InvalidState(_)) => {}
+                other => panic!("expected InvalidState, got {:?}", other),
+            }
+            assert_eq!(server.request_count(), 1);
+
+            bridge.force_disable_rc().await.unwrap();
+            assert_eq!(server.request_count(), 2);
+        }
+
+        #[cfg(feature = "mock")]
+        #[tokio::test]
+        async fn safe_start_injects_on_fresh_simulator() {
+            let simulator = crate::mock::MockSimulator::start(0).unwrap();
+            let bridge = AsyncLocalBridge::builder()
+                .addr(simulator.address())
+                .connect_timeout(Duration::from_millis(1000))
+                .safe_start(SafeStart::default())
+                .build()
+                .await
+                .unwrap();
+
+            bridge.disable_rc().await.unwrap();
+
+            assert!(simulator.controller_injected());
+        }
     }
 
     // ========================================================================
@@ -367,6 +1266,29 @@ mod tests {
     mod exchange_data {
         use super::*;
 
+        #[tokio::test]
+        async fn sends_inputs_within_channel_limits() {
+            let port = get_available_port();
+            let server = Server::new(port, vec!["return-data-200".to_string()]);
+            let bridge = AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .channel_limits(ChannelLimits::default().with_range(2, 0.0, 0.8))
+                .build()
+                .await
+                .unwrap();
+            let control = ControlInputs {
+                channels: [1.0; 12],
+            };
+
+            bridge.exchange_data(&control).await.unwrap();
+
+            let mut expected = control.clone();
+            expected.channels[2] = 0.8;
+            assert!(server.requests()[0].contains(&encode_control_inputs(&expected)));
+            assert_eq!(bridge.statistics().channel_limit_count, 1);
+        }
+
         #[tokio::test]
         async fn returns_simulator_state_on_success() {
             let port = get_available_port();
@@ -382,54 +1304,643 @@ mod tests {
         }
 
         #[tokio::test]
-        async fn returns_soap_fault_on_500() {
+        async fn skips_100_continue_before_state() {
             let port = get_available_port();
-            let _server = Server::new(port, vec!["return-data-500".to_string()]);
+            let _server = Server::with_continue(port, vec!["return-data-200".to_string()]);
             let bridge = create_bridge(port).await.unwrap();
 
-            let control = ControlInputs::default();
-            let result = bridge.exchange_data(&control).await;
+            let result = bridge.exchange_data(&ControlInputs::default()).await;
 
-            match result {
-                Err(BridgeError::SoapFault(msg)) => {
-                    assert_eq!(msg, "RealFlight Link controller has not been instantiated");
-                }
-                other => panic!("expected SoapFault, got {:?}", other),
-            }
+            assert!(result.is_ok(), "expected Ok: {:?}", result);
+            assert_eq!(result.unwrap().current_physics_speed_multiplier, 1.0);
         }
 
+        #[cfg(feature = "compression")]
         #[tokio::test]
-        async fn parses_boolean_fields() {
+        async fn decodes_gzipped_state() {
             let port = get_available_port();
-            let _server = Server::new(port, vec!["return-data-200".to_string()]);
+            let _server = Server::gzip(port, vec!["return-data-200".to_string()]);
             let bridge = create_bridge(port).await.unwrap();
 
-            let state = bridge
-                .exchange_data(&ControlInputs::default())
-                .await
-                .unwrap();
+            let result = bridge.exchange_data(&ControlInputs::default()).await;
 
-            assert!(!state.is_locked);
-            assert!(!state.has_lost_components);
-            assert!(state.an_engine_is_running);
-            assert!(!state.is_touching_ground);
-            assert!(state.flight_axis_controller_is_active);
+            assert!(result.is_ok(), "expected Ok: {:?}", result);
+            assert_eq!(result.unwrap().current_physics_speed_multiplier, 1.0);
         }
 
-        #[cfg(not(feature = "uom"))]
         #[tokio::test]
-        async fn parses_velocity_fields() {
+        async fn tap_sees_exchange_data_body() {
             let port = get_available_port();
             let _server = Server::new(port, vec!["return-data-200".to_string()]);
-            let bridge = create_bridge(port).await.unwrap();
-
-            let state = bridge
-                .exchange_data(&ControlInputs::default())
+            let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let log = Arc::clone(&seen);
+            let bridge = AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .tap(Arc::new(move |action, body, response| {
+                    log.lock().unwrap().push((
+                        action.to_string(),
+                        body.to_string(),
+                        response.status_code,
+                    ));
+                }))
+                .build()
                 .await
                 .unwrap();
 
-            assert_relative_eq!(state.airspeed, 0.040872246);
-            assert_relative_eq!(state.groundspeed, 4.643444754E-06);
+            let control = ControlInputs::default();
+            bridge.exchange_data(&control).await.unwrap();
+
+            let seen = seen.lock().unwrap();
+            assert_eq!(seen.len(), 1);
+            assert_eq!(seen[0].0, "ExchangeData");
+            assert_eq!(seen[0].1, encode_control_inputs(&control));
+            assert_eq!(seen[0].2, 200);
+        }
+
+        #[tokio::test]
+        async fn transcript_pairs_requests_with_responses() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-200".to_string()]);
+            let dir = std::env::temp_dir().join(format!(
+                "realflight-bridge-{}-transcript-async",
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            let bridge = AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .transcript(&dir)
+                .build()
+                .await
+                .unwrap();
+
+            let control = ControlInputs::default();
+            bridge.exchange_data(&control).await.unwrap();
+            // Dropping the bridge waits for the transcript to be written
+            drop(bridge);
+
+            let transcript = crate::Transcript::open(&dir).unwrap();
+            let entries = transcript.entries();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].action(), Some("ExchangeData"));
+            assert!(entries[0].request.ends_with("</soap:Envelope>"));
+            assert_eq!(entries[0].status_code(), Some(200));
+            assert!(entries[0].response_body().contains("<ReturnData>"));
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+
+        #[tokio::test]
+        async fn caches_last_state_when_enabled() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-200".to_string()]);
+            let bridge = AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .cache_last_state(true)
+                .build()
+                .await
+                .unwrap();
+            let mut updates = bridge.watch_state().unwrap();
+            assert!(bridge.last_state().is_none());
+
+            let state = bridge
+                .exchange_data(&ControlInputs::default())
+                .await
+                .unwrap();
+
+            updates.changed().await.unwrap();
+            assert_eq!(updates.borrow_and_update().as_ref(), Some(&state));
+            assert_eq!(bridge.last_state(), Some(state));
+        }
+
+        #[tokio::test]
+        async fn no_cache_by_default() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-200".to_string()]);
+            let bridge = create_bridge(port).await.unwrap();
+
+            bridge
+                .exchange_data(&ControlInputs::default())
+                .await
+                .unwrap();
+
+            assert!(bridge.last_state().is_none());
+            assert!(bridge.watch_state().is_none());
+        }
+
+        #[tokio::test]
+        async fn exchanges_through_shared_arc() {
+            async fn exchange<B: AsyncBridge>(bridge: B) -> Result<SimulatorState, BridgeError> {
+                bridge.exchange_data(&ControlInputs::default()).await
+            }
+
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-200".to_string()]);
+            let bridge = Arc::new(create_bridge(port).await.unwrap());
+
+            let result = tokio::spawn(exchange(Arc::clone(&bridge))).await.unwrap();
+
+            assert!(result.is_ok(), "expected Ok: {:?}", result);
+            assert_eq!(bridge.statistics().request_count, 1);
+        }
+
+        #[tokio::test]
+        async fn returns_soap_fault_on_500() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-500".to_string()]);
+            let bridge = create_bridge(port).await.unwrap();
+
+            let control = ControlInputs::default();
+            let result = bridge.exchange_data(&control).await;
+
+            match result {
+                Err(BridgeError::SoapFault(msg)) => {
+                    assert_eq!(msg, "RealFlight Link controller has not been instantiated");
+                }
+                other => panic!("expected SoapFault, got {:?}", other),
+            }
+        }
+
+        #[tokio::test]
+        async fn parses_boolean_fields() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-200".to_string()]);
+            let bridge = create_bridge(port).await.unwrap();
+
+            let state = bridge
+                .exchange_data(&ControlInputs::default())
+                .await
+                .unwrap();
+
+            assert!(!state.is_locked);
+            assert!(!state.has_lost_components);
+            assert!(state.an_engine_is_running);
+            assert!(!state.is_touching_ground);
+            assert!(state.flight_axis_controller_is_active);
+        }
+
+        #[cfg(not(feature = "uom"))]
+        #[tokio::test]
+        async fn parses_velocity_fields() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-200".to_string()]);
+            let bridge = create_bridge(port).await.unwrap();
+
+            let state = bridge
+                .exchange_data(&ControlInputs::default())
+                .await
+                .unwrap();
+
+            assert_relative_eq!(state.airspeed, 0.040872246);
+            assert_relative_eq!(state.groundspeed, 4.643444754E-06);
+        }
+
+        #[tokio::test]
+        async fn exchange_data_timeout_fails_on_silent_simulator() {
+            // Connections complete in the backlog but are never answered
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let bridge = create_bridge(port).await.unwrap();
+
+            let timeout = Duration::from_millis(50);
+            let result = bridge
+                .exchange_data_timeout(&ControlInputs::default(), timeout)
+                .await;
+
+            match result {
+                Err(BridgeError::Timeout(elapsed)) => assert_eq!(elapsed, timeout),
+                other => panic!("expected Timeout, got {:?}", other),
+            }
+        }
+    }
+
+    mod pipeline {
+        use super::*;
+
+        #[tokio::test]
+        async fn results_arrive_in_submission_order() {
+            let port = get_available_port();
+            // Responses are served last to first
+            let server = Server::new(
+                port,
+                vec![
+                    "return-data-200".to_string(),
+                    "return-data-500".to_string(),
+                    "return-data-200".to_string(),
+                    "return-data-500".to_string(),
+                ],
+            );
+            let bridge = AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .pool_size(2)
+                .build()
+                .await
+                .unwrap();
+
+            let (inputs, mut states) = bridge.exchange_pipeline(4);
+            for i in 0..4 {
+                let mut control = ControlInputs::default();
+                control.channels[0] = i as f32 / 4.0;
+                inputs.send(control).await.unwrap();
+            }
+            drop(inputs);
+
+            let mut results = Vec::new();
+            while let Some(result) = states.recv().await {
+                results.push(result.is_ok());
+            }
+            assert_eq!(results, vec![false, true, false, true]);
+
+            let requests = server.requests();
+            for (i, request) in requests.iter().enumerate() {
+                let expected = format!("<item>{}</item>", i as f32 / 4.0);
+                assert!(request.contains(&expected), "request {} out of order", i);
+            }
+            assert_eq!(bridge.statistics().request_count, 4);
+        }
+
+        #[tokio::test]
+        async fn dropping_receiver_stops_pipeline() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-200".to_string(); 2]);
+            let bridge = create_bridge(port).await.unwrap();
+
+            let (inputs, states) = bridge.exchange_pipeline(1);
+            drop(states);
+
+            inputs.send(ControlInputs::default()).await.unwrap();
+            tokio::time::timeout(Duration::from_secs(1), inputs.closed())
+                .await
+                .expect("pipeline should stop");
+        }
+    }
+
+    mod sharing {
+        use super::*;
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::{TcpListener as TokioTcpListener, TcpStream};
+
+        const TASKS: usize = 4;
+        const EXCHANGES_PER_TASK: usize = 100;
+
+        /// Serves concurrent connections, answering each exchange with a state
+        /// whose previous inputs echo the channels of that request.
+        async fn spawn_echo_server() -> SocketAddr {
+            let listener = TokioTcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let template = std::fs::read_to_string(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/responses/return-data-200.xml"
+            ))
+            .unwrap();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(echo(stream, template.clone()));
+                }
+            });
+            addr
+        }
+
+        async fn echo(stream: TcpStream, template: String) {
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+                    return;
+                }
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("Content-Length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut request = vec![0u8; content_length];
+            reader.read_exact(&mut request).await.unwrap();
+            let request = String::from_utf8(request).unwrap();
+
+            let items = between(
+                &request,
+                "<m-channelValues-0to1>",
+                "</m-channelValues-0to1>",
+            );
+            let open = "arrayType=\"xsd:double[12]\">";
+            let start = template.find(open).unwrap() + open.len();
+            let end = start + template[start..].find("</m-channelValues-0to1>").unwrap();
+            let body = format!("{}{}{}", &template[..start], items, &template[end..]);
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = reader.get_mut().write_all(response.as_bytes()).await;
+        }
+
+        fn between<'a>(text: &'a str, open: &str, close: &str) -> &'a str {
+            let start = text.find(open).unwrap() + open.len();
+            let end = start + text[start..].find(close).unwrap();
+            &text[start..end]
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn clones_exchange_concurrently_without_mixing_responses() {
+            let addr = spawn_echo_server().await;
+            let bridge = AsyncLocalBridge::builder()
+                .addr(addr)
+                .connect_timeout(Duration::from_millis(1000))
+                .pool_size(TASKS)
+                .build()
+                .await
+                .unwrap();
+
+            let tasks: Vec<_> = (0..TASKS)
+                .map(|task| {
+                    let bridge = bridge.clone();
+                    tokio::spawn(async move {
+                        for i in 0..EXCHANGES_PER_TASK {
+                            let mut control = ControlInputs::default();
+                            control.channels[0] = task as f32 / TASKS as f32;
+                            control.channels[1] = i as f32 / EXCHANGES_PER_TASK as f32;
+
+                            let state = bridge.exchange_data(&control).await.unwrap();
+                            assert_eq!(
+                                state.previous_inputs.channels, control.channels,
+                                "task {} exchange {} got another request's response",
+                                task, i
+                            );
+                        }
+                    })
+                })
+                .collect();
+            for task in tasks {
+                task.await.unwrap();
+            }
+
+            let stats = bridge.statistics();
+            assert_eq!(stats.request_count, (TASKS * EXCHANGES_PER_TASK) as u32);
+            assert_eq!(stats.error_count, 0);
+            assert_eq!(stats.in_flight, 0);
+        }
+
+        #[tokio::test]
+        async fn shutdown_of_one_clone_leaves_others_working() {
+            let addr = spawn_echo_server().await;
+            let bridge = AsyncLocalBridge::builder()
+                .addr(addr)
+                .connect_timeout(Duration::from_millis(1000))
+                .build()
+                .await
+                .unwrap();
+            let clone = bridge.clone();
+
+            bridge.shutdown().await.unwrap();
+
+            assert!(clone.exchange_data(&ControlInputs::default()).await.is_ok());
+            clone.shutdown().await.unwrap();
+        }
+    }
+
+    mod state_stream {
+        use super::*;
+        use futures_util::StreamExt;
+
+        #[tokio::test]
+        async fn yields_states_with_latest_inputs() {
+            let port = get_available_port();
+            let server = Server::new(port, vec!["return-data-200".to_string(); 4]);
+            let bridge = create_bridge(port).await.unwrap();
+            let (inputs, inputs_rx) = watch::channel(ControlInputs::default());
+            let mut states = std::pin::pin!(bridge.state_stream(10.0, inputs_rx));
+
+            for throttle in [0.25, 0.5, 0.75] {
+                let mut control = ControlInputs::default();
+                control.channels[2] = throttle;
+                inputs.send(control).unwrap();
+
+                let state = states.next().await.unwrap();
+                assert!(state.is_ok(), "expected Ok: {:?}", state);
+            }
+
+            let requests = server.requests();
+            assert_eq!(requests.len(), 3);
+            assert!(requests[0].contains("<item>0.25</item>"));
+            assert!(requests[1].contains("<item>0.5</item>"));
+            assert!(requests[2].contains("<item>0.75</item>"));
+        }
+
+        #[tokio::test]
+        async fn reports_missed_ticks() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-200".to_string(); 3]);
+            let bridge = create_bridge(port).await.unwrap();
+            let (_inputs, inputs_rx) = watch::channel(ControlInputs::default());
+            let mut states = std::pin::pin!(bridge.state_stream(100.0, inputs_rx));
+
+            assert!(states.next().await.unwrap().is_ok());
+
+            // A slow consumer holds the stream past several 10ms ticks
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            match states.next().await.unwrap() {
+                Err(BridgeError::MissedDeadline(missed)) => assert!(missed >= 1),
+                other => panic!("expected MissedDeadline, got {:?}", other),
+            }
+            assert!(states.next().await.unwrap().is_ok());
+        }
+
+        #[tokio::test]
+        async fn ends_when_inputs_sender_dropped() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-200".to_string()]);
+            let bridge = create_bridge(port).await.unwrap();
+            let (inputs, inputs_rx) = watch::channel(ControlInputs::default());
+            let mut states = std::pin::pin!(bridge.state_stream(10.0, inputs_rx));
+
+            drop(inputs);
+            assert!(states.next().await.is_none());
+        }
+    }
+
+    mod inline_pool {
+        use super::*;
+
+        async fn create_inline_bridge(port: u16) -> Result<AsyncLocalBridge, BridgeError> {
+            AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .pool_mode(PoolMode::Inline)
+                .build()
+                .await
+        }
+
+        #[tokio::test(flavor = "current_thread")]
+        async fn exchanges_without_background_task() {
+            let port = get_available_port();
+            // Responses are served last to first
+            let server = Server::new(
+                port,
+                vec![
+                    "restore-original-controller-device-200".to_string(),
+                    "return-data-200".to_string(),
+                    "return-data-200".to_string(),
+                    "inject-uav-controller-interface-200".to_string(),
+                ],
+            );
+            let bridge = create_inline_bridge(port).await.unwrap();
+
+            bridge.disable_rc().await.unwrap();
+            for _ in 0..2 {
+                let result = bridge.exchange_data(&ControlInputs::default()).await;
+                assert!(result.is_ok(), "expected Ok: {:?}", result);
+            }
+
+            assert_eq!(bridge.statistics().request_count, 3);
+            bridge.shutdown().await.unwrap();
+            assert_eq!(server.request_count(), 4);
+
+            // Nothing connects ahead of time, so wake the server to let it exit
+            let _ = std::net::TcpStream::connect(format!("127.0.0.1:{}", port));
+        }
+
+        #[tokio::test(flavor = "current_thread")]
+        async fn build_fails_when_simulator_unreachable() {
+            let port = get_available_port();
+
+            match create_inline_bridge(port).await {
+                Err(BridgeError::Initialization(msg)) => {
+                    assert!(
+                        msg.contains("Failed to connect"),
+                        "unexpected message: {}",
+                        msg
+                    );
+                }
+                other => panic!("expected Initialization error, got {:?}", other.err()),
+            }
+        }
+    }
+
+    mod build_when_ready {
+        use super::*;
+
+        #[tokio::test]
+        async fn waits_for_late_simulator() {
+            let port = get_available_port();
+            let server = tokio::task::spawn_blocking(move || {
+                std::thread::sleep(Duration::from_millis(200));
+                Server::new(port, vec!["reset-aircraft-200".to_string()])
+            });
+
+            // The init timeout is shorter than the wait, and not applied
+            let bridge = AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .init_timeout(Duration::from_millis(100))
+                .build_when_ready()
+                .await
+                .unwrap();
+            let server = server.await.unwrap();
+
+            bridge.reset_aircraft().await.unwrap();
+            assert_eq!(server.request_count(), 1);
+        }
+    }
+
+    mod verify_on_connect {
+        use super::*;
+        use std::io::{Read, Write};
+
+        fn verifying_builder(addr: SocketAddr) -> AsyncLocalBridgeBuilder {
+            AsyncLocalBridge::builder()
+                .addr(addr)
+                .connect_timeout(Duration::from_millis(1000))
+                .init_timeout(Duration::from_millis(200))
+                .verify_on_connect(true)
+        }
+
+        #[tokio::test]
+        async fn accepts_realflight_fault() {
+            let port = get_available_port();
+            let server = Server::new(port, vec!["return-data-500".to_string(); 2]);
+
+            let addr = format!("127.0.0.1:{}", port).parse().unwrap();
+            let result = verifying_builder(addr).build().await;
+
+            assert!(result.is_ok(), "expected Ok: {:?}", result.err());
+            assert_eq!(server.request_count(), 1);
+        }
+
+        #[tokio::test]
+        async fn rejects_silent_peer() {
+            // Accepts connections (via the listen backlog) but never replies
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+            let result = verifying_builder(listener.local_addr().unwrap())
+                .build()
+                .await;
+
+            match result {
+                Err(BridgeError::Initialization(msg)) => {
+                    assert!(msg.contains("no response"), "unexpected message: {}", msg);
+                }
+                other => panic!("expected Initialization error, got {:?}", other.err()),
+            }
+        }
+
+        #[tokio::test]
+        async fn rejects_other_http_service() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            std::thread::spawn(move || {
+                if let Ok((mut stream, _)) = listener.accept() {
+                    let mut buf = [0u8; 4096];
+                    let _ = stream.read(&mut buf);
+                    let _ = stream
+                        .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 9\r\n\r\nnot found");
+                }
+            });
+
+            match verifying_builder(addr).build().await {
+                Err(BridgeError::Initialization(msg)) => {
+                    assert!(msg.contains("HTTP 404"), "unexpected message: {}", msg);
+                }
+                other => panic!("expected Initialization error, got {:?}", other.err()),
+            }
+        }
+    }
+
+    #[cfg(feature = "raw-soap")]
+    mod raw_soap {
+        use super::*;
+
+        #[tokio::test]
+        async fn raw_client_sends_custom_action() {
+            let port = get_available_port();
+            let server = Server::new(port, vec!["reset-aircraft-200".to_string()]);
+            let client = AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .build_soap_client()
+                .await
+                .unwrap();
+
+            let response = client
+                .send_action("QueryBetaFeature", "<name>wind</name>")
+                .await
+                .unwrap();
+
+            assert_eq!(response.status_code, 200);
+            assert!(response.body.contains("ResetAircraftResponse"));
+            let requests = server.requests();
+            assert_eq!(requests.len(), 1);
+            assert!(
+                requests[0].contains("<QueryBetaFeature><name>wind</name></QueryBetaFeature>"),
+                "unexpected request: {}",
+                requests[0]
+            );
         }
     }
 
@@ -450,7 +1961,13 @@ mod tests {
                     "reset-aircraft-200".to_string(),
                 ],
             );
-            let bridge = create_bridge(port).await.unwrap();
+            let bridge = AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .reset_debounce(Duration::ZERO)
+                .build()
+                .await
+                .unwrap();
 
             bridge.reset_aircraft().await.unwrap();
             bridge.reset_aircraft().await.unwrap();
@@ -459,5 +1976,144 @@ mod tests {
             assert_eq!(stats.request_count, 2);
             assert_eq!(stats.error_count, 0);
         }
+
+        #[tokio::test]
+        async fn reset_within_debounce_is_not_sent() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["reset-aircraft-200".to_string()]);
+            let bridge = AsyncLocalBridge::builder()
+                .addr(format!("127.0.0.1:{}", port).parse().unwrap())
+                .connect_timeout(Duration::from_millis(1000))
+                .init_timeout(Duration::from_secs(5))
+                .reset_debounce(Duration::from_millis(500))
+                .build()
+                .await
+                .unwrap();
+
+            bridge.reset_aircraft().await.unwrap();
+            bridge.reset_aircraft().await.unwrap();
+
+            assert_eq!(bridge.statistics().request_count, 1);
+        }
+
+        #[tokio::test]
+        async fn concurrent_exchanges_wait_on_single_connection_pool() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-200".to_string(); 3]);
+            let bridge = create_bridge(port).await.unwrap();
+            let control = ControlInputs::default();
+
+            let (first, second) = tokio::join!(
+                bridge.exchange_data(&control),
+                bridge.exchange_data(&control)
+            );
+            assert!(first.is_ok() && second.is_ok());
+
+            let stats = bridge.statistics();
+            assert!(stats.pool_wait_total > Duration::ZERO);
+            assert!(stats.io_total > Duration::ZERO);
+            assert_eq!(stats.in_flight, 0);
+        }
+    }
+
+    // ========================================================================
+    // Ping Tests
+    // ========================================================================
+
+    mod ping {
+        use super::*;
+
+        #[tokio::test]
+        async fn returns_round_trip_time() {
+            let port = get_available_port();
+            let server = Server::new(port, vec!["return-data-200".to_string()]);
+            let bridge = create_bridge(port).await.unwrap();
+
+            let latency = bridge.ping().await.unwrap();
+            assert!(latency > Duration::ZERO);
+            assert!(latency < Duration::from_secs(1), "took {:?}", latency);
+
+            let requests = server.requests();
+            assert_eq!(requests.len(), 1);
+            assert!(requests[0].contains("<m-selectedChannels>0</m-selectedChannels>"));
+        }
+
+        #[tokio::test]
+        async fn succeeds_on_soap_fault() {
+            let port = get_available_port();
+            let _server = Server::new(port, vec!["return-data-500".to_string()]);
+            let bridge = create_bridge(port).await.unwrap();
+
+            let result = bridge.ping().await;
+            assert!(result.is_ok(), "expected Ok: {:?}", result);
+        }
+    }
+
+    // ========================================================================
+    // Shutdown Tests
+    // ========================================================================
+
+    mod shutdown {
+        use super::*;
+
+        fn restore_count(server: &Server) -> usize {
+            server
+                .requests()
+                .iter()
+                .filter(|r| r.contains("RestoreOriginalControllerDevice"))
+                .count()
+        }
+
+        #[tokio::test]
+        async fn restores_rc_when_disabled() {
+            let port = get_available_port();
+            // Responses are served last to first
+            let server = Server::new(
+                port,
+                vec![
+                    "restore-original-controller-device-200".to_string(),
+                    "inject-uav-controller-interface-200".to_string(),
+                ],
+            );
+            let bridge = create_bridge(port).await.unwrap();
+
+            bridge.disable_rc().await.unwrap();
+            let result = bridge.shutdown().await;
+
+            assert!(result.is_ok(), "expected Ok: {:?}", result);
+            assert_eq!(restore_count(&server), 1);
+        }
+
+        #[tokio::test]
+        async fn skips_restore_after_manual_enable() {
+            let port = get_available_port();
+            let server = Server::new(
+                port,
+                vec![
+                    "restore-original-controller-device-200".to_string(),
+                    "inject-uav-controller-interface-200".to_string(),
+                ],
+            );
+            let bridge = create_bridge(port).await.unwrap();
+
+            bridge.disable_rc().await.unwrap();
+            bridge.enable_rc().await.unwrap();
+            let result = bridge.shutdown().await;
+
+            assert!(result.is_ok(), "expected Ok: {:?}", result);
+            assert_eq!(restore_count(&server), 1);
+        }
+
+        #[tokio::test]
+        async fn sends_nothing_when_rc_never_disabled() {
+            let port = get_available_port();
+            let server = Server::new(port, vec!["reset-aircraft-200".to_string()]);
+            let bridge = create_bridge(port).await.unwrap();
+
+            let result = bridge.shutdown().await;
+
+            assert!(result.is_ok(), "expected Ok: {:?}", result);
+            assert_eq!(server.request_count(), 0);
+        }
     }
 }
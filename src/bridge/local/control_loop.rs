@@ -0,0 +1,241 @@
+//! Fixed-rate control loop built on [RealFlightLocalBridge].
+
+use std::time::Duration;
+
+use crate::bridge::RealFlightBridge;
+use crate::clock::{Clock, SystemClock};
+use crate::diagnostics::{PhysicsHealth, PhysicsWatchdog};
+use crate::timing::{RateLimiter, Tick};
+use crate::{BridgeError, ControlInputs, SimulatorState};
+
+use super::RealFlightLocalBridge;
+
+/// Decision returned by the closure passed to [RealFlightLocalBridge::run_control_loop].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ControlOutcome {
+    /// Send these inputs on the next iteration
+    Continue(ControlInputs),
+    /// Leave the loop
+    Stop,
+}
+
+/// Timing summary of a completed control loop.
+///
+/// Jitter is how late an iteration started relative to its schedule.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LoopReport {
+    /// Number of completed data exchanges
+    pub iterations: u64,
+    /// Time from the first exchange until the loop stopped
+    pub elapsed: Duration,
+    /// Average start delay across iterations
+    pub mean_jitter: Duration,
+    /// Largest start delay of any iteration
+    pub max_jitter: Duration,
+    /// Iterations whose exchange and closure took longer than one period
+    pub overruns: u64,
+    /// Stalls of the simulator's physics time, with
+    /// [run_control_loop_with_watchdog](RealFlightLocalBridge::run_control_loop_with_watchdog)
+    pub physics_stalls: u64,
+    /// Jumps of the simulator's physics time, with
+    /// [run_control_loop_with_watchdog](RealFlightLocalBridge::run_control_loop_with_watchdog)
+    pub physics_jumps: u64,
+}
+
+impl RealFlightLocalBridge {
+    /// Runs a control loop at a fixed rate until `f` returns [ControlOutcome::Stop].
+    ///
+    /// Each iteration exchanges the current inputs for a new [SimulatorState] and
+    /// passes it to `f`, which returns the inputs for the next iteration. The first
+    /// iteration sends [ControlInputs::default]. Iterations are paced by a
+    /// [RateLimiter], so time spent exchanging data and in `f` is taken out of the
+    /// wait and the loop holds `rate_hz` as long as an iteration fits within one
+    /// period. After an overrun the schedule restarts from the current time
+    /// instead of bursting to catch up.
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first failed exchange and returns its error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate_hz` is not a positive, finite number.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{ControlInputs, ControlOutcome, RealFlightBridge, RealFlightLocalBridge};
+    /// use std::error::Error;
+    ///
+    /// fn main() -> Result<(), Box<dyn Error>> {
+    ///     let bridge = RealFlightLocalBridge::new()?;
+    ///     bridge.disable_rc()?;
+    ///
+    ///     let report = bridge.run_control_loop(100.0, |state| {
+    ///         if state.has_lost_components {
+    ///             return ControlOutcome::Stop;
+    ///         }
+    ///         let mut inputs = ControlInputs::default();
+    ///         inputs.channels[2] = 1.0; // Full throttle
+    ///         ControlOutcome::Continue(inputs)
+    ///     })?;
+    ///     println!("{} iterations, max jitter {:?}", report.iterations, report.max_jitter);
+    ///
+    ///     bridge.enable_rc()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn run_control_loop<F>(&self, rate_hz: f32, f: F) -> Result<LoopReport, BridgeError>
+    where
+        F: FnMut(&SimulatorState) -> ControlOutcome,
+    {
+        self.run_control_loop_with_clock(rate_hz, &SystemClock, f)
+    }
+
+    /// Runs [run_control_loop](Self::run_control_loop) with its timing taken
+    /// from `clock`.
+    ///
+    /// With a [ManualClock](crate::clock::ManualClock) the loop runs without
+    /// waiting and its report is exact, which makes the schedule testable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate_hz` is not a positive, finite number.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::clock::ManualClock;
+    /// use realflight_bridge::{ControlInputs, ControlOutcome, RealFlightLocalBridge};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bridge = RealFlightLocalBridge::new()?;
+    /// let clock = ManualClock::new();
+    /// let mut remaining = 10;
+    ///
+    /// let report = bridge.run_control_loop_with_clock(100.0, &clock, |_| {
+    ///     remaining -= 1;
+    ///     if remaining == 0 {
+    ///         ControlOutcome::Stop
+    ///     } else {
+    ///         ControlOutcome::Continue(ControlInputs::default())
+    ///     }
+    /// })?;
+    /// // Nine periods of 10 ms, without waiting for them
+    /// println!("{:?}", report.elapsed);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run_control_loop_with_clock<F>(
+        &self,
+        rate_hz: f32,
+        clock: &impl Clock,
+        mut f: F,
+    ) -> Result<LoopReport, BridgeError>
+    where
+        F: FnMut(&SimulatorState) -> ControlOutcome,
+    {
+        self.run_loop(rate_hz, clock, |state, _| f(state))
+    }
+
+    /// Runs [run_control_loop](Self::run_control_loop) with every state fed to
+    /// `watchdog`, passing `f` the [PhysicsHealth] it reports along with the
+    /// state.
+    ///
+    /// When RealFlight's physics pauses, exchanges keep returning the same
+    /// state; `f` can hold its integrators until the health is
+    /// [Ok](PhysicsHealth::Ok) again, and reset them after a
+    /// [jump](PhysicsHealth::Jumped). Stalls and jumps are counted in the
+    /// report. The loop is timed on the watchdog's clock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate_hz` is not a positive, finite number.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::diagnostics::{PhysicsHealth, PhysicsWatchdog, WatchdogConfig};
+    /// use realflight_bridge::{ControlInputs, ControlOutcome, RealFlightLocalBridge};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bridge = RealFlightLocalBridge::new()?;
+    /// let mut watchdog = PhysicsWatchdog::new(WatchdogConfig::default());
+    /// let inputs = ControlInputs::default();
+    ///
+    /// let report = bridge.run_control_loop_with_watchdog(100.0, &mut watchdog, |state, health| {
+    ///     if let PhysicsHealth::Ok = health {
+    ///         // Update inputs from state...
+    ///     }
+    ///     ControlOutcome::Continue(inputs.clone())
+    /// })?;
+    /// println!("{} physics stalls", report.physics_stalls);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run_control_loop_with_watchdog<C, F>(
+        &self,
+        rate_hz: f32,
+        watchdog: &mut PhysicsWatchdog<C>,
+        mut f: F,
+    ) -> Result<LoopReport, BridgeError>
+    where
+        C: Clock + Clone,
+        F: FnMut(&SimulatorState, &PhysicsHealth) -> ControlOutcome,
+    {
+        let clock = watchdog.clock().clone();
+        let mut last = watchdog.status();
+        self.run_loop(rate_hz, &clock, |state, report| {
+            let health = watchdog.feed(state);
+            match health {
+                PhysicsHealth::Stalled { .. } if !matches!(last, PhysicsHealth::Stalled { .. }) => {
+                    report.physics_stalls += 1
+                }
+                PhysicsHealth::Jumped { .. } => report.physics_jumps += 1,
+                _ => {}
+            }
+            last = health;
+            f(state, &health)
+        })
+    }
+
+    /// Exchanges data at `rate_hz` on `clock` until `step` returns
+    /// [ControlOutcome::Stop].
+    fn run_loop<F>(
+        &self,
+        rate_hz: f32,
+        clock: &impl Clock,
+        mut step: F,
+    ) -> Result<LoopReport, BridgeError>
+    where
+        F: FnMut(&SimulatorState, &mut LoopReport) -> ControlOutcome,
+    {
+        let mut limiter = RateLimiter::with_clock(rate_hz, clock);
+        let mut report = LoopReport::default();
+        let mut total_jitter = Duration::ZERO;
+        let mut inputs = ControlInputs::default();
+        let start = limiter.deadline();
+
+        loop {
+            let jitter = clock.now().saturating_duration_since(limiter.deadline());
+            total_jitter += jitter;
+            report.max_jitter = report.max_jitter.max(jitter);
+
+            let state = self.exchange_data(&inputs)?;
+            report.iterations += 1;
+
+            match step(&state, &mut report) {
+                ControlOutcome::Continue(next) => inputs = next,
+                ControlOutcome::Stop => break,
+            }
+
+            if let Tick::Missed { .. } = limiter.wait() {
+                report.overruns += 1;
+            }
+        }
+
+        report.elapsed = clock.now() - start;
+        report.mean_jitter = total_jitter.div_f64(report.iterations as f64);
+        Ok(report)
+    }
+}
@@ -1,19 +1,49 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    borrow::Cow,
+    net::SocketAddr,
+    ops::Deref,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use log::{debug, error, warn};
 
 use super::RealFlightBridge;
-use crate::encoders::encode_control_inputs;
-use crate::soap_client::{SoapClient, tcp::TcpSoapClient};
-use crate::{BridgeError, ControlInputs, SimulatorState, Statistics, StatisticsEngine};
+use crate::clock::{Clock, SystemClock};
+use crate::constants::{
+    DEFAULT_CONNECT_TIMEOUT, DEFAULT_MAX_IDLE, DEFAULT_MAX_RESPONSE_SIZE, DEFAULT_POOL_SIZE,
+    DEFAULT_RESET_DEBOUNCE, DEFAULT_SAFE_START_MAX_AIRSPEED, DEFAULT_SIMULATOR_HOST,
+};
+use crate::encoders::{encode_control_inputs, encode_idle_inputs, units};
+#[cfg(feature = "custom-transport")]
+use crate::soap_client::custom::Counted;
+use crate::soap_client::pool::InitSignal;
+pub use crate::soap_client::pool::InitState;
+pub use crate::soap_client::transcript::{Transcript, TranscriptEntry};
+pub use crate::soap_client::{RequestTap, SoapResponseSummary};
+use crate::soap_client::{SoapClient, SoapResponse, Wait, tcp::TcpSoapClient};
+use crate::{
+    BridgeError, ControlInputs, SimulatorState, SoapFaultKind, Statistics, StatisticsEngine,
+};
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-support"))]
 use crate::soap_client::stub::StubSoapClient;
 
 #[cfg(feature = "rt-tokio")]
 mod async_impl;
 #[cfg(feature = "rt-tokio")]
-pub use async_impl::{AsyncLocalBridge, AsyncLocalBridgeBuilder};
+pub use async_impl::{AsyncLocalBridge, AsyncLocalBridgeBuilder, PoolMode};
+
+mod control_loop;
+pub use control_loop::{ControlOutcome, LoopReport};
 
 const EMPTY_BODY: &str = "";
+/// Times [RealFlightLocalBridge::ensure_rc_disabled] restores and re-injects the controller
+const RC_RECOVERY_ATTEMPTS: usize = 3;
 
 /// A high-level client for interacting with RealFlight simulators via RealFlight Link.
 ///
@@ -77,6 +107,21 @@ const EMPTY_BODY: &str = "";
 pub struct RealFlightLocalBridge {
     statistics: Arc<StatisticsEngine>,
     soap_client: Box<dyn SoapClient>,
+    /// Progress of the connection pool; `None` for a client without one
+    init: Option<Arc<InitSignal>>,
+    /// Whether the pool last reached the simulator; `None` for a client without one
+    sim_reachable: Option<Arc<AtomicBool>>,
+    /// Whether this bridge has injected the RealFlight Link controller
+    rc_disabled: AtomicBool,
+    simulator_host: String,
+    /// Whether successful exchanges are stored in `last_state`
+    cache_last_state: bool,
+    last_state: RwLock<Option<SimulatorState>>,
+    reset_debounce: ResetDebounce,
+    /// Checks the aircraft is at rest before the controller is injected
+    safe_start: Option<SafeStart>,
+    /// Limits applied to every exchange's inputs
+    channel_limits: Option<ChannelLimits>,
 }
 
 impl RealFlightBridge for RealFlightLocalBridge {
@@ -118,12 +163,7 @@ impl RealFlightBridge for RealFlightLocalBridge {
     /// }
     /// ```
     fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
-        let body = encode_control_inputs(control);
-        let response = self.soap_client.send_action("ExchangeData", &body)?;
-        match response.status_code {
-            200 => crate::decoders::decode_simulator_state(&response.body),
-            _ => Err(BridgeError::SoapFault(response.fault_message())),
-        }
+        self.exchange_data_with(control, Wait::Blocking)
     }
 
     /// Reverts the RealFlight simulator to use its original Spektrum (or built-in) RC input.
@@ -155,9 +195,14 @@ impl RealFlightBridge for RealFlightLocalBridge {
     /// }
     /// ```
     fn enable_rc(&self) -> Result<(), BridgeError> {
-        self.soap_client
+        let result: Result<(), BridgeError> = self
+            .soap_client
             .send_action("RestoreOriginalControllerDevice", EMPTY_BODY)?
-            .into()
+            .into();
+        if result.is_ok() {
+            self.rc_disabled.store(false, Ordering::Relaxed);
+        }
+        result
     }
 
     /// Switches the RealFlight simulator's input to the external RealFlight Link controller,
@@ -172,6 +217,14 @@ impl RealFlightBridge for RealFlightLocalBridge {
     /// Returns `Ok(())` if RealFlight Link mode is successfully activated, or an `Err` if
     /// the request fails (e.g., simulator is not ready or rejects the command).
     ///
+    /// With [Configuration::safe_start] set, the state is read first, and the
+    /// call fails with [BridgeError::InvalidState] without injecting the
+    /// controller if the aircraft is off the ground or moving faster than the
+    /// allowed airspeed. [RealFlightLocalBridge::force_disable_rc] skips the
+    /// check. RealFlight only reports the state once the controller is
+    /// injected, so the check applies when one is left injected, e.g. by a
+    /// process that crashed mid-flight; otherwise there is nothing to check.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -190,9 +243,15 @@ impl RealFlightBridge for RealFlightLocalBridge {
     /// }
     /// ```
     fn disable_rc(&self) -> Result<(), BridgeError> {
-        self.soap_client
-            .send_action("InjectUAVControllerInterface", EMPTY_BODY)?
-            .into()
+        if let Some(safe_start) = &self.safe_start {
+            let response = self
+                .soap_client
+                .send_action("ExchangeData", &encode_idle_inputs())?;
+            if let Some(state) = safe_start_state(response)? {
+                safe_start.check(&state)?;
+            }
+        }
+        self.force_disable_rc()
     }
 
     /// Resets the currently loaded aircraft in the RealFlight simulator, analogous
@@ -207,6 +266,9 @@ impl RealFlightBridge for RealFlightLocalBridge {
     /// `Ok(())` upon a successful reset. Returns an error if RealFlight rejects the command
     /// or if a network issue prevents delivery.
     ///
+    /// A reset requested within [Configuration::reset_debounce] of the last
+    /// one the simulator accepted is not sent, and returns `Ok(())`.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -225,9 +287,17 @@ impl RealFlightBridge for RealFlightLocalBridge {
     /// }
     /// ```
     fn reset_aircraft(&self) -> Result<(), BridgeError> {
-        self.soap_client
+        if !self.reset_debounce.admit() {
+            return Ok(());
+        }
+        let result: Result<(), BridgeError> = self
+            .soap_client
             .send_action("ResetAircraft", EMPTY_BODY)?
-            .into()
+            .into();
+        if result.is_ok() {
+            self.reset_debounce.record();
+        }
+        result
     }
 }
 
@@ -266,13 +336,20 @@ impl RealFlightLocalBridge {
     ///
     /// - If the TCP connection pool cannot be established (e.g., RealFlight is not running).
     pub fn new() -> Result<RealFlightLocalBridge, BridgeError> {
-        let statistics = Arc::new(StatisticsEngine::new());
-        let soap_client = TcpSoapClient::new(Configuration::default(), statistics.clone())?;
-        soap_client.ensure_pool_initialized()?;
+        let soap_client = TcpSoapClient::connect(&Configuration::default())?;
 
         Ok(RealFlightLocalBridge {
-            statistics: statistics.clone(),
+            statistics: soap_client.statistics.clone(),
+            init: Some(soap_client.init_signal()),
+            sim_reachable: Some(soap_client.sim_reachable()),
             soap_client: Box::new(soap_client),
+            rc_disabled: AtomicBool::new(false),
+            simulator_host: DEFAULT_SIMULATOR_HOST.to_string(),
+            cache_last_state: false,
+            last_state: RwLock::new(None),
+            reset_debounce: ResetDebounce::new(DEFAULT_RESET_DEBOUNCE),
+            safe_start: None,
+            channel_limits: None,
         })
     }
 
@@ -320,20 +397,83 @@ impl RealFlightLocalBridge {
     pub fn with_configuration(
         configuration: &Configuration,
     ) -> Result<RealFlightLocalBridge, BridgeError> {
-        let statistics = Arc::new(StatisticsEngine::new());
-        let soap_client = TcpSoapClient::new(configuration.clone(), statistics.clone())?;
-        soap_client.ensure_pool_initialized()?;
+        let soap_client = TcpSoapClient::connect(configuration)?;
 
         Ok(RealFlightLocalBridge {
-            statistics: statistics.clone(),
+            statistics: soap_client.statistics.clone(),
+            init: Some(soap_client.init_signal()),
+            sim_reachable: Some(soap_client.sim_reachable()),
             soap_client: Box::new(soap_client),
+            rc_disabled: AtomicBool::new(false),
+            simulator_host: configuration.simulator_host.clone(),
+            cache_last_state: configuration.cache_last_state,
+            last_state: RwLock::new(None),
+            reset_debounce: ResetDebounce::new(configuration.reset_debounce),
+            safe_start: configuration.safe_start,
+            channel_limits: configuration.channel_limits,
         })
     }
 
-    /// Creates a new RealFlightLink client
-    /// simulator_url: the url to the RealFlight simulator
-    #[cfg(test)]
-    pub(crate) fn stub(mut soap_client: StubSoapClient) -> RealFlightLocalBridge {
+    /// Creates a bridge that sends its requests over `soap_client` instead of
+    /// a pool of TCP connections to RealFlight.
+    ///
+    /// For links the bridge cannot open itself, such as SOAP tunnelled over a
+    /// serial line in a hardware-in-the-loop rack. No connection pool is
+    /// created, so [RealFlightLocalBridge::try_initialized] reports
+    /// [InitState::Ready] and [RealFlightLocalBridge::simulator_host] is
+    /// empty. The bridge counts the client's requests, errors and round-trip
+    /// times in [RealFlightLocalBridge::statistics]; see [SoapClient] for the
+    /// contract a client must meet.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{
+    ///     BridgeError, RealFlightBridge, RealFlightLocalBridge, SoapClient, SoapResponse, Wait,
+    /// };
+    ///
+    /// struct SerialTunnel;
+    ///
+    /// impl SoapClient for SerialTunnel {
+    ///     fn send_action_with(
+    ///         &self,
+    ///         action: &str,
+    ///         body: &str,
+    ///         wait: Wait,
+    ///     ) -> Result<SoapResponse, BridgeError> {
+    ///         // Write the request to the tunnel and read back the response
+    ///         # unimplemented!()
+    ///     }
+    /// }
+    ///
+    /// let bridge = RealFlightLocalBridge::with_soap_client(Box::new(SerialTunnel));
+    /// bridge.reset_aircraft()?;
+    /// # Ok::<(), BridgeError>(())
+    /// ```
+    #[cfg(feature = "custom-transport")]
+    pub fn with_soap_client(soap_client: Box<dyn SoapClient>) -> RealFlightLocalBridge {
+        let statistics = Arc::new(StatisticsEngine::new());
+
+        RealFlightLocalBridge {
+            soap_client: Box::new(Counted::new(soap_client, statistics.clone())),
+            statistics,
+            init: None,
+            sim_reachable: None,
+            rc_disabled: AtomicBool::new(false),
+            simulator_host: String::new(),
+            cache_last_state: false,
+            last_state: RwLock::new(None),
+            reset_debounce: ResetDebounce::new(DEFAULT_RESET_DEBOUNCE),
+            safe_start: None,
+            channel_limits: None,
+        }
+    }
+
+    /// Creates a bridge that answers from `soap_client` instead of RealFlight.
+    ///
+    /// Requires the `test-support` feature outside this crate.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn stub(mut soap_client: StubSoapClient) -> RealFlightLocalBridge {
         let statistics = Arc::new(StatisticsEngine::new());
 
         soap_client.statistics = Some(statistics.clone());
@@ -341,6 +481,15 @@ impl RealFlightLocalBridge {
         RealFlightLocalBridge {
             statistics,
             soap_client: Box::new(soap_client),
+            init: None,
+            sim_reachable: None,
+            rc_disabled: AtomicBool::new(false),
+            simulator_host: DEFAULT_SIMULATOR_HOST.to_string(),
+            cache_last_state: false,
+            last_state: RwLock::new(None),
+            reset_debounce: ResetDebounce::new(DEFAULT_RESET_DEBOUNCE),
+            safe_start: None,
+            channel_limits: None,
         }
     }
 
@@ -349,10 +498,344 @@ impl RealFlightLocalBridge {
         self.soap_client.requests().clone()
     }
 
+    /// Reports whether the connection pool has reached the simulator, without
+    /// blocking.
+    ///
+    /// Only [Configuration::lazy_connect] bridges can be [InitState::Pending];
+    /// other bridges are constructed once the pool is ready.
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{Configuration, InitState, RealFlightLocalBridge};
+    ///
+    /// let config = Configuration {
+    ///     lazy_connect: true,
+    ///     ..Default::default()
+    /// };
+    /// let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+    /// if bridge.try_initialized() == InitState::Pending {
+    ///     println!("Waiting for RealFlight...");
+    /// }
+    /// ```
+    pub fn try_initialized(&self) -> InitState {
+        self.init
+            .as_ref()
+            .map_or(InitState::Ready, |init| init.state())
+    }
+
+    /// Blocks until the connection pool has reached the simulator, however
+    /// long that takes.
+    ///
+    /// Meant for [Configuration::lazy_connect] bridges, which are constructed
+    /// before the pool is ready; other bridges return at once. Fails with
+    /// [BridgeError::Initialization] if the pool gave up, e.g. because
+    /// [Configuration::simulator_host] is not a valid address.
+    pub fn wait_initialized(&self) -> Result<(), BridgeError> {
+        match &self.init {
+            Some(init) => init.wait_ready(None),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the connection pool's latest connection to the simulator
+    /// succeeded.
+    ///
+    /// The pool connects whenever it refills, so an idle bridge only notices
+    /// RealFlight quitting with [Configuration::probe_interval] set. `false`
+    /// until the first connection is made.
+    pub fn simulator_reachable(&self) -> bool {
+        self.sim_reachable
+            .as_ref()
+            .is_none_or(|reachable| reachable.load(Ordering::Relaxed))
+    }
+
     /// Get statistics for the RealFlightBridge
     pub fn statistics(&self) -> Statistics {
         self.statistics.snapshot()
     }
+
+    /// Returns a copy of the most recent state received from the simulator.
+    ///
+    /// Requires [Configuration::cache_last_state]; always `None` otherwise, and
+    /// until the first successful exchange. Safe to call from other threads while
+    /// a control loop is exchanging data.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{BridgeError, Configuration, RealFlightLocalBridge};
+    /// use std::sync::Arc;
+    /// use std::thread;
+    ///
+    /// fn main() -> Result<(), BridgeError> {
+    ///     let config = Configuration {
+    ///         cache_last_state: true,
+    ///         ..Default::default()
+    ///     };
+    ///     let bridge = Arc::new(RealFlightLocalBridge::with_configuration(&config)?);
+    ///
+    ///     let telemetry = Arc::clone(&bridge);
+    ///     thread::spawn(move || {
+    ///         if let Some(state) = telemetry.last_state() {
+    ///             println!("Altitude: {:?}", state.altitude_agl);
+    ///         }
+    ///     });
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn last_state(&self) -> Option<SimulatorState> {
+        self.last_state.read().unwrap().clone()
+    }
+
+    /// Returns the simulator address this bridge was configured with.
+    ///
+    /// Useful for telling bridges apart when driving several simulators at once.
+    pub fn simulator_host(&self) -> &str {
+        &self.simulator_host
+    }
+
+    /// Checks that RealFlight Link is responding and returns the round-trip time.
+    ///
+    /// Sends an `ExchangeData` request that selects no channels, so the aircraft
+    /// is not affected. Any SOAP response counts as success, including the fault
+    /// returned before [RealFlightBridge::disable_rc] has been called.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{RealFlightLocalBridge, BridgeError};
+    ///
+    /// fn main() -> Result<(), BridgeError> {
+    ///     let bridge = RealFlightLocalBridge::new()?;
+    ///
+    ///     let latency = bridge.ping()?;
+    ///     println!("RealFlight Link responded in {:?}", latency);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn ping(&self) -> Result<Duration, BridgeError> {
+        let start = Instant::now();
+        self.soap_client
+            .send_action("ExchangeData", &encode_idle_inputs())?;
+        Ok(start.elapsed())
+    }
+
+    /// Like [RealFlightBridge::exchange_data], but fails with [BridgeError::Timeout]
+    /// instead of blocking past `deadline`.
+    ///
+    /// The deadline covers waiting for a pooled connection, sending the request
+    /// and reading the response. It takes precedence over
+    /// [Configuration::io_timeout]. The reported timeout is the time that was left
+    /// when the call started.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{BridgeError, ControlInputs, RealFlightLocalBridge};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// fn main() -> Result<(), BridgeError> {
+    ///     let bridge = RealFlightLocalBridge::new()?;
+    ///
+    ///     let deadline = Instant::now() + Duration::from_millis(10);
+    ///     match bridge.exchange_data_deadline(&ControlInputs::default(), deadline) {
+    ///         Ok(state) => println!("Airspeed: {:?}", state.airspeed),
+    ///         Err(BridgeError::Timeout(_)) => println!("Simulator too slow, skipping frame"),
+    ///         Err(e) => return Err(e),
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn exchange_data_deadline(
+        &self,
+        control: &ControlInputs,
+        deadline: Instant,
+    ) -> Result<SimulatorState, BridgeError> {
+        self.exchange_data_with(control, Wait::Until(deadline))
+    }
+
+    /// Like [RealFlightBridge::exchange_data], but fails immediately with
+    /// [BridgeError::Timeout] if no pooled connection is ready.
+    ///
+    /// Once a connection is taken, the exchange is bounded by
+    /// [Configuration::io_timeout] as usual.
+    pub fn try_exchange_data(
+        &self,
+        control: &ControlInputs,
+    ) -> Result<SimulatorState, BridgeError> {
+        self.exchange_data_with(control, Wait::Immediate)
+    }
+
+    fn exchange_data_with(
+        &self,
+        control: &ControlInputs,
+        wait: Wait,
+    ) -> Result<SimulatorState, BridgeError> {
+        let control = limit_inputs(self.channel_limits.as_ref(), control, &self.statistics);
+        let body = encode_control_inputs(&control);
+        let response = self
+            .soap_client
+            .send_action_with("ExchangeData", &body, wait)?;
+        let state = match response.status_code {
+            200 => crate::decoders::decode_simulator_state(&response.body)?,
+            _ => return Err(BridgeError::SoapFault(response.fault_message())),
+        };
+        if self.cache_last_state {
+            *self.last_state.write().unwrap() = Some(state.clone());
+        }
+        Ok(state)
+    }
+
+    /// Switches to the RealFlight Link controller without the
+    /// [Configuration::safe_start] check, for when the aircraft is known to be
+    /// safe to take over, e.g. to recover it mid-flight on purpose.
+    pub fn force_disable_rc(&self) -> Result<(), BridgeError> {
+        let result: Result<(), BridgeError> = self
+            .soap_client
+            .send_action("InjectUAVControllerInterface", EMPTY_BODY)?
+            .into();
+        if result.is_ok() {
+            self.rc_disabled.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Switches to the RealFlight Link controller, recovering from a controller
+    /// left injected by a previous process.
+    ///
+    /// If [RealFlightBridge::disable_rc] fails with
+    /// [SoapFaultKind::PreexistingController], the original controller device is
+    /// restored and the injection retried, up to three times. Any other error is
+    /// returned immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{RealFlightLocalBridge, BridgeError};
+    ///
+    /// fn main() -> Result<(), BridgeError> {
+    ///     let bridge = RealFlightLocalBridge::new()?;
+    ///
+    ///     // Succeeds even if a crashed controller left RealFlight Link injected
+    ///     bridge.ensure_rc_disabled()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn ensure_rc_disabled(&self) -> Result<(), BridgeError> {
+        let mut result = self.disable_rc();
+        for attempt in 1..=RC_RECOVERY_ATTEMPTS {
+            match &result {
+                Err(e) if e.fault_kind() == Some(SoapFaultKind::PreexistingController) => {
+                    warn!(
+                        "Controller already injected, restoring original device (attempt {})",
+                        attempt
+                    );
+                    // A failed restore is not fatal; the retried injection reports the outcome
+                    if let Err(e) = self.enable_rc() {
+                        warn!("Failed to restore original controller device: {}", e);
+                    }
+                    result = self.disable_rc();
+                }
+                _ => break,
+            }
+        }
+        result
+    }
+
+    /// Switches to the RealFlight Link controller and returns a guard that
+    /// switches back to the original RC device when dropped.
+    ///
+    /// Use this to make sure the simulator is not left with the RealFlight Link
+    /// controller injected when the control loop exits early, e.g. through `?`.
+    /// Otherwise the next [RealFlightBridge::disable_rc] fails with
+    /// "Preexisting controller reference".
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{RealFlightBridge, RealFlightLocalBridge, ControlInputs};
+    /// use std::error::Error;
+    ///
+    /// fn main() -> Result<(), Box<dyn Error>> {
+    ///     let bridge = RealFlightLocalBridge::new()?;
+    ///
+    ///     let guard = bridge.disable_rc_guarded()?;
+    ///     let state = guard.exchange_data(&ControlInputs::default())?;
+    ///     println!("Altitude: {:?}", state.altitude_agl);
+    ///
+    ///     // Original RC device is restored here
+    ///     drop(guard);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn disable_rc_guarded(&self) -> Result<RcGuard<'_>, BridgeError> {
+        self.disable_rc()?;
+        Ok(RcGuard { bridge: self })
+    }
+
+    /// Shuts the bridge down, leaving the simulator as it was found.
+    ///
+    /// Restores the original RC device if this bridge injected the RealFlight Link
+    /// controller and has not restored it since, then stops the connection pool's
+    /// background thread. The restore is attempted even if stopping the pool fails;
+    /// the first error encountered is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{RealFlightBridge, RealFlightLocalBridge};
+    /// use std::error::Error;
+    ///
+    /// fn main() -> Result<(), Box<dyn Error>> {
+    ///     let bridge = RealFlightLocalBridge::new()?;
+    ///     bridge.disable_rc()?;
+    ///
+    ///     // ... control loop ...
+    ///
+    ///     bridge.shutdown()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn shutdown(self) -> Result<(), BridgeError> {
+        let restored = self.restore_rc();
+        let stopped = self.soap_client.shutdown();
+        restored.and(stopped)
+    }
+
+    /// Restores the original RC device if this bridge still has it disabled.
+    fn restore_rc(&self) -> Result<(), BridgeError> {
+        if self.rc_disabled.load(Ordering::Relaxed) {
+            self.enable_rc()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Restores the original RC device when dropped.
+///
+/// Returned by [RealFlightLocalBridge::disable_rc_guarded]. Dereferences to the
+/// bridge, so it can be used in place of it for the duration of the control loop.
+/// If the RC device was already restored, e.g. by calling
+/// [RealFlightBridge::enable_rc], dropping the guard sends nothing.
+#[must_use = "the original RC device is restored as soon as the guard is dropped"]
+pub struct RcGuard<'a> {
+    bridge: &'a RealFlightLocalBridge,
+}
+
+impl Deref for RcGuard<'_> {
+    type Target = RealFlightLocalBridge;
+
+    fn deref(&self) -> &Self::Target {
+        self.bridge
+    }
+}
+
+impl Drop for RcGuard<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.bridge.restore_rc() {
+            error!("Failed to restore original RC device: {}", e);
+        }
+    }
 }
 
 /// Configuration settings for the RealFlight Link bridge.
@@ -371,13 +854,26 @@ impl RealFlightLocalBridge {
 ///
 /// The default configuration is suitable for most local development:
 /// ```rust
-/// use realflight_bridge::Configuration;
+/// use realflight_bridge::{Configuration, ConnectionMode, PoolSize};
 /// use std::time::Duration;
 ///
 /// let default_config = Configuration {
 ///     simulator_host: "127.0.0.1:18083".to_string(),
 ///     connect_timeout: Duration::from_millis(5),
-///     pool_size: 1,
+///     io_timeout: None,
+///     local_bind: None,
+///     pool_size: PoolSize::Fixed(1),
+///     max_idle: Duration::from_secs(10),
+///     lazy_connect: false,
+///     connection_mode: ConnectionMode::Pooled,
+///     probe_interval: None,
+///     max_response_size: 256 * 1024,
+///     tap: None,
+///     transcript: None,
+///     cache_last_state: false,
+///     reset_debounce: Duration::ZERO,
+///     safe_start: None,
+///     channel_limits: None,
 /// };
 /// ```
 ///
@@ -399,7 +895,9 @@ impl RealFlightLocalBridge {
 /// let config = Configuration {
 ///     simulator_host: "127.0.0.1:18083".to_string(),
 ///     connect_timeout: Duration::from_millis(25),  // Faster timeout
-///     pool_size: 5,                                // Larger connection pool
+///     io_timeout: Some(Duration::from_millis(50)), // Fail fast on a stalled response
+///     pool_size: 5.into(),                         // Larger connection pool
+///     ..Default::default()
 /// };
 /// ```
 ///
@@ -411,10 +909,12 @@ impl RealFlightLocalBridge {
 /// let config = Configuration {
 ///     simulator_host: "192.168.1.100:18083".to_string(),
 ///     connect_timeout: Duration::from_millis(100), // Longer timeout for network
-///     pool_size: 2,
+///     local_bind: Some("192.168.1.10:0".parse().unwrap()), // Source interface
+///     pool_size: 2.into(),
+///     ..Default::default()
 /// };
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct Configuration {
     /// The host where the RealFlight simulator is listening for connections.
     ///
@@ -438,10 +938,33 @@ pub struct Configuration {
     /// 5 milliseconds
     pub connect_timeout: Duration,
 
+    /// Maximum time to wait when reading from or writing to a simulator connection.
+    ///
+    /// Applied to each pooled stream before it is used. When the simulator stalls
+    /// mid-response, the request fails with [BridgeError::Timeout] instead of
+    /// blocking the bridge indefinitely. The wait for a pooled connection is
+    /// bounded by it as well.
+    ///
+    /// # Default
+    /// `None` (block until the simulator responds)
+    pub io_timeout: Option<Duration>,
+
+    /// Local address to bind outgoing simulator connections to.
+    ///
+    /// Useful on multi-homed machines where RealFlight or a firewall only accepts
+    /// connections from a specific interface. Use port `0` to let the OS pick the
+    /// source port. A bind failure surfaces as [BridgeError::Initialization].
+    ///
+    /// # Default
+    /// `None` (the OS selects the interface and port)
+    pub local_bind: Option<SocketAddr>,
+
     /// Size of the connection pool.
     ///
     /// The connection pool maintains a set of pre-established TCP connections
     /// to improve performance when making frequent requests to the simulator.
+    /// [PoolSize::Adaptive] lets the pool find its own size within bounds; a
+    /// plain number converts to [PoolSize::Fixed] with `.into()`.
     ///
     /// # Performance Impact
     /// * Larger values can improve throughput for frequent state updates
@@ -456,17 +979,526 @@ pub struct Configuration {
     ///
     /// # Default
     /// 1 connection
-    pub pool_size: usize,
+    pub pool_size: PoolSize,
+
+    /// Age after which a pooled connection is closed and replaced instead of
+    /// used.
+    ///
+    /// RealFlight drops connections that sit idle, and a request written to a
+    /// dropped connection fails. Connections the simulator has already closed
+    /// are replaced regardless of age.
+    ///
+    /// # Default
+    /// 10 seconds ([DEFAULT_MAX_IDLE])
+    pub max_idle: Duration,
+
+    /// Construct the bridge without waiting for the simulator.
+    ///
+    /// The connection pool keeps trying to reach RealFlight in the background
+    /// for as long as it takes, so an application can start before the
+    /// simulator does. Requests made in the meantime wait for the first
+    /// connection; [RealFlightLocalBridge::try_initialized] tells whether it
+    /// has been made.
+    ///
+    /// # Default
+    /// `false` (construction fails if the simulator is not reachable)
+    pub lazy_connect: bool,
+
+    /// Whether each request opens a new connection or one is kept open.
+    ///
+    /// See [ConnectionMode].
+    ///
+    /// # Default
+    /// [ConnectionMode::Pooled]
+    pub connection_mode: ConnectionMode,
+
+    /// How often the connection pool checks that the simulator is still
+    /// reachable while the bridge is idle.
+    ///
+    /// On each probe the pool closes one buffered connection and opens a
+    /// fresh one, without sending a request, and records the outcome for
+    /// [RealFlightLocalBridge::simulator_reachable].
+    ///
+    /// # Default
+    /// `None` (no probes)
+    pub probe_interval: Option<Duration>,
+
+    /// Largest simulator response body accepted, in bytes.
+    ///
+    /// A response declaring a larger body fails with [BridgeError::Protocol]
+    /// before anything is allocated for it, so a corrupted Content-Length
+    /// header cannot exhaust memory. State responses are about 4 KB.
+    ///
+    /// # Default
+    /// 256 KiB ([DEFAULT_MAX_RESPONSE_SIZE])
+    pub max_response_size: usize,
+
+    /// Callback invoked after each completed request with the SOAP action, the
+    /// request body and a [SoapResponseSummary].
+    ///
+    /// Useful for capturing the exact traffic a production build exchanges with
+    /// RealFlight. Requests that fail before a response is read are not reported.
+    ///
+    /// ```rust
+    /// use realflight_bridge::Configuration;
+    /// use std::sync::Arc;
+    ///
+    /// let config = Configuration {
+    ///     tap: Some(Arc::new(|action, body, response| {
+    ///         eprintln!("{} ({} bytes) -> {}", action, body.len(), response.status_code);
+    ///     })),
+    ///     ..Default::default()
+    /// };
+    /// ```
+    ///
+    /// # Default
+    /// `None` (no overhead)
+    pub tap: Option<RequestTap>,
+
+    /// Directory to write a transcript of the raw simulator traffic to, for
+    /// protocol debugging.
+    ///
+    /// Each request and its response, headers and body included, go to a
+    /// sequence-numbered, timestamped file; read them back with
+    /// [Transcript::open]. Files are written on a background thread; an
+    /// exchange arriving while
+    /// [TRANSCRIPT_CAPACITY](crate::constants::TRANSCRIPT_CAPACITY) others
+    /// still wait to be written is dropped and counted in
+    /// `Statistics::transcript_dropped_count`. Requests that
+    /// fail before a response is read are not captured. The directory is
+    /// created if needed; use one per bridge, as numbering starts at 1.
+    ///
+    /// ```rust
+    /// use realflight_bridge::Configuration;
+    ///
+    /// let config = Configuration {
+    ///     transcript: Some("rf-transcript".into()),
+    ///     ..Default::default()
+    /// };
+    /// ```
+    ///
+    /// # Default
+    /// `None` (nothing is captured)
+    pub transcript: Option<PathBuf>,
+
+    /// Keep a copy of the most recent [SimulatorState] for
+    /// [RealFlightLocalBridge::last_state].
+    ///
+    /// Costs one clone of the state per successful exchange.
+    ///
+    /// # Default
+    /// `false`
+    pub cache_last_state: bool,
+
+    /// Shortest time between two resets sent to the simulator.
+    ///
+    /// RealFlight is confused by resets in quick succession, and 500
+    /// milliseconds keeps them apart. A `reset_aircraft` within this interval
+    /// of the last one the simulator accepted is not sent and still returns `Ok(())`, so only set
+    /// it if callers do not rely on every reset reaching the simulator.
+    ///
+    /// # Default
+    /// `Duration::ZERO` ([DEFAULT_RESET_DEBOUNCE]), which sends every reset
+    pub reset_debounce: Duration,
+
+    /// Refuse to inject the RealFlight Link controller while the aircraft is
+    /// flying.
+    ///
+    /// Taking over mid-air hands the aircraft to a controller that has not
+    /// seen it yet, which usually ends the flight. With this set,
+    /// [RealFlightBridge::disable_rc] first reads the state and fails with
+    /// [BridgeError::InvalidState] unless the aircraft passes the check.
+    ///
+    /// # Default
+    /// `None` (no check)
+    pub safe_start: Option<SafeStart>,
+
+    /// Hard limits applied to the inputs of every exchange, whatever the
+    /// controller asks for.
+    ///
+    /// The limited inputs are what is sent, so they are what taps,
+    /// transcripts and each state's `previous_inputs` show. Exchanges whose
+    /// inputs were changed are counted in `Statistics::channel_limit_count`.
+    ///
+    /// # Default
+    /// `None` (inputs are sent as given)
+    pub channel_limits: Option<ChannelLimits>,
+}
+
+impl std::fmt::Debug for Configuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Configuration")
+            .field("simulator_host", &self.simulator_host)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("io_timeout", &self.io_timeout)
+            .field("local_bind", &self.local_bind)
+            .field("pool_size", &self.pool_size)
+            .field("max_idle", &self.max_idle)
+            .field("lazy_connect", &self.lazy_connect)
+            .field("connection_mode", &self.connection_mode)
+            .field("probe_interval", &self.probe_interval)
+            .field("max_response_size", &self.max_response_size)
+            .field("tap", &self.tap.as_ref().map(|_| ".."))
+            .field("transcript", &self.transcript)
+            .field("cache_last_state", &self.cache_last_state)
+            .field("reset_debounce", &self.reset_debounce)
+            .field("safe_start", &self.safe_start)
+            .field("channel_limits", &self.channel_limits)
+            .finish()
+    }
 }
 
 impl Default for Configuration {
     fn default() -> Self {
         Configuration {
-            simulator_host: crate::DEFAULT_SIMULATOR_HOST.to_string(),
-            connect_timeout: Duration::from_millis(5),
-            pool_size: 1,
+            simulator_host: DEFAULT_SIMULATOR_HOST.to_string(),
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            io_timeout: None,
+            local_bind: None,
+            pool_size: PoolSize::default(),
+            max_idle: DEFAULT_MAX_IDLE,
+            lazy_connect: false,
+            connection_mode: ConnectionMode::default(),
+            probe_interval: None,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            tap: None,
+            transcript: None,
+            cache_last_state: false,
+            reset_debounce: DEFAULT_RESET_DEBOUNCE,
+            safe_start: None,
+            channel_limits: None,
+        }
+    }
+}
+
+/// Number of connections a pool keeps ready, set with
+/// [Configuration::pool_size] or
+/// [AsyncLocalBridgeBuilder::pool_size](crate::AsyncLocalBridgeBuilder::pool_size).
+///
+/// ```rust
+/// use realflight_bridge::{Configuration, PoolSize};
+///
+/// let config = Configuration {
+///     pool_size: PoolSize::Adaptive { min: 1, max: 4 },
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolSize {
+    /// Always keep this many connections ready.
+    Fixed(usize),
+    /// Start with `min` connections and move between `min` and `max`: the pool
+    /// grows while requests often find it empty, and shrinks back once they
+    /// have stopped doing so for a while. [Statistics]
+    /// reports the current size as `pool_target` and how often requests found
+    /// the pool empty as `pool_miss_rate`.
+    Adaptive { min: usize, max: usize },
+}
+
+impl PoolSize {
+    /// Smallest and largest number of connections kept ready. An adaptive
+    /// pool always keeps at least one.
+    pub(crate) fn bounds(self) -> (usize, usize) {
+        match self {
+            PoolSize::Fixed(size) => (size, size),
+            PoolSize::Adaptive { min, max } => {
+                let min = min.max(1);
+                (min, max.max(min))
+            }
+        }
+    }
+}
+
+impl Default for PoolSize {
+    fn default() -> Self {
+        PoolSize::Fixed(DEFAULT_POOL_SIZE)
+    }
+}
+
+impl From<usize> for PoolSize {
+    fn from(size: usize) -> Self {
+        PoolSize::Fixed(size)
+    }
+}
+
+/// How requests reach the simulator, set with [Configuration::connection_mode]
+/// or
+/// [AsyncLocalBridgeBuilder::connection_mode](crate::AsyncLocalBridgeBuilder::connection_mode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConnectionMode {
+    /// Send every request on a new connection from the pool, as RealFlight
+    /// has historically required.
+    #[default]
+    Pooled,
+    /// Keep one connection open across requests with HTTP keep-alive, for
+    /// RealFlight builds that honor it; this saves opening a connection per
+    /// request. A kept connection the simulator has closed is replaced by a
+    /// new one from the pool, which is then kept instead. If it fails after
+    /// the request was written, only `ExchangeData` is retried on a new
+    /// connection; other requests fail, as the simulator may already have
+    /// applied them.
+    Reuse,
+}
+
+/// When `disable_rc` may inject the RealFlight Link controller, set with
+/// [Configuration::safe_start] or
+/// [AsyncLocalBridgeBuilder::safe_start](crate::AsyncLocalBridgeBuilder::safe_start).
+///
+/// The aircraft must be touching the ground and no faster than
+/// `max_airspeed`. RealFlight only reports the state while the controller is
+/// injected, so a `disable_rc` that finds none injected has nothing to check
+/// and injects it.
+///
+/// ```rust
+/// use realflight_bridge::{Configuration, SafeStart};
+///
+/// let config = Configuration {
+///     safe_start: Some(SafeStart::default()),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SafeStart {
+    /// Fastest airspeed at which the controller is still injected, in
+    /// meters/second. Defaults to [DEFAULT_SAFE_START_MAX_AIRSPEED].
+    pub max_airspeed: f32,
+}
+
+impl SafeStart {
+    /// Fails with [BridgeError::InvalidState] if `state` is not safe to take
+    /// over.
+    pub(crate) fn check(&self, state: &SimulatorState) -> Result<(), BridgeError> {
+        if !state.is_touching_ground {
+            return Err(BridgeError::InvalidState(
+                "Refusing to disable RC while the aircraft is airborne; use force_disable_rc to override"
+                    .into(),
+            ));
+        }
+        let airspeed = units::velocity(state.airspeed);
+        if airspeed > self.max_airspeed {
+            return Err(BridgeError::InvalidState(format!(
+                "Refusing to disable RC at {} m/s airspeed, above the {} m/s safe start limit; use force_disable_rc to override",
+                airspeed, self.max_airspeed
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SafeStart {
+    fn default() -> Self {
+        SafeStart {
+            max_airspeed: DEFAULT_SAFE_START_MAX_AIRSPEED,
+        }
+    }
+}
+
+/// Per-channel bounds on the inputs sent to the simulator, set with
+/// [Configuration::channel_limits] or
+/// [AsyncLocalBridgeBuilder::channel_limits](crate::AsyncLocalBridgeBuilder::channel_limits).
+///
+/// Each channel is clamped to `min..=max`, unless it is locked, in which case
+/// the locked value is sent instead. A NaN input is sent as `min`.
+///
+/// ```rust
+/// use realflight_bridge::{ChannelLimits, Configuration};
+///
+/// let config = Configuration {
+///     // Throttle never above 80%, landing gear kept down
+///     channel_limits: Some(ChannelLimits::default().with_range(2, 0.0, 0.8).with_locked(6, 1.0)),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChannelLimits {
+    /// Lowest value sent on each channel. Defaults to 0.0.
+    pub min: [f32; 12],
+    /// Highest value sent on each channel; wins over `min` if they cross.
+    /// Defaults to 1.0.
+    pub max: [f32; 12],
+    /// Value sent on a channel regardless of its input. Defaults to `None`
+    /// on every channel.
+    pub locked: [Option<f32>; 12],
+}
+
+impl ChannelLimits {
+    /// Limits `channel` to `min..=max`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is 12 or more.
+    pub fn with_range(mut self, channel: usize, min: f32, max: f32) -> Self {
+        self.min[channel] = min;
+        self.max[channel] = max;
+        self
+    }
+
+    /// Always sends `value` on `channel`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is 12 or more.
+    pub fn with_locked(mut self, channel: usize, value: f32) -> Self {
+        self.locked[channel] = Some(value);
+        self
+    }
+
+    /// Applies the limits to `inputs`, returning whether any channel changed.
+    pub fn apply(&self, inputs: &mut ControlInputs) -> bool {
+        let mut engaged = false;
+        for (channel, value) in inputs.channels.iter_mut().enumerate() {
+            let limited = match self.locked[channel] {
+                Some(locked) => locked,
+                None if value.is_nan() => self.min[channel],
+                None => value.max(self.min[channel]).min(self.max[channel]),
+            };
+            if limited.to_bits() != value.to_bits() {
+                *value = limited;
+                engaged = true;
+            }
+        }
+        engaged
+    }
+}
+
+impl Default for ChannelLimits {
+    fn default() -> Self {
+        ChannelLimits {
+            min: [0.0; 12],
+            max: [1.0; 12],
+            locked: [None; 12],
+        }
+    }
+}
+
+/// The state a [SafeStart] check reads from the response to an idle
+/// `ExchangeData`, which selects no channels so an aircraft still under a
+/// controller keeps flying its inputs. The state is not cached.
+///
+/// `None` if the RealFlight Link controller is not injected: RealFlight
+/// faults exchanges until it is, and the aircraft is then still flown by the
+/// RC device, so there is nothing to check.
+pub(crate) fn safe_start_state(
+    response: SoapResponse,
+) -> Result<Option<SimulatorState>, BridgeError> {
+    if response.status_code == 200 {
+        return crate::decoders::decode_simulator_state(&response.body).map(Some);
+    }
+    let error = BridgeError::SoapFault(response.fault_message());
+    match error.fault_kind() {
+        Some(SoapFaultKind::ControllerNotInstantiated) => Ok(None),
+        _ => Err(error),
+    }
+}
+
+/// The inputs to send for `control`, with `limits` applied and counted.
+pub(crate) fn limit_inputs<'a>(
+    limits: Option<&ChannelLimits>,
+    control: &'a ControlInputs,
+    statistics: &StatisticsEngine,
+) -> Cow<'a, ControlInputs> {
+    let Some(limits) = limits else {
+        return Cow::Borrowed(control);
+    };
+    let mut limited = control.clone();
+    if limits.apply(&mut limited) {
+        statistics.increment_channel_limit_count();
+        Cow::Owned(limited)
+    } else {
+        Cow::Borrowed(control)
+    }
+}
+
+/// RealFlight releases with known RealFlight Link behaviour.
+///
+/// Used with [Configuration::for_version] to select a preset that matches the
+/// simulator being driven.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RealFlightVersion {
+    /// RealFlight 9.x (including 9.5)
+    V9,
+    /// RealFlight Evolution
+    Evolution,
+    /// Any other release; uses the library defaults
+    Custom,
+}
+
+impl Configuration {
+    /// Returns a preset [Configuration] for a known RealFlight release.
+    ///
+    /// The presets are documented contracts: the values returned for each version
+    /// only change when the simulator's behaviour requires it. All presets connect
+    /// to [crate::DEFAULT_SIMULATOR_HOST].
+    ///
+    /// | Version     | connection_mode | connect_timeout | io_timeout | pool_size |
+    /// |-------------|-----------------|-----------------|------------|-----------|
+    /// | `V9`        | `Pooled`        | 5ms             | `None`     | 1         |
+    /// | `Evolution` | `Reuse`         | 5ms             | `None`     | 1         |
+    /// | `Custom`    | `Pooled`        | 5ms             | `None`     | 1         |
+    ///
+    /// RealFlight 9.5 closes the connection after every response, so its
+    /// preset opens one per request. Evolution honors HTTP keep-alive, so its
+    /// preset keeps one connection open with [ConnectionMode::Reuse]. `Custom`
+    /// is [Configuration::default].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use realflight_bridge::{Configuration, RealFlightVersion};
+    ///
+    /// let config = Configuration {
+    ///     simulator_host: "192.168.1.100:18083".to_string(),
+    ///     ..Configuration::for_version(RealFlightVersion::Evolution)
+    /// };
+    /// ```
+    pub fn for_version(version: RealFlightVersion) -> Configuration {
+        match version {
+            RealFlightVersion::V9 | RealFlightVersion::Custom => Configuration::default(),
+            RealFlightVersion::Evolution => Configuration {
+                connection_mode: ConnectionMode::Reuse,
+                ..Configuration::default()
+            },
+        }
+    }
+}
+
+/// Drops resets that follow the previous accepted one within an interval.
+pub(crate) struct ResetDebounce {
+    interval: Duration,
+    last: Mutex<Option<Instant>>,
+    clock: Box<dyn Clock>,
+}
+
+impl ResetDebounce {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self::with_clock(interval, SystemClock)
+    }
+
+    pub(crate) fn with_clock(interval: Duration, clock: impl Clock + 'static) -> Self {
+        ResetDebounce {
+            interval,
+            last: Mutex::new(None),
+            clock: Box::new(clock),
         }
     }
+
+    /// Whether a reset may be sent now.
+    pub(crate) fn admit(&self) -> bool {
+        let Some(previous) = *self.last.lock().unwrap() else {
+            return true;
+        };
+        let since = self.clock.now().saturating_duration_since(previous);
+        if since < self.interval {
+            debug!("Reset {:?} after the previous one not sent", since);
+            return false;
+        }
+        true
+    }
+
+    /// Starts the interval from a reset the simulator accepted.
+    pub(crate) fn record(&self) {
+        *self.last.lock().unwrap() = Some(self.clock.now());
+    }
 }
 
 #[cfg(test)]
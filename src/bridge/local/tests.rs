@@ -5,44 +5,27 @@
 //! - `configuration`: Tests for Configuration defaults and validation
 //! - `tcp_integration`: Integration tests using TCP stub server
 
+#![allow(clippy::excessive_precision)]
+
 use std::net::TcpListener;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use approx::assert_relative_eq;
 
 use crate::bridge::RealFlightBridge;
 use crate::soap_client::stub::StubSoapClient;
-use crate::{BridgeError, ControlInputs, DEFAULT_SIMULATOR_HOST};
+use crate::tests::golden::assert_golden;
+use crate::{BridgeError, ControlInputs, DEFAULT_SIMULATOR_HOST, SoapFaultKind};
 
-use super::{Configuration, RealFlightLocalBridge};
+use super::{
+    ChannelLimits, Configuration, ConnectionMode, InitState, PoolSize, RealFlightLocalBridge,
+    RealFlightVersion, ResetDebounce, SafeStart,
+};
 
 // ============================================================================
 // Test Fixtures
 // ============================================================================
 
-mod fixtures {
-    pub const RESET_AIRCRAFT_REQUEST: &str = "\
-        <?xml version='1.0' encoding='UTF-8'?>\
-        <soap:Envelope xmlns:soap='http://schemas.xmlsoap.org/soap/envelope/' \
-        xmlns:xsd='http://www.w3.org/2001/XMLSchema' \
-        xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>\
-        <soap:Body><ResetAircraft></ResetAircraft></soap:Body></soap:Envelope>";
-
-    pub const DISABLE_RC_REQUEST: &str = "\
-        <?xml version='1.0' encoding='UTF-8'?>\
-        <soap:Envelope xmlns:soap='http://schemas.xmlsoap.org/soap/envelope/' \
-        xmlns:xsd='http://www.w3.org/2001/XMLSchema' \
-        xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>\
-        <soap:Body><InjectUAVControllerInterface></InjectUAVControllerInterface></soap:Body></soap:Envelope>";
-
-    pub const ENABLE_RC_REQUEST: &str = "\
-        <?xml version='1.0' encoding='UTF-8'?>\
-        <soap:Envelope xmlns:soap='http://schemas.xmlsoap.org/soap/envelope/' \
-        xmlns:xsd='http://www.w3.org/2001/XMLSchema' \
-        xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>\
-        <soap:Body><RestoreOriginalControllerDevice></RestoreOriginalControllerDevice></soap:Body></soap:Envelope>";
-}
-
 fn stub_bridge(responses: Vec<&str>) -> RealFlightLocalBridge {
     let responses: Vec<String> = responses.into_iter().map(String::from).collect();
     RealFlightLocalBridge::stub(StubSoapClient::new(responses))
@@ -64,7 +47,7 @@ mod configuration_tests {
     #[test]
     fn default_pool_size_is_one() {
         let config = Configuration::default();
-        assert_eq!(config.pool_size, 1);
+        assert_eq!(config.pool_size, PoolSize::Fixed(1));
     }
 
     #[test]
@@ -73,17 +56,139 @@ mod configuration_tests {
         assert_eq!(config.connect_timeout, Duration::from_millis(5));
     }
 
+    #[test]
+    fn default_io_timeout_is_none() {
+        let config = Configuration::default();
+        assert_eq!(config.io_timeout, None);
+    }
+
+    #[test]
+    fn default_local_bind_is_none() {
+        let config = Configuration::default();
+        assert_eq!(config.local_bind, None);
+    }
+
+    #[test]
+    fn default_max_idle_is_10s() {
+        let config = Configuration::default();
+        assert_eq!(config.max_idle, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn default_connects_eagerly() {
+        let config = Configuration::default();
+        assert!(!config.lazy_connect);
+    }
+
+    #[test]
+    fn default_connection_mode_is_pooled() {
+        let config = Configuration::default();
+        assert_eq!(config.connection_mode, ConnectionMode::Pooled);
+    }
+
+    #[test]
+    fn default_does_not_probe() {
+        let config = Configuration::default();
+        assert_eq!(config.probe_interval, None);
+    }
+
+    #[test]
+    fn default_max_response_size_is_256_kib() {
+        let config = Configuration::default();
+        assert_eq!(config.max_response_size, 256 * 1024);
+    }
+
+    #[test]
+    fn default_does_not_cache_last_state() {
+        let config = Configuration::default();
+        assert!(!config.cache_last_state);
+    }
+
+    #[test]
+    fn default_reset_debounce_is_zero() {
+        let config = Configuration::default();
+        assert_eq!(config.reset_debounce, Duration::ZERO);
+    }
+
+    fn assert_preset(version: RealFlightVersion, connection_mode: ConnectionMode) {
+        let config = Configuration::for_version(version);
+        assert_eq!(config.simulator_host, DEFAULT_SIMULATOR_HOST);
+        assert_eq!(config.connection_mode, connection_mode);
+        assert_eq!(config.connect_timeout, Duration::from_millis(5));
+        assert_eq!(config.io_timeout, None);
+        assert_eq!(config.local_bind, None);
+        assert_eq!(config.pool_size, PoolSize::Fixed(1));
+    }
+
+    #[test]
+    fn v9_preset_values() {
+        assert_preset(RealFlightVersion::V9, ConnectionMode::Pooled);
+    }
+
+    #[test]
+    fn evolution_preset_values() {
+        assert_preset(RealFlightVersion::Evolution, ConnectionMode::Reuse);
+    }
+
+    #[test]
+    fn custom_preset_values() {
+        assert_preset(RealFlightVersion::Custom, ConnectionMode::Pooled);
+    }
+
+    #[test]
+    fn presets_differ_between_releases() {
+        let v9 = Configuration::for_version(RealFlightVersion::V9);
+        let evolution = Configuration::for_version(RealFlightVersion::Evolution);
+        assert_ne!(v9.connection_mode, evolution.connection_mode);
+    }
+
     #[test]
     fn configuration_is_cloneable() {
         let config = Configuration {
             simulator_host: "192.168.1.100:18083".to_string(),
             connect_timeout: Duration::from_millis(100),
-            pool_size: 5,
+            io_timeout: Some(Duration::from_millis(250)),
+            local_bind: Some("127.0.0.1:0".parse().unwrap()),
+            pool_size: PoolSize::Fixed(5),
+            max_idle: Duration::from_secs(1),
+            lazy_connect: true,
+            connection_mode: ConnectionMode::Reuse,
+            probe_interval: Some(Duration::from_secs(1)),
+            max_response_size: 1024,
+            tap: Some(std::sync::Arc::new(|_, _, _| {})),
+            transcript: Some("transcript".into()),
+            cache_last_state: true,
+            reset_debounce: Duration::ZERO,
+            safe_start: Some(SafeStart { max_airspeed: 2.0 }),
+            channel_limits: Some(ChannelLimits::default().with_locked(6, 1.0)),
         };
         let cloned = config.clone();
         assert_eq!(cloned.simulator_host, config.simulator_host);
         assert_eq!(cloned.connect_timeout, config.connect_timeout);
+        assert_eq!(cloned.io_timeout, config.io_timeout);
+        assert_eq!(cloned.local_bind, config.local_bind);
         assert_eq!(cloned.pool_size, config.pool_size);
+        assert_eq!(cloned.max_idle, config.max_idle);
+        assert!(cloned.lazy_connect);
+        assert_eq!(cloned.connection_mode, ConnectionMode::Reuse);
+        assert_eq!(cloned.probe_interval, config.probe_interval);
+        assert_eq!(cloned.max_response_size, 1024);
+        assert!(cloned.tap.is_some());
+        assert_eq!(cloned.transcript, config.transcript);
+        assert!(cloned.cache_last_state);
+        assert_eq!(cloned.reset_debounce, Duration::ZERO);
+        assert_eq!(cloned.safe_start, Some(SafeStart { max_airspeed: 2.0 }));
+        assert_eq!(cloned.channel_limits, config.channel_limits);
+    }
+
+    #[test]
+    fn debug_output_hides_tap() {
+        let config = Configuration {
+            tap: Some(std::sync::Arc::new(|_, _, _| {})),
+            ..Default::default()
+        };
+        let output = format!("{:?}", config);
+        assert!(output.contains("tap: Some(\"..\")"), "{}", output);
     }
 }
 
@@ -103,7 +208,7 @@ mod bridge_operations {
 
         let requests = bridge.requests();
         assert_eq!(requests.len(), 1);
-        assert_eq!(requests[0], fixtures::RESET_AIRCRAFT_REQUEST);
+        assert_golden("reset-aircraft.xml", &requests[0]);
     }
 
     #[test]
@@ -115,6 +220,55 @@ mod bridge_operations {
         assert_eq!(stats.request_count, 1);
     }
 
+    #[test]
+    fn default_sends_every_reset() {
+        let bridge = stub_bridge(vec!["reset-aircraft-200"]);
+
+        bridge.reset_aircraft().unwrap();
+        bridge.reset_aircraft().unwrap();
+
+        assert_eq!(bridge.requests().len(), 2);
+    }
+
+    #[test]
+    fn reset_within_debounce_is_not_sent() {
+        let mut bridge = stub_bridge(vec!["reset-aircraft-200"]);
+        bridge.reset_debounce = ResetDebounce::new(Duration::from_millis(500));
+
+        bridge.reset_aircraft().unwrap();
+        bridge.reset_aircraft().unwrap();
+
+        assert_eq!(bridge.requests().len(), 1);
+    }
+
+    #[test]
+    fn reset_after_debounce_is_sent() {
+        let clock = crate::clock::ManualClock::new();
+        let mut bridge = stub_bridge(vec!["reset-aircraft-200"]);
+        bridge.reset_debounce = ResetDebounce::with_clock(Duration::from_millis(20), clock.clone());
+
+        bridge.reset_aircraft().unwrap();
+        clock.advance(Duration::from_millis(19));
+        bridge.reset_aircraft().unwrap();
+        assert_eq!(bridge.requests().len(), 1);
+
+        clock.advance(Duration::from_millis(1));
+        bridge.reset_aircraft().unwrap();
+        assert_eq!(bridge.requests().len(), 2);
+    }
+
+    #[test]
+    fn failed_reset_does_not_start_debounce() {
+        let mut bridge = stub_bridge(vec!["return-data-500", "reset-aircraft-200"]);
+        bridge.reset_debounce =
+            ResetDebounce::with_clock(Duration::from_millis(500), crate::clock::ManualClock::new());
+
+        assert!(bridge.reset_aircraft().is_err());
+        bridge.reset_aircraft().unwrap();
+
+        assert_eq!(bridge.requests().len(), 2);
+    }
+
     #[test]
     fn disable_rc_sends_correct_request() {
         let bridge = stub_bridge(vec!["inject-uav-controller-interface-200"]);
@@ -124,7 +278,7 @@ mod bridge_operations {
 
         let requests = bridge.requests();
         assert_eq!(requests.len(), 1);
-        assert_eq!(requests[0], fixtures::DISABLE_RC_REQUEST);
+        assert_golden("inject-uav-controller-interface.xml", &requests[0]);
     }
 
     #[test]
@@ -149,7 +303,7 @@ mod bridge_operations {
 
         let requests = bridge.requests();
         assert_eq!(requests.len(), 1);
-        assert_eq!(requests[0], fixtures::ENABLE_RC_REQUEST);
+        assert_golden("restore-original-controller-device.xml", &requests[0]);
     }
 
     #[test]
@@ -164,12 +318,400 @@ mod bridge_operations {
             other => panic!("expected SoapFault, got {:?}", other),
         }
     }
+
+    #[test]
+    fn soap_faults_are_classified() {
+        let cases = [
+            (
+                "inject-uav-controller-interface-500",
+                SoapFaultKind::PreexistingController,
+            ),
+            (
+                "restore-original-controller-device-500",
+                SoapFaultKind::NoOriginalController,
+            ),
+            ("return-data-500", SoapFaultKind::ControllerNotInstantiated),
+        ];
+        for (response, kind) in cases {
+            let error = stub_bridge(vec![response]).reset_aircraft().unwrap_err();
+            assert_eq!(error.fault_kind(), Some(kind), "{}", response);
+        }
+    }
+
+    #[test]
+    fn non_fault_errors_are_not_classified() {
+        assert_eq!(SoapFaultKind::classify("Unexpected"), SoapFaultKind::Other);
+        assert_eq!(
+            BridgeError::Timeout(Duration::from_secs(1)).fault_kind(),
+            None
+        );
+    }
+}
+
+// ============================================================================
+// RC Recovery Tests
+// ============================================================================
+
+mod rc_recovery {
+    use super::*;
+
+    fn actions(bridge: &RealFlightLocalBridge) -> Vec<&'static str> {
+        bridge
+            .requests()
+            .iter()
+            .map(|r| {
+                if r.contains("InjectUAVControllerInterface") {
+                    "inject"
+                } else if r.contains("RestoreOriginalControllerDevice") {
+                    "restore"
+                } else {
+                    "other"
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn disables_rc_without_recovery() {
+        let bridge = stub_bridge(vec!["inject-uav-controller-interface-200"]);
+
+        assert!(bridge.ensure_rc_disabled().is_ok());
+        assert_eq!(actions(&bridge), vec!["inject"]);
+    }
+
+    #[test]
+    fn recovers_from_preexisting_controller() {
+        let bridge = stub_bridge(vec![
+            "inject-uav-controller-interface-500",
+            "restore-original-controller-device-200",
+            "inject-uav-controller-interface-200",
+        ]);
+
+        assert!(bridge.ensure_rc_disabled().is_ok());
+        assert_eq!(actions(&bridge), vec!["inject", "restore", "inject"]);
+    }
+
+    #[test]
+    fn gives_up_on_persistent_fault() {
+        let bridge = stub_bridge(vec!["inject-uav-controller-interface-500"]);
+
+        let error = bridge.ensure_rc_disabled().unwrap_err();
+        assert_eq!(
+            error.fault_kind(),
+            Some(SoapFaultKind::PreexistingController)
+        );
+        assert_eq!(
+            actions(&bridge),
+            vec![
+                "inject", "restore", "inject", "restore", "inject", "restore", "inject"
+            ]
+        );
+    }
+
+    #[test]
+    fn does_not_retry_other_faults() {
+        let bridge = stub_bridge(vec!["return-data-500"]);
+
+        let error = bridge.ensure_rc_disabled().unwrap_err();
+        assert_eq!(
+            error.fault_kind(),
+            Some(SoapFaultKind::ControllerNotInstantiated)
+        );
+        assert_eq!(actions(&bridge), vec!["inject"]);
+    }
 }
 
 // ============================================================================
 // Exchange Data Tests
 // ============================================================================
 
+mod safe_start {
+    use super::*;
+
+    fn safe_bridge(responses: Vec<&str>, safe_start: SafeStart) -> RealFlightLocalBridge {
+        let mut bridge = stub_bridge(responses);
+        bridge.safe_start = Some(safe_start);
+        bridge
+    }
+
+    fn assert_refused(bridge: &RealFlightLocalBridge) {
+        match bridge.disable_rc() {
+            Err(BridgeError::InvalidState(_)) => {}
+            other => panic!("expected InvalidState, got {:?}", other),
+        }
+        let requests = bridge.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("ExchangeData"));
+    }
+
+    #[test]
+    fn defaults_to_1_meter_per_second() {
+        assert_eq!(SafeStart::default().max_airspeed, 1.0);
+        assert_eq!(Configuration::default().safe_start, None);
+    }
+
+    #[test]
+    fn injects_when_grounded() {
+        let bridge = safe_bridge(
+            vec![
+                "return-data-grounded-200",
+                "inject-uav-controller-interface-200",
+            ],
+            SafeStart::default(),
+        );
+
+        bridge.disable_rc().unwrap();
+
+        let requests = bridge.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("ExchangeData"));
+        assert_golden("inject-uav-controller-interface.xml", &requests[1]);
+    }
+
+    #[test]
+    fn refuses_when_airborne() {
+        let bridge = safe_bridge(
+            vec![
+                "return-data-airborne-200",
+                "inject-uav-controller-interface-200",
+            ],
+            SafeStart::default(),
+        );
+
+        assert_refused(&bridge);
+    }
+
+    #[test]
+    fn refuses_when_off_the_ground_though_slow() {
+        let bridge = safe_bridge(
+            vec!["return-data-200", "inject-uav-controller-interface-200"],
+            SafeStart::default(),
+        );
+
+        assert_refused(&bridge);
+    }
+
+    #[test]
+    fn refuses_above_max_airspeed() {
+        let bridge = safe_bridge(
+            vec![
+                "return-data-grounded-200",
+                "inject-uav-controller-interface-200",
+            ],
+            SafeStart { max_airspeed: 0.1 },
+        );
+
+        assert_refused(&bridge);
+    }
+
+    #[test]
+    fn force_skips_check() {
+        let bridge = safe_bridge(
+            vec!["inject-uav-controller-interface-200"],
+            SafeStart::default(),
+        );
+
+        bridge.force_disable_rc().unwrap();
+
+        let requests = bridge.requests();
+        assert_eq!(requests.len(), 1);
+        assert_golden("inject-uav-controller-interface.xml", &requests[0]);
+    }
+
+    #[test]
+    fn injects_when_controller_not_instantiated() {
+        let bridge = safe_bridge(
+            vec!["return-data-500", "inject-uav-controller-interface-200"],
+            SafeStart::default(),
+        );
+
+        bridge.disable_rc().unwrap();
+
+        let requests = bridge.requests();
+        assert_eq!(requests.len(), 2);
+        assert_golden("inject-uav-controller-interface.xml", &requests[1]);
+    }
+
+    #[test]
+    fn failed_state_read_is_returned() {
+        // Any fault but a missing controller
+        let bridge = safe_bridge(
+            vec![
+                "inject-uav-controller-interface-500",
+                "inject-uav-controller-interface-200",
+            ],
+            SafeStart::default(),
+        );
+
+        assert!(matches!(
+            bridge.disable_rc(),
+            Err(BridgeError::SoapFault(_))
+        ));
+        assert_eq!(bridge.requests().len(), 1);
+    }
+
+    #[test]
+    fn state_read_leaves_inputs_alone() {
+        let mut bridge = safe_bridge(
+            vec![
+                "return-data-grounded-200",
+                "inject-uav-controller-interface-200",
+            ],
+            SafeStart::default(),
+        );
+        bridge.channel_limits = Some(ChannelLimits::default().with_locked(2, 1.0));
+        bridge.cache_last_state = true;
+
+        bridge.disable_rc().unwrap();
+
+        let idle = crate::encoders::encode_idle_inputs();
+        assert!(bridge.requests()[0].contains(&idle));
+        assert_eq!(bridge.last_state(), None);
+        assert_eq!(bridge.statistics().channel_limit_count, 0);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn injects_on_fresh_simulator() {
+        use crate::mock::MockSimulator;
+
+        let simulator = MockSimulator::start(0).unwrap();
+        let bridge = RealFlightLocalBridge::with_configuration(&Configuration {
+            simulator_host: simulator.host(),
+            safe_start: Some(SafeStart::default()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        bridge.disable_rc().unwrap();
+
+        assert!(simulator.controller_injected());
+    }
+}
+
+mod channel_limits {
+    use super::*;
+    use crate::encode_control_inputs;
+
+    fn inputs(value: f32) -> ControlInputs {
+        ControlInputs {
+            channels: [value; 12],
+        }
+    }
+
+    fn limited_bridge(responses: Vec<&str>, limits: ChannelLimits) -> RealFlightLocalBridge {
+        let mut bridge = stub_bridge(responses);
+        bridge.channel_limits = Some(limits);
+        bridge
+    }
+
+    #[test]
+    fn default_limits_pass_valid_inputs() {
+        let mut control = inputs(0.5);
+        control.channels[0] = 0.0;
+        control.channels[1] = 1.0;
+
+        assert!(!ChannelLimits::default().apply(&mut control.clone()));
+        assert_eq!(Configuration::default().channel_limits, None);
+    }
+
+    #[test]
+    fn clamps_to_range() {
+        let limits = ChannelLimits::default().with_range(2, 0.1, 0.8);
+        let mut high = inputs(1.0);
+        let mut low = inputs(0.0);
+
+        assert!(limits.apply(&mut high));
+        assert!(limits.apply(&mut low));
+
+        assert_eq!(high.channels[2], 0.8);
+        assert_eq!(low.channels[2], 0.1);
+        assert_eq!(high.channels[3], 1.0);
+        assert_eq!(low.channels[3], 0.0);
+    }
+
+    #[test]
+    fn clamps_out_of_range_inputs_by_default() {
+        let mut control = inputs(1.5);
+        control.channels[0] = -0.5;
+
+        assert!(ChannelLimits::default().apply(&mut control));
+
+        assert_eq!(control.channels[0], 0.0);
+        assert!(control.channels[1..].iter().all(|&value| value == 1.0));
+    }
+
+    #[test]
+    fn locked_channel_ignores_input() {
+        let limits = ChannelLimits::default().with_locked(6, 1.0);
+        let mut control = inputs(0.0);
+
+        assert!(limits.apply(&mut control));
+        assert_eq!(control.channels[6], 1.0);
+
+        // Already at the locked value
+        assert!(!limits.apply(&mut control));
+    }
+
+    #[test]
+    fn nan_is_sent_as_min() {
+        let limits = ChannelLimits::default().with_range(2, 0.2, 0.8);
+        let mut control = inputs(0.5);
+        control.channels[2] = f32::NAN;
+
+        assert!(limits.apply(&mut control));
+        assert_eq!(control.channels[2], 0.2);
+    }
+
+    #[test]
+    fn max_wins_when_range_is_crossed() {
+        let limits = ChannelLimits::default().with_range(0, 0.9, 0.1);
+        let mut control = inputs(0.5);
+
+        limits.apply(&mut control);
+        assert_eq!(control.channels[0], 0.1);
+    }
+
+    #[test]
+    fn exchange_sends_limited_inputs() {
+        let limits = ChannelLimits::default()
+            .with_range(2, 0.0, 0.8)
+            .with_locked(6, 1.0);
+        let bridge = limited_bridge(vec!["return-data-200"], limits);
+
+        bridge.exchange_data(&inputs(0.9)).unwrap();
+
+        let mut expected = inputs(0.9);
+        expected.channels[2] = 0.8;
+        expected.channels[6] = 1.0;
+        assert!(bridge.requests()[0].contains(&encode_control_inputs(&expected)));
+    }
+
+    #[test]
+    fn statistics_count_exchanges_that_engaged_limits() {
+        let limits = ChannelLimits::default().with_range(2, 0.0, 0.8);
+        let bridge = limited_bridge(vec!["return-data-200"], limits);
+
+        bridge.exchange_data(&inputs(1.0)).unwrap();
+        bridge.exchange_data(&inputs(0.5)).unwrap();
+        bridge.exchange_data(&inputs(0.9)).unwrap();
+
+        let stats = bridge.statistics();
+        assert_eq!(stats.request_count, 3);
+        assert_eq!(stats.channel_limit_count, 2);
+    }
+
+    #[test]
+    fn unlimited_bridge_counts_nothing() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+
+        bridge.exchange_data(&inputs(1.5)).unwrap();
+
+        assert!(bridge.requests()[0].contains(&encode_control_inputs(&inputs(1.5))));
+        assert_eq!(bridge.statistics().channel_limit_count, 0);
+    }
+}
+
 mod exchange_data {
     use super::*;
 
@@ -401,40 +943,552 @@ mod exchange_data {
             assert_relative_eq!(state.current_physics_time.get::<second>(), 72263.411813672);
         }
     }
-}
 
-// ============================================================================
-// TCP Integration Tests
-// ============================================================================
+    #[test]
+    fn last_state_readable_from_another_thread() {
+        let mut bridge = stub_bridge(vec!["return-data-200"]);
+        bridge.cache_last_state = true;
+        assert!(bridge.last_state().is_none());
+
+        let state = bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+        let cached = std::thread::scope(|s| s.spawn(|| bridge.last_state()).join().unwrap())
+            .expect("state should be cached");
+        assert_eq!(cached.airspeed, state.airspeed);
+        assert_eq!(cached.altitude_agl, state.altitude_agl);
+        assert_eq!(
+            cached.flight_axis_controller_is_active,
+            state.flight_axis_controller_is_active
+        );
+    }
 
-mod tcp_integration {
-    use super::*;
-    use crate::tests::soap_stub::Server;
+    #[test]
+    fn last_state_not_cached_by_default() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
 
-    fn get_available_port() -> u16 {
-        TcpListener::bind("127.0.0.1:0")
-            .unwrap()
-            .local_addr()
-            .unwrap()
-            .port()
+        bridge.exchange_data(&ControlInputs::default()).unwrap();
+        assert!(bridge.last_state().is_none());
     }
 
-    fn create_bridge(port: u16) -> Result<RealFlightLocalBridge, BridgeError> {
-        let config = Configuration {
-            simulator_host: format!("127.0.0.1:{}", port),
-            connect_timeout: Duration::from_millis(1000),
-            pool_size: 1,
-        };
-        RealFlightLocalBridge::with_configuration(&config)
+    #[test]
+    fn failed_exchange_keeps_previous_state() {
+        let mut bridge = stub_bridge(vec!["return-data-200", "return-data-500"]);
+        bridge.cache_last_state = true;
+
+        let state = bridge.exchange_data(&ControlInputs::default()).unwrap();
+        assert!(bridge.exchange_data(&ControlInputs::default()).is_err());
+        assert_eq!(bridge.last_state(), Some(state));
+    }
+
+    fn delayed_bridge(delay: Duration) -> RealFlightLocalBridge {
+        let stub = StubSoapClient::new(vec!["return-data-200".to_string()]).with_delay(delay);
+        RealFlightLocalBridge::stub(stub)
     }
 
     #[test]
-    fn tcp_client_sends_and_receives() {
-        let port = get_available_port();
-        let server = Server::new(port, vec!["reset-aircraft-200".to_string()]);
-        let bridge = create_bridge(port).unwrap();
+    fn deadline_met_returns_state() {
+        let bridge = delayed_bridge(Duration::from_millis(5));
+        let deadline = Instant::now() + Duration::from_millis(500);
 
-        let result = bridge.reset_aircraft();
+        let result = bridge.exchange_data_deadline(&ControlInputs::default(), deadline);
+        assert!(result.is_ok(), "expected Ok: {:?}", result);
+    }
+
+    #[test]
+    fn deadline_missed_returns_timeout() {
+        let bridge = delayed_bridge(Duration::from_millis(500));
+        let start = Instant::now();
+
+        let result = bridge
+            .exchange_data_deadline(&ControlInputs::default(), start + Duration::from_millis(10));
+        match result {
+            Err(BridgeError::Timeout(budget)) => assert!(budget <= Duration::from_millis(10)),
+            other => panic!("expected Timeout, got {:?}", other),
+        }
+        assert!(
+            start.elapsed() < Duration::from_millis(250),
+            "took {:?}",
+            start.elapsed()
+        );
+    }
+
+    #[test]
+    fn expired_deadline_returns_timeout() {
+        let bridge = delayed_bridge(Duration::from_millis(1));
+
+        let result = bridge.exchange_data_deadline(&ControlInputs::default(), Instant::now());
+        assert!(matches!(result, Err(BridgeError::Timeout(_))));
+    }
+
+    #[test]
+    fn try_exchange_data_returns_state() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+
+        let result = bridge.try_exchange_data(&ControlInputs::default());
+        assert!(result.is_ok(), "expected Ok: {:?}", result);
+        assert_eq!(bridge.statistics().request_count, 1);
+    }
+}
+
+// ============================================================================
+// Shutdown Tests
+// ============================================================================
+
+mod shutdown {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn logged_bridge(response: &str) -> (RealFlightLocalBridge, Arc<Mutex<Vec<String>>>) {
+        let client = StubSoapClient::new(vec![response.to_string()]);
+        let log = client.request_log();
+        (RealFlightLocalBridge::stub(client), log)
+    }
+
+    fn restore_count(log: &Mutex<Vec<String>>) -> usize {
+        log.lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.contains("RestoreOriginalControllerDevice"))
+            .count()
+    }
+
+    #[test]
+    fn restores_rc_when_disabled() {
+        let (bridge, log) = logged_bridge("inject-uav-controller-interface-200");
+
+        bridge.disable_rc().unwrap();
+        bridge.shutdown().unwrap();
+
+        assert_eq!(restore_count(&log), 1);
+    }
+
+    #[test]
+    fn skips_restore_after_manual_enable() {
+        let (bridge, log) = logged_bridge("inject-uav-controller-interface-200");
+
+        bridge.disable_rc().unwrap();
+        bridge.enable_rc().unwrap();
+        bridge.shutdown().unwrap();
+
+        assert_eq!(restore_count(&log), 1);
+    }
+
+    #[test]
+    fn sends_nothing_when_rc_never_disabled() {
+        let (bridge, log) = logged_bridge("return-data-200");
+
+        bridge.shutdown().unwrap();
+
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn guard_restores_rc_on_drop() {
+        let (bridge, log) = logged_bridge("inject-uav-controller-interface-200");
+
+        let guard = bridge.disable_rc_guarded().unwrap();
+        guard.reset_aircraft().unwrap();
+        assert_eq!(restore_count(&log), 0);
+        drop(guard);
+
+        assert_eq!(restore_count(&log), 1);
+    }
+
+    #[test]
+    fn guard_skips_restore_after_manual_enable() {
+        let (bridge, log) = logged_bridge("inject-uav-controller-interface-200");
+
+        let guard = bridge.disable_rc_guarded().unwrap();
+        guard.enable_rc().unwrap();
+        drop(guard);
+        bridge.shutdown().unwrap();
+
+        assert_eq!(restore_count(&log), 1);
+    }
+
+    #[test]
+    fn guard_not_returned_when_disable_fails() {
+        let (bridge, log) = logged_bridge("inject-uav-controller-interface-500");
+
+        assert!(bridge.disable_rc_guarded().is_err());
+        bridge.shutdown().unwrap();
+
+        assert_eq!(restore_count(&log), 0);
+    }
+}
+
+// ============================================================================
+// Wire Format Tests
+// ============================================================================
+
+mod wire_format {
+    use super::*;
+
+    fn inputs(channel: impl Fn(usize) -> f32) -> ControlInputs {
+        let mut inputs = ControlInputs::default();
+        for (i, value) in inputs.channels.iter_mut().enumerate() {
+            *value = channel(i);
+        }
+        inputs
+    }
+
+    fn exchange_request(inputs: &ControlInputs) -> String {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+        bridge.exchange_data(inputs).unwrap();
+        bridge.requests().remove(0)
+    }
+
+    #[test]
+    fn exchange_data_all_zero() {
+        let request = exchange_request(&ControlInputs::default());
+        assert_golden("exchange-data-zero.xml", &request);
+    }
+
+    #[test]
+    fn exchange_data_all_one() {
+        let request = exchange_request(&inputs(|_| 1.0));
+        assert_golden("exchange-data-one.xml", &request);
+    }
+
+    #[test]
+    fn exchange_data_neutral_sticks() {
+        let request = exchange_request(&inputs(|i| if matches!(i, 0 | 1 | 3) { 0.5 } else { 0.0 }));
+        assert_golden("exchange-data-neutral.xml", &request);
+    }
+
+    #[test]
+    fn exchange_data_ramp() {
+        let request = exchange_request(&inputs(|i| i as f32 / 11.0));
+        assert_golden("exchange-data-ramp.xml", &request);
+    }
+
+    #[test]
+    fn exchange_data_precision_edges() {
+        let values = [
+            0.1,
+            1.0 / 3.0,
+            1.0e-7,
+            f32::MIN_POSITIVE,
+            f32::EPSILON,
+            1.0 - f32::EPSILON,
+            0.999_999_9,
+            -0.0,
+            0.300_000_01,
+            123_456.79,
+            1.5,
+            -0.25,
+        ];
+        let request = exchange_request(&inputs(|i| values[i]));
+        assert_golden("exchange-data-precision.xml", &request);
+    }
+
+    #[test]
+    fn ping_selects_no_channels() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+        bridge.ping().unwrap();
+        assert_golden("exchange-data-idle.xml", &bridge.requests()[0]);
+    }
+}
+
+// ============================================================================
+// Control Loop Tests
+// ============================================================================
+
+mod control_loop {
+    use super::*;
+    use crate::ControlOutcome;
+    use crate::clock::ManualClock;
+    use crate::diagnostics::{PhysicsWatchdog, WatchdogConfig};
+
+    #[test]
+    fn runs_until_stopped_at_rate() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+        let clock = ManualClock::new();
+        let mut calls = 0;
+
+        let report = bridge
+            .run_control_loop_with_clock(200.0, &clock, |_| {
+                calls += 1;
+                if calls == 100 {
+                    ControlOutcome::Stop
+                } else {
+                    ControlOutcome::Continue(ControlInputs::default())
+                }
+            })
+            .unwrap();
+
+        assert_eq!(report.iterations, 100);
+        assert_eq!(bridge.statistics().request_count, 100);
+        // 99 sleeps of one period separate 100 iterations
+        let period = Duration::from_secs_f32(1.0 / 200.0);
+        assert_eq!(report.elapsed, period * 99);
+        assert_eq!(clock.elapsed(), report.elapsed);
+        assert_eq!(report.max_jitter, Duration::ZERO);
+        assert_eq!(report.overruns, 0);
+    }
+
+    #[test]
+    fn runs_on_system_clock() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+        let mut calls = 0;
+
+        let report = bridge
+            .run_control_loop(200.0, |_| {
+                calls += 1;
+                if calls == 3 {
+                    ControlOutcome::Stop
+                } else {
+                    ControlOutcome::Continue(ControlInputs::default())
+                }
+            })
+            .unwrap();
+
+        assert_eq!(report.iterations, 3);
+        assert!(
+            report.elapsed >= Duration::from_millis(10),
+            "{:?}",
+            report.elapsed
+        );
+    }
+
+    #[test]
+    fn overrun_restarts_schedule() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+        let clock = ManualClock::new();
+        let period = Duration::from_secs_f32(1.0 / 100.0);
+        let mut calls = 0;
+
+        let report = bridge
+            .run_control_loop_with_clock(100.0, &clock, |_| {
+                calls += 1;
+                if calls == 2 {
+                    // The second iteration takes two and a half periods
+                    clock.advance(period * 5 / 2);
+                }
+                if calls == 4 {
+                    ControlOutcome::Stop
+                } else {
+                    ControlOutcome::Continue(ControlInputs::default())
+                }
+            })
+            .unwrap();
+
+        assert_eq!(report.iterations, 4);
+        assert_eq!(report.overruns, 1);
+        // The third iteration starts right after the overrun instead of
+        // bursting through the missed periods
+        assert_eq!(report.elapsed, period * 2 + period * 5 / 2);
+        assert_eq!(report.max_jitter, Duration::ZERO);
+    }
+
+    #[test]
+    fn stop_on_first_iteration() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+
+        let report = bridge
+            .run_control_loop(200.0, |_| ControlOutcome::Stop)
+            .unwrap();
+
+        assert_eq!(report.iterations, 1);
+        assert_eq!(report.overruns, 0);
+    }
+
+    #[test]
+    fn sends_inputs_returned_by_closure() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+        let mut inputs = ControlInputs::default();
+        inputs.channels[2] = 1.0;
+        let mut first = true;
+
+        bridge
+            .run_control_loop(200.0, |_| {
+                if std::mem::take(&mut first) {
+                    ControlOutcome::Continue(inputs.clone())
+                } else {
+                    ControlOutcome::Stop
+                }
+            })
+            .unwrap();
+
+        let requests = bridge.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains("<item>1</item>"));
+    }
+
+    #[test]
+    fn aborts_on_exchange_error() {
+        let bridge = stub_bridge(vec!["return-data-500"]);
+
+        let result =
+            bridge.run_control_loop(
+                200.0,
+                |_| ControlOutcome::Continue(ControlInputs::default()),
+            );
+
+        assert!(matches!(result, Err(BridgeError::SoapFault(_))));
+        assert_eq!(bridge.requests().len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "rate_hz must be positive")]
+    fn rejects_zero_rate() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+        let _ = bridge.run_control_loop(0.0, |_| ControlOutcome::Stop);
+    }
+
+    fn watchdog(clock: &ManualClock) -> PhysicsWatchdog<ManualClock> {
+        PhysicsWatchdog::with_clock(
+            WatchdogConfig {
+                stall_after: Duration::from_millis(30),
+                ..Default::default()
+            },
+            clock.clone(),
+        )
+    }
+
+    #[test]
+    fn watchdog_reports_frozen_physics() {
+        // Every exchange returns the same physics time
+        let bridge = stub_bridge(vec!["return-data-200"]);
+        let clock = ManualClock::new();
+        let mut watchdog = watchdog(&clock);
+        let mut seen = Vec::new();
+
+        let report = bridge
+            .run_control_loop_with_watchdog(100.0, &mut watchdog, |_, health| {
+                seen.push(health.is_ok());
+                if seen.len() == 6 {
+                    ControlOutcome::Stop
+                } else {
+                    ControlOutcome::Continue(ControlInputs::default())
+                }
+            })
+            .unwrap();
+
+        assert_eq!(seen, [true, true, true, false, false, false]);
+        assert_eq!(report.physics_stalls, 1);
+        assert_eq!(report.physics_jumps, 0);
+        assert_eq!(report.iterations, 6);
+        assert_eq!(clock.elapsed(), report.elapsed);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn watchdog_reports_reset() {
+        use crate::diagnostics::PhysicsHealth;
+        use crate::mock::MockSimulator;
+
+        let simulator = MockSimulator::start(0).unwrap();
+        simulator.set_controller_injected(true);
+        let bridge = RealFlightLocalBridge::with_configuration(&Configuration {
+            simulator_host: simulator.host(),
+            ..Default::default()
+        })
+        .unwrap();
+        let clock = ManualClock::new();
+        let mut watchdog = watchdog(&clock);
+        let mut seen = Vec::new();
+
+        let report = bridge
+            .run_control_loop_with_watchdog(100.0, &mut watchdog, |_, health| {
+                seen.push(*health);
+                if seen.len() == 5 {
+                    bridge.reset_aircraft().unwrap();
+                }
+                if seen.len() == 8 {
+                    ControlOutcome::Stop
+                } else {
+                    ControlOutcome::Continue(ControlInputs::default())
+                }
+            })
+            .unwrap();
+
+        assert!(
+            matches!(seen[5], PhysicsHealth::Jumped { by } if by < 0.0),
+            "{:?}",
+            seen
+        );
+        assert!(seen[6..].iter().all(PhysicsHealth::is_ok), "{:?}", seen);
+        assert_eq!(report.physics_jumps, 1);
+        assert_eq!(report.physics_stalls, 0);
+    }
+}
+
+// ============================================================================
+// Shared Bridge Tests
+// ============================================================================
+
+mod shared_bridge {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn exchange<B: RealFlightBridge>(bridge: B) -> Result<crate::SimulatorState, BridgeError> {
+        bridge.exchange_data(&ControlInputs::default())
+    }
+
+    #[test]
+    fn arc_bridge_shared_across_threads() {
+        let bridge = Arc::new(stub_bridge(vec!["return-data-200"]));
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let bridge = Arc::clone(&bridge);
+                thread::spawn(move || exchange(bridge))
+            })
+            .collect();
+
+        for handle in handles {
+            let state = handle.join().unwrap().unwrap();
+            assert_eq!(state.current_physics_speed_multiplier, 1.0);
+        }
+        assert_eq!(bridge.statistics().request_count, 2);
+    }
+
+    #[test]
+    fn reference_and_box_forward_to_bridge() {
+        let bridge = stub_bridge(vec!["return-data-200"]);
+        assert!(exchange(&bridge).is_ok());
+
+        let boxed: Box<dyn RealFlightBridge> = Box::new(bridge);
+        assert!(exchange(boxed).is_ok());
+    }
+}
+
+// ============================================================================
+// TCP Integration Tests
+// ============================================================================
+
+mod tcp_integration {
+    use super::*;
+    use crate::tests::soap_stub::Server;
+    use std::sync::{Arc, Mutex};
+
+    fn get_available_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn create_bridge(port: u16) -> Result<RealFlightLocalBridge, BridgeError> {
+        let config = Configuration {
+            simulator_host: format!("127.0.0.1:{}", port),
+            connect_timeout: Duration::from_millis(1000),
+            pool_size: PoolSize::Fixed(1),
+            ..Default::default()
+        };
+        RealFlightLocalBridge::with_configuration(&config)
+    }
+
+    #[test]
+    fn tcp_client_sends_and_receives() {
+        let port = get_available_port();
+        let server = Server::new(port, vec!["reset-aircraft-200".to_string()]);
+        let bridge = create_bridge(port).unwrap();
+
+        let result = bridge.reset_aircraft();
         assert!(result.is_ok(), "expected Ok: {:?}", result);
 
         let stats = bridge.statistics();
@@ -443,4 +1497,502 @@ mod tcp_integration {
 
         drop(server);
     }
+
+    #[test]
+    fn skips_100_continue_before_state() {
+        let port = get_available_port();
+        let server = Server::with_continue(port, vec!["return-data-200".to_string()]);
+        let bridge = create_bridge(port).unwrap();
+
+        let result = bridge.exchange_data(&ControlInputs::default());
+        assert!(result.is_ok(), "expected Ok: {:?}", result);
+        assert_eq!(result.unwrap().current_physics_speed_multiplier, 1.0);
+
+        drop(server);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn decodes_gzipped_state() {
+        let port = get_available_port();
+        let server = Server::gzip(port, vec!["return-data-200".to_string()]);
+        let bridge = create_bridge(port).unwrap();
+
+        let result = bridge.exchange_data(&ControlInputs::default());
+        assert!(result.is_ok(), "expected Ok: {:?}", result);
+        assert_eq!(result.unwrap().current_physics_speed_multiplier, 1.0);
+
+        drop(server);
+    }
+
+    #[cfg(feature = "raw-soap")]
+    #[test]
+    fn raw_client_sends_custom_action() {
+        use crate::{SoapClient, TcpSoapClient};
+
+        let port = get_available_port();
+        let server = Server::new(port, vec!["reset-aircraft-200".to_string()]);
+        let config = Configuration {
+            simulator_host: format!("127.0.0.1:{}", port),
+            connect_timeout: Duration::from_millis(1000),
+            ..Default::default()
+        };
+        let client = TcpSoapClient::connect(&config).unwrap();
+
+        let response = client
+            .send_action("QueryBetaFeature", "<name>wind</name>")
+            .unwrap();
+
+        assert_eq!(response.status_code, 200);
+        assert!(response.body.contains("ResetAircraftResponse"));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(
+            requests[0].contains("<QueryBetaFeature><name>wind</name></QueryBetaFeature>"),
+            "unexpected request: {}",
+            requests[0]
+        );
+
+        drop(server);
+    }
+
+    #[test]
+    fn replaces_connection_closed_by_simulator() {
+        let port = get_available_port();
+        let listener = TcpListener::bind(format!("127.0.0.1:{}", port)).unwrap();
+        let config = Configuration {
+            simulator_host: format!("127.0.0.1:{}", port),
+            connect_timeout: Duration::from_millis(100),
+            pool_size: PoolSize::Fixed(1),
+            ..Default::default()
+        };
+        let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+
+        // The simulator hangs up on the waiting connection, then comes back
+        drop(listener.accept().unwrap());
+        drop(listener);
+        let server = Server::new(port, vec!["reset-aircraft-200".to_string()]);
+
+        let result = bridge.reset_aircraft();
+        assert!(result.is_ok(), "expected Ok: {:?}", result);
+        assert_eq!(server.request_count(), 1);
+
+        drop(server);
+    }
+
+    fn create_reusing_bridge(port: u16) -> RealFlightLocalBridge {
+        let config = Configuration {
+            simulator_host: format!("127.0.0.1:{}", port),
+            connect_timeout: Duration::from_millis(1000),
+            pool_size: PoolSize::Fixed(1),
+            connection_mode: ConnectionMode::Reuse,
+            reset_debounce: Duration::ZERO,
+            ..Default::default()
+        };
+        RealFlightLocalBridge::with_configuration(&config).unwrap()
+    }
+
+    #[test]
+    fn reuse_sends_requests_on_one_connection() {
+        let port = get_available_port();
+        let server = Server::keep_alive(port, vec!["reset-aircraft-200".to_string(); 3]);
+        let bridge = create_reusing_bridge(port);
+
+        for _ in 0..3 {
+            let result = bridge.reset_aircraft();
+            assert!(result.is_ok(), "expected Ok: {:?}", result);
+        }
+        assert_eq!(server.request_count(), 3);
+        assert_eq!(server.connection_count(), 1);
+        assert_eq!(bridge.statistics().request_count, 3);
+
+        drop(bridge);
+        drop(server);
+    }
+
+    #[test]
+    fn reuse_falls_back_when_simulator_closes_connection() {
+        let port = get_available_port();
+        // Closes each connection after answering it
+        let server = Server::new(port, vec!["reset-aircraft-200".to_string(); 2]);
+        let bridge = create_reusing_bridge(port);
+
+        for _ in 0..2 {
+            let result = bridge.reset_aircraft();
+            assert!(result.is_ok(), "expected Ok: {:?}", result);
+            // Lets the hang-up arrive, so the kept connection is seen closed
+            // before the next request is written
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(server.request_count(), 2);
+        assert_eq!(server.connection_count(), 2);
+        assert_eq!(bridge.statistics().request_count, 2);
+
+        drop(server);
+    }
+
+    #[test]
+    fn lazy_bridge_connects_once_simulator_starts() {
+        let port = get_available_port();
+        let config = Configuration {
+            simulator_host: format!("127.0.0.1:{}", port),
+            connect_timeout: Duration::from_millis(100),
+            pool_size: PoolSize::Fixed(1),
+            lazy_connect: true,
+            ..Default::default()
+        };
+        let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+        assert_eq!(bridge.try_initialized(), InitState::Pending);
+
+        // The simulator starts after the bridge
+        std::thread::sleep(Duration::from_millis(200));
+        let server = Server::new(port, vec!["reset-aircraft-200".to_string()]);
+
+        let result = bridge.reset_aircraft();
+        assert!(result.is_ok(), "expected Ok: {:?}", result);
+        assert_eq!(bridge.try_initialized(), InitState::Ready);
+
+        drop(server);
+    }
+
+    #[test]
+    fn wait_initialized_returns_once_simulator_starts() {
+        let port = get_available_port();
+        let config = Configuration {
+            simulator_host: format!("127.0.0.1:{}", port),
+            connect_timeout: Duration::from_millis(100),
+            pool_size: PoolSize::Fixed(1),
+            lazy_connect: true,
+            ..Default::default()
+        };
+        let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+
+        let server = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            Server::new(port, vec!["reset-aircraft-200".to_string()])
+        });
+
+        bridge.wait_initialized().unwrap();
+        assert_eq!(bridge.try_initialized(), InitState::Ready);
+
+        let server = server.join().unwrap();
+        let result = bridge.reset_aircraft();
+        assert!(result.is_ok(), "expected Ok: {:?}", result);
+
+        drop(server);
+    }
+
+    #[test]
+    fn wait_initialized_fails_when_pool_gives_up() {
+        let config = Configuration {
+            simulator_host: "not-an-address".to_string(),
+            lazy_connect: true,
+            ..Default::default()
+        };
+        let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+
+        match bridge.wait_initialized() {
+            Err(BridgeError::Initialization(msg)) => {
+                assert!(msg.contains("Invalid simulator host"), "{}", msg);
+            }
+            other => panic!("expected Initialization error, got {:?}", other),
+        }
+        assert!(matches!(bridge.try_initialized(), InitState::Failed(_)));
+    }
+
+    #[test]
+    fn probe_notices_simulator_quitting_while_idle() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let probe_interval = Duration::from_millis(100);
+        let config = Configuration {
+            simulator_host: listener.local_addr().unwrap().to_string(),
+            connect_timeout: Duration::from_millis(20),
+            pool_size: PoolSize::Fixed(1),
+            probe_interval: Some(probe_interval),
+            ..Default::default()
+        };
+        let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+        assert!(bridge.simulator_reachable());
+
+        // RealFlight quits while no requests are made
+        drop(listener);
+        std::thread::sleep(probe_interval * 2);
+
+        assert!(!bridge.simulator_reachable());
+    }
+
+    #[test]
+    fn io_timeout_bounds_wait_for_pooled_connection() {
+        // Room for one connection in the backlog and never accepts, so the
+        // pool cannot open a second
+        let listener =
+            socket2::Socket::new(socket2::Domain::IPV4, socket2::Type::STREAM, None).unwrap();
+        listener
+            .bind(
+                &"127.0.0.1:0"
+                    .parse::<std::net::SocketAddr>()
+                    .unwrap()
+                    .into(),
+            )
+            .unwrap();
+        listener.listen(0).unwrap();
+        let config = Configuration {
+            simulator_host: listener
+                .local_addr()
+                .unwrap()
+                .as_socket()
+                .unwrap()
+                .to_string(),
+            connect_timeout: Duration::from_millis(100),
+            io_timeout: Some(Duration::from_millis(20)),
+            pool_size: PoolSize::Fixed(1),
+            reset_debounce: Duration::ZERO,
+            ..Default::default()
+        };
+        let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+
+        // Unanswered on the pooled connection, then no connection left
+        for _ in 0..2 {
+            let result = bridge.reset_aircraft();
+            assert!(
+                matches!(result, Err(BridgeError::Timeout(t)) if t == Duration::from_millis(20)),
+                "expected Timeout: {:?}",
+                result
+            );
+        }
+        assert!(bridge.statistics().pool_starvation_count >= 1);
+    }
+
+    #[test]
+    fn ping_returns_round_trip_time() {
+        let port = get_available_port();
+        let server = Server::new(port, vec!["return-data-200".to_string()]);
+        let bridge = create_bridge(port).unwrap();
+
+        let latency = bridge.ping().unwrap();
+        assert!(latency > Duration::ZERO);
+        assert!(latency < Duration::from_secs(1), "took {:?}", latency);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("<m-selectedChannels>0</m-selectedChannels>"));
+
+        drop(server);
+    }
+
+    #[test]
+    fn ping_succeeds_on_soap_fault() {
+        let port = get_available_port();
+        let server = Server::new(port, vec!["return-data-500".to_string()]);
+        let bridge = create_bridge(port).unwrap();
+
+        let result = bridge.ping();
+        assert!(result.is_ok(), "expected Ok: {:?}", result);
+
+        drop(server);
+    }
+
+    #[test]
+    fn tap_sees_exchange_data_body() {
+        let port = get_available_port();
+        let server = Server::new(port, vec!["return-data-200".to_string()]);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let config = Configuration {
+            simulator_host: format!("127.0.0.1:{}", port),
+            connect_timeout: Duration::from_millis(1000),
+            tap: Some(Arc::new(move |action, body, response| {
+                log.lock().unwrap().push((
+                    action.to_string(),
+                    body.to_string(),
+                    response.status_code,
+                ));
+            })),
+            ..Default::default()
+        };
+        let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+
+        let mut control = ControlInputs::default();
+        control.channels[2] = 1.0;
+        bridge.exchange_data(&control).unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        let (action, body, status_code) = &seen[0];
+        assert_eq!(action, "ExchangeData");
+        assert_eq!(body, &crate::encode_control_inputs(&control));
+        assert_eq!(*status_code, 200);
+
+        drop(server);
+    }
+
+    #[test]
+    fn transcript_pairs_requests_with_responses() {
+        let port = get_available_port();
+        // Responses are served last to first
+        let server = Server::new(
+            port,
+            vec![
+                "return-data-200".to_string(),
+                "reset-aircraft-200".to_string(),
+            ],
+        );
+        let dir = std::env::temp_dir().join(format!(
+            "realflight-bridge-{}-transcript-sync",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = Configuration {
+            simulator_host: format!("127.0.0.1:{}", port),
+            connect_timeout: Duration::from_millis(1000),
+            transcript: Some(dir.clone()),
+            ..Default::default()
+        };
+        let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+
+        bridge.reset_aircraft().unwrap();
+        bridge.exchange_data(&ControlInputs::default()).unwrap();
+        // Dropping the bridge waits for the transcript to be written
+        drop(bridge);
+
+        let transcript = crate::Transcript::open(&dir).unwrap();
+        let entries = transcript.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].sequence, 1);
+        assert_eq!(entries[0].action(), Some("ResetAircraft"));
+        assert!(entries[0].response_body().contains("ResetAircraftResponse"));
+        assert_eq!(entries[1].sequence, 2);
+        assert_eq!(entries[1].action(), Some("ExchangeData"));
+        assert!(entries[1].request.starts_with("POST / HTTP/1.1\r\n"));
+        assert!(entries[1].response.contains("Server: gSOAP/2.7\r\n"));
+        assert_eq!(entries[1].status_code(), Some(200));
+        assert!(entries[1].timestamp >= entries[0].timestamp);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        drop(server);
+    }
+
+    #[test]
+    fn shutdown_restores_rc_and_stops_pool() {
+        let port = get_available_port();
+        // Responses are served last to first
+        let server = Server::new(
+            port,
+            vec![
+                "restore-original-controller-device-200".to_string(),
+                "inject-uav-controller-interface-200".to_string(),
+            ],
+        );
+        let bridge = create_bridge(port).unwrap();
+
+        bridge.disable_rc().unwrap();
+        let result = bridge.shutdown();
+        assert!(result.is_ok(), "expected Ok: {:?}", result);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains("RestoreOriginalControllerDevice"));
+
+        // The pool no longer connects, so wake the server to let it exit
+        let _ = std::net::TcpStream::connect(format!("127.0.0.1:{}", port));
+    }
+
+    #[test]
+    fn two_bridges_drive_separate_simulators() {
+        // Responses are served last to first
+        let responses = || {
+            vec![
+                "return-data-200".to_string(),
+                "return-data-200".to_string(),
+                "inject-uav-controller-interface-200".to_string(),
+            ]
+        };
+        let (port_a, port_b) = (get_available_port(), get_available_port());
+        let server_a = Server::new(port_a, responses());
+        let server_b = Server::new(port_b, responses());
+        let bridge_a = create_bridge(port_a).unwrap();
+        let bridge_b = create_bridge(port_b).unwrap();
+
+        assert_eq!(bridge_a.simulator_host(), format!("127.0.0.1:{}", port_a));
+        assert_eq!(bridge_b.simulator_host(), format!("127.0.0.1:{}", port_b));
+
+        bridge_a.disable_rc().unwrap();
+        bridge_b.disable_rc().unwrap();
+        for throttle in [0.25, 0.75] {
+            let mut control = ControlInputs::default();
+            control.channels[2] = throttle;
+            bridge_a.exchange_data(&control).unwrap();
+
+            control.channels[2] = 1.0 - throttle;
+            bridge_b.exchange_data(&control).unwrap();
+        }
+
+        assert_eq!(bridge_a.statistics().request_count, 3);
+        assert_eq!(bridge_b.statistics().request_count, 3);
+
+        let requests_a = server_a.requests();
+        let requests_b = server_b.requests();
+        assert_eq!(requests_a.len(), 3);
+        assert_eq!(requests_b.len(), 3);
+        assert!(requests_a[1].contains("<item>0.25</item>"));
+        assert!(requests_b[1].contains("<item>0.75</item>"));
+        assert!(requests_a[2].contains("<item>0.75</item>"));
+        assert!(requests_b[2].contains("<item>0.25</item>"));
+
+        drop(server_a);
+        drop(server_b);
+    }
+
+    #[test]
+    fn stalled_response_misses_deadline() {
+        // Accepts connections (via the listen backlog) but never replies
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Configuration {
+            simulator_host: listener.local_addr().unwrap().to_string(),
+            connect_timeout: Duration::from_millis(1000),
+            pool_size: PoolSize::Fixed(1),
+            ..Default::default()
+        };
+        let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(50);
+        let result = bridge.exchange_data_deadline(&ControlInputs::default(), deadline);
+        let elapsed = start.elapsed();
+
+        assert!(
+            matches!(result, Err(BridgeError::Timeout(_))),
+            "expected Timeout, got {:?}",
+            result
+        );
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_millis(500), "took {:?}", elapsed);
+    }
+
+    #[test]
+    fn stalled_response_returns_timeout() {
+        // Accepts connections (via the listen backlog) but never replies
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Configuration {
+            simulator_host: listener.local_addr().unwrap().to_string(),
+            connect_timeout: Duration::from_millis(1000),
+            io_timeout: Some(Duration::from_millis(100)),
+            pool_size: PoolSize::Fixed(1),
+            ..Default::default()
+        };
+        let bridge = RealFlightLocalBridge::with_configuration(&config).unwrap();
+
+        let start = Instant::now();
+        let result = bridge.reset_aircraft();
+        let elapsed = start.elapsed();
+
+        match result {
+            Err(BridgeError::Timeout(timeout)) => {
+                assert_eq!(timeout, Duration::from_millis(100));
+            }
+            other => panic!("expected Timeout, got {:?}", other),
+        }
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_secs(1), "took {:?}", elapsed);
+    }
 }
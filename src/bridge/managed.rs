@@ -0,0 +1,1354 @@
+//! Session tracking wrapper that rejects out-of-order RC operations.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+use super::RealFlightBridge;
+use crate::encoders::units;
+use crate::{BridgeError, ControlInputs, SimulatorState, SoapFaultKind};
+
+#[cfg(feature = "rt-tokio")]
+use std::{future::Future, pin::Pin};
+
+/// `current_aircraft_status` of a crashed aircraft.
+const CRASHED_STATUS: &str = "CAS-CRASHED";
+
+/// Which controller RealFlight is listening to, as far as a [ManagedBridge] knows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RcState {
+    /// The RealFlight Link controller is injected; `exchange_data` drives the aircraft
+    External,
+    /// The original RC device is active; `exchange_data` has no effect on the aircraft
+    Internal,
+    /// Not yet observed, e.g. right after connecting
+    Unknown,
+}
+
+/// A crash seen by a [ManagedBridge] in exchanged data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrashEvent {
+    /// Physics time of the first crashed state (s)
+    pub physics_time: f32,
+    /// When the crashed state arrived
+    pub detected_at: Instant,
+    /// `current_aircraft_status` of the crashed state
+    pub status: String,
+    /// Whether the aircraft had lost components
+    pub lost_components: bool,
+    /// Whether the original RC device was restored because of the crash
+    pub rc_restored: bool,
+}
+
+/// A loss of the RealFlight Link controller seen by a [ManagedBridge]: a
+/// state with [SimulatorState::flight_axis_controller_is_active] cleared after
+/// the bridge injected the controller.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControllerDropout {
+    /// Physics time of the first state without the controller (s)
+    pub physics_time: f32,
+    /// When the state arrived
+    pub detected_at: Instant,
+    /// Whether the controller was injected again straight away
+    pub reinjected: bool,
+}
+
+/// Delays between attempts to inject the RealFlight Link controller again
+/// after a [ControllerDropout].
+///
+/// The first attempt is made as soon as the dropout is seen. Each failed
+/// attempt doubles the delay before the next one, up to `max`. An attempt
+/// fails if the simulator rejects it, including with
+/// [SoapFaultKind::PreexistingController] as another controller then holds
+/// the link, or if exchanged states do not show the controller active within
+/// `catch_up` of it; the latter is reported as a new dropout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReinjectBackoff {
+    /// Delay after the first failed attempt. Defaults to 100 ms.
+    pub initial: Duration,
+    /// Longest delay between attempts. Defaults to 2 s.
+    pub max: Duration,
+    /// Longest time the simulator may take to report the controller active
+    /// after it was injected again. Defaults to 500 ms.
+    pub catch_up: Duration,
+}
+
+impl Default for ReinjectBackoff {
+    fn default() -> Self {
+        ReinjectBackoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(2),
+            catch_up: Duration::from_millis(500),
+        }
+    }
+}
+
+type CrashHook = Box<dyn FnMut(&CrashEvent) + Send>;
+
+type DropoutHook = Box<dyn FnMut(&ControllerDropout) + Send>;
+
+#[cfg(feature = "rt-tokio")]
+type AsyncCrashHook =
+    Box<dyn Fn(CrashEvent) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Crash latch, cleared when a reset is observed.
+#[derive(Default)]
+struct CrashTracker {
+    crash: Option<CrashEvent>,
+    last_physics_time: Option<f32>,
+}
+
+/// Controller dropout latch, cleared when the controller is seen active again
+/// or RC is handed over explicitly.
+#[derive(Default)]
+struct DropoutTracker {
+    /// This bridge injected the controller and has not restored the RC device
+    owned: bool,
+    dropped: bool,
+    /// The controller was injected again and the simulator has until
+    /// `catch_up_until` to report it active
+    reinjected: bool,
+    catch_up_until: Option<Instant>,
+    count: u64,
+    backoff: Duration,
+    next_attempt: Option<Instant>,
+}
+
+/// Wraps a bridge and enforces the order of RC operations.
+///
+/// Calls that cannot succeed in the current [RcState] return
+/// [BridgeError::InvalidState] without contacting the simulator. The state is
+/// updated from successful calls, from the faults the simulator returns, and from
+/// [SimulatorState::flight_axis_controller_is_active] in exchanged data.
+///
+/// ```text
+///                 disable_rc
+///    Unknown ────────────────────┐
+///     │   │                      ▼
+///     │   │  exchange_data   ┌──────────┐
+///     │   └─────────────────►│ External │◄──┐
+///     │    (controller       └──────────┘   │
+///     │     active)             │           │ disable_rc
+///     │ enable_rc               │ enable_rc │
+///     ▼                         ▼           │
+///    ┌──────────┐◄──────────────┘           │
+///    │ Internal │───────────────────────────┘
+///    └──────────┘
+/// ```
+///
+/// | Operation        | `Unknown` | `Internal`     | `External`     |
+/// |------------------|-----------|----------------|----------------|
+/// | `disable_rc`     | allowed   | allowed        | `InvalidState` |
+/// | `enable_rc`      | allowed   | `InvalidState` | allowed        |
+/// | `exchange_data`  | allowed   | `InvalidState` | allowed        |
+/// | `reset_aircraft` | allowed   | allowed        | allowed        |
+///
+/// # Crashes
+///
+/// An exchanged state with [SimulatorState::has_lost_components] set, or a
+/// `CAS-CRASHED` status, is a crash. The first such state is latched as a
+/// [CrashEvent] until a reset is observed: a successful `reset_aircraft`, a
+/// state with [SimulatorState::reset_button_has_been_pressed] set, or physics
+/// time going backward. Hooks registered with [on_crash](Self::on_crash) run
+/// once per crash. With [with_rc_restore](Self::with_rc_restore), the crash
+/// also hands the aircraft back to the original RC device straight away, so
+/// the simulator operator regains control.
+///
+/// # Controller dropouts
+///
+/// Once `disable_rc` has succeeded, an exchanged state with
+/// [SimulatorState::flight_axis_controller_is_active] cleared means RealFlight
+/// dropped the controller, e.g. because the user switched aircraft. The
+/// dropout is counted, hooks registered with
+/// [on_controller_dropout](Self::on_controller_dropout) run once, and later
+/// exchanges fail with [BridgeError::ControllerDropped] until `disable_rc`
+/// succeeds again. With [with_reinject](Self::with_reinject), the bridge
+/// injects the controller again itself, retrying with a [ReinjectBackoff].
+/// The dropout is over once a state shows the controller active again.
+///
+/// The bridge is also an [AsyncBridge](crate::AsyncBridge) when the wrapped
+/// bridge is one, with the `rt-tokio` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::{
+///     BridgeError, ControlInputs, ManagedBridge, RealFlightBridge, RealFlightLocalBridge,
+/// };
+///
+/// fn main() -> Result<(), BridgeError> {
+///     let bridge = ManagedBridge::new(RealFlightLocalBridge::new()?);
+///
+///     bridge.disable_rc()?;
+///     bridge.exchange_data(&ControlInputs::default())?;
+///
+///     // Rejected locally instead of faulting in the simulator
+///     assert!(matches!(bridge.disable_rc(), Err(BridgeError::InvalidState(_))));
+///     Ok(())
+/// }
+/// ```
+///
+/// Restoring the RC device on a crash:
+///
+/// ```no_run
+/// use realflight_bridge::{ControlInputs, ManagedBridge, RealFlightBridge, RealFlightLocalBridge};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let bridge = ManagedBridge::new(RealFlightLocalBridge::new()?)
+///     .with_rc_restore(true)
+///     .on_crash(|crash| eprintln!("crashed at {:.2} s: {}", crash.physics_time, crash.status));
+///
+/// bridge.disable_rc()?;
+/// while bridge.crash().is_none() {
+///     bridge.exchange_data(&ControlInputs::default())?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ManagedBridge<B> {
+    bridge: B,
+    state: Mutex<RcState>,
+    crash: Mutex<CrashTracker>,
+    rc_restore: bool,
+    crash_hooks: Mutex<Vec<CrashHook>>,
+    #[cfg(feature = "rt-tokio")]
+    async_crash_hooks: Vec<AsyncCrashHook>,
+    dropout: Mutex<DropoutTracker>,
+    reinject: Option<ReinjectBackoff>,
+    dropout_hooks: Mutex<Vec<DropoutHook>>,
+}
+
+impl<B> ManagedBridge<B> {
+    /// Wraps `bridge`, starting in [RcState::Unknown].
+    pub fn new(bridge: B) -> Self {
+        ManagedBridge {
+            bridge,
+            state: Mutex::new(RcState::Unknown),
+            crash: Mutex::new(CrashTracker::default()),
+            rc_restore: false,
+            crash_hooks: Mutex::new(Vec::new()),
+            #[cfg(feature = "rt-tokio")]
+            async_crash_hooks: Vec::new(),
+            dropout: Mutex::new(DropoutTracker::default()),
+            reinject: None,
+            dropout_hooks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Sets whether a crash restores the original RC device with `enable_rc`.
+    /// Defaults to `false`.
+    pub fn with_rc_restore(mut self, restore: bool) -> Self {
+        self.rc_restore = restore;
+        self
+    }
+
+    /// Calls `hook` once per crash, on the thread that exchanged the crashed
+    /// state, after the RC device has been restored if it is to be.
+    pub fn on_crash(self, hook: impl FnMut(&CrashEvent) + Send + 'static) -> Self {
+        self.crash_hooks.lock().unwrap().push(Box::new(hook));
+        self
+    }
+
+    /// Spawns `handler` on the current tokio runtime once per crash.
+    ///
+    /// The exchange that saw the crash does not wait for the handler. Crashes
+    /// seen outside a runtime are logged instead. Requires the `rt-tokio`
+    /// feature.
+    #[cfg(feature = "rt-tokio")]
+    pub fn on_crash_async<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(CrashEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.async_crash_hooks
+            .push(Box::new(move |crash| Box::pin(handler(crash))));
+        self
+    }
+
+    /// Injects the controller again with `disable_rc` after a
+    /// [ControllerDropout], retrying on failure after the delays in
+    /// `backoff`. By default a dropout is only reported.
+    pub fn with_reinject(mut self, backoff: ReinjectBackoff) -> Self {
+        self.reinject = Some(backoff);
+        self
+    }
+
+    /// Calls `hook` once per controller dropout, on the thread that exchanged
+    /// the state, after the first attempt to inject the controller again if
+    /// one is made.
+    pub fn on_controller_dropout(
+        self,
+        hook: impl FnMut(&ControllerDropout) + Send + 'static,
+    ) -> Self {
+        self.dropout_hooks.lock().unwrap().push(Box::new(hook));
+        self
+    }
+
+    /// Returns the number of controller dropouts seen so far.
+    pub fn controller_dropout_count(&self) -> u64 {
+        self.dropout.lock().unwrap().count
+    }
+
+    /// Returns the last known RC state.
+    pub fn rc_state(&self) -> RcState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Returns the crash latched since the last observed reset, if any.
+    pub fn crash(&self) -> Option<CrashEvent> {
+        self.crash.lock().unwrap().crash.clone()
+    }
+
+    /// Returns the wrapped bridge.
+    pub fn get_ref(&self) -> &B {
+        &self.bridge
+    }
+
+    /// Unwraps the bridge, discarding the tracked state.
+    pub fn into_inner(self) -> B {
+        self.bridge
+    }
+
+    fn set_state(&self, state: RcState) {
+        *self.state.lock().unwrap() = state;
+    }
+
+    /// Infers the state from a fault the simulator returned.
+    fn observe_fault(&self, error: &BridgeError) {
+        match error.fault_kind() {
+            Some(SoapFaultKind::PreexistingController) => self.set_state(RcState::External),
+            Some(SoapFaultKind::ControllerNotInstantiated) => self.set_state(RcState::Internal),
+            _ => {}
+        }
+    }
+
+    fn check_exchange(&self) -> Result<(), BridgeError> {
+        if self.rc_state() == RcState::Internal {
+            return Err(BridgeError::InvalidState(
+                "exchange_data requires the RealFlight Link controller; call disable_rc first"
+                    .into(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_enable(&self) -> Result<(), BridgeError> {
+        if self.rc_state() == RcState::Internal {
+            return Err(BridgeError::InvalidState(
+                "original RC device is already active".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_disable(&self) -> Result<(), BridgeError> {
+        if self.rc_state() == RcState::External {
+            return Err(BridgeError::InvalidState(
+                "RealFlight Link controller is already injected".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Updates the RC state and crash latch from exchanged data. Returns the
+    /// crash if `state` starts a new one.
+    fn observe_state(&self, state: &SimulatorState) -> Option<CrashEvent> {
+        self.set_state(if state.flight_axis_controller_is_active {
+            RcState::External
+        } else {
+            RcState::Internal
+        });
+
+        let physics_time = units::time(state.current_physics_time);
+        let mut tracker = self.crash.lock().unwrap();
+        let went_back = tracker
+            .last_physics_time
+            .is_some_and(|last| physics_time < last);
+        tracker.last_physics_time = Some(physics_time);
+        if state.reset_button_has_been_pressed || went_back {
+            tracker.crash = None;
+        }
+
+        let crashed =
+            state.has_lost_components || state.current_aircraft_status.starts_with(CRASHED_STATUS);
+        if !crashed || tracker.crash.is_some() {
+            return None;
+        }
+
+        let crash = CrashEvent {
+            physics_time,
+            detected_at: Instant::now(),
+            status: state.current_aircraft_status.clone(),
+            lost_components: state.has_lost_components,
+            rc_restored: false,
+        };
+        tracker.crash = Some(crash.clone());
+        Some(crash)
+    }
+
+    /// Records the outcome of the RC restore and runs the hooks.
+    fn report_crash(&self, mut crash: CrashEvent, restored: Option<Result<(), BridgeError>>) {
+        match restored {
+            Some(Ok(())) => {
+                self.set_state(RcState::Internal);
+                crash.rc_restored = true;
+            }
+            Some(Err(e)) => {
+                warn!("Failed to restore the RC device after a crash: {}", e);
+                self.observe_fault(&e);
+            }
+            None => {}
+        }
+        warn!(
+            "Aircraft crashed at {:.2} s ({})",
+            crash.physics_time, crash.status
+        );
+
+        if let Some(latched) = self.crash.lock().unwrap().crash.as_mut() {
+            latched.rc_restored = crash.rc_restored;
+        }
+        for hook in self.crash_hooks.lock().unwrap().iter_mut() {
+            hook(&crash);
+        }
+
+        #[cfg(feature = "rt-tokio")]
+        if !self.async_crash_hooks.is_empty() {
+            match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => {
+                    for handler in &self.async_crash_hooks {
+                        runtime.spawn(handler(crash.clone()));
+                    }
+                }
+                Err(_) => warn!("No tokio runtime to run the async crash handlers on"),
+            }
+        }
+    }
+
+    fn observe_reset(&self) {
+        self.crash.lock().unwrap().crash = None;
+    }
+
+    /// Records whether this bridge holds the controller, clearing any dropout.
+    fn own_controller(&self, owned: bool) {
+        let mut tracker = self.dropout.lock().unwrap();
+        tracker.owned = owned;
+        tracker.dropped = false;
+        tracker.reinjected = false;
+        tracker.catch_up_until = None;
+    }
+
+    /// Whether the exchange must inject the controller again first. Fails with
+    /// [BridgeError::ControllerDropped] while the controller is lost and no
+    /// attempt is due.
+    fn reinject_due(&self) -> Result<bool, BridgeError> {
+        let tracker = self.dropout.lock().unwrap();
+        if !tracker.dropped || tracker.reinjected {
+            return Ok(false);
+        }
+        match (self.reinject, tracker.next_attempt) {
+            (Some(_), None) => Ok(true),
+            (Some(_), Some(next)) if Instant::now() >= next => Ok(true),
+            _ => Err(BridgeError::ControllerDropped),
+        }
+    }
+
+    /// Records the outcome of an attempt to inject the controller again.
+    /// Returns whether the simulator accepted it; the controller only counts
+    /// as back once an exchanged state shows it active.
+    ///
+    /// A [SoapFaultKind::PreexistingController] fault is a failure: some other
+    /// controller holds the link.
+    fn record_reinject(&self, result: Result<(), BridgeError>) -> bool {
+        let backoff = self.reinject.unwrap_or_default();
+        let mut tracker = self.dropout.lock().unwrap();
+        if let Err(e) = result {
+            warn!(
+                "Failed to inject the RealFlight Link controller again: {}",
+                e
+            );
+            if tracker.next_attempt.is_none() {
+                tracker.backoff = backoff.initial;
+            }
+            tracker.next_attempt = Some(Instant::now() + tracker.backoff);
+            tracker.backoff = (tracker.backoff * 2).min(backoff.max);
+            return false;
+        }
+        tracker.reinjected = true;
+        tracker.catch_up_until = Some(Instant::now() + backoff.catch_up);
+        tracker.next_attempt = None;
+        drop(tracker);
+        self.set_state(RcState::External);
+        true
+    }
+
+    /// Updates the dropout latch from exchanged data. Returns the dropout if
+    /// `state` starts a new one, or the controller injected again has not
+    /// shown up within [ReinjectBackoff::catch_up].
+    fn observe_controller(&self, state: &SimulatorState) -> Option<ControllerDropout> {
+        let mut tracker = self.dropout.lock().unwrap();
+        if state.flight_axis_controller_is_active {
+            tracker.dropped = false;
+            tracker.reinjected = false;
+            tracker.catch_up_until = None;
+            return None;
+        }
+        if tracker.dropped {
+            if !tracker.reinjected {
+                return None;
+            }
+            if tracker
+                .catch_up_until
+                .is_some_and(|until| Instant::now() < until)
+            {
+                // Injected again, but the simulator has not caught up yet
+                drop(tracker);
+                self.set_state(RcState::External);
+                return None;
+            }
+            warn!("The RealFlight Link controller injected again did not take hold");
+        } else if !tracker.owned {
+            return None;
+        }
+
+        tracker.dropped = true;
+        tracker.reinjected = false;
+        tracker.catch_up_until = None;
+        tracker.next_attempt = None;
+        tracker.count += 1;
+        Some(ControllerDropout {
+            physics_time: units::time(state.current_physics_time),
+            detected_at: Instant::now(),
+            reinjected: false,
+        })
+    }
+
+    /// Logs the dropout and runs the hooks.
+    fn report_dropout(&self, dropout: ControllerDropout) {
+        warn!(
+            "RealFlight dropped the RealFlight Link controller at {:.2} s{}",
+            dropout.physics_time,
+            if dropout.reinjected {
+                "; injected it again"
+            } else {
+                ""
+            }
+        );
+        for hook in self.dropout_hooks.lock().unwrap().iter_mut() {
+            hook(&dropout);
+        }
+    }
+}
+
+impl<B: RealFlightBridge> RealFlightBridge for ManagedBridge<B> {
+    fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        if self.reinject_due()? && !self.record_reinject(self.bridge.disable_rc()) {
+            return Err(BridgeError::ControllerDropped);
+        }
+        self.check_exchange()?;
+
+        let state = self
+            .bridge
+            .exchange_data(control)
+            .inspect_err(|e| self.observe_fault(e))?;
+        if let Some(crash) = self.observe_state(&state) {
+            let restored = self.rc_restore.then(|| self.bridge.enable_rc());
+            if matches!(restored, Some(Ok(()))) {
+                self.own_controller(false);
+            }
+            self.report_crash(crash, restored);
+        }
+        if let Some(mut dropout) = self.observe_controller(&state) {
+            if self.reinject.is_some() {
+                dropout.reinjected = self.record_reinject(self.bridge.disable_rc());
+            }
+            self.report_dropout(dropout);
+        }
+        Ok(state)
+    }
+
+    fn enable_rc(&self) -> Result<(), BridgeError> {
+        self.check_enable()?;
+
+        self.bridge
+            .enable_rc()
+            .inspect_err(|e| self.observe_fault(e))?;
+        self.set_state(RcState::Internal);
+        self.own_controller(false);
+        Ok(())
+    }
+
+    fn disable_rc(&self) -> Result<(), BridgeError> {
+        self.check_disable()?;
+
+        self.bridge
+            .disable_rc()
+            .inspect_err(|e| self.observe_fault(e))?;
+        self.set_state(RcState::External);
+        self.own_controller(true);
+        Ok(())
+    }
+
+    fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        self.bridge.reset_aircraft()?;
+        self.observe_reset();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rt-tokio")]
+impl<B: crate::AsyncBridge> crate::AsyncBridge for ManagedBridge<B> {
+    async fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        if self.reinject_due()? && !self.record_reinject(self.bridge.disable_rc().await) {
+            return Err(BridgeError::ControllerDropped);
+        }
+        self.check_exchange()?;
+
+        let state = self
+            .bridge
+            .exchange_data(control)
+            .await
+            .inspect_err(|e| self.observe_fault(e))?;
+        if let Some(crash) = self.observe_state(&state) {
+            let restored = if self.rc_restore {
+                Some(self.bridge.enable_rc().await)
+            } else {
+                None
+            };
+            if matches!(restored, Some(Ok(()))) {
+                self.own_controller(false);
+            }
+            self.report_crash(crash, restored);
+        }
+        if let Some(mut dropout) = self.observe_controller(&state) {
+            if self.reinject.is_some() {
+                dropout.reinjected = self.record_reinject(self.bridge.disable_rc().await);
+            }
+            self.report_dropout(dropout);
+        }
+        Ok(state)
+    }
+
+    async fn enable_rc(&self) -> Result<(), BridgeError> {
+        self.check_enable()?;
+
+        self.bridge
+            .enable_rc()
+            .await
+            .inspect_err(|e| self.observe_fault(e))?;
+        self.set_state(RcState::Internal);
+        self.own_controller(false);
+        Ok(())
+    }
+
+    async fn disable_rc(&self) -> Result<(), BridgeError> {
+        self.check_disable()?;
+
+        self.bridge
+            .disable_rc()
+            .await
+            .inspect_err(|e| self.observe_fault(e))?;
+        self.set_state(RcState::External);
+        self.own_controller(true);
+        Ok(())
+    }
+
+    async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        self.bridge.reset_aircraft().await?;
+        self.observe_reset();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::local::RealFlightLocalBridge;
+    use crate::soap_client::stub::StubSoapClient;
+
+    fn managed(responses: Vec<&str>) -> ManagedBridge<RealFlightLocalBridge> {
+        let responses = responses.iter().map(|r| r.to_string()).collect();
+        ManagedBridge::new(RealFlightLocalBridge::stub(StubSoapClient::new(responses)))
+    }
+
+    fn assert_invalid_state<T: std::fmt::Debug>(result: Result<T, BridgeError>) {
+        match result {
+            Err(BridgeError::InvalidState(_)) => {}
+            other => panic!("expected InvalidState, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn starts_unknown() {
+        let bridge = managed(vec!["reset-aircraft-200"]);
+        assert_eq!(bridge.rc_state(), RcState::Unknown);
+    }
+
+    #[test]
+    fn unknown_to_external_on_disable() {
+        let bridge = managed(vec!["inject-uav-controller-interface-200"]);
+
+        bridge.disable_rc().unwrap();
+        assert_eq!(bridge.rc_state(), RcState::External);
+    }
+
+    #[test]
+    fn unknown_to_internal_on_enable() {
+        let bridge = managed(vec!["restore-original-controller-device-200"]);
+
+        bridge.enable_rc().unwrap();
+        assert_eq!(bridge.rc_state(), RcState::Internal);
+    }
+
+    #[test]
+    fn unknown_to_external_on_active_controller() {
+        let bridge = managed(vec!["return-data-200"]);
+
+        let state = bridge.exchange_data(&ControlInputs::default()).unwrap();
+        assert!(state.flight_axis_controller_is_active);
+        assert_eq!(bridge.rc_state(), RcState::External);
+    }
+
+    #[test]
+    fn unknown_to_internal_on_not_instantiated_fault() {
+        let bridge = managed(vec!["return-data-500"]);
+
+        assert!(bridge.exchange_data(&ControlInputs::default()).is_err());
+        assert_eq!(bridge.rc_state(), RcState::Internal);
+    }
+
+    #[test]
+    fn unknown_to_external_on_preexisting_controller_fault() {
+        let bridge = managed(vec!["inject-uav-controller-interface-500"]);
+
+        assert!(bridge.disable_rc().is_err());
+        assert_eq!(bridge.rc_state(), RcState::External);
+    }
+
+    #[test]
+    fn internal_to_external_on_disable() {
+        let bridge = managed(vec![
+            "restore-original-controller-device-200",
+            "inject-uav-controller-interface-200",
+        ]);
+
+        bridge.enable_rc().unwrap();
+        bridge.disable_rc().unwrap();
+        assert_eq!(bridge.rc_state(), RcState::External);
+    }
+
+    #[test]
+    fn external_to_internal_on_enable() {
+        let bridge = managed(vec![
+            "inject-uav-controller-interface-200",
+            "restore-original-controller-device-200",
+        ]);
+
+        bridge.disable_rc().unwrap();
+        bridge.enable_rc().unwrap();
+        assert_eq!(bridge.rc_state(), RcState::Internal);
+    }
+
+    #[test]
+    fn rejects_disable_when_external() {
+        let bridge = managed(vec!["inject-uav-controller-interface-200"]);
+        bridge.disable_rc().unwrap();
+
+        assert_invalid_state(bridge.disable_rc());
+        assert_eq!(bridge.get_ref().requests().len(), 1);
+        assert_eq!(bridge.rc_state(), RcState::External);
+    }
+
+    #[test]
+    fn rejects_enable_when_internal() {
+        let bridge = managed(vec!["restore-original-controller-device-200"]);
+        bridge.enable_rc().unwrap();
+
+        assert_invalid_state(bridge.enable_rc());
+        assert_eq!(bridge.get_ref().requests().len(), 1);
+    }
+
+    #[test]
+    fn rejects_exchange_when_internal() {
+        let bridge = managed(vec!["restore-original-controller-device-200"]);
+        bridge.enable_rc().unwrap();
+
+        assert_invalid_state(bridge.exchange_data(&ControlInputs::default()));
+        assert_eq!(bridge.get_ref().requests().len(), 1);
+    }
+
+    #[test]
+    fn reset_allowed_in_any_state() {
+        let bridge = managed(vec![
+            "restore-original-controller-device-200",
+            "reset-aircraft-200",
+        ]);
+        bridge.enable_rc().unwrap();
+
+        bridge.reset_aircraft().unwrap();
+        assert_eq!(bridge.rc_state(), RcState::Internal);
+    }
+
+    #[test]
+    fn failed_call_keeps_state_on_other_faults() {
+        let bridge = managed(vec!["restore-original-controller-device-500"]);
+
+        assert!(bridge.enable_rc().is_err());
+        assert_eq!(bridge.rc_state(), RcState::Unknown);
+    }
+
+    mod crash_tests {
+        use super::*;
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Wraps `bridge` with a hook counting crashes.
+        fn counted(
+            bridge: ManagedBridge<RealFlightLocalBridge>,
+        ) -> (ManagedBridge<RealFlightLocalBridge>, Arc<AtomicUsize>) {
+            let crashes = Arc::new(AtomicUsize::new(0));
+            let counter = Arc::clone(&crashes);
+            let bridge = bridge.on_crash(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+            (bridge, crashes)
+        }
+
+        #[test]
+        fn no_crash_in_normal_flight() {
+            let (bridge, crashes) = counted(managed(vec!["return-data-200"]));
+
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert_eq!(crashes.load(Ordering::SeqCst), 0);
+            assert_eq!(bridge.crash(), None);
+        }
+
+        #[test]
+        fn crash_latched_until_reset() {
+            let (bridge, crashes) = counted(managed(vec![
+                "return-data-crashed-200",
+                "return-data-crashed-200",
+                "return-data-crashed-200",
+                "reset-aircraft-200",
+                "return-data-crashed-200",
+            ]));
+
+            for _ in 0..3 {
+                bridge.exchange_data(&ControlInputs::default()).unwrap();
+            }
+            assert_eq!(crashes.load(Ordering::SeqCst), 1);
+
+            let crash = bridge.crash().unwrap();
+            approx::assert_relative_eq!(crash.physics_time, 72275.13, epsilon = 0.01);
+            assert_eq!(crash.status, "CAS-CRASHED");
+            assert!(crash.lost_components);
+            assert!(!crash.rc_restored);
+
+            bridge.reset_aircraft().unwrap();
+            assert_eq!(bridge.crash(), None);
+
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            assert_eq!(crashes.load(Ordering::SeqCst), 2);
+        }
+
+        #[test]
+        fn crash_restores_rc_once() {
+            let (bridge, crashes) = counted(
+                managed(vec![
+                    "return-data-200",
+                    "return-data-crashed-200",
+                    "restore-original-controller-device-200",
+                    "reset-aircraft-200",
+                    "inject-uav-controller-interface-200",
+                    "return-data-200",
+                ])
+                .with_rc_restore(true),
+            );
+
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            let state = bridge.exchange_data(&ControlInputs::default()).unwrap();
+            assert!(state.has_lost_components);
+
+            assert_eq!(crashes.load(Ordering::SeqCst), 1);
+            assert!(bridge.crash().unwrap().rc_restored);
+            assert_eq!(bridge.rc_state(), RcState::Internal);
+            let requests = bridge.get_ref().requests();
+            assert_eq!(requests.len(), 3);
+            assert!(requests[2].contains("RestoreOriginalControllerDevice"));
+
+            // The operator has the aircraft until the controller is injected again
+            assert_invalid_state(bridge.exchange_data(&ControlInputs::default()));
+
+            bridge.reset_aircraft().unwrap();
+            bridge.disable_rc().unwrap();
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert_eq!(crashes.load(Ordering::SeqCst), 1);
+            assert_eq!(bridge.crash(), None);
+        }
+
+        #[test]
+        fn physics_time_going_back_is_a_reset() {
+            // The crashed fixture is later than the normal one
+            let (bridge, crashes) = counted(managed(vec![
+                "return-data-crashed-200",
+                "return-data-200",
+                "return-data-crashed-200",
+            ]));
+
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            assert_eq!(bridge.crash(), None);
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert_eq!(crashes.load(Ordering::SeqCst), 2);
+        }
+
+        #[test]
+        fn failed_restore_still_reports_crash() {
+            let (bridge, crashes) = counted(
+                managed(vec![
+                    "return-data-crashed-200",
+                    "restore-original-controller-device-500",
+                ])
+                .with_rc_restore(true),
+            );
+
+            assert!(bridge.exchange_data(&ControlInputs::default()).is_ok());
+
+            assert_eq!(crashes.load(Ordering::SeqCst), 1);
+            assert!(!bridge.crash().unwrap().rc_restored);
+            assert_eq!(bridge.rc_state(), RcState::External);
+        }
+
+        #[cfg(feature = "rt-tokio")]
+        #[tokio::test]
+        async fn async_handler_spawned_on_crash() {
+            use crate::AsyncBridge;
+
+            /// Async bridge returning a crashed state and counting `enable_rc`.
+            #[derive(Default)]
+            struct CrashedBridge {
+                enables: AtomicUsize,
+            }
+
+            impl AsyncBridge for CrashedBridge {
+                async fn exchange_data(
+                    &self,
+                    _: &ControlInputs,
+                ) -> Result<SimulatorState, BridgeError> {
+                    Ok(SimulatorState {
+                        has_lost_components: true,
+                        current_aircraft_status: "CAS-CRASHED".into(),
+                        flight_axis_controller_is_active: true,
+                        ..Default::default()
+                    })
+                }
+
+                async fn enable_rc(&self) -> Result<(), BridgeError> {
+                    self.enables.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+
+                async fn disable_rc(&self) -> Result<(), BridgeError> {
+                    Ok(())
+                }
+
+                async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+                    Ok(())
+                }
+            }
+
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let bridge = ManagedBridge::new(CrashedBridge::default())
+                .with_rc_restore(true)
+                .on_crash_async(move |crash| {
+                    let tx = tx.clone();
+                    async move {
+                        let _ = tx.send(crash);
+                    }
+                });
+
+            AsyncBridge::exchange_data(&bridge, &ControlInputs::default())
+                .await
+                .unwrap();
+
+            let crash = rx.recv().await.unwrap();
+            assert!(crash.rc_restored);
+            assert_eq!(bridge.get_ref().enables.load(Ordering::SeqCst), 1);
+            assert_eq!(bridge.rc_state(), RcState::Internal);
+            assert!(rx.try_recv().is_err());
+        }
+    }
+
+    mod dropout_tests {
+        use super::*;
+        use std::collections::VecDeque;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        /// Wraps `bridge` with a hook recording dropouts.
+        fn recorded<B>(
+            bridge: ManagedBridge<B>,
+        ) -> (ManagedBridge<B>, Arc<Mutex<Vec<ControllerDropout>>>) {
+            let dropouts = Arc::new(Mutex::new(Vec::new()));
+            let recorder = Arc::clone(&dropouts);
+            let bridge = bridge.on_controller_dropout(move |dropout| {
+                recorder.lock().unwrap().push(dropout.clone());
+            });
+            (bridge, dropouts)
+        }
+
+        fn assert_dropped<T: std::fmt::Debug>(result: Result<T, BridgeError>) {
+            match result {
+                Err(BridgeError::ControllerDropped) => {}
+                other => panic!("expected ControllerDropped, got {:?}", other),
+            }
+        }
+
+        /// Bridge whose `disable_rc` results are scripted, always returning
+        /// a state without the controller.
+        struct FailingInjectBridge {
+            injects: Mutex<VecDeque<Result<(), BridgeError>>>,
+            exchanges: Mutex<usize>,
+        }
+
+        impl FailingInjectBridge {
+            fn new(injects: Vec<Result<(), BridgeError>>) -> Self {
+                FailingInjectBridge {
+                    injects: Mutex::new(injects.into()),
+                    exchanges: Mutex::new(0),
+                }
+            }
+        }
+
+        impl RealFlightBridge for FailingInjectBridge {
+            fn exchange_data(&self, _: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+                *self.exchanges.lock().unwrap() += 1;
+                Ok(SimulatorState {
+                    flight_axis_controller_is_active: false,
+                    ..Default::default()
+                })
+            }
+
+            fn enable_rc(&self) -> Result<(), BridgeError> {
+                Ok(())
+            }
+
+            fn disable_rc(&self) -> Result<(), BridgeError> {
+                self.injects
+                    .lock()
+                    .unwrap()
+                    .pop_front()
+                    .unwrap_or_else(|| Err(BridgeError::SoapFault("unscripted".into())))
+            }
+
+            fn reset_aircraft(&self) -> Result<(), BridgeError> {
+                Ok(())
+            }
+        }
+
+        fn failed_inject() -> Result<(), BridgeError> {
+            Err(BridgeError::SoapFault(
+                "Error setting new controller interface".into(),
+            ))
+        }
+
+        #[test]
+        fn inactive_controller_before_disable_is_not_a_dropout() {
+            let (bridge, dropouts) = recorded(managed(vec!["return-data-inactive-200"]));
+
+            let state = bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert!(!state.flight_axis_controller_is_active);
+            assert_eq!(bridge.controller_dropout_count(), 0);
+            assert!(dropouts.lock().unwrap().is_empty());
+            assert_eq!(bridge.rc_state(), RcState::Internal);
+        }
+
+        #[test]
+        fn dropout_reported_until_disable_rc() {
+            let (bridge, dropouts) = recorded(managed(vec![
+                "inject-uav-controller-interface-200",
+                "return-data-200",
+                "return-data-inactive-200",
+                "inject-uav-controller-interface-200",
+                "return-data-200",
+            ]));
+
+            bridge.disable_rc().unwrap();
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            let state = bridge.exchange_data(&ControlInputs::default()).unwrap();
+            assert!(!state.flight_axis_controller_is_active);
+
+            assert_eq!(bridge.controller_dropout_count(), 1);
+            {
+                let dropouts = dropouts.lock().unwrap();
+                assert_eq!(dropouts.len(), 1);
+                approx::assert_relative_eq!(dropouts[0].physics_time, 72264.58, epsilon = 0.01);
+                assert!(!dropouts[0].reinjected);
+            }
+
+            // Rejected locally until the controller is injected again
+            assert_dropped(bridge.exchange_data(&ControlInputs::default()));
+            assert_dropped(bridge.exchange_data(&ControlInputs::default()));
+            assert_eq!(bridge.get_ref().requests().len(), 3);
+
+            bridge.disable_rc().unwrap();
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            assert_eq!(bridge.controller_dropout_count(), 1);
+            assert_eq!(bridge.rc_state(), RcState::External);
+        }
+
+        #[test]
+        fn reinjects_when_controller_flips_for_two_frames() {
+            let (bridge, dropouts) = recorded(
+                managed(vec![
+                    "inject-uav-controller-interface-200",
+                    "return-data-200",
+                    "return-data-inactive-200",
+                    "inject-uav-controller-interface-200",
+                    "return-data-inactive-200",
+                    "return-data-200",
+                ])
+                .with_reinject(ReinjectBackoff::default()),
+            );
+
+            bridge.disable_rc().unwrap();
+            for _ in 0..4 {
+                bridge.exchange_data(&ControlInputs::default()).unwrap();
+                assert_eq!(bridge.rc_state(), RcState::External);
+            }
+
+            assert_eq!(bridge.controller_dropout_count(), 1);
+            let dropouts = dropouts.lock().unwrap();
+            assert_eq!(dropouts.len(), 1);
+            assert!(dropouts[0].reinjected);
+            let requests = bridge.get_ref().requests();
+            assert_eq!(requests.len(), 6);
+            assert!(requests[3].contains("InjectUAVControllerInterface"));
+        }
+
+        #[test]
+        fn preexisting_controller_is_a_failed_reinject() {
+            let (bridge, dropouts) = recorded(
+                managed(vec![
+                    "inject-uav-controller-interface-200",
+                    "return-data-inactive-200",
+                    "inject-uav-controller-interface-500",
+                    "return-data-200",
+                ])
+                .with_reinject(ReinjectBackoff {
+                    initial: Duration::from_secs(3600),
+                    ..Default::default()
+                }),
+            );
+
+            bridge.disable_rc().unwrap();
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert!(!dropouts.lock().unwrap()[0].reinjected);
+            assert_eq!(bridge.rc_state(), RcState::Internal);
+            assert_dropped(bridge.exchange_data(&ControlInputs::default()));
+            assert_eq!(bridge.get_ref().requests().len(), 3);
+        }
+
+        #[test]
+        fn reinject_that_does_not_take_hold_is_reported_again() {
+            let (bridge, dropouts) = recorded(
+                ManagedBridge::new(FailingInjectBridge::new(vec![Ok(()), Ok(()), Ok(())]))
+                    .with_reinject(ReinjectBackoff {
+                        catch_up: Duration::ZERO,
+                        ..Default::default()
+                    }),
+            );
+            bridge.disable_rc().unwrap();
+
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert_eq!(bridge.controller_dropout_count(), 2);
+            let dropouts = dropouts.lock().unwrap();
+            assert_eq!(dropouts.len(), 2);
+            assert!(dropouts.iter().all(|dropout| dropout.reinjected));
+            assert!(bridge.get_ref().injects.lock().unwrap().is_empty());
+        }
+
+        #[test]
+        fn reinject_may_catch_up_within_window() {
+            let bridge = ManagedBridge::new(FailingInjectBridge::new(vec![Ok(()), Ok(())]))
+                .with_reinject(ReinjectBackoff {
+                    catch_up: Duration::from_secs(3600),
+                    ..Default::default()
+                });
+            bridge.disable_rc().unwrap();
+
+            for _ in 0..3 {
+                bridge.exchange_data(&ControlInputs::default()).unwrap();
+            }
+
+            assert_eq!(bridge.controller_dropout_count(), 1);
+            assert_eq!(bridge.rc_state(), RcState::External);
+        }
+
+        #[test]
+        fn new_dropout_after_controller_returns() {
+            let bridge = managed(vec![
+                "inject-uav-controller-interface-200",
+                "return-data-inactive-200",
+                "inject-uav-controller-interface-200",
+                "return-data-200",
+                "return-data-inactive-200",
+                "inject-uav-controller-interface-200",
+            ])
+            .with_reinject(ReinjectBackoff::default());
+
+            bridge.disable_rc().unwrap();
+            for _ in 0..3 {
+                bridge.exchange_data(&ControlInputs::default()).unwrap();
+            }
+
+            assert_eq!(bridge.controller_dropout_count(), 2);
+        }
+
+        #[test]
+        fn enable_rc_is_not_a_dropout() {
+            let bridge = managed(vec![
+                "inject-uav-controller-interface-200",
+                "restore-original-controller-device-200",
+            ]);
+
+            bridge.disable_rc().unwrap();
+            bridge.enable_rc().unwrap();
+
+            assert_invalid_state(bridge.exchange_data(&ControlInputs::default()));
+            assert_eq!(bridge.controller_dropout_count(), 0);
+        }
+
+        #[test]
+        fn failed_reinject_waits_for_backoff() {
+            let bridge =
+                ManagedBridge::new(FailingInjectBridge::new(vec![Ok(()), failed_inject()]))
+                    .with_reinject(ReinjectBackoff {
+                        initial: Duration::from_secs(3600),
+                        max: Duration::from_secs(3600),
+                        ..Default::default()
+                    });
+            bridge.disable_rc().unwrap();
+
+            let state = bridge.exchange_data(&ControlInputs::default()).unwrap();
+            assert!(!state.flight_axis_controller_is_active);
+
+            assert_dropped(bridge.exchange_data(&ControlInputs::default()));
+            assert_eq!(*bridge.get_ref().exchanges.lock().unwrap(), 1);
+            assert!(bridge.get_ref().injects.lock().unwrap().is_empty());
+        }
+
+        #[test]
+        fn retries_reinject_when_due() {
+            let bridge = ManagedBridge::new(FailingInjectBridge::new(vec![
+                Ok(()),
+                failed_inject(),
+                failed_inject(),
+                Ok(()),
+            ]))
+            .with_reinject(ReinjectBackoff {
+                initial: Duration::ZERO,
+                max: Duration::ZERO,
+                ..Default::default()
+            });
+            bridge.disable_rc().unwrap();
+
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            assert_dropped(bridge.exchange_data(&ControlInputs::default()));
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert_eq!(*bridge.get_ref().exchanges.lock().unwrap(), 2);
+            assert_eq!(bridge.controller_dropout_count(), 1);
+            assert_eq!(bridge.rc_state(), RcState::External);
+        }
+
+        #[test]
+        fn backoff_doubles_up_to_max() {
+            let backoff = ReinjectBackoff {
+                initial: Duration::from_millis(100),
+                max: Duration::from_millis(350),
+                ..Default::default()
+            };
+            let bridge =
+                ManagedBridge::new(FailingInjectBridge::new(vec![])).with_reinject(backoff);
+
+            let mut delays = Vec::new();
+            for _ in 0..4 {
+                let before = Instant::now();
+                assert!(!bridge.record_reinject(failed_inject()));
+                let next = bridge.dropout.lock().unwrap().next_attempt.unwrap();
+                delays.push(next.duration_since(before));
+            }
+
+            let expected = [100, 200, 350, 350].map(Duration::from_millis);
+            for (delay, expected) in delays.iter().zip(expected) {
+                assert!(*delay >= expected && *delay < expected + Duration::from_millis(50));
+            }
+        }
+
+        #[cfg(feature = "rt-tokio")]
+        #[tokio::test]
+        async fn async_reinject_on_dropout() {
+            use crate::AsyncBridge;
+            use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+            /// Async bridge that drops the controller on the second exchange.
+            #[derive(Default)]
+            struct DroppingBridge {
+                exchanges: AtomicUsize,
+                injects: AtomicUsize,
+                active: AtomicBool,
+            }
+
+            impl AsyncBridge for DroppingBridge {
+                async fn exchange_data(
+                    &self,
+                    _: &ControlInputs,
+                ) -> Result<SimulatorState, BridgeError> {
+                    if self.exchanges.fetch_add(1, Ordering::SeqCst) == 1 {
+                        self.active.store(false, Ordering::SeqCst);
+                    }
+                    Ok(SimulatorState {
+                        flight_axis_controller_is_active: self.active.load(Ordering::SeqCst),
+                        ..Default::default()
+                    })
+                }
+
+                async fn enable_rc(&self) -> Result<(), BridgeError> {
+                    Ok(())
+                }
+
+                async fn disable_rc(&self) -> Result<(), BridgeError> {
+                    self.injects.fetch_add(1, Ordering::SeqCst);
+                    self.active.store(true, Ordering::SeqCst);
+                    Ok(())
+                }
+
+                async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+                    Ok(())
+                }
+            }
+
+            let bridge = ManagedBridge::new(DroppingBridge::default())
+                .with_reinject(ReinjectBackoff::default());
+
+            AsyncBridge::disable_rc(&bridge).await.unwrap();
+            for _ in 0..3 {
+                AsyncBridge::exchange_data(&bridge, &ControlInputs::default())
+                    .await
+                    .unwrap();
+            }
+
+            assert_eq!(bridge.controller_dropout_count(), 1);
+            assert_eq!(bridge.get_ref().injects.load(Ordering::SeqCst), 2);
+            assert_eq!(bridge.rc_state(), RcState::External);
+        }
+    }
+}
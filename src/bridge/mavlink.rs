@@ -0,0 +1,1024 @@
+//! Hardware-in-the-loop adapter speaking MAVLink HIL messages.
+//!
+//! This module requires the `mavlink` feature.
+
+#![cfg(feature = "mavlink")]
+
+use std::io;
+
+use crate::bridge::RealFlightBridge;
+use crate::bridge::local::{ControlOutcome, LoopReport, RealFlightLocalBridge};
+use crate::{BridgeError, ControlInputs, SimulatorState};
+
+/// Standard gravity [meters/second²]
+const GRAVITY: f32 = 9.80665;
+/// WGS84 equatorial radius (m)
+const EARTH_RADIUS: f64 = 6_378_137.0;
+/// Sea level air density of the standard atmosphere [kilograms/meter³]
+const AIR_DENSITY: f32 = 1.225;
+/// Every `HIL_SENSOR_UPDATED_*` flag, from accelerometer X to temperature
+const ALL_SENSORS_UPDATED: u32 = 0x1fff;
+/// `GPS_FIX_TYPE_3D_FIX`
+const FIX_TYPE_3D: u8 = 3;
+
+/// Fields of MAVLink `HIL_STATE_QUATERNION` (#115), in its units.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HilStateQuaternion {
+    /// Simulation time (µs)
+    pub time_usec: u64,
+    /// Attitude as `[w, x, y, z]`, body to NED
+    pub attitude_quaternion: [f32; 4],
+    /// Body roll rate [radians/second]
+    pub rollspeed: f32,
+    /// Body pitch rate [radians/second]
+    pub pitchspeed: f32,
+    /// Body yaw rate [radians/second]
+    pub yawspeed: f32,
+    /// Latitude [degrees * 1E7]
+    pub lat: i32,
+    /// Longitude [degrees * 1E7]
+    pub lon: i32,
+    /// Altitude above mean sea level (mm)
+    pub alt: i32,
+    /// Ground speed north [centimeters/second]
+    pub vx: i16,
+    /// Ground speed east [centimeters/second]
+    pub vy: i16,
+    /// Ground speed down [centimeters/second]
+    pub vz: i16,
+    /// Indicated airspeed [centimeters/second]
+    pub ind_airspeed: u16,
+    /// True airspeed [centimeters/second]
+    pub true_airspeed: u16,
+    /// Specific force along body X (mG)
+    pub xacc: i16,
+    /// Specific force along body Y (mG)
+    pub yacc: i16,
+    /// Specific force along body Z (mG)
+    pub zacc: i16,
+}
+
+/// Fields of MAVLink `HIL_GPS` (#113), in its units.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HilGps {
+    /// Simulation time (µs)
+    pub time_usec: u64,
+    /// `GPS_FIX_TYPE`, always a 3D fix
+    pub fix_type: u8,
+    /// Latitude [degrees * 1E7]
+    pub lat: i32,
+    /// Longitude [degrees * 1E7]
+    pub lon: i32,
+    /// Altitude above mean sea level (mm)
+    pub alt: i32,
+    /// Horizontal dilution of precision [HDOP * 100]
+    pub eph: u16,
+    /// Vertical dilution of precision [VDOP * 100]
+    pub epv: u16,
+    /// Ground speed [centimeters/second]
+    pub vel: u16,
+    /// Ground speed north [centimeters/second]
+    pub vn: i16,
+    /// Ground speed east [centimeters/second]
+    pub ve: i16,
+    /// Ground speed down [centimeters/second]
+    pub vd: i16,
+    /// Course over ground (cdeg), `u16::MAX` when stationary
+    pub cog: u16,
+    /// Number of satellites visible
+    pub satellites_visible: u8,
+}
+
+/// Fields of MAVLink `HIL_SENSOR` (#107), in its units.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HilSensor {
+    /// Simulation time (µs)
+    pub time_usec: u64,
+    /// Specific force along body X [meters/second²]
+    pub xacc: f32,
+    /// Specific force along body Y [meters/second²]
+    pub yacc: f32,
+    /// Specific force along body Z [meters/second²]
+    pub zacc: f32,
+    /// Body roll rate [radians/second]
+    pub xgyro: f32,
+    /// Body pitch rate [radians/second]
+    pub ygyro: f32,
+    /// Body yaw rate [radians/second]
+    pub zgyro: f32,
+    /// Magnetic field along body X (gauss)
+    pub xmag: f32,
+    /// Magnetic field along body Y (gauss)
+    pub ymag: f32,
+    /// Magnetic field along body Z (gauss)
+    pub zmag: f32,
+    /// Static pressure (hPa)
+    pub abs_pressure: f32,
+    /// Differential (pitot) pressure (hPa)
+    pub diff_pressure: f32,
+    /// Altitude from the static pressure (m)
+    pub pressure_alt: f32,
+    /// Temperature (°C)
+    pub temperature: f32,
+    /// `HIL_SENSOR_UPDATED_*` flags of the fields set
+    pub fields_updated: u32,
+    /// Sensor instance
+    pub id: u8,
+}
+
+/// Fields of MAVLink `HIL_ACTUATOR_CONTROLS` (#93), sent by the autopilot.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HilActuatorControls {
+    /// Autopilot time (µs)
+    pub time_usec: u64,
+    /// Control outputs, -1 to 1 (0 to 1 for throttles), NaN when unused
+    pub controls: [f32; 16],
+    /// `MAV_MODE_FLAG` bitmask of the autopilot
+    pub mode: u8,
+    /// Autopilot specific flags
+    pub flags: u64,
+}
+
+/// Messages describing one simulator step to the autopilot.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HilMessages {
+    /// Attitude, position and velocity
+    pub state: HilStateQuaternion,
+    /// GPS fix
+    pub gps: HilGps,
+    /// IMU, magnetometer and barometer readings
+    pub sensor: HilSensor,
+}
+
+/// MAVLink connection to an autopilot, supplied by the application.
+///
+/// Implement it over any MAVLink library and transport, e.g. by copying each
+/// message into its `mavlink::common` counterpart, whose fields carry the
+/// same names and units.
+pub trait HilLink {
+    /// Sends the messages for one simulator step.
+    fn send(&mut self, messages: &HilMessages) -> io::Result<()>;
+    /// Returns the newest actuator controls received since the last call,
+    /// without blocking.
+    fn receive(&mut self) -> io::Result<Option<HilActuatorControls>>;
+    /// Whether the autopilot has gone, ending [MavlinkAdapter::run]
+    fn is_closed(&self) -> bool {
+        false
+    }
+}
+
+/// Geodetic position of the origin of RealFlight's world frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GeoOrigin {
+    /// Latitude (deg)
+    pub latitude: f64,
+    /// Longitude (deg)
+    pub longitude: f64,
+}
+
+impl GeoOrigin {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        GeoOrigin {
+            latitude,
+            longitude,
+        }
+    }
+}
+
+/// How one actuator control drives a RealFlight channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChannelMapping {
+    /// Index into [HilActuatorControls::controls]
+    pub control: usize,
+    /// Whether the control spans 0 to 1, as throttles do, instead of -1 to 1
+    pub unipolar: bool,
+    /// Whether the channel moves opposite to the control
+    pub reversed: bool,
+}
+
+impl ChannelMapping {
+    /// Maps a -1 to 1 control onto the full channel, 0 onto 0.5.
+    pub fn bipolar(control: usize) -> Self {
+        ChannelMapping {
+            control,
+            unipolar: false,
+            reversed: false,
+        }
+    }
+
+    /// Maps a 0 to 1 control straight onto the channel.
+    pub fn unipolar(control: usize) -> Self {
+        ChannelMapping {
+            control,
+            unipolar: true,
+            reversed: false,
+        }
+    }
+
+    /// Reverses the channel.
+    pub fn reversed(mut self) -> Self {
+        self.reversed = !self.reversed;
+        self
+    }
+
+    fn apply(&self, controls: &HilActuatorControls) -> f32 {
+        // Unused and missing controls rest at zero
+        let value = match controls.controls.get(self.control) {
+            Some(value) if !value.is_nan() => *value,
+            _ => 0.0,
+        };
+        let value = if self.unipolar {
+            value
+        } else {
+            (value + 1.0) / 2.0
+        }
+        .clamp(0.0, 1.0);
+        if self.reversed { 1.0 - value } else { value }
+    }
+}
+
+/// Assigns actuator controls to the channels of [ControlInputs].
+///
+/// The default suits a fixed-wing autopilot sending roll, pitch, yaw and
+/// throttle as controls 0 to 3: they drive the aileron, elevator, rudder and
+/// throttle channels, with positive roll, pitch and yaw commanding right roll,
+/// nose up and nose right as the channels do. Unmapped channels are held at
+/// 0.5.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelMap {
+    channels: [Option<ChannelMapping>; 12],
+}
+
+impl Default for ChannelMap {
+    fn default() -> Self {
+        ChannelMap::empty()
+            .channel(0, ChannelMapping::bipolar(0))
+            .channel(1, ChannelMapping::bipolar(1))
+            .channel(2, ChannelMapping::unipolar(3))
+            .channel(3, ChannelMapping::bipolar(2))
+    }
+}
+
+impl ChannelMap {
+    /// A map leaving every channel unmapped.
+    pub fn empty() -> Self {
+        ChannelMap {
+            channels: [None; 12],
+        }
+    }
+
+    /// Drives `channel`, an index into [ControlInputs::channels], by `mapping`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is 12 or more.
+    pub fn channel(mut self, channel: usize, mapping: ChannelMapping) -> Self {
+        assert!(channel < 12, "channel must be below 12, got {}", channel);
+        self.channels[channel] = Some(mapping);
+        self
+    }
+
+    /// Converts actuator controls into channel values.
+    pub fn apply(&self, controls: &HilActuatorControls) -> ControlInputs {
+        let mut inputs = ControlInputs::default();
+        for (value, mapping) in inputs.channels.iter_mut().zip(&self.channels) {
+            *value = match mapping {
+                Some(mapping) => mapping.apply(controls),
+                None => 0.5,
+            };
+        }
+        inputs
+    }
+}
+
+/// Converts between [SimulatorState] and the MAVLink HIL messages, so
+/// RealFlight can stand in for the simulator of a PX4 or ArduPilot SITL
+/// setup.
+///
+/// Outgoing messages place RealFlight's world origin at a configured
+/// [GeoOrigin] and report attitude, velocities and accelerations in the
+/// north-east-down and forward-right-down frames MAVLink uses. Sensor values
+/// are derived without noise: the accelerometer reads specific force, -1 g
+/// along body Z when level at rest, barometer readings follow the standard
+/// atmosphere and the magnetometer a fixed field rotated into the body frame.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::{
+///     GeoOrigin, HilActuatorControls, HilLink, HilMessages, MavlinkAdapter, RealFlightBridge,
+///     RealFlightLocalBridge,
+/// };
+/// use std::error::Error;
+/// use std::io;
+///
+/// /// Connection to the autopilot, e.g. over the `mavlink` crate
+/// struct Autopilot;
+///
+/// impl HilLink for Autopilot {
+///     fn send(&mut self, messages: &HilMessages) -> io::Result<()> {
+///         // copy into HIL_STATE_QUATERNION, HIL_GPS and HIL_SENSOR and send them
+///         Ok(())
+///     }
+///
+///     fn receive(&mut self) -> io::Result<Option<HilActuatorControls>> {
+///         // newest HIL_ACTUATOR_CONTROLS received, if any
+///         Ok(None)
+///     }
+/// }
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///     let bridge = RealFlightLocalBridge::new()?;
+///     bridge.disable_rc()?;
+///
+///     let mut adapter = MavlinkAdapter::new(GeoOrigin::new(47.397742, 8.545594));
+///     let report = adapter.run(&bridge, &mut Autopilot, 250.0)?;
+///     println!("{} steps", report.iterations);
+///
+///     bridge.enable_rc()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MavlinkAdapter {
+    origin: GeoOrigin,
+    channel_map: ChannelMap,
+    magnetic_field: [f32; 3],
+    inputs: ControlInputs,
+}
+
+impl MavlinkAdapter {
+    /// Creates an adapter placing RealFlight's world origin at `origin`, with
+    /// the default [ChannelMap].
+    pub fn new(origin: GeoOrigin) -> Self {
+        let channel_map = ChannelMap::default();
+        let inputs = channel_map.apply(&HilActuatorControls::default());
+        MavlinkAdapter {
+            origin,
+            channel_map,
+            // Roughly the field at mid northern latitudes
+            magnetic_field: [0.21, 0.0, 0.42],
+            inputs,
+        }
+    }
+
+    /// Replaces the channel map; the inputs held until controls arrive are
+    /// those of all-zero controls.
+    pub fn channel_map(mut self, channel_map: ChannelMap) -> Self {
+        self.inputs = channel_map.apply(&HilActuatorControls::default());
+        self.channel_map = channel_map;
+        self
+    }
+
+    /// Sets the earth magnetic field the magnetometer reads, as north, east
+    /// and down components in gauss.
+    pub fn magnetic_field(mut self, north_east_down: [f32; 3]) -> Self {
+        self.magnetic_field = north_east_down;
+        self
+    }
+
+    /// Inputs sent on the next [step](Self::step).
+    pub fn inputs(&self) -> &ControlInputs {
+        &self.inputs
+    }
+
+    /// Converts `state` into `HIL_STATE_QUATERNION`.
+    pub fn hil_state_quaternion(&self, state: &SimulatorState) -> HilStateQuaternion {
+        let attitude = Attitude::of(state);
+        let (lat, lon) = self.position(state);
+        let [vx, vy, vz] = ground_velocity(state);
+        let airspeed = centimeters(raw::meters_per_second(state.airspeed)) as u16;
+        let [xacc, yacc, zacc] = attitude
+            .specific_force(state)
+            .map(|a| (a / GRAVITY * 1000.0) as i16);
+
+        HilStateQuaternion {
+            time_usec: time_usec(state),
+            attitude_quaternion: attitude.quaternion(),
+            rollspeed: raw::radians_per_second(state.roll_rate),
+            pitchspeed: raw::radians_per_second(state.pitch_rate),
+            yawspeed: raw::radians_per_second(state.yaw_rate),
+            lat,
+            lon,
+            alt: millimeters(raw::meters(state.altitude_asl)),
+            vx,
+            vy,
+            vz,
+            ind_airspeed: airspeed,
+            true_airspeed: airspeed,
+            xacc,
+            yacc,
+            zacc,
+        }
+    }
+
+    /// Converts `state` into `HIL_GPS`.
+    pub fn hil_gps(&self, state: &SimulatorState) -> HilGps {
+        let (lat, lon) = self.position(state);
+        let [vn, ve, vd] = ground_velocity(state);
+        let vel = (vn as f32).hypot(ve as f32) as u16;
+        let cog = match vel {
+            0 => u16::MAX,
+            _ => {
+                ((ve as f32).atan2(vn as f32).to_degrees().rem_euclid(360.0) * 100.0) as u16 % 36000
+            }
+        };
+
+        HilGps {
+            time_usec: time_usec(state),
+            fix_type: FIX_TYPE_3D,
+            lat,
+            lon,
+            alt: millimeters(raw::meters(state.altitude_asl)),
+            eph: 100,
+            epv: 100,
+            vel,
+            vn,
+            ve,
+            vd,
+            cog,
+            satellites_visible: 10,
+        }
+    }
+
+    /// Converts `state` into `HIL_SENSOR`.
+    pub fn hil_sensor(&self, state: &SimulatorState) -> HilSensor {
+        let attitude = Attitude::of(state);
+        let [xacc, yacc, zacc] = attitude.specific_force(state);
+        let [xmag, ymag, zmag] = attitude.to_body(self.magnetic_field);
+        let altitude = raw::meters(state.altitude_asl);
+        let airspeed = raw::meters_per_second(state.airspeed);
+
+        HilSensor {
+            time_usec: time_usec(state),
+            xacc,
+            yacc,
+            zacc,
+            xgyro: raw::radians_per_second(state.roll_rate),
+            ygyro: raw::radians_per_second(state.pitch_rate),
+            zgyro: raw::radians_per_second(state.yaw_rate),
+            xmag,
+            ymag,
+            zmag,
+            abs_pressure: 1013.25 * (1.0 - 2.25577e-5 * altitude).powf(5.25588),
+            diff_pressure: 0.5 * AIR_DENSITY * airspeed * airspeed / 100.0,
+            pressure_alt: altitude,
+            temperature: 15.0 - 0.0065 * altitude,
+            fields_updated: ALL_SENSORS_UPDATED,
+            id: 0,
+        }
+    }
+
+    /// Converts `state` into all messages sent each step.
+    pub fn messages(&self, state: &SimulatorState) -> HilMessages {
+        HilMessages {
+            state: self.hil_state_quaternion(state),
+            gps: self.hil_gps(state),
+            sensor: self.hil_sensor(state),
+        }
+    }
+
+    /// Converts `HIL_ACTUATOR_CONTROLS` into channel values by the channel map.
+    pub fn control_inputs(&self, controls: &HilActuatorControls) -> ControlInputs {
+        self.channel_map.apply(controls)
+    }
+
+    /// Runs one step: takes the newest actuator controls from `link`,
+    /// exchanges the resulting inputs with the simulator and sends the new
+    /// state back over `link`.
+    ///
+    /// Until the first controls arrive, the inputs of all-zero controls are
+    /// sent.
+    pub fn step<B: RealFlightBridge + ?Sized>(
+        &mut self,
+        bridge: &B,
+        link: &mut impl HilLink,
+    ) -> Result<SimulatorState, BridgeError> {
+        if let Some(controls) = link.receive()? {
+            self.inputs = self.control_inputs(&controls);
+        }
+        let state = bridge.exchange_data(&self.inputs)?;
+        link.send(&self.messages(&state))?;
+        Ok(state)
+    }
+
+    /// Pumps data both ways between `bridge` and `link` at `rate_hz` until
+    /// the link closes, on [RealFlightLocalBridge::run_control_loop].
+    ///
+    /// As there, the first exchange sends [ControlInputs::default].
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first failed exchange or link operation and returns its
+    /// error, link errors as [BridgeError::Connection].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate_hz` is not a positive, finite number.
+    pub fn run(
+        &mut self,
+        bridge: &RealFlightLocalBridge,
+        link: &mut impl HilLink,
+        rate_hz: f32,
+    ) -> Result<LoopReport, BridgeError> {
+        let mut failure = None;
+        let report = bridge.run_control_loop(rate_hz, |state| {
+            let pumped = link
+                .send(&self.messages(state))
+                .and_then(|()| link.receive());
+            match pumped {
+                Ok(controls) if !link.is_closed() => {
+                    if let Some(controls) = controls {
+                        self.inputs = self.channel_map.apply(&controls);
+                    }
+                    ControlOutcome::Continue(self.inputs.clone())
+                }
+                Ok(_) => ControlOutcome::Stop,
+                Err(e) => {
+                    failure = Some(e);
+                    ControlOutcome::Stop
+                }
+            }
+        })?;
+        match failure {
+            Some(e) => Err(e.into()),
+            None => Ok(report),
+        }
+    }
+
+    /// Latitude and longitude of the aircraft [degrees * 1E7].
+    fn position(&self, state: &SimulatorState) -> (i32, i32) {
+        let north = raw::meters(state.aircraft_position_x) as f64;
+        let east = raw::meters(state.aircraft_position_y) as f64;
+        let latitude = self.origin.latitude + (north / EARTH_RADIUS).to_degrees();
+        let longitude = self.origin.longitude
+            + (east / (EARTH_RADIUS * self.origin.latitude.to_radians().cos())).to_degrees();
+        ((latitude * 1e7) as i32, (longitude * 1e7) as i32)
+    }
+}
+
+/// Euler angles of the aircraft (rad).
+struct Attitude {
+    roll: f32,
+    pitch: f32,
+    yaw: f32,
+}
+
+impl Attitude {
+    fn of(state: &SimulatorState) -> Self {
+        Attitude {
+            roll: raw::radians(state.roll),
+            pitch: raw::radians(state.inclination),
+            yaw: raw::radians(state.azimuth),
+        }
+    }
+
+    /// Body to NED rotation as `[w, x, y, z]`.
+    fn quaternion(&self) -> [f32; 4] {
+        let (sr, cr) = (self.roll / 2.0).sin_cos();
+        let (sp, cp) = (self.pitch / 2.0).sin_cos();
+        let (sy, cy) = (self.yaw / 2.0).sin_cos();
+        [
+            cr * cp * cy + sr * sp * sy,
+            sr * cp * cy - cr * sp * sy,
+            cr * sp * cy + sr * cp * sy,
+            cr * cp * sy - sr * sp * cy,
+        ]
+    }
+
+    /// Rotates a NED vector into the body frame.
+    fn to_body(&self, [n, e, d]: [f32; 3]) -> [f32; 3] {
+        let (sr, cr) = self.roll.sin_cos();
+        let (sp, cp) = self.pitch.sin_cos();
+        let (sy, cy) = self.yaw.sin_cos();
+        [
+            cp * cy * n + cp * sy * e - sp * d,
+            (sr * sp * cy - cr * sy) * n + (sr * sp * sy + cr * cy) * e + sr * cp * d,
+            (cr * sp * cy + sr * sy) * n + (cr * sp * sy - sr * cy) * e + cr * cp * d,
+        ]
+    }
+
+    /// What an accelerometer reads: body acceleration less gravity
+    /// [meters/second²].
+    fn specific_force(&self, state: &SimulatorState) -> [f32; 3] {
+        let gravity = self.to_body([0.0, 0.0, GRAVITY]);
+        [
+            raw::meters_per_second_squared(state.acceleration_body_ax) - gravity[0],
+            raw::meters_per_second_squared(state.acceleration_body_ay) - gravity[1],
+            raw::meters_per_second_squared(state.acceleration_body_az) - gravity[2],
+        ]
+    }
+}
+
+/// North, east and down ground speed [centimeters/second].
+fn ground_velocity(state: &SimulatorState) -> [i16; 3] {
+    [
+        state.velocity_world_u,
+        state.velocity_world_v,
+        state.velocity_world_w,
+    ]
+    .map(|v| centimeters(raw::meters_per_second(v)) as i16)
+}
+
+fn time_usec(state: &SimulatorState) -> u64 {
+    (raw::seconds(state.current_physics_time) as f64 * 1e6) as u64
+}
+
+fn centimeters(meters: f32) -> f32 {
+    (meters * 100.0).round()
+}
+
+fn millimeters(meters: f32) -> i32 {
+    (meters * 1000.0).round() as i32
+}
+
+/// Raw values of [SimulatorState] quantities in SI units.
+#[cfg(feature = "uom")]
+mod raw {
+    use uom::si::acceleration::meter_per_second_squared;
+    use uom::si::angle::radian;
+    use uom::si::angular_velocity::radian_per_second;
+    use uom::si::length::meter;
+    use uom::si::time::second;
+    use uom::si::velocity::meter_per_second;
+
+    use crate::unit_types::*;
+
+    pub(super) fn meters(v: Length) -> f32 {
+        v.get::<meter>()
+    }
+
+    pub(super) fn meters_per_second(v: Velocity) -> f32 {
+        v.get::<meter_per_second>()
+    }
+
+    pub(super) fn meters_per_second_squared(v: Acceleration) -> f32 {
+        v.get::<meter_per_second_squared>()
+    }
+
+    pub(super) fn radians(v: Angle) -> f32 {
+        v.get::<radian>()
+    }
+
+    pub(super) fn radians_per_second(v: AngularVelocity) -> f32 {
+        v.get::<radian_per_second>()
+    }
+
+    pub(super) fn seconds(v: Time) -> f32 {
+        v.get::<second>()
+    }
+}
+
+/// Raw values of [SimulatorState] quantities in SI units.
+#[cfg(not(feature = "uom"))]
+mod raw {
+    pub(super) fn meters(v: f32) -> f32 {
+        v
+    }
+
+    pub(super) fn meters_per_second(v: f32) -> f32 {
+        v
+    }
+
+    pub(super) fn meters_per_second_squared(v: f32) -> f32 {
+        v
+    }
+
+    pub(super) fn radians(degrees: f32) -> f32 {
+        degrees.to_radians()
+    }
+
+    pub(super) fn radians_per_second(degrees_per_second: f32) -> f32 {
+        degrees_per_second.to_radians()
+    }
+
+    pub(super) fn seconds(v: f32) -> f32 {
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_simulator_state;
+    use crate::encoders::encode_control_inputs;
+    use crate::soap_client::stub::StubSoapClient;
+    use approx::assert_relative_eq;
+
+    static SIM_STATE_RESPONSE: &str = include_str!("../../testdata/responses/return-data-200.xml");
+
+    fn fixture_state() -> SimulatorState {
+        decode_simulator_state(SIM_STATE_RESPONSE).unwrap()
+    }
+
+    fn adapter() -> MavlinkAdapter {
+        MavlinkAdapter::new(GeoOrigin::new(47.0, 8.0))
+    }
+
+    /// Link replaying queued controls and recording what it is sent.
+    #[derive(Default)]
+    struct RecordingLink {
+        controls: Vec<HilActuatorControls>,
+        sent: Vec<HilMessages>,
+        close_after: Option<usize>,
+        fail: bool,
+    }
+
+    impl HilLink for RecordingLink {
+        fn send(&mut self, messages: &HilMessages) -> io::Result<()> {
+            if self.fail {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            self.sent.push(messages.clone());
+            Ok(())
+        }
+
+        fn receive(&mut self) -> io::Result<Option<HilActuatorControls>> {
+            Ok(self.controls.pop())
+        }
+
+        fn is_closed(&self) -> bool {
+            self.close_after.is_some_and(|n| self.sent.len() >= n)
+        }
+    }
+
+    fn actuator_controls(values: &[f32]) -> HilActuatorControls {
+        let mut controls = HilActuatorControls::default();
+        controls.controls[..values.len()].copy_from_slice(values);
+        controls
+    }
+
+    mod state_quaternion {
+        use super::*;
+
+        #[test]
+        fn attitude_round_trips_euler_angles() {
+            let [w, x, y, z] = adapter()
+                .hil_state_quaternion(&fixture_state())
+                .attitude_quaternion;
+
+            assert_relative_eq!(w * w + x * x + y * y + z * z, 1.0, epsilon = 1e-6);
+            let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+            let pitch = (2.0 * (w * y - z * x)).asin();
+            let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+            assert_relative_eq!(roll.to_degrees(), -0.747124, epsilon = 1e-3);
+            assert_relative_eq!(pitch.to_degrees(), 1.533278, epsilon = 1e-3);
+            assert_relative_eq!(yaw.to_degrees(), -89.607056, epsilon = 1e-3);
+        }
+
+        #[test]
+        fn rates_are_radians_per_second() {
+            let message = adapter().hil_state_quaternion(&fixture_state());
+
+            assert_relative_eq!(
+                message.rollspeed,
+                (-0.000032228f32).to_radians(),
+                epsilon = 1e-9
+            );
+            assert_relative_eq!(
+                message.pitchspeed,
+                0.001380354f32.to_radians(),
+                epsilon = 1e-9
+            );
+            assert_relative_eq!(
+                message.yawspeed,
+                0.001473751f32.to_radians(),
+                epsilon = 1e-9
+            );
+        }
+
+        #[test]
+        fn position_is_offset_north_and_east_of_origin() {
+            let message = adapter().hil_state_quaternion(&fixture_state());
+
+            // 5575.7 m north and 1716.0 m east of 47°N 8°E
+            assert!(
+                (message.lat - 470_500_872).abs() <= 2,
+                "lat {}",
+                message.lat
+            );
+            assert!((message.lon - 80_226_023).abs() <= 2, "lon {}", message.lon);
+            assert_eq!(message.alt, 1_127_371);
+        }
+
+        #[test]
+        fn velocities_are_centimeters_per_second_down_positive() {
+            let message = adapter().hil_state_quaternion(&fixture_state());
+
+            assert_eq!((message.vx, message.vy, message.vz), (0, 0, 4));
+            assert_eq!(message.ind_airspeed, 4);
+            assert_eq!(message.true_airspeed, 4);
+        }
+
+        #[test]
+        fn accelerometer_reads_minus_one_g_down_at_rest() {
+            let message = adapter().hil_state_quaternion(&fixture_state());
+
+            // Nose up and left wing down tilt gravity towards +X and +Y
+            assert_eq!(message.xacc, 26);
+            assert_eq!(message.yacc, 13);
+            assert!((message.zacc + 995).abs() <= 1, "zacc {}", message.zacc);
+        }
+
+        #[test]
+        fn time_is_physics_time_in_microseconds() {
+            let message = adapter().hil_state_quaternion(&fixture_state());
+            assert_eq!(message.time_usec / 1_000_000, 72263);
+        }
+    }
+
+    mod gps {
+        use super::*;
+
+        #[test]
+        fn reports_3d_fix_at_aircraft_position() {
+            let state = fixture_state();
+            let adapter = adapter();
+            let gps = adapter.hil_gps(&state);
+            let quaternion = adapter.hil_state_quaternion(&state);
+
+            assert_eq!(gps.fix_type, 3);
+            assert_eq!(
+                (gps.lat, gps.lon, gps.alt),
+                (quaternion.lat, quaternion.lon, quaternion.alt)
+            );
+            assert_eq!((gps.vn, gps.ve, gps.vd), (0, 0, 4));
+        }
+
+        #[test]
+        fn course_unknown_when_stationary() {
+            let gps = adapter().hil_gps(&fixture_state());
+            assert_eq!(gps.vel, 0);
+            assert_eq!(gps.cog, u16::MAX);
+        }
+
+        #[test]
+        fn course_follows_ground_track() {
+            let mut state = fixture_state();
+            state.velocity_world_u = crate::decoders::to_velocity(-10.0);
+            state.velocity_world_v = crate::decoders::to_velocity(0.0);
+            let gps = adapter().hil_gps(&state);
+            assert_eq!(gps.vel, 1000);
+            assert_eq!(gps.cog, 18000);
+
+            state.velocity_world_u = crate::decoders::to_velocity(0.0);
+            state.velocity_world_v = crate::decoders::to_velocity(-10.0);
+            assert_eq!(adapter().hil_gps(&state).cog, 27000);
+        }
+    }
+
+    mod sensor {
+        use super::*;
+
+        #[test]
+        fn accelerometer_and_gyro_in_si_units() {
+            let sensor = adapter().hil_sensor(&fixture_state());
+
+            assert_relative_eq!(sensor.xacc, 0.2622, epsilon = 1e-3);
+            assert_relative_eq!(sensor.yacc, 0.1277, epsilon = 1e-3);
+            assert_relative_eq!(sensor.zacc, -9.758, epsilon = 1e-3);
+            assert_relative_eq!(sensor.zgyro, 0.001473751f32.to_radians(), epsilon = 1e-9);
+        }
+
+        #[test]
+        fn magnetometer_rotates_field_into_body() {
+            // Heading west, magnetic north lies off the right wing
+            let sensor = adapter().hil_sensor(&fixture_state());
+
+            assert_relative_eq!(sensor.xmag, 0.0, epsilon = 0.02);
+            assert_relative_eq!(sensor.ymag, 0.21, epsilon = 0.02);
+            assert_relative_eq!(sensor.zmag, 0.42, epsilon = 0.02);
+        }
+
+        #[test]
+        fn barometer_follows_standard_atmosphere() {
+            let sensor = adapter().hil_sensor(&fixture_state());
+
+            assert_relative_eq!(sensor.abs_pressure, 884.95, epsilon = 0.05);
+            assert_relative_eq!(sensor.pressure_alt, 1127.371, epsilon = 1e-3);
+            assert_relative_eq!(sensor.temperature, 7.672, epsilon = 1e-3);
+            assert!(sensor.diff_pressure < 1e-4);
+            assert_eq!(sensor.fields_updated, 0x1fff);
+        }
+    }
+
+    mod channel_map {
+        use super::*;
+
+        #[test]
+        fn default_maps_roll_pitch_yaw_throttle() {
+            let inputs = ChannelMap::default().apply(&actuator_controls(&[0.5, -1.0, 0.2, 0.75]));
+
+            assert_relative_eq!(inputs.channels[0], 0.75);
+            assert_relative_eq!(inputs.channels[1], 0.0);
+            assert_relative_eq!(inputs.channels[2], 0.75);
+            assert_relative_eq!(inputs.channels[3], 0.6);
+            assert!(inputs.channels[4..].iter().all(|&c| c == 0.5));
+        }
+
+        #[test]
+        fn unused_controls_rest_at_zero() {
+            let controls = actuator_controls(&[f32::NAN, f32::NAN, f32::NAN, f32::NAN]);
+            let inputs = ChannelMap::default().apply(&controls);
+
+            assert_eq!(inputs.channels[..4], [0.5, 0.5, 0.0, 0.5]);
+        }
+
+        #[test]
+        fn reversed_and_clamped() {
+            let map = ChannelMap::empty()
+                .channel(0, ChannelMapping::bipolar(0).reversed())
+                .channel(5, ChannelMapping::unipolar(7))
+                .channel(6, ChannelMapping::bipolar(20));
+            let inputs = map.apply(&actuator_controls(&[
+                0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.5,
+            ]));
+
+            assert_relative_eq!(inputs.channels[0], 0.25);
+            assert_relative_eq!(inputs.channels[5], 1.0);
+            assert_relative_eq!(inputs.channels[6], 0.5);
+        }
+
+        #[test]
+        #[should_panic(expected = "channel must be below 12")]
+        fn rejects_channel_out_of_range() {
+            let _ = ChannelMap::empty().channel(12, ChannelMapping::bipolar(0));
+        }
+    }
+
+    mod pumping {
+        use super::*;
+
+        fn stub_bridge() -> (
+            RealFlightLocalBridge,
+            std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+        ) {
+            let client = StubSoapClient::new(vec!["return-data-200".to_string()]);
+            let requests = client.request_log();
+            (RealFlightLocalBridge::stub(client), requests)
+        }
+
+        #[test]
+        fn step_sends_received_controls_and_returns_state() {
+            let (bridge, requests) = stub_bridge();
+            let mut link = RecordingLink {
+                controls: vec![actuator_controls(&[1.0, 0.0, 0.0, 0.5])],
+                ..Default::default()
+            };
+            let mut adapter = adapter();
+
+            let state = adapter.step(&bridge, &mut link).unwrap();
+
+            let expected = adapter.control_inputs(&actuator_controls(&[1.0, 0.0, 0.0, 0.5]));
+            assert_eq!(adapter.inputs(), &expected);
+            assert!(requests.lock().unwrap()[0].contains(&encode_control_inputs(&expected)));
+            assert_eq!(link.sent, vec![adapter.messages(&state)]);
+        }
+
+        #[test]
+        fn step_holds_inputs_without_new_controls() {
+            let (bridge, _) = stub_bridge();
+            let mut link = RecordingLink::default();
+            let mut adapter = adapter();
+
+            adapter.step(&bridge, &mut link).unwrap();
+
+            assert_eq!(adapter.inputs().channels[..4], [0.5, 0.5, 0.0, 0.5]);
+            assert_eq!(link.sent.len(), 1);
+        }
+
+        #[test]
+        fn run_stops_when_link_closes() {
+            let (bridge, requests) = stub_bridge();
+            let mut link = RecordingLink {
+                controls: vec![actuator_controls(&[0.0, 0.0, 0.0, 1.0])],
+                close_after: Some(3),
+                ..Default::default()
+            };
+
+            let report = adapter().run(&bridge, &mut link, 1000.0).unwrap();
+
+            assert_eq!(report.iterations, 3);
+            assert_eq!(link.sent.len(), 3);
+            let mut full_throttle = ChannelMap::default().apply(&HilActuatorControls::default());
+            full_throttle.channels[2] = 1.0;
+            assert!(requests.lock().unwrap()[1].contains(&encode_control_inputs(&full_throttle)));
+        }
+
+        #[test]
+        fn run_returns_link_error() {
+            let (bridge, _) = stub_bridge();
+            let mut link = RecordingLink {
+                fail: true,
+                ..Default::default()
+            };
+
+            let result = adapter().run(&bridge, &mut link, 1000.0);
+
+            match result {
+                Err(BridgeError::Connection(e)) => assert_eq!(e.kind(), io::ErrorKind::BrokenPipe),
+                other => panic!("expected Connection error, got {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,578 @@
+//! Coordinating several simulators, one bridge per aircraft.
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::RealFlightBridge;
+use crate::{BridgeError, ControlInputs, SimulatorState};
+
+#[cfg(feature = "rt-tokio")]
+use super::DynAsyncBridge;
+
+/// Exchange counters for one aircraft of a [MultiBridge] or `AsyncMultiBridge`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AircraftStatistics {
+    /// Number of exchanges attempted
+    pub exchanges: u64,
+    /// Number of exchanges that failed
+    pub errors: u64,
+    /// Message of the most recent failure
+    pub last_error: Option<String>,
+    /// Average time an exchange took, successful or not
+    pub mean_latency: Duration,
+    /// Longest time an exchange took
+    pub max_latency: Duration,
+}
+
+/// Exchange counters across all aircraft of a [MultiBridge] or
+/// `AsyncMultiBridge`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultiStatistics {
+    /// Number of `exchange_all` calls
+    pub rounds: u64,
+    /// Number of exchanges attempted, across all aircraft
+    pub exchanges: u64,
+    /// Number of exchanges that failed, across all aircraft
+    pub errors: u64,
+    /// Longest time an `exchange_all` call took
+    pub max_round_latency: Duration,
+    /// Counters per aircraft, in bridge order
+    pub aircraft: Vec<AircraftStatistics>,
+}
+
+/// Drives one aircraft per bridge, for experiments with several RealFlight
+/// instances.
+///
+/// Each call goes to every bridge at once, one thread per bridge, and
+/// returns one result per bridge in bridge order. A bridge that fails does not
+/// affect the others: its error takes its place in the results and the rest
+/// carry on.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::{
+///     Configuration, ControlInputs, MultiBridge, RealFlightBridge, RealFlightLocalBridge,
+/// };
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut bridges: Vec<Box<dyn RealFlightBridge + Send + Sync>> = Vec::new();
+/// for host in ["192.168.1.10:18083", "192.168.1.11:18083", "192.168.1.12:18083"] {
+///     bridges.push(Box::new(RealFlightLocalBridge::with_configuration(&Configuration {
+///         simulator_host: host.to_string(),
+///         ..Default::default()
+///     })?));
+/// }
+/// let swarm = MultiBridge::new(bridges);
+/// swarm.disable_rc_all();
+///
+/// let inputs = vec![ControlInputs::default(); swarm.len()];
+/// for (aircraft, result) in swarm.exchange_all(&inputs).into_iter().enumerate() {
+///     match result {
+///         Ok(state) => println!("{}: {:?}", aircraft, state.altitude_agl),
+///         Err(e) => eprintln!("{}: {}", aircraft, e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiBridge {
+    bridges: Vec<Box<dyn RealFlightBridge + Send + Sync>>,
+    tracker: Tracker,
+}
+
+impl MultiBridge {
+    /// Coordinates `bridges`, one per aircraft.
+    pub fn new(bridges: Vec<Box<dyn RealFlightBridge + Send + Sync>>) -> Self {
+        let tracker = Tracker::new(bridges.len());
+        MultiBridge { bridges, tracker }
+    }
+
+    /// Number of aircraft.
+    pub fn len(&self) -> usize {
+        self.bridges.len()
+    }
+
+    /// Whether there are no aircraft.
+    pub fn is_empty(&self) -> bool {
+        self.bridges.is_empty()
+    }
+
+    /// Bridge of aircraft `index`, or `None` if there is no such aircraft.
+    pub fn bridge(&self, index: usize) -> Option<&(dyn RealFlightBridge + Send + Sync)> {
+        self.bridges.get(index).map(|bridge| bridge.as_ref())
+    }
+
+    /// Sends `inputs[i]` to aircraft `i`, for every aircraft at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one [ControlInputs] per aircraft.
+    pub fn exchange_all(
+        &self,
+        inputs: &[ControlInputs],
+    ) -> Vec<Result<SimulatorState, BridgeError>> {
+        check_inputs(self.len(), inputs);
+        let start = Instant::now();
+        let results = self.each(|i, bridge| {
+            let sent = Instant::now();
+            let result = bridge.exchange_data(&inputs[i]);
+            self.tracker
+                .record(i, sent.elapsed(), result.as_ref().err());
+            result
+        });
+        self.tracker.finish_round(start.elapsed());
+        results
+    }
+
+    /// Calls [disable_rc](RealFlightBridge::disable_rc) on every bridge at once.
+    pub fn disable_rc_all(&self) -> Vec<Result<(), BridgeError>> {
+        self.each(|_, bridge| bridge.disable_rc())
+    }
+
+    /// Calls [enable_rc](RealFlightBridge::enable_rc) on every bridge at once.
+    pub fn enable_rc_all(&self) -> Vec<Result<(), BridgeError>> {
+        self.each(|_, bridge| bridge.enable_rc())
+    }
+
+    /// Calls [reset_aircraft](RealFlightBridge::reset_aircraft) on every
+    /// bridge at once.
+    pub fn reset_all(&self) -> Vec<Result<(), BridgeError>> {
+        self.each(|_, bridge| bridge.reset_aircraft())
+    }
+
+    /// Exchange counters so far.
+    pub fn statistics(&self) -> MultiStatistics {
+        self.tracker.statistics()
+    }
+
+    /// Runs `f` for every bridge, the first on this thread and the others on
+    /// scoped threads, collecting the results in bridge order.
+    fn each<T, F>(&self, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, &(dyn RealFlightBridge + Send + Sync)) -> T + Sync,
+    {
+        let Some((first, rest)) = self.bridges.split_first() else {
+            return Vec::new();
+        };
+        let f = &f;
+        thread::scope(|scope| {
+            let handles: Vec<_> = rest
+                .iter()
+                .enumerate()
+                .map(|(i, bridge)| scope.spawn(move || f(i + 1, bridge.as_ref())))
+                .collect();
+            let mut results = Vec::with_capacity(self.bridges.len());
+            results.push(f(0, first.as_ref()));
+            for handle in handles {
+                match handle.join() {
+                    Ok(result) => results.push(result),
+                    Err(panic) => std::panic::resume_unwind(panic),
+                }
+            }
+            results
+        })
+    }
+}
+
+/// Async version of [MultiBridge], running the calls to every bridge
+/// concurrently on the current task.
+///
+/// Requires the `rt-tokio` feature.
+#[cfg(feature = "rt-tokio")]
+pub struct AsyncMultiBridge {
+    bridges: Vec<Box<dyn DynAsyncBridge>>,
+    tracker: Tracker,
+}
+
+#[cfg(feature = "rt-tokio")]
+impl AsyncMultiBridge {
+    /// Coordinates `bridges`, one per aircraft.
+    pub fn new(bridges: Vec<Box<dyn DynAsyncBridge>>) -> Self {
+        let tracker = Tracker::new(bridges.len());
+        AsyncMultiBridge { bridges, tracker }
+    }
+
+    /// Number of aircraft.
+    pub fn len(&self) -> usize {
+        self.bridges.len()
+    }
+
+    /// Whether there are no aircraft.
+    pub fn is_empty(&self) -> bool {
+        self.bridges.is_empty()
+    }
+
+    /// Bridge of aircraft `index`, or `None` if there is no such aircraft.
+    pub fn bridge(&self, index: usize) -> Option<&dyn DynAsyncBridge> {
+        self.bridges.get(index).map(|bridge| bridge.as_ref())
+    }
+
+    /// Sends `inputs[i]` to aircraft `i`, for every aircraft at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one [ControlInputs] per aircraft.
+    pub async fn exchange_all(
+        &self,
+        inputs: &[ControlInputs],
+    ) -> Vec<Result<SimulatorState, BridgeError>> {
+        check_inputs(self.len(), inputs);
+        let start = Instant::now();
+        let exchanges =
+            self.bridges
+                .iter()
+                .zip(inputs)
+                .enumerate()
+                .map(|(i, (bridge, inputs))| async move {
+                    let sent = Instant::now();
+                    let result = bridge.exchange_data(inputs).await;
+                    self.tracker
+                        .record(i, sent.elapsed(), result.as_ref().err());
+                    result
+                });
+        let results = futures_util::future::join_all(exchanges).await;
+        self.tracker.finish_round(start.elapsed());
+        results
+    }
+
+    /// Calls `disable_rc` on every bridge at once.
+    pub async fn disable_rc_all(&self) -> Vec<Result<(), BridgeError>> {
+        futures_util::future::join_all(self.bridges.iter().map(|bridge| bridge.disable_rc())).await
+    }
+
+    /// Calls `enable_rc` on every bridge at once.
+    pub async fn enable_rc_all(&self) -> Vec<Result<(), BridgeError>> {
+        futures_util::future::join_all(self.bridges.iter().map(|bridge| bridge.enable_rc())).await
+    }
+
+    /// Calls `reset_aircraft` on every bridge at once.
+    pub async fn reset_all(&self) -> Vec<Result<(), BridgeError>> {
+        futures_util::future::join_all(self.bridges.iter().map(|bridge| bridge.reset_aircraft()))
+            .await
+    }
+
+    /// Exchange counters so far.
+    pub fn statistics(&self) -> MultiStatistics {
+        self.tracker.statistics()
+    }
+}
+
+fn check_inputs(aircraft: usize, inputs: &[ControlInputs]) {
+    assert_eq!(
+        inputs.len(),
+        aircraft,
+        "expected one ControlInputs per aircraft"
+    );
+}
+
+/// Counters shared by the sync and async multi-bridges.
+struct Tracker {
+    state: Mutex<TrackerState>,
+}
+
+struct TrackerState {
+    rounds: u64,
+    max_round_latency: Duration,
+    aircraft: Vec<AircraftStatistics>,
+    total_latency: Vec<Duration>,
+}
+
+impl Tracker {
+    fn new(aircraft: usize) -> Tracker {
+        Tracker {
+            state: Mutex::new(TrackerState {
+                rounds: 0,
+                max_round_latency: Duration::ZERO,
+                aircraft: vec![AircraftStatistics::default(); aircraft],
+                total_latency: vec![Duration::ZERO; aircraft],
+            }),
+        }
+    }
+
+    /// Counts an exchange with aircraft `index` that took `latency`.
+    fn record(&self, index: usize, latency: Duration, error: Option<&BridgeError>) {
+        let mut state = self.state.lock().unwrap();
+        state.total_latency[index] += latency;
+        let total = state.total_latency[index];
+        let aircraft = &mut state.aircraft[index];
+        aircraft.exchanges += 1;
+        aircraft.mean_latency = total.div_f64(aircraft.exchanges as f64);
+        aircraft.max_latency = aircraft.max_latency.max(latency);
+        if let Some(error) = error {
+            aircraft.errors += 1;
+            aircraft.last_error = Some(error.to_string());
+        }
+    }
+
+    fn finish_round(&self, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.rounds += 1;
+        state.max_round_latency = state.max_round_latency.max(latency);
+    }
+
+    fn statistics(&self) -> MultiStatistics {
+        let state = self.state.lock().unwrap();
+        MultiStatistics {
+            rounds: state.rounds,
+            exchanges: state.aircraft.iter().map(|a| a.exchanges).sum(),
+            errors: state.aircraft.iter().map(|a| a.errors).sum(),
+            max_round_latency: state.max_round_latency,
+            aircraft: state.aircraft.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::local::RealFlightLocalBridge;
+    use crate::soap_client::stub::StubSoapClient;
+
+    const LATENCY: Duration = Duration::from_millis(50);
+
+    /// Bridge answering every call after `LATENCY`, failing if `fails`.
+    struct SlowBridge {
+        fails: bool,
+    }
+
+    impl SlowBridge {
+        fn reply<T>(&self, value: T) -> Result<T, BridgeError> {
+            if self.fails {
+                Err(BridgeError::Timeout(LATENCY))
+            } else {
+                Ok(value)
+            }
+        }
+    }
+
+    impl RealFlightBridge for SlowBridge {
+        fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+            thread::sleep(LATENCY);
+            self.reply(SimulatorState {
+                previous_inputs: control.clone(),
+                ..Default::default()
+            })
+        }
+
+        fn enable_rc(&self) -> Result<(), BridgeError> {
+            self.reply(())
+        }
+
+        fn disable_rc(&self) -> Result<(), BridgeError> {
+            self.reply(())
+        }
+
+        fn reset_aircraft(&self) -> Result<(), BridgeError> {
+            self.reply(())
+        }
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    impl crate::AsyncBridge for SlowBridge {
+        async fn exchange_data(
+            &self,
+            control: &ControlInputs,
+        ) -> Result<SimulatorState, BridgeError> {
+            tokio::time::sleep(LATENCY).await;
+            self.reply(SimulatorState {
+                previous_inputs: control.clone(),
+                ..Default::default()
+            })
+        }
+
+        async fn enable_rc(&self) -> Result<(), BridgeError> {
+            self.reply(())
+        }
+
+        async fn disable_rc(&self) -> Result<(), BridgeError> {
+            self.reply(())
+        }
+
+        async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+            self.reply(())
+        }
+    }
+
+    fn stub(response: &str) -> Box<dyn RealFlightBridge + Send + Sync> {
+        Box::new(RealFlightLocalBridge::stub(StubSoapClient::new(vec![
+            response.to_string(),
+        ])))
+    }
+
+    /// Inputs with channel 0 set to the aircraft's index.
+    fn inputs(aircraft: usize) -> Vec<ControlInputs> {
+        (0..aircraft)
+            .map(|i| {
+                let mut inputs = ControlInputs::default();
+                inputs.channels[0] = i as f32;
+                inputs
+            })
+            .collect()
+    }
+
+    mod multi_bridge_tests {
+        use super::*;
+
+        fn swarm() -> MultiBridge {
+            MultiBridge::new(vec![
+                stub("return-data-200"),
+                stub("return-data-500"),
+                stub("return-data-200"),
+            ])
+        }
+
+        #[test]
+        fn isolates_failing_aircraft() {
+            let swarm = swarm();
+
+            let results = swarm.exchange_all(&inputs(3));
+
+            assert_eq!(results.len(), 3);
+            assert!(results[0].is_ok());
+            assert!(matches!(results[1], Err(BridgeError::SoapFault(_))));
+            assert!(results[2].is_ok());
+        }
+
+        #[test]
+        fn aggregates_statistics() {
+            let swarm = swarm();
+
+            swarm.exchange_all(&inputs(3));
+            swarm.exchange_all(&inputs(3));
+            let statistics = swarm.statistics();
+
+            assert_eq!(statistics.rounds, 2);
+            assert_eq!(statistics.exchanges, 6);
+            assert_eq!(statistics.errors, 2);
+            assert_eq!(statistics.aircraft.len(), 3);
+            assert_eq!(statistics.aircraft[0].errors, 0);
+            assert_eq!(statistics.aircraft[1].exchanges, 2);
+            assert_eq!(statistics.aircraft[1].errors, 2);
+            assert!(statistics.aircraft[1].last_error.is_some());
+            assert!(statistics.aircraft[2].last_error.is_none());
+        }
+
+        #[test]
+        fn sends_each_aircraft_its_inputs() {
+            let swarm = MultiBridge::new(
+                (0..3)
+                    .map(|_| Box::new(SlowBridge { fails: false }) as _)
+                    .collect(),
+            );
+
+            let results = swarm.exchange_all(&inputs(3));
+
+            for (i, result) in results.into_iter().enumerate() {
+                assert_eq!(result.unwrap().previous_inputs.channels[0], i as f32);
+            }
+        }
+
+        #[test]
+        fn exchanges_concurrently() {
+            let swarm = MultiBridge::new(vec![
+                Box::new(SlowBridge { fails: false }),
+                Box::new(SlowBridge { fails: true }),
+                Box::new(SlowBridge { fails: false }),
+            ]);
+            let start = Instant::now();
+
+            let results = swarm.exchange_all(&inputs(3));
+
+            assert!(start.elapsed() < LATENCY * 2, "{:?}", start.elapsed());
+            assert!(results[1].is_err());
+            let statistics = swarm.statistics();
+            assert!(statistics.max_round_latency >= LATENCY);
+            assert!(statistics.aircraft[0].mean_latency >= LATENCY);
+        }
+
+        #[test]
+        fn runs_rc_calls_on_every_bridge() {
+            let swarm = MultiBridge::new(vec![
+                stub("inject-uav-controller-interface-200"),
+                stub("inject-uav-controller-interface-500"),
+                stub("inject-uav-controller-interface-200"),
+            ]);
+
+            let results = swarm.disable_rc_all();
+
+            assert!(results[0].is_ok());
+            assert!(results[1].is_err());
+            assert!(results[2].is_ok());
+        }
+
+        #[test]
+        fn handles_no_aircraft() {
+            let swarm = MultiBridge::new(Vec::new());
+
+            assert!(swarm.is_empty());
+            assert!(swarm.exchange_all(&[]).is_empty());
+            assert_eq!(swarm.statistics().rounds, 1);
+        }
+
+        #[test]
+        #[should_panic(expected = "one ControlInputs per aircraft")]
+        fn rejects_wrong_number_of_inputs() {
+            swarm().exchange_all(&inputs(2));
+        }
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    mod async_multi_bridge_tests {
+        use super::*;
+
+        fn swarm() -> AsyncMultiBridge {
+            AsyncMultiBridge::new(vec![
+                Box::new(SlowBridge { fails: false }),
+                Box::new(SlowBridge { fails: true }),
+                Box::new(SlowBridge { fails: false }),
+            ])
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn exchanges_concurrently_isolating_failures() {
+            let swarm = swarm();
+            let start = tokio::time::Instant::now();
+
+            let results = swarm.exchange_all(&inputs(3)).await;
+
+            assert_eq!(start.elapsed(), LATENCY);
+            assert_eq!(
+                results[0].as_ref().unwrap().previous_inputs.channels[0],
+                0.0
+            );
+            assert!(matches!(results[1], Err(BridgeError::Timeout(_))));
+            assert_eq!(
+                results[2].as_ref().unwrap().previous_inputs.channels[0],
+                2.0
+            );
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn aggregates_statistics() {
+            let swarm = swarm();
+
+            swarm.exchange_all(&inputs(3)).await;
+            let statistics = swarm.statistics();
+
+            assert_eq!(statistics.rounds, 1);
+            assert_eq!(statistics.exchanges, 3);
+            assert_eq!(statistics.errors, 1);
+            assert_eq!(statistics.aircraft[1].errors, 1);
+        }
+
+        #[tokio::test]
+        async fn runs_rc_calls_on_every_bridge() {
+            let swarm = swarm();
+
+            let results = swarm.reset_all().await;
+
+            assert!(results[0].is_ok());
+            assert!(results[1].is_err());
+            assert!(results[2].is_ok());
+            assert_eq!(swarm.len(), 3);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Connection lifecycle callbacks for the proxy server.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use log::{debug, info, warn};
+
+use crate::bridge::remote::RequestType;
+
+/// What one client did while it was connected, passed to
+/// [ProxyEvents::on_disconnect].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClientStats {
+    /// Requests answered, including refused ones
+    pub requests: u64,
+    /// Requests answered with an error
+    pub errors: u64,
+    /// Time from accepting the connection to closing it
+    pub connected_for: Duration,
+}
+
+/// Callbacks for client connections to an
+/// [AsyncProxyServer](super::AsyncProxyServer), set with
+/// [events](super::AsyncProxyServer::events).
+///
+/// Every method defaults to logging through the `log` crate, so an
+/// implementation overrides only the events it cares about. Clients of a
+/// server from [bind_uds](super::AsyncProxyServer::bind_uds) have no address
+/// and are reported as `0.0.0.0:0`.
+///
+/// Callbacks run on the server's task between requests, so they should return
+/// quickly.
+pub trait ProxyEvents: Send + Sync {
+    /// A client connected.
+    fn on_connect(&self, addr: SocketAddr) {
+        info!("New client connected: {}", addr);
+    }
+
+    /// A client disconnected, or was disconnected.
+    fn on_disconnect(&self, addr: SocketAddr, stats: ClientStats) {
+        info!(
+            "Client {} disconnected after {:?}: {} requests, {} errors",
+            addr, stats.connected_for, stats.requests, stats.errors
+        );
+    }
+
+    /// A client flying the aircraft went silent for `silent_for`, and the
+    /// [link failsafe](super::AsyncProxyServer::link_failsafe) is about to be
+    /// commanded.
+    fn on_link_lost(&self, addr: SocketAddr, silent_for: Duration) {
+        warn!(
+            "Lost link to {} after {:?} of silence; commanding failsafe",
+            addr, silent_for
+        );
+    }
+
+    /// A request was answered. `latency` covers the bridge call, and is zero
+    /// for requests refused without reaching the bridge.
+    fn on_request(&self, addr: SocketAddr, request: &RequestType, latency: Duration, ok: bool) {
+        // The token is a secret, so it stays out of the log
+        if let RequestType::Authenticate { .. } = request {
+            debug!("{} Authenticate in {:?} (ok: {})", addr, latency, ok);
+        } else {
+            debug!("{} {:?} in {:?} (ok: {})", addr, request, latency, ok);
+        }
+    }
+}
+
+/// Default [ProxyEvents], which only logs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogEvents;
+
+impl ProxyEvents for LogEvents {}
@@ -1,43 +1,197 @@
 //! Request handling for the proxy server.
 
-use log::{error, info};
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
 use postcard::{from_bytes, to_stdvec};
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader, BufWriter};
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, BufWriter};
+use tokio::sync::broadcast;
 use tokio_util::sync::CancellationToken;
 
-use crate::BridgeError;
 use crate::bridge::AsyncBridge;
-use crate::bridge::remote::{Request, RequestType, Response};
+use crate::bridge::remote::{
+    ErrorDetail, ProtocolInfo, Request, RequestType, Response, ResponseStatus, check_frame_length,
+    is_measured,
+};
+use crate::{BridgeError, SimulatorState};
+
+use super::events::{ClientStats, ProxyEvents};
+use super::rate_limit::TokenBucket;
+use super::{LinkFailsafe, RateLimit};
+
+/// Per-client settings configured on the server.
+#[derive(Clone, Copy)]
+pub(super) struct ClientOptions<'a> {
+    pub request_timeout: Duration,
+    pub max_frame_size: usize,
+    /// Token the client must send before anything but the handshake
+    pub auth_token: Option<&'a str>,
+    pub rate_limit: Option<RateLimit>,
+    /// Server-wide count of requests dropped by the rate limit
+    pub throttled_count: &'a AtomicU64,
+    pub link_failsafe: Option<&'a LinkFailsafe>,
+    /// Server-wide count of link failsafes commanded
+    pub link_loss_count: &'a AtomicU64,
+    /// Server-wide time of the last [RequestType::ExchangeData] from any client
+    pub last_exchange: &'a Mutex<Option<tokio::time::Instant>>,
+    pub events: &'a dyn ProxyEvents,
+}
+
+/// One client's connection, as reported to [ProxyEvents].
+struct Session<'a> {
+    peer: SocketAddr,
+    events: &'a dyn ProxyEvents,
+    stats: ClientStats,
+    /// When the client's last request for the simulator arrived; pings and
+    /// other protocol overhead do not count
+    last_request: tokio::time::Instant,
+    /// Whether the client is flying the aircraft, so losing it commands the
+    /// link failsafe
+    flying: bool,
+}
+
+impl Session<'_> {
+    /// When the client's link counts as lost, if it is flying and a failsafe
+    /// is configured.
+    fn link_deadline(&self, failsafe: Option<&LinkFailsafe>) -> Option<tokio::time::Instant> {
+        failsafe
+            .filter(|_| self.flying)
+            .map(|failsafe| self.last_request + failsafe.link_timeout)
+    }
+
+    /// Counts a response sent for `request_type` and reports it.
+    fn answered(&mut self, request_type: &RequestType, latency: Duration, response: &Response) {
+        let ok = matches!(response.status, ResponseStatus::Success);
+        self.stats.requests += 1;
+        if !ok {
+            self.stats.errors += 1;
+        }
+        self.events.on_request(self.peer, request_type, latency, ok);
+    }
+}
 
 /// Handles a single client connection.
-pub(super) async fn handle_client<B: AsyncBridge>(
-    stream: TcpStream,
+///
+/// Returns [BridgeError::Protocol], dropping the connection, if the client
+/// announces a request above `options.max_frame_size`. With an auth token
+/// set, a client that sends a wrong token, or any request other than
+/// [RequestType::Hello] before the token, is answered with an error and
+/// disconnected. With a rate limit, requests for the simulator beyond it are
+/// answered with [ErrorDetail::Throttled] without reaching the bridge.
+///
+/// Each state returned by [RequestType::ExchangeData] is sent on `states`.
+/// After [RequestType::Subscribe] the client receives those states as
+/// [Response::broadcast] frames, and its requests for the simulator are
+/// refused.
+///
+/// With a link failsafe, a client that has sent [RequestType::ExchangeData]
+/// and then sends no request for the simulator for the link timeout,
+/// connected or not, has the failsafe commanded on its behalf, unless another
+/// client has exchanged data since. Pings do not keep the link alive. The client stops flying once it sends [RequestType::EnableRC], the
+/// failsafe has been commanded, or another client has taken over.
+///
+/// Reports the connection, each answered request and the disconnection to
+/// `options.events`.
+pub(super) async fn handle_client<S, B>(
+    stream: S,
+    peer: SocketAddr,
     bridge: &B,
+    options: ClientOptions<'_>,
+    states: &broadcast::Sender<SimulatorState>,
     cancel: CancellationToken,
-) -> Result<(), BridgeError> {
-    stream.set_nodelay(true)?;
+) -> Result<(), BridgeError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    B: AsyncBridge + ?Sized,
+{
+    let connected_at = Instant::now();
+    let mut session = Session {
+        peer,
+        events: options.events,
+        stats: ClientStats::default(),
+        last_request: tokio::time::Instant::now(),
+        flying: false,
+    };
+    options.events.on_connect(peer);
+
+    let result = serve_requests(
+        stream,
+        bridge,
+        options,
+        states,
+        &mut session,
+        cancel.clone(),
+    )
+    .await;
 
-    let (read_half, write_half) = stream.into_split();
-    let mut reader = BufReader::new(read_half);
+    // The connection is gone, but an aircraft it was flying still needs the
+    // failsafe once the link times out
+    if let Some(deadline) = session.link_deadline(options.link_failsafe) {
+        tokio::select! {
+            _ = cancel.cancelled() => {}
+            _ = tokio::time::sleep_until(deadline) => {
+                command_failsafe(bridge, options, &mut session).await;
+            }
+        }
+    }
+
+    session.stats.connected_for = connected_at.elapsed();
+    options.events.on_disconnect(peer, session.stats);
+    result
+}
+
+/// Answers the client's requests until it disconnects or `cancel` fires.
+async fn serve_requests<S, B>(
+    stream: S,
+    bridge: &B,
+    options: ClientOptions<'_>,
+    states: &broadcast::Sender<SimulatorState>,
+    session: &mut Session<'_>,
+    cancel: CancellationToken,
+) -> Result<(), BridgeError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    B: AsyncBridge + ?Sized,
+{
+    let (read_half, write_half) = tokio::io::split(stream);
     let mut writer = BufWriter::new(write_half);
-    let mut length_buffer = [0u8; 4];
+    let mut authenticated = options.auth_token.is_none();
+    let mut bucket = options
+        .rate_limit
+        .map(|limit| TokenBucket::new(limit, Instant::now()));
+    let mut subscription: Option<broadcast::Receiver<SimulatorState>> = None;
+
+    // The reader moves into the pending read, so a broadcast sent while a
+    // request is half received does not lose its bytes
+    let read = read_frame(BufReader::new(read_half), options.max_frame_size);
+    tokio::pin!(read);
 
     loop {
         tokio::select! {
             _ = cancel.cancelled() => {
                 break;
             }
-            result = reader.read_exact(&mut length_buffer) => {
-                if result.is_err() {
-                    break; // Client disconnected
+            _ = link_lost(session.link_deadline(options.link_failsafe)) => {
+                command_failsafe(bridge, options, session).await;
+            }
+            state = next_state(&mut subscription) => {
+                match state {
+                    Ok(state) => send_response(&mut writer, Response::broadcast(state)).await?,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Subscriber fell behind; skipped {} states", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
-
-                let msg_length = u32::from_be_bytes(length_buffer) as usize;
-
-                // Read the request data
-                let mut buffer = vec![0u8; msg_length];
-                reader.read_exact(&mut buffer).await?;
+            }
+            (reader, frame) = &mut read => {
+                let Some(buffer) = frame? else {
+                    break; // Client disconnected
+                };
+                let received = tokio::time::Instant::now();
+                read.set(read_frame(reader, options.max_frame_size));
 
                 // Deserialize the request
                 let request: Request = match from_bytes(&buffer) {
@@ -47,21 +201,165 @@ pub(super) async fn handle_client<B: AsyncBridge>(
                         continue;
                     }
                 };
+                // A hung control loop may still send keep-alive pings
+                if is_measured(&request.request_type) {
+                    session.last_request = received;
+                }
 
-                // Process request
-                let response = process_request(request, bridge).await;
+                // Process request, answering with the id it was sent with
+                let id = request.id;
+                if !authenticated {
+                    if !admits(&request.request_type, options.auth_token) {
+                        error!("Client failed to authenticate; disconnecting");
+                        let response = Response { id, ..Response::error() };
+                        session.answered(&request.request_type, Duration::ZERO, &response);
+                        send_response(&mut writer, response).await?;
+                        break;
+                    }
+                    authenticated =
+                        matches!(request.request_type, RequestType::Authenticate { .. });
+                }
+                if subscription.is_some() && is_measured(&request.request_type) {
+                    error!("Subscriber sent {:?}; subscribers are read-only", request.request_type);
+                    let response = Response { id, ..Response::error() };
+                    session.answered(&request.request_type, Duration::ZERO, &response);
+                    send_response(&mut writer, response).await?;
+                    continue;
+                }
+                let throttled = match &mut bucket {
+                    Some(bucket) => {
+                        is_measured(&request.request_type) && !bucket.try_take(Instant::now())
+                    }
+                    None => false,
+                };
+                if throttled {
+                    options.throttled_count.fetch_add(1, Ordering::Relaxed);
+                    let response = Response {
+                        id,
+                        detail: Some(ErrorDetail::Throttled),
+                        ..Response::error()
+                    };
+                    session.answered(&request.request_type, Duration::ZERO, &response);
+                    send_response(&mut writer, response).await?;
+                    continue;
+                }
+                if request.request_type == RequestType::Subscribe && subscription.is_none() {
+                    subscription = Some(states.subscribe());
+                }
+                let started = Instant::now();
+                let mut response = process_request(&request, bridge, options.request_timeout).await;
+                response.id = id;
+                session.answered(&request.request_type, started.elapsed(), &response);
+                let exchange = request.request_type == RequestType::ExchangeData;
+                match request.request_type {
+                    RequestType::ExchangeData => {
+                        session.flying = true;
+                        let mut last = options.last_exchange.lock().unwrap();
+                        *last = (*last).max(Some(session.last_request));
+                    }
+                    RequestType::EnableRC => session.flying = false,
+                    _ => {}
+                }
+                if let (true, Some(state)) = (exchange, &response.payload) {
+                    // Nobody may be subscribed, which is not an error
+                    let _ = states.send(state.clone());
+                }
                 send_response(&mut writer, response).await?;
             }
         }
     }
 
-    info!("Client disconnected");
     Ok(())
 }
 
+/// Reads one length-prefixed frame, handing the reader back with it so the
+/// next read can continue where this one stopped. Yields `None` once the
+/// client has closed the connection.
+pub(crate) async fn read_frame<R: AsyncRead + Unpin>(
+    mut reader: BufReader<R>,
+    max_frame_size: usize,
+) -> (BufReader<R>, Result<Option<Vec<u8>>, BridgeError>) {
+    let mut length_buffer = [0u8; 4];
+    if reader.read_exact(&mut length_buffer).await.is_err() {
+        return (reader, Ok(None));
+    }
+
+    let msg_length = u32::from_be_bytes(length_buffer) as usize;
+    if let Err(e) = check_frame_length(msg_length, max_frame_size) {
+        return (reader, Err(e));
+    }
+
+    // Read the request data
+    let mut buffer = vec![0u8; msg_length];
+    let result = reader.read_exact(&mut buffer).await;
+    (
+        reader,
+        result.map(|_| Some(buffer)).map_err(BridgeError::from),
+    )
+}
+
+/// Waits for the next broadcast state, or forever without a subscription.
+async fn next_state(
+    subscription: &mut Option<broadcast::Receiver<SimulatorState>>,
+) -> Result<SimulatorState, broadcast::error::RecvError> {
+    match subscription {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Waits for the link deadline to pass, or forever without one.
+async fn link_lost(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Sends the link failsafe to the simulator for a client that went silent,
+/// unless another client has exchanged data since its last frame.
+///
+/// Failed calls are logged; the remaining frames are still sent.
+async fn command_failsafe<B: AsyncBridge + ?Sized>(
+    bridge: &B,
+    options: ClientOptions<'_>,
+    session: &mut Session<'_>,
+) {
+    let Some(failsafe) = options.link_failsafe else {
+        return;
+    };
+    session.flying = false;
+    let last_exchange = *options.last_exchange.lock().unwrap();
+    if last_exchange.is_some_and(|at| at > session.last_request) {
+        info!(
+            "Client {} went silent after another took over",
+            session.peer
+        );
+        return;
+    }
+    options.link_loss_count.fetch_add(1, Ordering::Relaxed);
+    options
+        .events
+        .on_link_lost(session.peer, session.last_request.elapsed());
+
+    for _ in 0..failsafe.repeat {
+        let sent = bridge
+            .exchange_data_timeout(&failsafe.frame, options.request_timeout)
+            .await;
+        if let Err(e) = sent {
+            error!("Error sending failsafe frame: {}", e);
+        }
+    }
+    if failsafe.restore_rc {
+        if let Err(e) = bounded(options.request_timeout, bridge.enable_rc()).await {
+            error!("Error restoring RC after link loss: {}", e);
+        }
+    }
+}
+
 /// Sends a response to the client.
-async fn send_response(
-    writer: &mut BufWriter<tokio::net::tcp::OwnedWriteHalf>,
+async fn send_response<W: AsyncWrite + Unpin>(
+    writer: &mut BufWriter<W>,
     response: Response,
 ) -> Result<(), BridgeError> {
     let response_bytes = to_stdvec(&response)
@@ -76,38 +374,116 @@ async fn send_response(
 }
 
 /// Processes a request using the async bridge.
-async fn process_request<B: AsyncBridge>(request: Request, bridge: &B) -> Response {
-    match request.request_type {
-        RequestType::EnableRC => match bridge.enable_rc().await {
+async fn process_request<B: AsyncBridge + ?Sized>(
+    request: &Request,
+    bridge: &B,
+    request_timeout: Duration,
+) -> Response {
+    match &request.request_type {
+        RequestType::EnableRC => match bounded(request_timeout, bridge.enable_rc()).await {
             Ok(()) => Response::success(),
             Err(e) => {
                 error!("Error enabling RC: {}", e);
-                Response::error()
+                failure(&e)
             }
         },
-        RequestType::DisableRC => match bridge.disable_rc().await {
+        RequestType::DisableRC => match bounded(request_timeout, bridge.disable_rc()).await {
             Ok(()) => Response::success(),
             Err(e) => {
                 error!("Error disabling RC: {}", e);
-                Response::error()
+                failure(&e)
             }
         },
-        RequestType::ResetAircraft => match bridge.reset_aircraft().await {
+        RequestType::ResetAircraft => match bounded(request_timeout, bridge.reset_aircraft()).await
+        {
             Ok(()) => Response::success(),
             Err(e) => {
                 error!("Error resetting aircraft: {}", e);
-                Response::error()
+                failure(&e)
             }
         },
-        RequestType::ExchangeData => match request.payload {
-            Some(payload) => match bridge.exchange_data(&payload).await {
+        RequestType::ExchangeData => match &request.payload {
+            Some(payload) => match bridge.exchange_data_timeout(payload, request_timeout).await {
                 Ok(state) => Response::success_with(state),
                 Err(e) => {
                     error!("Error exchanging data: {}", e);
-                    Response::error()
+                    failure(&e)
                 }
             },
             None => Response::error(),
         },
+        // Only reached once the client is authenticated, or none is required;
+        // the subscription itself is taken by the caller
+        RequestType::Ping | RequestType::Authenticate { .. } | RequestType::Subscribe => {
+            Response::success()
+        }
+        RequestType::Hello(client) => {
+            info!(
+                "Client speaks protocol {}.{} (realflight-bridge {})",
+                client.major, client.minor, client.crate_version
+            );
+            let mut reply = ProtocolInfo::current();
+            reply.features &= client.features;
+            let response = if client.is_compatible() {
+                Response::success()
+            } else {
+                error!("Client protocol {} is incompatible", client.major);
+                Response::error()
+            };
+            Response {
+                info: Some(reply),
+                ..response
+            }
+        }
+    }
+}
+
+/// Awaits a bridge call for at most `limit`, failing with
+/// [BridgeError::Timeout] after it. The call is dropped, so the bridge's
+/// rules for cancelled requests apply.
+async fn bounded<T>(
+    limit: Duration,
+    call: impl Future<Output = Result<T, BridgeError>>,
+) -> Result<T, BridgeError> {
+    tokio::time::timeout(limit, call)
+        .await
+        .unwrap_or(Err(BridgeError::Timeout(limit)))
+}
+
+/// Error response for a failed bridge call, telling the client when it
+/// timed out.
+fn failure(error: &BridgeError) -> Response {
+    let detail = match error {
+        BridgeError::Timeout(_) => Some(ErrorDetail::Timeout),
+        _ => None,
+    };
+    Response {
+        detail,
+        ..Response::error()
+    }
+}
+
+/// Whether a request may pass before the client has authenticated: the
+/// handshake, or the correct token.
+fn admits(request_type: &RequestType, expected: Option<&str>) -> bool {
+    match (request_type, expected) {
+        (RequestType::Hello(_), _) => true,
+        (RequestType::Authenticate { token }, Some(expected)) => tokens_match(expected, token),
+        _ => false,
+    }
+}
+
+/// Compares tokens in time independent of where they first differ, so the
+/// secret cannot be guessed byte by byte from response times. Only the
+/// length can leak.
+fn tokens_match(expected: &str, actual: &str) -> bool {
+    let (expected, actual) = (expected.as_bytes(), actual.as_bytes());
+    if expected.len() != actual.len() {
+        return false;
     }
+    expected
+        .iter()
+        .zip(actual)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
 }
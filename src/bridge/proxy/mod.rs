@@ -5,30 +5,135 @@
 
 #![cfg(feature = "rt-tokio")]
 
+mod events;
 mod handler;
+mod rate_limit;
+mod simulated;
 
 #[cfg(test)]
 mod tests;
 
+use std::future::Future;
 use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use futures_util::StreamExt;
+use futures_util::stream::FuturesUnordered;
 use log::{error, info};
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast;
 use tokio_util::sync::CancellationToken;
 
-use crate::BridgeError;
 use crate::bridge::AsyncBridge;
 use crate::bridge::local::AsyncLocalBridge;
+use crate::constants::{
+    DEFAULT_MAX_FRAME_SIZE, DEFAULT_PROXY_LINK_TIMEOUT, DEFAULT_PROXY_REQUEST_TIMEOUT,
+};
+use crate::{BridgeError, ControlInputs, SimulatorState};
 
-use handler::handle_client;
+pub use events::{ClientStats, LogEvents, ProxyEvents};
+#[cfg(feature = "fuzz-corpus")]
+pub(crate) use handler::read_frame;
+use handler::{ClientOptions, handle_client};
+pub use simulated::SimModel;
+use simulated::SimulatedBridge;
+
+/// States buffered for a subscriber before it starts skipping them.
+const BROADCAST_CAPACITY: usize = 16;
 
 /// Async server for forwarding requests to the RealFlight simulator.
 ///
-/// Currently handles one client at a time (serial). Future versions may support
-/// concurrent clients for multiplayer scenarios.
+/// Clients are served concurrently against the one bridge. A client that
+/// sends [RequestType::Subscribe](crate::bridge::remote::RequestType::Subscribe)
+/// becomes a read-only observer, receiving the state from every other
+/// client's exchange.
 pub struct AsyncProxyServer {
-    listener: TcpListener,
+    listener: Listener,
     local_addr: SocketAddr,
+    request_timeout: Duration,
+    max_frame_size: usize,
+    auth_token: Option<String>,
+    rate_limit: Option<RateLimit>,
+    throttled_count: AtomicU64,
+    link_failsafe: Option<LinkFailsafe>,
+    link_loss_count: AtomicU64,
+    /// When any client last exchanged data, so a dropped session does not
+    /// command the failsafe after another has taken over
+    last_exchange: Mutex<Option<tokio::time::Instant>>,
+    events: Arc<dyn ProxyEvents>,
+    /// Model served by [run](Self::run) instead of the simulator
+    simulation: Option<SimModel>,
+}
+
+/// Per-client request rate for [AsyncProxyServer::rate_limit].
+///
+/// Each client may send `burst` requests at once, then
+/// `max_requests_per_second` on average.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Sustained requests per second allowed for one client
+    pub max_requests_per_second: u32,
+    /// Requests a client may send back to back before being limited
+    pub burst: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit {
+            max_requests_per_second: 500,
+            burst: 50,
+        }
+    }
+}
+
+/// What the server does for a client whose link drops mid-flight, set with
+/// [AsyncProxyServer::link_failsafe].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkFailsafe {
+    /// Silence after which a flying client's link counts as lost. Defaults to
+    /// [DEFAULT_PROXY_LINK_TIMEOUT].
+    pub link_timeout: Duration,
+    /// Inputs sent to the simulator once the link is lost. Defaults to
+    /// throttle (channel 2) at 0 and every other channel centered.
+    pub frame: ControlInputs,
+    /// How many times `frame` is sent. Defaults to 3.
+    pub repeat: u32,
+    /// Whether the original RC device is restored with `enable_rc` after the
+    /// frames. Defaults to true.
+    pub restore_rc: bool,
+}
+
+impl Default for LinkFailsafe {
+    fn default() -> Self {
+        LinkFailsafe {
+            link_timeout: DEFAULT_PROXY_LINK_TIMEOUT,
+            frame: ControlInputs::neutral(),
+            repeat: 3,
+            restore_rc: true,
+        }
+    }
+}
+
+/// Socket the server accepts clients on.
+enum Listener {
+    Tcp(TcpListener),
+    /// Removes the socket file when dropped
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+}
+
+/// A newly accepted client.
+enum Accepted {
+    Tcp(TcpStream, SocketAddr),
+    #[cfg(unix)]
+    Unix(UnixStream),
 }
 
 impl AsyncProxyServer {
@@ -44,16 +149,191 @@ impl AsyncProxyServer {
         let local_addr = listener.local_addr()?;
 
         Ok(AsyncProxyServer {
-            listener,
+            listener: Listener::Tcp(listener),
             local_addr,
+            request_timeout: DEFAULT_PROXY_REQUEST_TIMEOUT,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            auth_token: None,
+            rate_limit: None,
+            throttled_count: AtomicU64::new(0),
+            link_failsafe: None,
+            link_loss_count: AtomicU64::new(0),
+            last_exchange: Mutex::new(None),
+            events: Arc::new(LogEvents),
+            simulation: None,
+        })
+    }
+
+    /// Creates a server that flies a simulated aircraft instead of forwarding
+    /// to RealFlight.
+    ///
+    /// [run](Self::run) answers exchanges from a small kinematic [SimModel]
+    /// that integrates the received inputs, one fixed step per exchange, so
+    /// clients and estimators can be tested over the network without the
+    /// simulator.
+    pub async fn new_simulated(bind_address: &str, model: SimModel) -> Result<Self, BridgeError> {
+        let server = Self::new(bind_address).await?;
+        Ok(AsyncProxyServer {
+            simulation: Some(model),
+            ..server
+        })
+    }
+
+    /// Creates a new async server listening on a Unix domain socket.
+    ///
+    /// Clients on the same host connect with
+    /// [RealFlightRemoteBridge::connect_uds](crate::RealFlightRemoteBridge::connect_uds),
+    /// skipping TCP on the loopback interface. The socket file is created at
+    /// `path` and removed when the server is dropped; binding fails if the
+    /// file already exists.
+    ///
+    /// # Returns
+    /// A `Result` containing the server instance, [BridgeError::Initialization]
+    /// if this user may not create the socket file, or the I/O error.
+    #[cfg(unix)]
+    pub async fn bind_uds(path: impl AsRef<Path>) -> Result<Self, BridgeError> {
+        let path = path.as_ref();
+        let listener = UnixListener::bind(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => BridgeError::Initialization(format!(
+                "Permission denied binding {}: {}",
+                path.display(),
+                e
+            )),
+            _ => BridgeError::Connection(e),
+        })?;
+
+        Ok(AsyncProxyServer {
+            listener: Listener::Unix(listener, path.to_path_buf()),
+            local_addr: SocketAddr::from(([0, 0, 0, 0], 0)),
+            request_timeout: DEFAULT_PROXY_REQUEST_TIMEOUT,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            auth_token: None,
+            rate_limit: None,
+            throttled_count: AtomicU64::new(0),
+            link_failsafe: None,
+            link_loss_count: AtomicU64::new(0),
+            last_exchange: Mutex::new(None),
+            events: Arc::new(LogEvents),
+            simulation: None,
         })
     }
 
+    /// Sets how long a forwarded request may take before the client is sent an
+    /// error response with [ErrorDetail::Timeout].
+    ///
+    /// Keeps one stuck simulator call from holding a client connection forever;
+    /// the connection stays open for the next request. The bridge drops the
+    /// timed-out call, which for [AsyncLocalBridge] closes its simulator
+    /// connection rather than returning it to the pool. Defaults to
+    /// [DEFAULT_PROXY_REQUEST_TIMEOUT].
+    ///
+    /// [ErrorDetail::Timeout]: crate::bridge::remote::ErrorDetail::Timeout
+    #[must_use]
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Sets the largest request, in bytes, accepted from a client. Defaults to
+    /// [DEFAULT_MAX_FRAME_SIZE].
+    ///
+    /// A client sending a longer length prefix is disconnected before anything
+    /// is allocated for the request.
+    #[must_use]
+    pub fn max_frame_size(mut self, bytes: usize) -> Self {
+        self.max_frame_size = bytes;
+        self
+    }
+
+    /// Requires clients to present `token` before they may send anything but
+    /// the version handshake. Disabled by default.
+    ///
+    /// Clients set the same token with
+    /// [RealFlightRemoteBridgeBuilder::auth_token](crate::RealFlightRemoteBridgeBuilder::auth_token).
+    /// A client with a missing or wrong token is answered with an error and
+    /// disconnected. The token travels in plain text, so it keeps other users
+    /// of a trusted network from flying the aircraft, not eavesdroppers out.
+    #[must_use]
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Limits how fast each client may send requests for the simulator.
+    /// Disabled by default.
+    ///
+    /// Keeps a client spinning in a tight loop from starving the simulator.
+    /// Requests over the limit are answered with [ResponseStatus::Error] and
+    /// [ErrorDetail::Throttled] and counted in
+    /// [throttled_count](Self::throttled_count); the handshake, pings and
+    /// authentication are not limited.
+    ///
+    /// [ResponseStatus::Error]: crate::bridge::remote::ResponseStatus::Error
+    /// [ErrorDetail::Throttled]: crate::bridge::remote::ErrorDetail::Throttled
+    #[must_use]
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Commands a failsafe when a client flying the aircraft goes silent.
+    /// Disabled by default, so the aircraft keeps flying the last inputs.
+    ///
+    /// A client is flying once it has sent
+    /// [RequestType::ExchangeData](crate::bridge::remote::RequestType::ExchangeData),
+    /// until it sends `EnableRC`. If no request for the simulator arrives
+    /// from it for `link_timeout`, whether it is still connected and pinging
+    /// or has dropped, the
+    /// server sends the failsafe frame to the simulator, optionally restores
+    /// the RC device, reports [ProxyEvents::on_link_lost] and counts the
+    /// event in [link_loss_count](Self::link_loss_count). No failsafe is sent
+    /// if any client has exchanged data since the silent one's last request, as
+    /// after a reconnect on a new connection. A client that is
+    /// still connected takes control back with its next exchange.
+    #[must_use]
+    pub fn link_failsafe(mut self, failsafe: LinkFailsafe) -> Self {
+        self.link_failsafe = Some(failsafe);
+        self
+    }
+
+    /// Sets the callbacks told about each client's connection, requests and
+    /// disconnection. Defaults to [LogEvents], which logs them.
+    #[must_use]
+    pub fn events(mut self, events: Arc<dyn ProxyEvents>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Returns how many requests the [rate limit](Self::rate_limit) has
+    /// dropped, across all clients.
+    pub fn throttled_count(&self) -> u64 {
+        self.throttled_count.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many times the [link failsafe](Self::link_failsafe) has
+    /// been commanded, across all clients.
+    pub fn link_loss_count(&self) -> u64 {
+        self.link_loss_count.load(Ordering::Relaxed)
+    }
+
     /// Returns the local address the server is bound to.
+    ///
+    /// A server from [bind_uds](Self::bind_uds) has no address and returns
+    /// `0.0.0.0:0`; see [socket_path](Self::socket_path).
     pub fn local_addr(&self) -> SocketAddr {
         self.local_addr
     }
 
+    /// Returns the path of the Unix domain socket, for a server from
+    /// [bind_uds](Self::bind_uds).
+    #[cfg(unix)]
+    pub fn socket_path(&self) -> Option<&Path> {
+        match &self.listener {
+            Listener::Unix(_, path) => Some(path),
+            Listener::Tcp(_) => None,
+        }
+    }
+
     /// Runs the server until the cancellation token is triggered.
     ///
     /// # Arguments
@@ -62,19 +342,31 @@ impl AsyncProxyServer {
     /// # Returns
     /// A `Result` indicating success or an error.
     pub async fn run(&self, cancel: CancellationToken) -> Result<(), BridgeError> {
+        if let Some(model) = self.simulation {
+            info!("Serving a simulated {:?} aircraft", model);
+            return self
+                .run_with_bridge(&SimulatedBridge::new(model), cancel)
+                .await;
+        }
         let bridge = AsyncLocalBridge::new().await?;
         self.run_with_bridge(&bridge, cancel).await
     }
 
     /// Runs the server with a custom bridge implementation.
     ///
-    /// This is useful for testing with mock bridges.
-    pub async fn run_with_bridge<B: AsyncBridge>(
+    /// This is useful for testing with mock bridges. Trait objects are accepted too,
+    /// e.g. `&dyn` [DynAsyncBridge](crate::bridge::DynAsyncBridge).
+    pub async fn run_with_bridge<B: AsyncBridge + ?Sized>(
         &self,
         bridge: &B,
         cancel: CancellationToken,
     ) -> Result<(), BridgeError> {
-        info!("Async server listening on {}", self.local_addr);
+        info!("Async server listening on {}", self.endpoint());
+
+        // Clients are polled together on this task, so they can share the
+        // borrowed bridge and subscribers can follow the controlling client
+        let (states, _) = broadcast::channel(BROADCAST_CAPACITY);
+        let mut clients = FuturesUnordered::new();
 
         loop {
             tokio::select! {
@@ -82,16 +374,15 @@ impl AsyncProxyServer {
                     info!("Server shutdown requested");
                     break;
                 }
+                Some(result) = clients.next(), if !clients.is_empty() => {
+                    if let Err(e) = result {
+                        error!("Error handling client: {}", e);
+                    }
+                }
                 result = self.listener.accept() => {
                     match result {
-                        Ok((stream, addr)) => {
-                            info!("New client connected: {}", addr);
-                            let client_cancel = cancel.clone();
-                            // For now, handle clients serially like the sync version
-                            // Could be changed to spawn tasks for concurrent clients
-                            if let Err(e) = handle_client(stream, bridge, client_cancel).await {
-                                error!("Error handling client: {}", e);
-                            }
+                        Ok(accepted) => {
+                            clients.push(self.serve(accepted, bridge, &states, cancel.clone()));
                         }
                         Err(e) => {
                             error!("Failed to accept connection: {}", e);
@@ -103,4 +394,75 @@ impl AsyncProxyServer {
 
         Ok(())
     }
+
+    /// Serves one accepted connection until the client leaves.
+    fn serve<'a, B: AsyncBridge + ?Sized>(
+        &'a self,
+        accepted: Accepted,
+        bridge: &'a B,
+        states: &'a broadcast::Sender<SimulatorState>,
+        cancel: CancellationToken,
+    ) -> Pin<Box<dyn Future<Output = Result<(), BridgeError>> + Send + 'a>> {
+        let options = ClientOptions {
+            request_timeout: self.request_timeout,
+            max_frame_size: self.max_frame_size,
+            auth_token: self.auth_token.as_deref(),
+            rate_limit: self.rate_limit,
+            throttled_count: &self.throttled_count,
+            link_failsafe: self.link_failsafe.as_ref(),
+            link_loss_count: &self.link_loss_count,
+            last_exchange: &self.last_exchange,
+            events: &*self.events,
+        };
+        match accepted {
+            Accepted::Tcp(stream, addr) => Box::pin(async move {
+                stream.set_nodelay(true)?;
+                handle_client(stream, addr, bridge, options, states, cancel).await
+            }),
+            // Unix domain socket peers have no address
+            #[cfg(unix)]
+            Accepted::Unix(stream) => Box::pin(handle_client(
+                stream,
+                self.local_addr,
+                bridge,
+                options,
+                states,
+                cancel,
+            )),
+        }
+    }
+
+    /// Address or socket path the server listens on, for logging.
+    fn endpoint(&self) -> String {
+        match &self.listener {
+            Listener::Tcp(_) => self.local_addr.to_string(),
+            #[cfg(unix)]
+            Listener::Unix(_, path) => path.display().to_string(),
+        }
+    }
+}
+
+impl Listener {
+    async fn accept(&self) -> std::io::Result<Accepted> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, addr) = listener.accept().await?;
+                Ok(Accepted::Tcp(stream, addr))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener, _) => {
+                let (stream, _) = listener.accept().await?;
+                Ok(Accepted::Unix(stream))
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Listener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
 }
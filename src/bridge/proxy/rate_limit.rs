@@ -0,0 +1,41 @@
+//! Per-client request rate limiting for the proxy server.
+
+use std::time::Instant;
+
+use super::RateLimit;
+
+/// Token bucket holding up to `burst` requests, refilled at
+/// `max_requests_per_second`.
+pub(super) struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket, so a client may start with a burst.
+    pub(super) fn new(limit: RateLimit, now: Instant) -> Self {
+        let capacity = f64::from(limit.burst.max(1));
+        TokenBucket {
+            capacity,
+            rate: f64::from(limit.max_requests_per_second),
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    /// Takes a token for one request, returning `false` if none is left.
+    pub(super) fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
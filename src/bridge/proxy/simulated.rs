@@ -0,0 +1,264 @@
+//! Kinematic aircraft model the proxy can serve in place of RealFlight, so
+//! clients can be exercised end to end without a simulator.
+
+use std::sync::Mutex;
+
+use crate::decoders::{
+    to_acceleration, to_angle, to_angular_velocity, to_length, to_time, to_velocity,
+};
+use crate::{AsyncBridge, BridgeError, ControlInputs, SimulatorState};
+
+/// Simulated time that passes with each exchange.
+const STEP: f32 = 0.01;
+/// Standard gravity (m/s²)
+const GRAVITY: f32 = 9.81;
+/// Airspeed the fixed-wing holds at half throttle (m/s)
+const TRIM_AIRSPEED: f32 = 20.0;
+/// Altitude the fixed-wing starts, and is reset, at (m)
+const TRIM_ALTITUDE: f32 = 100.0;
+
+/// Aircraft flown by a proxy from
+/// [new_simulated](super::AsyncProxyServer::new_simulated).
+///
+/// Both models read the standard channel mapping of [ControlInputs]: aileron,
+/// elevator, throttle and rudder on channels 1 to 4.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SimModel {
+    /// Airplane in trimmed level flight at 100 m and 20 m/s with the sticks
+    /// centered and half throttle. More throttle speeds it up and it climbs;
+    /// bank turns it.
+    #[default]
+    FixedWing,
+    /// Multirotor standing on the ground. Throttle above half climbs, below
+    /// half descends; the sticks tilt it to fly horizontally.
+    Hover,
+}
+
+/// Bridge answering exchanges from a [SimModel] instead of the simulator.
+pub(super) struct SimulatedBridge {
+    aircraft: Mutex<Aircraft>,
+}
+
+impl SimulatedBridge {
+    pub(super) fn new(model: SimModel) -> Self {
+        SimulatedBridge {
+            aircraft: Mutex::new(Aircraft::new(model)),
+        }
+    }
+}
+
+impl AsyncBridge for SimulatedBridge {
+    async fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        let mut aircraft = self.aircraft.lock().unwrap();
+        aircraft.step(control);
+        Ok(aircraft.state(control))
+    }
+
+    async fn enable_rc(&self) -> Result<(), BridgeError> {
+        self.aircraft.lock().unwrap().controller_active = false;
+        Ok(())
+    }
+
+    async fn disable_rc(&self) -> Result<(), BridgeError> {
+        self.aircraft.lock().unwrap().controller_active = true;
+        Ok(())
+    }
+
+    async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        let mut aircraft = self.aircraft.lock().unwrap();
+        *aircraft = Aircraft {
+            controller_active: aircraft.controller_active,
+            ..Aircraft::new(aircraft.model)
+        };
+        Ok(())
+    }
+}
+
+/// Point-mass state of the simulated aircraft. Angles are in degrees.
+struct Aircraft {
+    model: SimModel,
+    time: f32,
+    north: f32,
+    east: f32,
+    altitude: f32,
+    /// Ground velocity north, east and up (m/s)
+    velocity: [f32; 3],
+    /// Acceleration north, east and up over the last step (m/s²)
+    acceleration: [f32; 3],
+    /// Speed along the flight path; fixed-wing only
+    airspeed: f32,
+    heading: f32,
+    pitch: f32,
+    roll: f32,
+    /// Pitch, roll and yaw rates over the last step (deg/s)
+    rates: [f32; 3],
+    controller_active: bool,
+}
+
+impl Aircraft {
+    fn new(model: SimModel) -> Self {
+        let (altitude, airspeed) = match model {
+            SimModel::FixedWing => (TRIM_ALTITUDE, TRIM_AIRSPEED),
+            SimModel::Hover => (0.0, 0.0),
+        };
+        Aircraft {
+            model,
+            time: 0.0,
+            north: 0.0,
+            east: 0.0,
+            altitude,
+            velocity: [airspeed, 0.0, 0.0],
+            acceleration: [0.0; 3],
+            airspeed,
+            heading: 0.0,
+            pitch: 0.0,
+            roll: 0.0,
+            rates: [0.0; 3],
+            controller_active: false,
+        }
+    }
+
+    /// Advances the model by one [STEP] under `control`.
+    fn step(&mut self, control: &ControlInputs) {
+        let stick = |channel: usize| (control.channels[channel].clamp(0.0, 1.0) - 0.5) * 2.0;
+        let (aileron, elevator, rudder) = (stick(0), stick(1), stick(3));
+        let throttle = control.channels[2].clamp(0.0, 1.0);
+
+        // Attitude settles on the angle the sticks command
+        let (pitch_target, roll_target, yaw_rate) = match self.model {
+            SimModel::FixedWing => {
+                // Coordinated turn from the bank angle, plus rudder
+                let turn = GRAVITY * self.roll.to_radians().tan() / self.airspeed.max(1.0);
+                (
+                    elevator * 20.0,
+                    aileron * 45.0,
+                    turn.to_degrees() + rudder * 10.0,
+                )
+            }
+            SimModel::Hover => (-elevator * 30.0, aileron * 30.0, rudder * 90.0),
+        };
+        let pitch_rate = (pitch_target - self.pitch) * 2.0;
+        let roll_rate = (roll_target - self.roll) * 2.0;
+        self.pitch += pitch_rate * STEP;
+        self.roll += roll_rate * STEP;
+        self.heading = (self.heading + yaw_rate * STEP).rem_euclid(360.0);
+        self.rates = [pitch_rate, roll_rate, yaw_rate];
+
+        let (sin_heading, cos_heading) = self.heading.to_radians().sin_cos();
+        let previous = self.velocity;
+        match self.model {
+            SimModel::FixedWing => {
+                // Thrust balances drag at trim; climbing trades speed for height
+                let pitch = self.pitch.to_radians();
+                let drag = 4.0 * (self.airspeed / TRIM_AIRSPEED).powi(2);
+                let thrust = 8.0 * throttle;
+                self.airspeed += (thrust - drag - GRAVITY * pitch.sin()) * STEP;
+                self.airspeed = self.airspeed.max(0.0);
+
+                // Extra speed makes extra lift
+                let lift = 5.0 * ((self.airspeed / TRIM_AIRSPEED).powi(2) - 1.0);
+                let horizontal = self.airspeed * pitch.cos();
+                self.velocity = [
+                    horizontal * cos_heading,
+                    horizontal * sin_heading,
+                    self.airspeed * pitch.sin() + lift,
+                ];
+            }
+            SimModel::Hover => {
+                // Half throttle holds altitude; tilt pushes it over the ground
+                let forward = -GRAVITY * self.pitch.to_radians().tan();
+                let right = GRAVITY * self.roll.to_radians().tan();
+                let accel = [
+                    forward * cos_heading - right * sin_heading,
+                    forward * sin_heading + right * cos_heading,
+                    GRAVITY * (throttle * 2.0 - 1.0),
+                ];
+                for (velocity, accel) in self.velocity.iter_mut().zip(accel) {
+                    *velocity += (accel - 0.5 * *velocity) * STEP;
+                }
+            }
+        }
+
+        self.north += self.velocity[0] * STEP;
+        self.east += self.velocity[1] * STEP;
+        self.altitude += self.velocity[2] * STEP;
+        if self.altitude <= 0.0 {
+            self.altitude = 0.0;
+            self.velocity[2] = self.velocity[2].max(0.0);
+            if self.model == SimModel::Hover {
+                // Standing on its skids, tilting does not slide it
+                self.velocity[0] = 0.0;
+                self.velocity[1] = 0.0;
+            }
+        }
+        for ((acceleration, velocity), previous) in self
+            .acceleration
+            .iter_mut()
+            .zip(self.velocity)
+            .zip(previous)
+        {
+            *acceleration = (velocity - previous) / STEP;
+        }
+        self.time += STEP;
+    }
+
+    /// Reports the model as RealFlight would, echoing the inputs applied.
+    fn state(&self, control: &ControlInputs) -> SimulatorState {
+        let [north, east, up] = self.velocity;
+        let groundspeed = north.hypot(east);
+        let airspeed = match self.model {
+            SimModel::FixedWing => self.airspeed,
+            SimModel::Hover => groundspeed.hypot(up),
+        };
+        let [pitch_rate, roll_rate, yaw_rate] = self.rates;
+        let [x, y, z, w] = quaternion(self.roll, self.pitch, self.heading);
+        let throttle = control.channels[2].clamp(0.0, 1.0);
+
+        SimulatorState {
+            previous_inputs: control.clone(),
+            airspeed: to_velocity(airspeed),
+            altitude_asl: to_length(self.altitude),
+            altitude_agl: to_length(self.altitude),
+            groundspeed: to_velocity(groundspeed),
+            pitch_rate: to_angular_velocity(pitch_rate),
+            roll_rate: to_angular_velocity(roll_rate),
+            yaw_rate: to_angular_velocity(yaw_rate),
+            azimuth: to_angle(self.heading),
+            inclination: to_angle(self.pitch),
+            roll: to_angle(self.roll),
+            aircraft_position_x: to_length(self.north),
+            aircraft_position_y: to_length(self.east),
+            velocity_world_u: to_velocity(north),
+            velocity_world_v: to_velocity(east),
+            velocity_world_w: to_velocity(-up),
+            velocity_body_u: to_velocity(airspeed),
+            acceleration_world_ax: to_acceleration(self.acceleration[0]),
+            acceleration_world_ay: to_acceleration(self.acceleration[1]),
+            acceleration_world_az: to_acceleration(-self.acceleration[2]),
+            prop_rpm: throttle * 10_000.0,
+            an_engine_is_running: throttle > 0.0,
+            is_touching_ground: self.altitude <= 0.0,
+            current_physics_time: to_time(self.time),
+            current_physics_speed_multiplier: 1.0,
+            orientation_quaternion_x: x,
+            orientation_quaternion_y: y,
+            orientation_quaternion_z: z,
+            orientation_quaternion_w: w,
+            flight_axis_controller_is_active: self.controller_active,
+            ..SimulatorState::default()
+        }
+    }
+}
+
+/// Quaternion `[x, y, z, w]` for roll, pitch and yaw in degrees.
+fn quaternion(roll: f32, pitch: f32, yaw: f32) -> [f32; 4] {
+    let (sr, cr) = (roll.to_radians() / 2.0).sin_cos();
+    let (sp, cp) = (pitch.to_radians() / 2.0).sin_cos();
+    let (sy, cy) = (yaw.to_radians() / 2.0).sin_cos();
+    [
+        sr * cp * cy - cr * sp * sy,
+        cr * sp * cy + sr * cp * sy,
+        cr * cp * sy - sr * sp * cy,
+        cr * cp * cy + sr * sp * sy,
+    ]
+}
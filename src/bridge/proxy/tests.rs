@@ -1,6 +1,8 @@
 use super::*;
 use crate::ControlInputs;
-use crate::bridge::remote::{Request, RequestType, Response, ResponseStatus};
+use crate::bridge::remote::{
+    ErrorDetail, MessageKind, ProtocolInfo, Request, RequestType, Response, ResponseStatus,
+};
 use postcard::{from_bytes, to_stdvec};
 use std::io::{Read, Write};
 use std::sync::Arc;
@@ -146,6 +148,70 @@ async fn send_request_async(addr: String, request: Request) -> Response {
     .unwrap()
 }
 
+/// Sends each request on one connection, collecting responses until the
+/// server hangs up.
+async fn send_requests_async(addr: String, requests: Vec<Request>) -> Vec<Response> {
+    tokio::task::spawn_blocking(move || {
+        let mut stream = std::net::TcpStream::connect(&addr).unwrap();
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+
+        let mut responses = Vec::new();
+        for request in requests {
+            let request_bytes = to_stdvec(&request).unwrap();
+            let length_bytes = (request_bytes.len() as u32).to_be_bytes();
+            if stream.write_all(&length_bytes).is_err() || stream.write_all(&request_bytes).is_err()
+            {
+                break;
+            }
+
+            let mut length_buffer = [0u8; 4];
+            if stream.read_exact(&mut length_buffer).is_err() {
+                break;
+            }
+            let response_length = u32::from_be_bytes(length_buffer) as usize;
+            let mut response_buffer = vec![0u8; response_length];
+            stream.read_exact(&mut response_buffer).unwrap();
+            responses.push(from_bytes(&response_buffer).unwrap());
+        }
+        responses
+    })
+    .await
+    .unwrap()
+}
+
+/// Writes all requests in one go, then reads a response to each.
+async fn flood_async(addr: String, requests: Vec<Request>) -> Vec<Response> {
+    tokio::task::spawn_blocking(move || {
+        let mut stream = std::net::TcpStream::connect(&addr).unwrap();
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+
+        let mut frames = Vec::new();
+        for request in &requests {
+            let request_bytes = to_stdvec(request).unwrap();
+            frames.extend_from_slice(&(request_bytes.len() as u32).to_be_bytes());
+            frames.extend_from_slice(&request_bytes);
+        }
+        stream.write_all(&frames).unwrap();
+
+        (0..requests.len())
+            .map(|_| {
+                let mut length_buffer = [0u8; 4];
+                stream.read_exact(&mut length_buffer).unwrap();
+                let response_length = u32::from_be_bytes(length_buffer) as usize;
+                let mut response_buffer = vec![0u8; response_length];
+                stream.read_exact(&mut response_buffer).unwrap();
+                from_bytes(&response_buffer).unwrap()
+            })
+            .collect()
+    })
+    .await
+    .unwrap()
+}
+
 #[tokio::test]
 async fn routes_enable_rc_to_bridge() {
     let server = AsyncProxyServer::new("127.0.0.1:0").await.unwrap();
@@ -157,11 +223,10 @@ async fn routes_enable_rc_to_bridge() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
     let request = Request {
         request_type: RequestType::EnableRC,
         payload: None,
+        id: 0,
     };
     let response = send_request_async(addr, request).await;
 
@@ -172,6 +237,100 @@ async fn routes_enable_rc_to_bridge() {
     let _ = handle.await;
 }
 
+#[tokio::test]
+async fn echoes_request_id_in_response() {
+    let server = AsyncProxyServer::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let bridge = StubBridge::new();
+
+    let server_cancel = cancel.clone();
+    let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
+
+    let request = Request {
+        request_type: RequestType::ResetAircraft,
+        payload: None,
+        id: 42,
+    };
+    let response = send_request_async(addr, request).await;
+
+    assert_eq!(response.id, 42);
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
+#[tokio::test]
+async fn answers_hello_with_its_version() {
+    let server = AsyncProxyServer::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let bridge = StubBridge::new();
+
+    let server_cancel = cancel.clone();
+    let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
+
+    let client = ProtocolInfo {
+        features: u32::MAX,
+        ..ProtocolInfo::current()
+    };
+    let request = Request {
+        request_type: RequestType::Hello(client),
+        payload: None,
+        id: 1,
+    };
+    let response = send_request_async(addr.clone(), request).await;
+
+    assert!(matches!(response.status, ResponseStatus::Success));
+    let info = response.info.unwrap();
+    assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.features, ProtocolInfo::FEATURES);
+
+    // A client from another major version is told it cannot continue
+    let client = ProtocolInfo {
+        major: ProtocolInfo::MAJOR + 1,
+        ..ProtocolInfo::current()
+    };
+    let request = Request {
+        request_type: RequestType::Hello(client),
+        payload: None,
+        id: 2,
+    };
+    let response = send_request_async(addr, request).await;
+
+    assert!(matches!(response.status, ResponseStatus::Error));
+    assert_eq!(response.info.unwrap().major, ProtocolInfo::MAJOR);
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
+#[tokio::test]
+async fn answers_ping_without_calling_bridge() {
+    let server = AsyncProxyServer::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let bridge = StubBridge::new();
+    let enable_count = bridge.enable_rc_count.clone();
+
+    let server_cancel = cancel.clone();
+    let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
+
+    let request = Request {
+        request_type: RequestType::Ping,
+        payload: None,
+        id: 7,
+    };
+    let response = send_request_async(addr, request).await;
+
+    assert!(matches!(response.status, ResponseStatus::Success));
+    assert_eq!(response.id, 7);
+    assert_eq!(enable_count.load(Ordering::SeqCst), 0);
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
 #[tokio::test]
 async fn routes_disable_rc_to_bridge() {
     let server = AsyncProxyServer::new("127.0.0.1:0").await.unwrap();
@@ -183,11 +342,10 @@ async fn routes_disable_rc_to_bridge() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
     let request = Request {
         request_type: RequestType::DisableRC,
         payload: None,
+        id: 0,
     };
     let response = send_request_async(addr, request).await;
 
@@ -209,11 +367,10 @@ async fn routes_reset_aircraft_to_bridge() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
     let request = Request {
         request_type: RequestType::ResetAircraft,
         payload: None,
+        id: 0,
     };
     let response = send_request_async(addr, request).await;
 
@@ -235,11 +392,10 @@ async fn routes_exchange_data_to_bridge() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
     let request = Request {
         request_type: RequestType::ExchangeData,
         payload: Some(ControlInputs::default()),
+        id: 0,
     };
     let response = send_request_async(addr, request).await;
 
@@ -261,11 +417,10 @@ async fn exchange_data_without_payload_returns_error() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
     let request = Request {
         request_type: RequestType::ExchangeData,
         payload: None, // Missing required payload
+        id: 0,
     };
     let response = send_request_async(addr, request).await;
 
@@ -285,8 +440,6 @@ async fn handles_client_disconnect() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
     // Connect and immediately disconnect using spawn_blocking
     let addr_clone = addr.clone();
     tokio::task::spawn_blocking(move || {
@@ -343,11 +496,10 @@ async fn enable_rc_error_returns_error_response() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
     let request = Request {
         request_type: RequestType::EnableRC,
         payload: None,
+        id: 0,
     };
     let response = send_request_async(addr, request).await;
 
@@ -367,11 +519,10 @@ async fn disable_rc_error_returns_error_response() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
     let request = Request {
         request_type: RequestType::DisableRC,
         payload: None,
+        id: 0,
     };
     let response = send_request_async(addr, request).await;
 
@@ -391,11 +542,10 @@ async fn reset_aircraft_error_returns_error_response() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
     let request = Request {
         request_type: RequestType::ResetAircraft,
         payload: None,
+        id: 0,
     };
     let response = send_request_async(addr, request).await;
 
@@ -415,15 +565,114 @@ async fn exchange_data_error_returns_error_response() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let request = Request {
+        request_type: RequestType::ExchangeData,
+        payload: Some(ControlInputs::default()),
+        id: 0,
+    };
+    let response = send_request_async(addr, request).await;
+
+    assert!(matches!(response.status, ResponseStatus::Error));
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
+/// Bridge whose calls never complete, like a simulator that stopped responding.
+struct HangingBridge;
+
+impl AsyncBridge for HangingBridge {
+    async fn exchange_data(
+        &self,
+        _control: &ControlInputs,
+    ) -> Result<crate::SimulatorState, BridgeError> {
+        std::future::pending().await
+    }
+
+    async fn enable_rc(&self) -> Result<(), BridgeError> {
+        std::future::pending().await
+    }
+
+    async fn disable_rc(&self) -> Result<(), BridgeError> {
+        std::future::pending().await
+    }
+
+    async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        std::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn stuck_exchange_returns_error_after_request_timeout() {
+    let server = AsyncProxyServer::new("127.0.0.1:0")
+        .await
+        .unwrap()
+        .request_timeout(std::time::Duration::from_millis(50));
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let bridge = HangingBridge;
+
+    let server_cancel = cancel.clone();
+    let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
     let request = Request {
         request_type: RequestType::ExchangeData,
         payload: Some(ControlInputs::default()),
+        id: 0,
     };
+    let start = std::time::Instant::now();
     let response = send_request_async(addr, request).await;
 
     assert!(matches!(response.status, ResponseStatus::Error));
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn request_timeout_keeps_connection_open() {
+    let timeout = std::time::Duration::from_secs(2);
+    let server = AsyncProxyServer::new("127.0.0.1:0")
+        .await
+        .unwrap()
+        .request_timeout(timeout);
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+
+    let server_cancel = cancel.clone();
+    let handle =
+        tokio::spawn(async move { server.run_with_bridge(&HangingBridge, server_cancel).await });
+
+    let mut stream = tokio::net::TcpStream::connect(&addr).await.unwrap();
+    let start = tokio::time::Instant::now();
+    write_request(&mut stream, request(RequestType::EnableRC, 1)).await;
+    write_request(
+        &mut stream,
+        Request {
+            request_type: RequestType::ExchangeData,
+            payload: Some(ControlInputs::default()),
+            id: 2,
+        },
+    )
+    .await;
+    write_request(&mut stream, request(RequestType::Ping, 3)).await;
+
+    // Unbounded reads: the paused clock would jump straight to a client-side
+    // timeout while the server is still reading
+    for id in [1, 2] {
+        let response = receive(&mut stream).await;
+        assert_eq!(response.id, id);
+        assert!(matches!(response.status, ResponseStatus::Error));
+        assert_eq!(response.detail, Some(ErrorDetail::Timeout));
+    }
+    // Each stuck call waited out the timeout in turn
+    assert!(start.elapsed() >= timeout * 2);
+
+    // The connection survived the timeouts
+    let response = receive(&mut stream).await;
+    assert_eq!(response.id, 3);
+    assert!(matches!(response.status, ResponseStatus::Success));
 
     cancel.cancel();
     let _ = handle.await;
@@ -440,8 +689,6 @@ async fn malformed_request_continues_handling() {
     let server_cancel = cancel.clone();
     let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
 
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
     // Send malformed data, then a valid request
     tokio::task::spawn_blocking({
         let addr = addr.clone();
@@ -462,6 +709,7 @@ async fn malformed_request_continues_handling() {
             let request = Request {
                 request_type: RequestType::EnableRC,
                 payload: None,
+                id: 0,
             };
             let request_bytes = to_stdvec(&request).unwrap();
             let length_bytes = (request_bytes.len() as u32).to_be_bytes();
@@ -489,3 +737,1036 @@ async fn malformed_request_continues_handling() {
     cancel.cancel();
     let _ = handle.await;
 }
+
+#[tokio::test]
+async fn reconnecting_clients_share_one_bridge() {
+    let server = AsyncProxyServer::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let bridge = StubBridge::new();
+    let exchange_count = bridge.exchange_count.clone();
+
+    let server_cancel = cancel.clone();
+    let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
+
+    // Each call opens and closes its own connection
+    for _ in 0..3 {
+        let request = Request {
+            request_type: RequestType::ExchangeData,
+            payload: Some(ControlInputs::default()),
+            id: 0,
+        };
+        let response = send_request_async(addr.clone(), request).await;
+        assert!(matches!(response.status, ResponseStatus::Success));
+    }
+
+    // All three clients were served by the one bridge
+    assert_eq!(exchange_count.load(Ordering::SeqCst), 3);
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
+#[tokio::test]
+async fn oversized_request_drops_client() {
+    let server = AsyncProxyServer::new("127.0.0.1:0")
+        .await
+        .unwrap()
+        .max_frame_size(64);
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let bridge = StubBridge::new();
+    let enable_count = bridge.enable_rc_count.clone();
+
+    let server_cancel = cancel.clone();
+    let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
+
+    // Announce a huge request; the server hangs up instead of reading it
+    let closed = tokio::task::spawn_blocking({
+        let addr = addr.clone();
+        move || {
+            let mut stream = std::net::TcpStream::connect(&addr).unwrap();
+            stream
+                .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+                .unwrap();
+            stream.write_all(&u32::MAX.to_be_bytes()).unwrap();
+            stream.flush().unwrap();
+
+            let mut buffer = [0u8; 1];
+            matches!(stream.read(&mut buffer), Ok(0) | Err(_))
+        }
+    })
+    .await
+    .unwrap();
+    assert!(closed);
+
+    // The next client is served as usual
+    let request = Request {
+        request_type: RequestType::EnableRC,
+        payload: None,
+        id: 0,
+    };
+    let response = send_request_async(addr, request).await;
+    assert!(matches!(response.status, ResponseStatus::Success));
+    assert_eq!(enable_count.load(Ordering::SeqCst), 1);
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
+// ========================================================================
+// Dynamic Bridge Tests
+// ========================================================================
+
+mod dyn_bridge {
+    // AsyncBridge is deliberately not imported so method calls resolve to DynAsyncBridge
+    use super::{AsyncProxyServer, CancellationToken, Ordering, StubBridge};
+    use crate::bridge::DynAsyncBridge;
+    use crate::{AsyncLocalBridge, AsyncRemoteBridge, ControlInputs};
+
+    async fn select_bridge(
+        use_remote: bool,
+        local_port: u16,
+        proxy_addr: &str,
+    ) -> Box<dyn DynAsyncBridge> {
+        if use_remote {
+            Box::new(AsyncRemoteBridge::new(proxy_addr).await.unwrap())
+        } else {
+            let addr = format!("127.0.0.1:{}", local_port).parse().unwrap();
+            Box::new(
+                AsyncLocalBridge::builder()
+                    .addr(addr)
+                    .connect_timeout(std::time::Duration::from_secs(1))
+                    .build()
+                    .await
+                    .unwrap(),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn dyn_bridge_selected_at_runtime() {
+        let local_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let _soap_server =
+            crate::tests::soap_stub::Server::new(local_port, vec!["return-data-200".to_string()]);
+
+        let server = AsyncProxyServer::new("127.0.0.1:0").await.unwrap();
+        let proxy_addr = server.local_addr().to_string();
+        let cancel = CancellationToken::new();
+        let stub = StubBridge::new();
+        let exchange_count = stub.exchange_count.clone();
+        let server_cancel = cancel.clone();
+        let handle =
+            tokio::spawn(async move { server.run_with_bridge(&stub, server_cancel).await });
+
+        for use_remote in [false, true] {
+            let bridge = select_bridge(use_remote, local_port, &proxy_addr).await;
+            let result = bridge.exchange_data(&ControlInputs::default()).await;
+            assert!(result.is_ok(), "expected Ok: {:?}", result);
+        }
+        assert_eq!(exchange_count.load(Ordering::SeqCst), 1);
+
+        cancel.cancel();
+        let _ = handle.await;
+    }
+}
+
+// ========================================================================
+// Authentication Tests
+// ========================================================================
+
+fn request(request_type: RequestType, id: u32) -> Request {
+    Request {
+        request_type,
+        payload: None,
+        id,
+    }
+}
+
+fn authenticate(token: &str, id: u32) -> Request {
+    let token = token.to_string();
+    request(RequestType::Authenticate { token }, id)
+}
+
+async fn start_with_token(token: &str) -> (String, Arc<AtomicUsize>, CancellationToken) {
+    let server = AsyncProxyServer::new("127.0.0.1:0")
+        .await
+        .unwrap()
+        .auth_token(token);
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let bridge = StubBridge::new();
+    let enable_count = bridge.enable_rc_count.clone();
+
+    let server_cancel = cancel.clone();
+    tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
+    (addr, enable_count, cancel)
+}
+
+#[tokio::test]
+async fn auth_token_rejects_missing_auth() {
+    let (addr, enable_count, cancel) = start_with_token("secret").await;
+
+    let requests = vec![
+        request(RequestType::EnableRC, 1),
+        request(RequestType::EnableRC, 2),
+    ];
+    let responses = send_requests_async(addr, requests).await;
+
+    // Answered with an error, then disconnected
+    assert_eq!(responses.len(), 1);
+    assert!(matches!(responses[0].status, ResponseStatus::Error));
+    assert_eq!(responses[0].id, 1);
+    assert_eq!(enable_count.load(Ordering::SeqCst), 0);
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn auth_token_rejects_wrong_token() {
+    let (addr, enable_count, cancel) = start_with_token("secret").await;
+
+    for token in ["secreT", "secret-but-longer", ""] {
+        let requests = vec![authenticate(token, 1), request(RequestType::EnableRC, 2)];
+        let responses = send_requests_async(addr.clone(), requests).await;
+
+        assert_eq!(responses.len(), 1, "token {:?}", token);
+        assert!(matches!(responses[0].status, ResponseStatus::Error));
+    }
+    assert_eq!(enable_count.load(Ordering::SeqCst), 0);
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn auth_token_admits_handshake_and_correct_token() {
+    let (addr, enable_count, cancel) = start_with_token("secret").await;
+
+    let requests = vec![
+        request(RequestType::Hello(ProtocolInfo::current()), 1),
+        authenticate("secret", 2),
+        request(RequestType::EnableRC, 3),
+    ];
+    let responses = send_requests_async(addr, requests).await;
+
+    assert_eq!(responses.len(), 3);
+    assert!(
+        responses
+            .iter()
+            .all(|response| matches!(response.status, ResponseStatus::Success))
+    );
+    assert_eq!(enable_count.load(Ordering::SeqCst), 1);
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn remote_bridges_present_auth_token() {
+    let (addr, enable_count, cancel) = start_with_token("secret").await;
+
+    let bridge = crate::AsyncRemoteBridge::builder(&addr)
+        .auth_token("secret")
+        .build()
+        .await
+        .unwrap();
+    crate::AsyncBridge::enable_rc(&bridge).await.unwrap();
+    drop(bridge);
+
+    let sync_addr = addr.clone();
+    tokio::task::spawn_blocking(move || {
+        let bridge = crate::RealFlightRemoteBridge::builder(&sync_addr)
+            .auth_token("secret")
+            .build()
+            .unwrap();
+        crate::RealFlightBridge::enable_rc(&bridge).unwrap();
+    })
+    .await
+    .unwrap();
+    assert_eq!(enable_count.load(Ordering::SeqCst), 2);
+
+    let result = crate::AsyncRemoteBridge::builder(&addr)
+        .auth_token("wrong")
+        .build()
+        .await;
+    assert!(matches!(result, Err(BridgeError::Initialization(_))));
+
+    let result = tokio::task::spawn_blocking(move || {
+        crate::RealFlightRemoteBridge::builder(&addr)
+            .auth_token("wrong")
+            .build()
+    })
+    .await
+    .unwrap();
+    assert!(matches!(result, Err(BridgeError::Initialization(_))));
+    cancel.cancel();
+}
+
+// ========================================================================
+// Rate Limit Tests
+// ========================================================================
+
+#[test]
+fn token_bucket_allows_burst_then_refills() {
+    let limit = RateLimit {
+        max_requests_per_second: 10,
+        burst: 10,
+    };
+    let start = std::time::Instant::now();
+    let mut bucket = rate_limit::TokenBucket::new(limit, start);
+
+    assert!((0..10).all(|_| bucket.try_take(start)));
+    assert!(!bucket.try_take(start));
+
+    // One token back every 100 ms
+    let later = start + std::time::Duration::from_millis(100);
+    assert!(bucket.try_take(later));
+    assert!(!bucket.try_take(later));
+
+    // Never more than the burst, however long the client was idle
+    let much_later = later + std::time::Duration::from_secs(60);
+    assert_eq!((0..20).filter(|_| bucket.try_take(much_later)).count(), 10);
+}
+
+#[tokio::test]
+async fn rate_limit_throttles_flooding_client() {
+    let limit = RateLimit {
+        max_requests_per_second: 10,
+        burst: 10,
+    };
+    let server = Arc::new(
+        AsyncProxyServer::new("127.0.0.1:0")
+            .await
+            .unwrap()
+            .rate_limit(limit),
+    );
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let bridge = StubBridge::new();
+    let exchange_count = bridge.exchange_count.clone();
+
+    let server_cancel = cancel.clone();
+    let running = Arc::clone(&server);
+    let handle = tokio::spawn(async move { running.run_with_bridge(&bridge, server_cancel).await });
+
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let mut requests: Vec<Request> = (0..100)
+        .map(|id| Request {
+            request_type: RequestType::ExchangeData,
+            payload: Some(ControlInputs::default()),
+            id,
+        })
+        .collect();
+    requests.push(request(RequestType::Ping, 100));
+    let responses = flood_async(addr, requests).await;
+    assert_eq!(responses.len(), 101);
+
+    let throttled = responses
+        .iter()
+        .filter(|response| response.detail == Some(ErrorDetail::Throttled))
+        .count();
+    // The burst passes, plus at most a token or two refilled during the flood
+    assert!((85..=90).contains(&throttled), "throttled {}", throttled);
+    assert_eq!(exchange_count.load(Ordering::SeqCst), 100 - throttled);
+    assert_eq!(server.throttled_count(), throttled as u64);
+
+    // Pings are never throttled
+    assert!(matches!(responses[100].status, ResponseStatus::Success));
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
+// ========================================================================
+// Broadcast Tests
+// ========================================================================
+
+/// Bridge whose state echoes the inputs, so each exchange is recognizable.
+struct EchoBridge;
+
+impl AsyncBridge for EchoBridge {
+    async fn exchange_data(
+        &self,
+        control: &ControlInputs,
+    ) -> Result<crate::SimulatorState, BridgeError> {
+        Ok(crate::SimulatorState {
+            previous_inputs: control.clone(),
+            ..Default::default()
+        })
+    }
+
+    async fn enable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    async fn disable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+}
+
+async fn write_request(stream: &mut tokio::net::TcpStream, request: Request) {
+    use tokio::io::AsyncWriteExt;
+
+    let request_bytes = to_stdvec(&request).unwrap();
+    let length_bytes = (request_bytes.len() as u32).to_be_bytes();
+    stream.write_all(&length_bytes).await.unwrap();
+    stream.write_all(&request_bytes).await.unwrap();
+}
+
+async fn read_response(stream: &mut tokio::net::TcpStream) -> Response {
+    tokio::time::timeout(std::time::Duration::from_secs(5), receive(stream))
+        .await
+        .unwrap()
+}
+
+/// Reads the next frame, waiting as long as it takes.
+async fn receive(stream: &mut tokio::net::TcpStream) -> Response {
+    use tokio::io::AsyncReadExt;
+
+    let mut length_buffer = [0u8; 4];
+    stream.read_exact(&mut length_buffer).await.unwrap();
+    let mut buffer = vec![0u8; u32::from_be_bytes(length_buffer) as usize];
+    stream.read_exact(&mut buffer).await.unwrap();
+    from_bytes(&buffer).unwrap()
+}
+
+async fn subscribe(addr: &str) -> tokio::net::TcpStream {
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    write_request(&mut stream, request(RequestType::Subscribe, 1)).await;
+    let response = read_response(&mut stream).await;
+    assert!(matches!(response.status, ResponseStatus::Success));
+    assert_eq!((response.kind, response.id), (MessageKind::Response, 1));
+    stream
+}
+
+#[tokio::test]
+async fn subscribers_receive_controller_state() {
+    let server = AsyncProxyServer::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+
+    let server_cancel = cancel.clone();
+    let handle =
+        tokio::spawn(async move { server.run_with_bridge(&EchoBridge, server_cancel).await });
+
+    let mut first = subscribe(&addr).await;
+    let mut second = subscribe(&addr).await;
+
+    let mut control = ControlInputs::default();
+    for step in 1..=2 {
+        control.channels[0] = step as f32 / 10.0;
+        let response = send_request_async(
+            addr.clone(),
+            Request {
+                request_type: RequestType::ExchangeData,
+                payload: Some(control.clone()),
+                id: 7,
+            },
+        )
+        .await;
+        assert_eq!((response.kind, response.id), (MessageKind::Response, 7));
+
+        for subscriber in [&mut first, &mut second] {
+            let broadcast = read_response(subscriber).await;
+            assert_eq!(broadcast.kind, MessageKind::StateBroadcast);
+            assert_eq!(broadcast.id, 0);
+            assert_eq!(broadcast.payload.unwrap().previous_inputs, control);
+        }
+    }
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
+#[tokio::test]
+async fn subscriber_cannot_control_simulator() {
+    let server = AsyncProxyServer::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let bridge = StubBridge::new();
+    let exchange_count = bridge.exchange_count.clone();
+    let enable_count = bridge.enable_rc_count.clone();
+
+    let server_cancel = cancel.clone();
+    let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
+
+    let mut subscriber = subscribe(&addr).await;
+    write_request(
+        &mut subscriber,
+        Request {
+            request_type: RequestType::ExchangeData,
+            payload: Some(ControlInputs::default()),
+            id: 2,
+        },
+    )
+    .await;
+    write_request(&mut subscriber, request(RequestType::EnableRC, 3)).await;
+    write_request(&mut subscriber, request(RequestType::Ping, 4)).await;
+
+    for (id, succeeds) in [(2, false), (3, false), (4, true)] {
+        let response = read_response(&mut subscriber).await;
+        assert_eq!((response.kind, response.id), (MessageKind::Response, id));
+        assert_eq!(matches!(response.status, ResponseStatus::Success), succeeds);
+    }
+    assert_eq!(exchange_count.load(Ordering::SeqCst), 0);
+    assert_eq!(enable_count.load(Ordering::SeqCst), 0);
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
+// ========================================================================
+// Event Tests
+// ========================================================================
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Connect(std::net::SocketAddr),
+    Request(std::net::SocketAddr, String, bool),
+    Disconnect(std::net::SocketAddr, u64, u64),
+}
+
+#[derive(Default)]
+struct RecordingEvents {
+    events: std::sync::Mutex<Vec<Event>>,
+}
+
+impl ProxyEvents for RecordingEvents {
+    fn on_connect(&self, addr: std::net::SocketAddr) {
+        self.events.lock().unwrap().push(Event::Connect(addr));
+    }
+
+    fn on_disconnect(&self, addr: std::net::SocketAddr, stats: ClientStats) {
+        let event = Event::Disconnect(addr, stats.requests, stats.errors);
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn on_request(
+        &self,
+        addr: std::net::SocketAddr,
+        request: &RequestType,
+        _latency: std::time::Duration,
+        ok: bool,
+    ) {
+        let event = Event::Request(addr, format!("{:?}", request), ok);
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+#[tokio::test]
+async fn reports_session_events() {
+    let recorder = Arc::new(RecordingEvents::default());
+    let server = AsyncProxyServer::new("127.0.0.1:0")
+        .await
+        .unwrap()
+        .events(recorder.clone());
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let bridge = StubBridge::new();
+
+    let server_cancel = cancel.clone();
+    let handle = tokio::spawn(async move { server.run_with_bridge(&bridge, server_cancel).await });
+
+    let requests = vec![
+        request(RequestType::Ping, 1),
+        request(RequestType::EnableRC, 2),
+        // Fails: an exchange needs a payload
+        request(RequestType::ExchangeData, 3),
+    ];
+    assert_eq!(send_requests_async(addr, requests).await.len(), 3);
+
+    // The server notices the hang-up shortly after
+    for _ in 0..50 {
+        if recorder.events.lock().unwrap().len() == 5 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    let events = std::mem::take(&mut *recorder.events.lock().unwrap());
+    let Some(Event::Connect(peer)) = events.first() else {
+        panic!("expected a connect event first, got {:?}", events);
+    };
+    let peer = *peer;
+    assert_eq!(peer.ip().to_string(), "127.0.0.1");
+    assert_eq!(
+        events[1..],
+        [
+            Event::Request(peer, "Ping".into(), true),
+            Event::Request(peer, "EnableRC".into(), true),
+            Event::Request(peer, "ExchangeData".into(), false),
+            Event::Disconnect(peer, 3, 1),
+        ]
+    );
+
+    cancel.cancel();
+    let _ = handle.await;
+}
+
+// ========================================================================
+// Link Failsafe Tests
+// ========================================================================
+
+/// Bridge that records the inputs it is sent.
+#[derive(Default)]
+struct RecordingBridge {
+    inputs: std::sync::Mutex<Vec<ControlInputs>>,
+    enable_rc_count: AtomicUsize,
+}
+
+impl RecordingBridge {
+    fn inputs(&self) -> Vec<ControlInputs> {
+        self.inputs.lock().unwrap().clone()
+    }
+}
+
+impl AsyncBridge for RecordingBridge {
+    async fn exchange_data(
+        &self,
+        control: &ControlInputs,
+    ) -> Result<crate::SimulatorState, BridgeError> {
+        self.inputs.lock().unwrap().push(control.clone());
+        Ok(crate::SimulatorState::default())
+    }
+
+    async fn enable_rc(&self) -> Result<(), BridgeError> {
+        self.enable_rc_count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn disable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+}
+
+fn full_throttle(id: u32) -> Request {
+    Request {
+        payload: Some(ControlInputs {
+            channels: [1.0; 12],
+        }),
+        ..request(RequestType::ExchangeData, id)
+    }
+}
+
+/// Starts a server with `failsafe` in front of a recording bridge.
+async fn start_with_failsafe(
+    failsafe: LinkFailsafe,
+) -> (
+    Arc<AsyncProxyServer>,
+    Arc<RecordingBridge>,
+    CancellationToken,
+) {
+    let server = AsyncProxyServer::new("127.0.0.1:0")
+        .await
+        .unwrap()
+        .link_failsafe(failsafe);
+    let server = Arc::new(server);
+    let bridge = Arc::new(RecordingBridge::default());
+    let cancel = CancellationToken::new();
+
+    let (task_server, task_bridge, task_cancel) = (server.clone(), bridge.clone(), cancel.clone());
+    tokio::spawn(async move {
+        task_server
+            .run_with_bridge(&*task_bridge, task_cancel)
+            .await
+    });
+    (server, bridge, cancel)
+}
+
+/// Waits until the bridge has received `count` frames.
+async fn wait_for_inputs(bridge: &RecordingBridge, count: usize) -> Vec<ControlInputs> {
+    for _ in 0..100 {
+        if bridge.inputs().len() >= count {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    bridge.inputs()
+}
+
+fn fast_failsafe() -> LinkFailsafe {
+    LinkFailsafe {
+        link_timeout: std::time::Duration::from_millis(50),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn default_link_failsafe() {
+    let failsafe = LinkFailsafe::default();
+
+    assert_eq!(failsafe.link_timeout, std::time::Duration::from_millis(500));
+    assert_eq!(failsafe.frame, ControlInputs::neutral());
+    assert_eq!(failsafe.repeat, 3);
+    assert!(failsafe.restore_rc);
+}
+
+#[tokio::test]
+async fn abrupt_disconnect_commands_failsafe() {
+    let (server, bridge, cancel) = start_with_failsafe(fast_failsafe()).await;
+
+    let mut stream = tokio::net::TcpStream::connect(server.local_addr())
+        .await
+        .unwrap();
+    write_request(&mut stream, full_throttle(1)).await;
+    read_response(&mut stream).await;
+    drop(stream);
+
+    let inputs = wait_for_inputs(&bridge, 4).await;
+    assert_eq!(inputs.len(), 4);
+    assert_eq!(inputs[0].channels, [1.0; 12]);
+    assert!(inputs[1..].iter().all(|i| *i == ControlInputs::neutral()));
+    assert_eq!(bridge.enable_rc_count.load(Ordering::SeqCst), 1);
+    assert_eq!(server.link_loss_count(), 1);
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn silent_client_commands_failsafe_then_takes_control_back() {
+    let frame = ControlInputs {
+        channels: [0.25; 12],
+    };
+    let (server, bridge, cancel) = start_with_failsafe(LinkFailsafe {
+        frame: frame.clone(),
+        repeat: 2,
+        restore_rc: false,
+        ..fast_failsafe()
+    })
+    .await;
+
+    let mut stream = tokio::net::TcpStream::connect(server.local_addr())
+        .await
+        .unwrap();
+    write_request(&mut stream, full_throttle(1)).await;
+    read_response(&mut stream).await;
+
+    let inputs = wait_for_inputs(&bridge, 3).await;
+    assert_eq!(inputs[1..], [frame.clone(), frame]);
+    assert_eq!(bridge.enable_rc_count.load(Ordering::SeqCst), 0);
+
+    write_request(&mut stream, full_throttle(2)).await;
+    let response = read_response(&mut stream).await;
+    assert!(matches!(response.status, ResponseStatus::Success));
+    assert_eq!(bridge.inputs()[3].channels, [1.0; 12]);
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn pinging_client_still_commands_failsafe() {
+    let frame = ControlInputs {
+        channels: [0.25; 12],
+    };
+    let (server, bridge, cancel) = start_with_failsafe(LinkFailsafe {
+        frame: frame.clone(),
+        repeat: 1,
+        restore_rc: false,
+        ..fast_failsafe()
+    })
+    .await;
+
+    let mut stream = tokio::net::TcpStream::connect(server.local_addr())
+        .await
+        .unwrap();
+    write_request(&mut stream, full_throttle(1)).await;
+    read_response(&mut stream).await;
+
+    for id in 2..12 {
+        write_request(&mut stream, request(RequestType::Ping, id)).await;
+        read_response(&mut stream).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let inputs = bridge.inputs();
+    assert_eq!(inputs.len(), 2);
+    assert_eq!(inputs[0].channels, [1.0; 12]);
+    assert_eq!(inputs[1], frame);
+    assert_eq!(server.link_loss_count(), 1);
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn no_failsafe_after_client_restores_rc() {
+    let (server, bridge, cancel) = start_with_failsafe(fast_failsafe()).await;
+
+    let mut stream = tokio::net::TcpStream::connect(server.local_addr())
+        .await
+        .unwrap();
+    write_request(&mut stream, full_throttle(1)).await;
+    read_response(&mut stream).await;
+    write_request(&mut stream, request(RequestType::EnableRC, 2)).await;
+    read_response(&mut stream).await;
+    drop(stream);
+
+    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+    assert_eq!(bridge.inputs().len(), 1);
+    assert_eq!(bridge.enable_rc_count.load(Ordering::SeqCst), 1);
+    assert_eq!(server.link_loss_count(), 0);
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn no_failsafe_after_client_reconnects() {
+    let (server, bridge, cancel) = start_with_failsafe(LinkFailsafe {
+        link_timeout: std::time::Duration::from_millis(200),
+        ..Default::default()
+    })
+    .await;
+
+    let mut stream = tokio::net::TcpStream::connect(server.local_addr())
+        .await
+        .unwrap();
+    write_request(&mut stream, full_throttle(1)).await;
+    read_response(&mut stream).await;
+    drop(stream);
+
+    // Reconnect within the link timeout and keep flying past it
+    let mut stream = tokio::net::TcpStream::connect(server.local_addr())
+        .await
+        .unwrap();
+    for id in 2..22 {
+        write_request(&mut stream, full_throttle(id)).await;
+        read_response(&mut stream).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    assert!(bridge.inputs().iter().all(|i| i.channels == [1.0; 12]));
+    assert_eq!(bridge.enable_rc_count.load(Ordering::SeqCst), 0);
+    assert_eq!(server.link_loss_count(), 0);
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn no_failsafe_for_clients_that_never_flew() {
+    let (server, bridge, cancel) = start_with_failsafe(fast_failsafe()).await;
+
+    let mut stream = tokio::net::TcpStream::connect(server.local_addr())
+        .await
+        .unwrap();
+    write_request(&mut stream, request(RequestType::Ping, 1)).await;
+    read_response(&mut stream).await;
+
+    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+    assert!(bridge.inputs().is_empty());
+    assert_eq!(server.link_loss_count(), 0);
+
+    cancel.cancel();
+}
+
+// ========================================================================
+// Simulated Aircraft Tests
+// ========================================================================
+
+#[cfg(feature = "uom")]
+fn altitude(state: &crate::SimulatorState) -> f32 {
+    state.altitude_agl.get::<uom::si::length::meter>()
+}
+#[cfg(not(feature = "uom"))]
+fn altitude(state: &crate::SimulatorState) -> f32 {
+    state.altitude_agl
+}
+
+#[cfg(feature = "uom")]
+fn physics_time(state: &crate::SimulatorState) -> f32 {
+    state.current_physics_time.get::<uom::si::time::second>()
+}
+#[cfg(not(feature = "uom"))]
+fn physics_time(state: &crate::SimulatorState) -> f32 {
+    state.current_physics_time
+}
+
+/// Flies 100 exchanges against a simulated proxy at a fixed throttle.
+async fn fly_simulated(model: SimModel, throttle: f32) -> Vec<crate::SimulatorState> {
+    let server = AsyncProxyServer::new_simulated("127.0.0.1:0", model)
+        .await
+        .unwrap();
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+
+    let server_cancel = cancel.clone();
+    let handle = tokio::spawn(async move { server.run(server_cancel).await });
+
+    let mut control = ControlInputs {
+        channels: [0.5; 12],
+    };
+    control.channels[2] = throttle;
+    let requests = (0..100)
+        .map(|id| Request {
+            request_type: RequestType::ExchangeData,
+            payload: Some(control.clone()),
+            id,
+        })
+        .collect();
+    let responses = flood_async(addr, requests).await;
+
+    cancel.cancel();
+    let _ = handle.await;
+    responses
+        .into_iter()
+        .map(|response| response.payload.unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn simulated_altitude_follows_throttle() {
+    for model in [SimModel::FixedWing, SimModel::Hover] {
+        let climbing = fly_simulated(model, 1.0).await;
+        let idle = fly_simulated(model, 0.0).await;
+        assert_eq!((climbing.len(), idle.len()), (100, 100));
+
+        assert!(
+            altitude(&climbing[99]) > altitude(&idle[99]) + 0.5,
+            "{:?}: {} m at full throttle, {} m idle",
+            model,
+            altitude(&climbing[99]),
+            altitude(&idle[99])
+        );
+        assert!(altitude(&climbing[99]) > altitude(&climbing[0]));
+
+        // Each exchange advances the clock and echoes what was applied
+        assert!(physics_time(&climbing[99]) > physics_time(&climbing[98]));
+        assert_eq!(climbing[99].previous_inputs.channels[2], 1.0);
+    }
+}
+
+// ========================================================================
+// Unix Domain Socket Tests
+// ========================================================================
+
+#[cfg(unix)]
+mod uds {
+    use super::{AsyncProxyServer, CancellationToken, Ordering, StubBridge};
+    use crate::{AsyncBridge, BridgeError, ControlInputs, RealFlightBridge};
+    use crate::{AsyncRemoteBridge, RealFlightRemoteBridge};
+    use std::path::PathBuf;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "realflight-bridge-{}-{}.sock",
+            std::process::id(),
+            name
+        ))
+    }
+
+    #[tokio::test]
+    async fn sync_bridge_talks_over_uds() {
+        let path = socket_path("sync");
+        let server = AsyncProxyServer::bind_uds(&path).await.unwrap();
+        assert_eq!(server.socket_path(), Some(path.as_path()));
+        let cancel = CancellationToken::new();
+        let stub = StubBridge::new();
+        let enable_count = stub.enable_rc_count.clone();
+        let exchange_count = stub.exchange_count.clone();
+        let server_cancel = cancel.clone();
+        let handle =
+            tokio::spawn(async move { server.run_with_bridge(&stub, server_cancel).await });
+
+        let client_path = path.clone();
+        tokio::task::spawn_blocking(move || {
+            let bridge = RealFlightRemoteBridge::connect_uds(&client_path).unwrap();
+            assert!(bridge.proxy_info().is_some());
+            bridge.enable_rc().unwrap();
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            assert!(bridge.is_connected());
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(enable_count.load(Ordering::SeqCst), 1);
+        assert_eq!(exchange_count.load(Ordering::SeqCst), 1);
+
+        cancel.cancel();
+        let _ = handle.await;
+    }
+
+    #[tokio::test]
+    async fn async_bridge_talks_over_uds() {
+        let path = socket_path("async");
+        let server = AsyncProxyServer::bind_uds(&path).await.unwrap();
+        let cancel = CancellationToken::new();
+        let stub = StubBridge::new();
+        let exchange_count = stub.exchange_count.clone();
+        let server_cancel = cancel.clone();
+        let handle =
+            tokio::spawn(async move { server.run_with_bridge(&stub, server_cancel).await });
+
+        let bridge = AsyncRemoteBridge::connect_uds(&path).await.unwrap();
+        let result = bridge.exchange_data(&ControlInputs::default()).await;
+        assert!(result.is_ok(), "expected Ok: {:?}", result);
+        assert!(bridge.is_connected().await);
+        assert_eq!(exchange_count.load(Ordering::SeqCst), 1);
+
+        drop(bridge);
+        cancel.cancel();
+        let _ = handle.await;
+    }
+
+    #[tokio::test]
+    async fn socket_file_removed_on_drop() {
+        let path = socket_path("drop");
+        let server = AsyncProxyServer::bind_uds(&path).await.unwrap();
+        assert!(path.exists());
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn bind_fails_on_existing_socket() {
+        let path = socket_path("in-use");
+        let _server = AsyncProxyServer::bind_uds(&path).await.unwrap();
+
+        let result = AsyncProxyServer::bind_uds(&path).await;
+        assert!(matches!(result, Err(BridgeError::Connection(_))));
+    }
+
+    #[tokio::test]
+    async fn connect_to_missing_socket_fails_initialization() {
+        let path = socket_path("missing");
+
+        let result = AsyncRemoteBridge::connect_uds(&path).await;
+        assert!(matches!(result, Err(BridgeError::Initialization(_))));
+
+        let result = RealFlightRemoteBridge::connect_uds(&path);
+        assert!(matches!(result, Err(BridgeError::Initialization(_))));
+    }
+}
+
+#[tokio::test]
+async fn run_with_bridge_accepts_trait_object() {
+    let server = AsyncProxyServer::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().to_string();
+    let cancel = CancellationToken::new();
+    let stub = StubBridge::new();
+    let reset_count = stub.reset_count.clone();
+    let bridge: Box<dyn crate::bridge::DynAsyncBridge> = Box::new(stub);
+
+    let server_cancel = cancel.clone();
+    let handle = tokio::spawn(async move { server.run_with_bridge(&*bridge, server_cancel).await });
+
+    let request = Request {
+        request_type: RequestType::ResetAircraft,
+        payload: None,
+        id: 0,
+    };
+    let response = send_request_async(addr, request).await;
+
+    assert!(matches!(response.status, ResponseStatus::Success));
+    assert_eq!(reset_count.load(Ordering::SeqCst), 1);
+
+    cancel.cancel();
+    let _ = handle.await;
+}
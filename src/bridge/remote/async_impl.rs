@@ -1,51 +1,198 @@
 //! Async implementation of the remote bridge for RealFlight simulator.
 
+use std::future::poll_fn;
 use std::net::ToSocketAddrs;
-use std::time::Duration;
-
-use log::error;
-use postcard::{from_bytes, to_stdvec};
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader, BufWriter};
+#[cfg(unix)]
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use log::warn;
+use tokio::io::{
+    AsyncBufRead, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, BufWriter,
+};
 use tokio::net::TcpStream;
 use tokio::sync::Mutex;
 use tokio::time::timeout;
+use tokio_util::sync::{CancellationToken, DropGuard};
 
 use crate::bridge::AsyncBridge;
-use crate::{BridgeError, ControlInputs, SimulatorState};
-
-use super::{Request, RequestType, Response};
-
-const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+use crate::constants::{
+    DEFAULT_MAX_FRAME_SIZE, DEFAULT_REMOTE_CONNECT_TIMEOUT, DEFAULT_REMOTE_MAX_STALE_FRAMES,
+};
+use crate::{BridgeError, ControlInputs, SimulatorState, Statistics, StatisticsEngine};
+
+use super::transport::Target;
+use super::{
+    ProtocolInfo, Request, RequestType, Response, StaleState, check_authenticated,
+    check_frame_length, check_hello, decode_response, encode_request, id_mismatch, is_measured,
+    missing_payload,
+};
 
 /// Builder for AsyncRemoteBridge.
 ///
 /// Configure options synchronously, then call `build()` to connect.
 #[derive(Debug, Clone)]
 pub struct AsyncRemoteBridgeBuilder {
-    address: String,
+    target: Target,
     connect_timeout: Duration,
+    legacy_protocol: bool,
+    keep_alive: Option<Duration>,
+    max_stale_frames: u32,
+    max_frame_size: usize,
+    auth_token: Option<String>,
 }
 
 impl AsyncRemoteBridgeBuilder {
     /// Creates a new builder with the specified address.
     pub fn new(address: &str) -> Self {
+        Self::with_target(Target::Address(address.to_string()))
+    }
+
+    /// Creates a new builder for a proxy listening on the Unix domain socket
+    /// at `path`.
+    ///
+    /// See [RealFlightRemoteBridgeBuilder::uds](crate::RealFlightRemoteBridgeBuilder::uds).
+    #[cfg(unix)]
+    pub fn uds(path: impl AsRef<Path>) -> Self {
+        Self::with_target(Target::Unix(path.as_ref().to_path_buf()))
+    }
+
+    fn with_target(target: Target) -> Self {
         Self {
-            address: address.to_string(),
-            connect_timeout: DEFAULT_TIMEOUT,
+            target,
+            connect_timeout: DEFAULT_REMOTE_CONNECT_TIMEOUT,
+            legacy_protocol: false,
+            keep_alive: None,
+            max_stale_frames: DEFAULT_REMOTE_MAX_STALE_FRAMES,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            auth_token: None,
         }
     }
 
-    /// Sets the connection timeout.
+    /// Sets the connection timeout, also the time allowed for the proxy to
+    /// answer the version handshake.
     #[must_use]
     pub fn timeout(mut self, timeout: Duration) -> Self {
         self.connect_timeout = timeout;
         self
     }
 
-    /// Builds the AsyncRemoteBridge, connecting to the server.
+    /// Talks to proxies that predate the version handshake. Disabled by default.
+    ///
+    /// See [RealFlightRemoteBridgeBuilder::legacy_protocol](crate::RealFlightRemoteBridgeBuilder::legacy_protocol).
+    #[must_use]
+    pub fn legacy_protocol(mut self, enabled: bool) -> Self {
+        self.legacy_protocol = enabled;
+        self
+    }
+
+    /// Pings the proxy from a spawned task once no request has been sent for
+    /// `interval`. Disabled by default.
+    ///
+    /// See [RealFlightRemoteBridgeBuilder::keep_alive](crate::RealFlightRemoteBridgeBuilder::keep_alive).
+    /// A ping that fails makes [AsyncRemoteBridge::is_connected] return `false`
+    /// until a request succeeds.
+    #[must_use]
+    pub fn keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = Some(interval);
+        self
+    }
+
+    /// Sets how many consecutive failed exchanges
+    /// [AsyncRemoteBridge::exchange_data_or_last] answers with the last good
+    /// state before returning the error. Defaults to
+    /// [DEFAULT_REMOTE_MAX_STALE_FRAMES].
+    #[must_use]
+    pub fn max_stale_frames(mut self, frames: u32) -> Self {
+        self.max_stale_frames = frames;
+        self
+    }
+
+    /// Sets the largest response, in bytes, the bridge accepts from the proxy.
+    /// Defaults to [DEFAULT_MAX_FRAME_SIZE].
+    ///
+    /// See [RealFlightRemoteBridgeBuilder::max_frame_size](crate::RealFlightRemoteBridgeBuilder::max_frame_size).
+    /// After an oversized response the bridge is no longer connected.
+    #[must_use]
+    pub fn max_frame_size(mut self, bytes: usize) -> Self {
+        self.max_frame_size = bytes;
+        self
+    }
+
+    /// Presents `token` to a proxy that requires one. Not sent by default.
+    ///
+    /// See [RealFlightRemoteBridgeBuilder::auth_token](crate::RealFlightRemoteBridgeBuilder::auth_token).
+    #[must_use]
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Builds the AsyncRemoteBridge, connecting to the server and checking
+    /// that it speaks a compatible protocol.
     pub async fn build(self) -> Result<AsyncRemoteBridge, BridgeError> {
-        let addr = self
-            .address
+        let (read_half, write_half) = self.connect().await?;
+
+        let mut shared = Shared {
+            reader: Mutex::new(BufReader::new(read_half)),
+            writer: Mutex::new(BufWriter::new(write_half)),
+            response_buffer: Mutex::new(Vec::with_capacity(4096)),
+            next_id: AtomicU32::new(0),
+            legacy_protocol: self.legacy_protocol,
+            proxy_info: None,
+            last_used: std::sync::Mutex::new(Instant::now()),
+            alive: AtomicBool::new(true),
+            closed: AtomicBool::new(false),
+            statistics: StatisticsEngine::new(),
+            stale: std::sync::Mutex::new(StaleState::default()),
+            max_stale_frames: self.max_stale_frames,
+            max_frame_size: self.max_frame_size,
+        };
+        if !self.legacy_protocol {
+            shared.proxy_info = Some(shared.handshake(self.connect_timeout).await?);
+            if let Some(token) = &self.auth_token {
+                shared.authenticate(token, self.connect_timeout).await?;
+            }
+        }
+
+        let inner = Arc::new(shared);
+        let keep_alive = match self.keep_alive {
+            Some(interval) if !self.legacy_protocol => {
+                let cancel = CancellationToken::new();
+                let task =
+                    Arc::clone(&inner).keep_alive(interval, self.connect_timeout, cancel.clone());
+                tokio::spawn(task);
+                Some(cancel.drop_guard())
+            }
+            _ => None,
+        };
+
+        Ok(AsyncRemoteBridge {
+            inner,
+            _keep_alive: keep_alive,
+        })
+    }
+
+    /// Connects to the first address the target resolves to, or to its Unix
+    /// domain socket, returning the stream split in two.
+    async fn connect(&self) -> Result<(ReadHalf, WriteHalf), BridgeError> {
+        let address = match &self.target {
+            Target::Address(address) => address,
+            #[cfg(unix)]
+            Target::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path).await.map_err(|e| {
+                    BridgeError::Initialization(format!("Connection failed: {}", e))
+                })?;
+                let (read_half, write_half) = stream.into_split();
+                return Ok((Box::new(read_half), Box::new(write_half)));
+            }
+        };
+
+        let addr = address
             .to_socket_addrs()
             .map_err(|e| BridgeError::Initialization(format!("Invalid address: {}", e)))?
             .next()
@@ -66,15 +213,16 @@ impl AsyncRemoteBridgeBuilder {
             .map_err(|e| BridgeError::Initialization(format!("Failed to set nodelay: {}", e)))?;
 
         let (read_half, write_half) = stream.into_split();
-
-        Ok(AsyncRemoteBridge {
-            reader: Mutex::new(BufReader::new(read_half)),
-            writer: Mutex::new(BufWriter::new(write_half)),
-            response_buffer: Mutex::new(Vec::with_capacity(4096)),
-        })
+        Ok((Box::new(read_half), Box::new(write_half)))
     }
 }
 
+/// Read side of the connection to the proxy, over TCP or a Unix domain socket.
+type ReadHalf = Box<dyn AsyncRead + Send + Unpin>;
+
+/// Write side of the connection to the proxy.
+type WriteHalf = Box<dyn AsyncWrite + Send + Unpin>;
+
 /// Async client for interacting with a remote RealFlight simulator via a proxy server.
 ///
 /// # Examples
@@ -105,36 +253,59 @@ impl AsyncRemoteBridgeBuilder {
 /// }
 /// ```
 pub struct AsyncRemoteBridge {
-    reader: Mutex<BufReader<tokio::net::tcp::OwnedReadHalf>>,
-    writer: Mutex<BufWriter<tokio::net::tcp::OwnedWriteHalf>>,
+    inner: Arc<Shared>,
+    /// Cancels the keep-alive task when the bridge is dropped
+    _keep_alive: Option<DropGuard>,
+}
+
+/// State shared with the keep-alive task.
+struct Shared {
+    reader: Mutex<BufReader<ReadHalf>>,
+    writer: Mutex<BufWriter<WriteHalf>>,
     response_buffer: Mutex<Vec<u8>>,
+    next_id: AtomicU32,
+    legacy_protocol: bool,
+    proxy_info: Option<ProtocolInfo>,
+    /// When the last request was sent
+    last_used: std::sync::Mutex<Instant>,
+    /// Cleared by a failed keep-alive ping, set again by a successful request
+    alive: AtomicBool,
+    /// Set once a response broke the framing; the stream is unusable after
+    closed: AtomicBool,
+    statistics: StatisticsEngine,
+    stale: std::sync::Mutex<StaleState>,
+    max_stale_frames: u32,
+    max_frame_size: usize,
 }
 
 impl AsyncBridge for AsyncRemoteBridge {
     async fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
         let response = self
+            .inner
             .send_request(RequestType::ExchangeData, Some(control))
             .await?;
-        if let Some(state) = response.payload {
-            Ok(state)
-        } else {
-            error!("No payload in response: {:?}", response.status);
-            Err(BridgeError::SoapFault("No payload in response".to_string()))
+        match response.payload {
+            Some(state) => Ok(state),
+            None => Err(missing_payload(&response)),
         }
     }
 
     async fn enable_rc(&self) -> Result<(), BridgeError> {
-        self.send_request(RequestType::EnableRC, None).await?;
+        self.inner.send_request(RequestType::EnableRC, None).await?;
         Ok(())
     }
 
     async fn disable_rc(&self) -> Result<(), BridgeError> {
-        self.send_request(RequestType::DisableRC, None).await?;
+        self.inner
+            .send_request(RequestType::DisableRC, None)
+            .await?;
         Ok(())
     }
 
     async fn reset_aircraft(&self) -> Result<(), BridgeError> {
-        self.send_request(RequestType::ResetAircraft, None).await?;
+        self.inner
+            .send_request(RequestType::ResetAircraft, None)
+            .await?;
         Ok(())
     }
 }
@@ -150,22 +321,154 @@ impl AsyncRemoteBridge {
         AsyncRemoteBridgeBuilder::new(address)
     }
 
+    /// Creates a new AsyncRemoteBridge connected to a proxy listening on the
+    /// Unix domain socket at `path`.
+    ///
+    /// Async equivalent of [RealFlightRemoteBridge::connect_uds](crate::RealFlightRemoteBridge::connect_uds).
+    #[cfg(unix)]
+    pub async fn connect_uds(path: impl AsRef<Path>) -> Result<Self, BridgeError> {
+        AsyncRemoteBridgeBuilder::uds(path).build().await
+    }
+
+    /// The proxy's versions and the features both sides support, or `None`
+    /// with [AsyncRemoteBridgeBuilder::legacy_protocol].
+    pub fn proxy_info(&self) -> Option<&ProtocolInfo> {
+        self.inner.proxy_info.as_ref()
+    }
+
+    /// Exchanges data, answering a failed exchange with the last good state.
+    ///
+    /// Async equivalent of [RealFlightRemoteBridge::exchange_data_or_last](crate::RealFlightRemoteBridge::exchange_data_or_last).
+    pub async fn exchange_data_or_last(
+        &self,
+        control: &ControlInputs,
+    ) -> Result<(SimulatorState, bool), BridgeError> {
+        let result = self.exchange_data(control).await;
+        let mut stale = self.inner.stale.lock().unwrap();
+        stale.resolve(result, self.inner.max_stale_frames)
+    }
+
+    /// Number of consecutive failed exchanges seen by
+    /// [exchange_data_or_last](Self::exchange_data_or_last); zero after a
+    /// success.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.inner.stale.lock().unwrap().failures()
+    }
+
+    /// Returns a snapshot of client-side statistics.
+    ///
+    /// See [RealFlightRemoteBridge::statistics](crate::RealFlightRemoteBridge::statistics).
+    pub fn statistics(&self) -> Statistics {
+        self.inner.statistics.snapshot()
+    }
+
+    /// Checks whether the connection to the proxy is still open.
+    ///
+    /// Async equivalent of [RealFlightRemoteBridge::is_connected](crate::RealFlightRemoteBridge::is_connected).
+    /// Waits only for in-flight requests to release the connection.
+    pub async fn is_connected(&self) -> bool {
+        if !self.inner.alive.load(Ordering::Relaxed) || self.inner.closed.load(Ordering::Relaxed) {
+            return false;
+        }
+
+        let mut reader = self.inner.reader.lock().await;
+        if !reader.buffer().is_empty() {
+            return true;
+        }
+
+        // Poll once; a pending read means the socket is open with nothing to
+        // read, and anything that did arrive stays in the buffer
+        poll_fn(|cx| match Pin::new(&mut *reader).poll_fill_buf(cx) {
+            Poll::Pending => Poll::Ready(true),
+            Poll::Ready(Ok(buffer)) => Poll::Ready(!buffer.is_empty()),
+            Poll::Ready(Err(_)) => Poll::Ready(false),
+        })
+        .await
+    }
+}
+
+impl Shared {
+    /// Sends [RequestType::Hello], waiting at most `limit` for the reply.
+    async fn handshake(&self, limit: Duration) -> Result<ProtocolInfo, BridgeError> {
+        let hello = self.send_request(RequestType::Hello(ProtocolInfo::current()), None);
+        let response = timeout(limit, hello)
+            .await
+            .map_err(|_| {
+                BridgeError::Initialization(format!(
+                    "No reply to the version handshake within {:?}; \
+                     proxies that predate it need legacy_protocol",
+                    limit
+                ))
+            })?
+            .map_err(|e| match e {
+                BridgeError::Protocol(_) => e,
+                e => BridgeError::Initialization(format!("Version handshake failed: {}", e)),
+            })?;
+        check_hello(response)
+    }
+
+    /// Sends [RequestType::Authenticate], waiting at most `limit` for the reply.
+    async fn authenticate(&self, token: &str, limit: Duration) -> Result<(), BridgeError> {
+        let request = Request {
+            request_type: RequestType::Authenticate {
+                token: token.to_string(),
+            },
+            payload: None,
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+        };
+        let response = timeout(limit, self.exchange(&request))
+            .await
+            .map_err(|_| {
+                BridgeError::Initialization(format!(
+                    "No reply to authentication within {:?}",
+                    limit
+                ))
+            })?
+            .map_err(|e| BridgeError::Initialization(format!("Authentication failed: {}", e)))?;
+        check_authenticated(response, request.id)
+    }
+
     /// Sends a request to the server and receives a response.
     async fn send_request(
         &self,
         request_type: RequestType,
         payload: Option<&ControlInputs>,
     ) -> Result<Response, BridgeError> {
+        *self.last_used.lock().unwrap() = Instant::now();
         let request = Request {
             request_type,
             payload: payload.cloned(),
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
         };
+        if !is_measured(&request.request_type) {
+            return self.exchange(&request).await;
+        }
+
+        let _in_flight = self.statistics.track_in_flight();
+        self.statistics.increment_request_count();
+        let start = Instant::now();
+        let result = self.exchange(&request).await;
+        self.statistics.add_io_time(start.elapsed());
+        if result.is_err() {
+            self.statistics.increment_error_count();
+        }
+        result
+    }
 
+    /// Writes one request and reads its response.
+    async fn exchange(&self, request: &Request) -> Result<Response, BridgeError> {
         // Serialize the request to a byte vector
-        let request_bytes = to_stdvec(&request)
+        let request_bytes = encode_request(request, self.legacy_protocol)
             .map_err(|e| BridgeError::SoapFault(format!("Serialization error: {}", e)))?;
 
         let mut writer = self.writer.lock().await;
+        if self.closed.load(Ordering::Relaxed) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "Connection closed after a protocol error",
+            )
+            .into());
+        }
 
         // Send the length of the request (4 bytes)
         let length_bytes = (request_bytes.len() as u32).to_be_bytes();
@@ -175,14 +478,20 @@ impl AsyncRemoteBridge {
         writer.write_all(&request_bytes).await?;
         writer.flush().await?;
 
-        drop(writer); // Release lock before reading
-
+        // Queue for the reader before releasing the writer, so responses are
+        // read in the order their requests were written
         let mut reader = self.reader.lock().await;
+        drop(writer);
 
         // Read the response length (4 bytes)
         let mut length_buffer = [0u8; 4];
         reader.read_exact(&mut length_buffer).await?;
         let response_length = u32::from_be_bytes(length_buffer) as usize;
+        if let Err(e) = check_frame_length(response_length, self.max_frame_size) {
+            // The rest of the frame is never read, so nothing after it can be
+            self.closed.store(true, Ordering::Relaxed);
+            return Err(e);
+        }
 
         // Read the response data into reusable buffer
         let mut response_buffer = self.response_buffer.lock().await;
@@ -191,11 +500,48 @@ impl AsyncRemoteBridge {
         reader.read_exact(&mut response_buffer).await?;
 
         // Deserialize the response
-        let response: Response = from_bytes(&response_buffer)
+        let response = decode_response(&response_buffer, request.id, self.legacy_protocol)
             .map_err(|e| BridgeError::SoapFault(format!("Deserialization error: {}", e)))?;
 
+        if response.id != request.id {
+            return Err(id_mismatch(request.id, response.id));
+        }
+        self.alive.store(true, Ordering::Relaxed);
         Ok(response)
     }
+
+    /// Pings the proxy whenever the connection has been idle for `interval`,
+    /// waiting at most `limit` for each reply, until `cancel` fires.
+    async fn keep_alive(
+        self: Arc<Self>,
+        interval: Duration,
+        limit: Duration,
+        cancel: CancellationToken,
+    ) {
+        loop {
+            let idle = self.last_used.lock().unwrap().elapsed();
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(interval.saturating_sub(idle)) => {}
+            }
+            if self.last_used.lock().unwrap().elapsed() < interval {
+                continue; // A request was sent while waiting
+            }
+
+            let ping = timeout(limit, self.send_request(RequestType::Ping, None));
+            let result = tokio::select! {
+                _ = cancel.cancelled() => return,
+                result = ping => result,
+            };
+            let error = match result {
+                Ok(Ok(_)) => continue,
+                Ok(Err(e)) => e,
+                Err(_) => BridgeError::Timeout(limit),
+            };
+            warn!("Keep-alive ping failed: {}", error);
+            self.alive.store(false, Ordering::Relaxed);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -203,6 +549,10 @@ mod tests {
     use super::*;
     use crate::bridge::AsyncBridge;
     use crate::bridge::remote::Response;
+    use crate::bridge::remote::tests::{
+        accept_with_hello, answer_hello, answer_pings, answer_requests,
+    };
+    use postcard::{from_bytes, to_stdvec};
     use std::io::{Read, Write};
     use std::net::TcpListener;
 
@@ -217,7 +567,7 @@ mod tests {
 
         // Accept one connection in background
         let handle = std::thread::spawn(move || {
-            let _ = listener.accept();
+            let _ = accept_with_hello(&listener);
         });
 
         let result = AsyncRemoteBridge::builder(&addr)
@@ -254,6 +604,166 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn incompatible_major_fails_build() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let info = ProtocolInfo {
+                major: ProtocolInfo::MAJOR + 1,
+                ..ProtocolInfo::current()
+            };
+            answer_hello(&mut stream, info).unwrap();
+        });
+
+        let result = AsyncRemoteBridge::new(&addr).await;
+
+        match result {
+            Err(BridgeError::Initialization(msg)) => assert!(msg.contains("protocol")),
+            Err(e) => panic!("expected Initialization, got {:?}", e),
+            Ok(_) => panic!("expected Initialization"),
+        }
+        let _ = handle.join();
+    }
+
+    #[tokio::test]
+    async fn unanswered_handshake_fails_build() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let result = AsyncRemoteBridge::builder(&addr)
+            .timeout(Duration::from_millis(100))
+            .build()
+            .await;
+
+        match result {
+            Err(BridgeError::Initialization(msg)) => assert!(msg.contains("legacy_protocol")),
+            Err(e) => panic!("expected Initialization, got {:?}", e),
+            Ok(_) => panic!("expected Initialization"),
+        }
+        drop(listener);
+    }
+
+    // ========================================================================
+    // Statistics Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn statistics_track_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let handle = std::thread::spawn(move || {
+            let stream = accept_with_hello(&listener).unwrap();
+            mock_server_send_response(stream.try_clone().unwrap(), Response::success());
+            mock_server_send_response(stream, Response::success());
+        });
+
+        let bridge = AsyncRemoteBridge::new(&addr).await.unwrap();
+        assert_eq!(bridge.statistics().request_count, 0);
+
+        bridge.enable_rc().await.unwrap();
+        bridge.reset_aircraft().await.unwrap();
+
+        let statistics = bridge.statistics();
+        assert_eq!(statistics.request_count, 2);
+        assert_eq!(statistics.error_count, 0);
+        assert_eq!(statistics.in_flight, 0);
+        assert!(statistics.io_total > Duration::ZERO);
+        let _ = handle.join();
+    }
+
+    #[tokio::test]
+    async fn exchange_data_or_last_reuses_state() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let state = SimulatorState {
+            prop_rpm: 1.0,
+            ..Default::default()
+        };
+
+        let handle = std::thread::spawn(move || {
+            let mut stream = accept_with_hello(&listener).unwrap();
+            let responses = vec![
+                Response::success_with(state),
+                Response::error(),
+                Response::error(),
+            ];
+            answer_requests(&mut stream, responses);
+        });
+
+        let bridge = AsyncRemoteBridge::builder(&addr)
+            .max_stale_frames(1)
+            .build()
+            .await
+            .unwrap();
+        let control = ControlInputs::default();
+
+        let (state, stale) = bridge.exchange_data_or_last(&control).await.unwrap();
+        assert_eq!((state.prop_rpm, stale), (1.0, false));
+
+        let (state, stale) = bridge.exchange_data_or_last(&control).await.unwrap();
+        assert_eq!((state.prop_rpm, stale), (1.0, true));
+        assert_eq!(bridge.consecutive_failures(), 1);
+
+        let result = bridge.exchange_data_or_last(&control).await;
+        assert!(matches!(result, Err(BridgeError::SoapFault(_))));
+        assert_eq!(bridge.consecutive_failures(), 2);
+        let _ = handle.join();
+    }
+
+    // ========================================================================
+    // Keep-Alive Tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn keep_alive_pings_idle_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let handle = std::thread::spawn(move || {
+            let mut stream = accept_with_hello(&listener).unwrap();
+            answer_pings(&mut stream, 3);
+            stream
+        });
+
+        let bridge = AsyncRemoteBridge::builder(&addr)
+            .keep_alive(Duration::from_millis(20))
+            .build()
+            .await
+            .unwrap();
+
+        let stream = tokio::task::spawn_blocking(move || handle.join().unwrap())
+            .await
+            .unwrap();
+        drop(stream);
+        drop(bridge);
+    }
+
+    #[tokio::test]
+    async fn failed_ping_marks_disconnected() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        // Answer the handshake, then read pings without replying
+        let handle = std::thread::spawn(move || accept_with_hello(&listener).unwrap());
+
+        let bridge = AsyncRemoteBridge::builder(&addr)
+            .timeout(Duration::from_millis(50))
+            .keep_alive(Duration::from_millis(20))
+            .build()
+            .await
+            .unwrap();
+        assert!(bridge.is_connected().await);
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        assert!(!bridge.is_connected().await);
+        let _ = handle.join();
+    }
+
     // ========================================================================
     // Helper Functions
     // ========================================================================
@@ -265,8 +775,13 @@ mod tests {
         let msg_length = u32::from_be_bytes(length_buffer) as usize;
         let mut buffer = vec![0u8; msg_length];
         stream.read_exact(&mut buffer).unwrap();
+        let request: Request = from_bytes(&buffer).unwrap();
 
-        // Send response
+        // Send response, answering the request's id
+        let response = Response {
+            id: request.id,
+            ..response
+        };
         let response_bytes = to_stdvec(&response).unwrap();
         let length_bytes = (response_bytes.len() as u32).to_be_bytes();
         stream.write_all(&length_bytes).unwrap();
@@ -284,7 +799,7 @@ mod tests {
         let addr = listener.local_addr().unwrap().to_string();
 
         let handle = std::thread::spawn(move || {
-            let (stream, _) = listener.accept().unwrap();
+            let stream = accept_with_hello(&listener).unwrap();
             mock_server_send_response(stream, Response::success());
         });
 
@@ -301,7 +816,7 @@ mod tests {
         let addr = listener.local_addr().unwrap().to_string();
 
         let handle = std::thread::spawn(move || {
-            let (stream, _) = listener.accept().unwrap();
+            let stream = accept_with_hello(&listener).unwrap();
             mock_server_send_response(stream, Response::success());
         });
 
@@ -318,7 +833,7 @@ mod tests {
         let addr = listener.local_addr().unwrap().to_string();
 
         let handle = std::thread::spawn(move || {
-            let (stream, _) = listener.accept().unwrap();
+            let stream = accept_with_hello(&listener).unwrap();
             mock_server_send_response(stream, Response::success());
         });
 
@@ -335,7 +850,7 @@ mod tests {
         let addr = listener.local_addr().unwrap().to_string();
 
         let handle = std::thread::spawn(move || {
-            let (stream, _) = listener.accept().unwrap();
+            let stream = accept_with_hello(&listener).unwrap();
             let state = SimulatorState::default();
             mock_server_send_response(stream, Response::success_with(state));
         });
@@ -358,7 +873,7 @@ mod tests {
         let addr = listener.local_addr().unwrap().to_string();
 
         let handle = std::thread::spawn(move || {
-            let (stream, _) = listener.accept().unwrap();
+            let stream = accept_with_hello(&listener).unwrap();
             // Send success but with no payload
             mock_server_send_response(stream, Response::success());
         });
@@ -376,13 +891,50 @@ mod tests {
         let _ = handle.join();
     }
 
+    #[tokio::test]
+    async fn mismatched_response_id_returns_protocol_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let handle = std::thread::spawn(move || {
+            let mut stream = accept_with_hello(&listener).unwrap();
+            let mut length_buffer = [0u8; 4];
+            stream.read_exact(&mut length_buffer).unwrap();
+            let msg_length = u32::from_be_bytes(length_buffer) as usize;
+            let mut buffer = vec![0u8; msg_length];
+            stream.read_exact(&mut buffer).unwrap();
+            let request: Request = from_bytes(&buffer).unwrap();
+
+            // Answer as if replying to a different request
+            let response = Response {
+                id: request.id + 1,
+                ..Response::success()
+            };
+            let response_bytes = to_stdvec(&response).unwrap();
+            let length_bytes = (response_bytes.len() as u32).to_be_bytes();
+            stream.write_all(&length_bytes).unwrap();
+            stream.write_all(&response_bytes).unwrap();
+            stream.flush().unwrap();
+        });
+
+        let bridge = AsyncRemoteBridge::new(&addr).await.unwrap();
+        let result = bridge.enable_rc().await;
+
+        assert!(
+            matches!(result, Err(BridgeError::Protocol(_))),
+            "expected Protocol, got {:?}",
+            result
+        );
+        let _ = handle.join();
+    }
+
     #[tokio::test]
     async fn malformed_response_returns_error() {
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
         let addr = listener.local_addr().unwrap().to_string();
 
         let handle = std::thread::spawn(move || {
-            let (mut stream, _) = listener.accept().unwrap();
+            let mut stream = accept_with_hello(&listener).unwrap();
             // Read the request
             let mut length_buffer = [0u8; 4];
             stream.read_exact(&mut length_buffer).unwrap();
@@ -410,13 +962,69 @@ mod tests {
         let _ = handle.join();
     }
 
+    #[tokio::test]
+    async fn oversized_response_closes_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let handle = std::thread::spawn(move || {
+            let mut stream = accept_with_hello(&listener).unwrap();
+            let mut length_buffer = [0u8; 4];
+            stream.read_exact(&mut length_buffer).unwrap();
+            let mut buffer = vec![0u8; u32::from_be_bytes(length_buffer) as usize];
+            stream.read_exact(&mut buffer).unwrap();
+
+            stream.write_all(&1000u32.to_be_bytes()).unwrap();
+            stream.write_all(&[0u8; 16]).unwrap();
+            stream
+        });
+
+        let bridge = AsyncRemoteBridge::builder(&addr)
+            .max_frame_size(64)
+            .build()
+            .await
+            .unwrap();
+
+        let result = bridge.enable_rc().await;
+        assert!(
+            matches!(result, Err(BridgeError::Protocol(_))),
+            "Expected BridgeError::Protocol, got {:?}",
+            result
+        );
+        assert!(!bridge.is_connected().await);
+        let result = bridge.enable_rc().await;
+        assert!(matches!(result, Err(BridgeError::Connection(_))));
+        let _stream = handle.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn exchange_data_timeout_fails_on_silent_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        // Answer the handshake, then keep the connection open without replying
+        let handle = std::thread::spawn(move || accept_with_hello(&listener).unwrap());
+
+        let bridge = AsyncRemoteBridge::new(&addr).await.unwrap();
+        let timeout = Duration::from_millis(50);
+        let result = bridge
+            .exchange_data_timeout(&ControlInputs::default(), timeout)
+            .await;
+
+        match result {
+            Err(BridgeError::Timeout(elapsed)) => assert_eq!(elapsed, timeout),
+            other => panic!("expected Timeout, got {:?}", other),
+        }
+        let _ = handle.join();
+    }
+
     #[tokio::test]
     async fn server_disconnect_returns_error() {
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
         let addr = listener.local_addr().unwrap().to_string();
 
         let handle = std::thread::spawn(move || {
-            let (stream, _) = listener.accept().unwrap();
+            let stream = accept_with_hello(&listener).unwrap();
             // Close connection immediately without responding
             drop(stream);
         });
@@ -427,4 +1035,26 @@ mod tests {
         assert!(result.is_err());
         let _ = handle.join();
     }
+
+    #[tokio::test]
+    async fn is_connected_tracks_server_hang_up() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let (hang_up, hang_up_signal) = std::sync::mpsc::channel::<()>();
+
+        let handle = std::thread::spawn(move || {
+            let stream = accept_with_hello(&listener).unwrap();
+            let _ = hang_up_signal.recv();
+            drop(stream);
+        });
+
+        let bridge = AsyncRemoteBridge::new(&addr).await.unwrap();
+        assert!(bridge.is_connected().await);
+
+        hang_up.send(()).unwrap();
+        let _ = handle.join();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(!bridge.is_connected().await);
+    }
 }
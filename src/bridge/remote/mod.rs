@@ -5,7 +5,9 @@
 //! - **[`RequestType`]**: Enumerates the types of requests that can be sent (e.g., [RequestType::EnableRC], [RequestType::ExchangeData]).
 //! - **[`Request`]**: Defines the structure of client requests, including an optional [ControlInputs] payload.
 //! - **[`Response`]**: Defines server responses, including a status and optional [SimulatorState] payload.
+//! - **[`ProtocolInfo`]**: Versions exchanged in the [RequestType::Hello] handshake.
 //! - **[`RealFlightRemoteBridge`]**: Client struct for connecting to the server and sending requests.
+//! - **[`ReconnectPolicy`]**: How the client re-dials the server after losing its connection.
 //!
 //! ## Usage
 //!
@@ -24,24 +26,29 @@
 
 #[cfg(feature = "rt-tokio")]
 mod async_impl;
+mod transport;
 #[cfg(feature = "rt-tokio")]
 pub use async_impl::{AsyncRemoteBridge, AsyncRemoteBridgeBuilder};
 
-use std::cell::RefCell;
-use std::io::{BufReader, BufWriter};
-use std::time::Duration;
-use std::{
-    io::{Read, Write},
-    net::{TcpStream, ToSocketAddrs},
-};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+#[cfg(unix)]
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use log::error;
+use log::{error, warn};
 use postcard::{from_bytes, to_stdvec};
 use serde::{Deserialize, Serialize};
 
-use crate::{BridgeError, ControlInputs, SimulatorState};
+use crate::constants::{
+    DEFAULT_MAX_FRAME_SIZE, DEFAULT_REMOTE_CONNECT_TIMEOUT, DEFAULT_REMOTE_MAX_STALE_FRAMES,
+};
+use crate::{BridgeError, ControlInputs, SimulatorState, Statistics, StatisticsEngine};
 
 use super::RealFlightBridge;
+use transport::{Endpoint, Stream, Target};
 
 #[cfg(test)]
 mod tests;
@@ -57,6 +64,19 @@ pub enum RequestType {
     ResetAircraft,
     /// Send [ControlInputs] and receive [SimulatorState]
     ExchangeData,
+    /// Version handshake, the first request on each connection
+    Hello(ProtocolInfo),
+    /// Keep-alive sent on an idle connection; does not reach the simulator
+    Ping,
+    /// Shared secret for a proxy that requires one, sent after the handshake
+    Authenticate {
+        /// Token configured on the proxy
+        token: String,
+    },
+    /// Follow the controlling client read-only: the proxy pushes a
+    /// [MessageKind::StateBroadcast] after each of its exchanges. Requests
+    /// for the simulator are refused once subscribed.
+    Subscribe,
 }
 
 /// Represents a request sent from the client to the server.
@@ -66,6 +86,8 @@ pub struct Request {
     pub request_type: RequestType,
     /// Optional [ControlInputs] data
     pub payload: Option<ControlInputs>,
+    /// Correlation id, echoed in the matching [Response]
+    pub id: u32,
 }
 
 /// Represents a response sent from the server to the client.
@@ -75,6 +97,24 @@ pub struct Response {
     pub status: ResponseStatus,
     /// Optional [SimulatorState] data
     pub payload: Option<SimulatorState>,
+    /// Id of the [Request] this answers
+    pub id: u32,
+    /// The proxy's [ProtocolInfo], in reply to [RequestType::Hello]
+    pub info: Option<ProtocolInfo>,
+    /// Why the request failed, when the proxy can tell
+    pub detail: Option<ErrorDetail>,
+    /// Whether this answers a request or was pushed to a subscriber
+    pub kind: MessageKind,
+}
+
+/// Distinguishes the frames a proxy sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageKind {
+    /// Answer to the [Request] with the same id
+    Response,
+    /// [SimulatorState] from another client's exchange, sent unsolicited
+    /// after [RequestType::Subscribe]; the id is always 0
+    StateBroadcast,
 }
 
 /// Indicates the status of a response.
@@ -86,12 +126,27 @@ pub enum ResponseStatus {
     Error,
 }
 
+/// Reason given with a [ResponseStatus::Error].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorDetail {
+    /// The client exceeded the proxy's rate limit; the request was dropped
+    /// without reaching the simulator
+    Throttled,
+    /// The simulator did not answer within the proxy's request timeout; the
+    /// connection stays open for further requests
+    Timeout,
+}
+
 #[cfg(feature = "rt-tokio")]
 impl Response {
     pub(crate) fn success() -> Self {
         Self {
             status: ResponseStatus::Success,
             payload: None,
+            id: 0,
+            info: None,
+            detail: None,
+            kind: MessageKind::Response,
         }
     }
 
@@ -99,6 +154,10 @@ impl Response {
         Self {
             status: ResponseStatus::Success,
             payload: Some(state),
+            id: 0,
+            info: None,
+            detail: None,
+            kind: MessageKind::Response,
         }
     }
 
@@ -106,33 +165,345 @@ impl Response {
         Self {
             status: ResponseStatus::Error,
             payload: None,
+            id: 0,
+            info: None,
+            detail: None,
+            kind: MessageKind::Response,
+        }
+    }
+
+    pub(crate) fn broadcast(state: SimulatorState) -> Self {
+        Self {
+            kind: MessageKind::StateBroadcast,
+            ..Self::success_with(state)
+        }
+    }
+}
+
+/// Versions exchanged in the [RequestType::Hello] handshake.
+///
+/// The client sends its own; the proxy replies with its own, with `features`
+/// narrowed to the bits both sides support. Peers must share a major version.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolInfo {
+    /// Wire protocol major version, changed when the framing is incompatible
+    pub major: u16,
+    /// Wire protocol minor version, changed for additions older peers can ignore
+    pub minor: u16,
+    /// Version of realflight-bridge the peer was built from
+    pub crate_version: String,
+    /// Optional capabilities; unknown bits must be ignored
+    pub features: u32,
+}
+
+impl ProtocolInfo {
+    /// Major version spoken by this build. Version 2 added request ids.
+    pub const MAJOR: u16 = 2;
+    /// Minor version spoken by this build.
+    pub const MINOR: u16 = 0;
+    /// Feature bits supported by this build. None are defined yet.
+    pub const FEATURES: u32 = 0;
+
+    /// Describes this build.
+    pub fn current() -> Self {
+        ProtocolInfo {
+            major: Self::MAJOR,
+            minor: Self::MINOR,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            features: Self::FEATURES,
+        }
+    }
+
+    /// Whether a peer described by `self` can talk to this build.
+    pub fn is_compatible(&self) -> bool {
+        self.major == Self::MAJOR
+    }
+}
+
+/// How a [RealFlightRemoteBridge] re-dials the proxy after losing its connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Connection attempts made before a request gives up
+    pub max_attempts: u32,
+    /// Pause between consecutive attempts
+    pub backoff: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            max_attempts: 3,
+            backoff: Duration::from_millis(500),
         }
     }
 }
 
+/// Builder for [RealFlightRemoteBridge].
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::{RealFlightRemoteBridge, ReconnectPolicy};
+/// use std::time::Duration;
+///
+/// fn main() -> Result<(), realflight_bridge::BridgeError> {
+///     let bridge = RealFlightRemoteBridge::builder("192.168.1.100:8080")
+///         .timeout(Duration::from_secs(2))
+///         .reconnect(ReconnectPolicy::default())
+///         .build()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RealFlightRemoteBridgeBuilder {
+    target: Target,
+    connect_timeout: Duration,
+    reconnect: Option<ReconnectPolicy>,
+    legacy_protocol: bool,
+    keep_alive: Option<Duration>,
+    max_stale_frames: u32,
+    max_frame_size: usize,
+    auth_token: Option<String>,
+}
+
+impl RealFlightRemoteBridgeBuilder {
+    /// Creates a new builder with the specified address.
+    pub fn new(address: &str) -> Self {
+        Self::with_target(Target::Address(address.to_string()))
+    }
+
+    /// Creates a new builder for a proxy listening on the Unix domain socket
+    /// at `path`, e.g. one started with
+    /// [AsyncProxyServer::bind_uds](crate::AsyncProxyServer::bind_uds).
+    ///
+    /// Skips TCP on the loopback interface when the proxy runs on the same
+    /// host. The [timeout](Self::timeout) only bounds the handshake, as a
+    /// local socket connects or fails at once.
+    #[cfg(unix)]
+    pub fn uds(path: impl AsRef<Path>) -> Self {
+        Self::with_target(Target::Unix(path.as_ref().to_path_buf()))
+    }
+
+    fn with_target(target: Target) -> Self {
+        Self {
+            target,
+            connect_timeout: DEFAULT_REMOTE_CONNECT_TIMEOUT,
+            reconnect: None,
+            legacy_protocol: false,
+            keep_alive: None,
+            max_stale_frames: DEFAULT_REMOTE_MAX_STALE_FRAMES,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            auth_token: None,
+        }
+    }
+
+    /// Sets the connection timeout, also used when reconnecting and as the
+    /// time allowed for the proxy to answer the version handshake.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Re-dials the proxy when the connection is lost. Disabled by default.
+    ///
+    /// Before each request, a connection that failed or was closed by the proxy
+    /// is replaced, trying up to `max_attempts` times. A request that fails
+    /// mid-exchange is returned as an error and never resent, because the proxy
+    /// may already have applied it; the next request uses a new connection.
+    #[must_use]
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    /// Talks to proxies that predate the version handshake. Disabled by default.
+    ///
+    /// Skips [RequestType::Hello] and leaves request ids off the wire, so
+    /// responses cannot be checked against their requests.
+    #[must_use]
+    pub fn legacy_protocol(mut self, enabled: bool) -> Self {
+        self.legacy_protocol = enabled;
+        self
+    }
+
+    /// Pings the proxy from a background thread once no request has been sent
+    /// for `interval`. Disabled by default.
+    ///
+    /// Keeps NAT gateways and firewalls from dropping an idle connection. A
+    /// ping that fails makes [RealFlightRemoteBridge::is_connected] return
+    /// `false`; with a [ReconnectPolicy], the next ping re-dials. Ignored with
+    /// [legacy_protocol](Self::legacy_protocol), as older proxies cannot
+    /// answer pings.
+    ///
+    /// Dropping the bridge waits for a ping in flight, at most the
+    /// [timeout](Self::timeout).
+    #[must_use]
+    pub fn keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = Some(interval);
+        self
+    }
+
+    /// Sets how many consecutive failed exchanges
+    /// [RealFlightRemoteBridge::exchange_data_or_last] answers with the last
+    /// good state before returning the error. Defaults to
+    /// [DEFAULT_REMOTE_MAX_STALE_FRAMES].
+    #[must_use]
+    pub fn max_stale_frames(mut self, frames: u32) -> Self {
+        self.max_stale_frames = frames;
+        self
+    }
+
+    /// Sets the largest response, in bytes, the bridge accepts from the proxy.
+    /// Defaults to [DEFAULT_MAX_FRAME_SIZE].
+    ///
+    /// A length prefix above the limit fails the request with
+    /// [BridgeError::Protocol] and closes the connection, before anything is
+    /// allocated for it.
+    #[must_use]
+    pub fn max_frame_size(mut self, bytes: usize) -> Self {
+        self.max_frame_size = bytes;
+        self
+    }
+
+    /// Presents `token` to a proxy that requires one, set with
+    /// [AsyncProxyServer::auth_token](crate::AsyncProxyServer::auth_token).
+    /// Not sent by default.
+    ///
+    /// The token is sent after the version handshake on every connection,
+    /// including reconnects. Building fails with
+    /// [BridgeError::Initialization] if the proxy rejects it. Ignored with
+    /// [legacy_protocol](Self::legacy_protocol).
+    #[must_use]
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Builds the bridge, connecting to the proxy and checking that it speaks
+    /// a compatible protocol.
+    pub fn build(self) -> Result<RealFlightRemoteBridge, BridgeError> {
+        let endpoint = self.target.resolve()?;
+        let mut connection = Connection::open(&endpoint, self.connect_timeout)
+            .map_err(|e| BridgeError::Initialization(format!("Connection failed: {}", e)))?;
+        connection.max_frame_size = self.max_frame_size;
+        let next_id = AtomicU32::new(0);
+        let proxy_info = connection.handshake(
+            self.legacy_protocol,
+            next_id.fetch_add(1, Ordering::Relaxed),
+            self.connect_timeout,
+        )?;
+        if let (Some(token), false) = (&self.auth_token, self.legacy_protocol) {
+            let id = next_id.fetch_add(1, Ordering::Relaxed);
+            connection.authenticate(token, id, self.connect_timeout)?;
+        }
+
+        let inner = Arc::new(Shared {
+            connection: Mutex::new(connection),
+            endpoint,
+            connect_timeout: self.connect_timeout,
+            reconnect: self.reconnect,
+            reconnect_count: AtomicU32::new(0),
+            next_id,
+            legacy_protocol: self.legacy_protocol,
+            proxy_info,
+            statistics: StatisticsEngine::new(),
+            stale: Mutex::new(StaleState::default()),
+            max_stale_frames: self.max_stale_frames,
+            max_frame_size: self.max_frame_size,
+            auth_token: self.auth_token,
+        });
+        let keep_alive = match self.keep_alive {
+            Some(interval) if !self.legacy_protocol => {
+                Some(KeepAlive::start(Arc::clone(&inner), interval)?)
+            }
+            _ => None,
+        };
+
+        Ok(RealFlightRemoteBridge {
+            inner,
+            _keep_alive: keep_alive,
+        })
+    }
+}
+
 /// Client struct for managing TCP communication with the simulator server.
+///
+/// The bridge is `Sync`, so it can be shared behind an `Arc`, e.g. between a
+/// telemetry thread and a control thread. Requests from different threads are
+/// sent one at a time, each holding the connection until its response arrives.
 pub struct RealFlightRemoteBridge {
-    reader: RefCell<BufReader<TcpStream>>, // Buffered reader for incoming data
-    writer: RefCell<BufWriter<TcpStream>>, // Buffered writer for outgoing data
-    response_buffer: RefCell<Vec<u8>>,     // Reusable buffer for responses
+    inner: Arc<Shared>,
+    /// Stops the keep-alive thread when the bridge is dropped
+    _keep_alive: Option<KeepAlive>,
+}
+
+/// State shared with the keep-alive thread.
+struct Shared {
+    connection: Mutex<Connection>,
+    /// Where the proxy is, dialled again on reconnect
+    endpoint: Endpoint,
+    connect_timeout: Duration,
+    reconnect: Option<ReconnectPolicy>,
+    reconnect_count: AtomicU32,
+    next_id: AtomicU32,
+    legacy_protocol: bool,
+    proxy_info: Option<ProtocolInfo>,
+    statistics: StatisticsEngine,
+    stale: Mutex<StaleState>,
+    max_stale_frames: u32,
+    max_frame_size: usize,
+    auth_token: Option<String>,
+}
+
+/// Connection to the proxy, locked for the duration of a request.
+struct Connection {
+    reader: BufReader<Stream>, // Buffered reader for incoming data
+    writer: BufWriter<Stream>, // Buffered writer for outgoing data
+    response_buffer: Vec<u8>,  // Reusable buffer for responses
+    /// Whether the last request failed with an I/O error
+    broken: bool,
+    /// Whether to use the framing of proxies that predate the handshake
+    legacy: bool,
+    /// When the last request on this connection was sent
+    last_used: Instant,
+    /// Request sent by `send_request_nonblocking` and not yet answered
+    pending: Option<Pending>,
+    /// Bytes of the pending response read so far
+    partial: Vec<u8>,
+    /// Largest response length prefix accepted
+    max_frame_size: usize,
+}
+
+/// A request awaiting [RealFlightRemoteBridge::poll_response].
+#[derive(Clone, Copy)]
+struct Pending {
+    id: u32,
+    sent: Instant,
+}
+
+/// Background thread sending [RequestType::Ping] on an idle connection.
+struct KeepAlive {
+    stop: mpsc::Sender<()>,
+    handle: Option<JoinHandle<()>>,
 }
 
 impl RealFlightBridge for RealFlightRemoteBridge {
     /// Enables remote control on the simulator.
     fn enable_rc(&self) -> Result<(), BridgeError> {
-        self.send_request(RequestType::EnableRC, None)?;
+        self.inner.send_request(RequestType::EnableRC, None)?;
         Ok(())
     }
 
     /// Disables remote control on the simulator. (Enables control by the RealFlight link.)
     fn disable_rc(&self) -> Result<(), BridgeError> {
-        self.send_request(RequestType::DisableRC, None)?;
+        self.inner.send_request(RequestType::DisableRC, None)?;
         Ok(())
     }
 
     /// Resets the aircraft state in the simulator.
     fn reset_aircraft(&self) -> Result<(), BridgeError> {
-        self.send_request(RequestType::ResetAircraft, None)?;
+        self.inner.send_request(RequestType::ResetAircraft, None)?;
         Ok(())
     }
 
@@ -144,103 +515,779 @@ impl RealFlightBridge for RealFlightRemoteBridge {
     /// # Returns
     /// The [SimulatorState] or an error if no state is returned.
     fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
-        let response = self.send_request(RequestType::ExchangeData, Some(control.clone()))?;
-        if let Some(state) = response.payload {
-            Ok(state)
-        } else {
-            error!("No payload in response: {:?}", response.status);
-            Err(BridgeError::SoapFault("No payload in response".to_string()))
+        let response = self
+            .inner
+            .send_request(RequestType::ExchangeData, Some(control.clone()))?;
+        match response.payload {
+            Some(state) => Ok(state),
+            None => Err(missing_payload(&response)),
         }
     }
 }
 
 impl RealFlightRemoteBridge {
-    /// Default connection timeout (5 seconds)
-    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
-
     /// Creates a new client instance connected to the specified address.
     ///
     /// # Arguments
     /// * `address` - The server address (e.g., "127.0.0.1:18083").
     ///
     /// # Returns
-    /// A `Result` containing the new client instance or an I/O error.
-    pub fn new(address: &str) -> std::io::Result<Self> {
-        Self::with_timeout(address, Self::DEFAULT_TIMEOUT)
+    /// A `Result` containing the new client instance, or
+    /// [BridgeError::Initialization] if the proxy is unreachable or speaks an
+    /// incompatible protocol.
+    pub fn new(address: &str) -> Result<Self, BridgeError> {
+        Self::with_timeout(address, DEFAULT_REMOTE_CONNECT_TIMEOUT)
     }
 
     /// Creates a new client instance with a custom timeout.
     ///
+    /// Each address `address` resolves to is tried in turn, waiting at most
+    /// `timeout` on each, so a host that silently drops packets fails fast.
+    ///
     /// # Arguments
     /// * `address` - The server address (e.g., "127.0.0.1:18083").
-    /// * `timeout` - Connection timeout duration.
+    /// * `timeout` - Connection timeout duration, per resolved address.
     ///
     /// # Returns
-    /// A `Result` containing the new client instance or an I/O error.
-    pub fn with_timeout(address: &str, timeout: Duration) -> std::io::Result<Self> {
-        let addr = address.to_socket_addrs()?.next().ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid address")
-        })?;
+    /// A `Result` containing the new client instance, or
+    /// [BridgeError::Initialization] if the proxy is unreachable or speaks an
+    /// incompatible protocol.
+    pub fn with_timeout(address: &str, timeout: Duration) -> Result<Self, BridgeError> {
+        RealFlightRemoteBridgeBuilder::new(address)
+            .timeout(timeout)
+            .build()
+    }
 
-        let stream = TcpStream::connect_timeout(&addr, timeout)?;
-        stream.set_nodelay(true)?;
+    /// Returns a builder for custom configuration.
+    pub fn builder(address: &str) -> RealFlightRemoteBridgeBuilder {
+        RealFlightRemoteBridgeBuilder::new(address)
+    }
 
-        Ok(RealFlightRemoteBridge {
-            reader: RefCell::new(BufReader::new(stream.try_clone()?)),
-            writer: RefCell::new(BufWriter::new(stream)),
-            response_buffer: RefCell::new(Vec::with_capacity(4096)),
-        })
+    /// Creates a new client instance connected to a proxy listening on the
+    /// Unix domain socket at `path`.
+    ///
+    /// # Returns
+    /// A `Result` containing the new client instance, or
+    /// [BridgeError::Initialization] if the socket is missing, not accessible
+    /// to this user, or speaks an incompatible protocol.
+    #[cfg(unix)]
+    pub fn connect_uds(path: impl AsRef<Path>) -> Result<Self, BridgeError> {
+        RealFlightRemoteBridgeBuilder::uds(path).build()
+    }
+
+    /// The proxy's versions and the features both sides support, or `None`
+    /// with [RealFlightRemoteBridgeBuilder::legacy_protocol].
+    pub fn proxy_info(&self) -> Option<&ProtocolInfo> {
+        self.inner.proxy_info.as_ref()
+    }
+
+    /// Exchanges data like [exchange_data](RealFlightBridge::exchange_data),
+    /// but answers a failed exchange with the last good state.
+    ///
+    /// Returns the state and whether it is stale. When the proxy reports an
+    /// error, the previous state is returned with `true` until more than
+    /// [max_stale_frames](RealFlightRemoteBridgeBuilder::max_stale_frames)
+    /// consecutive exchanges have failed. Connection errors, and failures
+    /// before any state has been received, are returned at once.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use realflight_bridge::{BridgeError, ControlInputs, RealFlightRemoteBridge};
+    ///
+    /// fn main() -> Result<(), BridgeError> {
+    ///     let bridge = RealFlightRemoteBridge::new("192.168.1.100:8080")?;
+    ///     let (state, stale) = bridge.exchange_data_or_last(&ControlInputs::default())?;
+    ///     if stale {
+    ///         println!("Reusing state after {} failures", bridge.consecutive_failures());
+    ///     }
+    ///     println!("Airspeed: {:?}", state.airspeed);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn exchange_data_or_last(
+        &self,
+        control: &ControlInputs,
+    ) -> Result<(SimulatorState, bool), BridgeError> {
+        let result = self.exchange_data(control);
+        let mut stale = self.inner.stale.lock().unwrap();
+        stale.resolve(result, self.inner.max_stale_frames)
+    }
+
+    /// Sends [ControlInputs] without waiting for the response.
+    ///
+    /// Collect the [SimulatorState] later with
+    /// [poll_response](Self::poll_response). Only one request may be
+    /// outstanding: until it has been answered, or has failed, this method
+    /// and the blocking requests return [BridgeError::InvalidState], and
+    /// keep-alive pings are held back.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::{Duration, Instant};
+    /// use realflight_bridge::{BridgeError, ControlInputs, RealFlightRemoteBridge};
+    ///
+    /// fn main() -> Result<(), BridgeError> {
+    ///     let bridge = RealFlightRemoteBridge::new("192.168.1.100:8080")?;
+    ///     bridge.send_request_nonblocking(&ControlInputs::default())?;
+    ///     loop {
+    ///         // Redraw the UI, then check for the response without blocking
+    ///         let deadline = Instant::now() + Duration::from_millis(5);
+    ///         if let Some(state) = bridge.poll_response(deadline)? {
+    ///             println!("Airspeed: {:?}", state.airspeed);
+    ///             break;
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn send_request_nonblocking(&self, control: &ControlInputs) -> Result<(), BridgeError> {
+        let mut connection = self.inner.connection.lock().unwrap();
+        self.inner.prepare(&mut connection)?;
+
+        let request = Request {
+            request_type: RequestType::ExchangeData,
+            payload: Some(control.clone()),
+            id: self.inner.next_id.fetch_add(1, Ordering::Relaxed),
+        };
+        let sent = Instant::now();
+        if let Err(e) = connection.write_request(&request) {
+            self.inner.statistics.increment_request_count();
+            self.inner.statistics.increment_error_count();
+            if !is_malformed(&e) {
+                connection.broken = true;
+            }
+            return Err(e);
+        }
+        connection.pending = Some(Pending {
+            id: request.id,
+            sent,
+        });
+        Ok(())
     }
 
+    /// Checks for the response to
+    /// [send_request_nonblocking](Self::send_request_nonblocking), waiting
+    /// until `deadline` at most.
+    ///
+    /// Returns `Ok(None)` if the response has not fully arrived by then; the
+    /// bytes read so far are kept for the next call. A deadline already in
+    /// the past only reads what is available without blocking.
+    ///
+    /// # Errors
+    /// [BridgeError::InvalidState] if no request is outstanding; otherwise
+    /// the errors of [exchange_data](RealFlightBridge::exchange_data), after
+    /// which the request is no longer outstanding.
+    pub fn poll_response(&self, deadline: Instant) -> Result<Option<SimulatorState>, BridgeError> {
+        let mut connection = self.inner.connection.lock().unwrap();
+        let Some(pending) = connection.pending else {
+            return Err(BridgeError::InvalidState(
+                "No request is outstanding".to_string(),
+            ));
+        };
+
+        let result = match connection.poll_frame(pending.id, deadline) {
+            Ok(None) => return Ok(None),
+            Ok(Some(response)) => Ok(response),
+            Err(e) => Err(e),
+        };
+        connection.pending = None;
+        connection.partial.clear();
+        self.inner.statistics.increment_request_count();
+        self.inner.statistics.add_io_time(pending.sent.elapsed());
+        if result.is_err() {
+            self.inner.statistics.increment_error_count();
+        }
+        if let Err(e) = &result {
+            if !is_malformed(e) {
+                connection.broken = true;
+            }
+        }
+
+        let response = result?;
+        if response.id != pending.id {
+            connection.broken = true;
+            return Err(id_mismatch(pending.id, response.id));
+        }
+        match response.payload {
+            Some(state) => Ok(Some(state)),
+            None => Err(missing_payload(&response)),
+        }
+    }
+
+    /// Number of consecutive failed exchanges seen by
+    /// [exchange_data_or_last](Self::exchange_data_or_last); zero after a
+    /// success.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.inner.stale.lock().unwrap().failures()
+    }
+
+    /// Returns a snapshot of client-side statistics.
+    ///
+    /// Counts requests sent through the proxy and their round-trip time in
+    /// `io_total`, excluding the handshake and keep-alive pings.
+    pub fn statistics(&self) -> Statistics {
+        self.inner.statistics.snapshot()
+    }
+
+    /// Returns how many times the bridge has replaced a lost connection.
+    pub fn reconnect_count(&self) -> u32 {
+        self.inner.reconnect_count.load(Ordering::Relaxed)
+    }
+
+    /// Checks whether the connection to the proxy is still open.
+    ///
+    /// Peeks at the socket without blocking or consuming data. Returns `false`
+    /// once the proxy has closed the connection, the socket has failed, or a
+    /// request or keep-alive ping has failed on it.
+    /// Waits only for a request in flight on another thread to finish.
+    pub fn is_connected(&self) -> bool {
+        let mut connection = self.inner.connection.lock().unwrap();
+        !connection.broken && connection.is_open()
+    }
+}
+
+impl Shared {
     /// Sends a request to the server and receives a response.
     ///
+    /// With a [ReconnectPolicy], a lost connection is replaced first.
+    ///
     /// # Arguments
     /// * `request_type` - The type of request to send.
     /// * `payload` - Optional [ControlInputs] to include in the request.
     ///
     /// # Returns
-    /// A `Result` containing the server's response or an I/O error.
+    /// A `Result` containing the server's response, an I/O error, or
+    /// [BridgeError::Protocol] if the response answers a different request.
     fn send_request(
         &self,
         request_type: RequestType,
         payload: Option<ControlInputs>,
-    ) -> std::io::Result<Response> {
+    ) -> Result<Response, BridgeError> {
+        let mut connection = self.connection.lock().unwrap();
+        self.send_locked(&mut connection, request_type, payload, None)
+    }
+
+    /// Sends a request on a locked connection, waiting at most `limit` for
+    /// the response if set.
+    fn send_locked(
+        &self,
+        connection: &mut Connection,
+        request_type: RequestType,
+        payload: Option<ControlInputs>,
+        limit: Option<Duration>,
+    ) -> Result<Response, BridgeError> {
+        self.prepare(connection)?;
+
         let request = Request {
             request_type,
             payload,
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
         };
+        let start = Instant::now();
+        let result = connection.exchange_within(&request, limit);
+        if is_measured(&request.request_type) {
+            self.statistics.increment_request_count();
+            self.statistics.add_io_time(start.elapsed());
+            if result.is_err() {
+                self.statistics.increment_error_count();
+            }
+        }
+        if let Err(e) = &result {
+            // A malformed response leaves the stream usable
+            if !is_malformed(e) {
+                connection.broken = true;
+            }
+        }
 
-        // Serialize the request to a byte vector
-        let request_bytes = to_stdvec(&request)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let response = result?;
+        if response.id != request.id {
+            // Requests and responses are out of step from here on
+            connection.broken = true;
+            return Err(id_mismatch(request.id, response.id));
+        }
+        Ok(response)
+    }
 
-        let mut writer = self.writer.borrow_mut();
+    /// Readies a locked connection for a new request, replacing a lost one
+    /// if there is a [ReconnectPolicy].
+    fn prepare(&self, connection: &mut Connection) -> Result<(), BridgeError> {
+        if connection.pending.is_some() {
+            return Err(BridgeError::InvalidState(
+                "A non-blocking request is still outstanding".to_string(),
+            ));
+        }
+        connection.last_used = Instant::now();
+        if let Some(policy) = &self.reconnect {
+            if connection.broken || !connection.is_open() {
+                *connection = self.redial(policy)?;
+                connection.last_used = Instant::now();
+                self.reconnect_count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        Ok(())
+    }
 
-        // Send the length of the request (4 bytes)
-        let length_bytes = (request_bytes.len() as u32).to_be_bytes();
-        writer.write_all(&length_bytes)?;
+    /// Opens a replacement connection, returning the last connect error once
+    /// all attempts fail.
+    fn redial(&self, policy: &ReconnectPolicy) -> Result<Connection, BridgeError> {
+        let mut last_error = None;
+        for attempt in 0..policy.max_attempts {
+            if attempt > 0 {
+                thread::sleep(policy.backoff);
+            }
+            match self.dial() {
+                Ok(connection) => return Ok(connection),
+                Err(e) => {
+                    error!(
+                        "Reconnect attempt {} to {} failed: {}",
+                        attempt + 1,
+                        self.endpoint,
+                        e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotConnected, "Connection to proxy lost").into()
+        }))
+    }
 
-        // Send the serialized request data
-        writer.write_all(&request_bytes)?;
-        writer.flush()?;
+    /// Opens a connection and repeats the handshake, and authentication if
+    /// configured, on it.
+    fn dial(&self) -> Result<Connection, BridgeError> {
+        let mut connection = Connection::open(&self.endpoint, self.connect_timeout)?;
+        connection.max_frame_size = self.max_frame_size;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        connection.handshake(self.legacy_protocol, id, self.connect_timeout)?;
+        if let (Some(token), false) = (&self.auth_token, self.legacy_protocol) {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            connection.authenticate(token, id, self.connect_timeout)?;
+        }
+        Ok(connection)
+    }
+}
 
-        let mut reader = self.reader.borrow_mut();
+impl KeepAlive {
+    fn start(shared: Arc<Shared>, interval: Duration) -> std::io::Result<Self> {
+        let (stop, stopped) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name(format!("keep-alive-{}", shared.endpoint))
+            .spawn(move || shared.keep_alive(interval, stopped))?;
+        Ok(KeepAlive {
+            stop,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        let _ = self.stop.send(());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Shared {
+    /// Pings the proxy whenever the connection has been idle for `interval`,
+    /// until `stopped` receives or its sender is dropped.
+    fn keep_alive(&self, interval: Duration, stopped: mpsc::Receiver<()>) {
+        loop {
+            let idle = self.connection.lock().unwrap().last_used.elapsed();
+            if stopped.recv_timeout(interval.saturating_sub(idle))
+                != Err(mpsc::RecvTimeoutError::Timeout)
+            {
+                return;
+            }
+
+            let mut connection = self.connection.lock().unwrap();
+            if connection.last_used.elapsed() < interval {
+                continue; // A request was sent while waiting
+            }
+            if connection.pending.is_some() || (connection.broken && self.reconnect.is_none()) {
+                // Busy, or nothing to revive; check again after another interval
+                connection.last_used = Instant::now();
+                continue;
+            }
+            let limit = Some(self.connect_timeout);
+            if let Err(e) = self.send_locked(&mut connection, RequestType::Ping, None, limit) {
+                warn!("Keep-alive ping to {} failed: {}", self.endpoint, e);
+            }
+        }
+    }
+}
+
+impl Connection {
+    /// Opens a connection to the first reachable address, waiting at most
+    /// `timeout` on each. Returns the error from the last address tried.
+    fn open(endpoint: &Endpoint, timeout: Duration) -> std::io::Result<Self> {
+        Self::from_stream(Stream::connect(endpoint, timeout)?)
+    }
+
+    fn from_stream(stream: Stream) -> std::io::Result<Self> {
+        Ok(Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: BufWriter::new(stream),
+            response_buffer: Vec::with_capacity(4096),
+            broken: false,
+            legacy: false,
+            last_used: Instant::now(),
+            pending: None,
+            partial: Vec::new(),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+        })
+    }
+
+    /// Sends [RequestType::Hello], waiting at most `timeout` for the reply.
+    /// In legacy mode, only switches to the old framing.
+    fn handshake(
+        &mut self,
+        legacy: bool,
+        id: u32,
+        timeout: Duration,
+    ) -> Result<Option<ProtocolInfo>, BridgeError> {
+        if legacy {
+            self.legacy = true;
+            return Ok(None);
+        }
+
+        let request = Request {
+            request_type: RequestType::Hello(ProtocolInfo::current()),
+            payload: None,
+            id,
+        };
+        let response = self
+            .exchange_within(&request, Some(timeout))
+            .map_err(|e| match e {
+                BridgeError::Connection(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    BridgeError::Initialization(format!(
+                        "No reply to the version handshake within {:?}; \
+                         proxies that predate it need legacy_protocol",
+                        timeout
+                    ))
+                }
+                BridgeError::Protocol(_) => e,
+                e => BridgeError::Initialization(format!("Version handshake failed: {}", e)),
+            })?;
+        if response.id != id {
+            return Err(id_mismatch(id, response.id));
+        }
+        check_hello(response).map(Some)
+    }
+
+    /// Sends [RequestType::Authenticate], waiting at most `timeout` for the
+    /// reply.
+    fn authenticate(&mut self, token: &str, id: u32, timeout: Duration) -> Result<(), BridgeError> {
+        let request = Request {
+            request_type: RequestType::Authenticate {
+                token: token.to_string(),
+            },
+            payload: None,
+            id,
+        };
+        let response = self
+            .exchange_within(&request, Some(timeout))
+            .map_err(|e| BridgeError::Initialization(format!("Authentication failed: {}", e)))?;
+        check_authenticated(response, id)
+    }
+
+    /// Checks the socket without blocking or consuming data; anything that
+    /// has arrived stays in the read buffer.
+    fn is_open(&mut self) -> bool {
+        if !self.reader.buffer().is_empty() {
+            return true;
+        }
+
+        if self.reader.get_ref().set_nonblocking(true).is_err() {
+            return false;
+        }
+        let connected = match self.reader.fill_buf() {
+            Ok(buffer) => !buffer.is_empty(), // empty on orderly shutdown by the proxy
+            Err(e) => e.kind() == std::io::ErrorKind::WouldBlock,
+        };
+        self.reader.get_ref().set_nonblocking(false).is_ok() && connected
+    }
+
+    /// Like [exchange](Self::exchange), waiting at most `limit` for the
+    /// response if set.
+    fn exchange_within(
+        &mut self,
+        request: &Request,
+        limit: Option<Duration>,
+    ) -> Result<Response, BridgeError> {
+        if limit.is_none() {
+            return self.exchange(request);
+        }
+        self.reader.get_ref().set_read_timeout(limit)?;
+        let result = self.exchange(request);
+        self.reader.get_ref().set_read_timeout(None)?;
+        result
+    }
+
+    /// Writes one request and reads its response.
+    fn exchange(&mut self, request: &Request) -> Result<Response, BridgeError> {
+        self.write_request(request)?;
 
         // Read the response length (4 bytes)
         let mut length_buffer = [0u8; 4];
-        reader.read_exact(&mut length_buffer)?;
+        self.reader.read_exact(&mut length_buffer)?;
         let response_length = u32::from_be_bytes(length_buffer) as usize;
+        self.check_frame_length(response_length)?;
 
         // Read the response data into reusable buffer
-        let mut response_buffer = self.response_buffer.borrow_mut();
-        response_buffer.clear();
-        response_buffer.resize(response_length, 0);
-        reader.read_exact(&mut response_buffer)?;
+        self.response_buffer.clear();
+        self.response_buffer.resize(response_length, 0);
+        self.reader.read_exact(&mut self.response_buffer)?;
 
         // Deserialize the response
-        let response: Response = from_bytes(&response_buffer)
+        let response = decode_response(&self.response_buffer, request.id, self.legacy)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
 
         Ok(response)
     }
+
+    /// Writes one length-prefixed request.
+    fn write_request(&mut self, request: &Request) -> Result<(), BridgeError> {
+        // Serialize the request to a byte vector
+        let request_bytes = encode_request(request, self.legacy)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        // Send the length of the request (4 bytes)
+        let length_bytes = (request_bytes.len() as u32).to_be_bytes();
+        self.writer.write_all(&length_bytes)?;
+
+        // Send the serialized request data
+        self.writer.write_all(&request_bytes)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads into the partial-response buffer until a whole response has
+    /// arrived or `deadline` passes, returning `None` in the latter case.
+    fn poll_frame(
+        &mut self,
+        request_id: u32,
+        deadline: Instant,
+    ) -> Result<Option<Response>, BridgeError> {
+        let mut chunk = [0u8; 4096];
+        loop {
+            if self.partial.len() >= 4 {
+                let length = u32::from_be_bytes(self.partial[..4].try_into().unwrap()) as usize;
+                self.check_frame_length(length)?;
+                if self.partial.len() >= 4 + length {
+                    let response =
+                        decode_response(&self.partial[4..4 + length], request_id, self.legacy)
+                            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                    return Ok(Some(response));
+                }
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.read_until(&mut chunk, remaining) {
+                Ok(0) => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+                Ok(n) => self.partial.extend_from_slice(&chunk[..n]),
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Rejects a response length above the limit, shutting the socket so the
+    /// rest of the oversized frame is never read.
+    fn check_frame_length(&mut self, length: usize) -> Result<(), BridgeError> {
+        let result = check_frame_length(length, self.max_frame_size);
+        if result.is_err() {
+            let _ = self.reader.get_ref().shutdown();
+        }
+        result
+    }
+
+    /// Reads whatever is available, waiting at most `wait`; a zero wait
+    /// switches the socket to non-blocking mode for the read.
+    fn read_until(&mut self, buffer: &mut [u8], wait: Duration) -> std::io::Result<usize> {
+        if !self.reader.buffer().is_empty() {
+            return self.reader.read(buffer);
+        }
+        let stream = self.reader.get_ref();
+        if wait.is_zero() {
+            stream.set_nonblocking(true)?;
+            let result = self.reader.read(buffer);
+            self.reader.get_ref().set_nonblocking(false)?;
+            result
+        } else {
+            stream.set_read_timeout(Some(wait))?;
+            let result = self.reader.read(buffer);
+            self.reader.get_ref().set_read_timeout(None)?;
+            result
+        }
+    }
+}
+
+/// Last good state and failure streak behind `exchange_data_or_last`.
+#[derive(Default)]
+pub(crate) struct StaleState {
+    last: Option<SimulatorState>,
+    failures: u32,
+}
+
+impl StaleState {
+    /// Records the outcome of an exchange. A proxy error is answered with the
+    /// last good state while at most `limit` exchanges in a row have failed.
+    pub(crate) fn resolve(
+        &mut self,
+        result: Result<SimulatorState, BridgeError>,
+        limit: u32,
+    ) -> Result<(SimulatorState, bool), BridgeError> {
+        let error = match result {
+            Ok(state) => {
+                self.failures = 0;
+                self.last = Some(state.clone());
+                return Ok((state, false));
+            }
+            Err(e) => e,
+        };
+
+        self.failures = self.failures.saturating_add(1);
+        match (&error, &self.last) {
+            // The proxy answered, but the simulator call behind it failed
+            (BridgeError::SoapFault(_), Some(last)) if self.failures <= limit => {
+                Ok((last.clone(), true))
+            }
+            _ => Err(error),
+        }
+    }
+
+    pub(crate) fn failures(&self) -> u32 {
+        self.failures
+    }
+}
+
+/// Whether a request counts toward the bridge statistics. Protocol overhead
+/// does not reach the simulator, so it is left out.
+pub(crate) fn is_measured(request_type: &RequestType) -> bool {
+    !matches!(
+        request_type,
+        RequestType::Hello(_)
+            | RequestType::Ping
+            | RequestType::Authenticate { .. }
+            | RequestType::Subscribe
+    )
+}
+
+/// Whether an error came from a frame that could not be encoded or decoded,
+/// which leaves the stream usable.
+fn is_malformed(error: &BridgeError) -> bool {
+    matches!(error, BridgeError::Connection(e) if e.kind() == std::io::ErrorKind::InvalidData)
+}
+
+/// Rejects a frame length prefix above `max`, before anything is allocated
+/// for the frame.
+pub(crate) fn check_frame_length(length: usize, max: usize) -> Result<(), BridgeError> {
+    if length > max {
+        return Err(BridgeError::Protocol(format!(
+            "Frame of {} bytes exceeds the {} byte limit",
+            length, max
+        )));
+    }
+    Ok(())
+}
+
+/// Error for an exchange answered without a [SimulatorState].
+pub(crate) fn missing_payload(response: &Response) -> BridgeError {
+    if response.detail == Some(ErrorDetail::Throttled) {
+        warn!("Request throttled by the proxy");
+        return BridgeError::SoapFault("Request throttled by the proxy".to_string());
+    }
+    error!("No payload in response: {:?}", response.status);
+    BridgeError::SoapFault("No payload in response".to_string())
+}
+
+/// Error for a response whose id does not match the request sent.
+pub(crate) fn id_mismatch(expected: u32, actual: u32) -> BridgeError {
+    BridgeError::Protocol(format!(
+        "Response id {} does not match request id {}",
+        actual, expected
+    ))
+}
+
+/// Serializes a request. Proxies that predate the handshake expect only the
+/// fields before `id`; postcard encodes a struct exactly like a tuple of them.
+pub(crate) fn encode_request(request: &Request, legacy: bool) -> postcard::Result<Vec<u8>> {
+    if legacy {
+        to_stdvec(&(&request.request_type, &request.payload))
+    } else {
+        to_stdvec(request)
+    }
+}
+
+/// Deserializes a response. A legacy response carries no id, so it is taken
+/// to answer the request just sent, as before ids existed.
+pub(crate) fn decode_response(
+    bytes: &[u8],
+    request_id: u32,
+    legacy: bool,
+) -> postcard::Result<Response> {
+    if legacy {
+        let (status, payload) = from_bytes(bytes)?;
+        Ok(Response {
+            status,
+            payload,
+            id: request_id,
+            info: None,
+            detail: None,
+            kind: MessageKind::Response,
+        })
+    } else {
+        from_bytes(bytes)
+    }
+}
+
+/// Checks the reply to [RequestType::Authenticate]. A proxy that rejects the
+/// token answers with an error and hangs up.
+pub(crate) fn check_authenticated(response: Response, request_id: u32) -> Result<(), BridgeError> {
+    if response.id != request_id {
+        return Err(id_mismatch(request_id, response.id));
+    }
+    match response.status {
+        ResponseStatus::Success => Ok(()),
+        ResponseStatus::Error => Err(BridgeError::Initialization(
+            "Proxy rejected the auth token".into(),
+        )),
+    }
+}
+
+/// Extracts the proxy's [ProtocolInfo] from a reply to [RequestType::Hello],
+/// rejecting proxies with a different major version.
+pub(crate) fn check_hello(response: Response) -> Result<ProtocolInfo, BridgeError> {
+    let info = response.info.ok_or_else(|| {
+        BridgeError::Initialization("Proxy did not describe its protocol version".into())
+    })?;
+    if !info.is_compatible() {
+        return Err(BridgeError::Initialization(format!(
+            "Proxy speaks protocol {}.{} (realflight-bridge {}), this client speaks {}.{}",
+            info.major,
+            info.minor,
+            info.crate_version,
+            ProtocolInfo::MAJOR,
+            ProtocolInfo::MINOR
+        )));
+    }
+    Ok(info)
 }
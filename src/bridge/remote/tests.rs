@@ -4,19 +4,26 @@
 //! - `connection_tests`: Connection establishment and timeout tests
 //! - `operation_tests`: Tests for bridge operations (enable_rc, disable_rc, etc.)
 //! - `error_handling`: Tests for error conditions and edge cases
+//! - `reconnect`: Tests for replacing a lost connection
+//! - `sharing`: Tests for using one bridge from several threads
 
 use std::{
     io::{ErrorKind, Read, Write},
     net::{TcpListener, TcpStream},
+    sync::Arc,
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use postcard::{from_bytes, to_stdvec};
 
 use crate::{BridgeError, ControlInputs, RealFlightBridge, SimulatorState};
 
-use super::{RealFlightRemoteBridge, Request, RequestType, Response, ResponseStatus};
+use super::transport::Endpoint;
+use super::{
+    Connection, MessageKind, ProtocolInfo, RealFlightRemoteBridge, ReconnectPolicy, Request,
+    RequestType, Response, ResponseStatus,
+};
 
 // ============================================================================
 // Connection Tests
@@ -28,9 +35,10 @@ fn test_connection_failure() {
     // Attempt to connect to a port where no server is running
     let result = RealFlightRemoteBridge::new("127.0.0.1:1");
 
-    assert!(result.is_err());
-    if let Err(e) = result {
-        assert_eq!(e.kind(), ErrorKind::ConnectionRefused);
+    match result {
+        Err(BridgeError::Initialization(msg)) => assert!(msg.contains("Connection failed")),
+        Err(e) => panic!("expected initialization error, got {:?}", e),
+        Ok(_) => panic!("expected initialization error"),
     }
 }
 
@@ -46,6 +54,32 @@ fn test_with_timeout_connection_failure() {
     assert!(elapsed < Duration::from_secs(2));
 }
 
+/// Tests that a host dropping packets fails within the connect timeout
+#[test]
+fn test_with_timeout_non_routable_address() {
+    let start = std::time::Instant::now();
+    let result =
+        RealFlightRemoteBridge::with_timeout("10.255.255.1:18083", Duration::from_millis(100));
+    let elapsed = start.elapsed();
+
+    assert!(result.is_err());
+    assert!(elapsed < Duration::from_secs(1), "took {:?}", elapsed);
+}
+
+/// Tests that an unreachable address is skipped in favour of the next one
+#[test]
+fn test_connect_falls_back_to_next_address() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let refused = "127.0.0.1:1".parse().unwrap();
+
+    let endpoint = Endpoint::Tcp(vec![refused, address]);
+    let connection = Connection::open(&endpoint, Duration::from_millis(100));
+
+    assert!(connection.is_ok());
+    assert!(listener.accept().is_ok());
+}
+
 /// Tests invalid address handling
 #[test]
 fn test_invalid_address() {
@@ -64,7 +98,7 @@ fn test_enable_rc() {
     let address = listener.local_addr().unwrap();
 
     let server_thread = thread::spawn(move || {
-        if let Ok((stream, _)) = listener.accept() {
+        if let Ok(stream) = accept_with_hello(&listener) {
             handle_mock_success(stream);
         }
     });
@@ -86,7 +120,7 @@ fn test_disable_rc() {
     let address = listener.local_addr().unwrap();
 
     let server_thread = thread::spawn(move || {
-        if let Ok((stream, _)) = listener.accept() {
+        if let Ok(stream) = accept_with_hello(&listener) {
             handle_mock_success(stream);
         }
     });
@@ -108,7 +142,7 @@ fn test_reset_aircraft() {
     let address = listener.local_addr().unwrap();
 
     let server_thread = thread::spawn(move || {
-        if let Ok((stream, _)) = listener.accept() {
+        if let Ok(stream) = accept_with_hello(&listener) {
             handle_mock_success(stream);
         }
     });
@@ -130,7 +164,7 @@ fn test_exchange_data() {
     let address = listener.local_addr().unwrap();
 
     let server_thread = thread::spawn(move || {
-        if let Ok((stream, _)) = listener.accept() {
+        if let Ok(stream) = accept_with_hello(&listener) {
             handle_mock_exchange_data(stream);
         }
     });
@@ -156,7 +190,7 @@ fn test_exchange_data_no_payload() {
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let address = listener.local_addr().unwrap();
     let server_thread = thread::spawn(move || {
-        if let Ok((stream, _)) = listener.accept() {
+        if let Ok(stream) = accept_with_hello(&listener) {
             handle_mock_exchange_no_payload(stream);
         }
     });
@@ -169,7 +203,6 @@ fn test_exchange_data_no_payload() {
     let result = client.exchange_data(&control);
     assert!(result.is_err());
 
-    terminate_server(&address.to_string());
     let _ = server_thread.join();
 }
 
@@ -179,7 +212,7 @@ fn test_malformed_response() {
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let address = listener.local_addr().unwrap();
     let server_thread = thread::spawn(move || {
-        if let Ok((stream, _)) = listener.accept() {
+        if let Ok(stream) = accept_with_hello(&listener) {
             handle_mock_malformed_response(stream);
         }
     });
@@ -199,7 +232,96 @@ fn test_malformed_response() {
         }
     }
 
-    terminate_server(&address.to_string());
+    let _ = server_thread.join();
+}
+
+/// Tests that a response answering a different request is rejected
+#[test]
+fn test_mismatched_response_id() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        if let Ok(stream) = accept_with_hello(&listener) {
+            handle_mock_wrong_id(stream);
+        }
+    });
+
+    let client = RealFlightRemoteBridge::new(&address.to_string()).unwrap();
+
+    let result = client.enable_rc();
+    assert!(
+        matches!(result, Err(BridgeError::Protocol(_))),
+        "Expected BridgeError::Protocol, got {:?}",
+        result
+    );
+
+    let _ = server_thread.join();
+}
+
+/// Tests that a length prefix above the limit is rejected and closes the connection
+#[test]
+fn test_oversized_response_closes_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let stream = accept_with_hello(&listener).unwrap();
+        handle_mock_length_prefix(stream, 1000)
+    });
+
+    let client = RealFlightRemoteBridge::builder(&address.to_string())
+        .max_frame_size(64)
+        .build()
+        .unwrap();
+
+    let result = client.enable_rc();
+    assert!(
+        matches!(result, Err(BridgeError::Protocol(_))),
+        "Expected BridgeError::Protocol, got {:?}",
+        result
+    );
+    assert!(!client.is_connected());
+    let _ = server_thread.join();
+}
+
+/// Tests that an absurd length prefix is rejected under the default limit
+#[test]
+fn test_absurd_length_prefix_rejected() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let stream = accept_with_hello(&listener).unwrap();
+        handle_mock_length_prefix(stream, u32::MAX)
+    });
+
+    let client = RealFlightRemoteBridge::new(&address.to_string()).unwrap();
+
+    let result = client.exchange_data(&ControlInputs::default());
+    assert!(matches!(result, Err(BridgeError::Protocol(_))));
+    let _ = server_thread.join();
+}
+
+/// Tests that polling for a response applies the same limit
+#[test]
+fn test_poll_response_rejects_oversized_response() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let stream = accept_with_hello(&listener).unwrap();
+        handle_mock_length_prefix(stream, 1000)
+    });
+
+    let client = RealFlightRemoteBridge::builder(&address.to_string())
+        .max_frame_size(64)
+        .build()
+        .unwrap();
+    client
+        .send_request_nonblocking(&ControlInputs::default())
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let result = client.poll_response(deadline);
+    assert!(matches!(result, Err(BridgeError::Protocol(_))));
+    assert!(!client.is_connected());
     let _ = server_thread.join();
 }
 
@@ -209,9 +331,7 @@ fn test_server_disconnect() {
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let address = listener.local_addr().unwrap();
     let server_thread = thread::spawn(move || {
-        if let Ok((_, _)) = listener.accept() {
-            return;
-        }
+        let _ = accept_with_hello(&listener);
     });
 
     thread::sleep(Duration::from_millis(100));
@@ -227,13 +347,647 @@ fn test_server_disconnect() {
     let _ = server_thread.join();
 }
 
+/// Tests connection liveness before and after the server hangs up
+#[test]
+fn test_is_connected() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (hang_up, hang_up_signal) = std::sync::mpsc::channel::<()>();
+    let server_thread = thread::spawn(move || {
+        let stream = accept_with_hello(&listener).unwrap();
+        let _ = hang_up_signal.recv();
+        drop(stream);
+    });
+
+    let client = RealFlightRemoteBridge::new(&address.to_string()).unwrap();
+    assert!(client.is_connected());
+
+    hang_up.send(()).unwrap();
+    let _ = server_thread.join();
+    thread::sleep(Duration::from_millis(50));
+
+    assert!(!client.is_connected());
+}
+
+// ============================================================================
+// Statistics Tests
+// ============================================================================
+
+/// Tests that requests are counted and timed on the client side
+#[test]
+fn test_statistics_track_requests() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let stream = accept_with_hello(&listener).unwrap();
+        handle_mock_success(stream.try_clone().unwrap());
+        handle_mock_malformed_response(stream);
+    });
+
+    let client = RealFlightRemoteBridge::new(&address.to_string()).unwrap();
+    assert_eq!(client.statistics().request_count, 0);
+
+    assert!(client.enable_rc().is_ok());
+    assert!(client.disable_rc().is_err());
+
+    let statistics = client.statistics();
+    assert_eq!(statistics.request_count, 2);
+    assert_eq!(statistics.error_count, 1);
+    assert!(statistics.io_total > Duration::ZERO);
+    let _ = server_thread.join();
+}
+
+// ============================================================================
+// Stale State Tests
+// ============================================================================
+
+fn state_with_rpm(prop_rpm: f32) -> Response {
+    Response {
+        status: ResponseStatus::Success,
+        payload: Some(SimulatorState {
+            prop_rpm,
+            ..Default::default()
+        }),
+        id: 0,
+        info: None,
+        detail: None,
+        kind: MessageKind::Response,
+    }
+}
+
+fn proxy_error() -> Response {
+    Response {
+        status: ResponseStatus::Error,
+        payload: None,
+        id: 0,
+        info: None,
+        detail: None,
+        kind: MessageKind::Response,
+    }
+}
+
+/// Tests that proxy errors reuse the last state until the streak exceeds the limit
+#[test]
+fn test_exchange_data_or_last_reuses_state() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let mut stream = accept_with_hello(&listener).unwrap();
+        let responses = vec![
+            state_with_rpm(1.0),
+            proxy_error(),
+            state_with_rpm(2.0),
+            proxy_error(),
+            proxy_error(),
+        ];
+        answer_requests(&mut stream, responses);
+    });
+
+    let client = RealFlightRemoteBridge::builder(&address.to_string())
+        .max_stale_frames(1)
+        .build()
+        .unwrap();
+    let control = ControlInputs::default();
+
+    let (state, stale) = client.exchange_data_or_last(&control).unwrap();
+    assert_eq!((state.prop_rpm, stale), (1.0, false));
+
+    let (state, stale) = client.exchange_data_or_last(&control).unwrap();
+    assert_eq!((state.prop_rpm, stale), (1.0, true));
+    assert_eq!(client.consecutive_failures(), 1);
+
+    let (state, stale) = client.exchange_data_or_last(&control).unwrap();
+    assert_eq!((state.prop_rpm, stale), (2.0, false));
+    assert_eq!(client.consecutive_failures(), 0);
+
+    let (state, stale) = client.exchange_data_or_last(&control).unwrap();
+    assert_eq!((state.prop_rpm, stale), (2.0, true));
+
+    let result = client.exchange_data_or_last(&control);
+    assert!(matches!(result, Err(BridgeError::SoapFault(_))));
+    assert_eq!(client.consecutive_failures(), 2);
+    let _ = server_thread.join();
+}
+
+/// Tests that a failure before any good state is returned as an error
+#[test]
+fn test_exchange_data_or_last_without_previous_state() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let mut stream = accept_with_hello(&listener).unwrap();
+        answer_requests(&mut stream, vec![proxy_error()]);
+    });
+
+    let client = RealFlightRemoteBridge::new(&address.to_string()).unwrap();
+
+    let result = client.exchange_data_or_last(&ControlInputs::default());
+    assert!(matches!(result, Err(BridgeError::SoapFault(_))));
+    assert_eq!(client.consecutive_failures(), 1);
+    let _ = server_thread.join();
+}
+
+// ============================================================================
+// Non-Blocking Tests
+// ============================================================================
+
+/// Tests that polling returns nothing until a delayed, split response is complete
+#[test]
+fn test_poll_response_waits_for_delayed_response() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let mut stream = accept_with_hello(&listener).unwrap();
+        let mut length_buffer = [0u8; 4];
+        stream.read_exact(&mut length_buffer).unwrap();
+        let mut buffer = vec![0u8; u32::from_be_bytes(length_buffer) as usize];
+        stream.read_exact(&mut buffer).unwrap();
+        let request: Request = from_bytes(&buffer).unwrap();
+
+        let response = Response {
+            id: request.id,
+            ..state_with_rpm(3.0)
+        };
+        let response_bytes = to_stdvec(&response).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        stream
+            .write_all(&(response_bytes.len() as u32).to_be_bytes())
+            .unwrap();
+        stream.write_all(&response_bytes[..1]).unwrap();
+        stream.flush().unwrap();
+        thread::sleep(Duration::from_millis(100));
+        stream.write_all(&response_bytes[1..]).unwrap();
+        stream
+    });
+
+    let client = RealFlightRemoteBridge::new(&address.to_string()).unwrap();
+    client
+        .send_request_nonblocking(&ControlInputs::default())
+        .unwrap();
+
+    let mut polls = 0;
+    let state = loop {
+        polls += 1;
+        let deadline = Instant::now() + Duration::from_millis(20);
+        if let Some(state) = client.poll_response(deadline).unwrap() {
+            break state;
+        }
+        assert!(polls < 100, "response never arrived");
+    };
+
+    assert_eq!(state.prop_rpm, 3.0);
+    assert!(polls > 2);
+    assert_eq!(client.statistics().request_count, 1);
+    let _stream = server_thread.join().unwrap();
+}
+
+/// Tests that a past deadline reads without blocking
+#[test]
+fn test_poll_response_with_past_deadline_does_not_block() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || accept_with_hello(&listener).unwrap());
+
+    let client = RealFlightRemoteBridge::new(&address.to_string()).unwrap();
+    let _stream = server_thread.join().unwrap();
+    client
+        .send_request_nonblocking(&ControlInputs::default())
+        .unwrap();
+
+    let start = Instant::now();
+    assert!(client.poll_response(Instant::now()).unwrap().is_none());
+    assert!(start.elapsed() < Duration::from_millis(500));
+}
+
+/// Tests that only one request may be outstanding
+#[test]
+fn test_nonblocking_request_must_be_answered_first() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let mut stream = accept_with_hello(&listener).unwrap();
+        answer_requests(&mut stream, vec![state_with_rpm(1.0)]);
+        stream
+    });
+
+    let client = RealFlightRemoteBridge::new(&address.to_string()).unwrap();
+    let result = client.poll_response(Instant::now());
+    assert!(matches!(result, Err(BridgeError::InvalidState(_))));
+
+    let control = ControlInputs::default();
+    client.send_request_nonblocking(&control).unwrap();
+    let result = client.send_request_nonblocking(&control);
+    assert!(matches!(result, Err(BridgeError::InvalidState(_))));
+    let result = client.exchange_data(&control);
+    assert!(matches!(result, Err(BridgeError::InvalidState(_))));
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let state = client.poll_response(deadline).unwrap().unwrap();
+    assert_eq!(state.prop_rpm, 1.0);
+    let result = client.poll_response(Instant::now());
+    assert!(matches!(result, Err(BridgeError::InvalidState(_))));
+    let _stream = server_thread.join().unwrap();
+}
+
+// ============================================================================
+// Reconnect Tests
+// ============================================================================
+
+fn quick_reconnect(max_attempts: u32) -> ReconnectPolicy {
+    ReconnectPolicy {
+        max_attempts,
+        backoff: Duration::from_millis(10),
+    }
+}
+
+/// Tests that a connection closed by the proxy is replaced before the next request
+#[test]
+fn test_reconnects_after_proxy_restart() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (dropped, dropped_signal) = std::sync::mpsc::channel::<()>();
+    let server_thread = thread::spawn(move || {
+        let first = accept_with_hello(&listener).unwrap();
+        drop(first);
+        dropped.send(()).unwrap();
+
+        let second = accept_with_hello(&listener).unwrap();
+        handle_mock_success(second);
+    });
+
+    let client = RealFlightRemoteBridge::builder(&address.to_string())
+        .reconnect(quick_reconnect(3))
+        .build()
+        .unwrap();
+    dropped_signal.recv().unwrap();
+    thread::sleep(Duration::from_millis(50));
+
+    let result = client.enable_rc();
+
+    assert!(result.is_ok(), "Enable RC failed: {:?}", result);
+    assert_eq!(client.reconnect_count(), 1);
+    let _ = server_thread.join();
+}
+
+/// Tests that a request failing mid-exchange is not resent, and the next one reconnects
+#[test]
+fn test_failed_request_is_not_replayed() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        // Read the first request, then hang up without answering
+        let mut first = accept_with_hello(&listener).unwrap();
+        let mut length_buffer = [0u8; 4];
+        first.read_exact(&mut length_buffer).unwrap();
+        drop(first);
+
+        let second = accept_with_hello(&listener).unwrap();
+        handle_mock_success(second);
+    });
+
+    let client = RealFlightRemoteBridge::builder(&address.to_string())
+        .reconnect(quick_reconnect(3))
+        .build()
+        .unwrap();
+
+    assert!(client.enable_rc().is_err());
+    assert_eq!(client.reconnect_count(), 0);
+
+    let result = client.reset_aircraft();
+    assert!(result.is_ok(), "Reset failed: {:?}", result);
+    assert_eq!(client.reconnect_count(), 1);
+    let _ = server_thread.join();
+}
+
+/// Tests that the connect error is returned once all attempts fail
+#[test]
+fn test_reconnect_gives_up_after_max_attempts() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let stream = accept_with_hello(&listener).unwrap();
+        drop(stream);
+        // Listener dropped here, so reconnecting is refused
+    });
+
+    let client = RealFlightRemoteBridge::builder(&address.to_string())
+        .reconnect(quick_reconnect(2))
+        .build()
+        .unwrap();
+    let _ = server_thread.join();
+    thread::sleep(Duration::from_millis(50));
+
+    let start = std::time::Instant::now();
+    let result = client.enable_rc();
+
+    match result {
+        Err(BridgeError::Connection(e)) => assert_eq!(e.kind(), ErrorKind::ConnectionRefused),
+        other => panic!("expected connection error, got {:?}", other),
+    }
+    assert!(start.elapsed() >= Duration::from_millis(10));
+    assert_eq!(client.reconnect_count(), 0);
+}
+
+/// Tests that without a policy a lost connection is not replaced
+#[test]
+fn test_no_reconnect_by_default() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let first = accept_with_hello(&listener).unwrap();
+        drop(first);
+        // A second connection would be served, but must not be made
+        let _ = listener.set_nonblocking(true);
+        thread::sleep(Duration::from_millis(200));
+        listener.accept().is_ok()
+    });
+
+    let client = RealFlightRemoteBridge::new(&address.to_string()).unwrap();
+    thread::sleep(Duration::from_millis(50));
+
+    assert!(client.enable_rc().is_err());
+    assert_eq!(client.reconnect_count(), 0);
+    assert!(!server_thread.join().unwrap());
+}
+
+// ============================================================================
+// Handshake Tests
+// ============================================================================
+
+/// Tests that the proxy's versions are available after construction
+#[test]
+fn test_handshake_reports_proxy_info() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let info = ProtocolInfo {
+            crate_version: "9.9.9".to_string(),
+            ..ProtocolInfo::current()
+        };
+        answer_hello(&mut stream, info).unwrap();
+    });
+
+    let client = RealFlightRemoteBridge::new(&address.to_string()).unwrap();
+
+    let info = client.proxy_info().unwrap();
+    assert_eq!(info.major, ProtocolInfo::MAJOR);
+    assert_eq!(info.crate_version, "9.9.9");
+    let _ = server_thread.join();
+}
+
+/// Tests that a proxy with another major version is rejected
+#[test]
+fn test_incompatible_major_fails_construction() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let info = ProtocolInfo {
+            major: ProtocolInfo::MAJOR + 1,
+            ..ProtocolInfo::current()
+        };
+        answer_hello(&mut stream, info).unwrap();
+    });
+
+    let result = RealFlightRemoteBridge::new(&address.to_string());
+
+    match result {
+        Err(BridgeError::Initialization(msg)) => assert!(msg.contains("protocol"), "{}", msg),
+        Err(e) => panic!("expected initialization error, got {:?}", e),
+        Ok(_) => panic!("expected initialization error"),
+    }
+    let _ = server_thread.join();
+}
+
+/// Tests that a proxy ignoring the handshake fails within the timeout
+#[test]
+fn test_unanswered_handshake_fails_construction() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (done, done_signal) = std::sync::mpsc::channel::<()>();
+    let server_thread = thread::spawn(move || {
+        // An old proxy cannot decode Hello and sends nothing back
+        let (_stream, _) = listener.accept().unwrap();
+        let _ = done_signal.recv();
+    });
+
+    let start = std::time::Instant::now();
+    let result =
+        RealFlightRemoteBridge::with_timeout(&address.to_string(), Duration::from_millis(100));
+
+    match result {
+        Err(BridgeError::Initialization(msg)) => {
+            assert!(msg.contains("legacy_protocol"), "{}", msg)
+        }
+        Err(e) => panic!("expected initialization error, got {:?}", e),
+        Ok(_) => panic!("expected initialization error"),
+    }
+    assert!(start.elapsed() < Duration::from_secs(2));
+    done.send(()).unwrap();
+    let _ = server_thread.join();
+}
+
+/// Tests talking to a proxy that predates request ids and the handshake
+#[test]
+fn test_legacy_protocol_talks_to_old_proxy() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut length_buffer = [0u8; 4];
+        stream.read_exact(&mut length_buffer).unwrap();
+        let mut buffer = vec![0u8; u32::from_be_bytes(length_buffer) as usize];
+        stream.read_exact(&mut buffer).unwrap();
+
+        // The old layout: request type and payload, nothing after
+        let ((request_type, _), rest): ((RequestType, Option<ControlInputs>), _) =
+            postcard::take_from_bytes(&buffer).unwrap();
+        assert_eq!(request_type, RequestType::ExchangeData);
+        assert!(rest.is_empty());
+
+        let response = (ResponseStatus::Success, Some(SimulatorState::default()));
+        let response_bytes = to_stdvec(&response).unwrap();
+        stream
+            .write_all(&(response_bytes.len() as u32).to_be_bytes())
+            .unwrap();
+        stream.write_all(&response_bytes).unwrap();
+    });
+
+    let client = RealFlightRemoteBridge::builder(&address.to_string())
+        .legacy_protocol(true)
+        .build()
+        .unwrap();
+
+    let result = client.exchange_data(&ControlInputs::default());
+    assert!(result.is_ok(), "Exchange failed: {:?}", result);
+    assert!(client.proxy_info().is_none());
+    let _ = server_thread.join();
+}
+
+// ============================================================================
+// Keep-Alive Tests
+// ============================================================================
+
+/// Tests that an idle connection is pinged repeatedly
+#[test]
+fn test_keep_alive_pings_idle_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let mut stream = accept_with_hello(&listener).unwrap();
+        answer_pings(&mut stream, 3);
+        stream
+    });
+
+    let client = RealFlightRemoteBridge::builder(&address.to_string())
+        .keep_alive(Duration::from_millis(20))
+        .build()
+        .unwrap();
+
+    // Closing the stream ends the next ping at once, so dropping the client doesn't wait
+    drop(server_thread.join().unwrap());
+    drop(client);
+}
+
+/// Tests that an unanswered ping marks the connection as lost
+#[test]
+fn test_failed_ping_marks_disconnected() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (done, done_signal) = std::sync::mpsc::channel::<()>();
+    let server_thread = thread::spawn(move || {
+        // Answer the handshake, then read pings without replying
+        let _stream = accept_with_hello(&listener).unwrap();
+        let _ = done_signal.recv();
+    });
+
+    let client = RealFlightRemoteBridge::builder(&address.to_string())
+        .timeout(Duration::from_millis(50))
+        .keep_alive(Duration::from_millis(20))
+        .build()
+        .unwrap();
+    assert!(client.is_connected());
+
+    thread::sleep(Duration::from_millis(300));
+
+    assert!(!client.is_connected());
+    done.send(()).unwrap();
+    let _ = server_thread.join();
+}
+
+// ============================================================================
+// Sharing Tests
+// ============================================================================
+
+/// Compile-time check that the bridge can be shared across threads
+#[test]
+fn test_bridge_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<RealFlightRemoteBridge>();
+}
+
+/// Tests calling the bridge from two threads through a shared Arc
+#[test]
+fn test_shared_bridge_across_threads() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_thread = thread::spawn(move || {
+        let stream = accept_with_hello(&listener).unwrap();
+        handle_mock_success(stream.try_clone().unwrap());
+        handle_mock_success(stream);
+    });
+
+    let client = Arc::new(RealFlightRemoteBridge::new(&address.to_string()).unwrap());
+
+    for _ in 0..2 {
+        let client = Arc::clone(&client);
+        let result = thread::spawn(move || client.enable_rc()).join().unwrap();
+        assert!(result.is_ok(), "Enable RC failed: {:?}", result);
+    }
+
+    let _ = server_thread.join();
+}
+
 // ============================================================================
 // Helper functions
 // ============================================================================
 
-/// Forces termination of a running server by connecting to it
-fn terminate_server(address: &str) {
-    if let Ok(_) = TcpStream::connect(address) {}
+/// Accepts a connection and answers its version handshake
+pub(super) fn accept_with_hello(listener: &TcpListener) -> std::io::Result<TcpStream> {
+    let (mut stream, _) = listener.accept()?;
+    answer_hello(&mut stream, ProtocolInfo::current())?;
+    Ok(stream)
+}
+
+/// Reads a [RequestType::Hello] and replies with `info`, as a proxy would
+pub(super) fn answer_hello(stream: &mut TcpStream, info: ProtocolInfo) -> std::io::Result<()> {
+    let mut length_buffer = [0u8; 4];
+    stream.read_exact(&mut length_buffer)?;
+    let mut buffer = vec![0u8; u32::from_be_bytes(length_buffer) as usize];
+    stream.read_exact(&mut buffer)?;
+    let request: Request = from_bytes(&buffer).unwrap();
+    assert!(matches!(request.request_type, RequestType::Hello(_)));
+
+    let response = Response {
+        status: ResponseStatus::Success,
+        payload: None,
+        id: request.id,
+        info: Some(info),
+        detail: None,
+        kind: MessageKind::Response,
+    };
+    let response_bytes = to_stdvec(&response).unwrap();
+    stream.write_all(&(response_bytes.len() as u32).to_be_bytes())?;
+    stream.write_all(&response_bytes)?;
+    stream.flush()
+}
+
+/// Answers one request with each of `responses` in turn, echoing request ids
+pub(super) fn answer_requests(stream: &mut TcpStream, responses: Vec<Response>) {
+    for response in responses {
+        let mut length_buffer = [0u8; 4];
+        stream.read_exact(&mut length_buffer).unwrap();
+        let mut buffer = vec![0u8; u32::from_be_bytes(length_buffer) as usize];
+        stream.read_exact(&mut buffer).unwrap();
+        let request: Request = from_bytes(&buffer).unwrap();
+
+        let response = Response {
+            id: request.id,
+            ..response
+        };
+        let response_bytes = to_stdvec(&response).unwrap();
+        stream
+            .write_all(&(response_bytes.len() as u32).to_be_bytes())
+            .unwrap();
+        stream.write_all(&response_bytes).unwrap();
+    }
+}
+
+/// Reads `count` [RequestType::Ping] frames, answering each with success
+pub(super) fn answer_pings(stream: &mut TcpStream, count: usize) {
+    for _ in 0..count {
+        let mut length_buffer = [0u8; 4];
+        stream.read_exact(&mut length_buffer).unwrap();
+        let mut buffer = vec![0u8; u32::from_be_bytes(length_buffer) as usize];
+        stream.read_exact(&mut buffer).unwrap();
+        let request: Request = from_bytes(&buffer).unwrap();
+        assert_eq!(request.request_type, RequestType::Ping);
+
+        let response = Response {
+            status: ResponseStatus::Success,
+            payload: None,
+            id: request.id,
+            info: None,
+            detail: None,
+            kind: MessageKind::Response,
+        };
+        let response_bytes = to_stdvec(&response).unwrap();
+        stream
+            .write_all(&(response_bytes.len() as u32).to_be_bytes())
+            .unwrap();
+        stream.write_all(&response_bytes).unwrap();
+    }
 }
 
 /// Mock handler that returns a success response
@@ -246,10 +1000,15 @@ fn handle_mock_success(mut stream: TcpStream) {
     let msg_length = u32::from_be_bytes(length_buffer) as usize;
     let mut buffer = vec![0u8; msg_length];
     let _ = stream.read_exact(&mut buffer);
+    let id = from_bytes::<Request>(&buffer).map_or(0, |request| request.id);
 
     let response = Response {
         status: ResponseStatus::Success,
         payload: None,
+        id,
+        info: None,
+        detail: None,
+        kind: MessageKind::Response,
     };
 
     let response_bytes = to_stdvec(&response).unwrap();
@@ -278,6 +1037,10 @@ fn handle_mock_exchange_data(mut stream: TcpStream) {
     let response = Response {
         status: ResponseStatus::Success,
         payload: Some(SimulatorState::default()),
+        id: request.id,
+        info: None,
+        detail: None,
+        kind: MessageKind::Response,
     };
 
     let response_bytes = to_stdvec(&response).unwrap();
@@ -298,10 +1061,44 @@ fn handle_mock_exchange_no_payload(mut stream: TcpStream) {
     let msg_length = u32::from_be_bytes(length_buffer) as usize;
     let mut buffer = vec![0u8; msg_length];
     let _ = stream.read_exact(&mut buffer);
+    let id = from_bytes::<Request>(&buffer).map_or(0, |request| request.id);
+
+    let response = Response {
+        status: ResponseStatus::Success,
+        payload: None,
+        id,
+        info: None,
+        detail: None,
+        kind: MessageKind::Response,
+    };
+
+    let response_bytes = to_stdvec(&response).unwrap();
+    let length_bytes = (response_bytes.len() as u32).to_be_bytes();
+
+    let _ = stream.write_all(&length_bytes);
+    let _ = stream.write_all(&response_bytes);
+    let _ = stream.flush();
+}
+
+/// Mock handler that answers with an id other than the request's
+fn handle_mock_wrong_id(mut stream: TcpStream) {
+    stream.set_nodelay(true).unwrap();
+
+    let mut length_buffer = [0u8; 4];
+    let _ = stream.read_exact(&mut length_buffer);
+
+    let msg_length = u32::from_be_bytes(length_buffer) as usize;
+    let mut buffer = vec![0u8; msg_length];
+    let _ = stream.read_exact(&mut buffer);
+    let request: Request = from_bytes(&buffer).unwrap();
 
     let response = Response {
         status: ResponseStatus::Success,
         payload: None,
+        id: request.id + 1,
+        info: None,
+        detail: None,
+        kind: MessageKind::Response,
     };
 
     let response_bytes = to_stdvec(&response).unwrap();
@@ -313,6 +1110,20 @@ fn handle_mock_exchange_no_payload(mut stream: TcpStream) {
 }
 
 /// Mock handler that returns malformed data
+/// Reads one request and answers with `length` as the length prefix,
+/// followed by a few bytes of the announced frame.
+fn handle_mock_length_prefix(mut stream: TcpStream, length: u32) -> TcpStream {
+    let mut length_buffer = [0u8; 4];
+    stream.read_exact(&mut length_buffer).unwrap();
+    let mut buffer = vec![0u8; u32::from_be_bytes(length_buffer) as usize];
+    stream.read_exact(&mut buffer).unwrap();
+
+    let _ = stream.write_all(&length.to_be_bytes());
+    let _ = stream.write_all(&[0u8; 16]);
+    let _ = stream.flush();
+    stream
+}
+
 fn handle_mock_malformed_response(mut stream: TcpStream) {
     stream.set_nodelay(true).unwrap();
 
@@ -327,6 +1138,6 @@ fn handle_mock_malformed_response(mut stream: TcpStream) {
     let length_bytes = (malformed_data.len() as u32).to_be_bytes();
 
     let _ = stream.write_all(&length_bytes);
-    let _ = stream.write_all(&malformed_data.as_slice());
+    let _ = stream.write_all(malformed_data.as_slice());
     let _ = stream.flush();
 }
@@ -0,0 +1,157 @@
+//! Byte streams the remote protocol runs over: TCP, or a Unix domain socket
+//! when the proxy runs on the same host.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::BridgeError;
+
+/// What a remote bridge builder was asked to connect to.
+#[derive(Debug, Clone)]
+pub(crate) enum Target {
+    /// Host and port, resolved when the bridge is built
+    Address(String),
+    /// Path of the proxy's Unix domain socket
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Where a remote bridge connects, and re-dials, to.
+#[derive(Debug, Clone)]
+pub(crate) enum Endpoint {
+    /// Resolved proxy addresses, tried in order
+    Tcp(Vec<SocketAddr>),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl Target {
+    /// Resolves the target, keeping every address a host name resolves to.
+    pub(crate) fn resolve(&self) -> Result<Endpoint, BridgeError> {
+        match self {
+            Target::Address(address) => {
+                let addrs: Vec<SocketAddr> = address
+                    .to_socket_addrs()
+                    .map_err(|e| BridgeError::Initialization(format!("Invalid address: {}", e)))?
+                    .collect();
+                if addrs.is_empty() {
+                    return Err(BridgeError::Initialization("Invalid address".into()));
+                }
+                Ok(Endpoint::Tcp(addrs))
+            }
+            #[cfg(unix)]
+            Target::Unix(path) => Ok(Endpoint::Unix(path.clone())),
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Tcp(addrs) => write!(f, "{}", addrs[0]),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Connected stream to the proxy.
+pub(crate) enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    /// Connects to the first reachable address, waiting at most `timeout` on
+    /// each. Returns the error from the last address tried.
+    ///
+    /// A Unix domain socket is local, so it connects or fails at once.
+    pub(crate) fn connect(endpoint: &Endpoint, timeout: Duration) -> std::io::Result<Self> {
+        match endpoint {
+            Endpoint::Tcp(addrs) => {
+                let mut last_error = None;
+                for addr in addrs {
+                    match TcpStream::connect_timeout(addr, timeout) {
+                        Ok(stream) => {
+                            stream.set_nodelay(true)?;
+                            return Ok(Stream::Tcp(stream));
+                        }
+                        Err(e) => last_error = Some(e),
+                    }
+                }
+                Err(last_error.unwrap_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid address")
+                }))
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(path) => UnixStream::connect(path).map(Stream::Unix),
+        }
+    }
+
+    pub(crate) fn try_clone(&self) -> std::io::Result<Self> {
+        match self {
+            Stream::Tcp(stream) => stream.try_clone().map(Stream::Tcp),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.try_clone().map(Stream::Unix),
+        }
+    }
+
+    pub(crate) fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.set_read_timeout(timeout),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.set_read_timeout(timeout),
+        }
+    }
+
+    pub(crate) fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.set_nonblocking(nonblocking),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+
+    pub(crate) fn shutdown(&self) -> std::io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.shutdown(std::net::Shutdown::Both),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.shutdown(std::net::Shutdown::Both),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
@@ -0,0 +1,486 @@
+//! Timed playback of control frames on an async bridge.
+//!
+//! This module requires the `rt-tokio` feature.
+
+#![cfg(feature = "rt-tokio")]
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::warn;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::bridge::AsyncBridge;
+use crate::bridge::local::AsyncLocalBridge;
+use crate::clock::{Clock, SystemClock};
+use crate::{BridgeError, ControlInputs, SimulatorState};
+
+/// Control frames due at absolute times, ordered by time.
+type Horizon = Vec<(Instant, ControlInputs)>;
+
+/// Plays out control frames at scheduled times on a spawned task.
+///
+/// A planner hands over a whole horizon of future frames with
+/// [schedule](Self::schedule) and does not take part in the timing loop. Each
+/// new horizon replaces the frames still queued from the previous one. The
+/// state returned by every exchange is published on a [watch] channel.
+///
+/// # Timing
+///
+/// Frames are sent at their due time, one exchange at a time. When an exchange
+/// runs past the due time of later frames, only the newest overdue frame is
+/// sent, so the aircraft never replays stale inputs in a burst. Failed
+/// exchanges are logged and playback continues with the next frame.
+///
+/// # Stopping
+///
+/// [stop](Self::stop) lets the exchange in flight finish, sends the final frame
+/// if one was configured, and hands the bridge back. Dropping the scheduler
+/// stops it the same way in the background.
+///
+/// # Clock
+///
+/// Due times are read from a [Clock], the [SystemClock] unless the scheduler
+/// is started with [with_clock](Self::with_clock). A
+/// [ManualClock](crate::clock::ManualClock) plays a horizon without waiting,
+/// for tests of a planner.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::{AsyncBridge, AsyncLocalBridge, ControlInputs, ControlScheduler};
+/// use std::time::Duration;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let bridge = AsyncLocalBridge::new().await?;
+///     bridge.disable_rc().await?;
+///
+///     let mut neutral = ControlInputs::default();
+///     neutral.channels[0] = 0.5;
+///     neutral.channels[1] = 0.5;
+///     neutral.channels[3] = 0.5;
+///     let scheduler = ControlScheduler::with_final_frame(bridge, neutral);
+///
+///     // 50 frames at 500 Hz
+///     let horizon = (0..50)
+///         .map(|i| (Duration::from_millis(2 * i), ControlInputs::default()))
+///         .collect();
+///     scheduler.schedule(horizon)?;
+///
+///     let mut states = scheduler.states();
+///     states.changed().await?;
+///     println!("Airspeed: {:?}", states.borrow().as_ref().map(|s| s.airspeed));
+///
+///     let bridge = scheduler.stop().await?;
+///     bridge.enable_rc().await?;
+///     Ok(())
+/// }
+/// ```
+pub struct ControlScheduler<B = AsyncLocalBridge> {
+    horizon: watch::Sender<Horizon>,
+    states: watch::Receiver<Option<SimulatorState>>,
+    cancel: CancellationToken,
+    task: Option<JoinHandle<(B, Result<(), BridgeError>)>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<B: AsyncBridge + 'static> ControlScheduler<B> {
+    /// Starts a scheduler that owns `bridge`, with an empty horizon.
+    pub fn new(bridge: B) -> Self {
+        Self::with_clock(bridge, None, SystemClock)
+    }
+
+    /// Starts a scheduler that sends `final_frame` when it stops.
+    ///
+    /// Use it to leave the aircraft with neutral inputs instead of whatever the
+    /// last played frame commanded.
+    pub fn with_final_frame(bridge: B, final_frame: ControlInputs) -> Self {
+        Self::with_clock(bridge, Some(final_frame), SystemClock)
+    }
+
+    /// Starts a scheduler that times frames with `clock`, and sends
+    /// `final_frame`, if any, when it stops.
+    pub fn with_clock(
+        bridge: B,
+        final_frame: Option<ControlInputs>,
+        clock: impl Clock + 'static,
+    ) -> Self {
+        let clock: Arc<dyn Clock> = Arc::new(clock);
+        let (horizon_tx, horizon_rx) = watch::channel(Horizon::new());
+        let (state_tx, state_rx) = watch::channel(None);
+        let cancel = CancellationToken::new();
+
+        let task = tokio::spawn(run(
+            bridge,
+            horizon_rx,
+            state_tx,
+            cancel.clone(),
+            final_frame,
+            Arc::clone(&clock),
+        ));
+
+        ControlScheduler {
+            horizon: horizon_tx,
+            states: state_rx,
+            cancel,
+            task: Some(task),
+            clock,
+        }
+    }
+
+    /// Replaces the queued frames with `frames`.
+    ///
+    /// Each frame is due at its offset from the time of this call; frames with
+    /// equal offsets are sent in the order given. Frames not yet sent from the
+    /// previous horizon are discarded. Fails with [BridgeError::InvalidState] if
+    /// the scheduler task is no longer running.
+    pub fn schedule(&self, frames: Vec<(Duration, ControlInputs)>) -> Result<(), BridgeError> {
+        let now = self.clock.now();
+        let mut horizon: Horizon = frames
+            .into_iter()
+            .map(|(offset, control)| (now + offset, control))
+            .collect();
+        horizon.sort_by_key(|(due, _)| *due);
+
+        self.horizon
+            .send(horizon)
+            .map_err(|_| BridgeError::InvalidState("control scheduler has stopped".into()))
+    }
+
+    /// Subscribes to the states returned by played frames.
+    ///
+    /// The receiver holds `None` until the first successful exchange.
+    pub fn states(&self) -> watch::Receiver<Option<SimulatorState>> {
+        self.states.clone()
+    }
+
+    /// Stops playback and returns the bridge.
+    ///
+    /// Waits for the exchange in flight, then sends the final frame if one was
+    /// configured. An error from the final frame is returned instead of the
+    /// bridge.
+    pub async fn stop(mut self) -> Result<B, BridgeError> {
+        self.cancel.cancel();
+        let task = self.task.take().expect("task is only taken by stop");
+        let (bridge, result) = task.await.map_err(|e| {
+            BridgeError::InvalidState(format!("control scheduler task failed: {}", e))
+        })?;
+        result.map(|()| bridge)
+    }
+}
+
+impl<B> Drop for ControlScheduler<B> {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// Scheduler task: plays the current horizon until cancelled.
+async fn run<B: AsyncBridge>(
+    bridge: B,
+    mut horizon_rx: watch::Receiver<Horizon>,
+    state_tx: watch::Sender<Option<SimulatorState>>,
+    cancel: CancellationToken,
+    final_frame: Option<ControlInputs>,
+    clock: Arc<dyn Clock>,
+) -> (B, Result<(), BridgeError>) {
+    let mut queue: VecDeque<(Instant, ControlInputs)> = VecDeque::new();
+
+    loop {
+        let next_due = queue.front().map(|(due, _)| *due);
+        let wait = next_due.map_or(Duration::ZERO, |due| {
+            due.saturating_duration_since(clock.now())
+        });
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => break,
+            changed = horizon_rx.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                queue = horizon_rx.borrow_and_update().iter().cloned().collect();
+            }
+            _ = clock.sleep_async(wait), if next_due.is_some() => {
+                let control = take_due_frame(&mut queue, clock.now());
+                match bridge.exchange_data(&control).await {
+                    Ok(state) => {
+                        state_tx.send_replace(Some(state));
+                    }
+                    Err(e) => warn!("Scheduled exchange failed: {}", e),
+                }
+            }
+        }
+    }
+
+    let result = match final_frame {
+        Some(control) => bridge.exchange_data(&control).await.map(|state| {
+            state_tx.send_replace(Some(state));
+        }),
+        None => Ok(()),
+    };
+    (bridge, result)
+}
+
+/// Removes every frame due by `now` and returns the newest of them.
+///
+/// The queue's first frame must be due.
+fn take_due_frame(queue: &mut VecDeque<(Instant, ControlInputs)>, now: Instant) -> ControlInputs {
+    let (_, mut control) = queue.pop_front().expect("a frame is due");
+    while let Some((due, _)) = queue.front() {
+        if *due > now {
+            break;
+        }
+        control = queue.pop_front().expect("front exists").1;
+    }
+    control
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::sync::Mutex;
+    use tokio::time::Instant;
+
+    /// Records when each exchange starts and answers after `latency`.
+    #[derive(Clone, Default)]
+    struct RecordingBridge {
+        sent: Arc<Mutex<Vec<(Instant, f32)>>>,
+        latency: Duration,
+    }
+
+    impl RecordingBridge {
+        fn with_latency(latency: Duration) -> Self {
+            RecordingBridge {
+                latency,
+                ..Default::default()
+            }
+        }
+
+        /// Offsets from `start` and channel 0 of every exchange so far.
+        fn sent_since(&self, start: Instant) -> Vec<(Duration, f32)> {
+            self.sent
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(at, value)| (*at - start, *value))
+                .collect()
+        }
+    }
+
+    impl AsyncBridge for RecordingBridge {
+        async fn exchange_data(
+            &self,
+            control: &ControlInputs,
+        ) -> Result<SimulatorState, BridgeError> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((Instant::now(), control.channels[0]));
+            tokio::time::sleep(self.latency).await;
+            Ok(SimulatorState {
+                previous_inputs: control.clone(),
+                ..Default::default()
+            })
+        }
+
+        async fn enable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        async fn disable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+    }
+
+    fn frame(offset_ms: u64, value: f32) -> (Duration, ControlInputs) {
+        let mut control = ControlInputs::default();
+        control.channels[0] = value;
+        (Duration::from_millis(offset_ms), control)
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn plays_frames_at_their_offsets() {
+        let bridge = RecordingBridge::default();
+        let scheduler = ControlScheduler::new(bridge.clone());
+        let start = Instant::now();
+
+        scheduler
+            .schedule(vec![frame(0, 0.1), frame(2, 0.2), frame(4, 0.3)])
+            .unwrap();
+        tokio::time::sleep(ms(10)).await;
+
+        assert_eq!(
+            bridge.sent_since(start),
+            vec![(ms(0), 0.1), (ms(2), 0.2), (ms(4), 0.3)]
+        );
+        scheduler.stop().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sorts_frames_by_offset() {
+        let bridge = RecordingBridge::default();
+        let scheduler = ControlScheduler::new(bridge.clone());
+        let start = Instant::now();
+
+        scheduler
+            .schedule(vec![frame(4, 0.3), frame(0, 0.1), frame(2, 0.2)])
+            .unwrap();
+        tokio::time::sleep(ms(10)).await;
+
+        assert_eq!(
+            bridge.sent_since(start),
+            vec![(ms(0), 0.1), (ms(2), 0.2), (ms(4), 0.3)]
+        );
+        scheduler.stop().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn new_horizon_replaces_queued_frames() {
+        let bridge = RecordingBridge::default();
+        let scheduler = ControlScheduler::new(bridge.clone());
+        let start = Instant::now();
+
+        scheduler
+            .schedule(vec![frame(0, 0.1), frame(10, 0.2), frame(20, 0.3)])
+            .unwrap();
+        tokio::time::sleep(ms(5)).await;
+        scheduler
+            .schedule(vec![frame(0, 0.7), frame(10, 0.8)])
+            .unwrap();
+        tokio::time::sleep(ms(30)).await;
+
+        assert_eq!(
+            bridge.sent_since(start),
+            vec![(ms(0), 0.1), (ms(5), 0.7), (ms(15), 0.8)]
+        );
+        scheduler.stop().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_exchange_skips_to_newest_overdue_frame() {
+        let bridge = RecordingBridge::with_latency(ms(5));
+        let scheduler = ControlScheduler::new(bridge.clone());
+        let start = Instant::now();
+
+        scheduler
+            .schedule(vec![
+                frame(0, 0.1),
+                frame(2, 0.2),
+                frame(4, 0.3),
+                frame(6, 0.4),
+            ])
+            .unwrap();
+        tokio::time::sleep(ms(20)).await;
+
+        // 0.2 and 0.3 were overdue when the first exchange finished at 5 ms
+        assert_eq!(
+            bridge.sent_since(start),
+            vec![(ms(0), 0.1), (ms(5), 0.3), (ms(10), 0.4)]
+        );
+        scheduler.stop().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn publishes_states() {
+        let bridge = RecordingBridge::default();
+        let scheduler = ControlScheduler::new(bridge);
+        let mut states = scheduler.states();
+        assert!(states.borrow().is_none());
+
+        scheduler.schedule(vec![frame(1, 0.6)]).unwrap();
+        states.changed().await.unwrap();
+
+        let state = states.borrow_and_update().clone().unwrap();
+        assert_eq!(state.previous_inputs.channels[0], 0.6);
+        scheduler.stop().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_sends_final_frame() {
+        let bridge = RecordingBridge::default();
+        let scheduler = ControlScheduler::with_final_frame(bridge.clone(), frame(0, 0.5).1);
+        let start = Instant::now();
+
+        scheduler
+            .schedule(vec![frame(0, 0.1), frame(100, 0.2)])
+            .unwrap();
+        tokio::time::sleep(ms(10)).await;
+        scheduler.stop().await.unwrap();
+
+        assert_eq!(bridge.sent_since(start), vec![(ms(0), 0.1), (ms(10), 0.5)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_without_final_frame_sends_nothing() {
+        let bridge = RecordingBridge::default();
+        let scheduler = ControlScheduler::new(bridge.clone());
+
+        scheduler.schedule(vec![frame(100, 0.2)]).unwrap();
+        tokio::time::sleep(ms(10)).await;
+        scheduler.stop().await.unwrap();
+
+        assert!(bridge.sent.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_waits_for_exchange_in_flight() {
+        let bridge = RecordingBridge::with_latency(ms(50));
+        let scheduler = ControlScheduler::with_final_frame(bridge.clone(), frame(0, 0.5).1);
+        let start = Instant::now();
+
+        scheduler.schedule(vec![frame(0, 0.1)]).unwrap();
+        tokio::time::sleep(ms(10)).await;
+        scheduler.stop().await.unwrap();
+
+        assert_eq!(bridge.sent_since(start), vec![(ms(0), 0.1), (ms(50), 0.5)]);
+        assert_eq!(start.elapsed(), ms(100));
+    }
+
+    #[tokio::test]
+    async fn manual_clock_plays_horizon_without_waiting() {
+        let bridge = RecordingBridge::default();
+        let clock = ManualClock::new();
+        let scheduler = ControlScheduler::with_clock(bridge.clone(), None, clock.clone());
+        let mut states = scheduler.states();
+
+        scheduler
+            .schedule(vec![frame(0, 0.1), frame(10_000, 0.2), frame(20_000, 0.3)])
+            .unwrap();
+        while states.changed().await.is_ok() {
+            let last = states.borrow_and_update().clone().unwrap();
+            if last.previous_inputs.channels[0] == 0.3 {
+                break;
+            }
+        }
+
+        let sent: Vec<f32> = bridge.sent.lock().unwrap().iter().map(|s| s.1).collect();
+        assert_eq!(sent, vec![0.1, 0.2, 0.3]);
+        assert_eq!(clock.elapsed(), ms(20_000));
+        scheduler.stop().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drop_stops_playback() {
+        let bridge = RecordingBridge::default();
+        let scheduler = ControlScheduler::new(bridge.clone());
+
+        scheduler.schedule(vec![frame(10, 0.1)]).unwrap();
+        drop(scheduler);
+        tokio::time::sleep(ms(20)).await;
+
+        assert!(bridge.sent.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,273 @@
+//! Time sources for the timed helpers.
+//!
+//! [RealFlightLocalBridge::run_control_loop_with_clock](crate::RealFlightLocalBridge::run_control_loop_with_clock)
+//! and `ControlScheduler::with_clock` read the time and sleep through a
+//! [Clock]. [SystemClock] is the real one and the default everywhere. A
+//! [ManualClock] only moves when told to, so a test can check the timing of a
+//! loop exactly and without waiting for it.
+//!
+//! ```
+//! use realflight_bridge::clock::{Clock, ManualClock};
+//! use std::time::Duration;
+//!
+//! let clock = ManualClock::new();
+//! let start = clock.now();
+//!
+//! clock.sleep(Duration::from_millis(20));
+//! clock.advance(Duration::from_millis(5));
+//!
+//! assert_eq!(clock.now() - start, Duration::from_millis(25));
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "rt-tokio")]
+use std::{future::Future, pin::Pin};
+
+/// Source of the current time and of sleeps.
+pub trait Clock: Send + Sync {
+    /// Current time.
+    fn now(&self) -> Instant;
+
+    /// Blocks the calling thread for `duration`.
+    fn sleep(&self, duration: Duration);
+
+    /// Blocks the calling thread until `deadline`, more precisely than
+    /// [sleep](Self::sleep) where the clock can.
+    ///
+    /// Used for the last moments before a deadline. Defaults to sleeping for
+    /// the time left; [SystemClock] busy-waits instead.
+    fn spin_until(&self, deadline: Instant) {
+        self.sleep(deadline.saturating_duration_since(self.now()));
+    }
+
+    /// Waits for `duration` without blocking the runtime.
+    ///
+    /// Defaults to [tokio::time::sleep]. Requires the `rt-tokio` feature.
+    #[cfg(feature = "rt-tokio")]
+    fn sleep_async(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+
+    fn spin_until(&self, deadline: Instant) {
+        (**self).spin_until(deadline)
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    fn sleep_async(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).sleep_async(duration)
+    }
+}
+
+/// The system's monotonic clock.
+///
+/// With the `rt-tokio` feature the time is read from tokio's clock, which is
+/// the system clock unless a test has paused tokio's time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        #[cfg(feature = "rt-tokio")]
+        return tokio::time::Instant::now().into_std();
+        #[cfg(not(feature = "rt-tokio"))]
+        return Instant::now();
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+
+    fn spin_until(&self, deadline: Instant) {
+        // Spin on the std clock, which keeps running while tokio's is paused
+        let end = Instant::now() + deadline.saturating_duration_since(self.now());
+        while Instant::now() < end {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+/// Clock that only moves when advanced or slept on.
+///
+/// Clones share the same time, so a test keeps one and hands another to the
+/// code under test. Sleeping advances the time by the requested duration and
+/// returns at once: a loop runs as fast as it can while seeing exactly the time
+/// it asked for. [advance](Self::advance) stands in for time spent elsewhere,
+/// such as a slow exchange.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    start: Instant,
+    now: Arc<Mutex<Instant>>,
+}
+
+impl ManualClock {
+    /// Starts at the current system time.
+    pub fn new() -> Self {
+        let start = Instant::now();
+        ManualClock {
+            start,
+            now: Arc::new(Mutex::new(start)),
+        }
+    }
+
+    /// Moves the time forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+
+    /// Time advanced since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.now() - self.start
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+
+    /// Yields to the runtime once, then advances the time by `duration`.
+    ///
+    /// A sleep dropped before it completes leaves the time unchanged.
+    #[cfg(feature = "rt-tokio")]
+    fn sleep_async(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async move {
+            tokio::task::yield_now().await;
+            self.advance(duration);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    mod manual_clock_tests {
+        use super::*;
+
+        #[test]
+        fn starts_without_elapsed_time() {
+            let clock = ManualClock::new();
+            assert_eq!(clock.elapsed(), Duration::ZERO);
+        }
+
+        #[test]
+        fn sleep_advances_time() {
+            let clock = ManualClock::new();
+            let start = clock.now();
+
+            clock.sleep(ms(20));
+
+            assert_eq!(clock.now() - start, ms(20));
+        }
+
+        #[test]
+        fn spin_advances_to_deadline() {
+            let clock = ManualClock::new();
+            let deadline = clock.now() + ms(3);
+
+            clock.spin_until(deadline);
+            clock.spin_until(deadline - ms(1));
+
+            assert_eq!(clock.now(), deadline);
+        }
+
+        #[test]
+        fn clones_share_time() {
+            let clock = ManualClock::new();
+            let shared = clock.clone();
+
+            shared.advance(ms(5));
+            clock.advance(ms(10));
+
+            assert_eq!(shared.elapsed(), ms(15));
+            assert_eq!(clock.now(), shared.now());
+        }
+
+        #[test]
+        fn steps_from_another_thread() {
+            let clock = ManualClock::new();
+            let stepper = clock.clone();
+
+            std::thread::spawn(move || stepper.advance(ms(7)))
+                .join()
+                .unwrap();
+
+            assert_eq!(clock.elapsed(), ms(7));
+        }
+
+        #[cfg(feature = "rt-tokio")]
+        #[tokio::test]
+        async fn async_sleep_advances_time() {
+            let clock = ManualClock::new();
+
+            clock.sleep_async(ms(30)).await;
+
+            assert_eq!(clock.elapsed(), ms(30));
+        }
+
+        #[cfg(feature = "rt-tokio")]
+        #[tokio::test]
+        async fn dropped_async_sleep_leaves_time() {
+            let clock = ManualClock::new();
+
+            let sleep = clock.sleep_async(ms(30));
+            drop(sleep);
+
+            assert_eq!(clock.elapsed(), Duration::ZERO);
+        }
+    }
+
+    mod system_clock_tests {
+        use super::*;
+
+        #[test]
+        fn sleep_waits() {
+            let start = SystemClock.now();
+            SystemClock.sleep(ms(5));
+            assert!(SystemClock.now() - start >= ms(5));
+        }
+
+        #[test]
+        fn spins_until_deadline() {
+            let deadline = SystemClock.now() + ms(2);
+            SystemClock.spin_until(deadline);
+            assert!(SystemClock.now() >= deadline);
+        }
+
+        #[cfg(feature = "rt-tokio")]
+        #[tokio::test(start_paused = true)]
+        async fn follows_paused_tokio_time() {
+            let start = SystemClock.now();
+
+            SystemClock.sleep_async(ms(500)).await;
+
+            assert_eq!(SystemClock.now() - start, ms(500));
+        }
+    }
+}
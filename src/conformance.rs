@@ -0,0 +1,497 @@
+//! Conformance suite checking a bridge against a live RealFlight.
+//!
+//! [run] takes a bridge through the RealFlight Link lifecycle and reports
+//! each step in a [ConformanceReport]: injecting the controller, a run of
+//! exchanges at each configured rate, resetting the aircraft, restoring the
+//! controller, and the faults RealFlight answers out-of-order requests with.
+//! Running it through the same host, port and firewall a controller will use
+//! shows whether that setup works before any flying is done.
+//!
+//! The suite leaves RealFlight with its own controller restored. Run it with
+//! RealFlight showing an aircraft and no RealFlight Link controller injected.
+//!
+//! ```no_run
+//! use realflight_bridge::RealFlightLocalBridge;
+//! use realflight_bridge::conformance::{self, ConformanceConfig};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bridge = RealFlightLocalBridge::new()?;
+//! let report = conformance::run(&bridge, &ConformanceConfig::default());
+//! println!("{}", report);
+//! assert!(report.passed());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The crate's own suite is `tests/conformance.rs`, which runs against the
+//! simulator at `REALFLIGHT_SIMULATOR_HOST` (default `127.0.0.1:18083`) when
+//! `REALFLIGHT_CONFORMANCE=1` is set:
+//!
+//! ```text
+//! REALFLIGHT_CONFORMANCE=1 cargo test --test conformance -- --nocapture
+//! ```
+
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bridge::RealFlightBridge;
+use crate::{BridgeError, ControlInputs};
+
+/// Settings for a conformance [run].
+#[derive(Clone, Debug)]
+pub struct ConformanceConfig {
+    /// Exchanges made at each rate. Defaults to 1000
+    pub exchanges: u64,
+    /// Rates to exchange data at, in Hz. Defaults to 50, 100 and 200
+    pub rates_hz: Vec<f32>,
+}
+
+impl Default for ConformanceConfig {
+    fn default() -> Self {
+        ConformanceConfig {
+            exchanges: 1000,
+            rates_hz: vec![50.0, 100.0, 200.0],
+        }
+    }
+}
+
+/// Result of one step of a conformance [run].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    /// The bridge behaved as RealFlight should
+    Passed,
+    /// The bridge did not, for the given reason
+    Failed(String),
+    /// Not run, because a step it depends on failed
+    Skipped,
+}
+
+/// A named step of a conformance [run] and its outcome.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Check {
+    /// What was checked
+    pub name: String,
+    /// Whether it passed
+    pub outcome: Outcome,
+}
+
+/// Exchange latencies at the nearest-rank percentiles.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Latency {
+    /// Median
+    pub p50: Duration,
+    /// 90th percentile
+    pub p90: Duration,
+    /// 99th percentile
+    pub p99: Duration,
+    /// Slowest exchange
+    pub max: Duration,
+}
+
+impl Latency {
+    /// Percentiles of `samples`, or zero for none.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Latency {
+        if samples.is_empty() {
+            return Latency::default();
+        }
+        samples.sort_unstable();
+        let rank = |p: f64| samples[((p * samples.len() as f64).ceil() as usize).max(1) - 1];
+        Latency {
+            p50: rank(0.50),
+            p90: rank(0.90),
+            p99: rank(0.99),
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Exchanges made at one rate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExchangeRun {
+    /// Requested rate
+    pub rate_hz: f32,
+    /// Number of exchanges attempted
+    pub exchanges: u64,
+    /// Number of exchanges that failed
+    pub errors: u64,
+    /// Time from the first exchange until the last completed
+    pub elapsed: Duration,
+    /// Latency of the successful exchanges
+    pub latency: Latency,
+}
+
+impl ExchangeRun {
+    /// Exchanges attempted per second.
+    pub fn achieved_rate(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+        self.exchanges as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Outcome of a conformance [run].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConformanceReport {
+    /// Every step, in the order run
+    pub checks: Vec<Check>,
+    /// One run per configured rate
+    pub runs: Vec<ExchangeRun>,
+}
+
+impl ConformanceReport {
+    /// Whether every check passed.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.outcome == Outcome::Passed)
+    }
+
+    fn push(&mut self, name: impl Into<String>, outcome: Outcome) -> bool {
+        let passed = outcome == Outcome::Passed;
+        self.checks.push(Check {
+            name: name.into(),
+            outcome,
+        });
+        passed
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "RealFlight conformance")?;
+        for check in &self.checks {
+            match &check.outcome {
+                Outcome::Passed => writeln!(f, "  pass  {}", check.name)?,
+                Outcome::Failed(reason) => writeln!(f, "  FAIL  {}: {}", check.name, reason)?,
+                Outcome::Skipped => writeln!(f, "  skip  {}", check.name)?,
+            }
+        }
+        if !self.runs.is_empty() {
+            writeln!(
+                f,
+                "  {:>8} {:>9} {:>6} {:>10} {:>10} {:>10} {:>10}",
+                "rate", "achieved", "errors", "p50", "p90", "p99", "max"
+            )?;
+        }
+        for run in &self.runs {
+            writeln!(
+                f,
+                "  {:>6.0}Hz {:>7.1}Hz {:>6} {:>10.2?} {:>10.2?} {:>10.2?} {:>10.2?}",
+                run.rate_hz,
+                run.achieved_rate(),
+                run.errors,
+                run.latency.p50,
+                run.latency.p90,
+                run.latency.p99,
+                run.latency.max
+            )?;
+        }
+        write!(f, "{}", if self.passed() { "passed" } else { "FAILED" })
+    }
+}
+
+/// Runs the whole suite against `bridge`.
+///
+/// The steps are, in order:
+///
+/// 1. `disable_rc` injects the RealFlight Link controller
+/// 2. a second `disable_rc` fails with a SOAP fault
+/// 3. [exchange_at_rate] for each of [ConformanceConfig::rates_hz]
+/// 4. `reset_aircraft` succeeds
+/// 5. `enable_rc` restores RealFlight's controller
+/// 6. a second `enable_rc` fails with a SOAP fault
+/// 7. `exchange_data` fails with a SOAP fault once the controller is restored
+///
+/// Steps 2 to 5 are skipped if the controller could not be injected, and the
+/// last two if it could not be restored.
+///
+/// # Panics
+///
+/// Panics if a rate is not a positive, finite number.
+pub fn run(bridge: &dyn RealFlightBridge, config: &ConformanceConfig) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+
+    let injected = report.push(
+        "disable_rc injects controller",
+        succeeds(bridge.disable_rc()),
+    );
+    let mut restored = false;
+    if injected {
+        report.push("second disable_rc is rejected", faults(bridge.disable_rc()));
+        for &rate_hz in &config.rates_hz {
+            let run = exchange_at_rate(bridge, rate_hz, config.exchanges);
+            let outcome = match run.errors {
+                0 => Outcome::Passed,
+                n => Outcome::Failed(format!("{} of {} exchanges failed", n, run.exchanges)),
+            };
+            report.push(
+                format!("{} exchanges at {} Hz", config.exchanges, rate_hz),
+                outcome,
+            );
+            report.runs.push(run);
+        }
+        report.push("reset_aircraft succeeds", succeeds(bridge.reset_aircraft()));
+        restored = report.push(
+            "enable_rc restores controller",
+            succeeds(bridge.enable_rc()),
+        );
+    } else {
+        report.push("second disable_rc is rejected", Outcome::Skipped);
+        for &rate_hz in &config.rates_hz {
+            report.push(
+                format!("{} exchanges at {} Hz", config.exchanges, rate_hz),
+                Outcome::Skipped,
+            );
+        }
+        report.push("reset_aircraft succeeds", Outcome::Skipped);
+        report.push("enable_rc restores controller", Outcome::Skipped);
+    }
+
+    if restored {
+        report.push("second enable_rc is rejected", faults(bridge.enable_rc()));
+        let exchanged = bridge.exchange_data(&ControlInputs::default());
+        report.push(
+            "exchange_data without controller is rejected",
+            faults(exchanged),
+        );
+    } else {
+        report.push("second enable_rc is rejected", Outcome::Skipped);
+        report.push(
+            "exchange_data without controller is rejected",
+            Outcome::Skipped,
+        );
+    }
+
+    report
+}
+
+/// Makes `count` exchanges of neutral inputs at `rate_hz`, recording the
+/// latency of each.
+///
+/// Sleep time is shortened by the time an exchange took, and after an
+/// overrun the schedule restarts from the current time. Failed exchanges are
+/// counted and the run carries on.
+///
+/// # Panics
+///
+/// Panics if `rate_hz` is not a positive, finite number.
+pub fn exchange_at_rate(bridge: &dyn RealFlightBridge, rate_hz: f32, count: u64) -> ExchangeRun {
+    assert!(
+        rate_hz.is_finite() && rate_hz > 0.0,
+        "rate_hz must be positive and finite, got {}",
+        rate_hz
+    );
+    let period = Duration::from_secs_f64(1.0 / rate_hz as f64);
+    let mut channels = [0.5; 12];
+    channels[2] = 0.0; // Throttle off
+    let inputs = ControlInputs { channels };
+
+    let mut latencies = Vec::with_capacity(count as usize);
+    let mut errors = 0;
+    let start = Instant::now();
+    let mut scheduled = start;
+    for _ in 0..count {
+        let sent = Instant::now();
+        match bridge.exchange_data(&inputs) {
+            Ok(_) => latencies.push(sent.elapsed()),
+            Err(_) => errors += 1,
+        }
+
+        scheduled += period;
+        let now = Instant::now();
+        if now < scheduled {
+            thread::sleep(scheduled - now);
+        } else {
+            scheduled = now;
+        }
+    }
+
+    ExchangeRun {
+        rate_hz,
+        exchanges: count,
+        errors,
+        elapsed: start.elapsed(),
+        latency: Latency::from_samples(latencies),
+    }
+}
+
+fn succeeds<T>(result: Result<T, BridgeError>) -> Outcome {
+    match result {
+        Ok(_) => Outcome::Passed,
+        Err(e) => Outcome::Failed(e.to_string()),
+    }
+}
+
+/// Passes if RealFlight rejected the request with a SOAP fault.
+fn faults<T>(result: Result<T, BridgeError>) -> Outcome {
+    match result {
+        Err(BridgeError::SoapFault(_)) => Outcome::Passed,
+        Err(e) => Outcome::Failed(format!("expected a SOAP fault, got {}", e)),
+        Ok(_) => Outcome::Failed("expected a SOAP fault, request succeeded".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn config() -> ConformanceConfig {
+        ConformanceConfig {
+            exchanges: 20,
+            rates_hz: vec![500.0, 1000.0],
+        }
+    }
+
+    mod latency_tests {
+        use super::*;
+
+        #[test]
+        fn takes_nearest_rank_percentiles() {
+            let samples = (1..=100).rev().map(ms).collect();
+
+            let latency = Latency::from_samples(samples);
+
+            assert_eq!(latency.p50, ms(50));
+            assert_eq!(latency.p90, ms(90));
+            assert_eq!(latency.p99, ms(99));
+            assert_eq!(latency.max, ms(100));
+        }
+
+        #[test]
+        fn single_sample_is_every_percentile() {
+            let latency = Latency::from_samples(vec![ms(7)]);
+
+            assert_eq!(latency.p50, ms(7));
+            assert_eq!(latency.max, ms(7));
+        }
+
+        #[test]
+        fn no_samples_is_zero() {
+            assert_eq!(Latency::from_samples(Vec::new()), Latency::default());
+        }
+    }
+
+    #[cfg(feature = "mock")]
+    mod mock_simulator_tests {
+        use super::*;
+        use crate::mock::MockSimulator;
+        use crate::{Configuration, RealFlightLocalBridge};
+
+        fn connect(simulator: &MockSimulator) -> RealFlightLocalBridge {
+            RealFlightLocalBridge::with_configuration(&Configuration {
+                simulator_host: simulator.host(),
+                ..Default::default()
+            })
+            .unwrap()
+        }
+
+        #[test]
+        fn passes_against_simulator() {
+            let simulator = MockSimulator::start(0).unwrap();
+            let bridge = connect(&simulator);
+
+            let report = run(&bridge, &config());
+
+            assert!(report.passed(), "{}", report);
+            assert_eq!(report.checks.len(), 8);
+            assert_eq!(report.runs.len(), 2);
+            assert!(
+                report
+                    .runs
+                    .iter()
+                    .all(|r| r.exchanges == 20 && r.errors == 0)
+            );
+            assert!(!simulator.controller_injected());
+        }
+
+        #[test]
+        fn skips_when_controller_left_injected() {
+            let simulator = MockSimulator::start(0).unwrap();
+            simulator.set_controller_injected(true);
+            let bridge = connect(&simulator);
+
+            let report = run(&bridge, &config());
+
+            assert!(!report.passed());
+            assert!(matches!(report.checks[0].outcome, Outcome::Failed(_)));
+            assert!(
+                report.checks[1..]
+                    .iter()
+                    .all(|c| c.outcome == Outcome::Skipped)
+            );
+            assert!(report.runs.is_empty());
+            assert!(report.to_string().ends_with("FAILED"));
+        }
+    }
+
+    mod lenient_bridge_tests {
+        use super::*;
+        use crate::SimulatorState;
+
+        /// Bridge accepting every request, unlike RealFlight.
+        struct LenientBridge;
+
+        impl RealFlightBridge for LenientBridge {
+            fn exchange_data(&self, _: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+                Ok(SimulatorState::default())
+            }
+
+            fn enable_rc(&self) -> Result<(), BridgeError> {
+                Ok(())
+            }
+
+            fn disable_rc(&self) -> Result<(), BridgeError> {
+                Ok(())
+            }
+
+            fn reset_aircraft(&self) -> Result<(), BridgeError> {
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn fails_error_path_checks() {
+            let report = run(&LenientBridge, &config());
+
+            let failed: Vec<_> = report
+                .checks
+                .iter()
+                .filter(|c| matches!(c.outcome, Outcome::Failed(_)))
+                .map(|c| c.name.as_str())
+                .collect();
+            assert_eq!(
+                failed,
+                [
+                    "second disable_rc is rejected",
+                    "second enable_rc is rejected",
+                    "exchange_data without controller is rejected"
+                ]
+            );
+        }
+
+        #[test]
+        fn prints_every_check() {
+            let report = run(&LenientBridge, &config());
+
+            let printed = report.to_string();
+
+            assert_eq!(printed.matches("pass").count(), 5);
+            assert_eq!(printed.matches("FAIL ").count(), 3);
+            assert!(printed.contains("500Hz"));
+        }
+
+        #[test]
+        fn exchanges_at_rate() {
+            let run = exchange_at_rate(&LenientBridge, 200.0, 10);
+
+            assert_eq!(run.exchanges, 10);
+            assert_eq!(run.errors, 0);
+            assert!(run.elapsed >= ms(45));
+            assert!(run.achieved_rate() <= 200.0);
+        }
+    }
+}
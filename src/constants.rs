@@ -0,0 +1,128 @@
+//! Protocol constants and library defaults.
+//!
+//! These are the values the bridges use when no explicit configuration is given.
+//! They are public so applications can build on them instead of repeating magic numbers.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use realflight_bridge::Configuration;
+//! use realflight_bridge::constants::{DEFAULT_CONNECT_TIMEOUT, REALFLIGHT_LINK_PORT};
+//!
+//! // Connect to RealFlight running on another machine on the standard port
+//! let config = Configuration {
+//!     simulator_host: format!("192.168.1.100:{}", REALFLIGHT_LINK_PORT),
+//!     connect_timeout: DEFAULT_CONNECT_TIMEOUT * 10,
+//!     ..Default::default()
+//! };
+//! assert_eq!(config.simulator_host, "192.168.1.100:18083");
+//! ```
+
+use std::time::Duration;
+
+/// Default TCP port RealFlight Link listens on.
+pub const REALFLIGHT_LINK_PORT: u16 = 18083;
+
+/// Default RealFlight simulator address (localhost on standard port)
+pub const DEFAULT_SIMULATOR_HOST: &str = "127.0.0.1:18083";
+
+/// Default time allowed to establish a TCP connection to the simulator.
+///
+/// Kept short because the simulator is expected to run on the same machine.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_millis(5);
+
+/// Default time to wait for the connection pool to create its initial connections.
+pub const DEFAULT_INIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the connection pool keeps retrying its first connections, so a
+/// simulator that starts just after the bridge is still found. Shorter than
+/// [DEFAULT_INIT_TIMEOUT], so a simulator that never starts is reported as a
+/// connection failure.
+pub const INIT_RETRY_WINDOW: Duration = Duration::from_secs(2);
+
+/// Pause between the connection pool's attempts while the simulator is not
+/// yet reachable.
+pub const INIT_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Default number of pre-created connections to the simulator.
+///
+/// The pool creates the next connection while the current request is in flight,
+/// so one is enough for a sequential control loop.
+pub const DEFAULT_POOL_SIZE: usize = 1;
+
+/// Default age after which a pooled simulator connection is discarded instead
+/// of used.
+///
+/// RealFlight closes connections that sit idle, so a connection that waited
+/// this long is replaced rather than trusted.
+pub const DEFAULT_MAX_IDLE: Duration = Duration::from_secs(10);
+
+/// Default shortest time between two resets a local bridge sends: zero, so
+/// every reset is sent. See
+/// [Configuration::reset_debounce](crate::Configuration::reset_debounce).
+pub const DEFAULT_RESET_DEBOUNCE: Duration = Duration::ZERO;
+
+/// Number of successive states that must agree for the aircraft to count as
+/// settled after a reset.
+///
+/// See [RealFlightBridge::reset_aircraft_and_wait](crate::RealFlightBridge::reset_aircraft_and_wait).
+pub const RESET_SETTLE_SAMPLES: usize = 5;
+
+/// Largest change in `altitude_agl` between settled states (m).
+pub const RESET_SETTLE_ALTITUDE: f32 = 0.05;
+
+/// Largest change in any world velocity component between settled states
+/// [meters/second].
+pub const RESET_SETTLE_VELOCITY: f32 = 0.1;
+
+/// Maximum number of response body bytes passed to a request tap.
+///
+/// See [Configuration::tap](crate::Configuration::tap).
+pub const SUMMARY_BODY_LIMIT: usize = 1024;
+
+/// Default limit on the body of one simulator response, in bytes.
+///
+/// State responses are about 4 KB; the limit keeps a corrupted Content-Length
+/// header from making the bridge allocate gigabytes.
+pub const DEFAULT_MAX_RESPONSE_SIZE: usize = 256 * 1024;
+
+/// Number of exchanges queued for the transcript writer before further ones
+/// are dropped.
+///
+/// See [Configuration::transcript](crate::Configuration::transcript).
+pub const TRANSCRIPT_CAPACITY: usize = 256;
+
+/// Default time allowed to connect to a `realflight_bridge_proxy` from a remote bridge.
+pub const DEFAULT_REMOTE_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default time the proxy waits for the simulator to answer one forwarded request.
+pub const DEFAULT_PROXY_REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Default fastest airspeed, in meters/second, at which a safe start still
+/// injects the RealFlight Link controller.
+pub const DEFAULT_SAFE_START_MAX_AIRSPEED: f32 = 1.0;
+
+/// Default silence after which the proxy commands the link failsafe for a
+/// client that was flying the aircraft.
+pub const DEFAULT_PROXY_LINK_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Default number of consecutive failed exchanges a remote bridge answers with
+/// the previous state in `exchange_data_or_last`.
+pub const DEFAULT_REMOTE_MAX_STALE_FRAMES: u32 = 1;
+
+/// Default limit on the length prefix of one remote protocol frame, in bytes.
+///
+/// Frames are far smaller in practice; the limit keeps a corrupted or hostile
+/// peer from making the reader allocate gigabytes.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_host_uses_link_port() {
+        let port = DEFAULT_SIMULATOR_HOST.rsplit(':').next().unwrap();
+        assert_eq!(port.parse::<u16>().unwrap(), REALFLIGHT_LINK_PORT);
+    }
+}
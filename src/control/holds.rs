@@ -0,0 +1,443 @@
+//! Heading and altitude holds, as closed-loop examples on [Pid].
+//!
+//! Each hold reads what it needs from a [SimulatorState] and returns
+//! [ChannelCommands] for the channel it drives, so holds can be combined with
+//! [ControlInputs::merge](crate::ControlInputs::merge):
+//!
+//! ```no_run
+//! use realflight_bridge::control::Pid;
+//! use realflight_bridge::control::holds::{AltitudeHold, HeadingHold};
+//! use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bridge = RealFlightLocalBridge::new()?;
+//! bridge.disable_rc()?;
+//!
+//! let mut altitude = AltitudeHold::new(Pid::new(0.5, 0.0, 0.0), Pid::new(0.1, 0.05, 0.0));
+//! let mut heading = HeadingHold::new(Pid::new(1.0, 0.0, 0.0), Pid::new(0.01, 0.2, 0.0));
+//! let mut inputs = ControlInputs { channels: [0.5; 12] };
+//! let dt = Duration::from_millis(10);
+//!
+//! loop {
+//!     let state = bridge.exchange_data(&inputs)?;
+//!     inputs.merge(&altitude.update(150.0, &state, dt));
+//!     inputs.merge(&heading.update(-170.0, &state, dt));
+//! }
+//! # }
+//! ```
+//!
+//! Like the rest of [control](super), these are demonstrations to start from,
+//! not an autopilot.
+
+use std::time::Duration;
+
+use super::{ChannelCommands, Pid};
+use crate::SimulatorState;
+use crate::encoders::units;
+
+/// Signed difference from `current` to `target` in degrees, wrapped to
+/// -180..180, so the shortest turn is taken across north or south.
+///
+/// `heading_error(-170.0, 170.0)` is 20: turning right by 20 degrees through
+/// 180 reaches the target.
+pub fn heading_error(target: f32, current: f32) -> f32 {
+    (target - current + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Holds an altitude above sea level on the throttle (or collective) channel.
+///
+/// Two loops run in cascade: the altitude [Pid] turns the altitude error in
+/// meters into a climb rate target in meters/second, and the climb [Pid]
+/// turns the error from the climb rate in `velocity_world_w` into throttle
+/// around the trim. Limit the altitude [Pid]'s output to bound the climb and
+/// descent rates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AltitudeHold {
+    /// Altitude error (m) to climb rate target [meters/second]
+    pub altitude: Pid,
+    /// Climb rate error [meters/second] to throttle around the trim
+    pub climb: Pid,
+    trim: f32,
+    channel: usize,
+}
+
+impl AltitudeHold {
+    /// Altitude hold on channel 2 with a trim of 0.5. The climb [Pid]'s
+    /// output limits are replaced by the throttle range around the trim.
+    pub fn new(altitude: Pid, climb: Pid) -> Self {
+        AltitudeHold {
+            altitude,
+            climb,
+            trim: 0.5,
+            channel: 2,
+        }
+        .with_trim(0.5)
+    }
+
+    /// Centers the throttle on `trim`, clamped to 0.0..=1.0, instead of 0.5.
+    /// Set it to the throttle that holds level flight.
+    pub fn with_trim(mut self, trim: f32) -> Self {
+        self.trim = trim.clamp(0.0, 1.0);
+        self.climb = self.climb.with_output_limits(-self.trim, 1.0 - self.trim);
+        self
+    }
+
+    /// Drives `channel` instead of channel 2, such as channel 5 for the
+    /// collective pitch of a helicopter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is 12 or more.
+    pub fn with_channel(mut self, channel: usize) -> Self {
+        assert!(channel < 12, "channel must be below 12, got {}", channel);
+        self.channel = channel;
+        self
+    }
+
+    /// Throttle that holds level flight.
+    pub fn trim(&self) -> f32 {
+        self.trim
+    }
+
+    /// Clears the state of both loops.
+    pub fn reset(&mut self) {
+        self.altitude.reset();
+        self.climb.reset();
+    }
+
+    /// Throttle command to reach `target` meters above sea level, `dt` after
+    /// the last update.
+    pub fn update(&mut self, target: f32, state: &SimulatorState, dt: Duration) -> ChannelCommands {
+        let altitude = units::length(state.altitude_asl);
+        // velocity_world_w points down
+        let climb = -units::velocity(state.velocity_world_w);
+
+        let climb_target = self.altitude.update(target, altitude, dt);
+        let throttle = self.trim + self.climb.update(climb_target, climb, dt);
+        ChannelCommands::new().with(self.channel, throttle)
+    }
+}
+
+/// Holds a heading on the rudder channel.
+///
+/// Two loops run in cascade: the heading [Pid] turns the error from the
+/// `azimuth`, wrapped with [heading_error], into a yaw rate target in
+/// degrees/second, and the yaw rate [Pid] turns the error from the
+/// `yaw_rate` into deflection around the 0.5 center. Limit the heading
+/// [Pid]'s output to bound the turn rate.
+///
+/// Aircraft that turn by banking can be steered on the aileron instead with
+/// [with_channel](Self::with_channel).
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadingHold {
+    /// Heading error (deg) to yaw rate target [degrees/second]
+    pub heading: Pid,
+    /// Yaw rate error [degrees/second] to deflection
+    pub yaw_rate: Pid,
+    channel: usize,
+}
+
+impl HeadingHold {
+    /// Heading hold on channel 3. The yaw rate [Pid]'s output limits are
+    /// replaced by -1..=1 for full deflection either way.
+    pub fn new(heading: Pid, yaw_rate: Pid) -> Self {
+        HeadingHold {
+            heading,
+            yaw_rate: yaw_rate.with_output_limits(-1.0, 1.0),
+            channel: 3,
+        }
+    }
+
+    /// Drives `channel` instead of channel 3.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is 12 or more.
+    pub fn with_channel(mut self, channel: usize) -> Self {
+        assert!(channel < 12, "channel must be below 12, got {}", channel);
+        self.channel = channel;
+        self
+    }
+
+    /// Clears the state of both loops.
+    pub fn reset(&mut self) {
+        self.heading.reset();
+        self.yaw_rate.reset();
+    }
+
+    /// Deflection command to turn to `target` degrees from north, `dt` after
+    /// the last update.
+    pub fn update(&mut self, target: f32, state: &SimulatorState, dt: Duration) -> ChannelCommands {
+        let azimuth = units::angle(state.azimuth);
+        // Measure the heading on the target's side of the wrap, so neither the
+        // error nor the derivative jumps when the azimuth crosses 180
+        let heading = target - heading_error(target, azimuth);
+
+        let yaw_rate_target = self.heading.update(target, heading, dt);
+        let deflection =
+            self.yaw_rate
+                .update(yaw_rate_target, units::angular_velocity(state.yaw_rate), dt);
+        ChannelCommands::new().with(self.channel, 0.5 + deflection / 2.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoders::{to_angle, to_angular_velocity, to_length, to_velocity};
+    use approx::assert_relative_eq;
+
+    const DT: Duration = Duration::from_millis(10);
+
+    mod heading_error_tests {
+        use super::*;
+
+        #[test]
+        fn takes_shortest_turn() {
+            assert_eq!(heading_error(30.0, 10.0), 20.0);
+            assert_eq!(heading_error(10.0, 30.0), -20.0);
+            assert_eq!(heading_error(0.0, 0.0), 0.0);
+        }
+
+        #[test]
+        fn wraps_across_180() {
+            assert_eq!(heading_error(-170.0, 170.0), 20.0);
+            assert_eq!(heading_error(170.0, -170.0), -20.0);
+            assert_eq!(heading_error(-179.0, 179.0), 2.0);
+            assert_eq!(heading_error(179.0, -179.0), -2.0);
+            assert_eq!(heading_error(180.0, -180.0), 0.0);
+        }
+
+        #[test]
+        fn wraps_across_north() {
+            assert_eq!(heading_error(10.0, 350.0), 20.0);
+            assert_eq!(heading_error(350.0, 10.0), -20.0);
+            assert_eq!(heading_error(0.0, 359.0), 1.0);
+        }
+
+        #[test]
+        fn half_turn_goes_left() {
+            assert_eq!(heading_error(90.0, -90.0), -180.0);
+            assert_eq!(heading_error(-90.0, 90.0), -180.0);
+        }
+    }
+
+    mod heading_hold_tests {
+        use super::*;
+
+        fn state(azimuth: f32, yaw_rate: f32) -> SimulatorState {
+            SimulatorState {
+                azimuth: to_angle(azimuth),
+                yaw_rate: to_angular_velocity(yaw_rate),
+                ..Default::default()
+            }
+        }
+
+        fn proportional() -> HeadingHold {
+            HeadingHold::new(Pid::new(1.0, 0.0, 0.0), Pid::new(0.01, 0.0, 0.0))
+        }
+
+        #[test]
+        fn turns_toward_target() {
+            let mut hold = proportional();
+
+            let right = hold.update(30.0, &state(10.0, 0.0), DT);
+            let left = hold.update(10.0, &state(30.0, 0.0), DT);
+
+            // Yaw rate target of 20 degrees/second gives 0.2 deflection
+            assert_relative_eq!(right.get(3).unwrap(), 0.6);
+            assert_relative_eq!(left.get(3).unwrap(), 0.4);
+            assert_eq!(right.iter().count(), 1);
+        }
+
+        #[test]
+        fn turns_right_across_180() {
+            let mut hold = proportional();
+
+            let commands = hold.update(-170.0, &state(170.0, 0.0), DT);
+
+            assert_relative_eq!(commands.get(3).unwrap(), 0.6);
+        }
+
+        #[test]
+        fn turns_left_across_180() {
+            let mut hold = proportional();
+
+            let commands = hold.update(170.0, &state(-170.0, 0.0), DT);
+
+            assert_relative_eq!(commands.get(3).unwrap(), 0.4);
+        }
+
+        #[test]
+        fn derivative_is_smooth_across_180() {
+            // Derivative only: -kd times the heading rate
+            let mut hold = HeadingHold::new(Pid::new(0.0, 0.0, 0.1), Pid::new(0.01, 0.0, 0.0));
+
+            hold.update(180.0, &state(179.5, 0.0), DT);
+            // 1 degree in 10 ms is 100 degrees/second
+            let commands = hold.update(180.0, &state(-179.5, 0.0), DT);
+
+            // Yaw rate target of -10 degrees/second gives -0.1 deflection
+            assert_relative_eq!(commands.get(3).unwrap(), 0.45, max_relative = 1e-4);
+        }
+
+        #[test]
+        fn damps_on_yaw_rate() {
+            let mut hold = proportional();
+
+            // On target but yawing right at 20 degrees/second
+            let commands = hold.update(90.0, &state(90.0, 20.0), DT);
+
+            assert_relative_eq!(commands.get(3).unwrap(), 0.4);
+        }
+
+        #[test]
+        fn drives_configured_channel() {
+            let mut hold = proportional().with_channel(0);
+
+            let commands = hold.update(30.0, &state(10.0, 0.0), DT);
+
+            assert_relative_eq!(commands.get(0).unwrap(), 0.6);
+            assert_eq!(commands.get(3), None);
+        }
+    }
+
+    mod altitude_hold_tests {
+        use super::*;
+
+        fn state(altitude_asl: f32, climb: f32) -> SimulatorState {
+            SimulatorState {
+                altitude_asl: to_length(altitude_asl),
+                velocity_world_w: to_velocity(-climb),
+                ..Default::default()
+            }
+        }
+
+        fn proportional() -> AltitudeHold {
+            AltitudeHold::new(Pid::new(0.5, 0.0, 0.0), Pid::new(0.1, 0.0, 0.0)).with_trim(0.6)
+        }
+
+        #[test]
+        fn adds_throttle_below_target() {
+            let mut hold = proportional();
+
+            let commands = hold.update(110.0, &state(100.0, 0.0), DT);
+
+            // Climb rate target of 5 meters/second gives 0.5 throttle above trim,
+            // clamped to full
+            assert_eq!(commands.get(2), Some(1.0));
+            assert_eq!(commands.iter().count(), 1);
+        }
+
+        #[test]
+        fn trims_at_target() {
+            let mut hold = proportional();
+
+            let commands = hold.update(100.0, &state(100.0, 0.0), DT);
+
+            assert_relative_eq!(commands.get(2).unwrap(), 0.6);
+        }
+
+        #[test]
+        fn reads_climb_from_downward_velocity() {
+            let mut hold = proportional();
+
+            // On target and climbing at 2 meters/second
+            let commands = hold.update(100.0, &state(100.0, 2.0), DT);
+
+            assert_relative_eq!(commands.get(2).unwrap(), 0.4);
+        }
+
+        #[test]
+        fn limits_climb_pid_to_throttle_range() {
+            let hold = proportional();
+
+            assert_eq!(hold.trim(), 0.6);
+            let (min, max) = hold.climb.output_limits();
+            assert_relative_eq!(min, -0.6);
+            assert_relative_eq!(max, 0.4);
+        }
+
+        #[test]
+        fn settles_on_point_mass() {
+            // Vertical speed follows throttle above trim with a 0.5 s lag, at
+            // 10 meters/second for full throttle
+            let mut hold = AltitudeHold::new(
+                Pid::new(0.8, 0.0, 0.0).with_output_limits(-3.0, 3.0),
+                Pid::new(0.1, 0.2, 0.0),
+            );
+            let dt = DT.as_secs_f32();
+            let (mut altitude, mut climb) = (100.0, 0.0);
+
+            for _ in 0..3000 {
+                let throttle = hold
+                    .update(120.0, &state(altitude, climb), DT)
+                    .get(2)
+                    .unwrap();
+                climb += ((throttle - hold.trim()) * 20.0 - climb) * dt / 0.5;
+                altitude += climb * dt;
+            }
+
+            assert_relative_eq!(altitude, 120.0, max_relative = 1e-3);
+        }
+
+        #[test]
+        fn drives_configured_channel() {
+            let mut hold = proportional().with_channel(5);
+
+            let commands = hold.update(100.0, &state(100.0, 0.0), DT);
+
+            assert_relative_eq!(commands.get(5).unwrap(), 0.6);
+            assert_eq!(commands.get(2), None);
+        }
+    }
+
+    #[cfg(feature = "mock")]
+    mod mock_simulator_tests {
+        use super::*;
+        use crate::mock::MockSimulator;
+        use crate::{Configuration, ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+
+        #[test]
+        fn holds_heading_across_180() {
+            let simulator = MockSimulator::start(0).unwrap();
+            let bridge = RealFlightLocalBridge::with_configuration(&Configuration {
+                simulator_host: simulator.host(),
+                ..Default::default()
+            })
+            .unwrap();
+            bridge.disable_rc().unwrap();
+
+            let mut hold = HeadingHold::new(
+                Pid::new(2.0, 0.0, 0.0).with_output_limits(-40.0, 40.0),
+                Pid::new(0.01, 1.0, 0.0),
+            );
+            let mut inputs = ControlInputs {
+                channels: [0.5; 12],
+            };
+            inputs.channels[2] = 0.0;
+            let mut fly = |target: f32, steps: usize| {
+                let mut azimuths = Vec::new();
+                for _ in 0..steps {
+                    let state = bridge.exchange_data(&inputs).unwrap();
+                    azimuths.push(units::angle(state.azimuth));
+                    inputs.merge(&hold.update(target, &state, DT));
+                }
+                azimuths
+            };
+
+            fly(170.0, 800);
+            let azimuths = fly(-170.0, 300);
+
+            // Turned right through 180 rather than back through north
+            let last = *azimuths.last().unwrap();
+            assert!(heading_error(-170.0, last).abs() < 0.5, "ended at {}", last);
+            assert!(
+                azimuths.iter().all(|azimuth| azimuth.abs() > 160.0),
+                "{:?}",
+                azimuths
+            );
+        }
+    }
+}
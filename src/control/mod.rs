@@ -0,0 +1,574 @@
+//! PID building blocks for closing loops on [SimulatorState].
+//!
+//! [Pid] is a textbook PID controller with output limits, integral
+//! anti-windup and the derivative taken on the measurement.
+//! [AttitudeController] runs three of them to hold roll, pitch and yaw rate
+//! targets through the aileron, elevator and rudder channels of a
+//! [ControlInputs]. The [holds] module builds heading and altitude holds on
+//! them, which set only the channels they drive as [ChannelCommands] for
+//! [ControlInputs::merge].
+//!
+//! This is a convenience layer for experiments and tests, not an autopilot:
+//! there is no navigation, no gain scheduling and no failsafe. Gains that fly
+//! one RealFlight model will not fly another.
+//!
+//! ```no_run
+//! use realflight_bridge::control::{AttitudeController, Pid, RateTargets};
+//! use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bridge = RealFlightLocalBridge::new()?;
+//! bridge.disable_rc()?;
+//!
+//! let rate_pid = || Pid::new(0.01, 0.005, 0.0);
+//! let mut controller = AttitudeController::new(rate_pid(), rate_pid(), rate_pid());
+//! let mut inputs = ControlInputs::default();
+//! inputs.channels[2] = 0.7; // Throttle
+//!
+//! // Roll right at 30 degrees/second, holding pitch and yaw
+//! let targets = RateTargets { roll: 30.0, pitch: 0.0, yaw: 0.0 };
+//! for _ in 0..200 {
+//!     let state = bridge.exchange_data(&inputs)?;
+//!     controller.update(&targets, &state, Duration::from_millis(10), &mut inputs);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod holds;
+
+use std::time::Duration;
+
+use crate::encoders::units;
+use crate::{ControlInputs, SimulatorState};
+
+/// PID controller.
+///
+/// The integral stops growing while the output is held at a limit in the
+/// direction of the error, so it does not wind up during saturation. The
+/// derivative acts on the measurement rather than the error, so a setpoint
+/// step does not kick the output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pid {
+    /// Proportional gain
+    pub kp: f32,
+    /// Integral gain, per second
+    pub ki: f32,
+    /// Derivative gain, in seconds
+    pub kd: f32,
+    min: f32,
+    max: f32,
+    integral: f32,
+    last_measurement: Option<f32>,
+}
+
+impl Pid {
+    /// Controller with the given gains and no output limits.
+    pub fn new(kp: f32, ki: f32, kd: f32) -> Self {
+        Pid {
+            kp,
+            ki,
+            kd,
+            min: f32::NEG_INFINITY,
+            max: f32::INFINITY,
+            integral: 0.0,
+            last_measurement: None,
+        }
+    }
+
+    /// Limits the output to `min..=max`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max`.
+    pub fn with_output_limits(mut self, min: f32, max: f32) -> Self {
+        assert!(min <= max, "min must not exceed max, got {}..={}", min, max);
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    /// Output limits.
+    pub fn output_limits(&self) -> (f32, f32) {
+        (self.min, self.max)
+    }
+
+    /// Integral term of the last output.
+    pub fn integral(&self) -> f32 {
+        self.integral
+    }
+
+    /// Clears the integral and the previous measurement.
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.last_measurement = None;
+    }
+
+    /// Output for `measurement` against `setpoint`, `dt` after the last update.
+    ///
+    /// The first update after creation or [reset](Self::reset) has no
+    /// derivative term. A zero `dt` updates neither the integral nor the
+    /// derivative.
+    pub fn update(&mut self, setpoint: f32, measurement: f32, dt: Duration) -> f32 {
+        let dt = dt.as_secs_f32();
+        let error = setpoint - measurement;
+        let proportional = self.kp * error;
+
+        let derivative = match self.last_measurement {
+            Some(last) if dt > 0.0 => -self.kd * (measurement - last) / dt,
+            _ => 0.0,
+        };
+        self.last_measurement = Some(measurement);
+
+        let integral = self.integral + self.ki * error * dt;
+        let output = proportional + integral + derivative;
+        // Only integrate when it does not push further into saturation
+        let winding_up = (output > self.max && error > 0.0) || (output < self.min && error < 0.0);
+        if !winding_up {
+            self.integral = integral;
+        }
+
+        (proportional + self.integral + derivative).clamp(self.min, self.max)
+    }
+}
+
+/// Body rates for an [AttitudeController] to hold, in degrees/second.
+///
+/// Positive values roll right, pitch nose up and yaw nose right.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RateTargets {
+    /// Roll rate around body X axis [degrees/second]
+    pub roll: f32,
+    /// Pitch rate around body Y axis [degrees/second]
+    pub pitch: f32,
+    /// Yaw rate around body Z axis [degrees/second]
+    pub yaw: f32,
+}
+
+/// Holds roll, pitch and yaw rate targets through the control surfaces.
+///
+/// Each axis has a [Pid] from rate error in degrees/second to deflection,
+/// limited to -1..=1 for full deflection either way. Deflection `d` is sent
+/// as `0.5 + d / 2` on the axis's channel: by default channel 0 (aileron),
+/// 1 (elevator) and 3 (rudder), as RealFlight assigns them. The other
+/// channels, such as the throttle on channel 2, are left as they are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttitudeController {
+    /// Roll rate controller
+    pub roll: Pid,
+    /// Pitch rate controller
+    pub pitch: Pid,
+    /// Yaw rate controller
+    pub yaw: Pid,
+    channels: [usize; 3],
+}
+
+impl AttitudeController {
+    /// Controller with one [Pid] per axis. Their output limits are replaced
+    /// by -1..=1.
+    pub fn new(roll: Pid, pitch: Pid, yaw: Pid) -> Self {
+        AttitudeController {
+            roll: roll.with_output_limits(-1.0, 1.0),
+            pitch: pitch.with_output_limits(-1.0, 1.0),
+            yaw: yaw.with_output_limits(-1.0, 1.0),
+            channels: [0, 1, 3],
+        }
+    }
+
+    /// Drives the aileron, elevator and rudder from these channels instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a channel is 12 or more.
+    pub fn with_channels(mut self, aileron: usize, elevator: usize, rudder: usize) -> Self {
+        for channel in [aileron, elevator, rudder] {
+            assert!(channel < 12, "channel must be below 12, got {}", channel);
+        }
+        self.channels = [aileron, elevator, rudder];
+        self
+    }
+
+    /// Clears the state of every axis.
+    pub fn reset(&mut self) {
+        self.roll.reset();
+        self.pitch.reset();
+        self.yaw.reset();
+    }
+
+    /// Updates the control surface channels of `inputs` from the rates in
+    /// `state`, `dt` after the last update.
+    pub fn update(
+        &mut self,
+        targets: &RateTargets,
+        state: &SimulatorState,
+        dt: Duration,
+        inputs: &mut ControlInputs,
+    ) {
+        let roll = self
+            .roll
+            .update(targets.roll, units::angular_velocity(state.roll_rate), dt);
+        let pitch = self
+            .pitch
+            .update(targets.pitch, units::angular_velocity(state.pitch_rate), dt);
+        let yaw = self
+            .yaw
+            .update(targets.yaw, units::angular_velocity(state.yaw_rate), dt);
+
+        for (channel, deflection) in self.channels.into_iter().zip([roll, pitch, yaw]) {
+            inputs.channels[channel] = 0.5 + deflection / 2.0;
+        }
+    }
+}
+
+/// Values for some of the 12 channels of a [ControlInputs], leaving the
+/// others alone when merged with [ControlInputs::merge].
+///
+/// Controllers that drive part of the aircraft return these, so several can
+/// share one [ControlInputs].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ChannelCommands {
+    channels: [Option<f32>; 12],
+}
+
+impl ChannelCommands {
+    /// Commands for no channels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `channel` to `value`, clamped to 0.0..=1.0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is 12 or more.
+    pub fn set(&mut self, channel: usize, value: f32) {
+        assert!(channel < 12, "channel must be below 12, got {}", channel);
+        self.channels[channel] = Some(value.clamp(0.0, 1.0));
+    }
+
+    /// Builder form of [set](Self::set).
+    pub fn with(mut self, channel: usize, value: f32) -> Self {
+        self.set(channel, value);
+        self
+    }
+
+    /// Value set for `channel`, if any.
+    pub fn get(&self, channel: usize) -> Option<f32> {
+        self.channels.get(channel).copied().flatten()
+    }
+
+    /// Takes the channels `other` sets, replacing any set here.
+    pub fn merge(&mut self, other: &ChannelCommands) {
+        for (own, theirs) in self.channels.iter_mut().zip(other.channels) {
+            if theirs.is_some() {
+                *own = theirs;
+            }
+        }
+    }
+
+    /// Channels set and their values, in channel order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, f32)> + '_ {
+        self.channels
+            .iter()
+            .enumerate()
+            .filter_map(|(channel, value)| value.map(|value| (channel, value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoders::to_angular_velocity;
+    use approx::assert_relative_eq;
+
+    const DT: Duration = Duration::from_millis(10);
+
+    fn dt() -> f32 {
+        DT.as_secs_f32()
+    }
+
+    mod pid_tests {
+        use super::*;
+
+        #[test]
+        fn proportional_scales_error() {
+            let mut pid = Pid::new(2.0, 0.0, 0.0);
+
+            assert_eq!(pid.update(3.0, 1.0, DT), 4.0);
+            assert_eq!(pid.update(1.0, 3.0, DT), -4.0);
+        }
+
+        #[test]
+        fn p_step_response_on_integrator() {
+            // y' = u, u = kp (r - y): y_n = r (1 - (1 - kp dt)^n)
+            let (kp, r) = (5.0, 10.0);
+            let mut pid = Pid::new(kp, 0.0, 0.0);
+            let mut y = 0.0;
+
+            for n in 1..=50 {
+                y += pid.update(r, y, DT) * dt();
+                let expected = r * (1.0 - (1.0 - kp * dt()).powi(n));
+                assert_relative_eq!(y, expected, max_relative = 1e-4);
+            }
+        }
+
+        #[test]
+        fn i_step_response_on_static_plant() {
+            // y_n = u_(n-1), u_n = u_(n-1) + ki dt (r - y_n): u_n = r (1 - (1 - ki dt)^(n + 1))
+            let (ki, r) = (20.0, 4.0);
+            let mut pid = Pid::new(0.0, ki, 0.0);
+            let mut y = 0.0;
+
+            for n in 0..50 {
+                y = pid.update(r, y, DT);
+                let expected = r * (1.0 - (1.0 - ki * dt()).powi(n + 1));
+                assert_relative_eq!(y, expected, max_relative = 1e-4);
+            }
+            assert_relative_eq!(pid.integral(), y);
+        }
+
+        #[test]
+        fn derivative_opposes_measurement_change() {
+            let mut pid = Pid::new(0.0, 0.0, 0.5);
+
+            assert_eq!(pid.update(0.0, 0.0, DT), 0.0);
+            // Rising at 100 units/second
+            assert_relative_eq!(pid.update(0.0, 1.0, DT), -50.0, max_relative = 1e-5);
+        }
+
+        #[test]
+        fn setpoint_step_does_not_kick() {
+            let mut pid = Pid::new(0.0, 0.0, 0.5);
+
+            pid.update(0.0, 2.0, DT);
+
+            assert_eq!(pid.update(100.0, 2.0, DT), 0.0);
+        }
+
+        #[test]
+        fn clamps_output() {
+            let mut pid = Pid::new(10.0, 0.0, 0.0).with_output_limits(-1.0, 2.0);
+
+            assert_eq!(pid.update(1.0, 0.0, DT), 2.0);
+            assert_eq!(pid.update(-1.0, 0.0, DT), -1.0);
+            assert_eq!(pid.output_limits(), (-1.0, 2.0));
+        }
+
+        #[test]
+        fn integral_does_not_wind_up() {
+            let mut pid = Pid::new(0.0, 10.0, 0.0).with_output_limits(-1.0, 1.0);
+
+            for _ in 0..1000 {
+                pid.update(1.0, 0.0, DT);
+            }
+            assert!(pid.integral() <= 1.0 + 10.0 * dt(), "{}", pid.integral());
+
+            // Leaves saturation on the first update after the error reverses
+            assert!(pid.update(-1.0, 0.0, DT) < 1.0);
+        }
+
+        #[test]
+        fn integrates_out_of_saturation() {
+            let mut pid = Pid::new(0.0, 10.0, 0.0).with_output_limits(-1.0, 1.0);
+            for _ in 0..1000 {
+                pid.update(1.0, 0.0, DT);
+            }
+            let saturated = pid.integral();
+
+            pid.update(-1.0, 0.0, DT);
+
+            assert_relative_eq!(pid.integral(), saturated - 10.0 * dt());
+        }
+
+        #[test]
+        fn zero_dt_holds_state() {
+            let mut pid = Pid::new(1.0, 1.0, 1.0);
+            pid.update(0.0, 0.0, DT);
+
+            assert_eq!(pid.update(1.0, 0.5, Duration::ZERO), 0.5);
+            assert_eq!(pid.integral(), 0.0);
+        }
+
+        #[test]
+        fn reset_clears_state() {
+            let mut pid = Pid::new(0.0, 1.0, 1.0);
+            pid.update(1.0, 0.0, DT);
+            pid.update(1.0, 0.5, DT);
+
+            pid.reset();
+
+            assert_eq!(pid.integral(), 0.0);
+            // No derivative on the first update after a reset
+            assert_eq!(pid.update(0.0, 5.0, Duration::ZERO), 0.0);
+        }
+
+        #[test]
+        #[should_panic(expected = "min must not exceed max")]
+        fn rejects_inverted_limits() {
+            Pid::new(1.0, 0.0, 0.0).with_output_limits(1.0, -1.0);
+        }
+    }
+
+    mod attitude_controller_tests {
+        use super::*;
+
+        fn state(roll_rate: f32, pitch_rate: f32, yaw_rate: f32) -> SimulatorState {
+            SimulatorState {
+                roll_rate: to_angular_velocity(roll_rate),
+                pitch_rate: to_angular_velocity(pitch_rate),
+                yaw_rate: to_angular_velocity(yaw_rate),
+                ..Default::default()
+            }
+        }
+
+        fn proportional(kp: f32) -> AttitudeController {
+            AttitudeController::new(
+                Pid::new(kp, 0.0, 0.0),
+                Pid::new(kp, 0.0, 0.0),
+                Pid::new(kp, 0.0, 0.0),
+            )
+        }
+
+        #[test]
+        fn maps_rate_errors_to_surface_channels() {
+            let mut controller = proportional(0.01);
+            let mut inputs = ControlInputs::default();
+            inputs.channels[2] = 0.7;
+            let targets = RateTargets {
+                roll: 30.0,
+                pitch: -20.0,
+                yaw: 0.0,
+            };
+
+            controller.update(&targets, &state(10.0, 0.0, 40.0), DT, &mut inputs);
+
+            // Deflections 0.2, -0.2 and -0.4 around the 0.5 center
+            assert_relative_eq!(inputs.channels[0], 0.6);
+            assert_relative_eq!(inputs.channels[1], 0.4);
+            assert_relative_eq!(inputs.channels[3], 0.3);
+            assert_eq!(inputs.channels[2], 0.7);
+        }
+
+        #[test]
+        fn limits_to_full_deflection() {
+            let mut controller = proportional(1.0);
+            let mut inputs = ControlInputs::default();
+            let targets = RateTargets {
+                roll: 90.0,
+                pitch: -90.0,
+                yaw: 0.0,
+            };
+
+            controller.update(&targets, &state(0.0, 0.0, 0.0), DT, &mut inputs);
+
+            assert_eq!(inputs.channels[0], 1.0);
+            assert_eq!(inputs.channels[1], 0.0);
+            assert_eq!(inputs.channels[3], 0.5);
+        }
+
+        #[test]
+        fn uses_configured_channels() {
+            let mut controller = proportional(0.01).with_channels(4, 5, 6);
+            let mut inputs = ControlInputs::default();
+            let targets = RateTargets {
+                roll: 10.0,
+                pitch: 20.0,
+                yaw: 30.0,
+            };
+
+            controller.update(&targets, &state(0.0, 0.0, 0.0), DT, &mut inputs);
+
+            assert_relative_eq!(inputs.channels[4], 0.55);
+            assert_relative_eq!(inputs.channels[5], 0.6);
+            assert_relative_eq!(inputs.channels[6], 0.65);
+            assert_eq!(inputs.channels[0], 0.0);
+        }
+
+        #[test]
+        fn pi_holds_rate_on_stick_to_rate_plant() {
+            // Rate follows the stick with a first-order lag of time constant tau
+            // towards 90 degrees/second at full deflection: PI with ki = kp / tau
+            // cancels the lag, so the rate settles on the target without error
+            let tau = 0.1;
+            let kp = 0.02;
+            let mut controller = AttitudeController::new(
+                Pid::new(kp, kp / tau, 0.0),
+                Pid::new(0.0, 0.0, 0.0),
+                Pid::new(0.0, 0.0, 0.0),
+            );
+            let targets = RateTargets {
+                roll: 30.0,
+                ..Default::default()
+            };
+            let mut inputs = ControlInputs::default();
+            let mut rate = 0.0;
+
+            for _ in 0..500 {
+                controller.update(&targets, &state(rate, 0.0, 0.0), DT, &mut inputs);
+                let deflection = (inputs.channels[0] - 0.5) * 2.0;
+                rate += (deflection * 90.0 - rate) * dt() / tau;
+            }
+
+            assert_relative_eq!(rate, 30.0, max_relative = 1e-3);
+        }
+
+        #[test]
+        #[should_panic(expected = "channel must be below 12")]
+        fn rejects_invalid_channel() {
+            proportional(1.0).with_channels(0, 1, 12);
+        }
+    }
+
+    mod channel_commands_tests {
+        use super::*;
+
+        #[test]
+        fn sets_only_given_channels() {
+            let commands = ChannelCommands::new().with(2, 0.7).with(3, 0.4);
+
+            assert_eq!(commands.get(2), Some(0.7));
+            assert_eq!(commands.get(0), None);
+            assert_eq!(commands.get(12), None);
+            assert_eq!(commands.iter().collect::<Vec<_>>(), [(2, 0.7), (3, 0.4)]);
+        }
+
+        #[test]
+        fn clamps_values() {
+            let commands = ChannelCommands::new().with(0, 1.5).with(1, -0.5);
+
+            assert_eq!(commands.get(0), Some(1.0));
+            assert_eq!(commands.get(1), Some(0.0));
+        }
+
+        #[test]
+        fn later_commands_win() {
+            let mut commands = ChannelCommands::new().with(2, 0.7).with(3, 0.4);
+
+            commands.merge(&ChannelCommands::new().with(3, 0.6).with(5, 1.0));
+
+            assert_eq!(
+                commands.iter().collect::<Vec<_>>(),
+                [(2, 0.7), (3, 0.6), (5, 1.0)]
+            );
+        }
+
+        #[test]
+        fn merges_into_control_inputs() {
+            let mut inputs = ControlInputs {
+                channels: [0.5; 12],
+            };
+
+            inputs.merge(&ChannelCommands::new().with(2, 0.7));
+
+            assert_eq!(inputs.channels[2], 0.7);
+            assert_eq!(inputs.channels[3], 0.5);
+        }
+
+        #[test]
+        #[should_panic(expected = "channel must be below 12")]
+        fn rejects_invalid_channel() {
+            ChannelCommands::new().set(12, 0.5);
+        }
+    }
+}
@@ -17,74 +17,74 @@ pub const OUNCES_PER_LITER: f32 = 33.814;
 // Converter functions: wrap f32 into appropriate types based on feature flag
 
 #[cfg(feature = "uom")]
-fn to_velocity(v: f32) -> Velocity {
+pub(crate) fn to_velocity(v: f32) -> Velocity {
     Velocity::new::<meter_per_second>(v)
 }
 #[cfg(not(feature = "uom"))]
-fn to_velocity(v: f32) -> Velocity {
+pub(crate) fn to_velocity(v: f32) -> Velocity {
     v
 }
 
 #[cfg(feature = "uom")]
-fn to_length(v: f32) -> Length {
+pub(crate) fn to_length(v: f32) -> Length {
     Length::new::<meter>(v)
 }
 #[cfg(not(feature = "uom"))]
-fn to_length(v: f32) -> Length {
+pub(crate) fn to_length(v: f32) -> Length {
     v
 }
 
 #[cfg(feature = "uom")]
-fn to_angular_velocity(v: f32) -> AngularVelocity {
+pub(crate) fn to_angular_velocity(v: f32) -> AngularVelocity {
     AngularVelocity::new::<degree_per_second>(v)
 }
 #[cfg(not(feature = "uom"))]
-fn to_angular_velocity(v: f32) -> AngularVelocity {
+pub(crate) fn to_angular_velocity(v: f32) -> AngularVelocity {
     v
 }
 
 #[cfg(feature = "uom")]
-fn to_angle(v: f32) -> Angle {
+pub(crate) fn to_angle(v: f32) -> Angle {
     Angle::new::<degree>(v)
 }
 #[cfg(not(feature = "uom"))]
-fn to_angle(v: f32) -> Angle {
+pub(crate) fn to_angle(v: f32) -> Angle {
     v
 }
 
 #[cfg(feature = "uom")]
-fn to_acceleration(v: f32) -> Acceleration {
+pub(crate) fn to_acceleration(v: f32) -> Acceleration {
     Acceleration::new::<meter_per_second_squared>(v)
 }
 #[cfg(not(feature = "uom"))]
-fn to_acceleration(v: f32) -> Acceleration {
+pub(crate) fn to_acceleration(v: f32) -> Acceleration {
     v
 }
 
 #[cfg(feature = "uom")]
-fn to_electric_potential(v: f32) -> ElectricPotential {
+pub(crate) fn to_electric_potential(v: f32) -> ElectricPotential {
     ElectricPotential::new::<volt>(v)
 }
 #[cfg(not(feature = "uom"))]
-fn to_electric_potential(v: f32) -> ElectricPotential {
+pub(crate) fn to_electric_potential(v: f32) -> ElectricPotential {
     v
 }
 
 #[cfg(feature = "uom")]
-fn to_electric_current(v: f32) -> ElectricCurrent {
+pub(crate) fn to_electric_current(v: f32) -> ElectricCurrent {
     ElectricCurrent::new::<ampere>(v)
 }
 #[cfg(not(feature = "uom"))]
-fn to_electric_current(v: f32) -> ElectricCurrent {
+pub(crate) fn to_electric_current(v: f32) -> ElectricCurrent {
     v
 }
 
 #[cfg(feature = "uom")]
-fn to_electric_charge(v: f32) -> ElectricCharge {
+pub(crate) fn to_electric_charge(v: f32) -> ElectricCharge {
     ElectricCharge::new::<milliampere_hour>(v)
 }
 #[cfg(not(feature = "uom"))]
-fn to_electric_charge(v: f32) -> ElectricCharge {
+pub(crate) fn to_electric_charge(v: f32) -> ElectricCharge {
     v
 }
 
@@ -94,11 +94,11 @@ fn to_volume(v: f32) -> Volume {
 }
 
 #[cfg(feature = "uom")]
-fn to_time(v: f32) -> Time {
+pub(crate) fn to_time(v: f32) -> Time {
     Time::new::<second>(v)
 }
 #[cfg(not(feature = "uom"))]
-fn to_time(v: f32) -> Time {
+pub(crate) fn to_time(v: f32) -> Time {
     v
 }
 
@@ -183,7 +183,15 @@ pub fn decode_simulator_state(xml: &str) -> Result<SimulatorState, BridgeError>
                             field: format!("channel[{}]", channel_ndx),
                             message: format!("{}", e),
                         })?;
-                        result.previous_inputs.channels[channel_ndx] = value;
+                        let channel = result
+                            .previous_inputs
+                            .channels
+                            .get_mut(channel_ndx)
+                            .ok_or_else(|| BridgeError::Parse {
+                                field: format!("channel[{}]", channel_ndx),
+                                message: "more channels than ControlInputs holds".to_string(),
+                            })?;
+                        *channel = value;
                         channel_ndx += 1;
                     } else {
                         decode_state_field(&mut result, &open_tag, &content)?;
@@ -5,6 +5,8 @@
 //! - `decode_state_tests`: Tests for full simulator state decoding
 //! - `error_handling`: Tests for parse error handling
 
+#![allow(clippy::excessive_precision)]
+
 use approx::assert_relative_eq;
 
 use super::*;
@@ -156,6 +158,22 @@ mod error_handling {
         }
     }
 
+    #[test]
+    fn returns_error_for_extra_channel() {
+        let xml = format!(
+            "<m-channelValues-0to1>{}</m-channelValues-0to1>",
+            "<item>0.5</item>".repeat(13)
+        );
+        let result = decode_simulator_state(&xml);
+
+        match result {
+            Err(BridgeError::Parse { field, .. }) => {
+                assert_eq!(field, "channel[12]");
+            }
+            other => panic!("expected Parse error, got {:?}", other),
+        }
+    }
+
     #[test]
     fn ignores_unknown_fields() {
         let xml = r#"<unknown-field>some value</unknown-field><m-propRPM>100.0</m-propRPM>"#;
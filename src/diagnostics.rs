@@ -0,0 +1,912 @@
+//! Long-running stability checks against a bridge.
+//!
+//! [soak_test] exchanges data with a bridge at a fixed rate for a configured
+//! duration, such as overnight, and returns a [SoakReport]: totals, one
+//! [SoakSnapshot] per [snapshot_interval](SoakConfig::snapshot_interval) and
+//! every [Stall], a stretch without a successful exchange. Errors are counted
+//! and the run carries on, so a report shows how a bridge recovers as well as
+//! how often it fails. `soak_test_async` does the same for an `AsyncBridge`,
+//! with the `rt-tokio` feature.
+//!
+//! The report derives `Serialize`, so it can be saved and compared between
+//! runs. It holds what the exchanges themselves show; counters internal to a
+//! bridge, such as connection pool misses, come from its `statistics()`.
+//!
+//! [PhysicsWatchdog] checks the states a control loop receives instead: when
+//! RealFlight's physics pauses, for instance while loading assets or after the
+//! window loses focus, exchanges keep succeeding with a frozen
+//! `current_physics_time`, and a reset sends it back to zero.
+//!
+//! ```no_run
+//! use realflight_bridge::diagnostics::{SoakConfig, soak_test};
+//! use realflight_bridge::{RealFlightBridge, RealFlightLocalBridge};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bridge = RealFlightLocalBridge::new()?;
+//! bridge.disable_rc()?;
+//!
+//! let report = soak_test(
+//!     &bridge,
+//!     SoakConfig {
+//!         duration: Duration::from_secs(8 * 60 * 60),
+//!         ..Default::default()
+//!     },
+//! );
+//! println!("{} stalls, error rate {:.4}", report.stalls.len(), report.error_rate());
+//!
+//! bridge.enable_rc()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::{Duration, Instant};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::bridge::RealFlightBridge;
+use crate::clock::{Clock, SystemClock};
+use crate::encoders::units;
+use crate::{BridgeError, ControlInputs, SimulatorState};
+
+#[cfg(feature = "rt-tokio")]
+use crate::bridge::AsyncBridge;
+
+/// Settings for a [soak_test].
+#[derive(Clone, Debug)]
+pub struct SoakConfig {
+    /// How long to keep exchanging data. Defaults to one hour
+    pub duration: Duration,
+    /// Exchanges per second. Defaults to 50
+    pub rate_hz: f32,
+    /// Length of the window each [SoakSnapshot] covers. Defaults to one minute
+    pub snapshot_interval: Duration,
+    /// Time without a successful exchange reported as a [Stall]. Defaults to
+    /// five seconds
+    pub stall_threshold: Duration,
+    /// Inputs sent on every exchange
+    pub inputs: ControlInputs,
+}
+
+impl Default for SoakConfig {
+    fn default() -> Self {
+        SoakConfig {
+            duration: Duration::from_secs(60 * 60),
+            rate_hz: 50.0,
+            snapshot_interval: Duration::from_secs(60),
+            stall_threshold: Duration::from_secs(5),
+            inputs: ControlInputs::default(),
+        }
+    }
+}
+
+/// Outcome of a [soak_test].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SoakReport {
+    /// Time from the first exchange until the test stopped
+    pub elapsed: Duration,
+    /// Number of exchanges attempted
+    pub exchanges: u64,
+    /// Number of exchanges that failed
+    pub errors: u64,
+    /// Message of the most recent failure
+    pub last_error: Option<String>,
+    /// Exchanges and errors per window, oldest first
+    pub snapshots: Vec<SoakSnapshot>,
+    /// Stretches without a successful exchange, oldest first
+    pub stalls: Vec<Stall>,
+}
+
+impl SoakReport {
+    /// Fraction of exchanges that failed, or 0 if none were attempted.
+    pub fn error_rate(&self) -> f64 {
+        if self.exchanges == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.exchanges as f64
+    }
+}
+
+/// Counters for one window of a [soak_test].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SoakSnapshot {
+    /// End of the window, from the start of the test
+    pub at: Duration,
+    /// Number of exchanges attempted in the window
+    pub exchanges: u64,
+    /// Number of exchanges that failed in the window
+    pub errors: u64,
+    /// Average time an exchange took, successful or not
+    pub mean_latency: Duration,
+    /// Longest time an exchange took
+    pub max_latency: Duration,
+    /// Exchanges that took longer than one period
+    pub overruns: u64,
+}
+
+/// A stretch of a [soak_test] without a successful exchange.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stall {
+    /// Time of the last successful exchange before the stall, from the start
+    /// of the test
+    pub at: Duration,
+    /// Time until the next successful exchange, or until the test stopped
+    pub length: Duration,
+}
+
+/// Runs a soak test with `bridge` on the system clock.
+///
+/// Exchanges [SoakConfig::inputs] every period until [SoakConfig::duration]
+/// has passed. Sleep time is shortened by the time an exchange took, and
+/// after an overrun the schedule restarts from the current time.
+///
+/// # Panics
+///
+/// Panics if `rate_hz` is not a positive, finite number or
+/// `snapshot_interval` is zero.
+pub fn soak_test(bridge: &dyn RealFlightBridge, config: SoakConfig) -> SoakReport {
+    soak_test_with_clock(bridge, config, &SystemClock)
+}
+
+/// Runs [soak_test] with its timing taken from `clock`.
+///
+/// With a [ManualClock](crate::clock::ManualClock) the test runs without
+/// waiting, so a long soak can be checked in a unit test.
+///
+/// # Panics
+///
+/// Panics if `rate_hz` is not a positive, finite number or
+/// `snapshot_interval` is zero.
+pub fn soak_test_with_clock(
+    bridge: &dyn RealFlightBridge,
+    config: SoakConfig,
+    clock: &impl Clock,
+) -> SoakReport {
+    let mut soak = Soak::new(&config, clock.now());
+    while clock.now() - soak.start < config.duration {
+        let sent = clock.now();
+        let result = bridge.exchange_data(&config.inputs).map(drop);
+        let now = clock.now();
+        soak.record(sent, now, result);
+
+        if let Some(wait) = soak.next_wait(now) {
+            clock.sleep(wait);
+        }
+    }
+    soak.finish(clock.now())
+}
+
+/// Async version of [soak_test], on the system clock.
+///
+/// # Panics
+///
+/// Panics if `rate_hz` is not a positive, finite number or
+/// `snapshot_interval` is zero.
+#[cfg(feature = "rt-tokio")]
+pub async fn soak_test_async(bridge: &impl AsyncBridge, config: SoakConfig) -> SoakReport {
+    soak_test_async_with_clock(bridge, config, &SystemClock).await
+}
+
+/// Async version of [soak_test_with_clock].
+///
+/// # Panics
+///
+/// Panics if `rate_hz` is not a positive, finite number or
+/// `snapshot_interval` is zero.
+#[cfg(feature = "rt-tokio")]
+pub async fn soak_test_async_with_clock(
+    bridge: &impl AsyncBridge,
+    config: SoakConfig,
+    clock: &impl Clock,
+) -> SoakReport {
+    let mut soak = Soak::new(&config, clock.now());
+    while clock.now() - soak.start < config.duration {
+        let sent = clock.now();
+        let result = bridge.exchange_data(&config.inputs).await.map(drop);
+        let now = clock.now();
+        soak.record(sent, now, result);
+
+        if let Some(wait) = soak.next_wait(now) {
+            clock.sleep_async(wait).await;
+        }
+    }
+    soak.finish(clock.now())
+}
+
+/// Running state of a soak test, shared by the sync and async loops.
+struct Soak {
+    start: Instant,
+    period: Duration,
+    snapshot_interval: Duration,
+    stall_threshold: Duration,
+    scheduled: Instant,
+    report: SoakReport,
+    /// Window being counted, and when it ends
+    window: SoakSnapshot,
+    window_end: Instant,
+    total_latency: Duration,
+    last_success: Instant,
+}
+
+impl Soak {
+    fn new(config: &SoakConfig, start: Instant) -> Soak {
+        assert!(
+            config.rate_hz.is_finite() && config.rate_hz > 0.0,
+            "rate_hz must be positive and finite, got {}",
+            config.rate_hz
+        );
+        assert!(
+            !config.snapshot_interval.is_zero(),
+            "snapshot_interval must not be zero"
+        );
+        Soak {
+            start,
+            period: Duration::from_secs_f64(1.0 / config.rate_hz as f64),
+            snapshot_interval: config.snapshot_interval,
+            stall_threshold: config.stall_threshold,
+            scheduled: start,
+            report: SoakReport::default(),
+            window: SoakSnapshot::default(),
+            window_end: start + config.snapshot_interval,
+            total_latency: Duration::ZERO,
+            last_success: start,
+        }
+    }
+
+    /// Counts an exchange sent at `sent` that completed at `now`.
+    fn record(&mut self, sent: Instant, now: Instant, result: Result<(), BridgeError>) {
+        while sent >= self.window_end {
+            self.close_window();
+        }
+
+        let latency = now - sent;
+        self.window.exchanges += 1;
+        self.total_latency += latency;
+        self.window.max_latency = self.window.max_latency.max(latency);
+        self.report.exchanges += 1;
+
+        match result {
+            Ok(()) => {
+                self.check_stall(now);
+                self.last_success = now;
+            }
+            Err(e) => {
+                self.window.errors += 1;
+                self.report.errors += 1;
+                self.report.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Time to sleep until the next exchange, or `None` after an overrun.
+    fn next_wait(&mut self, now: Instant) -> Option<Duration> {
+        self.scheduled += self.period;
+        if now < self.scheduled {
+            return Some(self.scheduled - now);
+        }
+        self.window.overruns += 1;
+        self.scheduled = now;
+        None
+    }
+
+    /// Records a stall if no exchange has succeeded for the threshold.
+    fn check_stall(&mut self, now: Instant) {
+        let length = now - self.last_success;
+        if length < self.stall_threshold {
+            return;
+        }
+        let at = self.last_success - self.start;
+        warn!("Soak test stalled for {:?} after {:?}", length, at);
+        self.report.stalls.push(Stall { at, length });
+    }
+
+    /// Moves the current window into the report and starts the next.
+    fn close_window(&mut self) {
+        let mut window = std::mem::take(&mut self.window);
+        window.at = self.window_end - self.start;
+        if window.exchanges > 0 {
+            window.mean_latency = self.total_latency.div_f64(window.exchanges as f64);
+        }
+        self.report.snapshots.push(window);
+        self.total_latency = Duration::ZERO;
+        self.window_end += self.snapshot_interval;
+    }
+
+    fn finish(mut self, now: Instant) -> SoakReport {
+        self.check_stall(now);
+        if self.window.exchanges > 0 {
+            // A partial last window ends when the test does
+            self.window_end = now;
+            self.close_window();
+        }
+        self.report.elapsed = now - self.start;
+        self.report
+    }
+}
+
+/// Thresholds for a [PhysicsWatchdog].
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchdogConfig {
+    /// Time `current_physics_time` may stand still before the physics is
+    /// reported [Stalled](PhysicsHealth::Stalled). Defaults to 250 ms
+    pub stall_after: Duration,
+    /// Largest forward step of `current_physics_time` between two states that
+    /// is not reported as a [jump](PhysicsHealth::Jumped). Defaults to one
+    /// second
+    pub max_step: Duration,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        WatchdogConfig {
+            stall_after: Duration::from_millis(250),
+            max_step: Duration::from_secs(1),
+        }
+    }
+}
+
+/// What a [PhysicsWatchdog] made of the latest state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PhysicsHealth {
+    /// Physics time is advancing
+    Ok,
+    /// Physics time has not advanced for `stalled_for`
+    Stalled { stalled_for: Duration },
+    /// Physics time moved by `by` seconds in one step: more than
+    /// [max_step](WatchdogConfig::max_step) forward, or backward, as it does
+    /// when the aircraft is reset
+    Jumped { by: f32 },
+}
+
+impl PhysicsHealth {
+    /// Whether physics time is advancing normally.
+    pub fn is_ok(&self) -> bool {
+        matches!(self, PhysicsHealth::Ok)
+    }
+}
+
+/// Watches `current_physics_time` for stalls and jumps.
+///
+/// Feed it every state a loop receives with [feed](Self::feed). A stall is
+/// measured on the watchdog's clock from the last state whose physics time
+/// advanced. A callback set with [with_callback](Self::with_callback) is
+/// called when the health changes, and on every jump.
+///
+/// ```no_run
+/// use realflight_bridge::diagnostics::{PhysicsWatchdog, WatchdogConfig};
+/// use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let bridge = RealFlightLocalBridge::new()?;
+/// let mut watchdog = PhysicsWatchdog::new(WatchdogConfig::default())
+///     .with_callback(|health| eprintln!("physics: {:?}", health));
+///
+/// loop {
+///     let state = bridge.exchange_data(&ControlInputs::default())?;
+///     if !watchdog.feed(&state).is_ok() {
+///         // Hold the controller's integrators
+///         continue;
+///     }
+///     // ...
+/// }
+/// # }
+/// ```
+pub struct PhysicsWatchdog<C: Clock = SystemClock> {
+    clock: C,
+    config: WatchdogConfig,
+    health: PhysicsHealth,
+    /// Physics time of the last state, and when it last advanced
+    last_time: Option<f32>,
+    last_advance: Instant,
+    callback: Option<HealthCallback>,
+}
+
+/// Called by a [PhysicsWatchdog] when the health changes.
+type HealthCallback = Box<dyn FnMut(&PhysicsHealth) + Send>;
+
+impl PhysicsWatchdog {
+    /// Watchdog on the system clock.
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self::with_clock(config, SystemClock)
+    }
+}
+
+impl<C: Clock> PhysicsWatchdog<C> {
+    /// Watchdog measuring stalls on `clock`.
+    pub fn with_clock(config: WatchdogConfig, clock: C) -> Self {
+        let last_advance = clock.now();
+        PhysicsWatchdog {
+            clock,
+            config,
+            health: PhysicsHealth::Ok,
+            last_time: None,
+            last_advance,
+            callback: None,
+        }
+    }
+
+    /// Calls `callback` with the new health when it changes between
+    /// [Ok](PhysicsHealth::Ok), [Stalled](PhysicsHealth::Stalled) and
+    /// [Jumped](PhysicsHealth::Jumped), and on every jump.
+    pub fn with_callback(mut self, callback: impl FnMut(&PhysicsHealth) + Send + 'static) -> Self {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Clock stalls are measured on.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Health as of the last [feed](Self::feed), [Ok](PhysicsHealth::Ok)
+    /// before the first.
+    pub fn status(&self) -> PhysicsHealth {
+        self.health
+    }
+
+    /// Forgets the states fed so far.
+    pub fn reset(&mut self) {
+        self.health = PhysicsHealth::Ok;
+        self.last_time = None;
+        self.last_advance = self.clock.now();
+    }
+
+    /// Checks the physics time of `state` against the last, returning the new
+    /// health.
+    pub fn feed(&mut self, state: &SimulatorState) -> PhysicsHealth {
+        let now = self.clock.now();
+        let time = units::time(state.current_physics_time);
+
+        let health = match self.last_time {
+            None => PhysicsHealth::Ok,
+            Some(last) => {
+                let step = time - last;
+                if step < 0.0 || step > self.config.max_step.as_secs_f32() {
+                    PhysicsHealth::Jumped { by: step }
+                } else if step > 0.0 {
+                    PhysicsHealth::Ok
+                } else {
+                    let stalled_for = now - self.last_advance;
+                    if stalled_for >= self.config.stall_after {
+                        PhysicsHealth::Stalled { stalled_for }
+                    } else {
+                        PhysicsHealth::Ok
+                    }
+                }
+            }
+        };
+
+        if self.last_time != Some(time) {
+            self.last_advance = now;
+        }
+        self.last_time = Some(time);
+        self.set_health(health);
+        health
+    }
+
+    fn set_health(&mut self, health: PhysicsHealth) {
+        let changed = std::mem::discriminant(&health) != std::mem::discriminant(&self.health)
+            || matches!(health, PhysicsHealth::Jumped { .. });
+        self.health = health;
+        if !changed {
+            return;
+        }
+        match health {
+            PhysicsHealth::Ok => {}
+            PhysicsHealth::Stalled { stalled_for } => {
+                warn!("Physics time has not advanced for {:?}", stalled_for)
+            }
+            PhysicsHealth::Jumped { by } => warn!("Physics time jumped by {}s", by),
+        }
+        if let Some(callback) = &mut self.callback {
+            callback(&health);
+        }
+    }
+}
+
+impl<C: Clock + std::fmt::Debug> std::fmt::Debug for PhysicsWatchdog<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PhysicsWatchdog")
+            .field("clock", &self.clock)
+            .field("config", &self.config)
+            .field("health", &self.health)
+            .field("last_time", &self.last_time)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::local::RealFlightLocalBridge;
+    use crate::clock::ManualClock;
+    use crate::soap_client::stub::StubSoapClient;
+    use crate::{SimulatorState, decode_simulator_state};
+    use std::ops::Range;
+
+    static SIM_STATE_RESPONSE: &str = include_str!("../testdata/responses/return-data-200.xml");
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn config(duration: Duration, rate_hz: f32, snapshot_interval: Duration) -> SoakConfig {
+        SoakConfig {
+            duration,
+            rate_hz,
+            snapshot_interval,
+            stall_threshold: ms(500),
+            ..Default::default()
+        }
+    }
+
+    /// Bridge that fails while the clock is within `outage`, taking `latency`
+    /// for each exchange.
+    struct FlakyBridge {
+        clock: ManualClock,
+        outage: Range<Duration>,
+        latency: Duration,
+    }
+
+    impl FlakyBridge {
+        fn new(clock: &ManualClock, outage: Range<Duration>) -> Self {
+            FlakyBridge {
+                clock: clock.clone(),
+                outage,
+                latency: Duration::ZERO,
+            }
+        }
+
+        fn exchange(&self) -> Result<SimulatorState, BridgeError> {
+            let sent = self.clock.elapsed();
+            self.clock.advance(self.latency);
+            if self.outage.contains(&sent) {
+                return Err(BridgeError::Timeout(self.latency));
+            }
+            decode_simulator_state(SIM_STATE_RESPONSE)
+        }
+    }
+
+    impl RealFlightBridge for FlakyBridge {
+        fn exchange_data(&self, _control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+            self.exchange()
+        }
+
+        fn enable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        fn disable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        fn reset_aircraft(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    impl AsyncBridge for FlakyBridge {
+        async fn exchange_data(
+            &self,
+            _control: &ControlInputs,
+        ) -> Result<SimulatorState, BridgeError> {
+            self.exchange()
+        }
+
+        async fn enable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        async fn disable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reports_snapshot_per_interval() {
+        let bridge =
+            RealFlightLocalBridge::stub(StubSoapClient::new(vec!["return-data-200".to_string()]));
+        let clock = ManualClock::new();
+
+        let report = soak_test_with_clock(&bridge, config(ms(1000), 100.0, ms(250)), &clock);
+
+        assert_eq!(report.elapsed, ms(1000));
+        assert_eq!(report.exchanges, 100);
+        assert_eq!(report.errors, 0);
+        assert_eq!(report.error_rate(), 0.0);
+        assert_eq!(report.last_error, None);
+        assert!(report.stalls.is_empty());
+        let ends: Vec<_> = report.snapshots.iter().map(|s| s.at).collect();
+        assert_eq!(ends, [ms(250), ms(500), ms(750), ms(1000)]);
+        assert!(report.snapshots.iter().all(|s| s.exchanges == 25));
+    }
+
+    #[test]
+    fn reports_unrecovered_stall() {
+        let bridge = RealFlightLocalBridge::stub(StubSoapClient::new(vec![
+            "return-data-200".to_string(),
+            "return-data-500".to_string(),
+        ]));
+        let clock = ManualClock::new();
+
+        let report = soak_test_with_clock(&bridge, config(ms(1000), 10.0, ms(1000)), &clock);
+
+        assert_eq!(report.exchanges, 10);
+        assert_eq!(report.errors, 9);
+        assert_eq!(report.error_rate(), 0.9);
+        assert!(report.last_error.is_some());
+        assert_eq!(
+            report.stalls,
+            [Stall {
+                at: Duration::ZERO,
+                length: ms(1000)
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_recovered_stall() {
+        let clock = ManualClock::new();
+        let bridge = FlakyBridge::new(&clock, ms(200)..ms(900));
+
+        let report = soak_test_with_clock(&bridge, config(ms(2000), 10.0, ms(1000)), &clock);
+
+        assert_eq!(report.exchanges, 20);
+        assert_eq!(report.errors, 7);
+        assert_eq!(
+            report.stalls,
+            [Stall {
+                at: ms(100),
+                length: ms(800)
+            }]
+        );
+        let errors: Vec<_> = report.snapshots.iter().map(|s| s.errors).collect();
+        assert_eq!(errors, [7, 0]);
+    }
+
+    #[test]
+    fn ignores_gap_below_threshold() {
+        let clock = ManualClock::new();
+        let bridge = FlakyBridge::new(&clock, ms(200)..ms(500));
+
+        let report = soak_test_with_clock(&bridge, config(ms(1000), 10.0, ms(1000)), &clock);
+
+        assert_eq!(report.errors, 3);
+        assert!(report.stalls.is_empty());
+    }
+
+    #[test]
+    fn counts_latency_and_overruns() {
+        let clock = ManualClock::new();
+        let mut bridge = FlakyBridge::new(&clock, Duration::ZERO..Duration::ZERO);
+        bridge.latency = ms(150);
+
+        let report = soak_test_with_clock(&bridge, config(ms(600), 10.0, ms(1000)), &clock);
+
+        assert_eq!(report.exchanges, 4);
+        let snapshot = &report.snapshots[0];
+        assert_eq!(snapshot.at, ms(600));
+        assert_eq!(snapshot.mean_latency, ms(150));
+        assert_eq!(snapshot.max_latency, ms(150));
+        assert_eq!(snapshot.overruns, 4);
+    }
+
+    #[test]
+    fn serializes_report() {
+        let clock = ManualClock::new();
+        let bridge = FlakyBridge::new(&clock, ms(0)..ms(100));
+
+        let report = soak_test_with_clock(&bridge, config(ms(300), 10.0, ms(100)), &clock);
+        let encoded = postcard::to_allocvec(&report).unwrap();
+
+        assert_eq!(
+            postcard::from_bytes::<SoakReport>(&encoded).unwrap(),
+            report
+        );
+    }
+
+    #[test]
+    fn runs_on_system_clock() {
+        let bridge =
+            RealFlightLocalBridge::stub(StubSoapClient::new(vec!["return-data-200".to_string()]));
+
+        let report = soak_test(&bridge, config(ms(50), 200.0, ms(20)));
+
+        assert!(report.elapsed >= ms(50));
+        assert!(report.exchanges > 0);
+        assert!(!report.snapshots.is_empty());
+        let counted: u64 = report.snapshots.iter().map(|s| s.exchanges).sum();
+        assert_eq!(counted, report.exchanges);
+    }
+
+    #[test]
+    #[should_panic(expected = "rate_hz must be positive")]
+    fn rejects_zero_rate() {
+        let clock = ManualClock::new();
+        let bridge = FlakyBridge::new(&clock, Duration::ZERO..Duration::ZERO);
+
+        soak_test_with_clock(&bridge, config(ms(100), 0.0, ms(100)), &clock);
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    #[tokio::test]
+    async fn async_soak_matches_sync() {
+        let clock = ManualClock::new();
+        let bridge = FlakyBridge::new(&clock, ms(200)..ms(900));
+        let sync = soak_test_with_clock(&bridge, config(ms(2000), 10.0, ms(1000)), &clock);
+
+        let clock = ManualClock::new();
+        let bridge = FlakyBridge::new(&clock, ms(200)..ms(900));
+        let report =
+            soak_test_async_with_clock(&bridge, config(ms(2000), 10.0, ms(1000)), &clock).await;
+
+        assert_eq!(report, sync);
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn async_runs_on_system_clock() {
+        let clock = ManualClock::new();
+        let bridge = FlakyBridge::new(&clock, Duration::ZERO..Duration::ZERO);
+
+        let report = soak_test_async(&bridge, config(ms(1000), 10.0, ms(500))).await;
+
+        assert_eq!(report.exchanges, 10);
+        assert_eq!(report.snapshots.len(), 2);
+    }
+
+    mod physics_watchdog_tests {
+        use super::*;
+        use crate::decoders::to_time;
+        use std::sync::{Arc, Mutex};
+
+        fn state(physics_time: f32) -> SimulatorState {
+            SimulatorState {
+                current_physics_time: to_time(physics_time),
+                ..Default::default()
+            }
+        }
+
+        /// Feeds one state per 10 ms of `clock` with these physics times.
+        fn feed_all(
+            watchdog: &mut PhysicsWatchdog<ManualClock>,
+            times: &[f32],
+        ) -> Vec<PhysicsHealth> {
+            times
+                .iter()
+                .map(|&time| {
+                    watchdog.clock().advance(ms(10));
+                    watchdog.feed(&state(time))
+                })
+                .collect()
+        }
+
+        fn watchdog() -> PhysicsWatchdog<ManualClock> {
+            PhysicsWatchdog::with_clock(
+                WatchdogConfig {
+                    stall_after: ms(30),
+                    max_step: ms(100),
+                },
+                ManualClock::new(),
+            )
+        }
+
+        #[test]
+        fn advancing_time_is_ok() {
+            let mut watchdog = watchdog();
+
+            let health = feed_all(&mut watchdog, &[0.0, 0.01, 0.02, 0.03, 0.05]);
+
+            assert!(health.iter().all(PhysicsHealth::is_ok), "{:?}", health);
+            assert_eq!(watchdog.status(), PhysicsHealth::Ok);
+        }
+
+        #[test]
+        fn reports_stall_after_threshold() {
+            let mut watchdog = watchdog();
+
+            let health = feed_all(&mut watchdog, &[1.0, 1.01, 1.01, 1.01, 1.01, 1.01]);
+
+            assert_eq!(
+                health[2..],
+                [
+                    PhysicsHealth::Ok,
+                    PhysicsHealth::Ok,
+                    PhysicsHealth::Stalled {
+                        stalled_for: ms(30)
+                    },
+                    PhysicsHealth::Stalled {
+                        stalled_for: ms(40)
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn recovers_when_time_advances() {
+            let mut watchdog = watchdog();
+            feed_all(&mut watchdog, &[1.0, 1.0, 1.0, 1.0, 1.0]);
+            assert!(!watchdog.status().is_ok());
+
+            feed_all(&mut watchdog, &[1.01]);
+
+            assert_eq!(watchdog.status(), PhysicsHealth::Ok);
+        }
+
+        #[test]
+        fn reports_forward_jump() {
+            let mut watchdog = watchdog();
+
+            let health = feed_all(&mut watchdog, &[1.0, 1.01, 1.5, 1.51]);
+
+            assert_eq!(health[1], PhysicsHealth::Ok);
+            match health[2] {
+                PhysicsHealth::Jumped { by } => assert!((by - 0.49).abs() < 1e-4, "{}", by),
+                other => panic!("expected jump, got {:?}", other),
+            }
+            assert_eq!(health[3], PhysicsHealth::Ok);
+        }
+
+        #[test]
+        fn step_within_max_is_ok() {
+            let mut watchdog = watchdog();
+
+            let health = feed_all(&mut watchdog, &[1.0, 1.0625]);
+
+            assert_eq!(health[1], PhysicsHealth::Ok);
+        }
+
+        #[test]
+        fn reports_backward_jump_on_reset() {
+            let mut watchdog = watchdog();
+
+            let health = feed_all(&mut watchdog, &[12.5, 12.51, 0.0, 0.01, 0.02]);
+
+            assert_eq!(health[2], PhysicsHealth::Jumped { by: -12.51 });
+            // Carries on from the new time base
+            assert!(health[3..].iter().all(PhysicsHealth::is_ok), "{:?}", health);
+        }
+
+        #[test]
+        fn calls_back_on_changes_and_jumps() {
+            let seen = Arc::new(Mutex::new(Vec::new()));
+            let recorded = seen.clone();
+            let mut watchdog =
+                watchdog().with_callback(move |health| recorded.lock().unwrap().push(*health));
+
+            feed_all(
+                &mut watchdog,
+                &[5.0, 5.0, 5.0, 5.0, 5.0, 5.01, 0.0, 10.0, 10.01],
+            );
+
+            assert_eq!(
+                *seen.lock().unwrap(),
+                [
+                    PhysicsHealth::Stalled {
+                        stalled_for: ms(30)
+                    },
+                    PhysicsHealth::Ok,
+                    PhysicsHealth::Jumped { by: -5.01 },
+                    PhysicsHealth::Jumped { by: 10.0 },
+                    PhysicsHealth::Ok,
+                ]
+            );
+        }
+
+        #[test]
+        fn reset_forgets_last_time() {
+            let mut watchdog = watchdog();
+            feed_all(&mut watchdog, &[5.0]);
+
+            watchdog.reset();
+
+            assert_eq!(feed_all(&mut watchdog, &[0.0]), [PhysicsHealth::Ok]);
+        }
+    }
+}
@@ -1,10 +1,15 @@
 //! Encoding functions for RealFlight simulator protocol.
 
-use std::fmt::Write;
+use std::fmt::{Display, Write};
 
-use crate::ControlInputs;
+use crate::{ControlInputs, SimulatorState};
+
+pub(crate) mod units;
 
 const CONTROL_INPUTS_CAPACITY: usize = 291;
+const SIMULATOR_STATE_CAPACITY: usize = 2048;
+/// Selects all 12 channels
+const ALL_CHANNELS: u16 = 4095;
 
 /// Encodes control inputs into XML format for the RealFlight simulator.
 #[cfg(any(test, feature = "bench-internals"))]
@@ -18,11 +23,26 @@ pub(crate) fn encode_control_inputs(inputs: &ControlInputs) -> String {
     encode_control_inputs_inner(inputs)
 }
 
+/// Encodes inputs that select no channels, so the simulator ignores their values.
+///
+/// Used to probe RealFlight Link without affecting the aircraft.
+pub(crate) fn encode_idle_inputs() -> String {
+    encode_with_mask(&ControlInputs::default(), 0)
+}
+
 fn encode_control_inputs_inner(inputs: &ControlInputs) -> String {
+    encode_with_mask(inputs, ALL_CHANNELS)
+}
+
+fn encode_with_mask(inputs: &ControlInputs, selected_channels: u16) -> String {
     let mut message = String::with_capacity(CONTROL_INPUTS_CAPACITY);
 
     message.push_str("<pControlInputs>");
-    message.push_str("<m-selectedChannels>4095</m-selectedChannels>");
+    let _ = write!(
+        message,
+        "<m-selectedChannels>{}</m-selectedChannels>",
+        selected_channels
+    );
     message.push_str("<m-channelValues-0to1>");
     for num in inputs.channels.iter() {
         let _ = write!(message, "<item>{}</item>", num);
@@ -33,6 +53,154 @@ fn encode_control_inputs_inner(inputs: &ControlInputs) -> String {
     message
 }
 
+/// Encodes a state as the `ReturnData` element RealFlight answers
+/// `ExchangeData` with, in the units it reports, so that
+/// [decode_simulator_state](crate::decode_simulator_state) reads it back.
+pub(crate) fn encode_simulator_state(state: &SimulatorState) -> String {
+    let mut message = String::with_capacity(SIMULATOR_STATE_CAPACITY);
+
+    message.push_str("<ReturnData><m-previousInputsState>");
+    message.push_str("<m-selectedChannels>-1</m-selectedChannels>");
+    message.push_str("<m-channelValues-0to1>");
+    for num in state.previous_inputs.channels.iter() {
+        let _ = write!(message, "<item>{}</item>", num);
+    }
+    message.push_str("</m-channelValues-0to1>");
+    message.push_str("</m-previousInputsState><m-aircraftState>");
+    let fields: [(&str, &dyn Display); 45] = [
+        (
+            "m-currentPhysicsTime-SEC",
+            &units::time(state.current_physics_time),
+        ),
+        (
+            "m-currentPhysicsSpeedMultiplier",
+            &state.current_physics_speed_multiplier,
+        ),
+        ("m-airspeed-MPS", &units::velocity(state.airspeed)),
+        ("m-altitudeASL-MTR", &units::length(state.altitude_asl)),
+        ("m-altitudeAGL-MTR", &units::length(state.altitude_agl)),
+        ("m-groundspeed-MPS", &units::velocity(state.groundspeed)),
+        (
+            "m-pitchRate-DEGpSEC",
+            &units::angular_velocity(state.pitch_rate),
+        ),
+        (
+            "m-rollRate-DEGpSEC",
+            &units::angular_velocity(state.roll_rate),
+        ),
+        (
+            "m-yawRate-DEGpSEC",
+            &units::angular_velocity(state.yaw_rate),
+        ),
+        ("m-azimuth-DEG", &units::angle(state.azimuth)),
+        ("m-inclination-DEG", &units::angle(state.inclination)),
+        ("m-roll-DEG", &units::angle(state.roll)),
+        ("m-orientationQuaternion-X", &state.orientation_quaternion_x),
+        ("m-orientationQuaternion-Y", &state.orientation_quaternion_y),
+        ("m-orientationQuaternion-Z", &state.orientation_quaternion_z),
+        ("m-orientationQuaternion-W", &state.orientation_quaternion_w),
+        (
+            "m-aircraftPositionX-MTR",
+            &units::length(state.aircraft_position_x),
+        ),
+        (
+            "m-aircraftPositionY-MTR",
+            &units::length(state.aircraft_position_y),
+        ),
+        (
+            "m-velocityWorldU-MPS",
+            &units::velocity(state.velocity_world_u),
+        ),
+        (
+            "m-velocityWorldV-MPS",
+            &units::velocity(state.velocity_world_v),
+        ),
+        (
+            "m-velocityWorldW-MPS",
+            &units::velocity(state.velocity_world_w),
+        ),
+        (
+            "m-velocityBodyU-MPS",
+            &units::velocity(state.velocity_body_u),
+        ),
+        (
+            "m-velocityBodyV-MPS",
+            &units::velocity(state.velocity_body_v),
+        ),
+        (
+            "m-velocityBodyW-MPS",
+            &units::velocity(state.velocity_body_w),
+        ),
+        (
+            "m-accelerationWorldAX-MPS2",
+            &units::acceleration(state.acceleration_world_ax),
+        ),
+        (
+            "m-accelerationWorldAY-MPS2",
+            &units::acceleration(state.acceleration_world_ay),
+        ),
+        (
+            "m-accelerationWorldAZ-MPS2",
+            &units::acceleration(state.acceleration_world_az),
+        ),
+        (
+            "m-accelerationBodyAX-MPS2",
+            &units::acceleration(state.acceleration_body_ax),
+        ),
+        (
+            "m-accelerationBodyAY-MPS2",
+            &units::acceleration(state.acceleration_body_ay),
+        ),
+        (
+            "m-accelerationBodyAZ-MPS2",
+            &units::acceleration(state.acceleration_body_az),
+        ),
+        ("m-windX-MPS", &units::velocity(state.wind_x)),
+        ("m-windY-MPS", &units::velocity(state.wind_y)),
+        ("m-windZ-MPS", &units::velocity(state.wind_z)),
+        ("m-propRPM", &state.prop_rpm),
+        ("m-heliMainRotorRPM", &state.heli_main_rotor_rpm),
+        (
+            "m-batteryVoltage-VOLTS",
+            &units::electric_potential(state.battery_voltage),
+        ),
+        (
+            "m-batteryCurrentDraw-AMPS",
+            &units::electric_current(state.battery_current_draw),
+        ),
+        (
+            "m-batteryRemainingCapacity-MAH",
+            &units::electric_charge(state.battery_remaining_capacity),
+        ),
+        ("m-fuelRemaining-OZ", &units::volume(state.fuel_remaining)),
+        ("m-isLocked", &state.is_locked),
+        ("m-hasLostComponents", &state.has_lost_components),
+        ("m-anEngineIsRunning", &state.an_engine_is_running),
+        ("m-isTouchingGround", &state.is_touching_ground),
+        (
+            "m-flightAxisControllerIsActive",
+            &state.flight_axis_controller_is_active,
+        ),
+        ("m-currentAircraftStatus", &state.current_aircraft_status),
+    ];
+    for (name, value) in fields {
+        element(&mut message, name, value);
+    }
+    message.push_str("</m-aircraftState><m-notifications>");
+    element(
+        &mut message,
+        "m-resetButtonHasBeenPressed",
+        state.reset_button_has_been_pressed,
+    );
+    message.push_str("</m-notifications></ReturnData>");
+
+    message
+}
+
+fn element(message: &mut String, name: &str, value: impl Display) {
+    let _ = write!(message, "<{0}>{1}</{0}>", name, value);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,4 +257,26 @@ mod tests {
         assert!(encoded.contains("<item>0</item>"));
         assert!(encoded.contains("<item>1</item>"));
     }
+
+    #[test]
+    fn encode_idle_selects_no_channels() {
+        let encoded = encode_idle_inputs();
+
+        assert!(encoded.contains("<m-selectedChannels>0</m-selectedChannels>"));
+        assert_eq!(encoded.matches("<item>").count(), 12);
+    }
+
+    #[test]
+    fn encoded_state_decodes_to_same_state() {
+        let state = crate::decode_simulator_state(include_str!(
+            "../../testdata/responses/return-data-200.xml"
+        ))
+        .unwrap();
+
+        let encoded = encode_simulator_state(&state);
+
+        assert!(encoded.starts_with("<ReturnData>"));
+        assert!(encoded.contains("<m-azimuth-DEG>-89.607056</m-azimuth-DEG>"));
+        assert_eq!(crate::decode_simulator_state(&encoded).unwrap(), state);
+    }
 }
@@ -0,0 +1,103 @@
+//! Plain values of [SimulatorState](crate::SimulatorState) quantities in the
+//! units RealFlight reports, undoing the conversions made when decoding.
+
+use crate::unit_types::*;
+
+#[cfg(feature = "uom")]
+use crate::decoders::OUNCES_PER_LITER;
+#[cfg(feature = "uom")]
+use uom::si::{
+    acceleration::meter_per_second_squared, angle::degree, angular_velocity::degree_per_second,
+    electric_charge::milliampere_hour, electric_current::ampere, electric_potential::volt,
+    length::meter, time::second, velocity::meter_per_second, volume::liter,
+};
+
+#[cfg(feature = "uom")]
+pub(crate) fn velocity(v: Velocity) -> f32 {
+    v.get::<meter_per_second>()
+}
+#[cfg(not(feature = "uom"))]
+pub(crate) fn velocity(v: Velocity) -> f32 {
+    v
+}
+
+#[cfg(feature = "uom")]
+pub(crate) fn length(v: Length) -> f32 {
+    v.get::<meter>()
+}
+#[cfg(not(feature = "uom"))]
+pub(crate) fn length(v: Length) -> f32 {
+    v
+}
+
+#[cfg(feature = "uom")]
+pub(crate) fn angular_velocity(v: AngularVelocity) -> f32 {
+    v.get::<degree_per_second>()
+}
+#[cfg(not(feature = "uom"))]
+pub(crate) fn angular_velocity(v: AngularVelocity) -> f32 {
+    v
+}
+
+#[cfg(feature = "uom")]
+pub(crate) fn angle(v: Angle) -> f32 {
+    v.get::<degree>()
+}
+#[cfg(not(feature = "uom"))]
+pub(crate) fn angle(v: Angle) -> f32 {
+    v
+}
+
+#[cfg(feature = "uom")]
+pub(crate) fn acceleration(v: Acceleration) -> f32 {
+    v.get::<meter_per_second_squared>()
+}
+#[cfg(not(feature = "uom"))]
+pub(crate) fn acceleration(v: Acceleration) -> f32 {
+    v
+}
+
+#[cfg(feature = "uom")]
+pub(crate) fn electric_potential(v: ElectricPotential) -> f32 {
+    v.get::<volt>()
+}
+#[cfg(not(feature = "uom"))]
+pub(crate) fn electric_potential(v: ElectricPotential) -> f32 {
+    v
+}
+
+#[cfg(feature = "uom")]
+pub(crate) fn electric_current(v: ElectricCurrent) -> f32 {
+    v.get::<ampere>()
+}
+#[cfg(not(feature = "uom"))]
+pub(crate) fn electric_current(v: ElectricCurrent) -> f32 {
+    v
+}
+
+#[cfg(feature = "uom")]
+pub(crate) fn electric_charge(v: ElectricCharge) -> f32 {
+    v.get::<milliampere_hour>()
+}
+#[cfg(not(feature = "uom"))]
+pub(crate) fn electric_charge(v: ElectricCharge) -> f32 {
+    v
+}
+
+#[cfg(feature = "uom")]
+pub(crate) fn volume(v: Volume) -> f32 {
+    v.get::<liter>() * OUNCES_PER_LITER
+}
+#[cfg(not(feature = "uom"))]
+pub(crate) fn volume(v: Volume) -> f32 {
+    v
+}
+
+#[cfg(feature = "uom")]
+pub(crate) fn time(v: Time) -> f32 {
+    v.get::<second>()
+}
+#[cfg(not(feature = "uom"))]
+pub(crate) fn time(v: Time) -> f32 {
+    v
+}
@@ -0,0 +1,497 @@
+//! Predicting the aircraft's motion between exchanges.
+//!
+//! An estimator or controller that runs faster than `exchange_data` can
+//! return needs states between responses. [StateExtrapolator] keeps the last
+//! two states and predicts position, velocity and orientation at any physics
+//! time from them: position and velocity with the constant acceleration the
+//! two velocities imply, and orientation by integrating the body rates into
+//! the orientation quaternion.
+//!
+//! ```no_run
+//! use realflight_bridge::estimation::StateExtrapolator;
+//! use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bridge = RealFlightLocalBridge::new()?;
+//! let mut extrapolator = StateExtrapolator::new();
+//! extrapolator.update(&bridge.exchange_data(&ControlInputs::default())?);
+//! extrapolator.update(&bridge.exchange_data(&ControlInputs::default())?);
+//!
+//! // Four estimator steps at 1 kHz before the next response
+//! for step in 1..=4 {
+//!     let prediction = extrapolator.predict(Duration::from_millis(step)).unwrap();
+//!     println!("altitude {:.2} m", prediction.altitude_asl);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use crate::SimulatorState;
+use crate::encoders::units;
+
+/// How far past the latest state a [StateExtrapolator] predicts before
+/// flagging its predictions, by default.
+pub const DEFAULT_HORIZON: Duration = Duration::from_millis(20);
+
+/// Motion of the aircraft predicted by a [StateExtrapolator], in the units
+/// and frames of [SimulatorState].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Prediction {
+    /// Physics time the prediction is for (s)
+    pub physics_time: f32,
+    /// As `aircraft_position_x` (m)
+    pub position_x: f32,
+    /// As `aircraft_position_y` (m)
+    pub position_y: f32,
+    /// As `altitude_asl` (m)
+    pub altitude_asl: f32,
+    /// As `velocity_world_u` [meters/second]
+    pub velocity_world_u: f32,
+    /// As `velocity_world_v` [meters/second]
+    pub velocity_world_v: f32,
+    /// As `velocity_world_w`, positive down [meters/second]
+    pub velocity_world_w: f32,
+    /// Orientation quaternion `[x, y, z, w]`, normalized
+    pub orientation: [f32; 4],
+    /// Whether the prediction is further than the horizon from the latest
+    /// state, and should not be trusted
+    pub beyond_horizon: bool,
+}
+
+/// Predicts motion between and beyond the states it is fed.
+///
+/// Feed it every state received with [update](Self::update). With two states,
+/// the difference between their world velocities and between their body
+/// rates gives constant linear and angular accelerations; with one, both are
+/// taken as zero. Orientation is integrated in the body frame, so it is exact
+/// for rotation about a fixed body axis with constant angular acceleration.
+///
+/// Predictions further than the [horizon](Self::with_horizon) from the
+/// latest state have [Prediction::beyond_horizon] set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateExtrapolator {
+    horizon: Duration,
+    previous: Option<Sample>,
+    latest: Option<Sample>,
+}
+
+/// What a [StateExtrapolator] keeps of a state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Sample {
+    /// [seconds]
+    time: f32,
+    /// x, y and altitude above sea level [meters]
+    position: [f32; 3],
+    /// World u, v and w [meters/second]
+    velocity: [f32; 3],
+    /// Roll, pitch and yaw rates [radians/second]
+    rates: [f32; 3],
+    /// `[x, y, z, w]`
+    orientation: [f32; 4],
+}
+
+impl Sample {
+    fn new(state: &SimulatorState) -> Sample {
+        Sample {
+            time: units::time(state.current_physics_time),
+            position: [
+                units::length(state.aircraft_position_x),
+                units::length(state.aircraft_position_y),
+                units::length(state.altitude_asl),
+            ],
+            velocity: [
+                units::velocity(state.velocity_world_u),
+                units::velocity(state.velocity_world_v),
+                units::velocity(state.velocity_world_w),
+            ],
+            rates: [
+                units::angular_velocity(state.roll_rate).to_radians(),
+                units::angular_velocity(state.pitch_rate).to_radians(),
+                units::angular_velocity(state.yaw_rate).to_radians(),
+            ],
+            orientation: [
+                state.orientation_quaternion_x,
+                state.orientation_quaternion_y,
+                state.orientation_quaternion_z,
+                state.orientation_quaternion_w,
+            ],
+        }
+    }
+}
+
+impl Default for StateExtrapolator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StateExtrapolator {
+    /// Extrapolator with the [DEFAULT_HORIZON] and no states.
+    pub fn new() -> Self {
+        StateExtrapolator {
+            horizon: DEFAULT_HORIZON,
+            previous: None,
+            latest: None,
+        }
+    }
+
+    /// Flags predictions further than `horizon` from the latest state
+    /// instead of [DEFAULT_HORIZON].
+    pub fn with_horizon(mut self, horizon: Duration) -> Self {
+        self.horizon = horizon;
+        self
+    }
+
+    /// How far from the latest state predictions are trusted.
+    pub fn horizon(&self) -> Duration {
+        self.horizon
+    }
+
+    /// Physics time of the latest state, in seconds.
+    pub fn latest_time(&self) -> Option<f32> {
+        self.latest.map(|sample| sample.time)
+    }
+
+    /// Forgets the states fed so far.
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.latest = None;
+    }
+
+    /// Takes `state` as the latest.
+    ///
+    /// A state at the same physics time as the latest replaces it, and one
+    /// from before it, as after a reset, starts over from that state alone.
+    pub fn update(&mut self, state: &SimulatorState) {
+        let sample = Sample::new(state);
+        match self.latest {
+            Some(latest) if sample.time > latest.time => self.previous = Some(latest),
+            Some(latest) if sample.time == latest.time => {}
+            _ => self.previous = None,
+        }
+        self.latest = Some(sample);
+    }
+
+    /// Prediction `offset` past the latest state, or `None` before the first.
+    pub fn predict(&self, offset: Duration) -> Option<Prediction> {
+        let latest = self.latest?;
+        Some(self.extrapolate(&latest, offset.as_secs_f32()))
+    }
+
+    /// Prediction at `physics_time` seconds, before or after the latest state,
+    /// or `None` before the first.
+    ///
+    /// Between the last two states this interpolates along the same constant
+    /// acceleration.
+    pub fn predict_at(&self, physics_time: f32) -> Option<Prediction> {
+        let latest = self.latest?;
+        Some(self.extrapolate(&latest, physics_time - latest.time))
+    }
+
+    fn extrapolate(&self, latest: &Sample, dt: f32) -> Prediction {
+        let (acceleration, angular_acceleration) = match self.previous {
+            Some(previous) => {
+                let span = latest.time - previous.time;
+                (
+                    sub(latest.velocity, previous.velocity).map(|dv| dv / span),
+                    sub(latest.rates, previous.rates).map(|dr| dr / span),
+                )
+            }
+            None => ([0.0; 3], [0.0; 3]),
+        };
+
+        let velocity: [f32; 3] = std::array::from_fn(|i| latest.velocity[i] + acceleration[i] * dt);
+        let displacement: [f32; 3] =
+            std::array::from_fn(|i| latest.velocity[i] * dt + 0.5 * acceleration[i] * dt * dt);
+        // The mean rate over the step turns a fixed axis by the exact angle
+        let rates: [f32; 3] =
+            std::array::from_fn(|i| latest.rates[i] + 0.5 * angular_acceleration[i] * dt);
+
+        Prediction {
+            physics_time: latest.time + dt,
+            position_x: latest.position[0] + displacement[0],
+            position_y: latest.position[1] + displacement[1],
+            // w points down
+            altitude_asl: latest.position[2] - displacement[2],
+            velocity_world_u: velocity[0],
+            velocity_world_v: velocity[1],
+            velocity_world_w: velocity[2],
+            orientation: rotate(latest.orientation, rates, dt),
+            beyond_horizon: dt.abs() > self.horizon.as_secs_f32(),
+        }
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|i| a[i] - b[i])
+}
+
+/// Turns `q` by body `rates` [radians/second] held for `dt` seconds.
+fn rotate(q: [f32; 4], rates: [f32; 3], dt: f32) -> [f32; 4] {
+    let norm = rates.iter().map(|r| r * r).sum::<f32>().sqrt();
+    let angle = norm * dt;
+    if angle == 0.0 {
+        return normalize(q);
+    }
+    let (sin, cos) = (angle / 2.0).sin_cos();
+    let step = [
+        rates[0] / norm * sin,
+        rates[1] / norm * sin,
+        rates[2] / norm * sin,
+        cos,
+    ];
+    normalize(multiply(q, step))
+}
+
+/// Hamilton product `a * b` of `[x, y, z, w]` quaternions.
+fn multiply(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let [ax, ay, az, aw] = a;
+    let [bx, by, bz, bw] = b;
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
+
+fn normalize(q: [f32; 4]) -> [f32; 4] {
+    let norm = q.iter().map(|c| c * c).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return q;
+    }
+    q.map(|c| c / norm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoders::{to_angular_velocity, to_length, to_time, to_velocity};
+    use approx::assert_relative_eq;
+
+    /// Motion with constant world acceleration and constant angular
+    /// acceleration about a fixed body axis, from which states at any time
+    /// are computed exactly.
+    struct Trajectory {
+        position: [f32; 3],
+        velocity: [f32; 3],
+        acceleration: [f32; 3],
+        orientation: [f32; 4],
+        /// Unit body axis
+        axis: [f32; 3],
+        /// [degrees/second]
+        rate: f32,
+        /// [degrees/second²]
+        angular_acceleration: f32,
+    }
+
+    impl Trajectory {
+        fn climbing_roll() -> Self {
+            let norm = (1.0f32 + 4.0 + 9.0).sqrt();
+            Trajectory {
+                position: [120.0, -35.0, 250.0],
+                velocity: [18.0, -4.0, -2.5],
+                acceleration: [1.5, 0.8, -3.0],
+                orientation: normalize([0.1, -0.2, 0.3, 0.9]),
+                axis: [1.0 / norm, 2.0 / norm, 3.0 / norm],
+                rate: 120.0,
+                angular_acceleration: 400.0,
+            }
+        }
+
+        /// Exact state `t` seconds in, at physics time `10 + t`.
+        fn state(&self, t: f32) -> SimulatorState {
+            let at = |i: usize| {
+                self.position[i] + self.velocity[i] * t + 0.5 * self.acceleration[i] * t * t
+            };
+            let speed = |i: usize| self.velocity[i] + self.acceleration[i] * t;
+            let rate = self.rate + self.angular_acceleration * t;
+            let angle = (self.rate * t + 0.5 * self.angular_acceleration * t * t).to_radians();
+            let (sin, cos) = (angle / 2.0).sin_cos();
+            let turn = [
+                self.axis[0] * sin,
+                self.axis[1] * sin,
+                self.axis[2] * sin,
+                cos,
+            ];
+            let [x, y, z, w] = multiply(self.orientation, turn);
+
+            SimulatorState {
+                current_physics_time: to_time(10.0 + t),
+                aircraft_position_x: to_length(at(0)),
+                aircraft_position_y: to_length(at(1)),
+                // Altitude rises as w, positive down, falls
+                altitude_asl: to_length(2.0 * self.position[2] - at(2)),
+                velocity_world_u: to_velocity(speed(0)),
+                velocity_world_v: to_velocity(speed(1)),
+                velocity_world_w: to_velocity(speed(2)),
+                roll_rate: to_angular_velocity(rate * self.axis[0]),
+                pitch_rate: to_angular_velocity(rate * self.axis[1]),
+                yaw_rate: to_angular_velocity(rate * self.axis[2]),
+                orientation_quaternion_x: x,
+                orientation_quaternion_y: y,
+                orientation_quaternion_z: z,
+                orientation_quaternion_w: w,
+                ..Default::default()
+            }
+        }
+    }
+
+    fn assert_matches(prediction: &Prediction, state: &SimulatorState) {
+        let expected = Sample::new(state);
+        assert_relative_eq!(prediction.physics_time, expected.time, max_relative = 1e-6);
+        assert_relative_eq!(
+            prediction.position_x,
+            expected.position[0],
+            max_relative = 1e-5
+        );
+        assert_relative_eq!(
+            prediction.position_y,
+            expected.position[1],
+            max_relative = 1e-5
+        );
+        assert_relative_eq!(
+            prediction.altitude_asl,
+            expected.position[2],
+            max_relative = 1e-5
+        );
+        let velocity = [
+            prediction.velocity_world_u,
+            prediction.velocity_world_v,
+            prediction.velocity_world_w,
+        ];
+        for (predicted, expected) in velocity.iter().zip(expected.velocity) {
+            assert_relative_eq!(*predicted, expected, epsilon = 1e-4);
+        }
+        for (predicted, expected) in prediction.orientation.iter().zip(expected.orientation) {
+            assert_relative_eq!(*predicted, expected, epsilon = 1e-5);
+        }
+    }
+
+    /// Extrapolator fed the trajectory at `times`.
+    fn fed(trajectory: &Trajectory, times: &[f32]) -> StateExtrapolator {
+        let mut extrapolator = StateExtrapolator::new();
+        for &t in times {
+            extrapolator.update(&trajectory.state(t));
+        }
+        extrapolator
+    }
+
+    #[test]
+    fn extrapolates_5_ms_exactly() {
+        let trajectory = Trajectory::climbing_roll();
+        // Responses 1/300 of a second apart
+        let extrapolator = fed(&trajectory, &[0.0, 1.0 / 300.0]);
+
+        let prediction = extrapolator.predict(Duration::from_millis(5)).unwrap();
+
+        assert_matches(&prediction, &trajectory.state(1.0 / 300.0 + 0.005));
+        assert!(!prediction.beyond_horizon);
+    }
+
+    #[test]
+    fn interpolates_between_states() {
+        let trajectory = Trajectory::climbing_roll();
+        let extrapolator = fed(&trajectory, &[0.0, 0.01]);
+
+        let prediction = extrapolator.predict_at(10.004).unwrap();
+
+        assert_matches(&prediction, &trajectory.state(0.004));
+    }
+
+    #[test]
+    fn turns_heading_at_yaw_rate() {
+        let half = 15.0f32.to_radians() / 2.0;
+        let mut extrapolator = StateExtrapolator::new();
+        extrapolator.update(&SimulatorState {
+            yaw_rate: to_angular_velocity(90.0),
+            orientation_quaternion_z: half.sin(),
+            orientation_quaternion_w: half.cos(),
+            ..Default::default()
+        });
+
+        let prediction = extrapolator.predict(Duration::from_millis(10)).unwrap();
+
+        // 15 degrees plus 0.9 degrees of yaw
+        let half = 15.9f32.to_radians() / 2.0;
+        assert_relative_eq!(prediction.orientation[2], half.sin(), epsilon = 1e-6);
+        assert_relative_eq!(prediction.orientation[3], half.cos(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn holds_velocity_with_one_state() {
+        let mut extrapolator = StateExtrapolator::new();
+        extrapolator.update(&SimulatorState {
+            aircraft_position_x: to_length(5.0),
+            altitude_asl: to_length(100.0),
+            velocity_world_u: to_velocity(20.0),
+            velocity_world_w: to_velocity(-2.0),
+            orientation_quaternion_w: 1.0,
+            ..Default::default()
+        });
+
+        let prediction = extrapolator.predict(Duration::from_millis(10)).unwrap();
+
+        assert_relative_eq!(prediction.position_x, 5.2);
+        assert_relative_eq!(prediction.altitude_asl, 100.02);
+        assert_eq!(prediction.velocity_world_u, 20.0);
+        assert_eq!(prediction.orientation, [0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn flags_predictions_beyond_horizon() {
+        let trajectory = Trajectory::climbing_roll();
+        let extrapolator = fed(&trajectory, &[0.0, 0.01]).with_horizon(Duration::from_millis(8));
+
+        assert!(
+            !extrapolator
+                .predict(Duration::from_millis(8))
+                .unwrap()
+                .beyond_horizon
+        );
+        assert!(
+            extrapolator
+                .predict(Duration::from_millis(9))
+                .unwrap()
+                .beyond_horizon
+        );
+        assert!(extrapolator.predict_at(9.99).unwrap().beyond_horizon);
+        assert_eq!(extrapolator.horizon(), Duration::from_millis(8));
+    }
+
+    #[test]
+    fn predicts_nothing_before_first_state() {
+        let extrapolator = StateExtrapolator::new();
+
+        assert_eq!(extrapolator.predict(Duration::ZERO), None);
+        assert_eq!(extrapolator.latest_time(), None);
+    }
+
+    #[test]
+    fn starts_over_when_time_goes_back() {
+        let trajectory = Trajectory::climbing_roll();
+        let mut extrapolator = fed(&trajectory, &[0.0, 0.01]);
+
+        // Reset to the first state: no acceleration from before the reset
+        let state = trajectory.state(0.0);
+        extrapolator.update(&state);
+        let prediction = extrapolator.predict(Duration::from_millis(10)).unwrap();
+
+        assert_relative_eq!(prediction.velocity_world_u, 18.0);
+        assert_eq!(extrapolator.latest_time(), Some(10.0));
+    }
+
+    #[test]
+    fn repeated_state_keeps_previous() {
+        let trajectory = Trajectory::climbing_roll();
+        let mut extrapolator = fed(&trajectory, &[0.0, 0.01]);
+
+        extrapolator.update(&trajectory.state(0.01));
+        let prediction = extrapolator.predict(Duration::from_millis(5)).unwrap();
+
+        assert_matches(&prediction, &trajectory.state(0.015));
+    }
+}
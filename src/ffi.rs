@@ -0,0 +1,911 @@
+//! C ABI for [RealFlightLocalBridge], for autopilots written in C or C++.
+//!
+//! The declarations are in `include/realflight_bridge.h`. Functions returning
+//! `int` return [RFB_OK] on success and [RFB_ERROR] on failure; functions
+//! returning a pointer return null on failure. After a failure,
+//! [rfb_last_error_message] describes it. Null arguments are reported as
+//! failures, and a panic inside the library is caught and reported the same
+//! way instead of unwinding into C.
+//!
+//! ```c
+//! #include "realflight_bridge.h"
+//!
+//! RfbBridge *bridge = rfb_bridge_new();
+//! if (!bridge) {
+//!     fprintf(stderr, "%s\n", rfb_last_error_message());
+//!     return 1;
+//! }
+//! float channels[12] = {0.5f, 0.5f, 0.0f, 0.5f};
+//! RfbState state;
+//! rfb_disable_rc(bridge);
+//! if (rfb_exchange_data(bridge, channels, &state) == RFB_OK) {
+//!     printf("altitude %f m\n", state.altitude_asl);
+//! }
+//! rfb_bridge_free(bridge);
+//! ```
+//!
+//! This module requires the `ffi` feature. The crate builds as an `rlib`
+//! only; build the shared library to link against with
+//!
+//! ```sh
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! ```
+
+#![cfg(feature = "ffi")]
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::bridge::local::{Configuration, RealFlightLocalBridge};
+use crate::bridge::remote::RealFlightRemoteBridge;
+use crate::{BridgeError, ControlInputs, RealFlightBridge, SimulatorState, Statistics};
+
+/// Returned by a call that succeeded.
+pub const RFB_OK: c_int = 0;
+/// Returned by a call that failed; see [rfb_last_error_message].
+pub const RFB_ERROR: c_int = -1;
+
+/// Opaque handle to a bridge, created by [rfb_bridge_new],
+/// [rfb_bridge_with_host] or [rfb_remote_bridge_new] and released by
+/// [rfb_bridge_free].
+pub struct RfbBridge {
+    bridge: Handle,
+}
+
+/// Bridge behind an [RfbBridge].
+enum Handle {
+    Local(Box<RealFlightLocalBridge>),
+    Remote(RealFlightRemoteBridge),
+}
+
+impl Handle {
+    fn bridge(&self) -> &dyn RealFlightBridge {
+        match self {
+            Handle::Local(bridge) => bridge,
+            Handle::Remote(bridge) => bridge,
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        match self {
+            Handle::Local(bridge) => bridge.statistics(),
+            Handle::Remote(bridge) => bridge.statistics(),
+        }
+    }
+}
+
+/// [Statistics] as plain values, with durations in seconds.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RfbStatistics {
+    pub runtime: f64,
+    pub error_count: u32,
+    pub frequency: f32,
+    pub request_count: u32,
+    pub cancelled_count: u32,
+    pub pool_wait_total: f64,
+    pub io_total: f64,
+    pub in_flight: u32,
+    pub pool_target: u32,
+    pub pool_miss_rate: f32,
+    pub pool_starvation_count: u32,
+    pub transcript_dropped_count: u32,
+    pub channel_limit_count: u32,
+}
+
+impl From<&Statistics> for RfbStatistics {
+    fn from(statistics: &Statistics) -> Self {
+        RfbStatistics {
+            runtime: statistics.runtime.as_secs_f64(),
+            error_count: statistics.error_count,
+            frequency: statistics.frequency,
+            request_count: statistics.request_count,
+            cancelled_count: statistics.cancelled_count,
+            pool_wait_total: statistics.pool_wait_total.as_secs_f64(),
+            io_total: statistics.io_total.as_secs_f64(),
+            in_flight: statistics.in_flight,
+            pool_target: statistics.pool_target,
+            pool_miss_rate: statistics.pool_miss_rate,
+            pool_starvation_count: statistics.pool_starvation_count,
+            transcript_dropped_count: statistics.transcript_dropped_count,
+            channel_limit_count: statistics.channel_limit_count,
+        }
+    }
+}
+
+/// [SimulatorState] as plain values in SI units, whether or not the `uom`
+/// feature is enabled.
+///
+/// Angles are in radians, angular rates in radians/second, battery capacity
+/// in coulombs and fuel in cubic meters; the other fields keep the units
+/// documented on [SimulatorState]. `current_aircraft_status` is omitted.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RfbState {
+    pub previous_channels: [f32; 12],
+    pub airspeed: f32,
+    pub altitude_asl: f32,
+    pub altitude_agl: f32,
+    pub groundspeed: f32,
+    pub pitch_rate: f32,
+    pub roll_rate: f32,
+    pub yaw_rate: f32,
+    pub azimuth: f32,
+    pub inclination: f32,
+    pub roll: f32,
+    pub aircraft_position_x: f32,
+    pub aircraft_position_y: f32,
+    pub velocity_world_u: f32,
+    pub velocity_world_v: f32,
+    pub velocity_world_w: f32,
+    pub velocity_body_u: f32,
+    pub velocity_body_v: f32,
+    pub velocity_body_w: f32,
+    pub acceleration_world_ax: f32,
+    pub acceleration_world_ay: f32,
+    pub acceleration_world_az: f32,
+    pub acceleration_body_ax: f32,
+    pub acceleration_body_ay: f32,
+    pub acceleration_body_az: f32,
+    pub wind_x: f32,
+    pub wind_y: f32,
+    pub wind_z: f32,
+    pub prop_rpm: f32,
+    pub heli_main_rotor_rpm: f32,
+    pub battery_voltage: f32,
+    pub battery_current_draw: f32,
+    pub battery_remaining_capacity: f32,
+    pub fuel_remaining: f32,
+    pub is_locked: bool,
+    pub has_lost_components: bool,
+    pub an_engine_is_running: bool,
+    pub is_touching_ground: bool,
+    pub current_physics_time: f32,
+    pub current_physics_speed_multiplier: f32,
+    pub orientation_quaternion_x: f32,
+    pub orientation_quaternion_y: f32,
+    pub orientation_quaternion_z: f32,
+    pub orientation_quaternion_w: f32,
+    pub flight_axis_controller_is_active: bool,
+    pub reset_button_has_been_pressed: bool,
+}
+
+impl From<&SimulatorState> for RfbState {
+    fn from(state: &SimulatorState) -> Self {
+        RfbState {
+            previous_channels: state.previous_inputs.channels,
+            airspeed: si::velocity(state.airspeed),
+            altitude_asl: si::length(state.altitude_asl),
+            altitude_agl: si::length(state.altitude_agl),
+            groundspeed: si::velocity(state.groundspeed),
+            pitch_rate: si::angular_velocity(state.pitch_rate),
+            roll_rate: si::angular_velocity(state.roll_rate),
+            yaw_rate: si::angular_velocity(state.yaw_rate),
+            azimuth: si::angle(state.azimuth),
+            inclination: si::angle(state.inclination),
+            roll: si::angle(state.roll),
+            aircraft_position_x: si::length(state.aircraft_position_x),
+            aircraft_position_y: si::length(state.aircraft_position_y),
+            velocity_world_u: si::velocity(state.velocity_world_u),
+            velocity_world_v: si::velocity(state.velocity_world_v),
+            velocity_world_w: si::velocity(state.velocity_world_w),
+            velocity_body_u: si::velocity(state.velocity_body_u),
+            velocity_body_v: si::velocity(state.velocity_body_v),
+            velocity_body_w: si::velocity(state.velocity_body_w),
+            acceleration_world_ax: si::acceleration(state.acceleration_world_ax),
+            acceleration_world_ay: si::acceleration(state.acceleration_world_ay),
+            acceleration_world_az: si::acceleration(state.acceleration_world_az),
+            acceleration_body_ax: si::acceleration(state.acceleration_body_ax),
+            acceleration_body_ay: si::acceleration(state.acceleration_body_ay),
+            acceleration_body_az: si::acceleration(state.acceleration_body_az),
+            wind_x: si::velocity(state.wind_x),
+            wind_y: si::velocity(state.wind_y),
+            wind_z: si::velocity(state.wind_z),
+            prop_rpm: state.prop_rpm,
+            heli_main_rotor_rpm: state.heli_main_rotor_rpm,
+            battery_voltage: si::electric_potential(state.battery_voltage),
+            battery_current_draw: si::electric_current(state.battery_current_draw),
+            battery_remaining_capacity: si::electric_charge(state.battery_remaining_capacity),
+            fuel_remaining: si::volume(state.fuel_remaining),
+            is_locked: state.is_locked,
+            has_lost_components: state.has_lost_components,
+            an_engine_is_running: state.an_engine_is_running,
+            is_touching_ground: state.is_touching_ground,
+            current_physics_time: si::time(state.current_physics_time),
+            current_physics_speed_multiplier: state.current_physics_speed_multiplier,
+            orientation_quaternion_x: state.orientation_quaternion_x,
+            orientation_quaternion_y: state.orientation_quaternion_y,
+            orientation_quaternion_z: state.orientation_quaternion_z,
+            orientation_quaternion_w: state.orientation_quaternion_w,
+            flight_axis_controller_is_active: state.flight_axis_controller_is_active,
+            reset_button_has_been_pressed: state.reset_button_has_been_pressed,
+        }
+    }
+}
+
+/// SI values of [SimulatorState] quantities.
+#[cfg(feature = "uom")]
+mod si {
+    use crate::unit_types::*;
+
+    pub(super) fn velocity(v: Velocity) -> f32 {
+        v.value
+    }
+
+    pub(super) fn length(v: Length) -> f32 {
+        v.value
+    }
+
+    pub(super) fn angular_velocity(v: AngularVelocity) -> f32 {
+        v.value
+    }
+
+    pub(super) fn angle(v: Angle) -> f32 {
+        v.value
+    }
+
+    pub(super) fn acceleration(v: Acceleration) -> f32 {
+        v.value
+    }
+
+    pub(super) fn electric_potential(v: ElectricPotential) -> f32 {
+        v.value
+    }
+
+    pub(super) fn electric_current(v: ElectricCurrent) -> f32 {
+        v.value
+    }
+
+    pub(super) fn electric_charge(v: ElectricCharge) -> f32 {
+        v.value
+    }
+
+    pub(super) fn volume(v: Volume) -> f32 {
+        v.value
+    }
+
+    pub(super) fn time(v: Time) -> f32 {
+        v.value
+    }
+}
+
+/// SI values of [SimulatorState] quantities, converted from the units
+/// RealFlight reports.
+#[cfg(not(feature = "uom"))]
+mod si {
+    /// Coulombs in one milliampere-hour.
+    const COULOMBS_PER_MILLIAMPERE_HOUR: f32 = 3.6;
+    /// US fluid ounces in one cubic meter, matching the decoder's liter factor.
+    const OUNCES_PER_CUBIC_METER: f32 = 33_814.0;
+
+    pub(super) fn velocity(v: f32) -> f32 {
+        v
+    }
+
+    pub(super) fn length(v: f32) -> f32 {
+        v
+    }
+
+    pub(super) fn angular_velocity(v: f32) -> f32 {
+        v.to_radians()
+    }
+
+    pub(super) fn angle(v: f32) -> f32 {
+        v.to_radians()
+    }
+
+    pub(super) fn acceleration(v: f32) -> f32 {
+        v
+    }
+
+    pub(super) fn electric_potential(v: f32) -> f32 {
+        v
+    }
+
+    pub(super) fn electric_current(v: f32) -> f32 {
+        v
+    }
+
+    pub(super) fn electric_charge(v: f32) -> f32 {
+        v * COULOMBS_PER_MILLIAMPERE_HOUR
+    }
+
+    pub(super) fn volume(v: f32) -> f32 {
+        v / OUNCES_PER_CUBIC_METER
+    }
+
+    pub(super) fn time(v: f32) -> f32 {
+        v
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', " ");
+    let message = CString::new(message).expect("nul bytes were replaced");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, recording its error or panic for [rfb_last_error_message] and
+/// returning `failed` in their place.
+fn guard<T>(failed: T, f: impl FnOnce() -> Result<T, String>) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_last_error(message);
+            failed
+        }
+        Err(panic) => {
+            let detail = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown cause");
+            set_last_error(format!("Panic in realflight-bridge: {}", detail));
+            failed
+        }
+    }
+}
+
+fn connect(f: impl FnOnce() -> Result<Handle, BridgeError>) -> *mut RfbBridge {
+    guard(ptr::null_mut(), || {
+        let bridge = f().map_err(|e| e.to_string())?;
+        Ok(Box::into_raw(Box::new(RfbBridge { bridge })))
+    })
+}
+
+/// Reads a nul-terminated argument, recording why it is unusable.
+///
+/// # Safety
+///
+/// `value` must be null or point to a nul-terminated string.
+unsafe fn string_arg(value: *const c_char, name: &str) -> Option<String> {
+    if value.is_null() {
+        set_last_error(format!("{} is null", name));
+        return None;
+    }
+    // SAFETY: the caller passes a nul-terminated string
+    match unsafe { CStr::from_ptr(value) }.to_str() {
+        Ok(value) => Some(value.to_string()),
+        Err(_) => {
+            set_last_error(format!("{} is not valid UTF-8", name));
+            None
+        }
+    }
+}
+
+/// Runs a bridge call for a function returning [RFB_OK] or [RFB_ERROR].
+///
+/// # Safety
+///
+/// `bridge` must be null or a live handle.
+unsafe fn call(
+    bridge: *const RfbBridge,
+    f: impl FnOnce(&dyn RealFlightBridge) -> Result<(), BridgeError>,
+) -> c_int {
+    guard(RFB_ERROR, || {
+        // SAFETY: the caller passes null or a live handle
+        let bridge = unsafe { bridge.as_ref() }.ok_or("bridge is null")?;
+        f(bridge.bridge.bridge()).map_err(|e| e.to_string())?;
+        Ok(RFB_OK)
+    })
+}
+
+/// Connects to RealFlight at the default address, `127.0.0.1:18083`.
+///
+/// Returns null on failure.
+#[unsafe(no_mangle)]
+pub extern "C" fn rfb_bridge_new() -> *mut RfbBridge {
+    connect(|| {
+        RealFlightLocalBridge::with_configuration(&Configuration::default())
+            .map(|bridge| Handle::Local(Box::new(bridge)))
+    })
+}
+
+/// Connects to RealFlight at `host`, a nul-terminated `address:port`.
+///
+/// Returns null on failure.
+///
+/// # Safety
+///
+/// `host` must be null or point to a nul-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rfb_bridge_with_host(host: *const c_char) -> *mut RfbBridge {
+    // SAFETY: forwarded from the caller
+    let Some(host) = (unsafe { string_arg(host, "host") }) else {
+        return ptr::null_mut();
+    };
+    let configuration = Configuration {
+        simulator_host: host,
+        ..Default::default()
+    };
+    connect(|| {
+        RealFlightLocalBridge::with_configuration(&configuration)
+            .map(|bridge| Handle::Local(Box::new(bridge)))
+    })
+}
+
+/// Connects to a RealFlight proxy at `address`, a nul-terminated
+/// `address:port`; see [RealFlightRemoteBridge].
+///
+/// Returns null on failure.
+///
+/// # Safety
+///
+/// `address` must be null or point to a nul-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rfb_remote_bridge_new(address: *const c_char) -> *mut RfbBridge {
+    // SAFETY: forwarded from the caller
+    let Some(address) = (unsafe { string_arg(address, "address") }) else {
+        return ptr::null_mut();
+    };
+    connect(|| RealFlightRemoteBridge::new(&address).map(Handle::Remote))
+}
+
+/// Sends twelve channel values, each from 0.0 to 1.0, and writes the state
+/// RealFlight returns to `out`.
+///
+/// # Safety
+///
+/// `bridge` must be null or a live handle, `channels` null or point to 12
+/// floats, and `out` null or point to writable memory for an [RfbState].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rfb_exchange_data(
+    bridge: *const RfbBridge,
+    channels: *const f32,
+    out: *mut RfbState,
+) -> c_int {
+    if channels.is_null() {
+        set_last_error("channels is null");
+        return RFB_ERROR;
+    }
+    if out.is_null() {
+        set_last_error("out is null");
+        return RFB_ERROR;
+    }
+    // SAFETY: the caller passes a handle and pointers valid as documented
+    unsafe {
+        call(bridge, |bridge| {
+            let mut control = ControlInputs::default();
+            control
+                .channels
+                .copy_from_slice(std::slice::from_raw_parts(channels, 12));
+            let state = bridge.exchange_data(&control)?;
+            out.write(RfbState::from(&state));
+            Ok(())
+        })
+    }
+}
+
+/// Hands control to the bridge; see [RealFlightBridge::disable_rc].
+///
+/// # Safety
+///
+/// `bridge` must be null or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rfb_disable_rc(bridge: *const RfbBridge) -> c_int {
+    // SAFETY: forwarded from the caller
+    unsafe { call(bridge, |bridge| bridge.disable_rc()) }
+}
+
+/// Returns control to the RC transmitter; see [RealFlightBridge::enable_rc].
+///
+/// # Safety
+///
+/// `bridge` must be null or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rfb_enable_rc(bridge: *const RfbBridge) -> c_int {
+    // SAFETY: forwarded from the caller
+    unsafe { call(bridge, |bridge| bridge.enable_rc()) }
+}
+
+/// Resets the aircraft; see [RealFlightBridge::reset_aircraft].
+///
+/// # Safety
+///
+/// `bridge` must be null or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rfb_reset(bridge: *const RfbBridge) -> c_int {
+    // SAFETY: forwarded from the caller
+    unsafe { call(bridge, |bridge| bridge.reset_aircraft()) }
+}
+
+/// Writes the bridge's request statistics to `out`.
+///
+/// # Safety
+///
+/// `bridge` must be null or a live handle, and `out` null or point to
+/// writable memory for an [RfbStatistics].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rfb_statistics(
+    bridge: *const RfbBridge,
+    out: *mut RfbStatistics,
+) -> c_int {
+    if out.is_null() {
+        set_last_error("out is null");
+        return RFB_ERROR;
+    }
+    guard(RFB_ERROR, || {
+        // SAFETY: the caller passes null or a live handle
+        let bridge = unsafe { bridge.as_ref() }.ok_or("bridge is null")?;
+        let statistics = bridge.bridge.statistics();
+        // SAFETY: the caller passes writable memory for an RfbStatistics
+        unsafe { out.write(RfbStatistics::from(&statistics)) };
+        Ok(RFB_OK)
+    })
+}
+
+/// Describes the last failure on the calling thread, or returns null if
+/// nothing has failed.
+///
+/// The string stays valid until the next failure on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn rfb_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Closes the bridge and releases the handle. Null is ignored.
+///
+/// # Safety
+///
+/// `bridge` must be null or a live handle, which is invalid afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rfb_bridge_free(bridge: *mut RfbBridge) {
+    if bridge.is_null() {
+        return;
+    }
+    guard((), || {
+        // SAFETY: the caller passes a live handle created by Box::into_raw
+        drop(unsafe { Box::from_raw(bridge) });
+        Ok(())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::soap_client::stub::StubSoapClient;
+    use approx::assert_relative_eq;
+
+    fn stub_handle(responses: &[&str]) -> *mut RfbBridge {
+        let responses = responses.iter().map(|r| r.to_string()).collect();
+        let bridge = RealFlightLocalBridge::stub(StubSoapClient::new(responses));
+        Box::into_raw(Box::new(RfbBridge {
+            bridge: Handle::Local(Box::new(bridge)),
+        }))
+    }
+
+    fn requests(bridge: *mut RfbBridge) -> Vec<String> {
+        match &unsafe { &*bridge }.bridge {
+            Handle::Local(bridge) => bridge.requests(),
+            Handle::Remote(_) => unreachable!("stub handles are local"),
+        }
+    }
+
+    fn last_error() -> String {
+        let message = rfb_last_error_message();
+        assert!(!message.is_null());
+        unsafe { CStr::from_ptr(message) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    mod exchange_tests {
+        use super::*;
+
+        #[test]
+        fn writes_state_in_si_units() {
+            let bridge = stub_handle(&["return-data-200"]);
+            let channels = [0.5f32; 12];
+            let mut state = RfbState::default();
+
+            let rc = unsafe { rfb_exchange_data(bridge, channels.as_ptr(), &mut state) };
+
+            assert_eq!(rc, RFB_OK);
+            assert_relative_eq!(state.altitude_asl, 1127.371);
+            assert_relative_eq!(state.azimuth, (-89.607_056f32).to_radians());
+            assert_relative_eq!(state.yaw_rate, 0.001_473_751f32.to_radians());
+            assert_relative_eq!(
+                state.battery_remaining_capacity,
+                3999.9907 * 3.6,
+                epsilon = 0.01
+            );
+            assert_relative_eq!(state.fuel_remaining, -1.0 / 33_814.0);
+            assert_relative_eq!(state.current_physics_time, 72263.41);
+            assert!(state.an_engine_is_running);
+            assert!(state.flight_axis_controller_is_active);
+            assert!(!state.is_touching_ground);
+            unsafe { rfb_bridge_free(bridge) };
+        }
+
+        #[test]
+        fn matches_decoded_state() {
+            let xml = include_str!("../testdata/responses/return-data-200.xml");
+            let decoded = crate::decode_simulator_state(xml).unwrap();
+            let bridge = stub_handle(&["return-data-200"]);
+            let channels = [0.0f32; 12];
+            let mut state = RfbState::default();
+
+            unsafe { rfb_exchange_data(bridge, channels.as_ptr(), &mut state) };
+
+            assert_eq!(state, RfbState::from(&decoded));
+            unsafe { rfb_bridge_free(bridge) };
+        }
+
+        #[test]
+        fn sends_channels() {
+            let bridge = stub_handle(&["return-data-200"]);
+            let mut channels = [0.0f32; 12];
+            channels[2] = 0.75;
+            let mut state = RfbState::default();
+
+            unsafe { rfb_exchange_data(bridge, channels.as_ptr(), &mut state) };
+
+            let requests = requests(bridge);
+            assert!(requests[0].contains("<item>0.75</item>"));
+            unsafe { rfb_bridge_free(bridge) };
+        }
+
+        #[test]
+        fn reports_soap_fault() {
+            let bridge = stub_handle(&["return-data-500"]);
+            let channels = [0.0f32; 12];
+            let mut state = RfbState::default();
+
+            let rc = unsafe { rfb_exchange_data(bridge, channels.as_ptr(), &mut state) };
+
+            assert_eq!(rc, RFB_ERROR);
+            assert!(!last_error().is_empty());
+            unsafe { rfb_bridge_free(bridge) };
+        }
+    }
+
+    mod control_tests {
+        use super::*;
+
+        #[test]
+        fn disable_enable_and_reset() {
+            let bridge = stub_handle(&[
+                "inject-uav-controller-interface-200",
+                "restore-original-controller-device-200",
+                "reset-aircraft-200",
+            ]);
+
+            assert_eq!(unsafe { rfb_disable_rc(bridge) }, RFB_OK);
+            assert_eq!(unsafe { rfb_enable_rc(bridge) }, RFB_OK);
+            assert_eq!(unsafe { rfb_reset(bridge) }, RFB_OK);
+
+            let requests = requests(bridge);
+            assert!(requests[0].contains("InjectUAVControllerInterface"));
+            assert!(requests[1].contains("RestoreOriginalControllerDevice"));
+            assert!(requests[2].contains("ResetAircraft"));
+            unsafe { rfb_bridge_free(bridge) };
+        }
+
+        #[test]
+        fn reports_failed_disable() {
+            let bridge = stub_handle(&["inject-uav-controller-interface-500"]);
+
+            assert_eq!(unsafe { rfb_disable_rc(bridge) }, RFB_ERROR);
+            assert!(!last_error().is_empty());
+            unsafe { rfb_bridge_free(bridge) };
+        }
+    }
+
+    mod statistics_tests {
+        use super::*;
+
+        #[test]
+        fn counts_requests() {
+            let bridge = stub_handle(&["return-data-200"]);
+            let channels = [0.0f32; 12];
+            let mut state = RfbState::default();
+            let mut statistics = RfbStatistics::default();
+
+            unsafe { rfb_exchange_data(bridge, channels.as_ptr(), &mut state) };
+            let rc = unsafe { rfb_statistics(bridge, &mut statistics) };
+
+            assert_eq!(rc, RFB_OK);
+            assert_eq!(statistics.request_count, 1);
+            assert_eq!(statistics.error_count, 0);
+            assert!(statistics.runtime > 0.0);
+            unsafe { rfb_bridge_free(bridge) };
+        }
+
+        #[test]
+        fn rejects_null_arguments() {
+            let bridge = stub_handle(&[]);
+            let mut statistics = RfbStatistics::default();
+
+            assert_eq!(
+                unsafe { rfb_statistics(ptr::null(), &mut statistics) },
+                RFB_ERROR
+            );
+            assert_eq!(last_error(), "bridge is null");
+            assert_eq!(
+                unsafe { rfb_statistics(bridge, ptr::null_mut()) },
+                RFB_ERROR
+            );
+            assert_eq!(last_error(), "out is null");
+            unsafe { rfb_bridge_free(bridge) };
+        }
+    }
+
+    mod remote_tests {
+        use super::*;
+
+        #[test]
+        fn rejects_null_address() {
+            let bridge = unsafe { rfb_remote_bridge_new(ptr::null()) };
+
+            assert!(bridge.is_null());
+            assert_eq!(last_error(), "address is null");
+        }
+
+        #[test]
+        fn reports_unreachable_proxy() {
+            let address = CString::new("not-a-host").unwrap();
+
+            let bridge = unsafe { rfb_remote_bridge_new(address.as_ptr()) };
+
+            assert!(bridge.is_null());
+            assert!(!last_error().is_empty());
+        }
+    }
+
+    mod null_tests {
+        use super::*;
+
+        #[test]
+        fn rejects_null_bridge() {
+            let channels = [0.0f32; 12];
+            let mut state = RfbState::default();
+
+            let rc = unsafe { rfb_exchange_data(ptr::null(), channels.as_ptr(), &mut state) };
+            assert_eq!(rc, RFB_ERROR);
+            assert_eq!(last_error(), "bridge is null");
+
+            assert_eq!(unsafe { rfb_disable_rc(ptr::null()) }, RFB_ERROR);
+            assert_eq!(unsafe { rfb_enable_rc(ptr::null()) }, RFB_ERROR);
+            assert_eq!(unsafe { rfb_reset(ptr::null()) }, RFB_ERROR);
+        }
+
+        #[test]
+        fn rejects_null_buffers() {
+            let bridge = stub_handle(&[]);
+            let channels = [0.0f32; 12];
+            let mut state = RfbState::default();
+
+            let rc = unsafe { rfb_exchange_data(bridge, ptr::null(), &mut state) };
+            assert_eq!(rc, RFB_ERROR);
+            assert_eq!(last_error(), "channels is null");
+
+            let rc = unsafe { rfb_exchange_data(bridge, channels.as_ptr(), ptr::null_mut()) };
+            assert_eq!(rc, RFB_ERROR);
+            assert_eq!(last_error(), "out is null");
+
+            assert!(requests(bridge).is_empty());
+            unsafe { rfb_bridge_free(bridge) };
+        }
+
+        #[test]
+        fn rejects_null_host() {
+            let bridge = unsafe { rfb_bridge_with_host(ptr::null()) };
+
+            assert!(bridge.is_null());
+            assert_eq!(last_error(), "host is null");
+        }
+
+        #[test]
+        fn free_ignores_null() {
+            unsafe { rfb_bridge_free(ptr::null_mut()) };
+        }
+    }
+
+    mod error_tests {
+        use super::*;
+
+        #[test]
+        fn no_message_before_failure() {
+            std::thread::spawn(|| assert!(rfb_last_error_message().is_null()))
+                .join()
+                .unwrap();
+        }
+
+        #[test]
+        fn reports_bad_host() {
+            let host = CString::new("not-a-host").unwrap();
+
+            let bridge = unsafe { rfb_bridge_with_host(host.as_ptr()) };
+
+            assert!(bridge.is_null());
+            assert!(!last_error().is_empty());
+        }
+
+        #[test]
+        fn catches_panics() {
+            let rc = guard(RFB_ERROR, || panic!("boom"));
+
+            assert_eq!(rc, RFB_ERROR);
+            assert_eq!(last_error(), "Panic in realflight-bridge: boom");
+        }
+    }
+
+    mod header_tests {
+        use super::*;
+
+        const HEADER: &str = include_str!("../include/realflight_bridge.h");
+
+        #[test]
+        fn declares_every_function() {
+            for function in [
+                "rfb_bridge_new",
+                "rfb_bridge_with_host",
+                "rfb_remote_bridge_new",
+                "rfb_statistics",
+                "rfb_exchange_data",
+                "rfb_disable_rc",
+                "rfb_enable_rc",
+                "rfb_reset",
+                "rfb_last_error_message",
+                "rfb_bridge_free",
+            ] {
+                assert!(
+                    HEADER.contains(&format!("{}(", function)),
+                    "{} missing",
+                    function
+                );
+            }
+        }
+
+        #[test]
+        fn state_fields_in_order() {
+            let source = include_str!("ffi.rs");
+            let rust_fields = field_names(source, "pub struct RfbState {", "pub ");
+            let c_fields = field_names(HEADER, "typedef struct RfbState {", "");
+
+            assert_eq!(rust_fields.len(), 46);
+            assert_eq!(rust_fields, c_fields);
+            assert_eq!(
+                std::mem::size_of::<RfbState>(),
+                12 * 4 + 39 * 4 + 6 + 2,
+                "unexpected padding"
+            );
+        }
+
+        #[test]
+        fn statistics_fields_in_order() {
+            let source = include_str!("ffi.rs");
+            let rust_fields = field_names(source, "pub struct RfbStatistics {", "pub ");
+            let c_fields = field_names(HEADER, "typedef struct RfbStatistics {", "");
+
+            assert_eq!(rust_fields.len(), 13);
+            assert_eq!(rust_fields, c_fields);
+        }
+
+        fn field_names(source: &str, start: &str, prefix: &str) -> Vec<String> {
+            let body = &source[source.find(start).unwrap() + start.len()..];
+            let body = &body[..body.find('}').unwrap()];
+            body.lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with('/') && !line.starts_with('*'))
+                .filter_map(|line| line.strip_prefix(prefix))
+                .filter_map(|line| {
+                    let name = line.split([':', ';', '[']).next()?.trim();
+                    let name = name.rsplit(' ').next()?;
+                    (!name.is_empty()).then(|| name.to_string())
+                })
+                .collect()
+        }
+    }
+}
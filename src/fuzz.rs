@@ -0,0 +1,425 @@
+//! Entry points for the cargo-fuzz targets in `fuzz/`.
+//!
+//! Each function takes the raw bytes a fuzzer produces and must return
+//! without panicking, whatever they hold. The module's tests run the same
+//! code on randomized input, generated from fixed seeds so every run checks
+//! the same cases: well-formed and malformed RealFlight responses, and
+//! arbitrary proxy frames. They are plain `rand` loops rather than proptest,
+//! so a failing case is reported as generated, without shrinking. Run them
+//! with `cargo test --features fuzz-corpus`.
+//!
+//! This module requires the `fuzz-corpus` feature.
+
+#![cfg(feature = "fuzz-corpus")]
+
+use futures_util::FutureExt;
+use tokio::io::BufReader;
+
+use crate::bridge::proxy::read_frame;
+use crate::bridge::remote::Request;
+use crate::constants::DEFAULT_MAX_FRAME_SIZE;
+
+/// Decodes `data` as a RealFlight `ReturnData` response.
+pub fn decode_simulator_state(data: &[u8]) {
+    if let Ok(xml) = std::str::from_utf8(data) {
+        let _ = crate::decoders::decode_simulator_state(xml);
+    }
+}
+
+/// Extracts an element from a document.
+///
+/// The first byte of `data` is the length of the element name that follows;
+/// the rest is the document.
+pub fn extract_element(data: &[u8]) {
+    let Some((&length, rest)) = data.split_first() else {
+        return;
+    };
+    let (name, xml) = rest.split_at(usize::from(length).min(rest.len()));
+    if let (Ok(name), Ok(xml)) = (std::str::from_utf8(name), std::str::from_utf8(xml)) {
+        let _ = crate::decoders::extract_element(name, xml);
+    }
+}
+
+/// Reads `data` as the bytes a client sent a proxy.
+///
+/// Frames are read as the proxy reads them, each deserialized as a
+/// [Request], until the data ends or a frame is refused.
+pub fn read_proxy_frames(data: &[u8]) {
+    let _ = proxy_requests(data);
+}
+
+/// Every frame read from `data`, with `None` for frames that are not a
+/// [Request].
+fn proxy_requests(data: &[u8]) -> Vec<Option<Request>> {
+    let mut reader = BufReader::new(data);
+    let mut requests = Vec::new();
+    loop {
+        let (next, frame) = read_frame(reader, DEFAULT_MAX_FRAME_SIZE)
+            .now_or_never()
+            .expect("reads from memory never wait");
+        reader = next;
+        match frame {
+            Ok(Some(buffer)) => requests.push(postcard::from_bytes(&buffer).ok()),
+            Ok(None) | Err(_) => return requests,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ControlInputs;
+    use crate::bridge::remote::{ProtocolInfo, RequestType};
+    use rand::rngs::StdRng;
+    use rand::{RngExt, SeedableRng};
+
+    /// Generated inputs per test
+    const CASES: usize = 500;
+
+    /// Numeric fields of a `ReturnData` response
+    const FLOAT_FIELDS: [&str; 39] = [
+        "m-currentPhysicsTime-SEC",
+        "m-currentPhysicsSpeedMultiplier",
+        "m-airspeed-MPS",
+        "m-altitudeASL-MTR",
+        "m-altitudeAGL-MTR",
+        "m-groundspeed-MPS",
+        "m-pitchRate-DEGpSEC",
+        "m-rollRate-DEGpSEC",
+        "m-yawRate-DEGpSEC",
+        "m-azimuth-DEG",
+        "m-inclination-DEG",
+        "m-roll-DEG",
+        "m-orientationQuaternion-X",
+        "m-orientationQuaternion-Y",
+        "m-orientationQuaternion-Z",
+        "m-orientationQuaternion-W",
+        "m-aircraftPositionX-MTR",
+        "m-aircraftPositionY-MTR",
+        "m-velocityWorldU-MPS",
+        "m-velocityWorldV-MPS",
+        "m-velocityWorldW-MPS",
+        "m-velocityBodyU-MPS",
+        "m-velocityBodyV-MPS",
+        "m-velocityBodyW-MPS",
+        "m-accelerationWorldAX-MPS2",
+        "m-accelerationWorldAY-MPS2",
+        "m-accelerationWorldAZ-MPS2",
+        "m-accelerationBodyAX-MPS2",
+        "m-accelerationBodyAY-MPS2",
+        "m-accelerationBodyAZ-MPS2",
+        "m-windX-MPS",
+        "m-windY-MPS",
+        "m-windZ-MPS",
+        "m-propRPM",
+        "m-heliMainRotorRPM",
+        "m-batteryVoltage-VOLTS",
+        "m-batteryCurrentDraw-AMPS",
+        "m-batteryRemainingCapacity-MAH",
+        "m-fuelRemaining-OZ",
+    ];
+
+    /// Boolean fields of a `ReturnData` response
+    const BOOL_FIELDS: [&str; 6] = [
+        "m-isLocked",
+        "m-hasLostComponents",
+        "m-anEngineIsRunning",
+        "m-isTouchingGround",
+        "m-flightAxisControllerIsActive",
+        "m-resetButtonHasBeenPressed",
+    ];
+
+    /// Fragments spliced into responses to break them
+    const DEBRIS: [&str; 12] = [
+        "<",
+        ">",
+        "</",
+        "<?",
+        "/",
+        "<item>",
+        "</item>",
+        "<item>0.5</item>",
+        "<m-propRPM>",
+        "</m-isLocked>",
+        "NaN",
+        "\u{1F6E9}",
+    ];
+
+    /// A response as RealFlight would send it.
+    struct Response {
+        xml: String,
+        channels: Vec<f32>,
+        prop_rpm: Option<f32>,
+        is_locked: Option<bool>,
+    }
+
+    fn well_formed(rng: &mut StdRng) -> Response {
+        let channels: Vec<f32> = (0..rng.random_range(0..=12))
+            .map(|_| rng.random_range(0.0..=1.0))
+            .collect();
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><SOAP-ENV:Envelope><SOAP-ENV:Body>\
+             <ReturnDataResponse><ReturnData><m-previousInputsState>\
+             <m-selectedChannels>-1</m-selectedChannels><m-channelValues-0to1>",
+        );
+        for channel in &channels {
+            xml.push_str(&format!("<item>{}</item>", channel));
+        }
+        xml.push_str("</m-channelValues-0to1></m-previousInputsState><m-aircraftState>");
+
+        let mut prop_rpm = None;
+        for name in FLOAT_FIELDS {
+            if rng.random_bool(0.8) {
+                let value = rng.random_range(-1.0e6..1.0e6);
+                if name == "m-propRPM" {
+                    prop_rpm = Some(value);
+                }
+                xml.push_str(&format!("<{name}>{value}</{name}>"));
+            }
+        }
+        let mut is_locked = None;
+        for name in BOOL_FIELDS {
+            if rng.random_bool(0.8) {
+                let value = rng.random_bool(0.5);
+                if name == "m-isLocked" {
+                    is_locked = Some(value);
+                }
+                xml.push_str(&format!("<{name}>{value}</{name}>"));
+            }
+        }
+        xml.push_str(
+            "<m-currentAircraftStatus>CAS-FLYING</m-currentAircraftStatus>\
+             </m-aircraftState></ReturnData></ReturnDataResponse>\
+             </SOAP-ENV:Body></SOAP-ENV:Envelope>",
+        );
+
+        Response {
+            xml,
+            channels,
+            prop_rpm,
+            is_locked,
+        }
+    }
+
+    /// A well-formed response broken by a few random edits.
+    fn malformed(rng: &mut StdRng) -> String {
+        let mut xml = well_formed(rng).xml;
+        for _ in 0..rng.random_range(1..=4) {
+            let at = char_boundary(&xml, rng.random_range(0..=xml.len()));
+            match rng.random_range(0..5) {
+                0 => xml.truncate(at),
+                1 => xml.insert_str(at, DEBRIS[rng.random_range(0..DEBRIS.len())]),
+                2 => {
+                    let end = char_boundary(&xml, rng.random_range(at..=xml.len()));
+                    xml.replace_range(at..end, "");
+                }
+                3 => {
+                    // More channels than the inputs hold
+                    let items = "<item>1</item>".repeat(rng.random_range(1..=20));
+                    xml = xml.replacen(
+                        "<m-channelValues-0to1>",
+                        &format!("<m-channelValues-0to1>{items}"),
+                        1,
+                    );
+                }
+                _ => {
+                    let garbage: String = (0..rng.random_range(1..16))
+                        .map(|_| rng.random::<char>())
+                        .collect();
+                    xml.insert_str(at, &garbage);
+                }
+            }
+        }
+        xml
+    }
+
+    /// First char boundary at or after `index`.
+    fn char_boundary(s: &str, mut index: usize) -> usize {
+        while !s.is_char_boundary(index) {
+            index += 1;
+        }
+        index
+    }
+
+    fn bytes(rng: &mut StdRng, max_len: usize) -> Vec<u8> {
+        (0..rng.random_range(0..=max_len))
+            .map(|_| rng.random())
+            .collect()
+    }
+
+    fn request(rng: &mut StdRng) -> Request {
+        let request_type = match rng.random_range(0..7) {
+            0 => RequestType::EnableRC,
+            1 => RequestType::DisableRC,
+            2 => RequestType::ResetAircraft,
+            3 => RequestType::ExchangeData,
+            4 => RequestType::Hello(ProtocolInfo::current()),
+            5 => RequestType::Ping,
+            _ => RequestType::Subscribe,
+        };
+        let payload = rng.random_bool(0.5).then(|| {
+            let mut control = ControlInputs::default();
+            for channel in control.channels.iter_mut() {
+                *channel = rng.random();
+            }
+            control
+        });
+        Request {
+            request_type,
+            payload,
+            id: rng.random(),
+        }
+    }
+
+    fn frame(request: &Request) -> Vec<u8> {
+        let body = postcard::to_stdvec(request).unwrap();
+        let mut frame = (body.len() as u32).to_be_bytes().to_vec();
+        frame.extend(body);
+        frame
+    }
+
+    mod decoder_properties {
+        use super::*;
+
+        #[test]
+        fn decodes_well_formed_responses() {
+            let mut rng = StdRng::seed_from_u64(1);
+            for _ in 0..CASES {
+                let response = well_formed(&mut rng);
+
+                let state = crate::decoders::decode_simulator_state(&response.xml)
+                    .unwrap_or_else(|e| panic!("{}: {}", e, response.xml));
+
+                let mut channels = [0.0; 12];
+                channels[..response.channels.len()].copy_from_slice(&response.channels);
+                assert_eq!(state.previous_inputs.channels, channels);
+                assert_eq!(state.prop_rpm, response.prop_rpm.unwrap_or_default());
+                assert_eq!(state.is_locked, response.is_locked.unwrap_or_default());
+                assert_eq!(state.current_aircraft_status, "CAS-FLYING");
+            }
+        }
+
+        #[test]
+        fn survives_malformed_responses() {
+            let mut rng = StdRng::seed_from_u64(2);
+            for _ in 0..CASES {
+                decode_simulator_state(malformed(&mut rng).as_bytes());
+            }
+        }
+
+        #[test]
+        fn survives_arbitrary_bytes() {
+            let mut rng = StdRng::seed_from_u64(3);
+            for _ in 0..CASES {
+                decode_simulator_state(&bytes(&mut rng, 256));
+            }
+        }
+    }
+
+    mod extract_element_properties {
+        use super::*;
+
+        #[test]
+        fn extracts_generated_elements() {
+            let mut rng = StdRng::seed_from_u64(4);
+            for _ in 0..CASES {
+                let name: String = (0..rng.random_range(1..12))
+                    .map(|_| rng.random_range('a'..='z'))
+                    .collect();
+                let content = format!("{}", rng.random::<f32>());
+                let xml = format!(
+                    "{}<{name}>{content}</{name}>{}",
+                    malformed(&mut rng).replace(&format!("<{name}>"), ""),
+                    malformed(&mut rng).replace(&format!("<{name}>"), ""),
+                );
+
+                // The first closing tag wins; the suffix may hold another
+                let extracted = crate::decoders::extract_element(&name, &xml);
+                if !xml[..xml.find(&format!("<{name}>")).unwrap()].contains(&format!("</{name}>")) {
+                    assert_eq!(extracted, Some(content), "{}", xml);
+                }
+            }
+        }
+
+        #[test]
+        fn survives_arbitrary_bytes() {
+            let mut rng = StdRng::seed_from_u64(5);
+            for _ in 0..CASES {
+                extract_element(&bytes(&mut rng, 128));
+                let mut data = vec![rng.random_range(0..8u8)];
+                data.extend(malformed(&mut rng).into_bytes());
+                extract_element(&data);
+            }
+        }
+    }
+
+    mod proxy_frame_properties {
+        use super::*;
+
+        #[test]
+        fn reads_back_framed_requests() {
+            let mut rng = StdRng::seed_from_u64(6);
+            for _ in 0..CASES {
+                let sent: Vec<Request> = (0..rng.random_range(0..5))
+                    .map(|_| request(&mut rng))
+                    .collect();
+                let data: Vec<u8> = sent.iter().flat_map(frame).collect();
+
+                let read = proxy_requests(&data);
+
+                assert_eq!(read.len(), sent.len());
+                for (read, sent) in read.into_iter().zip(&sent) {
+                    let read = read.expect("a request");
+                    assert_eq!(read.id, sent.id);
+                    assert_eq!(read.request_type, sent.request_type);
+                    assert_eq!(read.payload, sent.payload);
+                }
+            }
+        }
+
+        #[test]
+        fn survives_corrupted_frames() {
+            let mut rng = StdRng::seed_from_u64(7);
+            for _ in 0..CASES {
+                let mut data: Vec<u8> = (0..rng.random_range(1..4))
+                    .flat_map(|_| frame(&request(&mut rng)))
+                    .collect();
+                for _ in 0..rng.random_range(1..4) {
+                    let at = rng.random_range(0..data.len());
+                    data[at] = rng.random();
+                }
+                data.truncate(rng.random_range(0..=data.len()));
+
+                read_proxy_frames(&data);
+            }
+        }
+
+        #[test]
+        fn survives_arbitrary_bytes() {
+            let mut rng = StdRng::seed_from_u64(8);
+            for _ in 0..CASES {
+                read_proxy_frames(&bytes(&mut rng, 256));
+            }
+        }
+
+        #[test]
+        fn stops_at_oversized_frame() {
+            let mut data = frame(&request(&mut StdRng::seed_from_u64(9)));
+            data.extend(u32::MAX.to_be_bytes());
+            data.extend([0; 16]);
+
+            assert_eq!(proxy_requests(&data).len(), 1);
+        }
+
+        #[test]
+        fn skips_frames_that_are_not_requests() {
+            // An overlong varint, then a request type past the last variant
+            let data = [0, 0, 0, 4, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 1, 0x7F];
+
+            let read = proxy_requests(&data);
+
+            assert_eq!(read.len(), 2);
+            assert!(read.iter().all(Option::is_none));
+        }
+    }
+}
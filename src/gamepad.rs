@@ -0,0 +1,630 @@
+//! Gamepad and joystick input, for flying manual test flights through the
+//! bridge without an RC transmitter.
+//!
+//! A [GamepadSource] reads a [GamepadBackend] and turns its axes and buttons
+//! into [ControlInputs] through a [GamepadMapping]. On Linux,
+//! [GamepadSource::new] opens the first joystick device, `/dev/input/js*`.
+//! Elsewhere, or to read a controller through a library such as `gilrs`,
+//! implement [GamepadBackend] and use [GamepadSource::with_backend].
+//!
+//! This module requires the `gamepad` feature.
+
+#![cfg(feature = "gamepad")]
+
+use std::io;
+
+use thiserror::Error;
+
+use crate::ControlInputs;
+
+/// Errors reading a gamepad.
+#[derive(Debug, Error)]
+pub enum GamepadError {
+    /// No gamepad is connected, or none can be read on this platform
+    #[error("No gamepad found")]
+    NoGamepad,
+
+    /// The gamepad was unplugged or stopped reporting
+    #[error("Gamepad disconnected")]
+    Disconnected,
+
+    /// Reading the gamepad failed
+    #[error("Gamepad I/O failed: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Current axis and button values of a gamepad.
+///
+/// Implement it to read a controller [GamepadSource] has no built-in support
+/// for, or to feed synthetic values in tests.
+pub trait GamepadBackend {
+    /// Takes in input that arrived since the last call, without blocking.
+    fn poll(&mut self) -> Result<(), GamepadError>;
+    /// Position of `axis` from -1.0 to 1.0, or 0.0 if the gamepad has no such
+    /// axis
+    fn axis(&self, axis: usize) -> f32;
+    /// Whether `button` is held; `false` if the gamepad has no such button
+    fn button(&self, button: usize) -> bool;
+}
+
+/// A gamepad control driving a channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamepadInput {
+    /// Axis by index; -1 to 1 spans the channel, centered at 0.5
+    Axis(usize),
+    /// Button by index; 1.0 while held, 0.0 otherwise
+    Button(usize),
+}
+
+/// How one gamepad control drives a RealFlight channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GamepadBinding {
+    /// Control read for the channel
+    pub input: GamepadInput,
+    /// Whether the channel moves opposite to the control
+    pub inverted: bool,
+}
+
+impl GamepadBinding {
+    /// Drives the channel by an axis.
+    pub fn axis(axis: usize) -> Self {
+        GamepadBinding {
+            input: GamepadInput::Axis(axis),
+            inverted: false,
+        }
+    }
+
+    /// Drives the channel by a button.
+    pub fn button(button: usize) -> Self {
+        GamepadBinding {
+            input: GamepadInput::Button(button),
+            inverted: false,
+        }
+    }
+
+    /// Inverts the channel.
+    pub fn inverted(mut self) -> Self {
+        self.inverted = !self.inverted;
+        self
+    }
+
+    fn apply(&self, backend: &impl GamepadBackend, deadband: f32) -> f32 {
+        let value = match self.input {
+            GamepadInput::Axis(axis) => (apply_deadband(backend.axis(axis), deadband) + 1.0) / 2.0,
+            GamepadInput::Button(button) => f32::from(u8::from(backend.button(button))),
+        }
+        .clamp(0.0, 1.0);
+        if self.inverted { 1.0 - value } else { value }
+    }
+}
+
+/// Zeroes axis positions within `deadband` of center and rescales the rest,
+/// so the output still spans -1 to 1 without a step at the deadband edge.
+fn apply_deadband(value: f32, deadband: f32) -> f32 {
+    // Unplugged or misbehaving axes rest at center
+    if value.is_nan() || value.abs() <= deadband {
+        return 0.0;
+    }
+    value.signum() * (value.abs() - deadband) / (1.0 - deadband)
+}
+
+/// Assigns gamepad controls to the channels of [ControlInputs].
+///
+/// The default suits an Xbox-style controller read through the Linux
+/// joystick driver, flown in mode 2: the right stick drives the aileron and
+/// elevator channels, the left stick the throttle and rudder, with stick
+/// forward for full throttle and stick back for nose up. Unmapped channels are
+/// held at 0.5. Axes within 0.05 of center read as centered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GamepadMapping {
+    channels: [Option<GamepadBinding>; 12],
+    deadband: f32,
+}
+
+impl Default for GamepadMapping {
+    fn default() -> Self {
+        GamepadMapping::empty()
+            .channel(0, GamepadBinding::axis(3))
+            .channel(1, GamepadBinding::axis(4))
+            .channel(2, GamepadBinding::axis(1).inverted())
+            .channel(3, GamepadBinding::axis(0))
+    }
+}
+
+impl GamepadMapping {
+    /// A mapping leaving every channel unmapped, with the default deadband.
+    pub fn empty() -> Self {
+        GamepadMapping {
+            channels: [None; 12],
+            deadband: 0.05,
+        }
+    }
+
+    /// Drives `channel`, an index into [ControlInputs::channels], by `binding`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is 12 or more.
+    pub fn channel(mut self, channel: usize, binding: GamepadBinding) -> Self {
+        assert!(channel < 12, "channel must be below 12, got {}", channel);
+        self.channels[channel] = Some(binding);
+        self
+    }
+
+    /// Treats axis positions within `deadband` of center as centered, to
+    /// hide stick drift; clamped to 0.0 to 0.99.
+    pub fn deadband(mut self, deadband: f32) -> Self {
+        self.deadband = deadband.clamp(0.0, 0.99);
+        self
+    }
+
+    /// Converts the current gamepad values into channel values.
+    pub fn apply(&self, backend: &impl GamepadBackend) -> ControlInputs {
+        let mut inputs = ControlInputs::default();
+        for (value, binding) in inputs.channels.iter_mut().zip(&self.channels) {
+            *value = match binding {
+                Some(binding) => binding.apply(backend, self.deadband),
+                None => 0.5,
+            };
+        }
+        inputs
+    }
+}
+
+/// Produces [ControlInputs] from a gamepad, on demand.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::gamepad::{GamepadMapping, GamepadSource};
+/// use realflight_bridge::{RealFlightBridge, RealFlightLocalBridge};
+/// use std::error::Error;
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///     let bridge = RealFlightLocalBridge::new()?;
+///     let mut gamepad = GamepadSource::new(GamepadMapping::default())?;
+///
+///     bridge.disable_rc()?;
+///     loop {
+///         let inputs = gamepad.inputs()?;
+///         bridge.exchange_data(&inputs)?;
+///     }
+/// }
+/// ```
+pub struct GamepadSource<B> {
+    backend: B,
+    mapping: GamepadMapping,
+}
+
+#[cfg(target_os = "linux")]
+impl GamepadSource<JoystickDevice> {
+    /// Opens the first Linux joystick device.
+    ///
+    /// Fails with [GamepadError::NoGamepad] if none is connected.
+    pub fn new(mapping: GamepadMapping) -> Result<Self, GamepadError> {
+        Ok(GamepadSource::with_backend(
+            JoystickDevice::find()?,
+            mapping,
+        ))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl GamepadSource<NoBackend> {
+    /// Fails with [GamepadError::NoGamepad]: there is no built-in backend for
+    /// this platform.
+    pub fn new(_mapping: GamepadMapping) -> Result<Self, GamepadError> {
+        Err(GamepadError::NoGamepad)
+    }
+}
+
+impl<B: GamepadBackend> GamepadSource<B> {
+    /// Reads `backend` through `mapping`.
+    pub fn with_backend(backend: B, mapping: GamepadMapping) -> Self {
+        GamepadSource { backend, mapping }
+    }
+
+    /// Polls the gamepad and returns the channel values it commands.
+    pub fn inputs(&mut self) -> Result<ControlInputs, GamepadError> {
+        self.backend.poll()?;
+        Ok(self.mapping.apply(&self.backend))
+    }
+
+    /// Returns the backend.
+    pub fn get_ref(&self) -> &B {
+        &self.backend
+    }
+}
+
+/// Placeholder backend on platforms without a built-in one.
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug)]
+pub enum NoBackend {}
+
+#[cfg(not(target_os = "linux"))]
+impl GamepadBackend for NoBackend {
+    fn poll(&mut self) -> Result<(), GamepadError> {
+        match *self {}
+    }
+
+    fn axis(&self, _axis: usize) -> f32 {
+        match *self {}
+    }
+
+    fn button(&self, _button: usize) -> bool {
+        match *self {}
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::JoystickDevice;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::fs::File;
+    use std::io::{self, Read};
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use log::debug;
+
+    use super::{GamepadBackend, GamepadError};
+
+    /// Joystick devices probed by [JoystickDevice::find].
+    const MAX_DEVICES: usize = 32;
+    const EVENT_SIZE: usize = 8;
+    const EVENT_BUTTON: u8 = 0x01;
+    const EVENT_AXIS: u8 = 0x02;
+    /// Set on the synthetic events reporting the state when the device opens
+    const EVENT_INIT: u8 = 0x80;
+
+    #[derive(Debug, Default)]
+    struct Values {
+        axes: Vec<f32>,
+        buttons: Vec<bool>,
+        disconnected: bool,
+    }
+
+    /// A Linux joystick device, `/dev/input/jsN`, read on a background thread.
+    ///
+    /// The thread ends when the device is unplugged or the backend dropped
+    /// and the device next reports.
+    #[derive(Debug)]
+    pub struct JoystickDevice {
+        shared: Arc<Mutex<Values>>,
+        current: Values,
+    }
+
+    impl JoystickDevice {
+        /// Opens the lowest numbered joystick device present.
+        pub fn find() -> Result<Self, GamepadError> {
+            (0..MAX_DEVICES)
+                .find_map(|n| JoystickDevice::open(format!("/dev/input/js{}", n)).ok())
+                .ok_or(GamepadError::NoGamepad)
+        }
+
+        /// Opens the joystick device at `path`.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self, GamepadError> {
+            let path = path.as_ref();
+            let device = File::open(path).map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => GamepadError::NoGamepad,
+                _ => GamepadError::Io(e),
+            })?;
+
+            let shared = Arc::new(Mutex::new(Values::default()));
+            let values = Arc::clone(&shared);
+            thread::Builder::new()
+                .name(format!("gamepad-{}", path.display()))
+                .spawn(move || read_events(device, values))?;
+
+            Ok(JoystickDevice {
+                shared,
+                current: Values::default(),
+            })
+        }
+    }
+
+    impl GamepadBackend for JoystickDevice {
+        fn poll(&mut self) -> Result<(), GamepadError> {
+            let shared = self.shared.lock().unwrap();
+            self.current.axes.clone_from(&shared.axes);
+            self.current.buttons.clone_from(&shared.buttons);
+            if shared.disconnected {
+                return Err(GamepadError::Disconnected);
+            }
+            Ok(())
+        }
+
+        fn axis(&self, axis: usize) -> f32 {
+            self.current.axes.get(axis).copied().unwrap_or(0.0)
+        }
+
+        fn button(&self, button: usize) -> bool {
+            self.current.buttons.get(button).copied().unwrap_or(false)
+        }
+    }
+
+    /// Applies `js_event`s from `device` to `values` until the device or the
+    /// backend goes away.
+    fn read_events(mut device: File, values: Arc<Mutex<Values>>) {
+        let mut event = [0u8; EVENT_SIZE];
+        loop {
+            if let Err(e) = device.read_exact(&mut event) {
+                debug!("Gamepad stopped reporting: {}", e);
+                break;
+            }
+            if Arc::strong_count(&values) == 1 {
+                return;
+            }
+
+            // struct js_event { u32 time; i16 value; u8 type; u8 number; }
+            let value = i16::from_ne_bytes([event[4], event[5]]);
+            let number = usize::from(event[7]);
+            let mut values = values.lock().unwrap();
+            match event[6] & !EVENT_INIT {
+                EVENT_AXIS => {
+                    if values.axes.len() <= number {
+                        values.axes.resize(number + 1, 0.0);
+                    }
+                    values.axes[number] = f32::from(value) / f32::from(i16::MAX);
+                }
+                EVENT_BUTTON => {
+                    if values.buttons.len() <= number {
+                        values.buttons.resize(number + 1, false);
+                    }
+                    values.buttons[number] = value != 0;
+                }
+                _ => {}
+            }
+        }
+        values.lock().unwrap().disconnected = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Backend reporting fixed axis and button values.
+    #[derive(Default)]
+    struct FakeGamepad {
+        axes: Vec<f32>,
+        buttons: Vec<bool>,
+        polls: usize,
+        fail: bool,
+    }
+
+    impl FakeGamepad {
+        fn axes(axes: &[f32]) -> Self {
+            FakeGamepad {
+                axes: axes.to_vec(),
+                ..Default::default()
+            }
+        }
+    }
+
+    impl GamepadBackend for FakeGamepad {
+        fn poll(&mut self) -> Result<(), GamepadError> {
+            self.polls += 1;
+            if self.fail {
+                return Err(GamepadError::Disconnected);
+            }
+            Ok(())
+        }
+
+        fn axis(&self, axis: usize) -> f32 {
+            self.axes.get(axis).copied().unwrap_or(0.0)
+        }
+
+        fn button(&self, button: usize) -> bool {
+            self.buttons.get(button).copied().unwrap_or(false)
+        }
+    }
+
+    mod mapping {
+        use super::*;
+        use approx::assert_relative_eq;
+
+        #[test]
+        fn axes_span_channel_around_center() {
+            let mapping = GamepadMapping::empty()
+                .deadband(0.0)
+                .channel(0, GamepadBinding::axis(0))
+                .channel(1, GamepadBinding::axis(1))
+                .channel(2, GamepadBinding::axis(2));
+
+            let inputs = mapping.apply(&FakeGamepad::axes(&[-1.0, 0.0, 0.5]));
+
+            assert_eq!(inputs.channels[..3], [0.0, 0.5, 0.75]);
+        }
+
+        #[test]
+        fn unmapped_channels_hold_center() {
+            let mapping = GamepadMapping::empty().channel(0, GamepadBinding::axis(0));
+
+            let inputs = mapping.apply(&FakeGamepad::axes(&[1.0]));
+
+            assert_eq!(inputs.channels[0], 1.0);
+            assert!(inputs.channels[1..].iter().all(|&value| value == 0.5));
+        }
+
+        #[test]
+        fn inverted_axis_mirrors_channel() {
+            let mapping = GamepadMapping::empty()
+                .deadband(0.0)
+                .channel(0, GamepadBinding::axis(0).inverted());
+
+            let inputs = mapping.apply(&FakeGamepad::axes(&[0.5]));
+
+            assert_eq!(inputs.channels[0], 0.25);
+        }
+
+        #[test]
+        fn deadband_centers_small_deflections() {
+            let mapping = GamepadMapping::empty()
+                .deadband(0.1)
+                .channel(0, GamepadBinding::axis(0))
+                .channel(1, GamepadBinding::axis(1))
+                .channel(2, GamepadBinding::axis(2))
+                .channel(3, GamepadBinding::axis(3));
+
+            let inputs = mapping.apply(&FakeGamepad::axes(&[0.08, -0.1, 0.55, -1.0]));
+
+            assert_eq!(inputs.channels[0], 0.5);
+            assert_eq!(inputs.channels[1], 0.5);
+            // Rescaled past the deadband: (0.55 - 0.1) / 0.9 = 0.5
+            assert_relative_eq!(inputs.channels[2], 0.75, max_relative = 1e-6);
+            assert_eq!(inputs.channels[3], 0.0);
+        }
+
+        #[test]
+        fn out_of_range_and_nan_axes_are_contained() {
+            let mapping = GamepadMapping::empty()
+                .channel(0, GamepadBinding::axis(0))
+                .channel(1, GamepadBinding::axis(1));
+
+            let inputs = mapping.apply(&FakeGamepad::axes(&[-1.2, f32::NAN]));
+
+            assert_eq!(inputs.channels[0], 0.0);
+            assert_eq!(inputs.channels[1], 0.5);
+        }
+
+        #[test]
+        fn buttons_drive_channel_fully() {
+            let mapping = GamepadMapping::empty()
+                .channel(4, GamepadBinding::button(0))
+                .channel(5, GamepadBinding::button(1))
+                .channel(6, GamepadBinding::button(0).inverted());
+            let gamepad = FakeGamepad {
+                buttons: vec![true, false],
+                ..Default::default()
+            };
+
+            let inputs = mapping.apply(&gamepad);
+
+            assert_eq!(inputs.channels[4..7], [1.0, 0.0, 0.0]);
+        }
+
+        #[test]
+        fn missing_controls_rest_at_center_or_released() {
+            let mapping = GamepadMapping::empty()
+                .channel(0, GamepadBinding::axis(7))
+                .channel(1, GamepadBinding::button(9));
+
+            let inputs = mapping.apply(&FakeGamepad::default());
+
+            assert_eq!(inputs.channels[..2], [0.5, 0.0]);
+        }
+
+        #[test]
+        fn default_flies_mode_2() {
+            // Left stick full forward and right, right stick back and left
+            let gamepad = FakeGamepad::axes(&[1.0, -1.0, 0.0, -1.0, 1.0]);
+
+            let inputs = GamepadMapping::default().apply(&gamepad);
+
+            assert_eq!(inputs.channels[..4], [0.0, 1.0, 1.0, 1.0]);
+        }
+
+        #[test]
+        #[should_panic(expected = "channel must be below 12")]
+        fn rejects_channel_out_of_range() {
+            let _ = GamepadMapping::empty().channel(12, GamepadBinding::axis(0));
+        }
+    }
+
+    mod source {
+        use super::*;
+
+        #[test]
+        fn polls_before_mapping() {
+            let mut source = GamepadSource::with_backend(
+                FakeGamepad::axes(&[0.0, 1.0]),
+                GamepadMapping::default(),
+            );
+
+            let inputs = source.inputs().unwrap();
+
+            assert_eq!(source.get_ref().polls, 1);
+            assert_eq!(inputs.channels[2], 0.0);
+        }
+
+        #[test]
+        fn reports_backend_errors() {
+            let gamepad = FakeGamepad {
+                fail: true,
+                ..Default::default()
+            };
+            let mut source = GamepadSource::with_backend(gamepad, GamepadMapping::default());
+
+            assert!(matches!(source.inputs(), Err(GamepadError::Disconnected)));
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        #[test]
+        fn new_fails_without_backend() {
+            assert!(matches!(
+                GamepadSource::new(GamepadMapping::default()),
+                Err(GamepadError::NoGamepad)
+            ));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    mod joystick_device {
+        use super::*;
+        use std::time::{Duration, Instant};
+
+        fn event(kind: u8, number: u8, value: i16) -> Vec<u8> {
+            let mut event = 1234u32.to_ne_bytes().to_vec();
+            event.extend_from_slice(&value.to_ne_bytes());
+            event.extend_from_slice(&[kind, number]);
+            event
+        }
+
+        /// Polls until the device reports it has stopped.
+        fn poll_until_disconnected(device: &mut JoystickDevice) {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while !matches!(device.poll(), Err(GamepadError::Disconnected)) {
+                assert!(Instant::now() < deadline, "device never disconnected");
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        #[test]
+        fn decodes_axis_and_button_events() {
+            let path = std::env::temp_dir().join(format!(
+                "realflight-bridge-{}-js-events",
+                std::process::id()
+            ));
+            let events = [
+                event(0x82, 0, 0),
+                event(0x81, 0, 0),
+                event(0x02, 0, i16::MAX),
+                event(0x02, 3, -16384),
+                event(0x01, 2, 1),
+            ]
+            .concat();
+            std::fs::write(&path, events).unwrap();
+
+            let mut device = JoystickDevice::open(&path).unwrap();
+            poll_until_disconnected(&mut device);
+
+            assert_eq!(device.axis(0), 1.0);
+            assert_eq!(device.axis(3), -16384.0 / 32767.0);
+            assert_eq!(device.axis(5), 0.0);
+            assert!(!device.button(0));
+            assert!(device.button(2));
+
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        #[test]
+        fn open_reports_missing_device_as_no_gamepad() {
+            let result = JoystickDevice::open("/dev/input/js-realflight-bridge-missing");
+
+            assert!(matches!(result, Err(GamepadError::NoGamepad)));
+        }
+    }
+}
@@ -19,7 +19,11 @@ use serde::Serialize;
 use thiserror::Error;
 
 /// Errors that can occur when interacting with the RealFlight simulator.
+///
+/// New variants may be added in minor releases, so matches need a wildcard
+/// arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum BridgeError {
     /// Connection to the simulator failed
     #[error("Connection failed: {0}")]
@@ -36,6 +40,68 @@ pub enum BridgeError {
     /// Failed to parse simulator response
     #[error("Parse error for field '{field}': {message}")]
     Parse { field: String, message: String },
+
+    /// The simulator did not respond within the configured I/O timeout
+    #[error("Timed out after {0:?} waiting for the simulator")]
+    Timeout(std::time::Duration),
+
+    /// Operation rejected because it is invalid in the current session state
+    #[error("Invalid state: {0}")]
+    InvalidState(String),
+
+    /// A fixed-rate loop fell behind and skipped this many ticks
+    #[error("Missed {0} tick(s) of the control loop")]
+    MissedDeadline(u64),
+
+    /// The proxy sent a response that does not answer the request, or a peer
+    /// sent a response larger than the configured limit
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+
+    /// RealFlight stopped listening to the RealFlight Link controller after it
+    /// was injected, and it has not been injected again
+    #[error(
+        "RealFlight dropped the RealFlight Link controller; call disable_rc to inject it again"
+    )]
+    ControllerDropped,
+}
+
+impl BridgeError {
+    /// Classifies a [BridgeError::SoapFault]. Returns `None` for other errors.
+    pub fn fault_kind(&self) -> Option<SoapFaultKind> {
+        match self {
+            BridgeError::SoapFault(message) => Some(SoapFaultKind::classify(message)),
+            _ => None,
+        }
+    }
+}
+
+/// Known faults reported by RealFlight Link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoapFaultKind {
+    /// The RealFlight Link controller is already injected, typically left behind
+    /// by a previous process ("Preexisting controller reference")
+    PreexistingController,
+    /// The RealFlight Link controller has not been injected yet
+    ControllerNotInstantiated,
+    /// There is no original controller device to restore
+    NoOriginalController,
+    /// Any fault not listed above
+    Other,
+}
+
+impl SoapFaultKind {
+    /// Classifies the fault message returned by the simulator.
+    pub fn classify(message: &str) -> Self {
+        match message.trim() {
+            "Preexisting controller reference" => SoapFaultKind::PreexistingController,
+            "RealFlight Link controller has not been instantiated" => {
+                SoapFaultKind::ControllerNotInstantiated
+            }
+            "Pointer to original controller device is null" => SoapFaultKind::NoOriginalController,
+            _ => SoapFaultKind::Other,
+        }
+    }
 }
 
 /// Conditional type aliases for physical quantities.
@@ -74,42 +140,135 @@ pub use decoders::extract_element;
 #[cfg(any(test, feature = "bench-internals"))]
 pub use encoders::encode_control_inputs;
 
+#[cfg(any(test, feature = "bench-internals"))]
+pub use soap_client::xml::{build_http_request, encode_envelope};
+
+#[cfg(all(feature = "rt-tokio", any(test, feature = "bench-internals")))]
+pub use soap_client::handoff::{HandoffReceiver, HandoffSender, channel as handoff_channel};
+
 pub mod bridge;
+pub mod clock;
+pub mod conformance;
+pub mod constants;
+pub mod control;
 mod decoders;
+pub mod diagnostics;
 mod encoders;
+pub mod estimation;
+pub mod ffi;
+#[doc(hidden)]
+pub mod fuzz;
+pub mod gamepad;
+pub mod logging;
+pub mod mock;
+pub mod phase;
+pub mod record;
+pub mod safety;
 mod soap_client;
 mod statistics;
+pub mod test_support;
+pub mod timing;
 
 pub use statistics::Statistics;
 pub(crate) use statistics::StatisticsEngine;
 
-/// Default RealFlight simulator address (localhost on standard port)
-pub const DEFAULT_SIMULATOR_HOST: &str = "127.0.0.1:18083";
+#[doc(inline)]
+pub use constants::DEFAULT_SIMULATOR_HOST;
 
 #[doc(inline)]
 pub use bridge::RealFlightBridge;
 #[doc(inline)]
+pub use bridge::armed::{ArmPolicy, ArmedBridge};
+#[doc(inline)]
+pub use bridge::failsafe::{
+    DeadManExpiry, DeadMansSwitch, FailsafeBridge, FailsafeConfig, FailsafeStatistics,
+};
+#[doc(inline)]
+pub use bridge::local::ChannelLimits;
+#[doc(inline)]
 pub use bridge::local::Configuration;
 #[doc(inline)]
+pub use bridge::local::ConnectionMode;
+#[doc(inline)]
+pub use bridge::local::InitState;
+#[doc(inline)]
+pub use bridge::local::PoolSize;
+#[doc(inline)]
+pub use bridge::local::RcGuard;
+#[doc(inline)]
 pub use bridge::local::RealFlightLocalBridge;
 #[doc(inline)]
-pub use bridge::remote::RealFlightRemoteBridge;
+pub use bridge::local::RealFlightVersion;
+#[doc(inline)]
+pub use bridge::local::SafeStart;
+#[doc(inline)]
+pub use bridge::local::{ControlOutcome, LoopReport};
+#[doc(inline)]
+pub use bridge::local::{RequestTap, SoapResponseSummary};
+#[doc(inline)]
+pub use bridge::local::{Transcript, TranscriptEntry};
+#[doc(inline)]
+pub use bridge::managed::{ControllerDropout, CrashEvent, ManagedBridge, RcState, ReinjectBackoff};
+#[doc(inline)]
+pub use bridge::multi::{AircraftStatistics, MultiBridge, MultiStatistics};
+#[doc(inline)]
+pub use bridge::remote::{
+    ProtocolInfo, RealFlightRemoteBridge, RealFlightRemoteBridgeBuilder, ReconnectPolicy,
+};
 
 // Async exports (requires rt-tokio feature)
 #[cfg(feature = "rt-tokio")]
 #[doc(inline)]
-pub use bridge::AsyncBridge;
+pub use bridge::local::{AsyncLocalBridge, AsyncLocalBridgeBuilder, PoolMode};
 #[cfg(feature = "rt-tokio")]
 #[doc(inline)]
-pub use bridge::local::{AsyncLocalBridge, AsyncLocalBridgeBuilder};
+pub use bridge::multi::AsyncMultiBridge;
 #[cfg(feature = "rt-tokio")]
 #[doc(inline)]
 pub use bridge::remote::{AsyncRemoteBridge, AsyncRemoteBridgeBuilder};
+#[cfg(feature = "rt-tokio")]
+#[doc(inline)]
+pub use bridge::scheduler::ControlScheduler;
+
+// MAVLink HIL adapter (requires mavlink feature)
+#[cfg(feature = "mavlink")]
+#[doc(inline)]
+pub use bridge::mavlink::{
+    ChannelMap, ChannelMapping, GeoOrigin, HilActuatorControls, HilGps, HilLink, HilMessages,
+    HilSensor, HilStateQuaternion, MavlinkAdapter,
+};
+#[cfg(feature = "rt-tokio")]
+#[doc(inline)]
+pub use bridge::{AsyncBridge, DynAsyncBridge};
+
+// Raw SOAP access (requires raw-soap feature)
+#[cfg(feature = "raw-soap")]
+#[doc(inline)]
+pub use soap_client::tcp::TcpSoapClient;
+#[cfg(all(feature = "bench-internals", not(feature = "raw-soap")))]
+#[doc(hidden)]
+pub use soap_client::tcp::TcpSoapClient;
+#[cfg(all(feature = "raw-soap", feature = "rt-tokio"))]
+#[doc(inline)]
+pub use soap_client::tcp_async::AsyncTcpSoapClient;
+
+// SOAP client traits (requires raw-soap or custom-transport feature)
+#[cfg(all(
+    any(feature = "raw-soap", feature = "custom-transport"),
+    feature = "rt-tokio"
+))]
+#[doc(inline)]
+pub use soap_client::AsyncSoapClient;
+#[cfg(any(feature = "raw-soap", feature = "custom-transport"))]
+#[doc(inline)]
+pub use soap_client::{SoapClient, SoapResponse, Wait};
 
 // Re-export for binary (not part of public API)
 #[cfg(feature = "rt-tokio")]
 #[doc(hidden)]
-pub use bridge::proxy::AsyncProxyServer;
+pub use bridge::proxy::{
+    AsyncProxyServer, ClientStats, LinkFailsafe, LogEvents, ProxyEvents, RateLimit, SimModel,
+};
 
 /// Control inputs for the RealFlight simulator using the standard RC channel mapping.
 /// Each channel value should be between 0.0 (minimum) and 1.0 (maximum).
@@ -159,9 +318,27 @@ pub struct ControlInputs {
     pub channels: [f32; 12],
 }
 
+impl ControlInputs {
+    /// Throttle (channel 2) at 0 and every other channel centered.
+    pub(crate) fn neutral() -> Self {
+        let mut neutral = ControlInputs {
+            channels: [0.5; 12],
+        };
+        neutral.channels[2] = 0.0;
+        neutral
+    }
+
+    /// Sets the channels `commands` sets, leaving the others as they are.
+    pub fn merge(&mut self, commands: &control::ChannelCommands) {
+        for (channel, value) in commands.iter() {
+            self.channels[channel] = value;
+        }
+    }
+}
+
 /// Represents the complete state of the simulated aircraft in RealFlight.
 /// Physical quantities use metric units (strongly-typed with `uom` feature, raw f32 otherwise).
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
 pub struct SimulatorState {
     /// Previous control inputs that led to this state
     pub previous_inputs: ControlInputs,
@@ -0,0 +1,508 @@
+//! CSV flight logs.
+//!
+//! This module requires the `csv-log` feature.
+
+#![cfg(feature = "csv-log")]
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::{ControlInputs, SimulatorState};
+
+/// Columns written by a [CsvLogger].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum FieldSelection {
+    /// Every [SimulatorState] field except `previous_inputs`, followed by the
+    /// twelve channels sent
+    #[default]
+    All,
+    /// Only the named columns, in the order given
+    ///
+    /// Names are the [SimulatorState] field names, e.g. `altitude_asl`, and
+    /// `channel_1` to `channel_12` for the channels sent.
+    Named(Vec<String>),
+}
+
+impl FieldSelection {
+    /// Selects the named columns.
+    pub fn named<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        FieldSelection::Named(names.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Writes one CSV row per simulator frame, for loading into pandas, a
+/// spreadsheet or similar.
+///
+/// The header names each column after its field, with its unit in brackets,
+/// e.g. `airspeed [m/s]`. Units follow the build: angles are written in
+/// degrees and fuel in ounces as RealFlight reports them, or in radians and
+/// liters with the `uom` feature. Rows are buffered and written out when the
+/// buffer fills, on [flush](Self::flush) and when the logger is dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::logging::{CsvLogger, FieldSelection};
+/// use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+/// use std::error::Error;
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///     let bridge = RealFlightLocalBridge::new()?;
+///     let fields = FieldSelection::named(["current_physics_time", "altitude_agl", "channel_3"]);
+///     let mut logger = CsvLogger::create("flight.csv", fields)?;
+///
+///     let mut inputs = ControlInputs::default();
+///     inputs.channels[2] = 1.0;
+///     for _ in 0..1000 {
+///         let state = bridge.exchange_data(&inputs)?;
+///         logger.log(&state, &inputs)?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct CsvLogger {
+    writer: BufWriter<File>,
+    columns: Vec<&'static Column>,
+    row: String,
+}
+
+impl CsvLogger {
+    /// Creates the file at `path`, replacing any existing one, and writes the
+    /// header row.
+    ///
+    /// Fails with [io::ErrorKind::InvalidInput] if `fields` names an unknown
+    /// column.
+    pub fn create(path: impl AsRef<Path>, fields: FieldSelection) -> io::Result<Self> {
+        let columns = match fields {
+            FieldSelection::All => COLUMNS.iter().collect(),
+            FieldSelection::Named(names) => names
+                .iter()
+                .map(|name| {
+                    COLUMNS
+                        .iter()
+                        .find(|column| column.name == name)
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                format!("unknown flight log field '{}'", name),
+                            )
+                        })
+                })
+                .collect::<io::Result<_>>()?,
+        };
+
+        let mut logger = CsvLogger {
+            writer: BufWriter::new(File::create(path)?),
+            columns,
+            row: String::new(),
+        };
+        for (i, column) in logger.columns.iter().enumerate() {
+            if i > 0 {
+                logger.row.push(',');
+            }
+            match column.unit {
+                "" => push_escaped(&mut logger.row, column.name),
+                unit => push_escaped(&mut logger.row, &format!("{} [{}]", column.name, unit)),
+            }
+        }
+        logger.end_row()?;
+        Ok(logger)
+    }
+
+    /// Appends a row for `state` and the `inputs` sent with the exchange that
+    /// returned it.
+    pub fn log(&mut self, state: &SimulatorState, inputs: &ControlInputs) -> io::Result<()> {
+        let frame = Frame { state, inputs };
+        for (i, column) in self.columns.iter().enumerate() {
+            if i > 0 {
+                self.row.push(',');
+            }
+            (column.write)(&frame, &mut self.row);
+        }
+        self.end_row()
+    }
+
+    /// Writes buffered rows to the file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn end_row(&mut self) -> io::Result<()> {
+        self.row.push('\n');
+        let written = self.writer.write_all(self.row.as_bytes());
+        self.row.clear();
+        written
+    }
+}
+
+impl Drop for CsvLogger {
+    fn drop(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            log::warn!("Failed to flush flight log: {}", e);
+        }
+    }
+}
+
+/// Appends `value` to a row, quoted if it contains a separator or quote.
+fn push_escaped(row: &mut String, value: &str) {
+    if value.contains([',', '"', '\n', '\r']) {
+        row.push('"');
+        row.push_str(&value.replace('"', "\"\""));
+        row.push('"');
+    } else {
+        row.push_str(value);
+    }
+}
+
+/// What one row is written from.
+struct Frame<'a> {
+    state: &'a SimulatorState,
+    inputs: &'a ControlInputs,
+}
+
+/// A column of the log.
+struct Column {
+    name: &'static str,
+    /// Empty for unitless columns
+    unit: &'static str,
+    write: fn(&Frame, &mut String),
+}
+
+/// Column of a [SimulatorState] field converted by one of the [units] modules.
+macro_rules! quantity {
+    ($field:ident, $kind:ident) => {
+        Column {
+            name: stringify!($field),
+            unit: units::$kind::UNIT,
+            write: |frame, row| {
+                let _ = write!(row, "{}", units::$kind::value(frame.state.$field));
+            },
+        }
+    };
+}
+
+/// Column of a [SimulatorState] field written as is.
+macro_rules! plain {
+    ($field:ident, $unit:expr) => {
+        Column {
+            name: stringify!($field),
+            unit: $unit,
+            write: |frame, row| {
+                let _ = write!(row, "{}", frame.state.$field);
+            },
+        }
+    };
+}
+
+/// Column of a channel sent; `$index` counts from 1.
+macro_rules! channel {
+    ($index:literal) => {
+        Column {
+            name: concat!("channel_", $index),
+            unit: "",
+            write: |frame, row| {
+                let _ = write!(row, "{}", frame.inputs.channels[$index - 1]);
+            },
+        }
+    };
+}
+
+/// Every column, in [FieldSelection::All] order.
+static COLUMNS: &[Column] = &[
+    quantity!(current_physics_time, time),
+    plain!(current_physics_speed_multiplier, ""),
+    quantity!(airspeed, velocity),
+    quantity!(altitude_asl, length),
+    quantity!(altitude_agl, length),
+    quantity!(groundspeed, velocity),
+    quantity!(pitch_rate, angular_velocity),
+    quantity!(roll_rate, angular_velocity),
+    quantity!(yaw_rate, angular_velocity),
+    quantity!(azimuth, angle),
+    quantity!(inclination, angle),
+    quantity!(roll, angle),
+    plain!(orientation_quaternion_x, ""),
+    plain!(orientation_quaternion_y, ""),
+    plain!(orientation_quaternion_z, ""),
+    plain!(orientation_quaternion_w, ""),
+    quantity!(aircraft_position_x, length),
+    quantity!(aircraft_position_y, length),
+    quantity!(velocity_world_u, velocity),
+    quantity!(velocity_world_v, velocity),
+    quantity!(velocity_world_w, velocity),
+    quantity!(velocity_body_u, velocity),
+    quantity!(velocity_body_v, velocity),
+    quantity!(velocity_body_w, velocity),
+    quantity!(acceleration_world_ax, acceleration),
+    quantity!(acceleration_world_ay, acceleration),
+    quantity!(acceleration_world_az, acceleration),
+    quantity!(acceleration_body_ax, acceleration),
+    quantity!(acceleration_body_ay, acceleration),
+    quantity!(acceleration_body_az, acceleration),
+    quantity!(wind_x, velocity),
+    quantity!(wind_y, velocity),
+    quantity!(wind_z, velocity),
+    plain!(prop_rpm, "rpm"),
+    plain!(heli_main_rotor_rpm, "rpm"),
+    quantity!(battery_voltage, electric_potential),
+    quantity!(battery_current_draw, electric_current),
+    quantity!(battery_remaining_capacity, electric_charge),
+    quantity!(fuel_remaining, volume),
+    plain!(is_locked, ""),
+    plain!(has_lost_components, ""),
+    plain!(an_engine_is_running, ""),
+    plain!(is_touching_ground, ""),
+    plain!(flight_axis_controller_is_active, ""),
+    plain!(reset_button_has_been_pressed, ""),
+    Column {
+        name: "current_aircraft_status",
+        unit: "",
+        write: |frame, row| push_escaped(row, &frame.state.current_aircraft_status),
+    },
+    channel!(1),
+    channel!(2),
+    channel!(3),
+    channel!(4),
+    channel!(5),
+    channel!(6),
+    channel!(7),
+    channel!(8),
+    channel!(9),
+    channel!(10),
+    channel!(11),
+    channel!(12),
+];
+
+/// Units and raw values of [SimulatorState] quantities.
+#[cfg(not(feature = "uom"))]
+mod units {
+    macro_rules! raw {
+        ($($kind:ident: $unit:literal),* $(,)?) => {$(
+            pub(super) mod $kind {
+                pub(in super::super) const UNIT: &str = $unit;
+
+                pub(in super::super) fn value(v: f32) -> f32 {
+                    v
+                }
+            }
+        )*};
+    }
+
+    raw! {
+        velocity: "m/s",
+        length: "m",
+        angular_velocity: "deg/s",
+        angle: "deg",
+        acceleration: "m/s^2",
+        electric_potential: "V",
+        electric_current: "A",
+        electric_charge: "mAh",
+        volume: "oz",
+        time: "s",
+    }
+}
+
+/// Units and raw values of [SimulatorState] quantities.
+#[cfg(feature = "uom")]
+mod units {
+    macro_rules! si {
+        ($($kind:ident: $quantity:ident in $unit:ident as $name:literal),* $(,)?) => {$(
+            pub(super) mod $kind {
+                pub(in super::super) const UNIT: &str = $name;
+
+                pub(in super::super) fn value(v: uom::si::f32::$quantity) -> f32 {
+                    v.get::<uom::si::$kind::$unit>()
+                }
+            }
+        )*};
+    }
+
+    si! {
+        velocity: Velocity in meter_per_second as "m/s",
+        length: Length in meter as "m",
+        angular_velocity: AngularVelocity in radian_per_second as "rad/s",
+        angle: Angle in radian as "rad",
+        acceleration: Acceleration in meter_per_second_squared as "m/s^2",
+        electric_potential: ElectricPotential in volt as "V",
+        electric_current: ElectricCurrent in ampere as "A",
+        electric_charge: ElectricCharge in milliampere_hour as "mAh",
+        volume: Volume in liter as "L",
+        time: Time in second as "s",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_simulator_state;
+    use std::path::PathBuf;
+
+    static SIM_STATE_RESPONSE: &str = include_str!("../../testdata/responses/return-data-200.xml");
+
+    fn log_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "realflight-bridge-{}-{}.csv",
+            std::process::id(),
+            name
+        ))
+    }
+
+    /// Splits CSV text into rows of unquoted fields.
+    fn parse(text: &str) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut chars = text.chars().peekable();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match (c, quoted) {
+                ('"', true) if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                ('"', _) => quoted = !quoted,
+                (',', false) => row.push(std::mem::take(&mut field)),
+                ('\n', false) => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                (c, _) => field.push(c),
+            }
+        }
+        rows
+    }
+
+    fn write_frames(name: &str, fields: FieldSelection, frames: usize) -> Vec<Vec<String>> {
+        let path = log_path(name);
+        let mut logger = CsvLogger::create(&path, fields).unwrap();
+        let mut state = decode_simulator_state(SIM_STATE_RESPONSE).unwrap();
+        let mut inputs = ControlInputs::default();
+        for i in 0..frames {
+            state.prop_rpm = 1000.0 * i as f32;
+            inputs.channels[2] = i as f32 / 10.0;
+            logger.log(&state, &inputs).unwrap();
+        }
+        // Rows still buffered are written on drop
+        drop(logger);
+
+        let rows = parse(&std::fs::read_to_string(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        rows
+    }
+
+    fn column(rows: &[Vec<String>], header: &str) -> Vec<String> {
+        let index = rows[0]
+            .iter()
+            .position(|h| h == header)
+            .unwrap_or_else(|| panic!("no column {}", header));
+        rows[1..].iter().map(|row| row[index].clone()).collect()
+    }
+
+    #[test]
+    fn all_fields_header_has_units() {
+        let rows = write_frames("all", FieldSelection::All, 3);
+
+        assert_eq!(rows.len(), 4);
+        assert!(rows.iter().all(|row| row.len() == 58));
+        assert_eq!(rows[0][0], "current_physics_time [s]");
+        assert!(rows[0].contains(&"airspeed [m/s]".to_string()));
+        assert!(rows[0].contains(&"acceleration_body_az [m/s^2]".to_string()));
+        assert!(rows[0].contains(&"battery_remaining_capacity [mAh]".to_string()));
+        assert!(rows[0].contains(&"prop_rpm [rpm]".to_string()));
+        assert!(rows[0].contains(&"is_touching_ground".to_string()));
+        assert_eq!(rows[0][57], "channel_12");
+        #[cfg(not(feature = "uom"))]
+        {
+            assert!(rows[0].contains(&"azimuth [deg]".to_string()));
+            assert!(rows[0].contains(&"roll_rate [deg/s]".to_string()));
+            assert!(rows[0].contains(&"fuel_remaining [oz]".to_string()));
+        }
+        #[cfg(feature = "uom")]
+        {
+            assert!(rows[0].contains(&"azimuth [rad]".to_string()));
+            assert!(rows[0].contains(&"roll_rate [rad/s]".to_string()));
+            assert!(rows[0].contains(&"fuel_remaining [L]".to_string()));
+        }
+    }
+
+    #[test]
+    fn rows_hold_state_and_inputs() {
+        let rows = write_frames("values", FieldSelection::All, 3);
+
+        let altitude: Vec<f32> = column(&rows, "altitude_asl [m]")
+            .iter()
+            .map(|v| v.parse().unwrap())
+            .collect();
+        assert_eq!(altitude, [1127.371; 3]);
+        assert_eq!(column(&rows, "prop_rpm [rpm]"), ["0", "1000", "2000"]);
+        assert_eq!(column(&rows, "channel_3"), ["0", "0.1", "0.2"]);
+        assert_eq!(column(&rows, "channel_12"), ["0", "0", "0"]);
+        assert_eq!(column(&rows, "an_engine_is_running"), ["true"; 3]);
+        assert_eq!(
+            column(&rows, "current_aircraft_status"),
+            ["CAS-WAITINGTOLAUNCH"; 3]
+        );
+
+        #[cfg(not(feature = "uom"))]
+        assert_eq!(column(&rows, "azimuth [deg]")[0], "-89.607056");
+        #[cfg(feature = "uom")]
+        {
+            let azimuth: f32 = column(&rows, "azimuth [rad]")[0].parse().unwrap();
+            assert!((azimuth - (-89.607056f32).to_radians()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn named_fields_keep_given_order() {
+        let fields = FieldSelection::named(["channel_3", "altitude_agl", "is_locked"]);
+        let rows = write_frames("named", fields, 2);
+
+        assert_eq!(rows[0], ["channel_3", "altitude_agl [m]", "is_locked"]);
+        assert_eq!(rows[1], ["0", "0.26630992", "false"]);
+        assert_eq!(rows[2], ["0.1", "0.26630992", "false"]);
+    }
+
+    #[test]
+    fn quotes_status_with_separators() {
+        let path = log_path("quoted");
+        let mut logger =
+            CsvLogger::create(&path, FieldSelection::named(["current_aircraft_status"])).unwrap();
+        let state = SimulatorState {
+            current_aircraft_status: "CAS-CRASHED, \"badly\"".to_string(),
+            ..Default::default()
+        };
+        logger.log(&state, &ControlInputs::default()).unwrap();
+        logger.flush().unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            text,
+            "current_aircraft_status\n\"CAS-CRASHED, \"\"badly\"\"\"\n"
+        );
+        assert_eq!(parse(&text)[1], ["CAS-CRASHED, \"badly\""]);
+
+        drop(logger);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_field() {
+        let path = log_path("unknown");
+        let result = CsvLogger::create(&path, FieldSelection::named(["altitude", "airspeed"]));
+
+        match result {
+            Err(e) => {
+                assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+                assert!(e.to_string().contains("altitude"));
+            }
+            Ok(_) => panic!("expected unknown field to be rejected"),
+        }
+        assert!(!path.exists());
+    }
+}
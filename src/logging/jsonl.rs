@@ -0,0 +1,409 @@
+//! JSON lines telemetry streams.
+//!
+//! This module requires the `jsonl` feature.
+
+#![cfg(feature = "jsonl")]
+
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+#[cfg(feature = "rt-tokio")]
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::encoders::units;
+use crate::{ControlInputs, SimulatorState};
+
+/// Writes each simulator frame as one line of JSON, for piping into tools
+/// such as Foxglove or PlotJuggler.
+///
+/// Every line is an object with three keys:
+///
+/// * `timestamp`: seconds since the Unix epoch, as a float
+/// * `inputs`: the [ControlInputs] sent, as `{"channels": [...]}`
+/// * `state`: the [SimulatorState] returned, keyed by field name
+///
+/// State values are plain numbers in the units documented on each
+/// [SimulatorState] field, the units RealFlight reports, so a log reads the
+/// same whether or not the `uom` feature is enabled.
+///
+/// Lines are written to `W` as they are logged; wrap a file in a
+/// [BufWriter](std::io::BufWriter) to batch them. A sink is [Send] when `W`
+/// is, so it can be moved onto the task or thread driving a bridge. With
+/// `rt-tokio`, [AsyncJsonlSink] writes to a [tokio::io::AsyncWrite] instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::logging::JsonlSink;
+/// use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+/// use std::error::Error;
+/// use std::time::SystemTime;
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///     let bridge = RealFlightLocalBridge::new()?;
+///     let mut sink = JsonlSink::new(std::io::stdout().lock());
+///
+///     let inputs = ControlInputs::default();
+///     for _ in 0..1000 {
+///         let state = bridge.exchange_data(&inputs)?;
+///         sink.write(SystemTime::now(), &inputs, &state)?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct JsonlSink<W> {
+    writer: W,
+    line: Vec<u8>,
+}
+
+impl<W: Write> JsonlSink<W> {
+    /// Creates a sink writing lines to `writer`.
+    pub fn new(writer: W) -> Self {
+        JsonlSink {
+            writer,
+            line: Vec::new(),
+        }
+    }
+
+    /// Writes one line for `state` and the `inputs` sent with the exchange
+    /// that returned it, stamped with `timestamp`.
+    pub fn write(
+        &mut self,
+        timestamp: SystemTime,
+        inputs: &ControlInputs,
+        state: &SimulatorState,
+    ) -> io::Result<()> {
+        encode_line(&mut self.line, timestamp, inputs, state)?;
+        self.writer.write_all(&self.line)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Async variant of [JsonlSink], writing to a [tokio::io::AsyncWrite].
+///
+/// This type requires the `rt-tokio` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::logging::AsyncJsonlSink;
+/// use realflight_bridge::{AsyncBridge, AsyncLocalBridge, ControlInputs};
+/// use std::time::SystemTime;
+/// use tokio::io::BufWriter;
+/// use tokio::net::TcpStream;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let bridge = AsyncLocalBridge::new().await?;
+///     // Stream to a PlotJuggler or Foxglove bridge listening locally
+///     let stream = TcpStream::connect("127.0.0.1:9870").await?;
+///     let mut sink = AsyncJsonlSink::new(BufWriter::new(stream));
+///
+///     let inputs = ControlInputs::default();
+///     for _ in 0..1000 {
+///         let state = bridge.exchange_data(&inputs).await?;
+///         sink.write(SystemTime::now(), &inputs, &state).await?;
+///     }
+///     sink.flush().await?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "rt-tokio")]
+pub struct AsyncJsonlSink<W> {
+    writer: W,
+    line: Vec<u8>,
+}
+
+#[cfg(feature = "rt-tokio")]
+impl<W: AsyncWrite + Unpin> AsyncJsonlSink<W> {
+    /// Creates a sink writing lines to `writer`.
+    pub fn new(writer: W) -> Self {
+        AsyncJsonlSink {
+            writer,
+            line: Vec::new(),
+        }
+    }
+
+    /// Async equivalent of [JsonlSink::write].
+    pub async fn write(
+        &mut self,
+        timestamp: SystemTime,
+        inputs: &ControlInputs,
+        state: &SimulatorState,
+    ) -> io::Result<()> {
+        encode_line(&mut self.line, timestamp, inputs, state)?;
+        self.writer.write_all(&self.line).await
+    }
+
+    /// Flushes the underlying writer.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush().await
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Replaces the contents of `line` with the JSON line for one frame.
+fn encode_line(
+    line: &mut Vec<u8>,
+    timestamp: SystemTime,
+    inputs: &ControlInputs,
+    state: &SimulatorState,
+) -> io::Result<()> {
+    line.clear();
+    let record = Record {
+        timestamp,
+        inputs,
+        state: State(state),
+    };
+    serde_json::to_writer(&mut *line, &record)?;
+    line.push(b'\n');
+    Ok(())
+}
+
+/// One line of the log.
+struct Record<'a> {
+    timestamp: SystemTime,
+    inputs: &'a ControlInputs,
+    state: State<'a>,
+}
+
+impl Serialize for Record<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let timestamp = match self.timestamp.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs_f64(),
+            Err(e) => -e.duration().as_secs_f64(),
+        };
+        let mut record = serializer.serialize_struct("Record", 3)?;
+        record.serialize_field("timestamp", &timestamp)?;
+        record.serialize_field("inputs", self.inputs)?;
+        record.serialize_field("state", &self.state)?;
+        record.end()
+    }
+}
+
+/// [SimulatorState] serialized with quantities as plain numbers in the units
+/// documented on its fields, in declaration order.
+struct State<'a>(&'a SimulatorState);
+
+/// Serializes each listed field, converting those tagged with a [units]
+/// function.
+macro_rules! serialize_fields {
+    (@value $value:expr) => {
+        &$value
+    };
+    (@value $value:expr, $kind:ident) => {
+        &units::$kind($value)
+    };
+    ($fields:ident, $state:ident; $($field:ident $(=> $kind:ident)?),* $(,)?) => {
+        $(
+            $fields.serialize_field(
+                stringify!($field),
+                serialize_fields!(@value $state.$field $(, $kind)?),
+            )?;
+        )*
+    };
+}
+
+impl Serialize for State<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let state = self.0;
+        let mut fields = serializer.serialize_struct("SimulatorState", 47)?;
+        serialize_fields!(fields, state;
+            previous_inputs,
+            airspeed => velocity,
+            altitude_asl => length,
+            altitude_agl => length,
+            groundspeed => velocity,
+            pitch_rate => angular_velocity,
+            roll_rate => angular_velocity,
+            yaw_rate => angular_velocity,
+            azimuth => angle,
+            inclination => angle,
+            roll => angle,
+            aircraft_position_x => length,
+            aircraft_position_y => length,
+            velocity_world_u => velocity,
+            velocity_world_v => velocity,
+            velocity_world_w => velocity,
+            velocity_body_u => velocity,
+            velocity_body_v => velocity,
+            velocity_body_w => velocity,
+            acceleration_world_ax => acceleration,
+            acceleration_world_ay => acceleration,
+            acceleration_world_az => acceleration,
+            acceleration_body_ax => acceleration,
+            acceleration_body_ay => acceleration,
+            acceleration_body_az => acceleration,
+            wind_x => velocity,
+            wind_y => velocity,
+            wind_z => velocity,
+            prop_rpm,
+            heli_main_rotor_rpm,
+            battery_voltage => electric_potential,
+            battery_current_draw => electric_current,
+            battery_remaining_capacity => electric_charge,
+            fuel_remaining => volume,
+            is_locked,
+            has_lost_components,
+            an_engine_is_running,
+            is_touching_ground,
+            current_aircraft_status,
+            current_physics_time => time,
+            current_physics_speed_multiplier,
+            orientation_quaternion_x,
+            orientation_quaternion_y,
+            orientation_quaternion_z,
+            orientation_quaternion_w,
+            flight_axis_controller_is_active,
+            reset_button_has_been_pressed,
+        );
+        fields.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_simulator_state;
+    use serde_json::Value;
+    use std::time::Duration;
+
+    static SIM_STATE_RESPONSE: &str = include_str!("../../testdata/responses/return-data-200.xml");
+
+    /// The line logged for the fixture state, the same in every build.
+    static FIXTURE_LINE: &str = concat!(
+        r#"{"timestamp":1700000000.25,"inputs":{"channels":[0.0,0.0,0.75,0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0]},"state":{"previous_inputs":{"channels":[0.5,0.5,0.5,0.5,0.5,0.5,0.5,0.5,0.5,0.5,0.5,0.0]},"airspeed":0.040872246,"altitude_asl":1127.371,"altitude_agl":0.26630992,"groundspeed":0.0000046434448,"pitch_rate":0.0013803536,"roll_rate":-0.000032227897,"yaw_rate":0.001473751,"azimuth":-89.607056,"inclination":1.5332782,"roll":-0.74712425,"aircraft_position_x":5575.6807,"aircraft_position_y":1715.9622,"velocity_world_u":-0.0000020055827,"velocity_world_v":0.000004187985,"velocity_world_w":0.040872246,"velocity_body_u":-0.0010894691,"velocity_body_v":-0.0005307267,"velocity_body_w":0.040854275,"acceleration_world_ax":-0.00048305094,"acceleration_world_ay":0.0010086894,"acceleration_world_az":9.84421,"acceleration_body_ax":-0.00017693639,"acceleration_body_ay":-0.00008662045,"acceleration_body_az":0.044223785,"wind_x":0.0,"wind_y":0.0,"wind_z":0.0,"prop_rpm":47.404716,"heli_main_rotor_rpm":-1.0,"battery_voltage":12.599982,"battery_current_draw":0.0,"battery_remaining_capacity":3999.9907,"fuel_remaining":-1.0,"is_locked":false,"has_lost_components":false,"an_engine_is_running":true,"is_touching_ground":false,"current_aircraft_status":"CAS-WAITINGTOLAUNCH","current_physics_time":72263.414,"current_physics_speed_multiplier":1.0,"orientation_quaternion_x":0.0048992797,"orientation_quaternion_y":-0.01405397,"orientation_quaternion_z":-0.7046618,"orientation_quaternion_w":0.7093873,"flight_axis_controller_is_active":true,"reset_button_has_been_pressed":false}}"#,
+        "\n"
+    );
+
+    fn fixture() -> (SystemTime, ControlInputs, SimulatorState) {
+        let timestamp = UNIX_EPOCH + Duration::from_millis(1_700_000_000_250);
+        let mut inputs = ControlInputs::default();
+        inputs.channels[2] = 0.75;
+        let state = decode_simulator_state(SIM_STATE_RESPONSE).unwrap();
+        (timestamp, inputs, state)
+    }
+
+    fn fixture_line() -> String {
+        let (timestamp, inputs, state) = fixture();
+        let mut sink = JsonlSink::new(Vec::new());
+        sink.write(timestamp, &inputs, &state).unwrap();
+        String::from_utf8(sink.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn line_has_timestamp_inputs_and_state() {
+        let line = fixture_line();
+        assert!(line.ends_with('\n'));
+        let record: Value = serde_json::from_str(&line).unwrap();
+
+        assert!(line.starts_with(r#"{"timestamp":"#));
+        assert_eq!(record.as_object().unwrap().len(), 3);
+        assert_eq!(record["timestamp"], 1_700_000_000.25);
+        assert_eq!(record["inputs"]["channels"].as_array().unwrap().len(), 12);
+        assert_eq!(record["inputs"]["channels"][2], 0.75);
+
+        let state = record["state"].as_object().unwrap();
+        assert_eq!(state.len(), 47);
+        assert!(state["airspeed"].is_f64());
+        assert!(state["is_touching_ground"].is_boolean());
+        assert_eq!(state["current_aircraft_status"], "CAS-WAITINGTOLAUNCH");
+        assert_eq!(
+            state["previous_inputs"]["channels"]
+                .as_array()
+                .unwrap()
+                .len(),
+            12
+        );
+    }
+
+    #[test]
+    fn quantities_are_plain_numbers_in_reported_units() {
+        let record: Value = serde_json::from_str(&fixture_line()).unwrap();
+        let state = &record["state"];
+
+        let number = |field: &str| state[field].as_f64().unwrap() as f32;
+        assert_eq!(number("altitude_asl"), 1127.371);
+        assert_eq!(number("azimuth"), -89.607056);
+        assert_eq!(number("yaw_rate"), 0.001473751);
+        assert_eq!(number("battery_remaining_capacity"), 3999.9907);
+        assert_eq!(number("fuel_remaining"), -1.0);
+        assert_eq!(number("current_physics_time"), 72263.414);
+    }
+
+    #[test]
+    fn line_is_identical_across_features() {
+        assert_eq!(fixture_line(), FIXTURE_LINE);
+    }
+
+    #[cfg(not(feature = "uom"))]
+    #[test]
+    fn state_matches_derived_serialization() {
+        let (_, _, state) = fixture();
+        assert_eq!(
+            serde_json::to_string(&State(&state)).unwrap(),
+            serde_json::to_string(&state).unwrap()
+        );
+    }
+
+    #[test]
+    fn writes_one_line_per_frame() {
+        let (timestamp, inputs, state) = fixture();
+        let mut sink = JsonlSink::new(Vec::new());
+        for i in 0..3 {
+            sink.write(timestamp + Duration::from_secs(i), &inputs, &state)
+                .unwrap();
+        }
+
+        let output = String::from_utf8(sink.into_inner()).unwrap();
+        let timestamps: Vec<f64> = output
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<Value>(line).unwrap()["timestamp"]
+                    .as_f64()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            timestamps,
+            [1_700_000_000.25, 1_700_000_001.25, 1_700_000_002.25]
+        );
+    }
+
+    #[test]
+    fn sink_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<JsonlSink<std::fs::File>>();
+        #[cfg(feature = "rt-tokio")]
+        assert_send::<AsyncJsonlSink<tokio::net::TcpStream>>();
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    #[tokio::test]
+    async fn async_sink_writes_same_lines() {
+        let (timestamp, inputs, state) = fixture();
+        let mut sink = AsyncJsonlSink::new(Vec::new());
+        sink.write(timestamp, &inputs, &state).await.unwrap();
+        sink.flush().await.unwrap();
+
+        assert_eq!(
+            String::from_utf8(sink.into_inner()).unwrap(),
+            fixture_line()
+        );
+    }
+}
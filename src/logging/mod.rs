@@ -0,0 +1,17 @@
+//! Flight logs of simulator frames for post-flight analysis, and telemetry
+//! streams for live tooling.
+//!
+//! [CsvLogger] requires the `csv-log` feature and [JsonlSink] the `jsonl`
+//! feature. [UdpTelemetry] is always available.
+
+mod csv;
+mod jsonl;
+mod udp;
+
+#[cfg(feature = "csv-log")]
+pub use csv::{CsvLogger, FieldSelection};
+#[cfg(all(feature = "jsonl", feature = "rt-tokio"))]
+pub use jsonl::AsyncJsonlSink;
+#[cfg(feature = "jsonl")]
+pub use jsonl::JsonlSink;
+pub use udp::{TelemetryField, TelemetryFormat, UdpTelemetry};
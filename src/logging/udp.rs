@@ -0,0 +1,404 @@
+//! UDP telemetry for ground-station tooling.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+
+use crate::SimulatorState;
+use crate::encoders::units;
+
+/// Knots in one meter/second.
+const KNOTS_PER_METER_PER_SECOND: f32 = 1.943_844;
+/// Feet in one meter.
+const FEET_PER_METER: f32 = 3.280_84;
+/// Value X-Plane sends for a data set slot it has nothing for.
+const XPLANE_UNUSED: f32 = -999.0;
+
+/// Layout of the datagrams sent by a [UdpTelemetry].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TelemetryFormat {
+    /// X-Plane's `DATA` output: the 5-byte header `DATA*`, then one 36-byte
+    /// record per data set, an `i32` index and eight `f32` values, all
+    /// little-endian
+    ///
+    /// Each field fills its slot of the X-Plane data set it belongs to, in
+    /// X-Plane's units, and slots without a field hold -999. See
+    /// [TelemetryField] for the mapping.
+    #[default]
+    XPlane,
+    /// The fields as consecutive little-endian `f32` values, in the order
+    /// selected and the units documented on [SimulatorState]
+    Packed,
+}
+
+/// A [SimulatorState] value a [UdpTelemetry] can send.
+///
+/// The X-Plane data set and slot each field fills are listed with it;
+/// positions and velocities are mapped from RealFlight's north-east-down
+/// world axes onto X-Plane's east-up-south local axes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TelemetryField {
+    /// [SimulatorState::airspeed]; X-Plane set 3 (speeds), `Vind kias`
+    Airspeed,
+    /// [SimulatorState::groundspeed]; X-Plane set 3 (speeds), `Vtrue ktgs`
+    Groundspeed,
+    /// [SimulatorState::pitch_rate]; X-Plane set 16 (angular velocities), `Q rad/s`
+    PitchRate,
+    /// [SimulatorState::roll_rate]; X-Plane set 16 (angular velocities), `P rad/s`
+    RollRate,
+    /// [SimulatorState::yaw_rate]; X-Plane set 16 (angular velocities), `R rad/s`
+    YawRate,
+    /// [SimulatorState::inclination]; X-Plane set 17 (pitch, roll, headings), `pitch deg`
+    Inclination,
+    /// [SimulatorState::roll]; X-Plane set 17 (pitch, roll, headings), `roll deg`
+    Roll,
+    /// [SimulatorState::azimuth]; X-Plane set 17 (pitch, roll, headings), `hding true`
+    Azimuth,
+    /// [SimulatorState::altitude_asl]; X-Plane set 20 (lat, lon, altitude), `alt ftmsl`
+    AltitudeAsl,
+    /// [SimulatorState::altitude_agl]; X-Plane set 20 (lat, lon, altitude), `alt ftagl`
+    AltitudeAgl,
+    /// [SimulatorState::aircraft_position_x]; X-Plane set 21 (location), `z m`, negated
+    AircraftPositionX,
+    /// [SimulatorState::aircraft_position_y]; X-Plane set 21 (location), `x m`
+    AircraftPositionY,
+    /// [SimulatorState::velocity_world_u]; X-Plane set 21 (location), `vZ m/s`, negated
+    VelocityWorldU,
+    /// [SimulatorState::velocity_world_v]; X-Plane set 21 (location), `vX m/s`
+    VelocityWorldV,
+    /// [SimulatorState::velocity_world_w]; X-Plane set 21 (location), `vY m/s`, negated
+    VelocityWorldW,
+    /// [SimulatorState::prop_rpm]; X-Plane set 37 (engine RPM), engine 1
+    PropRpm,
+}
+
+impl TelemetryField {
+    /// Every field, in declaration order; the default selection.
+    pub const ALL: [TelemetryField; 16] = [
+        TelemetryField::Airspeed,
+        TelemetryField::Groundspeed,
+        TelemetryField::PitchRate,
+        TelemetryField::RollRate,
+        TelemetryField::YawRate,
+        TelemetryField::Inclination,
+        TelemetryField::Roll,
+        TelemetryField::Azimuth,
+        TelemetryField::AltitudeAsl,
+        TelemetryField::AltitudeAgl,
+        TelemetryField::AircraftPositionX,
+        TelemetryField::AircraftPositionY,
+        TelemetryField::VelocityWorldU,
+        TelemetryField::VelocityWorldV,
+        TelemetryField::VelocityWorldW,
+        TelemetryField::PropRpm,
+    ];
+
+    /// The value in the units documented on [SimulatorState].
+    fn value(self, state: &SimulatorState) -> f32 {
+        match self {
+            TelemetryField::Airspeed => units::velocity(state.airspeed),
+            TelemetryField::Groundspeed => units::velocity(state.groundspeed),
+            TelemetryField::PitchRate => units::angular_velocity(state.pitch_rate),
+            TelemetryField::RollRate => units::angular_velocity(state.roll_rate),
+            TelemetryField::YawRate => units::angular_velocity(state.yaw_rate),
+            TelemetryField::Inclination => units::angle(state.inclination),
+            TelemetryField::Roll => units::angle(state.roll),
+            TelemetryField::Azimuth => units::angle(state.azimuth),
+            TelemetryField::AltitudeAsl => units::length(state.altitude_asl),
+            TelemetryField::AltitudeAgl => units::length(state.altitude_agl),
+            TelemetryField::AircraftPositionX => units::length(state.aircraft_position_x),
+            TelemetryField::AircraftPositionY => units::length(state.aircraft_position_y),
+            TelemetryField::VelocityWorldU => units::velocity(state.velocity_world_u),
+            TelemetryField::VelocityWorldV => units::velocity(state.velocity_world_v),
+            TelemetryField::VelocityWorldW => units::velocity(state.velocity_world_w),
+            TelemetryField::PropRpm => state.prop_rpm,
+        }
+    }
+
+    /// The X-Plane data set index and slot of the field, and its value in
+    /// X-Plane's units.
+    fn xplane(self, state: &SimulatorState) -> (i32, usize, f32) {
+        let value = self.value(state);
+        match self {
+            TelemetryField::Airspeed => (3, 0, value * KNOTS_PER_METER_PER_SECOND),
+            TelemetryField::Groundspeed => (3, 3, value * KNOTS_PER_METER_PER_SECOND),
+            TelemetryField::PitchRate => (16, 0, value.to_radians()),
+            TelemetryField::RollRate => (16, 1, value.to_radians()),
+            TelemetryField::YawRate => (16, 2, value.to_radians()),
+            TelemetryField::Inclination => (17, 0, value),
+            TelemetryField::Roll => (17, 1, value),
+            TelemetryField::Azimuth => (17, 2, value),
+            TelemetryField::AltitudeAsl => (20, 2, value * FEET_PER_METER),
+            TelemetryField::AltitudeAgl => (20, 3, value * FEET_PER_METER),
+            TelemetryField::AircraftPositionX => (21, 2, -value),
+            TelemetryField::AircraftPositionY => (21, 0, value),
+            TelemetryField::VelocityWorldU => (21, 5, -value),
+            TelemetryField::VelocityWorldV => (21, 3, value),
+            TelemetryField::VelocityWorldW => (21, 4, -value),
+            TelemetryField::PropRpm => (37, 0, value),
+        }
+    }
+}
+
+/// Sends simulator states as UDP datagrams to a ground station or other
+/// telemetry consumer.
+///
+/// Each sent state is one datagram in the chosen [TelemetryFormat], holding
+/// the selected [fields](Self::fields). [every](Self::every) thins the stream
+/// to every Nth state for consumers slower than the control loop.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::logging::{TelemetryField, TelemetryFormat, UdpTelemetry};
+/// use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+/// use std::error::Error;
+/// use std::net::UdpSocket;
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///     let bridge = RealFlightLocalBridge::new()?;
+///     let socket = UdpSocket::bind("0.0.0.0:0")?;
+///     let target = "127.0.0.1:49003".parse()?;
+///     let mut telemetry = UdpTelemetry::new(socket, target, TelemetryFormat::XPlane)
+///         .fields([TelemetryField::Azimuth, TelemetryField::Inclination, TelemetryField::Roll])
+///         .every(10);
+///
+///     let inputs = ControlInputs::default();
+///     loop {
+///         let state = bridge.exchange_data(&inputs)?;
+///         telemetry.send(&state)?;
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct UdpTelemetry {
+    socket: UdpSocket,
+    target: SocketAddr,
+    format: TelemetryFormat,
+    fields: Vec<TelemetryField>,
+    every: u32,
+    /// States offered since the last one sent
+    skipped: u32,
+    datagram: Vec<u8>,
+}
+
+impl UdpTelemetry {
+    /// Sends every state, with [TelemetryField::ALL], from `socket` to `target`.
+    pub fn new(socket: UdpSocket, target: SocketAddr, format: TelemetryFormat) -> Self {
+        UdpTelemetry {
+            socket,
+            target,
+            format,
+            fields: TelemetryField::ALL.to_vec(),
+            every: 1,
+            skipped: 0,
+            datagram: Vec::new(),
+        }
+    }
+
+    /// Selects the fields to send. With [TelemetryFormat::Packed] they are
+    /// sent in the order given.
+    pub fn fields(mut self, fields: impl IntoIterator<Item = TelemetryField>) -> Self {
+        self.fields = fields.into_iter().collect();
+        self
+    }
+
+    /// Sends only every `n`th state, starting with the first; values below 1
+    /// are treated as 1.
+    pub fn every(mut self, n: u32) -> Self {
+        self.every = n.max(1);
+        self
+    }
+
+    /// Sends `state` if it is due, returning whether a datagram was sent.
+    pub fn send(&mut self, state: &SimulatorState) -> io::Result<bool> {
+        let due = self.skipped == 0;
+        self.skipped = (self.skipped + 1) % self.every;
+        if !due {
+            return Ok(false);
+        }
+
+        self.datagram.clear();
+        match self.format {
+            TelemetryFormat::XPlane => encode_xplane(&mut self.datagram, &self.fields, state),
+            TelemetryFormat::Packed => encode_packed(&mut self.datagram, &self.fields, state),
+        }
+        self.socket.send_to(&self.datagram, self.target)?;
+        Ok(true)
+    }
+}
+
+fn encode_xplane(datagram: &mut Vec<u8>, fields: &[TelemetryField], state: &SimulatorState) {
+    let mut sets = BTreeMap::new();
+    for field in fields {
+        let (index, slot, value) = field.xplane(state);
+        sets.entry(index).or_insert([XPLANE_UNUSED; 8])[slot] = value;
+    }
+
+    datagram.extend_from_slice(b"DATA*");
+    for (index, values) in sets {
+        datagram.extend_from_slice(&index.to_le_bytes());
+        for value in values {
+            datagram.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+fn encode_packed(datagram: &mut Vec<u8>, fields: &[TelemetryField], state: &SimulatorState) {
+    for field in fields {
+        datagram.extend_from_slice(&field.value(state).to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_simulator_state;
+    use std::time::Duration;
+
+    static SIM_STATE_RESPONSE: &str = include_str!("../../testdata/responses/return-data-200.xml");
+
+    fn fixture() -> SimulatorState {
+        decode_simulator_state(SIM_STATE_RESPONSE).unwrap()
+    }
+
+    /// A telemetry sink sending to a receiver bound on loopback.
+    fn telemetry(format: TelemetryFormat) -> (UdpTelemetry, UdpSocket) {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let target = receiver.local_addr().unwrap();
+        (UdpTelemetry::new(socket, target, format), receiver)
+    }
+
+    fn receive(receiver: &UdpSocket) -> Vec<u8> {
+        let mut buffer = [0u8; 2048];
+        let len = receiver.recv(&mut buffer).unwrap();
+        buffer[..len].to_vec()
+    }
+
+    fn f32_at(datagram: &[u8], offset: usize) -> f32 {
+        f32::from_le_bytes(datagram[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// Parses X-Plane `DATA` records into their index and values.
+    fn xplane_sets(datagram: &[u8]) -> Vec<(i32, [f32; 8])> {
+        assert_eq!(&datagram[..5], b"DATA*");
+        let records = &datagram[5..];
+        assert_eq!(records.len() % 36, 0);
+        records
+            .chunks(36)
+            .map(|record| {
+                let index = i32::from_le_bytes(record[..4].try_into().unwrap());
+                let values = std::array::from_fn(|slot| f32_at(record, 4 + slot * 4));
+                (index, values)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn packed_sends_selected_fields_in_order() {
+        let (telemetry, receiver) = telemetry(TelemetryFormat::Packed);
+        let mut telemetry = telemetry.fields([
+            TelemetryField::AltitudeAsl,
+            TelemetryField::Azimuth,
+            TelemetryField::PropRpm,
+        ]);
+
+        assert!(telemetry.send(&fixture()).unwrap());
+
+        let datagram = receive(&receiver);
+        assert_eq!(datagram.len(), 12);
+        assert_eq!(f32_at(&datagram, 0), 1127.371);
+        assert_eq!(f32_at(&datagram, 4), -89.607056);
+        assert_eq!(f32_at(&datagram, 8), 47.404716);
+    }
+
+    #[test]
+    fn xplane_fills_data_sets_in_xplane_units() {
+        let (telemetry, receiver) = telemetry(TelemetryFormat::XPlane);
+        let mut telemetry = telemetry.fields([
+            TelemetryField::Roll,
+            TelemetryField::Inclination,
+            TelemetryField::AltitudeAsl,
+            TelemetryField::YawRate,
+        ]);
+
+        telemetry.send(&fixture()).unwrap();
+
+        let sets = xplane_sets(&receive(&receiver));
+        let indexes: Vec<_> = sets.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indexes, [16, 17, 20]);
+
+        let (_, rates) = sets[0];
+        approx::assert_relative_eq!(rates[2], 0.001473751_f32.to_radians(), max_relative = 1e-5);
+        assert_eq!(rates[0], XPLANE_UNUSED);
+
+        let (_, attitude) = sets[1];
+        approx::assert_relative_eq!(attitude[0], 1.5332782, max_relative = 1e-5);
+        approx::assert_relative_eq!(attitude[1], -0.74712425, max_relative = 1e-5);
+        assert_eq!(attitude[2], XPLANE_UNUSED);
+
+        let (_, altitude) = sets[2];
+        approx::assert_relative_eq!(altitude[2], 1127.371 * FEET_PER_METER, max_relative = 1e-5);
+        assert_eq!(altitude[3], XPLANE_UNUSED);
+    }
+
+    #[test]
+    fn xplane_maps_world_axes() {
+        let (telemetry, receiver) = telemetry(TelemetryFormat::XPlane);
+        let mut telemetry = telemetry.fields([
+            TelemetryField::AircraftPositionX,
+            TelemetryField::AircraftPositionY,
+            TelemetryField::VelocityWorldW,
+        ]);
+
+        telemetry.send(&fixture()).unwrap();
+
+        let sets = xplane_sets(&receive(&receiver));
+        assert_eq!(sets.len(), 1);
+        let (index, location) = sets[0];
+        assert_eq!(index, 21);
+        approx::assert_relative_eq!(location[0], 1715.9622, max_relative = 1e-5);
+        approx::assert_relative_eq!(location[2], -5575.6807, max_relative = 1e-5);
+        approx::assert_relative_eq!(location[4], -0.040872246, max_relative = 1e-5);
+    }
+
+    #[test]
+    fn default_selection_sends_every_field() {
+        let (mut telemetry, receiver) = telemetry(TelemetryFormat::Packed);
+
+        telemetry.send(&fixture()).unwrap();
+
+        assert_eq!(receive(&receiver).len(), TelemetryField::ALL.len() * 4);
+    }
+
+    #[test]
+    fn every_sends_one_state_in_n() {
+        let (telemetry, receiver) = telemetry(TelemetryFormat::Packed);
+        let mut telemetry = telemetry.fields([TelemetryField::PropRpm]).every(3);
+        let mut state = fixture();
+
+        let mut sent = Vec::new();
+        for rpm in 0..7 {
+            state.prop_rpm = rpm as f32;
+            sent.push(telemetry.send(&state).unwrap());
+        }
+
+        assert_eq!(sent, [true, false, false, true, false, false, true]);
+        for rpm in [0.0, 3.0, 6.0] {
+            assert_eq!(f32_at(&receive(&receiver), 0), rpm);
+        }
+    }
+
+    #[test]
+    fn every_zero_sends_every_state() {
+        let (telemetry, _receiver) = telemetry(TelemetryFormat::Packed);
+        let mut telemetry = telemetry.every(0);
+
+        assert!(telemetry.send(&fixture()).unwrap());
+        assert!(telemetry.send(&fixture()).unwrap());
+    }
+}
@@ -0,0 +1,830 @@
+//! In-process stand-in for RealFlight, for integration tests in CI.
+//!
+//! [MockSimulator] listens for RealFlight Link SOAP requests on a local port
+//! and answers them the way RealFlight does, so a
+//! [RealFlightLocalBridge](crate::RealFlightLocalBridge) (or any other client) can be pointed at it unchanged. `ExchangeData`
+//! drives a simple aircraft model: channel 2 is throttle, and channels 0, 1
+//! and 3 command roll, pitch and yaw rates around the 0.5 center. Each
+//! exchange advances the model by [TIME_STEP] seconds of physics time, so runs
+//! are repeatable.
+//!
+//! Requests made out of order fail with the faults RealFlight returns:
+//!
+//! | Request | Fault |
+//! |---------|-------|
+//! | `InjectUAVControllerInterface` while injected | "Preexisting controller reference" |
+//! | `ExchangeData` while not injected | "RealFlight Link controller has not been instantiated" |
+//! | `RestoreOriginalControllerDevice` while not injected | "Pointer to original controller device is null" |
+//!
+//! ```
+//! use realflight_bridge::mock::MockSimulator;
+//! use realflight_bridge::{
+//!     BridgeError, Configuration, ControlInputs, RealFlightBridge, RealFlightLocalBridge,
+//! };
+//!
+//! let simulator = MockSimulator::start(0)?;
+//! let bridge = RealFlightLocalBridge::with_configuration(&Configuration {
+//!     simulator_host: simulator.host(),
+//!     ..Default::default()
+//! })?;
+//!
+//! bridge.disable_rc()?;
+//! let mut inputs = ControlInputs::default();
+//! inputs.channels = [0.5; 12];
+//! inputs.channels[2] = 1.0;
+//! let state = bridge.exchange_data(&inputs)?;
+//! assert!(state.an_engine_is_running);
+//! bridge.enable_rc()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! This module requires the `mock` feature.
+
+#![cfg(feature = "mock")]
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::decoders::{
+    extract_element, to_acceleration, to_angle, to_angular_velocity, to_electric_charge,
+    to_electric_current, to_electric_potential, to_length, to_time, to_velocity,
+};
+use crate::encoders::encode_simulator_state;
+use crate::{ControlInputs, SimulatorState};
+
+/// Physics time, in seconds, each `ExchangeData` advances the model by.
+pub const TIME_STEP: f32 = 0.01;
+
+/// Ground elevation above sea level [meters]
+const GROUND_ELEVATION: f32 = 100.0;
+/// Roll and pitch rate at full stick deflection [degrees/second]
+const MAX_RATE: f32 = 90.0;
+/// Forward acceleration at full throttle [meters/second²]
+const MAX_THRUST: f32 = 12.0;
+/// Speed-proportional drag, giving a top speed of 40 m/s [1/second]
+const DRAG: f32 = 0.3;
+/// Airspeed below which the aircraft cannot leave the ground [meters/second]
+const TAKEOFF_SPEED: f32 = 8.0;
+/// Largest bank angle [degrees]
+const MAX_ROLL: f32 = 60.0;
+/// Largest pitch angle [degrees]
+const MAX_PITCH: f32 = 45.0;
+/// Propeller speed at full throttle [revolutions/minute]
+const MAX_RPM: f32 = 12_000.0;
+/// Battery current at full throttle [amperes]
+const MAX_CURRENT: f32 = 40.0;
+/// Full battery charge [milliampere-hours]
+const BATTERY_CAPACITY: f32 = 4000.0;
+
+/// RealFlight stand-in serving RealFlight Link requests on a local port.
+///
+/// Stops listening when dropped.
+pub struct MockSimulator {
+    address: SocketAddr,
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// State shared with the connection threads.
+struct Shared {
+    running: AtomicBool,
+    simulator: Mutex<Simulator>,
+}
+
+/// What RealFlight tracks between requests.
+struct Simulator {
+    controller_injected: bool,
+    aircraft: Aircraft,
+    request_count: usize,
+}
+
+impl MockSimulator {
+    /// Listens on `127.0.0.1:port`, or on a free port if `port` is 0.
+    pub fn start(port: u16) -> io::Result<MockSimulator> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let address = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            running: AtomicBool::new(true),
+            simulator: Mutex::new(Simulator {
+                controller_injected: false,
+                aircraft: Aircraft::default(),
+                request_count: 0,
+            }),
+        });
+
+        let accepting = Arc::clone(&shared);
+        let handle = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if !accepting.running.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let shared = Arc::clone(&accepting);
+                thread::spawn(move || {
+                    // The client hanging up ends the connection either way
+                    let _ = serve(stream, &shared);
+                });
+            }
+        });
+
+        Ok(MockSimulator {
+            address,
+            shared,
+            handle: Some(handle),
+        })
+    }
+
+    /// Address the simulator listens on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Address in the `host:port` form of
+    /// [Configuration::simulator_host](crate::Configuration::simulator_host).
+    pub fn host(&self) -> String {
+        self.address.to_string()
+    }
+
+    /// Whether the RealFlight Link controller is injected, i.e. RC is disabled.
+    pub fn controller_injected(&self) -> bool {
+        self.simulator().controller_injected
+    }
+
+    /// Injects or removes the RealFlight Link controller without a request,
+    /// e.g. to leave it behind as a crashed process would.
+    pub fn set_controller_injected(&self, injected: bool) {
+        self.simulator().controller_injected = injected;
+    }
+
+    /// Current state of the aircraft model.
+    pub fn state(&self) -> SimulatorState {
+        let simulator = self.simulator();
+        simulator.aircraft.state(simulator.controller_injected)
+    }
+
+    /// Number of requests answered, faults included.
+    pub fn request_count(&self) -> usize {
+        self.simulator().request_count
+    }
+
+    fn simulator(&self) -> std::sync::MutexGuard<'_, Simulator> {
+        self.shared.simulator.lock().unwrap()
+    }
+}
+
+impl Drop for MockSimulator {
+    fn drop(&mut self) {
+        self.shared.running.store(false, Ordering::Relaxed);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect(self.address);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Answers requests on one connection until the client hangs up or a
+/// response closes it.
+fn serve(stream: TcpStream, shared: &Shared) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    while let Some(request) = read_request(&mut reader)? {
+        let (status, body) = shared.simulator.lock().unwrap().handle(&request);
+        writer.write_all(http_response(status, &body, request.keep_alive).as_bytes())?;
+        writer.flush()?;
+        if !request.keep_alive || !shared.running.load(Ordering::Relaxed) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// A parsed SOAP request.
+struct Request {
+    action: String,
+    body: String,
+    keep_alive: bool,
+}
+
+/// Reads one HTTP request, or `None` once the client hangs up.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<Request>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    let mut content_length = 0;
+    let mut action = None;
+    let mut keep_alive = false;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.parse().unwrap_or(0),
+            "soapaction" => action = Some(value.trim_matches(['\'', '"']).to_string()),
+            "connection" => keep_alive = value.eq_ignore_ascii_case("keep-alive"),
+            _ => {}
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8_lossy(&body).into_owned();
+    let action = action.or_else(|| body_action(&body)).unwrap_or_default();
+
+    Ok(Some(Request {
+        action,
+        body,
+        keep_alive,
+    }))
+}
+
+/// Name of the first element in the SOAP body.
+fn body_action(envelope: &str) -> Option<String> {
+    let body = &envelope[envelope.find("Body>")? + "Body>".len()..];
+    let element = body.strip_prefix('<')?;
+    let end = element.find(['>', ' ', '/'])?;
+    Some(element[..end].to_string())
+}
+
+impl Simulator {
+    /// Answers a request with an HTTP status and SOAP body content.
+    fn handle(&mut self, request: &Request) -> (u32, String) {
+        self.request_count += 1;
+        match request.action.as_str() {
+            "InjectUAVControllerInterface" => {
+                if self.controller_injected {
+                    return fault(
+                        "Error setting new controller interface",
+                        "Preexisting controller reference",
+                    );
+                }
+                self.controller_injected = true;
+                (200, empty_response("InjectUAVControllerInterfaceResponse"))
+            }
+            "RestoreOriginalControllerDevice" => {
+                if !self.controller_injected {
+                    return fault(
+                        "Error restoring controller device",
+                        "Pointer to original controller device is null",
+                    );
+                }
+                self.controller_injected = false;
+                (
+                    200,
+                    empty_response("RestoreOriginalControllerDeviceResponse"),
+                )
+            }
+            "ResetAircraft" => {
+                self.aircraft = Aircraft::default();
+                (200, empty_response("ResetAircraftResponse"))
+            }
+            "ExchangeData" => {
+                if !self.controller_injected {
+                    return fault(
+                        "Error setting channel values",
+                        "RealFlight Link controller has not been instantiated",
+                    );
+                }
+                self.aircraft.apply(&request.body);
+                self.aircraft.step(TIME_STEP);
+                let state = self.aircraft.state(self.controller_injected);
+                (200, encode_simulator_state(&state))
+            }
+            action => (
+                500,
+                format!(
+                    "<SOAP-ENV:Fault><faultcode>SOAP-ENV:Client</faultcode>\
+                     <faultstring>Method '{}' not implemented: method name or namespace not recognized</faultstring>\
+                     </SOAP-ENV:Fault>",
+                    action
+                ),
+            ),
+        }
+    }
+}
+
+fn empty_response(element: &str) -> String {
+    format!("<{0}><unused>0</unused></{0}>", element)
+}
+
+fn fault(faultstring: &str, detail: &str) -> (u32, String) {
+    (
+        500,
+        format!(
+            "<SOAP-ENV:Fault><faultcode>SOAP-ENV:Server</faultcode>\
+             <faultstring>{}</faultstring><detail>{}</detail></SOAP-ENV:Fault>",
+            faultstring, detail
+        ),
+    )
+}
+
+fn http_response(status: u32, content: &str, keep_alive: bool) -> String {
+    let envelope = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <SOAP-ENV:Envelope xmlns:SOAP-ENV=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+         xmlns:SOAP-ENC=\"http://schemas.xmlsoap.org/soap/encoding/\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\">\
+         <SOAP-ENV:Body>{}</SOAP-ENV:Body></SOAP-ENV:Envelope>",
+        content
+    );
+    let reason = if status == 200 {
+        "OK"
+    } else {
+        "Internal Server Error"
+    };
+    let connection = if keep_alive { "keep-alive" } else { "close" };
+    format!(
+        "HTTP/1.1 {} {}\r\nServer: gSOAP/2.7\r\nContent-Type: text/xml; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: {}\r\n\r\n{}",
+        status,
+        reason,
+        envelope.len(),
+        connection,
+        envelope
+    )
+}
+
+/// Point-mass aircraft flown by the channel values, in RealFlight's units.
+#[derive(Clone, Debug)]
+struct Aircraft {
+    inputs: ControlInputs,
+    /// North of the start position [meters]
+    x: f32,
+    /// East of the start position [meters]
+    y: f32,
+    /// Height above ground [meters]
+    altitude: f32,
+    /// [degrees], 0 is north
+    heading: f32,
+    /// [degrees]
+    pitch: f32,
+    /// [degrees]
+    roll: f32,
+    /// [degrees/second]
+    roll_rate: f32,
+    /// [degrees/second]
+    pitch_rate: f32,
+    /// [degrees/second]
+    yaw_rate: f32,
+    /// [meters/second]
+    airspeed: f32,
+    /// Forward acceleration [meters/second²]
+    acceleration: f32,
+    /// [milliampere-hours]
+    battery_remaining: f32,
+    /// [seconds]
+    time: f32,
+}
+
+impl Default for Aircraft {
+    fn default() -> Self {
+        Aircraft {
+            inputs: ControlInputs::default(),
+            x: 0.0,
+            y: 0.0,
+            altitude: 0.0,
+            heading: 0.0,
+            pitch: 0.0,
+            roll: 0.0,
+            roll_rate: 0.0,
+            pitch_rate: 0.0,
+            yaw_rate: 0.0,
+            airspeed: 0.0,
+            acceleration: 0.0,
+            battery_remaining: BATTERY_CAPACITY,
+            time: 0.0,
+        }
+    }
+}
+
+impl Aircraft {
+    /// Takes the selected channel values of an `ExchangeData` request.
+    fn apply(&mut self, body: &str) {
+        let selected: u32 = extract_element("m-selectedChannels", body)
+            .and_then(|mask| mask.parse().ok())
+            .unwrap_or(0);
+        let values = body.split("<item>").skip(1).filter_map(|item| {
+            item.split_once("</item>")
+                .and_then(|(value, _)| value.parse::<f32>().ok())
+        });
+        for (channel, value) in values.take(12).enumerate() {
+            if selected & (1 << channel) != 0 {
+                self.inputs.channels[channel] = value.clamp(0.0, 1.0);
+            }
+        }
+    }
+
+    fn throttle(&self) -> f32 {
+        self.inputs.channels[2]
+    }
+
+    fn on_ground(&self) -> bool {
+        self.altitude <= 0.0
+    }
+
+    /// Advances the model by `dt` seconds.
+    fn step(&mut self, dt: f32) {
+        let stick = |channel: usize| ((self.inputs.channels[channel] - 0.5) * 2.0).clamp(-1.0, 1.0);
+        let throttle = self.throttle();
+
+        self.acceleration = throttle * MAX_THRUST - DRAG * self.airspeed;
+        self.airspeed = (self.airspeed + self.acceleration * dt).max(0.0);
+        self.yaw_rate = stick(3) * MAX_RATE / 2.0;
+
+        if self.on_ground() && self.airspeed < TAKEOFF_SPEED {
+            self.roll_rate = 0.0;
+            self.pitch_rate = 0.0;
+            self.roll = 0.0;
+            self.pitch = 0.0;
+        } else {
+            self.roll_rate = stick(0) * MAX_RATE;
+            self.pitch_rate = stick(1) * MAX_RATE;
+            self.roll = (self.roll + self.roll_rate * dt).clamp(-MAX_ROLL, MAX_ROLL);
+            self.pitch = (self.pitch + self.pitch_rate * dt).clamp(-MAX_PITCH, MAX_PITCH);
+            // A banked aircraft turns
+            self.yaw_rate += self.roll / 2.0;
+        }
+        self.heading = wrap_degrees(self.heading + self.yaw_rate * dt);
+
+        let (climb, ground_speed) = self.climb_and_ground_speed();
+        self.altitude += climb * dt;
+        if self.altitude <= 0.0 {
+            self.altitude = 0.0;
+            self.pitch = self.pitch.max(0.0);
+            self.roll = 0.0;
+        }
+        let heading = self.heading.to_radians();
+        self.x += ground_speed * heading.cos() * dt;
+        self.y += ground_speed * heading.sin() * dt;
+
+        let current = throttle * MAX_CURRENT;
+        self.battery_remaining = (self.battery_remaining - current * dt / 3.6).max(0.0);
+        self.time += dt;
+    }
+
+    /// Vertical and horizontal components of the airspeed [meters/second].
+    fn climb_and_ground_speed(&self) -> (f32, f32) {
+        let pitch = self.pitch.to_radians();
+        (self.airspeed * pitch.sin(), self.airspeed * pitch.cos())
+    }
+
+    fn state(&self, controller_injected: bool) -> SimulatorState {
+        let throttle = self.throttle();
+        let current = throttle * MAX_CURRENT;
+        let voltage = 11.1 + 1.5 * self.battery_remaining / BATTERY_CAPACITY - 0.02 * current;
+        let (climb, ground_speed) = self.climb_and_ground_speed();
+        let heading = self.heading.to_radians();
+        let [qx, qy, qz, qw] = quaternion(self.roll, self.pitch, self.heading);
+
+        SimulatorState {
+            previous_inputs: self.inputs.clone(),
+            airspeed: to_velocity(self.airspeed),
+            altitude_asl: to_length(GROUND_ELEVATION + self.altitude),
+            altitude_agl: to_length(self.altitude),
+            groundspeed: to_velocity(ground_speed),
+            pitch_rate: to_angular_velocity(self.pitch_rate),
+            roll_rate: to_angular_velocity(self.roll_rate),
+            yaw_rate: to_angular_velocity(self.yaw_rate),
+            azimuth: to_angle(self.heading),
+            inclination: to_angle(self.pitch),
+            roll: to_angle(self.roll),
+            aircraft_position_x: to_length(self.x),
+            aircraft_position_y: to_length(self.y),
+            velocity_world_u: to_velocity(ground_speed * heading.cos()),
+            velocity_world_v: to_velocity(ground_speed * heading.sin()),
+            velocity_world_w: to_velocity(-climb),
+            velocity_body_u: to_velocity(self.airspeed),
+            velocity_body_v: to_velocity(0.0),
+            velocity_body_w: to_velocity(0.0),
+            acceleration_world_ax: to_acceleration(self.acceleration * heading.cos()),
+            acceleration_world_ay: to_acceleration(self.acceleration * heading.sin()),
+            acceleration_world_az: to_acceleration(0.0),
+            acceleration_body_ax: to_acceleration(self.acceleration),
+            acceleration_body_ay: to_acceleration(0.0),
+            acceleration_body_az: to_acceleration(0.0),
+            prop_rpm: throttle * MAX_RPM,
+            heli_main_rotor_rpm: -1.0,
+            battery_voltage: to_electric_potential(voltage),
+            battery_current_draw: to_electric_current(current),
+            battery_remaining_capacity: to_electric_charge(self.battery_remaining),
+            an_engine_is_running: throttle > 0.0,
+            is_touching_ground: self.on_ground(),
+            current_aircraft_status: if self.on_ground() {
+                "CAS-WAITINGTOLAUNCH"
+            } else {
+                "CAS-FLYING"
+            }
+            .to_string(),
+            current_physics_time: to_time(self.time),
+            current_physics_speed_multiplier: 1.0,
+            orientation_quaternion_x: qx,
+            orientation_quaternion_y: qy,
+            orientation_quaternion_z: qz,
+            orientation_quaternion_w: qw,
+            flight_axis_controller_is_active: controller_injected,
+            ..Default::default()
+        }
+    }
+}
+
+/// Wraps an angle to [-180, 180) degrees.
+fn wrap_degrees(degrees: f32) -> f32 {
+    (degrees + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Orientation quaternion `[x, y, z, w]` of roll, pitch and yaw in degrees.
+fn quaternion(roll: f32, pitch: f32, yaw: f32) -> [f32; 4] {
+    let (sr, cr) = (roll.to_radians() / 2.0).sin_cos();
+    let (sp, cp) = (pitch.to_radians() / 2.0).sin_cos();
+    let (sy, cy) = (yaw.to_radians() / 2.0).sin_cos();
+    [
+        sr * cp * cy - cr * sp * sy,
+        cr * sp * cy + sr * cp * sy,
+        cr * cp * sy - sr * sp * cy,
+        cr * cp * cy + sr * sp * sy,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::local::{Configuration, PoolSize, RealFlightLocalBridge};
+    use crate::encoders::units;
+    use crate::{RealFlightBridge, SoapFaultKind};
+    use std::io::Read;
+    use std::time::Duration;
+
+    fn connect(simulator: &MockSimulator) -> RealFlightLocalBridge {
+        RealFlightLocalBridge::with_configuration(&Configuration {
+            simulator_host: simulator.host(),
+            connect_timeout: Duration::from_millis(50),
+            pool_size: PoolSize::Fixed(1),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn inputs(throttle: f32, pitch: f32) -> ControlInputs {
+        let mut inputs = ControlInputs {
+            channels: [0.5; 12],
+        };
+        inputs.channels[1] = pitch;
+        inputs.channels[2] = throttle;
+        inputs
+    }
+
+    mod protocol_tests {
+        use super::*;
+
+        #[test]
+        fn serves_a_control_session() {
+            let simulator = MockSimulator::start(0).unwrap();
+            let bridge = connect(&simulator);
+
+            bridge.disable_rc().unwrap();
+            assert!(simulator.controller_injected());
+            let state = bridge.exchange_data(&inputs(0.8, 0.5)).unwrap();
+            bridge.enable_rc().unwrap();
+
+            assert!(!simulator.controller_injected());
+            assert!(state.flight_axis_controller_is_active);
+            assert!(state.an_engine_is_running);
+            assert_eq!(state.previous_inputs, inputs(0.8, 0.5));
+            assert_eq!(units::time(state.current_physics_time), TIME_STEP);
+            assert_eq!(simulator.request_count(), 3);
+        }
+
+        #[test]
+        fn exchange_before_disable_faults() {
+            let simulator = MockSimulator::start(0).unwrap();
+            let bridge = connect(&simulator);
+
+            let error = bridge.exchange_data(&inputs(0.5, 0.5)).unwrap_err();
+
+            assert_eq!(
+                error.fault_kind(),
+                Some(SoapFaultKind::ControllerNotInstantiated)
+            );
+        }
+
+        #[test]
+        fn second_disable_faults() {
+            let simulator = MockSimulator::start(0).unwrap();
+            let bridge = connect(&simulator);
+
+            bridge.disable_rc().unwrap();
+            let error = bridge.disable_rc().unwrap_err();
+
+            assert_eq!(
+                error.fault_kind(),
+                Some(SoapFaultKind::PreexistingController)
+            );
+        }
+
+        #[test]
+        fn enable_before_disable_faults() {
+            let simulator = MockSimulator::start(0).unwrap();
+            let bridge = connect(&simulator);
+
+            let error = bridge.enable_rc().unwrap_err();
+
+            assert_eq!(
+                error.fault_kind(),
+                Some(SoapFaultKind::NoOriginalController)
+            );
+        }
+
+        #[test]
+        fn ensure_rc_disabled_recovers_leftover_controller() {
+            let simulator = MockSimulator::start(0).unwrap();
+            simulator.set_controller_injected(true);
+            let bridge = connect(&simulator);
+
+            bridge.ensure_rc_disabled().unwrap();
+
+            assert!(simulator.controller_injected());
+            assert_eq!(simulator.request_count(), 3);
+        }
+
+        #[test]
+        fn unknown_action_faults() {
+            let simulator = MockSimulator::start(0).unwrap();
+            let mut stream = TcpStream::connect(simulator.address()).unwrap();
+            let body = "<soap:Envelope><soap:Body><QueryBetaFeature></QueryBetaFeature></soap:Body></soap:Envelope>";
+            write!(
+                stream,
+                "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+
+            assert!(response.starts_with("HTTP/1.1 500"));
+            assert!(response.contains("Method 'QueryBetaFeature' not implemented"));
+        }
+
+        #[test]
+        fn honors_keep_alive() {
+            let simulator = MockSimulator::start(0).unwrap();
+            let stream = TcpStream::connect(simulator.address()).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let body = "<soap:Body><ResetAircraft></ResetAircraft></soap:Body>";
+
+            for _ in 0..2 {
+                write!(
+                    writer,
+                    "POST / HTTP/1.1\r\nSoapaction: 'ResetAircraft'\r\nConnection: keep-alive\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+                let mut status = String::new();
+                reader.read_line(&mut status).unwrap();
+                assert!(status.starts_with("HTTP/1.1 200"), "{}", status);
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = header.strip_prefix("Content-Length: ") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut envelope = vec![0; length];
+                reader.read_exact(&mut envelope).unwrap();
+            }
+
+            assert_eq!(simulator.request_count(), 2);
+        }
+    }
+
+    mod model_tests {
+        use super::*;
+
+        fn fly(aircraft: &mut Aircraft, inputs: &ControlInputs, steps: usize) {
+            aircraft.inputs = inputs.clone();
+            for _ in 0..steps {
+                aircraft.step(TIME_STEP);
+            }
+        }
+
+        #[test]
+        fn starts_at_rest_on_the_ground() {
+            let state = Aircraft::default().state(false);
+
+            assert!(state.is_touching_ground);
+            assert!(!state.an_engine_is_running);
+            assert_eq!(units::length(state.altitude_asl), GROUND_ELEVATION);
+            assert_eq!(units::velocity(state.airspeed), 0.0);
+            assert_eq!(state.orientation_quaternion_w, 1.0);
+        }
+
+        #[test]
+        fn stays_down_below_takeoff_speed() {
+            let mut aircraft = Aircraft::default();
+
+            fly(&mut aircraft, &inputs(0.2, 1.0), 100);
+
+            assert!(aircraft.on_ground());
+            assert_eq!(aircraft.pitch, 0.0);
+        }
+
+        #[test]
+        fn climbs_with_throttle_and_up_elevator() {
+            let mut aircraft = Aircraft::default();
+
+            fly(&mut aircraft, &inputs(1.0, 0.5), 300);
+            fly(&mut aircraft, &inputs(1.0, 0.7), 100);
+            let state = aircraft.state(true);
+
+            assert!(!state.is_touching_ground);
+            assert!(units::length(state.altitude_agl) > 0.0);
+            assert!(units::angle(state.inclination) > 0.0);
+            assert!(units::length(state.aircraft_position_x) > 0.0);
+            assert_eq!(state.current_aircraft_status, "CAS-FLYING");
+        }
+
+        #[test]
+        fn drains_battery_with_throttle() {
+            let mut aircraft = Aircraft::default();
+
+            fly(&mut aircraft, &inputs(1.0, 0.5), 100);
+
+            // One second at 40 A
+            approx::assert_relative_eq!(
+                aircraft.battery_remaining,
+                BATTERY_CAPACITY - 40.0 / 3.6,
+                epsilon = 0.01
+            );
+        }
+
+        #[test]
+        fn applies_selected_channels_only() {
+            let mut aircraft = Aircraft::default();
+            let mut sent = ControlInputs::default();
+            sent.channels[2] = 0.9;
+
+            aircraft.apply(&crate::encoders::encode_idle_inputs());
+            assert_eq!(aircraft.inputs, ControlInputs::default());
+            aircraft.apply(&crate::encoders::encode_control_inputs(&sent));
+            assert_eq!(aircraft.inputs, sent);
+        }
+
+        #[test]
+        fn reset_returns_aircraft_to_start() {
+            let simulator = MockSimulator::start(0).unwrap();
+            let bridge = connect(&simulator);
+            bridge.disable_rc().unwrap();
+            for _ in 0..50 {
+                bridge.exchange_data(&inputs(1.0, 0.5)).unwrap();
+            }
+            assert!(units::velocity(simulator.state().airspeed) > 0.0);
+
+            bridge.reset_aircraft().unwrap();
+
+            let state = simulator.state();
+            assert_eq!(units::velocity(state.airspeed), 0.0);
+            assert_eq!(units::time(state.current_physics_time), 0.0);
+            assert!(simulator.controller_injected());
+        }
+
+        #[test]
+        fn quaternion_of_heading() {
+            let [x, y, z, w] = quaternion(0.0, 0.0, 90.0);
+
+            approx::assert_relative_eq!(x, 0.0);
+            approx::assert_relative_eq!(y, 0.0);
+            approx::assert_relative_eq!(z, std::f32::consts::FRAC_1_SQRT_2);
+            approx::assert_relative_eq!(w, std::f32::consts::FRAC_1_SQRT_2);
+        }
+
+        #[test]
+        fn wraps_heading() {
+            assert_eq!(wrap_degrees(190.0), -170.0);
+            assert_eq!(wrap_degrees(-190.0), 170.0);
+            assert_eq!(wrap_degrees(45.0), 45.0);
+        }
+    }
+}
@@ -0,0 +1,454 @@
+//! Flight phase detection from successive states.
+//!
+//! [FlightPhaseDetector] follows an aircraft from the ground through takeoff,
+//! flight and landing, or into a crash, and reports each change of
+//! [FlightPhase] with the physics time it happened at. Scripted tests can use
+//! it to sequence a flight: wait until the aircraft is airborne before
+//! starting a maneuver, or until it is back on the ground before checking
+//! the result.
+//!
+//! ```no_run
+//! use realflight_bridge::phase::{FlightPhase, FlightPhaseDetector, PhaseConfig};
+//! use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bridge = RealFlightLocalBridge::new()?;
+//! let mut detector = FlightPhaseDetector::new(PhaseConfig::default());
+//! let mut inputs = ControlInputs { channels: [0.5; 12] };
+//! inputs.channels[2] = 1.0;
+//!
+//! while detector.phase() != Some(FlightPhase::Airborne) {
+//!     let state = bridge.exchange_data(&inputs)?;
+//!     if let Some(transition) = detector.update(&state) {
+//!         println!("{:?} at {:.2} s", transition.to, transition.physics_time);
+//!     }
+//! }
+//! // Start the maneuver...
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use crate::SimulatorState;
+use crate::encoders::units;
+
+/// What the aircraft is doing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlightPhase {
+    /// Resting or taxiing on the ground
+    OnGround,
+    /// Left the ground, not yet at [airborne_altitude](PhaseConfig::airborne_altitude)
+    TakingOff,
+    /// Flying
+    Airborne,
+    /// Descending below [landing_altitude](PhaseConfig::landing_altitude),
+    /// until it has settled on the ground
+    Landing,
+    /// Lost components. Lasts until the aircraft is reset
+    Crashed,
+}
+
+/// A change of [FlightPhase].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhaseTransition {
+    /// Phase before the change
+    pub from: FlightPhase,
+    /// Phase after the change
+    pub to: FlightPhase,
+    /// Physics time of the state that completed the change (s)
+    pub physics_time: f32,
+}
+
+/// Thresholds for a [FlightPhaseDetector].
+///
+/// `landing_altitude` must be below `airborne_altitude`: the gap between them
+/// keeps an aircraft flying level near the ground from switching back and
+/// forth between [Airborne](FlightPhase::Airborne) and
+/// [Landing](FlightPhase::Landing).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhaseConfig {
+    /// Height above ground at which a takeoff, or an aborted landing, counts
+    /// as flying. Defaults to 3 m
+    pub airborne_altitude: f32,
+    /// Height above ground below which a descending aircraft is landing.
+    /// Defaults to 2 m
+    pub landing_altitude: f32,
+    /// Physics time the aircraft must stay on the ground after touching down
+    /// to count as landed, so bounces do not end the landing. Defaults to one
+    /// second
+    pub settle_time: Duration,
+}
+
+impl Default for PhaseConfig {
+    fn default() -> Self {
+        PhaseConfig {
+            airborne_altitude: 3.0,
+            landing_altitude: 2.0,
+            settle_time: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Derives the [FlightPhase] from successive states.
+///
+/// Uses `is_touching_ground`, `altitude_agl`, the vertical speed from
+/// `velocity_world_w` and `has_lost_components`:
+///
+/// | From | To | When |
+/// |------|----|------|
+/// | `OnGround` | `TakingOff` | no longer touching the ground |
+/// | `TakingOff` | `Airborne` | above `airborne_altitude` |
+/// | `TakingOff` | `OnGround` | touching the ground for `settle_time` |
+/// | `Airborne` | `Landing` | descending below `landing_altitude`, or touching the ground |
+/// | `Landing` | `OnGround` | touching the ground for `settle_time` |
+/// | `Landing` | `Airborne` | above `airborne_altitude` |
+/// | any | `Crashed` | components lost |
+///
+/// Physics time going backward, as it does when the aircraft is reset,
+/// starts over from the state that shows it.
+#[derive(Clone, Debug)]
+pub struct FlightPhaseDetector {
+    config: PhaseConfig,
+    phase: Option<FlightPhase>,
+    last_time: f32,
+    /// Physics time the current stretch of ground contact began
+    touchdown: Option<f32>,
+    transitions: Vec<PhaseTransition>,
+}
+
+impl FlightPhaseDetector {
+    /// Detector with no states yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `landing_altitude` is not below `airborne_altitude`.
+    pub fn new(config: PhaseConfig) -> Self {
+        assert!(
+            config.landing_altitude < config.airborne_altitude,
+            "landing_altitude must be below airborne_altitude, got {} and {}",
+            config.landing_altitude,
+            config.airborne_altitude
+        );
+        FlightPhaseDetector {
+            config,
+            phase: None,
+            last_time: f32::NEG_INFINITY,
+            touchdown: None,
+            transitions: Vec::new(),
+        }
+    }
+
+    /// Current phase, or `None` before the first state.
+    pub fn phase(&self) -> Option<FlightPhase> {
+        self.phase
+    }
+
+    /// Every transition so far, oldest first.
+    pub fn transitions(&self) -> &[PhaseTransition] {
+        &self.transitions
+    }
+
+    /// Forgets the states fed so far.
+    pub fn reset(&mut self) {
+        self.phase = None;
+        self.last_time = f32::NEG_INFINITY;
+        self.touchdown = None;
+        self.transitions.clear();
+    }
+
+    /// Takes the next state, returning the transition it completes, if any.
+    ///
+    /// The first state sets the phase without a transition.
+    pub fn update(&mut self, state: &SimulatorState) -> Option<PhaseTransition> {
+        let time = units::time(state.current_physics_time);
+        let reset = time < self.last_time;
+        self.last_time = time;
+
+        let touching = state.is_touching_ground;
+        if !touching {
+            self.touchdown = None;
+        } else if self.touchdown.is_none() || reset {
+            self.touchdown = Some(time);
+        }
+
+        let next = match self.phase {
+            Some(phase) if !reset => self.next_phase(phase, state, time),
+            _ => self.initial_phase(state),
+        };
+        let from = self.phase.replace(next)?;
+        if from == next {
+            return None;
+        }
+
+        let transition = PhaseTransition {
+            from,
+            to: next,
+            physics_time: time,
+        };
+        self.transitions.push(transition);
+        Some(transition)
+    }
+
+    fn initial_phase(&self, state: &SimulatorState) -> FlightPhase {
+        let altitude = units::length(state.altitude_agl);
+        if state.has_lost_components {
+            FlightPhase::Crashed
+        } else if state.is_touching_ground {
+            FlightPhase::OnGround
+        } else if altitude >= self.config.airborne_altitude {
+            FlightPhase::Airborne
+        } else {
+            FlightPhase::TakingOff
+        }
+    }
+
+    fn next_phase(&self, phase: FlightPhase, state: &SimulatorState, time: f32) -> FlightPhase {
+        if state.has_lost_components {
+            return FlightPhase::Crashed;
+        }
+        let altitude = units::length(state.altitude_agl);
+        // velocity_world_w points down
+        let descending = units::velocity(state.velocity_world_w) > 0.0;
+        let settled = self
+            .touchdown
+            .is_some_and(|touchdown| time - touchdown >= self.config.settle_time.as_secs_f32());
+
+        match phase {
+            FlightPhase::OnGround if !state.is_touching_ground => FlightPhase::TakingOff,
+            FlightPhase::TakingOff | FlightPhase::Landing
+                if altitude >= self.config.airborne_altitude =>
+            {
+                FlightPhase::Airborne
+            }
+            FlightPhase::TakingOff | FlightPhase::Landing if settled => FlightPhase::OnGround,
+            FlightPhase::Airborne
+                if (altitude < self.config.landing_altitude && descending)
+                    || state.is_touching_ground =>
+            {
+                FlightPhase::Landing
+            }
+            phase => phase,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoders::{to_length, to_time, to_velocity};
+
+    /// State at `time` seconds, `altitude` meters above ground, climbing at
+    /// `climb` meters/second.
+    fn state(time: f32, altitude: f32, climb: f32) -> SimulatorState {
+        SimulatorState {
+            current_physics_time: to_time(time),
+            altitude_agl: to_length(altitude),
+            velocity_world_w: to_velocity(-climb),
+            is_touching_ground: altitude <= 0.0,
+            ..Default::default()
+        }
+    }
+
+    fn crashed(time: f32, altitude: f32) -> SimulatorState {
+        SimulatorState {
+            has_lost_components: true,
+            ..state(time, altitude, 0.0)
+        }
+    }
+
+    /// Feeds `states`, returning the phases the detector moved to and when.
+    fn run(
+        detector: &mut FlightPhaseDetector,
+        states: &[SimulatorState],
+    ) -> Vec<(FlightPhase, f32)> {
+        states
+            .iter()
+            .filter_map(|state| detector.update(state))
+            .map(|transition| (transition.to, transition.physics_time))
+            .collect()
+    }
+
+    /// Takeoff, climb to 10 m and a descent to `descent_end` seconds, at one
+    /// state per 100 ms.
+    fn flight() -> Vec<SimulatorState> {
+        let mut states = vec![state(0.0, 0.0, 0.0), state(0.1, 0.0, 0.0)];
+        // Climb at 5 m/s to 10 m
+        for i in 1..=20 {
+            states.push(state(0.1 + i as f32 * 0.1, i as f32 * 0.5, 5.0));
+        }
+        // Descend at 2 m/s
+        for i in 1..=50 {
+            states.push(state(2.1 + i as f32 * 0.1, 10.0 - i as f32 * 0.2, -2.0));
+        }
+        states
+    }
+
+    fn detector() -> FlightPhaseDetector {
+        FlightPhaseDetector::new(PhaseConfig::default())
+    }
+
+    fn assert_times(actual: &[(FlightPhase, f32)], expected: &[(FlightPhase, f32)]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for ((phase, time), (expected_phase, expected_time)) in actual.iter().zip(expected) {
+            assert_eq!(phase, expected_phase, "{:?}", actual);
+            assert!((time - expected_time).abs() < 1e-4, "{:?}", actual);
+        }
+    }
+
+    #[test]
+    fn follows_takeoff_and_landing() {
+        let mut detector = detector();
+        let mut states = flight();
+        // On the ground from 7.1 s
+        for i in 1..=12 {
+            states.push(state(7.1 + i as f32 * 0.1, 0.0, 0.0));
+        }
+
+        let transitions = run(&mut detector, &states);
+
+        assert_times(
+            &transitions,
+            &[
+                (FlightPhase::TakingOff, 0.2),
+                (FlightPhase::Airborne, 0.7),
+                (FlightPhase::Landing, 6.2),
+                (FlightPhase::OnGround, 8.1),
+            ],
+        );
+        assert_eq!(detector.phase(), Some(FlightPhase::OnGround));
+        assert_eq!(detector.transitions().len(), 4);
+        assert_eq!(detector.transitions()[0].from, FlightPhase::OnGround);
+    }
+
+    #[test]
+    fn bounce_does_not_end_landing() {
+        let mut detector = detector();
+        let mut states = flight();
+        // Touch down at 7.1 s, bounce to 0.5 m and back, then settle
+        states.extend([
+            state(7.2, 0.0, 0.0),
+            state(7.3, 0.3, 1.5),
+            state(7.4, 0.5, 0.0),
+            state(7.5, 0.3, -1.5),
+            state(7.6, 0.0, 0.0),
+        ]);
+        for i in 1..=10 {
+            states.push(state(7.6 + i as f32 * 0.1, 0.0, 0.0));
+        }
+
+        let transitions = run(&mut detector, &states);
+
+        // Settled a full second after the last touchdown, not the first
+        assert_times(
+            &transitions[2..],
+            &[(FlightPhase::Landing, 6.2), (FlightPhase::OnGround, 8.6)],
+        );
+    }
+
+    #[test]
+    fn level_flight_near_ground_does_not_oscillate() {
+        let mut detector = detector();
+        let mut states = flight()[..63].to_vec();
+        assert_eq!(
+            run(&mut detector, &states).last().unwrap().0,
+            FlightPhase::Landing
+        );
+
+        // Wavering around the landing altitude
+        states.clear();
+        for i in 1..=20 {
+            let altitude = if i % 2 == 0 { 1.9 } else { 2.1 };
+            let climb = if i % 2 == 0 { -1.0 } else { 1.0 };
+            states.push(state(6.2 + i as f32 * 0.1, altitude, climb));
+        }
+
+        assert!(run(&mut detector, &states).is_empty());
+    }
+
+    #[test]
+    fn goes_around_from_landing() {
+        let mut detector = detector();
+        let mut states = flight()[..63].to_vec();
+        states.extend([state(6.3, 2.5, 5.0), state(6.4, 3.0, 5.0)]);
+
+        let transitions = run(&mut detector, &states);
+
+        assert_times(
+            &transitions[2..],
+            &[(FlightPhase::Landing, 6.2), (FlightPhase::Airborne, 6.4)],
+        );
+    }
+
+    #[test]
+    fn hop_settles_back_on_ground() {
+        let mut detector = detector();
+        let mut states = vec![state(0.0, 0.0, 0.0), state(0.1, 0.5, 1.0)];
+        for i in 0..=10 {
+            states.push(state(0.2 + i as f32 * 0.1, 0.0, 0.0));
+        }
+
+        let transitions = run(&mut detector, &states);
+
+        assert_times(
+            &transitions,
+            &[(FlightPhase::TakingOff, 0.1), (FlightPhase::OnGround, 1.2)],
+        );
+    }
+
+    #[test]
+    fn crash_lasts_until_reset() {
+        let mut detector = detector();
+        let mut states = flight()[..30].to_vec();
+        states.extend([crashed(3.0, 0.0), state(3.1, 0.0, 0.0), crashed(3.2, 0.0)]);
+        for i in 0..20 {
+            states.push(crashed(3.3 + i as f32 * 0.1, 0.0));
+        }
+
+        let transitions = run(&mut detector, &states);
+
+        assert_times(
+            &transitions,
+            &[
+                (FlightPhase::TakingOff, 0.2),
+                (FlightPhase::Airborne, 0.7),
+                (FlightPhase::Crashed, 3.0),
+            ],
+        );
+
+        // Reset puts the aircraft back on the runway at the start of physics time
+        let transition = detector.update(&state(0.0, 0.0, 0.0)).unwrap();
+        assert_eq!(transition.from, FlightPhase::Crashed);
+        assert_eq!(transition.to, FlightPhase::OnGround);
+    }
+
+    #[test]
+    fn crashes_from_ground() {
+        let mut detector = detector();
+
+        let transitions = run(&mut detector, &[state(0.0, 0.0, 0.0), crashed(0.1, 0.0)]);
+
+        assert_times(&transitions, &[(FlightPhase::Crashed, 0.1)]);
+    }
+
+    #[test]
+    fn first_state_sets_phase_without_transition() {
+        let mut detector = detector();
+
+        assert_eq!(detector.phase(), None);
+        assert_eq!(detector.update(&state(5.0, 50.0, 0.0)), None);
+        assert_eq!(detector.phase(), Some(FlightPhase::Airborne));
+
+        detector.reset();
+        assert_eq!(detector.phase(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "landing_altitude must be below airborne_altitude")]
+    fn rejects_overlapping_altitudes() {
+        FlightPhaseDetector::new(PhaseConfig {
+            landing_altitude: 3.0,
+            ..Default::default()
+        });
+    }
+}
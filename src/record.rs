@@ -0,0 +1,574 @@
+//! Recording of simulator sessions, and their replay in place of a bridge.
+//!
+//! A [SessionRecorder] wraps a bridge and writes every state it exchanges to
+//! a session file. A [ReplayBridge] reads the file back and serves the same
+//! states in the same order, so a controller regression seen against
+//! RealFlight can be reproduced without it.
+//!
+//! A session file is text: a header line, then one line per frame holding
+//! the seconds since the first frame, the twelve channels sent and the
+//! `ReturnData` element of the response.
+//!
+//! ```text
+//! realflight-bridge session 1
+//! 0.000000 0.5 0.5 0.5 0.5 0.5 0.5 0.5 0.5 0.5 0.5 0.5 0.5 <ReturnData>...</ReturnData>
+//! 0.020113 0.5 0.5 0.5 0.5 0.5 0.5 0.5 0.5 0.5 0.5 0.5 0.5 <ReturnData>...</ReturnData>
+//! ```
+//!
+//! States are stored in the units RealFlight reports, so a session replays
+//! the same whether or not it was recorded with the `uom` feature.
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+use crate::bridge::RealFlightBridge;
+use crate::decoders::decode_simulator_state;
+use crate::encoders::encode_simulator_state;
+use crate::{BridgeError, ControlInputs, SimulatorState};
+
+/// First line of every session file.
+const HEADER: &str = "realflight-bridge session 1";
+
+/// One exchange of a recorded session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// Time since the first frame of the session
+    pub elapsed: Duration,
+    /// Inputs sent to the simulator
+    pub inputs: ControlInputs,
+    /// State the simulator returned
+    pub state: SimulatorState,
+}
+
+/// Wraps a bridge and records every [exchange_data](RealFlightBridge::exchange_data)
+/// to a session file for a [ReplayBridge].
+///
+/// Only successful exchanges are recorded; the other bridge methods are
+/// passed through. The recorder wraps the bridge rather than installing a
+/// [RequestTap](crate::RequestTap), whose response summaries are cut short
+/// of a full `ReturnData` response.
+///
+/// Frames are buffered and flushed when the recorder is dropped. A frame
+/// that cannot be written is logged and skipped, leaving the exchange
+/// itself untouched.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::record::SessionRecorder;
+/// use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+/// use std::error::Error;
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///     let bridge = SessionRecorder::create("flight.session", RealFlightLocalBridge::new()?)?;
+///
+///     let inputs = ControlInputs::default();
+///     for _ in 0..1000 {
+///         bridge.exchange_data(&inputs)?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct SessionRecorder<B> {
+    bridge: B,
+    writer: Mutex<SessionWriter>,
+}
+
+struct SessionWriter {
+    output: BufWriter<File>,
+    started: Option<Instant>,
+    line: String,
+}
+
+impl<B> SessionRecorder<B> {
+    /// Creates the session file at `path`, truncating any existing file, and
+    /// wraps `bridge`.
+    pub fn create(path: impl AsRef<Path>, bridge: B) -> io::Result<Self> {
+        let mut output = BufWriter::new(File::create(path)?);
+        writeln!(output, "{}", HEADER)?;
+        Ok(SessionRecorder {
+            bridge,
+            writer: Mutex::new(SessionWriter {
+                output,
+                started: None,
+                line: String::new(),
+            }),
+        })
+    }
+
+    /// Returns the wrapped bridge.
+    pub fn get_ref(&self) -> &B {
+        &self.bridge
+    }
+
+    /// Flushes buffered frames to the session file.
+    pub fn flush(&self) -> io::Result<()> {
+        self.writer.lock().unwrap().output.flush()
+    }
+
+    fn record(&self, inputs: &ControlInputs, state: &SimulatorState) {
+        let mut writer = self.writer.lock().unwrap();
+        if let Err(e) = writer.write_frame(inputs, state) {
+            warn!("Failed to record session frame: {}", e);
+        }
+    }
+}
+
+impl SessionWriter {
+    fn write_frame(&mut self, inputs: &ControlInputs, state: &SimulatorState) -> io::Result<()> {
+        let elapsed = self.started.get_or_insert_with(Instant::now).elapsed();
+
+        self.line.clear();
+        let _ = write!(
+            self.line,
+            "{}.{:06}",
+            elapsed.as_secs(),
+            elapsed.subsec_micros()
+        );
+        for channel in inputs.channels {
+            let _ = write!(self.line, " {}", channel);
+        }
+        let _ = writeln!(self.line, " {}", encode_simulator_state(state));
+        self.output.write_all(self.line.as_bytes())
+    }
+}
+
+impl<B> Drop for SessionRecorder<B> {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Failed to flush session recording: {}", e);
+        }
+    }
+}
+
+impl<B: RealFlightBridge> RealFlightBridge for SessionRecorder<B> {
+    fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        let state = self.bridge.exchange_data(control)?;
+        self.record(control, &state);
+        Ok(state)
+    }
+
+    fn enable_rc(&self) -> Result<(), BridgeError> {
+        self.bridge.enable_rc()
+    }
+
+    fn disable_rc(&self) -> Result<(), BridgeError> {
+        self.bridge.disable_rc()
+    }
+
+    fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        self.bridge.reset_aircraft()
+    }
+}
+
+#[cfg(feature = "rt-tokio")]
+impl<B: crate::AsyncBridge> crate::AsyncBridge for SessionRecorder<B> {
+    async fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        let state = self.bridge.exchange_data(control).await?;
+        self.record(control, &state);
+        Ok(state)
+    }
+
+    async fn enable_rc(&self) -> Result<(), BridgeError> {
+        self.bridge.enable_rc().await
+    }
+
+    async fn disable_rc(&self) -> Result<(), BridgeError> {
+        self.bridge.disable_rc().await
+    }
+
+    async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        self.bridge.reset_aircraft().await
+    }
+}
+
+/// Bridge serving the states of a recorded session in order, in place of
+/// RealFlight.
+///
+/// Each [exchange_data](RealFlightBridge::exchange_data) returns the next
+/// recorded state, whatever inputs are passed; compare them with
+/// [Frame::inputs] to find where a controller diverges from the recorded
+/// run. Once every frame has been served, exchanges fail with
+/// [BridgeError::InvalidState]. `enable_rc`, `disable_rc` and
+/// `reset_aircraft` succeed without effect.
+///
+/// By default frames are served as fast as they are requested. With
+/// [paced](Self::paced), an exchange waits until its frame's offset from the
+/// first exchange matches the recording.
+///
+/// # Examples
+///
+/// ```no_run
+/// use realflight_bridge::record::ReplayBridge;
+/// use realflight_bridge::{ControlInputs, RealFlightBridge};
+/// use std::error::Error;
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///     let bridge = ReplayBridge::open("flight.session")?.paced(true);
+///
+///     for frame in bridge.frames() {
+///         let state = bridge.exchange_data(&ControlInputs::default())?;
+///         assert_eq!(state, frame.state);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ReplayBridge {
+    frames: Vec<Frame>,
+    paced: bool,
+    cursor: Mutex<Cursor>,
+}
+
+#[derive(Debug, Default)]
+struct Cursor {
+    next: usize,
+    started: Option<Instant>,
+}
+
+impl ReplayBridge {
+    /// Replays `frames` in order.
+    pub fn new(frames: Vec<Frame>) -> Self {
+        ReplayBridge {
+            frames,
+            paced: false,
+            cursor: Mutex::new(Cursor::default()),
+        }
+    }
+
+    /// Reads a session file written by a [SessionRecorder].
+    ///
+    /// Fails with [io::ErrorKind::InvalidData] on a file it cannot parse.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let reader = BufReader::new(File::open(path)?);
+        let frames = read_frames(reader)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        Ok(ReplayBridge::new(frames))
+    }
+
+    /// Whether to wait between exchanges so frames are served with the
+    /// recorded timing.
+    pub fn paced(mut self, paced: bool) -> Self {
+        self.paced = paced;
+        self
+    }
+
+    /// The recorded frames.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Number of frames not yet served.
+    pub fn remaining(&self) -> usize {
+        self.frames.len() - self.cursor.lock().unwrap().next
+    }
+
+    /// Takes the next frame and, when paced, the instant to serve it at.
+    fn next_frame(&self) -> Result<(&Frame, Option<Instant>), BridgeError> {
+        let mut cursor = self.cursor.lock().unwrap();
+        let frame = self.frames.get(cursor.next).ok_or_else(|| {
+            BridgeError::InvalidState(format!(
+                "Recorded session exhausted after {} frames",
+                self.frames.len()
+            ))
+        })?;
+        cursor.next += 1;
+
+        let deadline = self.paced.then(|| {
+            let started = *cursor
+                .started
+                .get_or_insert_with(|| Instant::now() - frame.elapsed);
+            started + frame.elapsed
+        });
+        Ok((frame, deadline))
+    }
+}
+
+impl RealFlightBridge for ReplayBridge {
+    fn exchange_data(&self, _control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        let (frame, deadline) = self.next_frame()?;
+        if let Some(deadline) = deadline {
+            std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        }
+        Ok(frame.state.clone())
+    }
+
+    fn enable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    fn disable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rt-tokio")]
+impl crate::AsyncBridge for ReplayBridge {
+    async fn exchange_data(&self, _control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        let (frame, deadline) = self.next_frame()?;
+        if let Some(deadline) = deadline {
+            tokio::time::sleep_until(deadline.into()).await;
+        }
+        Ok(frame.state.clone())
+    }
+
+    async fn enable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    async fn disable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+}
+
+fn read_frames(reader: impl BufRead) -> io::Result<Vec<Frame>> {
+    let mut lines = reader.lines();
+    match lines.next().transpose()? {
+        Some(header) if header == HEADER => {}
+        _ => return Err(invalid_data("missing session header".into())),
+    }
+
+    let mut frames = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+        let frame = parse_frame(&line)
+            .map_err(|what| invalid_data(format!("line {}: {}", index + 2, what)))?;
+        frames.push(frame);
+    }
+    Ok(frames)
+}
+
+fn parse_frame(line: &str) -> Result<Frame, String> {
+    let mut fields = line.splitn(14, ' ');
+
+    let elapsed = fields
+        .next()
+        .and_then(|elapsed| elapsed.split_once('.'))
+        .and_then(|(secs, micros)| Some((secs.parse().ok()?, micros.parse().ok()?)))
+        .map(|(secs, micros)| Duration::from_secs(secs) + Duration::from_micros(micros))
+        .ok_or("invalid elapsed time")?;
+
+    let mut inputs = ControlInputs::default();
+    for (index, channel) in inputs.channels.iter_mut().enumerate() {
+        *channel = fields
+            .next()
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| format!("invalid channel {}", index + 1))?;
+    }
+
+    let state = fields
+        .next()
+        .filter(|xml| xml.starts_with("<ReturnData>"))
+        .ok_or("missing ReturnData")?;
+    let state = decode_simulator_state(state).map_err(|e| e.to_string())?;
+
+    Ok(Frame {
+        elapsed,
+        inputs,
+        state,
+    })
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::local::RealFlightLocalBridge;
+    use crate::soap_client::stub::StubSoapClient;
+    use std::path::PathBuf;
+
+    static SESSION: &str = "testdata/sessions/takeoff.session";
+    static SIM_STATE_RESPONSE: &str = include_str!("../testdata/responses/return-data-200.xml");
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "realflight-bridge-{}-session-{}",
+            std::process::id(),
+            name
+        ))
+    }
+
+    fn recorded_session() -> ReplayBridge {
+        ReplayBridge::open(SESSION).unwrap()
+    }
+
+    fn assert_exhausted<T: std::fmt::Debug>(result: Result<T, BridgeError>) {
+        match result {
+            Err(BridgeError::InvalidState(message)) => assert!(message.contains("exhausted")),
+            other => panic!("expected InvalidState, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn opens_recorded_session() {
+        let bridge = recorded_session();
+        let frames = bridge.frames();
+
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0].elapsed, Duration::ZERO);
+        assert_eq!(frames[4].elapsed, Duration::from_micros(80_051));
+        assert_eq!(frames[4].inputs.channels[2], 1.0);
+        assert_eq!(
+            frames[0].state,
+            decode_simulator_state(SIM_STATE_RESPONSE).unwrap()
+        );
+        assert_eq!(frames[4].state.current_aircraft_status, "CAS-FLYING");
+    }
+
+    #[test]
+    fn replays_states_in_order() {
+        let bridge = recorded_session();
+        let expected: Vec<_> = bridge.frames().iter().map(|f| f.state.clone()).collect();
+
+        let replayed: Vec<_> = bridge
+            .frames()
+            .iter()
+            .map(|frame| bridge.exchange_data(&frame.inputs).unwrap())
+            .collect();
+
+        assert_eq!(replayed, expected);
+        assert_eq!(bridge.remaining(), 0);
+    }
+
+    #[test]
+    fn errors_once_exhausted() {
+        let bridge = ReplayBridge::new(recorded_session().frames()[..1].to_vec());
+
+        bridge.exchange_data(&ControlInputs::default()).unwrap();
+        assert_exhausted(bridge.exchange_data(&ControlInputs::default()));
+        assert_exhausted(bridge.exchange_data(&ControlInputs::default()));
+    }
+
+    #[test]
+    fn paced_replay_keeps_recorded_timing() {
+        let bridge = recorded_session().paced(true);
+        let last = bridge.frames()[4].elapsed;
+
+        let start = Instant::now();
+        for _ in 0..5 {
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+        }
+
+        assert!(start.elapsed() >= last, "{:?}", start.elapsed());
+    }
+
+    #[test]
+    fn rc_operations_succeed_without_effect() {
+        let bridge = recorded_session();
+
+        bridge.disable_rc().unwrap();
+        bridge.reset_aircraft().unwrap();
+        bridge.enable_rc().unwrap();
+        assert_eq!(bridge.remaining(), 5);
+    }
+
+    #[test]
+    fn replayed_run_reproduces_recording() {
+        let path = temp_file("rerecord");
+        let original = recorded_session();
+
+        let recorder = SessionRecorder::create(&path, recorded_session()).unwrap();
+        for frame in original.frames() {
+            recorder.exchange_data(&frame.inputs).unwrap();
+        }
+        drop(recorder);
+
+        let replay = ReplayBridge::open(&path).unwrap();
+        assert_eq!(replay.frames().len(), original.frames().len());
+        for (replayed, recorded) in replay.frames().iter().zip(original.frames()) {
+            assert_eq!(replayed.inputs, recorded.inputs);
+            assert_eq!(replayed.state, recorded.state);
+            assert_eq!(
+                replay.exchange_data(&replayed.inputs).unwrap(),
+                recorded.state
+            );
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn records_local_bridge_exchanges() {
+        let path = temp_file("local");
+        let client = StubSoapClient::new(vec![
+            "inject-uav-controller-interface-200".to_string(),
+            "return-data-200".to_string(),
+        ]);
+        let bridge = RealFlightLocalBridge::stub(client);
+        let mut inputs = ControlInputs::default();
+        inputs.channels[0] = 0.25;
+
+        let recorder = SessionRecorder::create(&path, bridge).unwrap();
+        recorder.disable_rc().unwrap();
+        let state = recorder.exchange_data(&inputs).unwrap();
+        recorder.flush().unwrap();
+
+        let frames = ReplayBridge::open(&path).unwrap().frames().to_vec();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].inputs, inputs);
+        assert_eq!(frames[0].state, state);
+
+        drop(recorder);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn failed_exchanges_are_not_recorded() {
+        let path = temp_file("failed");
+        let recorder = SessionRecorder::create(&path, ReplayBridge::new(Vec::new())).unwrap();
+
+        assert_exhausted(recorder.exchange_data(&ControlInputs::default()));
+        drop(recorder);
+
+        assert!(ReplayBridge::open(&path).unwrap().frames().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_rejects_malformed_session() {
+        let missing_header = read_frames("0.000000 0.5\n".as_bytes()).unwrap_err();
+        assert_eq!(missing_header.kind(), io::ErrorKind::InvalidData);
+
+        let bad_channel = format!("{}\n0.000000 0.5 oops\n", HEADER);
+        let err = read_frames(bad_channel.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 2"), "{}", err);
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    #[tokio::test]
+    async fn async_replay_serves_same_states() {
+        let bridge = recorded_session();
+
+        for frame in recorded_session().frames() {
+            let state = crate::AsyncBridge::exchange_data(&bridge, &frame.inputs)
+                .await
+                .unwrap();
+            assert_eq!(state, frame.state);
+        }
+        assert_exhausted(
+            crate::AsyncBridge::exchange_data(&bridge, &ControlInputs::default()).await,
+        );
+    }
+}
@@ -0,0 +1,537 @@
+//! Safe envelope monitoring for unattended runs.
+//!
+//! An [EnvelopeMonitor] checks every state it is fed against the limits of an
+//! [Envelope] and resets the aircraft when one is exceeded, so a soak test can
+//! run for hours without an aircraft drifting off the map or tumbling out of
+//! control. Resets are held off for [Envelope::reset_interval] after each one,
+//! as the states following a reset can still be out of bounds.
+//!
+//! ```no_run
+//! use realflight_bridge::safety::{Envelope, EnvelopeMonitor};
+//! use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bridge = RealFlightLocalBridge::new()?;
+//! let mut monitor = EnvelopeMonitor::new(Envelope {
+//!     min_x: -300.0,
+//!     max_x: 300.0,
+//!     min_y: -300.0,
+//!     max_y: 300.0,
+//!     max_altitude_agl: 150.0,
+//!     max_bank: 75.0,
+//!     ..Default::default()
+//! });
+//!
+//! loop {
+//!     let state = bridge.exchange_data(&ControlInputs::default())?;
+//!     if let Some(violation) = monitor.feed(&state, &bridge)? {
+//!         println!("{:?} at {} (limit {})", violation.field, violation.value, violation.limit);
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+use crate::bridge::RealFlightBridge;
+use crate::clock::{Clock, SystemClock};
+use crate::encoders::units;
+use crate::{BridgeError, SimulatorState};
+
+#[cfg(feature = "rt-tokio")]
+use crate::bridge::AsyncBridge;
+
+/// Limits of the safe envelope. Limits left at their defaults are not checked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Envelope {
+    /// Southern edge of the box, along world X (m). Defaults to no limit.
+    pub min_x: f32,
+    /// Northern edge of the box, along world X (m). Defaults to no limit.
+    pub max_x: f32,
+    /// Western edge of the box, along world Y (m). Defaults to no limit.
+    pub min_y: f32,
+    /// Eastern edge of the box, along world Y (m). Defaults to no limit.
+    pub max_y: f32,
+    /// Highest altitude above ground (m). Defaults to no limit.
+    pub max_altitude_agl: f32,
+    /// Highest airspeed [meters/second]. Defaults to no limit.
+    pub max_airspeed: f32,
+    /// Largest roll angle either way (deg). Defaults to no limit.
+    pub max_bank: f32,
+    /// Largest pitch angle either way (deg). Defaults to no limit.
+    pub max_pitch: f32,
+    /// Shortest time between two resets. Defaults to 2 s.
+    pub reset_interval: Duration,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Envelope {
+            min_x: f32::NEG_INFINITY,
+            max_x: f32::INFINITY,
+            min_y: f32::NEG_INFINITY,
+            max_y: f32::INFINITY,
+            max_altitude_agl: f32::INFINITY,
+            max_airspeed: f32::INFINITY,
+            max_bank: f32::INFINITY,
+            max_pitch: f32::INFINITY,
+            reset_interval: Duration::from_secs(2),
+        }
+    }
+}
+
+impl Envelope {
+    /// Returns the first limit `state` exceeds, if any.
+    ///
+    /// Limits are checked in the order of [EnvelopeField]. The returned
+    /// violation has `reset` unset.
+    pub fn check(&self, state: &SimulatorState) -> Option<Violation> {
+        let x = units::length(state.aircraft_position_x);
+        let y = units::length(state.aircraft_position_y);
+        let altitude = units::length(state.altitude_agl);
+        let airspeed = units::velocity(state.airspeed);
+        let roll = units::angle(state.roll);
+        let pitch = units::angle(state.inclination);
+
+        let checks = [
+            (EnvelopeField::PositionX, x, self.min_x, x < self.min_x),
+            (EnvelopeField::PositionX, x, self.max_x, x > self.max_x),
+            (EnvelopeField::PositionY, y, self.min_y, y < self.min_y),
+            (EnvelopeField::PositionY, y, self.max_y, y > self.max_y),
+            (
+                EnvelopeField::AltitudeAgl,
+                altitude,
+                self.max_altitude_agl,
+                altitude > self.max_altitude_agl,
+            ),
+            (
+                EnvelopeField::Airspeed,
+                airspeed,
+                self.max_airspeed,
+                airspeed > self.max_airspeed,
+            ),
+            (
+                EnvelopeField::Bank,
+                roll,
+                self.max_bank,
+                roll.abs() > self.max_bank,
+            ),
+            (
+                EnvelopeField::Pitch,
+                pitch,
+                self.max_pitch,
+                pitch.abs() > self.max_pitch,
+            ),
+        ];
+
+        checks
+            .into_iter()
+            .find(|(_, _, _, exceeded)| *exceeded)
+            .map(|(field, value, limit, _)| Violation {
+                field,
+                value,
+                limit,
+                physics_time: units::time(state.current_physics_time),
+                reset: false,
+            })
+    }
+}
+
+/// State field an [Envelope] limits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvelopeField {
+    /// `aircraft_position_x`
+    PositionX,
+    /// `aircraft_position_y`
+    PositionY,
+    /// `altitude_agl`
+    AltitudeAgl,
+    /// `airspeed`
+    Airspeed,
+    /// `roll`
+    Bank,
+    /// `inclination`
+    Pitch,
+}
+
+/// A state outside the envelope.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// Field that was out of bounds
+    pub field: EnvelopeField,
+    /// Its value, in the units of the limit
+    pub value: f32,
+    /// The limit it exceeded
+    pub limit: f32,
+    /// Physics time of the state (s)
+    pub physics_time: f32,
+    /// Whether the monitor reset the aircraft for it
+    pub reset: bool,
+}
+
+/// Resets the aircraft when it leaves an [Envelope].
+///
+/// Every violation is returned from [feed](Self::feed), but only those that
+/// led to a reset are kept in [violations](Self::violations). Reset times are
+/// read from a [Clock], the [SystemClock] unless the monitor is made with
+/// [with_clock](Self::with_clock).
+#[derive(Debug)]
+pub struct EnvelopeMonitor<C: Clock = SystemClock> {
+    envelope: Envelope,
+    clock: C,
+    last_reset: Option<Instant>,
+    violations: Vec<Violation>,
+}
+
+impl EnvelopeMonitor {
+    /// Monitors `envelope`.
+    pub fn new(envelope: Envelope) -> Self {
+        Self::with_clock(envelope, SystemClock)
+    }
+}
+
+impl<C: Clock> EnvelopeMonitor<C> {
+    /// Monitors `envelope`, spacing resets by the time of `clock`.
+    pub fn with_clock(envelope: Envelope, clock: C) -> Self {
+        EnvelopeMonitor {
+            envelope,
+            clock,
+            last_reset: None,
+            violations: Vec::new(),
+        }
+    }
+
+    /// Returns the envelope.
+    pub fn envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+
+    /// Returns the clock resets are spaced by.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Violations that led to a reset, oldest first.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    /// Number of resets made.
+    pub fn resets(&self) -> usize {
+        self.violations.len()
+    }
+
+    /// Forgets past violations and allows a reset at once.
+    pub fn clear(&mut self) {
+        self.last_reset = None;
+        self.violations.clear();
+    }
+
+    /// Checks `state` and resets the aircraft through `bridge` if it is out of
+    /// the envelope and no reset was made within the reset interval.
+    ///
+    /// Returns the violation, if any, or the error of a failed reset. A failed
+    /// reset still counts, so it is not retried before the interval is over.
+    pub fn feed<B: RealFlightBridge + ?Sized>(
+        &mut self,
+        state: &SimulatorState,
+        bridge: &B,
+    ) -> Result<Option<Violation>, BridgeError> {
+        match self.violation(state) {
+            Some(violation) if violation.reset => {
+                bridge.reset_aircraft()?;
+                Ok(Some(violation))
+            }
+            violation => Ok(violation),
+        }
+    }
+
+    /// Same as [feed](Self::feed), resetting through an async bridge.
+    ///
+    /// Requires the `rt-tokio` feature.
+    #[cfg(feature = "rt-tokio")]
+    pub async fn feed_async<B: AsyncBridge + ?Sized>(
+        &mut self,
+        state: &SimulatorState,
+        bridge: &B,
+    ) -> Result<Option<Violation>, BridgeError> {
+        match self.violation(state) {
+            Some(violation) if violation.reset => {
+                bridge.reset_aircraft().await?;
+                Ok(Some(violation))
+            }
+            violation => Ok(violation),
+        }
+    }
+
+    /// Checks `state` and, if a reset is due, records it.
+    fn violation(&mut self, state: &SimulatorState) -> Option<Violation> {
+        let mut violation = self.envelope.check(state)?;
+        let now = self.clock.now();
+        let due = self
+            .last_reset
+            .is_none_or(|last| now.saturating_duration_since(last) >= self.envelope.reset_interval);
+
+        if due {
+            warn!(
+                "{:?} {} beyond limit {}, resetting aircraft",
+                violation.field, violation.value, violation.limit
+            );
+            violation.reset = true;
+            self.last_reset = Some(now);
+            self.violations.push(violation.clone());
+        }
+        Some(violation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ControlInputs;
+    use crate::clock::ManualClock;
+    use crate::decoders::{to_angle, to_length, to_time, to_velocity};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Bridge counting resets.
+    #[derive(Default)]
+    struct StubBridge {
+        resets: AtomicUsize,
+    }
+
+    impl StubBridge {
+        fn resets(&self) -> usize {
+            self.resets.load(Ordering::SeqCst)
+        }
+    }
+
+    impl RealFlightBridge for StubBridge {
+        fn exchange_data(&self, _: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+            Ok(SimulatorState::default())
+        }
+
+        fn enable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        fn disable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        fn reset_aircraft(&self) -> Result<(), BridgeError> {
+            self.resets.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    impl AsyncBridge for StubBridge {
+        async fn exchange_data(&self, _: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+            Ok(SimulatorState::default())
+        }
+
+        async fn enable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        async fn disable_rc(&self) -> Result<(), BridgeError> {
+            Ok(())
+        }
+
+        async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+            self.resets.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn envelope() -> Envelope {
+        Envelope {
+            min_x: -100.0,
+            max_x: 100.0,
+            min_y: -50.0,
+            max_y: 50.0,
+            max_altitude_agl: 120.0,
+            max_airspeed: 40.0,
+            max_bank: 60.0,
+            max_pitch: 45.0,
+            ..Default::default()
+        }
+    }
+
+    fn monitor() -> EnvelopeMonitor<ManualClock> {
+        EnvelopeMonitor::with_clock(envelope(), ManualClock::new())
+    }
+
+    /// State at `time` seconds, `x` meters north of the origin.
+    fn state(time: f32, x: f32) -> SimulatorState {
+        SimulatorState {
+            current_physics_time: to_time(time),
+            aircraft_position_x: to_length(x),
+            altitude_agl: to_length(30.0),
+            airspeed: to_velocity(20.0),
+            ..Default::default()
+        }
+    }
+
+    /// A flight drifting north out of the box and staying out.
+    fn drift() -> impl Iterator<Item = SimulatorState> {
+        (0..100).map(|i| state(i as f32 * 0.02, i as f32 * 2.0))
+    }
+
+    #[test]
+    fn default_envelope_is_unbounded() {
+        let state = SimulatorState {
+            aircraft_position_x: to_length(1e6),
+            airspeed: to_velocity(500.0),
+            roll: to_angle(180.0),
+            ..Default::default()
+        };
+
+        assert_eq!(Envelope::default().check(&state), None);
+    }
+
+    #[test]
+    fn inside_envelope() {
+        assert_eq!(envelope().check(&state(0.0, 99.0)), None);
+        assert_eq!(envelope().check(&state(0.0, -99.0)), None);
+    }
+
+    #[test]
+    fn reports_offending_field() {
+        let cases = [
+            (
+                SimulatorState {
+                    aircraft_position_x: to_length(-101.0),
+                    ..Default::default()
+                },
+                EnvelopeField::PositionX,
+                -101.0,
+                -100.0,
+            ),
+            (
+                SimulatorState {
+                    aircraft_position_y: to_length(51.0),
+                    ..Default::default()
+                },
+                EnvelopeField::PositionY,
+                51.0,
+                50.0,
+            ),
+            (
+                SimulatorState {
+                    altitude_agl: to_length(121.0),
+                    ..Default::default()
+                },
+                EnvelopeField::AltitudeAgl,
+                121.0,
+                120.0,
+            ),
+            (
+                SimulatorState {
+                    airspeed: to_velocity(41.0),
+                    ..Default::default()
+                },
+                EnvelopeField::Airspeed,
+                41.0,
+                40.0,
+            ),
+            (
+                SimulatorState {
+                    roll: to_angle(-61.0),
+                    ..Default::default()
+                },
+                EnvelopeField::Bank,
+                -61.0,
+                60.0,
+            ),
+            (
+                SimulatorState {
+                    inclination: to_angle(46.0),
+                    ..Default::default()
+                },
+                EnvelopeField::Pitch,
+                46.0,
+                45.0,
+            ),
+        ];
+
+        for (state, field, value, limit) in cases {
+            let violation = envelope().check(&state).unwrap();
+            assert_eq!(violation.field, field);
+            approx::assert_relative_eq!(violation.value, value, epsilon = 1e-3);
+            approx::assert_relative_eq!(violation.limit, limit);
+            assert!(!violation.reset);
+        }
+    }
+
+    #[test]
+    fn resets_once_per_excursion() {
+        let mut monitor = monitor();
+        let bridge = StubBridge::default();
+
+        let mut reported = 0;
+        for state in drift() {
+            if monitor.feed(&state, &bridge).unwrap().is_some() {
+                reported += 1;
+            }
+        }
+
+        assert_eq!(bridge.resets(), 1);
+        assert_eq!(monitor.resets(), 1);
+        // Every state past 100 m is reported, only the first resets
+        assert_eq!(reported, 49);
+        let violation = &monitor.violations()[0];
+        assert_eq!(violation.field, EnvelopeField::PositionX);
+        approx::assert_relative_eq!(violation.value, 102.0);
+        approx::assert_relative_eq!(violation.physics_time, 1.02);
+        assert!(violation.reset);
+    }
+
+    #[test]
+    fn resets_again_after_interval() {
+        let mut monitor = monitor();
+        let bridge = StubBridge::default();
+        let outside = state(0.0, 150.0);
+
+        monitor.feed(&outside, &bridge).unwrap();
+        monitor.clock().advance(Duration::from_millis(1999));
+        let held = monitor.feed(&outside, &bridge).unwrap().unwrap();
+        assert!(!held.reset);
+        assert_eq!(bridge.resets(), 1);
+
+        monitor.clock().advance(Duration::from_millis(1));
+        assert!(monitor.feed(&outside, &bridge).unwrap().unwrap().reset);
+        assert_eq!(bridge.resets(), 2);
+        assert_eq!(monitor.violations().len(), 2);
+    }
+
+    #[test]
+    fn clear_allows_reset() {
+        let mut monitor = monitor();
+        let bridge = StubBridge::default();
+        let outside = state(0.0, 150.0);
+        monitor.feed(&outside, &bridge).unwrap();
+
+        monitor.clear();
+        assert!(monitor.violations().is_empty());
+        monitor.feed(&outside, &bridge).unwrap();
+
+        assert_eq!(bridge.resets(), 2);
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    #[tokio::test]
+    async fn resets_once_per_excursion_async() {
+        let mut monitor = monitor();
+        let bridge = StubBridge::default();
+
+        for state in drift() {
+            monitor.feed_async(&state, &bridge).await.unwrap();
+        }
+
+        assert_eq!(bridge.resets(), 1);
+        assert_eq!(monitor.violations()[0].field, EnvelopeField::PositionX);
+    }
+}
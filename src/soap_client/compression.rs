@@ -0,0 +1,521 @@
+//! Decoding of `gzip` and `deflate` compressed response bodies.
+//!
+//! Enabled by the `compression` feature. Requests then advertise
+//! `Accept-Encoding: gzip, deflate`, which a compressing proxy between the
+//! bridge and RealFlight can honor. The decoder is a small RFC 1951 inflater
+//! with the RFC 1952 (gzip) and RFC 1950 (zlib) wrappers; it only runs when a
+//! response declares a `Content-Encoding`.
+
+use crate::BridgeError;
+
+/// `Content-Encoding` of a response body.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ContentEncoding {
+    #[default]
+    Identity,
+    Gzip,
+    /// Zlib-wrapped, or raw deflate as some servers send
+    Deflate,
+}
+
+impl ContentEncoding {
+    /// Parses a `Content-Encoding` header value, `None` if it is not supported.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            v if v.eq_ignore_ascii_case("identity") => Some(ContentEncoding::Identity),
+            v if v.eq_ignore_ascii_case("gzip") || v.eq_ignore_ascii_case("x-gzip") => {
+                Some(ContentEncoding::Gzip)
+            }
+            v if v.eq_ignore_ascii_case("deflate") => Some(ContentEncoding::Deflate),
+            _ => None,
+        }
+    }
+}
+
+/// Decompresses `body`, failing with [BridgeError::Protocol] if it is corrupt
+/// or inflates beyond `limit` bytes. An identity body is returned as is.
+pub(crate) fn decode(
+    encoding: ContentEncoding,
+    body: Vec<u8>,
+    limit: usize,
+) -> Result<Vec<u8>, BridgeError> {
+    match encoding {
+        ContentEncoding::Identity => Ok(body),
+        ContentEncoding::Gzip => gunzip(&body, limit),
+        ContentEncoding::Deflate if is_zlib_header(&body) => unzlib(&body, limit),
+        ContentEncoding::Deflate => inflate(&body, limit).map(|(out, _)| out),
+    }
+}
+
+fn corrupt(what: &str) -> BridgeError {
+    BridgeError::Protocol(format!("Corrupt compressed response: {}", what))
+}
+
+/// Unwraps an RFC 1952 gzip member.
+fn gunzip(data: &[u8], limit: usize) -> Result<Vec<u8>, BridgeError> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    if data.len() < 18 || data[0] != 0x1f || data[1] != 0x8b || data[2] != 8 {
+        return Err(corrupt("missing gzip header"));
+    }
+    let flags = data[3];
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let len = data
+            .get(pos..pos + 2)
+            .ok_or_else(|| corrupt("truncated gzip header"))?;
+        pos += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = data
+                .get(pos..)
+                .and_then(|rest| rest.iter().position(|&b| b == 0))
+                .ok_or_else(|| corrupt("truncated gzip header"))?;
+            pos += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+
+    let compressed = data
+        .get(pos..)
+        .ok_or_else(|| corrupt("truncated gzip header"))?;
+    let (out, used) = inflate(compressed, limit)?;
+    let trailer = compressed
+        .get(used..used + 8)
+        .ok_or_else(|| corrupt("truncated gzip trailer"))?;
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if crc != crc32(&out) || size != out.len() as u32 {
+        return Err(corrupt("gzip checksum mismatch"));
+    }
+    Ok(out)
+}
+
+/// Whether `data` starts with an RFC 1950 zlib header.
+fn is_zlib_header(data: &[u8]) -> bool {
+    matches!(data, [cmf, flg, ..]
+        if cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0)
+}
+
+/// Unwraps an RFC 1950 zlib stream.
+fn unzlib(data: &[u8], limit: usize) -> Result<Vec<u8>, BridgeError> {
+    const FDICT: u8 = 0x20;
+
+    if data[1] & FDICT != 0 {
+        return Err(corrupt("zlib preset dictionaries are not supported"));
+    }
+    let (out, used) = inflate(&data[2..], limit)?;
+    let trailer = data
+        .get(2 + used..2 + used + 4)
+        .ok_or_else(|| corrupt("truncated zlib trailer"))?;
+    if u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]) != adler32(&out) {
+        return Err(corrupt("zlib checksum mismatch"));
+    }
+    Ok(out)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % MOD;
+        b = (b + a) % MOD;
+    }
+    b << 16 | a
+}
+
+const MAX_BITS: usize = 15;
+
+/// Base lengths and extra bits of length symbols 257..285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Base distances and extra bits of distance symbols 0..29
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Order in which code length code lengths are stored in a dynamic block
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Reads the bits of a deflate stream, least significant first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Next byte to load
+    pos: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, need: u32) -> Result<u32, BridgeError> {
+        while self.count < need {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or_else(|| corrupt("truncated deflate stream"))?;
+            self.pos += 1;
+            self.buffer |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1u32 << need) - 1);
+        self.buffer >>= need;
+        self.count -= need;
+        Ok(value)
+    }
+
+    /// Drops the bits left in the current byte.
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+/// Canonical Huffman code, decoded a bit at a time.
+struct Huffman {
+    /// Number of codes of each length
+    counts: [u16; MAX_BITS + 1],
+    /// Symbols ordered by code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, BridgeError> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // Reject over-subscribed codes
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return Err(corrupt("invalid Huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut BitReader<'_>) -> Result<u16, BridgeError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.bits(1)? as i32;
+            let count = i32::from(count);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(corrupt("invalid Huffman code"))
+    }
+}
+
+/// Inflates a raw RFC 1951 stream into at most `limit` bytes, returning the
+/// output and the number of input bytes consumed.
+fn inflate(data: &[u8], limit: usize) -> Result<(Vec<u8>, usize), BridgeError> {
+    let mut bits = BitReader {
+        data,
+        pos: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut out = Vec::with_capacity(data.len().saturating_mul(4).min(limit));
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => stored(&mut bits, &mut out, limit)?,
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths)?;
+                let distances = Huffman::new(&[5; 30])?;
+                codes(&mut bits, &mut out, &literals, &distances, limit)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut bits)?;
+                codes(&mut bits, &mut out, &literals, &distances, limit)?;
+            }
+            _ => return Err(corrupt("invalid block type")),
+        }
+        if last {
+            break;
+        }
+    }
+    bits.align();
+    Ok((out, bits.pos))
+}
+
+fn stored(bits: &mut BitReader<'_>, out: &mut Vec<u8>, limit: usize) -> Result<(), BridgeError> {
+    bits.align();
+    let header = bits
+        .data
+        .get(bits.pos..bits.pos + 4)
+        .ok_or_else(|| corrupt("truncated stored block"))?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    if len != !u16::from_le_bytes([header[2], header[3]]) {
+        return Err(corrupt("stored block length mismatch"));
+    }
+    bits.pos += 4;
+    let block = bits
+        .data
+        .get(bits.pos..bits.pos + len as usize)
+        .ok_or_else(|| corrupt("truncated stored block"))?;
+    check_limit(out.len() + block.len(), limit)?;
+    out.extend_from_slice(block);
+    bits.pos += block.len();
+    Ok(())
+}
+
+fn dynamic_codes(bits: &mut BitReader<'_>) -> Result<(Huffman, Huffman), BridgeError> {
+    let literal_count = bits.bits(5)? as usize + 257;
+    let distance_count = bits.bits(5)? as usize + 1;
+    let code_length_count = bits.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(corrupt("too many codes"));
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = bits.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths)?;
+
+    let mut lengths = vec![0u8; literal_count + distance_count];
+    let mut index = 0;
+    while index < lengths.len() {
+        let symbol = code_length_code.decode(bits)?;
+        let (value, repeat) = match symbol {
+            0..=15 => {
+                lengths[index] = symbol as u8;
+                index += 1;
+                continue;
+            }
+            16 => {
+                let previous = *index
+                    .checked_sub(1)
+                    .and_then(|i| lengths.get(i))
+                    .ok_or_else(|| corrupt("repeat without a previous length"))?;
+                (previous, 3 + bits.bits(2)? as usize)
+            }
+            17 => (0, 3 + bits.bits(3)? as usize),
+            _ => (0, 11 + bits.bits(7)? as usize),
+        };
+        let run = lengths
+            .get_mut(index..index + repeat)
+            .ok_or_else(|| corrupt("code lengths overrun"))?;
+        run.fill(value);
+        index += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err(corrupt("missing end-of-block code"));
+    }
+
+    let literals = Huffman::new(&lengths[..literal_count])?;
+    let distances = Huffman::new(&lengths[literal_count..])?;
+    Ok((literals, distances))
+}
+
+fn codes(
+    bits: &mut BitReader<'_>,
+    out: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+    limit: usize,
+) -> Result<(), BridgeError> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => {
+                check_limit(out.len() + 1, limit)?;
+                out.push(symbol as u8);
+            }
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                let (&base, &extra) = LENGTH_BASE
+                    .get(index)
+                    .zip(LENGTH_EXTRA.get(index))
+                    .ok_or_else(|| corrupt("invalid length symbol"))?;
+                let len = base as usize + bits.bits(u32::from(extra))? as usize;
+
+                let index = distances.decode(bits)? as usize;
+                let (&base, &extra) = DIST_BASE
+                    .get(index)
+                    .zip(DIST_EXTRA.get(index))
+                    .ok_or_else(|| corrupt("invalid distance symbol"))?;
+                let distance = base as usize + bits.bits(u32::from(extra))? as usize;
+                if distance > out.len() {
+                    return Err(corrupt("distance too far back"));
+                }
+
+                check_limit(out.len() + len, limit)?;
+                let start = out.len() - distance;
+                // Copies may overlap their own output
+                for i in 0..len {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
+
+fn check_limit(length: usize, limit: usize) -> Result<(), BridgeError> {
+    if length > limit {
+        return Err(BridgeError::Protocol(format!(
+            "Decompressed response body exceeds the {} byte limit",
+            limit
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATE: &str = include_str!("../../testdata/responses/return-data-200.xml");
+    const STATE_GZ: &[u8] = include_bytes!("../../testdata/responses/return-data-200.xml.gz");
+    const STATE_ZLIB: &[u8] = include_bytes!("../../testdata/responses/return-data-200.xml.zz");
+
+    #[test]
+    fn gunzips_state_response() {
+        let body = decode(ContentEncoding::Gzip, STATE_GZ.to_vec(), usize::MAX).unwrap();
+        assert_eq!(body, STATE.as_bytes());
+    }
+
+    #[test]
+    fn inflates_zlib_state_response() {
+        let body = decode(ContentEncoding::Deflate, STATE_ZLIB.to_vec(), usize::MAX).unwrap();
+        assert_eq!(body, STATE.as_bytes());
+    }
+
+    #[test]
+    fn inflates_raw_deflate() {
+        // The zlib stream without its header and checksum
+        let raw = STATE_ZLIB[2..STATE_ZLIB.len() - 4].to_vec();
+        let body = decode(ContentEncoding::Deflate, raw, usize::MAX).unwrap();
+        assert_eq!(body, STATE.as_bytes());
+    }
+
+    #[test]
+    fn inflates_fixed_and_stored_blocks() {
+        // zlib.compress(b"hello hello hello", level) with fixed Huffman codes
+        // at level 9 and a stored block at level 0
+        let fixed = [
+            0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00, 0x3a, 0x2e,
+            0x06, 0x7d,
+        ];
+        let stored = [
+            0x78, 0x01, 0x01, 0x11, 0x00, 0xee, 0xff, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x20, 0x68,
+            0x65, 0x6c, 0x6c, 0x6f, 0x20, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x3a, 0x2e, 0x06, 0x7d,
+        ];
+
+        for data in [&fixed[..], &stored[..]] {
+            let body = decode(ContentEncoding::Deflate, data.to_vec(), usize::MAX).unwrap();
+            assert_eq!(body, b"hello hello hello");
+        }
+    }
+
+    #[test]
+    fn identity_body_is_returned_unchanged() {
+        let body = b"<xml/>".to_vec();
+        let ptr = body.as_ptr();
+
+        let decoded = decode(ContentEncoding::Identity, body, 0).unwrap();
+
+        assert_eq!(decoded.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn rejects_body_inflating_beyond_limit() {
+        let result = decode(ContentEncoding::Gzip, STATE_GZ.to_vec(), 1024);
+        assert!(matches!(result, Err(BridgeError::Protocol(_))));
+    }
+
+    #[test]
+    fn rejects_corrupt_checksum() {
+        let mut data = STATE_GZ.to_vec();
+        let crc = data.len() - 8;
+        data[crc] ^= 0xff;
+
+        let result = decode(ContentEncoding::Gzip, data, usize::MAX);
+        assert!(matches!(result, Err(BridgeError::Protocol(msg)) if msg.contains("checksum")));
+    }
+
+    #[test]
+    fn rejects_truncated_stream() {
+        let data = STATE_GZ[..STATE_GZ.len() / 2].to_vec();
+        let result = decode(ContentEncoding::Gzip, data, usize::MAX);
+        assert!(matches!(result, Err(BridgeError::Protocol(_))));
+    }
+
+    #[test]
+    fn parses_content_encoding_values() {
+        assert_eq!(ContentEncoding::parse("gzip"), Some(ContentEncoding::Gzip));
+        assert_eq!(
+            ContentEncoding::parse(" GZIP "),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(
+            ContentEncoding::parse("x-gzip"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(
+            ContentEncoding::parse("Deflate"),
+            Some(ContentEncoding::Deflate)
+        );
+        assert_eq!(
+            ContentEncoding::parse("identity"),
+            Some(ContentEncoding::Identity)
+        );
+        assert_eq!(ContentEncoding::parse("br"), None);
+    }
+}
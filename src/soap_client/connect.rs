@@ -0,0 +1,169 @@
+//! Establishes TCP connections to the simulator, optionally from a fixed local address.
+
+use std::io;
+use std::mem::MaybeUninit;
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+use socket2::{Domain, Protocol, SockRef, Socket, Type};
+
+/// Reason a connection attempt failed.
+#[derive(Debug)]
+pub(crate) enum ConnectError {
+    /// The socket could not be bound to the requested local address
+    Bind(SocketAddr, io::Error),
+    /// The connection to the simulator could not be established
+    Connect(io::Error),
+}
+
+impl std::fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectError::Bind(addr, e) => {
+                write!(f, "Failed to bind to local address {}: {}", addr, e)
+            }
+            ConnectError::Connect(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Connects to `addr`, binding to `local_bind` first when one is provided.
+pub(crate) fn connect(
+    addr: &SocketAddr,
+    local_bind: Option<SocketAddr>,
+    timeout: Duration,
+) -> Result<TcpStream, ConnectError> {
+    let Some(local_bind) = local_bind else {
+        return TcpStream::connect_timeout(addr, timeout).map_err(ConnectError::Connect);
+    };
+
+    let socket = Socket::new(
+        Domain::for_address(*addr),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )
+    .map_err(ConnectError::Connect)?;
+    socket
+        .bind(&local_bind.into())
+        .map_err(|e| ConnectError::Bind(local_bind, e))?;
+    socket
+        .connect_timeout(&(*addr).into(), timeout)
+        .map_err(ConnectError::Connect)?;
+
+    Ok(socket.into())
+}
+
+/// Async version of [connect]. Does not apply a timeout; callers wrap it as needed.
+#[cfg(feature = "rt-tokio")]
+pub(crate) async fn connect_async(
+    addr: SocketAddr,
+    local_bind: Option<SocketAddr>,
+) -> Result<tokio::net::TcpStream, ConnectError> {
+    let Some(local_bind) = local_bind else {
+        return tokio::net::TcpStream::connect(addr)
+            .await
+            .map_err(ConnectError::Connect);
+    };
+
+    let socket = match addr {
+        SocketAddr::V4(_) => tokio::net::TcpSocket::new_v4(),
+        SocketAddr::V6(_) => tokio::net::TcpSocket::new_v6(),
+    }
+    .map_err(ConnectError::Connect)?;
+    socket
+        .bind(local_bind)
+        .map_err(|e| ConnectError::Bind(local_bind, e))?;
+
+    socket.connect(addr).await.map_err(ConnectError::Connect)
+}
+
+/// Connection waiting in a pool, with the time it was opened.
+pub(crate) struct Pooled<S> {
+    pub(crate) stream: S,
+    created: Instant,
+}
+
+impl<S> Pooled<S> {
+    pub(crate) fn new(stream: S) -> Self {
+        Pooled {
+            stream,
+            created: Instant::now(),
+        }
+    }
+
+    /// Whether the connection was opened more than `max_idle` ago.
+    pub(crate) fn is_stale(&self, max_idle: Duration) -> bool {
+        self.created.elapsed() > max_idle
+    }
+}
+
+/// Whether an unused connection can still carry a request. The socket must be
+/// in nonblocking mode.
+///
+/// The simulator never speaks first, so anything readable, whether data or
+/// the end of the stream, means the peer has given up on the connection.
+pub(crate) fn is_open(socket: SockRef<'_>) -> bool {
+    let mut byte = [MaybeUninit::uninit()];
+    matches!(socket.peek(&mut byte), Err(e) if e.kind() == io::ErrorKind::WouldBlock)
+}
+
+/// [is_open] for a stream in blocking mode, which it is left in.
+pub(crate) fn is_open_blocking(stream: &TcpStream) -> bool {
+    stream.set_nonblocking(true).is_ok()
+        && is_open(SockRef::from(stream))
+        && stream.set_nonblocking(false).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn connects_without_local_bind() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let stream = connect(&addr, None, Duration::from_secs(1));
+        assert!(stream.is_ok());
+    }
+
+    #[test]
+    fn binds_to_requested_local_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let local: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+        let stream = connect(&addr, Some(local), Duration::from_secs(1)).unwrap();
+        let (_, peer) = listener.accept().unwrap();
+
+        assert_eq!(peer, stream.local_addr().unwrap());
+    }
+
+    #[test]
+    fn open_until_peer_closes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        stream.set_nonblocking(true).unwrap();
+        let (peer, _) = listener.accept().unwrap();
+
+        assert!(is_open(SockRef::from(&stream)));
+
+        drop(peer);
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!is_open(SockRef::from(&stream)));
+    }
+
+    #[test]
+    fn reports_bind_failure() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        // TEST-NET-1 address is never assigned to a local interface
+        let local: SocketAddr = "192.0.2.1:0".parse().unwrap();
+
+        match connect(&addr, Some(local), Duration::from_secs(1)) {
+            Err(ConnectError::Bind(bound, _)) => assert_eq!(bound, local),
+            other => panic!("expected bind error, got {:?}", other),
+        }
+    }
+}
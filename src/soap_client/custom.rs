@@ -0,0 +1,83 @@
+//! Adapters running bridges over a user-supplied transport.
+//!
+//! Enabled by the `custom-transport` feature. The bridge keeps the statistics,
+//! so a transport only has to move one request and its response per call.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::{BridgeError, StatisticsEngine};
+
+use super::{SoapClient, SoapResponse, Wait};
+
+#[cfg(feature = "rt-tokio")]
+use super::AsyncSoapClient;
+#[cfg(feature = "rt-tokio")]
+use crate::bridge::BoxFuture;
+
+/// Counts the exchanges of a wrapped transport in the bridge's statistics.
+pub(crate) struct Counted<C> {
+    inner: C,
+    statistics: Arc<StatisticsEngine>,
+}
+
+impl<C> Counted<C> {
+    pub(crate) fn new(inner: C, statistics: Arc<StatisticsEngine>) -> Self {
+        Counted { inner, statistics }
+    }
+
+    /// Records one exchange that took since `start`, and whether it failed.
+    fn record<T>(&self, start: Instant, result: &Result<T, BridgeError>) {
+        self.statistics.increment_request_count();
+        self.statistics.add_io_time(start.elapsed());
+        if result.is_err() {
+            self.statistics.increment_error_count();
+        }
+    }
+}
+
+impl SoapClient for Counted<Box<dyn SoapClient>> {
+    fn send_action_with(
+        &self,
+        action: &str,
+        body: &str,
+        wait: Wait,
+    ) -> Result<SoapResponse, BridgeError> {
+        let start = Instant::now();
+        let result = self.inner.send_action_with(action, body, wait);
+        self.record(start, &result);
+        result
+    }
+
+    fn shutdown(self: Box<Self>) -> Result<(), BridgeError> {
+        self.inner.shutdown()
+    }
+}
+
+/// Object-safe form of [AsyncSoapClient], so an
+/// [AsyncLocalBridge](crate::AsyncLocalBridge) can hold any transport.
+#[cfg(feature = "rt-tokio")]
+pub(crate) trait DynAsyncSoapClient: Send + Sync {
+    fn send_action<'a>(
+        &'a self,
+        action: &'a str,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<SoapResponse, BridgeError>>;
+}
+
+#[cfg(feature = "rt-tokio")]
+impl<C: AsyncSoapClient> DynAsyncSoapClient for Counted<C> {
+    fn send_action<'a>(
+        &'a self,
+        action: &'a str,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<SoapResponse, BridgeError>> {
+        Box::pin(async move {
+            let _in_flight = self.statistics.track_in_flight();
+            let start = Instant::now();
+            let result = self.inner.send_action(action, body).await;
+            self.record(start, &result);
+            result
+        })
+    }
+}
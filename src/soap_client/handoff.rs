@@ -0,0 +1,257 @@
+//! Bounded channel handing connections from the async pool's background task
+//! to requests.
+//!
+//! Values sit in a lock-free crossbeam queue, so any number of tasks can wait
+//! on one receiver without an outer lock serializing them. [Notify] wakes a
+//! receiver when a value arrives and the sender when room frees up.
+
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crossbeam_channel::{Receiver, Sender, TryRecvError, TrySendError, bounded};
+use tokio::sync::Notify;
+
+/// Creates a handoff holding at most `capacity` values.
+pub fn channel<T>(capacity: usize) -> (HandoffSender<T>, HandoffReceiver<T>) {
+    let (tx, rx) = bounded(capacity);
+    let shared = Arc::new(Shared {
+        rx,
+        ready: Notify::new(),
+        room: Notify::new(),
+        closed: AtomicBool::new(false),
+    });
+    (
+        HandoffSender {
+            tx: Some(tx),
+            shared: Arc::clone(&shared),
+        },
+        HandoffReceiver { shared },
+    )
+}
+
+struct Shared<T> {
+    rx: Receiver<T>,
+    /// Signalled when a value is queued, or the sender is dropped
+    ready: Notify,
+    /// Signalled when a value is taken, or the receiver is closed
+    room: Notify,
+    /// Set once the receiver stops taking values
+    closed: AtomicBool,
+}
+
+impl<T> Shared<T> {
+    fn discard(&self) {
+        while self.rx.try_recv().is_ok() {}
+    }
+}
+
+/// Sending half of a [channel].
+pub struct HandoffSender<T> {
+    /// Dropped before waking receivers, so they observe the disconnect
+    tx: Option<Sender<T>>,
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> HandoffSender<T> {
+    /// Queues `value`, waiting for room. Gives the value back once the
+    /// receiver is closed or dropped.
+    pub async fn send(&self, mut value: T) -> Result<(), T> {
+        let tx = self.tx.as_ref().expect("sender is only taken on drop");
+        loop {
+            // Registered before trying, so a value taken in between still wakes us
+            let mut room = pin!(self.shared.room.notified());
+            room.as_mut().enable();
+
+            if self.shared.closed.load(Ordering::SeqCst) {
+                return Err(value);
+            }
+            match tx.try_send(value) {
+                Ok(()) => {
+                    // Raced with close, which may have drained before this landed
+                    if self.shared.closed.load(Ordering::SeqCst) {
+                        self.shared.discard();
+                    }
+                    self.shared.ready.notify_one();
+                    return Ok(());
+                }
+                Err(TrySendError::Full(v)) => value = v,
+                Err(TrySendError::Disconnected(v)) => return Err(v),
+            }
+            room.await;
+        }
+    }
+
+    /// Number of values waiting to be taken.
+    pub fn len(&self) -> usize {
+        self.tx.as_ref().map_or(0, Sender::len)
+    }
+
+    /// Whether no value is waiting to be taken.
+    #[cfg(any(test, feature = "bench-internals"))]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for HandoffSender<T> {
+    fn drop(&mut self) {
+        self.tx = None;
+        self.shared.ready.notify_waiters();
+    }
+}
+
+/// Receiving half of a [channel], shared by reference between tasks.
+pub struct HandoffReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> HandoffReceiver<T> {
+    /// Takes the next value, waiting for one. Returns `None` once the sender
+    /// is dropped and every value has been taken.
+    ///
+    /// Cancel-safe: a dropped future never removes a value.
+    pub async fn recv(&self) -> Option<T> {
+        loop {
+            // Registered before trying, so a value queued in between still wakes us
+            let mut ready = pin!(self.shared.ready.notified());
+            ready.as_mut().enable();
+
+            match self.shared.rx.try_recv() {
+                Ok(value) => {
+                    self.shared.room.notify_one();
+                    return Some(value);
+                }
+                Err(TryRecvError::Disconnected) => return None,
+                Err(TryRecvError::Empty) => ready.await,
+            }
+        }
+    }
+
+    /// Whether no value is waiting to be taken.
+    pub fn is_empty(&self) -> bool {
+        self.shared.rx.is_empty()
+    }
+
+    /// Stops taking values, failing any pending and later sends. Values
+    /// already queued are dropped, so connections close straight away.
+    pub fn close(&self) {
+        self.shared.closed.store(true, Ordering::SeqCst);
+        self.shared.discard();
+        self.shared.room.notify_waiters();
+    }
+}
+
+impl<T> Drop for HandoffReceiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn recv_waits_for_send() {
+        let (tx, rx) = channel(1);
+
+        let receiver = tokio::spawn(async move { rx.recv().await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        tx.send(7).await.unwrap();
+
+        assert_eq!(receiver.await.unwrap(), Some(7));
+    }
+
+    #[tokio::test]
+    async fn concurrent_receivers_each_take_one_value() {
+        let (tx, rx) = channel(4);
+        let rx = Arc::new(rx);
+
+        let receivers: Vec<_> = (0..4)
+            .map(|_| {
+                let rx = Arc::clone(&rx);
+                tokio::spawn(async move { rx.recv().await })
+            })
+            .collect();
+        for value in 0..4 {
+            tx.send(value).await.unwrap();
+        }
+
+        let mut values = Vec::new();
+        for receiver in receivers {
+            values.push(receiver.await.unwrap().unwrap());
+        }
+        values.sort();
+        assert_eq!(values, vec![0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn send_waits_for_room() {
+        let (tx, rx) = channel(1);
+        tx.send(1).await.unwrap();
+
+        let sender = tokio::spawn(async move {
+            tx.send(2).await.unwrap();
+            tx
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!sender.is_finished());
+
+        assert_eq!(rx.recv().await, Some(1));
+        let tx = sender.await.unwrap();
+        assert_eq!(tx.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn recv_returns_none_after_sender_dropped() {
+        let (tx, rx) = channel(1);
+        tx.send(1).await.unwrap();
+
+        let receiver = tokio::spawn(async move {
+            let first = rx.recv().await;
+            (first, rx.recv().await)
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(tx);
+
+        assert_eq!(receiver.await.unwrap(), (Some(1), None));
+    }
+
+    #[tokio::test]
+    async fn close_fails_waiting_send() {
+        let (tx, rx) = channel(1);
+        tx.send(1).await.unwrap();
+
+        let sender = tokio::spawn(async move { tx.send(2).await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        rx.close();
+
+        assert_eq!(sender.await.unwrap(), Err(2));
+    }
+
+    #[tokio::test]
+    async fn close_drops_queued_values() {
+        let (tx, rx) = channel(2);
+        let value = Arc::new(());
+        tx.send(Arc::clone(&value)).await.unwrap();
+
+        rx.close();
+
+        assert_eq!(Arc::strong_count(&value), 1);
+        assert!(tx.send(value).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn cancelled_recv_leaves_value() {
+        let (tx, rx) = channel(1);
+
+        let waiting = tokio::time::timeout(Duration::from_millis(10), rx.recv()).await;
+        assert!(waiting.is_err());
+        tx.send(1).await.unwrap();
+
+        assert!(!rx.is_empty());
+        assert_eq!(rx.recv().await, Some(1));
+    }
+}
@@ -1,15 +1,28 @@
+use std::sync::Arc;
+use std::time::Instant;
+
 use crate::BridgeError;
+use crate::constants::SUMMARY_BODY_LIMIT;
 use crate::decoders::extract_element;
 
 #[cfg(feature = "rt-tokio")]
 use std::future::Future;
 
+#[cfg(feature = "compression")]
+pub(crate) mod compression;
+pub(crate) mod connect;
+#[cfg(feature = "custom-transport")]
+pub(crate) mod custom;
 pub(crate) mod pool;
-#[cfg(test)]
+pub(crate) mod sizing;
+#[cfg(any(test, feature = "test-support"))]
 pub(crate) mod stub;
 pub(crate) mod tcp;
+pub(crate) mod transcript;
 pub(crate) mod xml;
 
+#[cfg(feature = "rt-tokio")]
+pub(crate) mod handoff;
 #[cfg(feature = "rt-tokio")]
 pub(crate) mod pool_async;
 #[cfg(all(test, feature = "rt-tokio"))]
@@ -21,8 +34,10 @@ pub(crate) use xml::encode_envelope;
 
 /// Response from a SOAP request to the RealFlight simulator
 #[derive(Debug)]
-pub(crate) struct SoapResponse {
+pub struct SoapResponse {
+    /// HTTP status code; RealFlight Link answers faults with 500
     pub status_code: u32,
+    /// Response body, a SOAP envelope
     pub body: String,
 }
 
@@ -34,8 +49,38 @@ impl SoapResponse {
             None => "Failed to extract error message".into(),
         }
     }
+
+    /// Summarizes the response for a [RequestTap]
+    pub fn summary(&self) -> SoapResponseSummary {
+        let mut end = self.body.len().min(SUMMARY_BODY_LIMIT);
+        while !self.body.is_char_boundary(end) {
+            end -= 1;
+        }
+        SoapResponseSummary {
+            status_code: self.status_code,
+            body: self.body[..end].to_string(),
+            truncated: end < self.body.len(),
+        }
+    }
+}
+
+/// Outcome of a simulator request, as seen by a [RequestTap].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SoapResponseSummary {
+    /// HTTP status code returned by the simulator
+    pub status_code: u32,
+    /// Response body, cut to at most [SUMMARY_BODY_LIMIT] bytes
+    pub body: String,
+    /// Whether `body` was cut short
+    pub truncated: bool,
 }
 
+/// Callback invoked after each completed simulator request with the SOAP action,
+/// the request body and a summary of the response.
+///
+/// Runs on the thread or task that sent the request, so it should return quickly.
+pub type RequestTap = Arc<dyn Fn(&str, &str, &SoapResponseSummary) + Send + Sync>;
+
 impl From<SoapResponse> for Result<(), BridgeError> {
     fn from(val: SoapResponse) -> Self {
         match val.status_code {
@@ -45,9 +90,57 @@ impl From<SoapResponse> for Result<(), BridgeError> {
     }
 }
 
+/// Whether `action` may be sent again after a kept connection failed
+/// mid-request.
+///
+/// The simulator may have applied a request it never answered. Resending a
+/// control frame is harmless, but injecting or restoring the controller or
+/// resetting the aircraft twice is not.
+pub(crate) fn is_resendable(action: &str) -> bool {
+    action == "ExchangeData"
+}
+
+/// How long a request may block before failing with [BridgeError::Timeout]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wait {
+    /// Wait for a pooled connection, then apply the configured I/O timeout
+    Blocking,
+    /// Fail unless a pooled connection is ready, then apply the configured I/O timeout
+    Immediate,
+    /// Acquire, send and receive before this instant
+    Until(Instant),
+}
+
 /// Trait for sending SOAP requests to the RealFlight simulator
-pub(crate) trait SoapClient: Send {
-    fn send_action(&self, action: &str, body: &str) -> Result<SoapResponse, BridgeError>;
+///
+/// With the `custom-transport` feature, implement it to carry RealFlight Link
+/// traffic over a link of your own and hand the client to
+/// [RealFlightLocalBridge::with_soap_client](crate::RealFlightLocalBridge::with_soap_client).
+/// Each call of [send_action_with](Self::send_action_with) must perform
+/// exactly one request/response exchange, with `body` wrapped in a SOAP
+/// envelope for `action` as RealFlight Link expects. The bridge counts
+/// requests, errors and round-trip time itself, so a transport keeps no
+/// statistics.
+pub trait SoapClient: Send + Sync {
+    /// Sends `body` wrapped in the element for `action` and returns the response.
+    ///
+    /// `action` is the SOAP action name, e.g. `ResetAircraft`, and `body` the XML
+    /// placed inside it. A fault is returned as a response with a non-200 status,
+    /// not as an error.
+    fn send_action(&self, action: &str, body: &str) -> Result<SoapResponse, BridgeError> {
+        self.send_action_with(action, body, Wait::Blocking)
+    }
+    /// Same as [send_action](Self::send_action), blocking at most as `wait` allows.
+    fn send_action_with(
+        &self,
+        action: &str,
+        body: &str,
+        wait: Wait,
+    ) -> Result<SoapResponse, BridgeError>;
+    /// Releases background resources, reporting any failure to do so cleanly
+    fn shutdown(self: Box<Self>) -> Result<(), BridgeError> {
+        Ok(())
+    }
     #[cfg(test)]
     fn requests(&self) -> Vec<String> {
         Vec::new()
@@ -55,8 +148,12 @@ pub(crate) trait SoapClient: Send {
 }
 
 /// Async trait for sending SOAP requests to the RealFlight simulator
+///
+/// Implement it under the same contract as [SoapClient] and hand the client
+/// to [AsyncLocalBridge::with_soap_client](crate::AsyncLocalBridge::with_soap_client).
 #[cfg(feature = "rt-tokio")]
-pub(crate) trait AsyncSoapClient: Send + Sync {
+pub trait AsyncSoapClient: Send + Sync {
+    /// Async equivalent of [SoapClient::send_action].
     fn send_action(
         &self,
         action: &str,
@@ -117,5 +214,31 @@ mod tests {
                 other => panic!("expected SoapFault, got {:?}", other),
             }
         }
+
+        #[test]
+        fn summary_keeps_short_body() {
+            let response = SoapResponse {
+                status_code: 200,
+                body: "<ok/>".to_string(),
+            };
+
+            let summary = response.summary();
+            assert_eq!(summary.status_code, 200);
+            assert_eq!(summary.body, "<ok/>");
+            assert!(!summary.truncated);
+        }
+
+        #[test]
+        fn summary_truncates_on_char_boundary() {
+            // 'é' is two bytes, so the limit falls inside a character
+            let response = SoapResponse {
+                status_code: 200,
+                body: format!("a{}", "é".repeat(SUMMARY_BODY_LIMIT)),
+            };
+
+            let summary = response.summary();
+            assert_eq!(summary.body.len(), SUMMARY_BODY_LIMIT - 1);
+            assert!(summary.truncated);
+        }
     }
 }
@@ -6,34 +6,49 @@
 use std::{
     net::TcpStream,
     sync::{
-        Arc, Mutex,
+        Arc, Condvar, Mutex,
         atomic::{AtomicBool, Ordering},
     },
     thread,
     time::{Duration, Instant},
 };
 
-use crossbeam_channel::{Receiver, Sender, bounded};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TryRecvError, bounded};
 use log::{debug, error};
 
 use crate::BridgeError;
 use crate::StatisticsEngine;
 use crate::bridge::local::Configuration;
+use crate::constants::{DEFAULT_INIT_TIMEOUT, INIT_RETRY_INTERVAL, INIT_RETRY_WINDOW};
 
-const INITIALIZATION_TIMEOUT: Duration = Duration::from_secs(5);
+use super::connect::{ConnectError, Pooled, connect, is_open_blocking};
+use super::sizing::Sizer;
+
+/// Wait for a connection beyond which a request counts as starved.
+const STARVATION_THRESHOLD: Duration = Duration::from_millis(1);
 
 /// Pre-creates TCP connections in a background thread to hide connection latency.
 ///
 /// The RealFlight SoapServer requires a new connection for each request.
 /// The idea for the pool is to create the next connection
 /// in the background while the current request is being processed.
+///
+/// With [PoolSize::Adaptive](crate::PoolSize::Adaptive) the creator thread
+/// keeps a [Sizer] target of connections ready rather than filling the
+/// channel.
+///
+/// With [Configuration::probe_interval] set, the creator thread also replaces
+/// one buffered connection with a fresh one on that schedule, so a simulator
+/// that quits while the bridge is idle is noticed.
 pub(crate) struct ConnectionPool {
     config: Configuration,
-    next_socket: Receiver<TcpStream>,
+    next_socket: Receiver<Pooled<TcpStream>>,
     creator_thread: Option<thread::JoinHandle<()>>,
-    running: Arc<AtomicBool>,
-    initialized: Arc<AtomicBool>,
-    init_error: Arc<Mutex<Option<String>>>,
+    /// Dropped to stop the creator thread, waking it from any pause
+    stop: Option<Sender<()>>,
+    init: Arc<InitSignal>,
+    /// Whether the creator thread's latest connection attempt succeeded
+    sim_reachable: Arc<AtomicBool>,
     statistics: Arc<StatisticsEngine>,
 }
 
@@ -42,15 +57,16 @@ impl ConnectionPool {
         config: Configuration,
         statistics: Arc<StatisticsEngine>,
     ) -> Result<Self, BridgeError> {
-        let (sender, receiver) = bounded(config.pool_size);
+        let (_, capacity) = config.pool_size.bounds();
+        let (sender, receiver) = bounded(capacity);
 
         let mut pool = ConnectionPool {
             config,
             next_socket: receiver,
             creator_thread: None,
-            running: Arc::new(AtomicBool::new(true)),
-            initialized: Arc::new(AtomicBool::new(false)),
-            init_error: Arc::new(Mutex::new(None)),
+            stop: None,
+            init: Arc::new(InitSignal::new(InitState::Pending)),
+            sim_reachable: Arc::new(AtomicBool::new(false)),
             statistics,
         };
 
@@ -59,66 +75,74 @@ impl ConnectionPool {
         Ok(pool)
     }
 
+    /// Waits for the creator thread to open the first connections.
     pub(crate) fn ensure_pool_initialized(&self) -> Result<(), BridgeError> {
-        let now = Instant::now();
-        while !self.initialized.load(Ordering::Relaxed) {
-            // Check for initialization error
-            if let Some(err) = self
-                .init_error
-                .lock()
-                .ok()
-                .and_then(|g| g.as_ref().cloned())
-            {
-                return Err(BridgeError::Initialization(format!(
-                    "Connection pool initialization failed: {}",
-                    err
-                )));
-            }
-            if now.elapsed() > INITIALIZATION_TIMEOUT {
-                return Err(BridgeError::Initialization(format!(
-                    "Connection pool did not initialize. Waited for {:?}.",
-                    INITIALIZATION_TIMEOUT
-                )));
-            }
-            thread::sleep(Duration::from_millis(100));
-        }
-        Ok(())
+        self.init.wait_ready(Some(DEFAULT_INIT_TIMEOUT))
+    }
+
+    /// Initialization progress, shared with the creator thread.
+    pub(crate) fn init_signal(&self) -> Arc<InitSignal> {
+        Arc::clone(&self.init)
+    }
+
+    /// Reachability of the simulator, updated by the creator thread.
+    pub(crate) fn sim_reachable(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.sim_reachable)
     }
 
     // Start the background thread that creates new connections
-    fn initialize_pool(&mut self, sender: Sender<TcpStream>) -> Result<(), BridgeError> {
+    fn initialize_pool(&mut self, sender: Sender<Pooled<TcpStream>>) -> Result<(), BridgeError> {
         let config = self.config.clone();
-        let running = Arc::clone(&self.running);
-        let initialized = Arc::clone(&self.initialized);
-        let init_error = Arc::clone(&self.init_error);
+        let (stop_sender, stop) = bounded::<()>(0);
+        self.stop = Some(stop_sender);
+        let init = Arc::clone(&self.init);
+        let sim_reachable = Arc::clone(&self.sim_reachable);
         let statistics = Arc::clone(&self.statistics);
+        // Probes take buffered connections from the same end as requests
+        let buffered = self.next_socket.clone();
 
-        let worker = thread::Builder::new().name("connection-pool".to_string());
+        // Named after the host so pools for different simulators are distinguishable
+        let worker =
+            thread::Builder::new().name(format!("connection-pool-{}", config.simulator_host));
         let handle = worker.spawn(move || {
-            debug!("Creating {} connections in pool.", config.pool_size);
+            let mut sizer = Sizer::new(config.pool_size, &statistics);
+            debug!("Creating {} connections in pool.", sizer.target());
 
             let simulator_address = match config.simulator_host.parse() {
                 Ok(addr) => addr,
                 Err(e) => {
                     let msg = format!("Invalid simulator host '{}': {}", config.simulator_host, e);
                     error!("{}", msg);
-                    if let Ok(mut guard) = init_error.lock() {
-                        *guard = Some(msg);
-                    }
+                    init.set(InitState::Failed(msg));
                     return;
                 }
             };
 
-            // Create initial connections
-            for i in 0..config.pool_size {
-                match TcpStream::connect_timeout(&simulator_address, config.connect_timeout) {
+            // Create initial connections, waiting out a simulator that is
+            // still starting. A lazy pool waits for as long as it takes.
+            let started = Instant::now();
+            let mut i = 0;
+            while i < sizer.target() {
+                match connect(
+                    &simulator_address,
+                    config.local_bind,
+                    config.connect_timeout,
+                ) {
                     Ok(stream) => {
-                        if let Err(e) = sender.send(stream) {
+                        i += 1;
+                        sim_reachable.store(true, Ordering::Relaxed);
+                        if let Err(e) = sender.send(Pooled::new(stream)) {
                             let msg = format!("Failed to queue initial connection {}: {}", i, e);
                             error!("{}", msg);
-                            if let Ok(mut guard) = init_error.lock() {
-                                *guard = Some(msg);
-                            }
+                            init.set(InitState::Failed(msg));
+                            return;
+                        }
+                    }
+                    Err(ConnectError::Connect(e))
+                        if config.lazy_connect || started.elapsed() < INIT_RETRY_WINDOW =>
+                    {
+                        debug!("Simulator not reachable yet, retrying: {}", e);
+                        if !pause(&stop, INIT_RETRY_INTERVAL) {
                             return;
                         }
                     }
@@ -128,26 +152,60 @@ impl ConnectionPool {
                             config.simulator_host, e
                         );
                         error!("{}", msg);
-                        if let Ok(mut guard) = init_error.lock() {
-                            *guard = Some(msg);
-                        }
+                        init.set(InitState::Failed(msg));
                         return;
                     }
                 }
             }
 
-            initialized.store(true, Ordering::Relaxed);
+            init.set(InitState::Ready);
 
-            // Continue creating connections as needed
-            while running.load(Ordering::Relaxed) {
-                if sender.is_full() {
-                    thread::sleep(config.connect_timeout / 2);
+            // Continue creating connections as needed, probing on schedule
+            let mut next_probe = config
+                .probe_interval
+                .map(|interval| Instant::now() + interval);
+            let until_probe = |next_probe: Option<Instant>, duration: Duration| {
+                next_probe.map_or(duration, |at| {
+                    duration.min(at.saturating_duration_since(Instant::now()))
+                })
+            };
+            while !is_stopped(&stop) {
+                if let (Some(at), Some(interval)) = (next_probe, config.probe_interval) {
+                    if Instant::now() >= at {
+                        // Closes the oldest buffered connection, if any
+                        drop(buffered.try_recv());
+                        match connect(
+                            &simulator_address,
+                            config.local_bind,
+                            config.connect_timeout,
+                        ) {
+                            Ok(stream) => {
+                                sim_reachable.store(true, Ordering::Relaxed);
+                                let _ = sender.try_send(Pooled::new(stream));
+                            }
+                            Err(e) => {
+                                debug!("Simulator probe failed: {}", e);
+                                sim_reachable.store(false, Ordering::Relaxed);
+                            }
+                        }
+                        next_probe = Some(Instant::now() + interval);
+                        continue;
+                    }
+                }
+
+                if sender.len() >= sizer.adjust(&statistics) {
+                    pause(&stop, until_probe(next_probe, config.connect_timeout / 2));
                     continue;
                 }
 
-                match TcpStream::connect_timeout(&simulator_address, config.connect_timeout) {
+                match connect(
+                    &simulator_address,
+                    config.local_bind,
+                    config.connect_timeout,
+                ) {
                     Ok(stream) => {
-                        if let Err(e) = sender.send(stream) {
+                        sim_reachable.store(true, Ordering::Relaxed);
+                        if let Err(e) = sender.send(Pooled::new(stream)) {
                             error!("Error sending connection: {}", e);
                             statistics.increment_error_count();
                         }
@@ -155,7 +213,8 @@ impl ConnectionPool {
                     Err(e) => {
                         error!("Error creating connection: {}", e);
                         statistics.increment_error_count();
-                        thread::sleep(config.connect_timeout);
+                        sim_reachable.store(false, Ordering::Relaxed);
+                        pause(&stop, until_probe(next_probe, config.connect_timeout));
                     }
                 }
             }
@@ -169,29 +228,198 @@ impl ConnectionPool {
 
     // Get a new connection, consuming it
     pub fn get_connection(&self) -> Result<TcpStream, BridgeError> {
-        self.next_socket.recv().map_err(|e| {
-            BridgeError::Initialization(format!("Failed to get connection from pool: {}", e))
-        })
+        loop {
+            self.statistics
+                .record_pool_take(self.next_socket.is_empty());
+            let started = Instant::now();
+            let pooled = self.next_socket.recv();
+            self.record_wait(started);
+            let pooled = pooled.map_err(|e| {
+                BridgeError::Initialization(format!("Failed to get connection from pool: {}", e))
+            })?;
+            if let Some(stream) = self.validate(pooled) {
+                return Ok(stream);
+            }
+        }
     }
-}
 
-impl Drop for ConnectionPool {
-    fn drop(&mut self) {
+    /// Takes a connection only if one is ready, failing with [BridgeError::Timeout] otherwise.
+    pub fn try_get_connection(&self) -> Result<TcpStream, BridgeError> {
+        loop {
+            self.statistics
+                .record_pool_take(self.next_socket.is_empty());
+            let pooled = self.next_socket.try_recv().map_err(|e| match e {
+                TryRecvError::Empty => BridgeError::Timeout(Duration::ZERO),
+                TryRecvError::Disconnected => BridgeError::Initialization(format!(
+                    "Failed to get connection from pool: {}",
+                    e
+                )),
+            })?;
+            if let Some(stream) = self.validate(pooled) {
+                return Ok(stream);
+            }
+        }
+    }
+
+    /// Waits at most `timeout` for a connection, failing with [BridgeError::Timeout].
+    pub fn get_connection_timeout(&self, timeout: Duration) -> Result<TcpStream, BridgeError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.statistics
+                .record_pool_take(self.next_socket.is_empty());
+            let started = Instant::now();
+            let pooled = self.next_socket.recv_deadline(deadline);
+            self.record_wait(started);
+            let pooled = pooled.map_err(|e| match e {
+                RecvTimeoutError::Timeout => BridgeError::Timeout(timeout),
+                RecvTimeoutError::Disconnected => BridgeError::Initialization(format!(
+                    "Failed to get connection from pool: {}",
+                    e
+                )),
+            })?;
+            if let Some(stream) = self.validate(pooled) {
+                return Ok(stream);
+            }
+        }
+    }
+
+    /// Counts a wait for the creator thread beyond [STARVATION_THRESHOLD] as
+    /// starvation.
+    fn record_wait(&self, started: Instant) {
+        let waited = started.elapsed();
+        if waited > STARVATION_THRESHOLD {
+            debug!("Waited {:?} for a pooled connection", waited);
+            self.statistics.increment_pool_starvation();
+        }
+    }
+
+    /// Returns the stream if it is still usable, or closes it if it is older
+    /// than [Configuration::max_idle] or the simulator has closed it. The
+    /// creator thread refills the pool in its place.
+    fn validate(&self, pooled: Pooled<TcpStream>) -> Option<TcpStream> {
+        if pooled.is_stale(self.config.max_idle) {
+            debug!(
+                "Discarding pooled connection idle beyond {:?}",
+                self.config.max_idle
+            );
+            return None;
+        }
+        let stream = pooled.stream;
+        if !is_open_blocking(&stream) {
+            debug!("Discarding pooled connection closed by the simulator");
+            return None;
+        }
+        Some(stream)
+    }
+
+    /// Stops the creator thread and waits for it to exit.
+    pub(crate) fn shutdown(mut self) -> Result<(), BridgeError> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Result<(), BridgeError> {
         // Signal the creator thread to stop
-        self.running.store(false, Ordering::Relaxed);
+        self.stop = None;
 
         // Wait for the creator thread to finish
-        if let Some(handle) = self.creator_thread.take() {
-            let _ = handle.join();
+        match self.creator_thread.take() {
+            Some(handle) => handle.join().map_err(|_| {
+                BridgeError::Connection(std::io::Error::other("Connection pool thread panicked"))
+            }),
+            None => Ok(()),
         }
     }
 }
 
+/// Progress of a connection pool opening its first connections, reported by
+/// [try_initialized](crate::RealFlightLocalBridge::try_initialized).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InitState {
+    /// Still connecting. A pool with
+    /// [lazy_connect](crate::Configuration::lazy_connect) stays here until the
+    /// simulator accepts connections.
+    Pending,
+    /// The first connections are open and requests can be sent
+    Ready,
+    /// The pool gave up, with the reason
+    Failed(String),
+}
+
+/// [InitState] the creator thread publishes, with a condition variable to
+/// wake threads waiting for it to leave [InitState::Pending].
+pub(crate) struct InitSignal {
+    state: Mutex<InitState>,
+    changed: Condvar,
+}
+
+impl InitSignal {
+    fn new(state: InitState) -> Self {
+        InitSignal {
+            state: Mutex::new(state),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn set(&self, state: InitState) {
+        *self.state.lock().unwrap() = state;
+        self.changed.notify_all();
+    }
+
+    pub(crate) fn state(&self) -> InitState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Waits for initialization to finish, for at most `timeout` if given,
+    /// failing with [BridgeError::Initialization] unless the pool is ready.
+    pub(crate) fn wait_ready(&self, timeout: Option<Duration>) -> Result<(), BridgeError> {
+        let pending = |state: &mut InitState| *state == InitState::Pending;
+        let guard = self.state.lock().unwrap();
+        let guard = match timeout {
+            Some(timeout) => {
+                self.changed
+                    .wait_timeout_while(guard, timeout, pending)
+                    .unwrap()
+                    .0
+            }
+            None => self.changed.wait_while(guard, pending).unwrap(),
+        };
+        match &*guard {
+            InitState::Ready => Ok(()),
+            InitState::Failed(err) => Err(BridgeError::Initialization(format!(
+                "Connection pool initialization failed: {}",
+                err
+            ))),
+            InitState::Pending => Err(BridgeError::Initialization(format!(
+                "Connection pool did not initialize. Waited for {:?}.",
+                timeout.unwrap_or_default()
+            ))),
+        }
+    }
+}
+
+/// Waits for `duration` unless the pool is stopped first. Returns whether the
+/// pool is still running.
+fn pause(stop: &Receiver<()>, duration: Duration) -> bool {
+    matches!(stop.recv_timeout(duration), Err(RecvTimeoutError::Timeout))
+}
+
+/// Whether the pool owning `stop` has been stopped.
+fn is_stopped(stop: &Receiver<()>) -> bool {
+    matches!(stop.try_recv(), Err(TryRecvError::Disconnected))
+}
+
+impl Drop for ConnectionPool {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::bridge::local::Configuration;
-    use std::net::TcpListener;
+    use crate::bridge::local::{Configuration, PoolSize};
+    use socket2::{Domain, Socket, Type};
+    use std::net::{SocketAddr, TcpListener};
 
     fn get_available_port() -> u16 {
         TcpListener::bind("127.0.0.1:0")
@@ -205,7 +433,8 @@ mod tests {
         Configuration {
             simulator_host: host.to_string(),
             connect_timeout: Duration::from_millis(100),
-            pool_size: 2,
+            pool_size: PoolSize::Fixed(2),
+            ..Default::default()
         }
     }
 
@@ -268,6 +497,52 @@ mod tests {
         }
     }
 
+    mod local_bind {
+        use super::*;
+
+        #[cfg(target_os = "linux")]
+        #[test]
+        fn connects_from_configured_address() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+            let config = Configuration {
+                local_bind: Some("127.0.0.2:0".parse().unwrap()),
+                ..test_config(&listener.local_addr().unwrap().to_string())
+            };
+            let stats = Arc::new(StatisticsEngine::new());
+
+            let pool = ConnectionPool::new(config, stats).unwrap();
+            pool.ensure_pool_initialized().unwrap();
+
+            let (_, peer) = listener.accept().unwrap();
+            assert_eq!(peer.ip().to_string(), "127.0.0.2");
+        }
+
+        #[test]
+        fn bind_failure_is_initialization_error() {
+            let port = get_available_port();
+            let _listener = TcpListener::bind(format!("127.0.0.1:{}", port)).unwrap();
+
+            let config = Configuration {
+                local_bind: Some("192.0.2.1:0".parse().unwrap()),
+                ..test_config(&format!("127.0.0.1:{}", port))
+            };
+            let stats = Arc::new(StatisticsEngine::new());
+
+            let pool = ConnectionPool::new(config, stats).unwrap();
+            match pool.ensure_pool_initialized() {
+                Err(BridgeError::Initialization(msg)) => {
+                    assert!(
+                        msg.contains("Failed to bind"),
+                        "unexpected message: {}",
+                        msg
+                    );
+                }
+                other => panic!("expected Initialization error, got {:?}", other),
+            }
+        }
+    }
+
     mod ensure_pool_initialized {
         use super::*;
 
@@ -310,6 +585,25 @@ mod tests {
             assert!(pool.ensure_pool_initialized().is_ok());
             assert!(pool.ensure_pool_initialized().is_ok());
         }
+
+        #[test]
+        fn waits_for_late_simulator() {
+            let port = get_available_port();
+            let config = test_config(&format!("127.0.0.1:{}", port));
+            let stats = Arc::new(StatisticsEngine::new());
+
+            let pool = ConnectionPool::new(config, stats).unwrap();
+
+            // The simulator starts listening after the pool has begun connecting
+            let simulator = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(300));
+                let listener = TcpListener::bind(format!("127.0.0.1:{}", port)).unwrap();
+                listener.accept().unwrap()
+            });
+
+            assert!(pool.ensure_pool_initialized().is_ok());
+            simulator.join().unwrap();
+        }
     }
 
     mod get_connection {
@@ -349,7 +643,8 @@ mod tests {
             let config = Configuration {
                 simulator_host: format!("127.0.0.1:{}", port),
                 connect_timeout: Duration::from_millis(100),
-                pool_size: 2,
+                pool_size: PoolSize::Fixed(2),
+                ..Default::default()
             };
             let stats = Arc::new(StatisticsEngine::new());
 
@@ -367,6 +662,134 @@ mod tests {
             let conn2 = pool.get_connection();
             assert!(conn2.is_ok());
         }
+
+        #[test]
+        fn replaces_connection_closed_by_simulator() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let config = Configuration {
+                pool_size: PoolSize::Fixed(1),
+                ..test_config(&listener.local_addr().unwrap().to_string())
+            };
+            let stats = Arc::new(StatisticsEngine::new());
+
+            let pool = ConnectionPool::new(config, stats).unwrap();
+            pool.ensure_pool_initialized().unwrap();
+
+            // The simulator hangs up on the waiting connection
+            drop(listener.accept().unwrap());
+            thread::sleep(Duration::from_millis(20));
+
+            let stream = pool.get_connection().unwrap();
+            let (replacement, _) = listener.accept().unwrap();
+            assert_eq!(
+                stream.local_addr().unwrap(),
+                replacement.peer_addr().unwrap()
+            );
+        }
+
+        #[test]
+        fn replaces_connection_idle_beyond_max_idle() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let config = Configuration {
+                pool_size: PoolSize::Fixed(1),
+                max_idle: Duration::from_millis(50),
+                ..test_config(&listener.local_addr().unwrap().to_string())
+            };
+            let stats = Arc::new(StatisticsEngine::new());
+
+            let pool = ConnectionPool::new(config, stats).unwrap();
+            pool.ensure_pool_initialized().unwrap();
+            let (_idle, _) = listener.accept().unwrap();
+            thread::sleep(Duration::from_millis(100));
+
+            let stream = pool.get_connection().unwrap();
+            let (replacement, _) = listener.accept().unwrap();
+            assert_eq!(
+                stream.local_addr().unwrap(),
+                replacement.peer_addr().unwrap()
+            );
+        }
+
+        /// A pool without a creator thread whose channel never receives a connection
+        fn empty_pool() -> (ConnectionPool, Sender<Pooled<TcpStream>>) {
+            let (sender, receiver) = bounded(1);
+            let pool = ConnectionPool {
+                config: test_config("127.0.0.1:18083"),
+                next_socket: receiver,
+                creator_thread: None,
+                stop: None,
+                init: Arc::new(InitSignal::new(InitState::Ready)),
+                sim_reachable: Arc::new(AtomicBool::new(false)),
+                statistics: Arc::new(StatisticsEngine::new()),
+            };
+            (pool, sender)
+        }
+
+        #[test]
+        fn try_get_connection_fails_fast_when_empty() {
+            let (pool, _sender) = empty_pool();
+
+            let result = pool.try_get_connection();
+            assert!(matches!(result, Err(BridgeError::Timeout(t)) if t == Duration::ZERO));
+        }
+
+        #[test]
+        fn get_connection_timeout_expires_when_empty() {
+            let (pool, _sender) = empty_pool();
+
+            let start = Instant::now();
+            let result = pool.get_connection_timeout(Duration::from_millis(20));
+            assert!(
+                matches!(result, Err(BridgeError::Timeout(t)) if t == Duration::from_millis(20))
+            );
+            assert!(start.elapsed() >= Duration::from_millis(20));
+            assert_eq!(pool.statistics.snapshot().pool_starvation_count, 1);
+        }
+
+        #[test]
+        fn rapid_takes_count_starvation() {
+            // Room for one connection in the backlog; further connects stall
+            // until the listener accepts
+            let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+            listener
+                .bind(&"127.0.0.1:0".parse::<SocketAddr>().unwrap().into())
+                .unwrap();
+            listener.listen(0).unwrap();
+            let addr = listener.local_addr().unwrap().as_socket().unwrap();
+            let config = Configuration {
+                pool_size: PoolSize::Fixed(1),
+                ..test_config(&addr.to_string())
+            };
+            let stats = Arc::new(StatisticsEngine::new());
+
+            let pool = ConnectionPool::new(config, stats.clone()).unwrap();
+            pool.ensure_pool_initialized().unwrap();
+
+            // A slow simulator accepts the waiting connections late
+            let accepter = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                (listener.accept().unwrap(), listener.accept().unwrap())
+            });
+
+            let _first = pool.get_connection().unwrap();
+            let _second = pool.get_connection().unwrap();
+
+            assert!(stats.snapshot().pool_starvation_count >= 1);
+            drop(accepter.join().unwrap());
+        }
+
+        #[test]
+        fn get_connection_timeout_returns_ready_connection() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let config = test_config(&listener.local_addr().unwrap().to_string());
+            let stats = Arc::new(StatisticsEngine::new());
+
+            let pool = ConnectionPool::new(config, stats).unwrap();
+            pool.ensure_pool_initialized().unwrap();
+
+            let conn = pool.get_connection_timeout(Duration::from_millis(500));
+            assert!(conn.is_ok());
+        }
     }
 
     mod pool_drop {
@@ -387,6 +810,36 @@ mod tests {
             drop(pool);
         }
 
+        #[test]
+        fn drop_interrupts_retry_pause() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let config = Configuration {
+                connect_timeout: Duration::from_secs(1),
+                pool_size: PoolSize::Fixed(1),
+                ..test_config(&listener.local_addr().unwrap().to_string())
+            };
+            let stats = Arc::new(StatisticsEngine::new());
+
+            let pool = ConnectionPool::new(config, stats.clone()).unwrap();
+            pool.ensure_pool_initialized().unwrap();
+
+            // The simulator goes away, so the creator thread pauses for the
+            // connect timeout after each failed connection
+            drop(listener);
+            let _conn = pool.next_socket.recv().unwrap();
+            while stats.snapshot().error_count == 0 {
+                thread::sleep(Duration::from_millis(5));
+            }
+
+            let start = Instant::now();
+            drop(pool);
+            assert!(
+                start.elapsed() < Duration::from_millis(50),
+                "drop took {:?}",
+                start.elapsed()
+            );
+        }
+
         #[test]
         fn drop_is_safe_before_initialization() {
             let port = get_available_port();
@@ -401,6 +854,37 @@ mod tests {
         }
     }
 
+    mod adaptive_size {
+        use super::*;
+
+        #[test]
+        fn grows_under_bursty_consumption() {
+            // Unaccepted connections wait in the listen backlog
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let config = Configuration {
+                pool_size: PoolSize::Adaptive { min: 1, max: 4 },
+                ..test_config(&listener.local_addr().unwrap().to_string())
+            };
+            let stats = Arc::new(StatisticsEngine::new());
+
+            let pool = ConnectionPool::new(config, stats.clone()).unwrap();
+            pool.ensure_pool_initialized().unwrap();
+            assert_eq!(stats.snapshot().pool_target, 1);
+
+            // Each burst takes more connections than one ready one covers
+            for _ in 0..6 {
+                for _ in 0..10 {
+                    pool.get_connection().unwrap();
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+
+            let snapshot = stats.snapshot();
+            assert!(snapshot.pool_target > 1, "target {}", snapshot.pool_target);
+            assert!(snapshot.pool_miss_rate > 0.0);
+        }
+    }
+
     mod background_connection_creation {
         use super::*;
 
@@ -413,7 +897,8 @@ mod tests {
             let config = Configuration {
                 simulator_host: format!("127.0.0.1:{}", port),
                 connect_timeout: Duration::from_millis(100),
-                pool_size: 1,
+                pool_size: PoolSize::Fixed(1),
+                ..Default::default()
             };
             let stats = Arc::new(StatisticsEngine::new());
 
@@ -423,7 +908,7 @@ mod tests {
             // Accept initial connection
             thread::sleep(Duration::from_millis(50));
             let mut accepted = 0;
-            while let Ok(_) = listener.accept() {
+            while listener.accept().is_ok() {
                 accepted += 1;
             }
             assert!(accepted >= 1, "should have accepted at least 1 connection");
@@ -436,7 +921,7 @@ mod tests {
 
             // Should have created at least one more connection
             let mut more_accepted = 0;
-            while let Ok(_) = listener.accept() {
+            while listener.accept().is_ok() {
                 more_accepted += 1;
             }
             assert!(
@@ -457,7 +942,8 @@ mod tests {
             let config = Configuration {
                 simulator_host: format!("127.0.0.1:{}", port),
                 connect_timeout: Duration::from_millis(50),
-                pool_size: 1,
+                pool_size: PoolSize::Fixed(1),
+                ..Default::default()
             };
             let stats = Arc::new(StatisticsEngine::new());
 
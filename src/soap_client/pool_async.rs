@@ -1,20 +1,29 @@
 //! Async connection pool for TCP connections to the RealFlight simulator.
 //!
 //! The RealFlight SoapServer requires a new connection for each request.
-//! This pool pre-creates connections in the background to hide latency.
+//! This pool pre-creates connections in the background to hide latency, or
+//! creates them on demand when spawning a task is not wanted.
 
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use log::{debug, error};
+use socket2::SockRef;
 use tokio::net::TcpStream;
-use tokio::sync::{Mutex, mpsc, watch};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 use tokio::time::timeout;
 use tokio_util::sync::CancellationToken;
 
 use crate::BridgeError;
 use crate::StatisticsEngine;
+use crate::bridge::local::PoolSize;
+use crate::constants::{DEFAULT_MAX_IDLE, INIT_RETRY_INTERVAL, INIT_RETRY_WINDOW};
+
+use super::connect::{ConnectError, Pooled, connect_async, is_open};
+use super::handoff::{self, HandoffReceiver};
+use super::sizing::Sizer;
 
 /// Pre-creates TCP connections in a background task to hide connection latency.
 ///
@@ -24,59 +33,95 @@ use crate::StatisticsEngine;
 ///
 /// # Cancellation Safety
 ///
-/// `get_connection()` is **NOT cancel-safe**. If the future is dropped while
-/// awaiting `recv()`, a connection may be lost from the channel. This is an
-/// acceptable trade-off because:
-/// - The background task continuously creates replacement connections
-/// - Cancellation mid-receive is rare in practice (typically only on shutdown)
-/// - The pool self-heals within one connection-creation cycle
+/// `get_connection()` is cancel-safe: a dropped future never removes a
+/// connection from the channel. A connection that was taken but never written
+/// to can be handed back with `return_connection()`, and is served before any
+/// newly created one.
 ///
-/// If cancel-safety is required, wrap calls in `tokio::select!` with care or
-/// use a dedicated cancellation token rather than dropping the future.
+/// Connections older than `max_idle`, or already closed by the simulator, are
+/// discarded instead of handed out.
 pub(crate) struct AsyncConnectionPool {
-    connections: Mutex<mpsc::Receiver<TcpStream>>,
-    cancel: CancellationToken,
-    init_result: watch::Receiver<Option<Result<(), String>>>,
+    source: ConnectionSource,
+    /// Unused connections handed back by cancelled requests
+    returned: std::sync::Mutex<Vec<Pooled<TcpStream>>>,
+    max_idle: Duration,
     statistics: Arc<StatisticsEngine>,
 }
 
+/// Where the pool gets new connections from.
+enum ConnectionSource {
+    /// Created ahead of time by a spawned task
+    Background {
+        connections: HandoffReceiver<Pooled<TcpStream>>,
+        cancel: CancellationToken,
+        task: Option<JoinHandle<()>>,
+        init_result: watch::Receiver<Option<Result<(), String>>>,
+    },
+    /// Created on demand by `get_connection`
+    Inline {
+        addr: SocketAddr,
+        local_bind: Option<SocketAddr>,
+        connect_timeout: Duration,
+    },
+}
+
 impl AsyncConnectionPool {
     /// Creates a new async connection pool.
     ///
     /// # Arguments
     /// * `addr` - The address to connect to
+    /// * `local_bind` - Optional local address to bind connections to
     /// * `connect_timeout` - Timeout for establishing connections
-    /// * `pool_size` - Number of connections to pre-create
+    /// * `pool_size` - Number of connections to keep ready
     /// * `statistics` - Statistics engine for tracking errors
     pub async fn new(
         addr: SocketAddr,
+        local_bind: Option<SocketAddr>,
         connect_timeout: Duration,
-        pool_size: usize,
+        pool_size: PoolSize,
         statistics: Arc<StatisticsEngine>,
     ) -> Result<Self, BridgeError> {
         let cancel = CancellationToken::new();
-        let (tx, rx) = mpsc::channel(pool_size);
+        let (_, capacity) = pool_size.bounds();
+        let (tx, rx) = handoff::channel(capacity);
 
         // Channel for communicating initialization result
         let (init_tx, init_rx) = watch::channel(None);
         let stats_clone = Arc::clone(&statistics);
         let task_cancel = cancel.clone();
 
-        debug!("Creating {} async connections in pool.", pool_size);
-
         // Spawn background task to create connections
-        tokio::spawn(async move {
-            // Create initial connections
-            for i in 0..pool_size {
-                match timeout(connect_timeout, TcpStream::connect(addr)).await {
+        let task = tokio::spawn(async move {
+            let mut sizer = Sizer::new(pool_size, &stats_clone);
+            debug!("Creating {} async connections in pool.", sizer.target());
+
+            // Create initial connections, waiting out a simulator that is
+            // still starting
+            let started = Instant::now();
+            let mut i = 0;
+            while i < sizer.target() {
+                match timeout(connect_timeout, connect_async(addr, local_bind)).await {
                     Ok(Ok(stream)) => {
-                        if tx.send(stream).await.is_err() {
+                        i += 1;
+                        if tx.send(Pooled::new(stream)).await.is_err() {
                             let msg = format!("Failed to queue initial connection {}", i);
                             error!("{}", msg);
                             let _ = init_tx.send(Some(Err(msg)));
                             return;
                         }
                     }
+                    Ok(Err(e @ ConnectError::Bind(..))) => {
+                        let msg = e.to_string();
+                        error!("{}", msg);
+                        let _ = init_tx.send(Some(Err(msg)));
+                        return;
+                    }
+                    Ok(Err(ConnectError::Connect(e))) if started.elapsed() < INIT_RETRY_WINDOW => {
+                        debug!("Simulator not reachable yet, retrying: {}", e);
+                        if !pause(&task_cancel, INIT_RETRY_INTERVAL).await {
+                            return;
+                        }
+                    }
                     Ok(Err(e)) => {
                         let msg = format!("Failed to connect to simulator at {}: {}", addr, e);
                         error!("{}", msg);
@@ -96,27 +141,42 @@ impl AsyncConnectionPool {
 
             // Continue creating connections as needed
             loop {
+                // A fixed pool waits for room in the channel when sending; an
+                // adaptive one holds back once its current target is ready
+                let target = sizer.adjust(&stats_clone);
+                let ready = tx.len();
+                if target < capacity && ready >= target {
+                    if !pause(&task_cancel, connect_timeout / 2).await {
+                        break;
+                    }
+                    continue;
+                }
+
                 tokio::select! {
                     _ = task_cancel.cancelled() => {
                         debug!("Connection pool shutting down");
                         break;
                     }
-                    result = timeout(connect_timeout, TcpStream::connect(addr)) => {
+                    result = timeout(connect_timeout, connect_async(addr, local_bind)) => {
                         match result {
                             Ok(Ok(stream)) => {
-                                if tx.send(stream).await.is_err() {
+                                if tx.send(Pooled::new(stream)).await.is_err() {
                                     break; // Receiver dropped
                                 }
                             }
                             Ok(Err(e)) => {
                                 error!("Error creating connection: {}", e);
                                 stats_clone.increment_error_count();
-                                tokio::time::sleep(connect_timeout).await;
+                                if !pause(&task_cancel, connect_timeout).await {
+                                    break;
+                                }
                             }
                             Err(_) => {
                                 error!("Connection timeout");
                                 stats_clone.increment_error_count();
-                                tokio::time::sleep(connect_timeout).await;
+                                if !pause(&task_cancel, connect_timeout).await {
+                                    break;
+                                }
                             }
                         }
                     }
@@ -125,71 +185,201 @@ impl AsyncConnectionPool {
         });
 
         Ok(Self {
-            connections: Mutex::new(rx),
-            cancel,
-            init_result: init_rx,
+            source: ConnectionSource::Background {
+                connections: rx,
+                cancel,
+                task: Some(task),
+                init_result: init_rx,
+            },
+            returned: std::sync::Mutex::new(Vec::new()),
+            max_idle: DEFAULT_MAX_IDLE,
             statistics,
         })
     }
 
-    /// Waits for the pool to be initialized with initial connections.
+    /// Creates a pool that connects on demand instead of spawning a task.
+    ///
+    /// Each `get_connection` pays the full connection setup time.
+    pub fn inline(
+        addr: SocketAddr,
+        local_bind: Option<SocketAddr>,
+        connect_timeout: Duration,
+        statistics: Arc<StatisticsEngine>,
+    ) -> Self {
+        Self {
+            source: ConnectionSource::Inline {
+                addr,
+                local_bind,
+                connect_timeout,
+            },
+            returned: std::sync::Mutex::new(Vec::new()),
+            max_idle: DEFAULT_MAX_IDLE,
+            statistics,
+        }
+    }
+
+    /// Waits at most `init_timeout` for the pool's initial connections.
     pub async fn ensure_initialized(&self, init_timeout: Duration) -> Result<(), BridgeError> {
-        let mut rx = self.init_result.clone();
-        let start = std::time::Instant::now();
+        timeout(init_timeout, self.initialized())
+            .await
+            .unwrap_or_else(|_| {
+                Err(BridgeError::Initialization(format!(
+                    "Connection pool did not initialize. Waited for {:?}.",
+                    init_timeout
+                )))
+            })
+    }
 
-        loop {
-            // Check current value
-            if let Some(result) = rx.borrow().as_ref() {
-                return match result {
-                    Ok(()) => Ok(()),
-                    Err(msg) => Err(BridgeError::Initialization(msg.clone())),
-                };
+    /// Waits for the pool's initial connections, however long the background
+    /// task takes to open them or give up.
+    ///
+    /// An inline pool opens its first connection here, so an unreachable
+    /// simulator is reported the same way in both modes.
+    pub async fn initialized(&self) -> Result<(), BridgeError> {
+        let init_result = match &self.source {
+            ConnectionSource::Background { init_result, .. } => init_result,
+            ConnectionSource::Inline {
+                addr,
+                local_bind,
+                connect_timeout,
+            } => {
+                let stream = connect_inline(*addr, *local_bind, *connect_timeout)
+                    .await
+                    .map_err(|e| match e {
+                        BridgeError::Initialization(_) => e,
+                        e => BridgeError::Initialization(format!(
+                            "Failed to connect to simulator at {}: {}",
+                            addr, e
+                        )),
+                    })?;
+                self.return_connection(Pooled::new(stream));
+                return Ok(());
             }
+        };
+
+        let mut rx = init_result.clone();
+        let result = rx.wait_for(Option::is_some).await.map_err(|_| {
+            BridgeError::Initialization("Initialization channel closed unexpectedly".into())
+        })?;
+        match &*result {
+            Some(Err(msg)) => Err(BridgeError::Initialization(msg.clone())),
+            _ => Ok(()),
+        }
+    }
 
-            // Check timeout
-            let remaining = init_timeout.saturating_sub(start.elapsed());
-            if remaining.is_zero() {
-                return Err(BridgeError::Initialization(format!(
-                    "Connection pool did not initialize. Waited for {:?}.",
-                    init_timeout
-                )));
+    /// Sets the age after which a waiting connection is discarded.
+    pub fn set_max_idle(&mut self, max_idle: Duration) {
+        self.max_idle = max_idle;
+    }
+
+    /// Gets a connection from the pool, skipping any that are no longer usable.
+    pub async fn get_connection(&self) -> Result<Pooled<TcpStream>, BridgeError> {
+        loop {
+            let pooled = self.next_connection().await?;
+            if pooled.is_stale(self.max_idle) {
+                debug!(
+                    "Discarding pooled connection idle beyond {:?}",
+                    self.max_idle
+                );
+            } else if !is_open(SockRef::from(&pooled.stream)) {
+                debug!("Discarding pooled connection closed by the simulator");
+            } else {
+                return Ok(pooled);
             }
+        }
+    }
 
-            // Wait for change with timeout
-            match timeout(remaining, rx.changed()).await {
-                Ok(Ok(())) => continue, // Value changed, check again
-                Ok(Err(_)) => {
-                    return Err(BridgeError::Initialization(
-                        "Initialization channel closed unexpectedly".into(),
-                    ));
-                }
-                Err(_) => {
-                    return Err(BridgeError::Initialization(format!(
-                        "Connection pool did not initialize. Waited for {:?}.",
-                        init_timeout
-                    )));
-                }
+    async fn next_connection(&self) -> Result<Pooled<TcpStream>, BridgeError> {
+        if let Some(pooled) = self.returned.lock().unwrap().pop() {
+            return Ok(pooled);
+        }
+        match &self.source {
+            ConnectionSource::Background { connections, .. } => {
+                self.statistics.record_pool_take(connections.is_empty());
+                connections
+                    .recv()
+                    .await
+                    .ok_or_else(|| BridgeError::Initialization("Connection pool closed".into()))
             }
+            ConnectionSource::Inline {
+                addr,
+                local_bind,
+                connect_timeout,
+            } => connect_inline(*addr, *local_bind, *connect_timeout)
+                .await
+                .map(Pooled::new)
+                .inspect_err(|_| self.statistics.increment_error_count()),
         }
     }
 
-    /// Gets a connection from the pool.
-    pub async fn get_connection(&self) -> Result<TcpStream, BridgeError> {
-        let mut rx = self.connections.lock().await;
-        rx.recv()
-            .await
-            .ok_or_else(|| BridgeError::Initialization("Connection pool closed".into()))
+    /// Hands back a connection that was taken but never used.
+    pub fn return_connection(&self, pooled: Pooled<TcpStream>) {
+        self.returned.lock().unwrap().push(pooled);
     }
 
     /// Returns a reference to the statistics engine.
     pub fn statistics(&self) -> &Arc<StatisticsEngine> {
         &self.statistics
     }
+
+    /// Stops the background task and waits for it to exit.
+    pub async fn shutdown(mut self) -> Result<(), BridgeError> {
+        let ConnectionSource::Background {
+            connections,
+            cancel,
+            task,
+            ..
+        } = &mut self.source
+        else {
+            return Ok(());
+        };
+        cancel.cancel();
+        // Unblocks the task if it is waiting for room in the channel
+        connections.close();
+
+        match task.take() {
+            Some(task) => task.await.map_err(|e| {
+                BridgeError::Connection(std::io::Error::other(format!(
+                    "Connection pool task failed: {}",
+                    e
+                )))
+            }),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Drop for AsyncConnectionPool {
     fn drop(&mut self) {
-        self.cancel.cancel();
+        if let ConnectionSource::Background { cancel, .. } = &self.source {
+            cancel.cancel();
+        }
+    }
+}
+
+/// Waits for `duration` unless the pool is cancelled first. Returns whether the
+/// pool is still running.
+async fn pause(cancel: &CancellationToken, duration: Duration) -> bool {
+    tokio::select! {
+        _ = cancel.cancelled() => false,
+        _ = tokio::time::sleep(duration) => true,
+    }
+}
+
+/// Opens a connection for an inline pool.
+async fn connect_inline(
+    addr: SocketAddr,
+    local_bind: Option<SocketAddr>,
+    connect_timeout: Duration,
+) -> Result<TcpStream, BridgeError> {
+    match timeout(connect_timeout, connect_async(addr, local_bind)).await {
+        Ok(Ok(stream)) => Ok(stream),
+        Ok(Err(e @ ConnectError::Bind(..))) => Err(BridgeError::Initialization(e.to_string())),
+        Ok(Err(ConnectError::Connect(e))) => Err(BridgeError::Connection(e)),
+        Err(_) => Err(BridgeError::Connection(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!("Connection timeout to simulator at {}", addr),
+        ))),
     }
 }
 
@@ -207,20 +397,26 @@ mod tests {
 
         // Accept connections in background using async listener
         let accept_handle = tokio::spawn(async move {
-            // Accept enough for initial pool + some extra
-            for _ in 0..5 {
-                if tokio::time::timeout(Duration::from_secs(2), listener.accept())
-                    .await
-                    .is_err()
-                {
-                    break;
+            // Accept enough for initial pool + some extra, keeping them open
+            let mut accepted = Vec::new();
+            while accepted.len() < 5 {
+                match tokio::time::timeout(Duration::from_secs(2), listener.accept()).await {
+                    Ok(Ok((stream, _))) => accepted.push(stream),
+                    _ => break,
                 }
             }
+            accepted
         });
 
-        let pool = AsyncConnectionPool::new(addr, Duration::from_secs(1), 2, stats)
-            .await
-            .unwrap();
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_secs(1),
+            PoolSize::Fixed(2),
+            stats,
+        )
+        .await
+        .unwrap();
 
         pool.ensure_initialized(Duration::from_secs(5))
             .await
@@ -240,15 +436,49 @@ mod tests {
         // Use a port that's unlikely to be listening
         let addr: SocketAddr = "127.0.0.1:1".parse().unwrap();
 
-        let pool = AsyncConnectionPool::new(addr, Duration::from_millis(100), 1, stats)
-            .await
-            .unwrap();
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_millis(100),
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         // Should timeout waiting for initialization
         let result = pool.ensure_initialized(Duration::from_millis(500)).await;
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn initialized_waits_for_late_listener() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_secs(1),
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
+
+        // The simulator starts listening after the pool's first attempts
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let listener = TokioTcpListener::bind(addr).await.unwrap();
+
+        pool.initialized().await.unwrap();
+        assert!(pool.get_connection().await.is_ok());
+
+        drop(listener);
+    }
+
     #[tokio::test]
     async fn get_connection_returns_valid_stream() {
         let listener = TokioTcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -264,15 +494,21 @@ mod tests {
             }
         });
 
-        let pool = AsyncConnectionPool::new(addr, Duration::from_secs(1), 1, stats)
-            .await
-            .unwrap();
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_secs(1),
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         pool.ensure_initialized(Duration::from_secs(5))
             .await
             .unwrap();
 
-        let mut conn = pool.get_connection().await.unwrap();
+        let mut conn = pool.get_connection().await.unwrap().stream;
 
         // Verify we can write and read from the connection
         conn.write_all(b"hello").await.unwrap();
@@ -285,24 +521,93 @@ mod tests {
         let _ = accept_handle.await;
     }
 
+    #[tokio::test]
+    async fn replaces_connection_idle_beyond_max_idle() {
+        let listener = TokioTcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+
+        let mut pool = AsyncConnectionPool::inline(addr, None, Duration::from_secs(1), stats);
+        pool.set_max_idle(Duration::from_millis(50));
+        pool.ensure_initialized(Duration::from_secs(5))
+            .await
+            .unwrap();
+        let (_idle, _) = listener.accept().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let conn = pool.get_connection().await.unwrap();
+        let (replacement, _) = listener.accept().await.unwrap();
+        assert_eq!(
+            conn.stream.local_addr().unwrap(),
+            replacement.peer_addr().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn adaptive_pool_grows_under_bursty_consumption() {
+        // Unaccepted connections wait in the listen backlog
+        let listener = TokioTcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_millis(100),
+            PoolSize::Adaptive { min: 1, max: 4 },
+            Arc::clone(&stats),
+        )
+        .await
+        .unwrap();
+        pool.ensure_initialized(Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(stats.snapshot().pool_target, 1);
+
+        // Each burst takes more connections than one ready one covers
+        for _ in 0..6 {
+            for _ in 0..10 {
+                pool.get_connection().await.unwrap();
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        let snapshot = stats.snapshot();
+        assert!(snapshot.pool_target > 1, "target {}", snapshot.pool_target);
+        assert!(snapshot.pool_miss_rate > 0.0);
+        drop(listener);
+    }
+
     #[tokio::test]
     async fn pool_replenishes_after_use() {
         let listener = TokioTcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
         let stats = Arc::new(StatisticsEngine::new());
 
-        // Accept multiple connections concurrently
+        // Accept multiple connections concurrently, keeping them open
         let accept_handle = tokio::spawn(async move {
+            let mut accepted = Vec::new();
             for _ in 0..5 {
                 // Use timeout to avoid blocking forever
-                let _ = tokio::time::timeout(Duration::from_secs(2), listener.accept()).await;
+                if let Ok(Ok((stream, _))) =
+                    tokio::time::timeout(Duration::from_secs(2), listener.accept()).await
+                {
+                    accepted.push(stream);
+                }
             }
+            accepted
         });
 
         // Use pool_size of 2 so replenishment is more visible
-        let pool = AsyncConnectionPool::new(addr, Duration::from_millis(500), 2, stats)
-            .await
-            .unwrap();
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_millis(500),
+            PoolSize::Fixed(2),
+            stats,
+        )
+        .await
+        .unwrap();
 
         pool.ensure_initialized(Duration::from_secs(5))
             .await
@@ -334,9 +639,15 @@ mod tests {
             }
         });
 
-        let pool = AsyncConnectionPool::new(addr, Duration::from_secs(1), 1, stats)
-            .await
-            .unwrap();
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_secs(1),
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         pool.ensure_initialized(Duration::from_secs(5))
             .await
@@ -353,6 +664,42 @@ mod tests {
         accept_handle.abort();
     }
 
+    #[tokio::test]
+    async fn shutdown_interrupts_retry_pause() {
+        let listener = TokioTcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_secs(1),
+            PoolSize::Fixed(1),
+            Arc::clone(&stats),
+        )
+        .await
+        .unwrap();
+        pool.ensure_initialized(Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        // The simulator goes away, so the task pauses for the connect timeout
+        // after each failed connection
+        drop(listener);
+        let _conn = pool.next_connection().await.unwrap();
+        while stats.snapshot().error_count == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let start = std::time::Instant::now();
+        pool.shutdown().await.unwrap();
+        assert!(
+            start.elapsed() < Duration::from_millis(50),
+            "shutdown took {:?}",
+            start.elapsed()
+        );
+    }
+
     #[tokio::test]
     async fn statistics_reference_returned() {
         let listener = TokioTcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -364,9 +711,15 @@ mod tests {
             let _ = listener.accept().await;
         });
 
-        let pool = AsyncConnectionPool::new(addr, Duration::from_secs(1), 1, stats)
-            .await
-            .unwrap();
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_secs(1),
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         // Verify statistics() returns the same Arc
         assert!(Arc::ptr_eq(pool.statistics(), &stats_clone));
@@ -383,13 +736,19 @@ mod tests {
 
         // Accept initial connection then drop listener
         let accept_handle = tokio::spawn(async move {
-            let _ = listener.accept().await;
+            listener.accept().await
             // Listener dropped here - subsequent connections will fail
         });
 
-        let pool = AsyncConnectionPool::new(addr, Duration::from_millis(50), 1, stats.clone())
-            .await
-            .unwrap();
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_millis(50),
+            PoolSize::Fixed(1),
+            stats.clone(),
+        )
+        .await
+        .unwrap();
 
         pool.ensure_initialized(Duration::from_secs(5))
             .await
@@ -399,7 +758,7 @@ mod tests {
         let _conn = pool.get_connection().await.unwrap();
 
         // Wait for accept_handle to finish (listener dropped)
-        let _ = accept_handle.await;
+        let _accepted = accept_handle.await;
 
         // Wait for background task to try creating connections and fail
         tokio::time::sleep(Duration::from_millis(200)).await;
@@ -419,9 +778,15 @@ mod tests {
         let addr: SocketAddr = "10.255.255.1:18083".parse().unwrap();
         let stats = Arc::new(StatisticsEngine::new());
 
-        let pool = AsyncConnectionPool::new(addr, Duration::from_millis(100), 1, stats)
-            .await
-            .unwrap();
+        let pool = AsyncConnectionPool::new(
+            addr,
+            None,
+            Duration::from_millis(100),
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         // Should fail due to timeout during initialization
         let result = pool.ensure_initialized(Duration::from_millis(500)).await;
@@ -441,4 +806,60 @@ mod tests {
             other => panic!("expected Initialization error, got {:?}", other),
         }
     }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn connects_from_configured_local_address() {
+        let listener = TokioTcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+        let local_bind: SocketAddr = "127.0.0.2:0".parse().unwrap();
+
+        let pool = AsyncConnectionPool::new(
+            addr,
+            Some(local_bind),
+            Duration::from_secs(1),
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
+
+        let (_, peer) = tokio::time::timeout(Duration::from_secs(2), listener.accept())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(peer.ip().to_string(), "127.0.0.2");
+
+        drop(pool);
+    }
+
+    #[tokio::test]
+    async fn init_fails_when_local_bind_unavailable() {
+        let listener = TokioTcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+        let local_bind: SocketAddr = "192.0.2.1:0".parse().unwrap();
+
+        let pool = AsyncConnectionPool::new(
+            addr,
+            Some(local_bind),
+            Duration::from_secs(1),
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
+
+        match pool.ensure_initialized(Duration::from_secs(2)).await {
+            Err(BridgeError::Initialization(msg)) => {
+                assert!(
+                    msg.contains("Failed to bind"),
+                    "unexpected message: {}",
+                    msg
+                );
+            }
+            other => panic!("expected Initialization error, got {:?}", other),
+        }
+    }
 }
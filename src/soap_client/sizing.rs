@@ -0,0 +1,136 @@
+//! Target size of a connection pool, adjusted from how often requests find it
+//! empty.
+
+use crate::StatisticsEngine;
+use crate::bridge::local::PoolSize;
+
+/// Takes from the pool between two adjustments of the target.
+const WINDOW: u32 = 20;
+/// Windows in a row without a miss before the target shrinks.
+const QUIET_WINDOWS: u32 = 4;
+
+/// Number of connections a pool's creator keeps ready.
+///
+/// The target grows by one after a window in which more than one take in ten
+/// found the pool empty, and shrinks by one after [QUIET_WINDOWS] windows
+/// without a miss. A [PoolSize::Fixed] pool has equal bounds, so its target
+/// never moves.
+pub(crate) struct Sizer {
+    min: usize,
+    max: usize,
+    target: usize,
+    last_takes: u32,
+    last_misses: u32,
+    quiet: u32,
+}
+
+impl Sizer {
+    /// Starts at the smallest size and publishes it to `statistics`.
+    pub(crate) fn new(size: PoolSize, statistics: &StatisticsEngine) -> Self {
+        let (min, max) = size.bounds();
+        let (last_takes, last_misses) = statistics.pool_takes();
+        statistics.set_pool_target(min);
+        Sizer {
+            min,
+            max,
+            target: min,
+            last_takes,
+            last_misses,
+            quiet: 0,
+        }
+    }
+
+    /// Connections to keep ready.
+    pub(crate) fn target(&self) -> usize {
+        self.target
+    }
+
+    /// Re-evaluates the target once a full window of takes has passed since
+    /// the last adjustment, and returns it.
+    pub(crate) fn adjust(&mut self, statistics: &StatisticsEngine) -> usize {
+        let (takes, misses) = statistics.pool_takes();
+        let window = takes.wrapping_sub(self.last_takes);
+        if window < WINDOW {
+            return self.target;
+        }
+        let missed = misses.wrapping_sub(self.last_misses);
+        self.last_takes = takes;
+        self.last_misses = misses;
+
+        if missed * 10 > window {
+            self.target = (self.target + 1).min(self.max);
+            self.quiet = 0;
+        } else if missed == 0 {
+            self.quiet += 1;
+            if self.quiet >= QUIET_WINDOWS {
+                self.target = self.target.saturating_sub(1).max(self.min);
+                self.quiet = 0;
+            }
+        } else {
+            self.quiet = 0;
+        }
+        statistics.set_pool_target(self.target);
+        self.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn takes(statistics: &StatisticsEngine, count: u32, missed: bool) {
+        for _ in 0..count {
+            statistics.record_pool_take(missed);
+        }
+    }
+
+    #[test]
+    fn grows_while_takes_miss() {
+        let statistics = StatisticsEngine::new();
+        let mut sizer = Sizer::new(PoolSize::Adaptive { min: 1, max: 3 }, &statistics);
+        assert_eq!(statistics.snapshot().pool_target, 1);
+
+        for expected in [2, 3, 3] {
+            takes(&statistics, WINDOW, true);
+            assert_eq!(sizer.adjust(&statistics), expected);
+        }
+        assert_eq!(statistics.snapshot().pool_target, 3);
+    }
+
+    #[test]
+    fn waits_for_a_full_window() {
+        let statistics = StatisticsEngine::new();
+        let mut sizer = Sizer::new(PoolSize::Adaptive { min: 1, max: 3 }, &statistics);
+
+        takes(&statistics, WINDOW - 1, true);
+        assert_eq!(sizer.adjust(&statistics), 1);
+        takes(&statistics, 1, true);
+        assert_eq!(sizer.adjust(&statistics), 2);
+    }
+
+    #[test]
+    fn shrinks_after_quiet_windows() {
+        let statistics = StatisticsEngine::new();
+        let mut sizer = Sizer::new(PoolSize::Adaptive { min: 1, max: 3 }, &statistics);
+        takes(&statistics, WINDOW, true);
+        sizer.adjust(&statistics);
+        takes(&statistics, WINDOW, true);
+        assert_eq!(sizer.adjust(&statistics), 3);
+
+        for _ in 0..QUIET_WINDOWS - 1 {
+            takes(&statistics, WINDOW, false);
+            assert_eq!(sizer.adjust(&statistics), 3);
+        }
+        takes(&statistics, WINDOW, false);
+        assert_eq!(sizer.adjust(&statistics), 2);
+    }
+
+    #[test]
+    fn fixed_size_never_moves() {
+        let statistics = StatisticsEngine::new();
+        let mut sizer = Sizer::new(PoolSize::Fixed(2), &statistics);
+
+        takes(&statistics, WINDOW, true);
+        assert_eq!(sizer.adjust(&statistics), 2);
+    }
+}
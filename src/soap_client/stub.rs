@@ -1,45 +1,74 @@
 //! Provides and implementation of a SOAP client that returns stubbed responses.
-//! Useful for testing.
+//! Useful for testing, and public with the `test-support` feature.
 
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::BridgeError;
 use crate::StatisticsEngine;
 
-use super::{SoapClient, SoapResponse, encode_envelope};
+use super::{SoapClient, SoapResponse, Wait, encode_envelope};
 
-#[cfg(test)]
-pub(crate) struct StubSoapClient {
+/// [SoapClient] answering each request with a canned RealFlight response
+/// instead of contacting the simulator.
+///
+/// Responses are named after the [fixtures] they load, e.g.
+/// `return-data-200` or `inject-uav-controller-interface-500`, and are served
+/// in order, repeating the last one once the rest are used. Hand the client
+/// to [RealFlightLocalBridge::stub](crate::RealFlightLocalBridge::stub).
+pub struct StubSoapClient {
     responses: Vec<String>,
     pub(crate) statistics: Option<Arc<StatisticsEngine>>,
-    requests: Mutex<Vec<String>>,
+    requests: Arc<Mutex<Vec<String>>>,
+    served: Mutex<usize>,
+    delay: Duration,
 }
 
-#[cfg(test)]
 impl StubSoapClient {
+    /// Serves `responses`, which must name [fixtures] and not be empty.
     pub fn new(responses: Vec<String>) -> Self {
         StubSoapClient {
             responses,
             statistics: None,
-            requests: Mutex::new(Vec::new()),
+            requests: Arc::new(Mutex::new(Vec::new())),
+            served: Mutex::new(0),
+            delay: Duration::ZERO,
         }
     }
 
+    /// Simulates a simulator that takes `delay` to respond to each request.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Shared handle to the recorded requests, readable after the client is consumed.
+    pub fn request_log(&self) -> Arc<Mutex<Vec<String>>> {
+        Arc::clone(&self.requests)
+    }
+
     fn add_request(&self, request: &str) {
         let mut requests = self.requests.lock().unwrap();
         requests.push(request.to_string());
     }
 
+    /// Serves responses in order, repeating the last one once the rest are used.
     fn next_response(&self) -> String {
-        self.responses.first().unwrap().clone()
+        let mut served = self.served.lock().unwrap();
+        let index = (*served).min(self.responses.len() - 1);
+        *served += 1;
+        self.responses[index].clone()
     }
 }
 
-#[cfg(test)]
 impl SoapClient for StubSoapClient {
-    fn send_action(&self, action: &str, body: &str) -> Result<SoapResponse, BridgeError> {
-        eprintln!("Sending action: {}", action);
-
+    fn send_action_with(
+        &self,
+        action: &str,
+        body: &str,
+        wait: Wait,
+    ) -> Result<SoapResponse, BridgeError> {
         let envelope = encode_envelope(action, body);
 
         if let Some(statistics) = &self.statistics {
@@ -47,8 +76,17 @@ impl SoapClient for StubSoapClient {
         }
         self.add_request(&envelope);
 
+        if let Wait::Until(deadline) = wait {
+            let budget = deadline.saturating_duration_since(Instant::now());
+            if self.delay > budget {
+                thread::sleep(budget);
+                return Err(BridgeError::Timeout(budget));
+            }
+        }
+        thread::sleep(self.delay);
+
         let response_key = self.next_response();
-        let code = response_key.split('-').last().unwrap();
+        let code = response_key.split('-').next_back().unwrap();
 
         Ok(SoapResponse {
             status_code: code.parse().unwrap(),
@@ -56,29 +94,19 @@ impl SoapClient for StubSoapClient {
         })
     }
 
+    #[cfg(test)]
     fn requests(&self) -> Vec<String> {
         self.requests.lock().unwrap().clone()
     }
 }
 
-#[cfg(test)]
 fn load_response(response_key: &str) -> String {
-    use std::path::PathBuf;
-
-    let response_path: PathBuf = [
-        env!("CARGO_MANIFEST_DIR"),
-        "testdata",
-        "responses",
-        &format!("{}.xml", response_key),
-    ]
-    .iter()
-    .collect();
-    eprintln!("Response path: {:?}", response_path);
-    let body = std::fs::read_to_string(response_path).unwrap();
+    let body = fixtures::response(response_key)
+        .unwrap_or_else(|| panic!("no stub response named {}", response_key));
 
     let mut buffer = String::new();
 
-    let code = response_key.split('-').last().unwrap();
+    let code = response_key.split('-').next_back().unwrap();
 
     buffer.push_str(&format!("HTTP/1.1 {} OK\r\n", code));
     buffer.push_str("Server: gSOAP/2.7\r\n");
@@ -86,7 +114,81 @@ fn load_response(response_key: &str) -> String {
     buffer.push_str(&format!("Content-Length: {}\r\n", body.len()));
     buffer.push_str("Connection: close\r\n");
     buffer.push_str("\r\n");
-    buffer.push_str(&body);
+    buffer.push_str(body);
 
     buffer
 }
+
+/// Recorded RealFlight Link responses, embedded in the crate.
+///
+/// Each is the SOAP envelope RealFlight returns for an action, with the HTTP
+/// status the name ends in.
+pub mod fixtures {
+    /// State returned by a successful `ExchangeData`.
+    pub const RETURN_DATA_200: &str = include_str!("../../testdata/responses/return-data-200.xml");
+    /// State returned by `ExchangeData` after the aircraft has crashed.
+    pub const RETURN_DATA_CRASHED_200: &str =
+        include_str!("../../testdata/responses/return-data-crashed-200.xml");
+    /// State returned by `ExchangeData` with the aircraft resting on the ground.
+    pub const RETURN_DATA_GROUNDED_200: &str =
+        include_str!("../../testdata/responses/return-data-grounded-200.xml");
+    /// State returned by `ExchangeData` with the aircraft in flight.
+    pub const RETURN_DATA_AIRBORNE_200: &str =
+        include_str!("../../testdata/responses/return-data-airborne-200.xml");
+    /// State returned by `ExchangeData` after RealFlight dropped the RealFlight
+    /// Link controller.
+    pub const RETURN_DATA_INACTIVE_200: &str =
+        include_str!("../../testdata/responses/return-data-inactive-200.xml");
+    /// Fault returned by a failed `ExchangeData`.
+    pub const RETURN_DATA_500: &str = include_str!("../../testdata/responses/return-data-500.xml");
+    /// Reply to a successful `InjectUAVControllerInterface` (disable RC).
+    pub const INJECT_UAV_CONTROLLER_INTERFACE_200: &str =
+        include_str!("../../testdata/responses/inject-uav-controller-interface-200.xml");
+    /// Fault returned by a failed `InjectUAVControllerInterface`.
+    pub const INJECT_UAV_CONTROLLER_INTERFACE_500: &str =
+        include_str!("../../testdata/responses/inject-uav-controller-interface-500.xml");
+    /// Reply to a successful `RestoreOriginalControllerDevice` (enable RC).
+    pub const RESTORE_ORIGINAL_CONTROLLER_DEVICE_200: &str =
+        include_str!("../../testdata/responses/restore-original-controller-device-200.xml");
+    /// Fault returned by a failed `RestoreOriginalControllerDevice`.
+    pub const RESTORE_ORIGINAL_CONTROLLER_DEVICE_500: &str =
+        include_str!("../../testdata/responses/restore-original-controller-device-500.xml");
+    /// Reply to a successful `ResetAircraft`.
+    pub const RESET_AIRCRAFT_200: &str =
+        include_str!("../../testdata/responses/reset-aircraft-200.xml");
+
+    /// Every fixture with the name a [StubSoapClient](super::StubSoapClient)
+    /// knows it by.
+    pub const ALL: [(&str, &str); 11] = [
+        ("return-data-200", RETURN_DATA_200),
+        ("return-data-crashed-200", RETURN_DATA_CRASHED_200),
+        ("return-data-grounded-200", RETURN_DATA_GROUNDED_200),
+        ("return-data-airborne-200", RETURN_DATA_AIRBORNE_200),
+        ("return-data-inactive-200", RETURN_DATA_INACTIVE_200),
+        ("return-data-500", RETURN_DATA_500),
+        (
+            "inject-uav-controller-interface-200",
+            INJECT_UAV_CONTROLLER_INTERFACE_200,
+        ),
+        (
+            "inject-uav-controller-interface-500",
+            INJECT_UAV_CONTROLLER_INTERFACE_500,
+        ),
+        (
+            "restore-original-controller-device-200",
+            RESTORE_ORIGINAL_CONTROLLER_DEVICE_200,
+        ),
+        (
+            "restore-original-controller-device-500",
+            RESTORE_ORIGINAL_CONTROLLER_DEVICE_500,
+        ),
+        ("reset-aircraft-200", RESET_AIRCRAFT_200),
+    ];
+
+    /// The fixture named `name`, if there is one.
+    pub fn response(name: &str) -> Option<&'static str> {
+        ALL.iter()
+            .find(|(fixture, _)| *fixture == name)
+            .map(|(_, body)| *body)
+    }
+}
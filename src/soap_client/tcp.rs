@@ -1,25 +1,67 @@
 //! Provides and implementation of a SOAP client that uses the TCP protocol.
 
 use std::{
-    io::{BufRead, BufReader, Read, Write},
+    io::{self, BufRead, BufReader, Read, Write},
     net::TcpStream,
-    sync::Arc,
+    sync::{Arc, Mutex, atomic::AtomicBool},
+    time::{Duration, Instant},
 };
 
+use log::debug;
+
 use crate::BridgeError;
 use crate::StatisticsEngine;
-use crate::bridge::local::Configuration;
+use crate::bridge::local::{Configuration, ConnectionMode};
 
-use super::pool::ConnectionPool;
-use super::xml::{build_http_request, create_response, parse_content_length, parse_status_line};
-use super::{SoapClient, SoapResponse, encode_envelope};
+#[cfg(feature = "compression")]
+use super::compression::decode;
+use super::connect::is_open_blocking;
+use super::pool::{ConnectionPool, InitSignal};
+use super::transcript::TranscriptRecorder;
+use super::xml::{
+    ChunkedDecoder, ResponseHeaders, build_http_request, check_body_size, create_response,
+    is_interim, parse_status_line,
+};
+use super::{RequestTap, SoapClient, SoapResponse, Wait, encode_envelope, is_resendable};
+#[cfg(any(test, feature = "bench-internals"))]
+use crate::{ControlInputs, SimulatorState, decode_simulator_state, encode_control_inputs};
 
 /// Implementation of a SOAP client for RealFlight Link that uses the TCP protocol.
-pub(crate) struct TcpSoapClient {
+///
+/// [RealFlightLocalBridge](crate::RealFlightLocalBridge) is built on this client.
+/// With the `raw-soap` feature it can be used directly to call actions the
+/// bridge does not wrap.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[cfg(feature = "raw-soap")]
+/// # fn main() -> Result<(), realflight_bridge::BridgeError> {
+/// use realflight_bridge::{Configuration, SoapClient, TcpSoapClient};
+///
+/// let client = TcpSoapClient::connect(&Configuration::default())?;
+/// let response = client.send_action("ResetAircraft", "")?;
+/// println!("{}: {}", response.status_code, response.body);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "raw-soap"))]
+/// # fn main() {}
+/// ```
+pub struct TcpSoapClient {
     /// Statistics engine for tracking performance
     pub(crate) statistics: Arc<StatisticsEngine>,
     /// Connection pool for managing TCP connections
     pub(crate) connection_pool: ConnectionPool,
+    /// Read/write timeout applied to each pooled stream
+    io_timeout: Option<Duration>,
+    /// Callback observing each completed request
+    tap: Option<RequestTap>,
+    /// Connection kept open between requests with [ConnectionMode::Reuse]
+    reused: Option<Mutex<Option<TcpStream>>>,
+    /// Largest response body accepted
+    max_response_size: usize,
+    /// Writer of [Configuration::transcript]
+    transcript: Option<TranscriptRecorder>,
 }
 
 impl SoapClient for TcpSoapClient {
@@ -28,26 +70,71 @@ impl SoapClient for TcpSoapClient {
     /// # Arguments
     /// * `action` - The SOAP action to send.
     /// * `body`   - The body of the SOAP request.
-    fn send_action(&self, action: &str, body: &str) -> Result<SoapResponse, BridgeError> {
+    /// * `wait`   - How long acquiring a connection and the exchange may block.
+    fn send_action_with(
+        &self,
+        action: &str,
+        body: &str,
+        wait: Wait,
+    ) -> Result<SoapResponse, BridgeError> {
         let envelope = encode_envelope(action, body);
-        let mut stream = self.connection_pool.get_connection()?;
-        self.send_request(&mut stream, action, &envelope)?;
-        self.statistics.increment_request_count();
 
-        self.read_response(&mut BufReader::new(stream))
+        let response = match &self.reused {
+            Some(reused) => self.send_reused(reused, action, &envelope, wait)?,
+            None => self.send_pooled(action, &envelope, wait)?.0,
+        };
+
+        if let Some(tap) = &self.tap {
+            tap(action, body, &response.summary());
+        }
+        Ok(response)
+    }
+
+    fn shutdown(self: Box<Self>) -> Result<(), BridgeError> {
+        self.connection_pool.shutdown()
     }
 }
 
 impl TcpSoapClient {
+    /// Connects to the simulator described by `configuration`.
+    ///
+    /// Fails if the connection pool cannot be initialized, e.g. because
+    /// RealFlight is not running, unless [Configuration::lazy_connect] is set.
+    pub fn connect(configuration: &Configuration) -> Result<Self, BridgeError> {
+        let statistics = Arc::new(StatisticsEngine::new());
+        let client = TcpSoapClient::new(configuration.clone(), statistics)?;
+        if !configuration.lazy_connect {
+            client.ensure_pool_initialized()?;
+        }
+        Ok(client)
+    }
+
     /// Creates a new TCP SOAP client.
-    pub fn new(
+    pub(crate) fn new(
         configuration: Configuration,
         statistics: Arc<StatisticsEngine>,
     ) -> Result<Self, BridgeError> {
+        let io_timeout = configuration.io_timeout;
+        let tap = configuration.tap.clone();
+        let max_response_size = configuration.max_response_size;
+        let transcript = configuration
+            .transcript
+            .as_deref()
+            .map(|dir| TranscriptRecorder::start(dir, statistics.clone()))
+            .transpose()?;
+        let reused = match configuration.connection_mode {
+            ConnectionMode::Pooled => None,
+            ConnectionMode::Reuse => Some(Mutex::new(None)),
+        };
         let connection_pool = ConnectionPool::new(configuration, statistics.clone())?;
         Ok(TcpSoapClient {
             statistics,
             connection_pool,
+            io_timeout,
+            tap,
+            reused,
+            max_response_size,
+            transcript,
         })
     }
 
@@ -56,48 +143,709 @@ impl TcpSoapClient {
         Ok(())
     }
 
-    /// Sends a request to the simulator.
+    pub(crate) fn init_signal(&self) -> Arc<InitSignal> {
+        self.connection_pool.init_signal()
+    }
+
+    pub(crate) fn sim_reachable(&self) -> Arc<AtomicBool> {
+        self.connection_pool.sim_reachable()
+    }
+
+    /// Exchanges a request over a connection from the pool, returning the
+    /// connection with the response.
+    fn send_pooled(
+        &self,
+        action: &str,
+        envelope: &str,
+        wait: Wait,
+    ) -> Result<(SoapResponse, TcpStream), BridgeError> {
+        match wait {
+            // Waiting for a connection counts against the I/O timeout too
+            Wait::Blocking => self.exchange(action, envelope, || match self.io_timeout {
+                Some(io_timeout) => self.connection_pool.get_connection_timeout(io_timeout),
+                None => self.connection_pool.get_connection(),
+            }),
+            Wait::Immediate => self.exchange(action, envelope, || {
+                self.connection_pool.try_get_connection()
+            }),
+            Wait::Until(deadline) => {
+                let budget = deadline.saturating_duration_since(Instant::now());
+                let next = || {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    let stream = self.connection_pool.get_connection_timeout(left)?;
+                    Ok(DeadlineStream { stream, deadline })
+                };
+                self.send_and_receive(next()?, action, envelope, next)
+                    .map(|(response, stream)| (response, stream.stream))
+                    .map_err(|e| map_timeout(e, Some(budget)))
+            }
+        }
+    }
+
+    /// Experimental: exchanges two control frames over one keep-alive
+    /// connection, writing both requests before reading either response.
+    ///
+    /// RealFlight Link is not known to honor HTTP/1.1 pipelining, so this only
+    /// exists to measure it. A simulator closing the connection early is
+    /// tolerated: if it hangs up before the first response both frames are
+    /// sent again one at a time, and if it hangs up after the first only the
+    /// second is.
+    #[cfg(any(test, feature = "bench-internals"))]
+    pub fn pipelined_exchange(
+        &self,
+        controls: &[ControlInputs; 2],
+    ) -> Result<[SimulatorState; 2], BridgeError> {
+        let bodies = controls.each_ref().map(encode_control_inputs);
+        let requests = bodies.each_ref().map(|body| {
+            build_http_request("ExchangeData", &encode_envelope("ExchangeData", body), true)
+        });
+
+        let mut stream = match self.io_timeout {
+            Some(io_timeout) => self.connection_pool.get_connection_timeout(io_timeout)?,
+            None => self.connection_pool.get_connection()?,
+        };
+        stream.set_read_timeout(self.io_timeout)?;
+        stream.set_write_timeout(self.io_timeout)?;
+        if let Err(e) = stream
+            .write_all(requests.concat().as_bytes())
+            .and_then(|()| stream.flush())
+        {
+            debug!("Pipelined write failed, exchanging one at a time: {}", e);
+            return self.exchange_each(&bodies);
+        }
+
+        let mut reader = BufReader::new(&stream);
+        let [first, second] = requests;
+        let first = match self.receive_pipelined(&mut reader, first, &bodies[0]) {
+            Ok(Some(response)) => response,
+            Ok(None) => {
+                debug!("Simulator closed pipelined connection, exchanging one at a time");
+                return self.exchange_each(&bodies);
+            }
+            Err(e) => return Err(map_timeout(e, self.io_timeout)),
+        };
+        let second = match self.receive_pipelined(&mut reader, second, &bodies[1]) {
+            Ok(Some(response)) => response,
+            Ok(None) => {
+                debug!("Simulator closed pipelined connection after one response");
+                self.send_action("ExchangeData", &bodies[1])?
+            }
+            Err(e) => return Err(map_timeout(e, self.io_timeout)),
+        };
+        Ok([decode_state(first)?, decode_state(second)?])
+    }
+
+    /// Exchanges each control frame on its own request.
+    #[cfg(any(test, feature = "bench-internals"))]
+    fn exchange_each(&self, bodies: &[String; 2]) -> Result<[SimulatorState; 2], BridgeError> {
+        let first = decode_state(self.send_action("ExchangeData", &bodies[0])?)?;
+        let second = decode_state(self.send_action("ExchangeData", &bodies[1])?)?;
+        Ok([first, second])
+    }
+
+    /// Reads the response to a pipelined `request` carrying `body`, or `None`
+    /// if the simulator closed the connection without answering it.
+    #[cfg(any(test, feature = "bench-internals"))]
+    fn receive_pipelined(
+        &self,
+        reader: &mut impl BufRead,
+        request: String,
+        body: &str,
+    ) -> Result<Option<SoapResponse>, BridgeError> {
+        match reader.fill_buf() {
+            Ok([]) => return Ok(None),
+            Ok(_) => {}
+            // Closing with a request left unread resets the connection
+            Err(e) if e.kind() == io::ErrorKind::ConnectionReset => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        self.statistics.increment_request_count();
+        let response = self.receive(reader, request)?;
+        if let Some(tap) = &self.tap {
+            tap("ExchangeData", body, &response.summary());
+        }
+        Ok(Some(response))
+    }
+
+    /// Exchanges a request over the kept connection, falling back to a
+    /// connection from the pool, which is kept instead, if the simulator has
+    /// closed it.
+    ///
+    /// A kept connection found closed before the request is written is
+    /// replaced for any action. Once the request has been written, only
+    /// [resendable](is_resendable) actions are sent again; others return the
+    /// error, as the simulator may already have applied them.
+    fn send_reused(
+        &self,
+        reused: &Mutex<Option<TcpStream>>,
+        action: &str,
+        envelope: &str,
+        wait: Wait,
+    ) -> Result<SoapResponse, BridgeError> {
+        let mut reused = reused.lock().unwrap();
+        // Taken out for the exchange, so a failed one leaves nothing behind
+        if let Some(stream) = reused.take().filter(kept_is_open) {
+            let timeout = match wait {
+                Wait::Blocking | Wait::Immediate => self.io_timeout,
+                Wait::Until(deadline) => Some(
+                    deadline
+                        .saturating_duration_since(Instant::now())
+                        .max(Duration::from_micros(1)),
+                ),
+            };
+            match self.send_kept(&stream, action, envelope, timeout) {
+                Ok(response) => {
+                    *reused = Some(stream);
+                    return Ok(response);
+                }
+                Err(BridgeError::Connection(e)) if !is_timeout(&e) && is_resendable(action) => {
+                    debug!(
+                        "Kept connection closed, retrying {} on a fresh one: {}",
+                        action, e
+                    );
+                }
+                Err(e) => return Err(map_timeout(e, timeout)),
+            }
+        }
+
+        let (response, stream) = self.send_pooled(action, envelope, wait)?;
+        *reused = Some(stream);
+        Ok(response)
+    }
+
+    /// Exchanges a request over a connection used before, failing with
+    /// [io::ErrorKind::UnexpectedEof] if the simulator closed it instead of
+    /// answering.
+    fn send_kept(
+        &self,
+        mut stream: &TcpStream,
+        action: &str,
+        envelope: &str,
+        timeout: Option<Duration>,
+    ) -> Result<SoapResponse, BridgeError> {
+        stream.set_read_timeout(timeout)?;
+        stream.set_write_timeout(timeout)?;
+        let request = self.send_request(&mut stream, action, envelope)?;
+
+        let mut reader = BufReader::new(stream);
+        if reader.fill_buf()?.is_empty() {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        self.statistics.increment_request_count();
+        self.receive(&mut reader, request)
+    }
+
+    /// Exchanges a request over pooled streams from `get` using the configured
+    /// I/O timeout.
+    fn exchange(
+        &self,
+        action: &str,
+        envelope: &str,
+        get: impl Fn() -> Result<TcpStream, BridgeError>,
+    ) -> Result<(SoapResponse, TcpStream), BridgeError> {
+        let next = || {
+            let stream = get()?;
+            stream.set_read_timeout(self.io_timeout)?;
+            stream.set_write_timeout(self.io_timeout)?;
+            Ok(stream)
+        };
+        self.send_and_receive(next()?, action, envelope, next)
+            .map_err(|e| map_timeout(e, self.io_timeout))
+    }
+
+    /// Sends the request over `stream`, moving to a stream from `next` once if
+    /// the write fails, and reads the response. Returns the stream used.
+    fn send_and_receive<S: Read + Write>(
+        &self,
+        mut stream: S,
+        action: &str,
+        envelope: &str,
+        next: impl FnOnce() -> Result<S, BridgeError>,
+    ) -> Result<(SoapResponse, S), BridgeError> {
+        // The simulator may close a pooled connection after the pool checked
+        // it, in which case the request never reached it and can be resent
+        let request = match self.send_request(&mut stream, action, envelope) {
+            Ok(request) => request,
+            Err(e) => {
+                debug!("Retrying {} on a fresh connection: {}", action, e);
+                stream = next()?;
+                self.send_request(&mut stream, action, envelope)?
+            }
+        };
+        self.statistics.increment_request_count();
+        let response = self.receive(&mut BufReader::new(&mut stream), request)?;
+        Ok((response, stream))
+    }
+
+    /// Sends a request to the simulator, counting a failed write as an error,
+    /// and returns the request sent. The caller drops a stream that fails, as
+    /// the simulator has reset it.
     fn send_request(
         &self,
-        stream: &mut TcpStream,
+        stream: &mut impl Write,
         action: &str,
         envelope: &str,
-    ) -> Result<(), BridgeError> {
-        let request = build_http_request(action, envelope);
-        stream.write_all(request.as_bytes())?;
-        stream.flush()?;
-        Ok(())
+    ) -> Result<String, BridgeError> {
+        let request = build_http_request(action, envelope, self.reused.is_some());
+        stream
+            .write_all(request.as_bytes())
+            .and_then(|()| stream.flush())
+            .inspect_err(|_| self.statistics.increment_error_count())?;
+        Ok(request)
+    }
+
+    /// Reads the response to `request`, capturing the exchange in the
+    /// transcript if one is written.
+    fn receive(
+        &self,
+        stream: &mut impl BufRead,
+        request: String,
+    ) -> Result<SoapResponse, BridgeError> {
+        let mut head = self.transcript.as_ref().map(|_| String::new());
+        let response = self.read_response(stream, head.as_mut())?;
+        if let (Some(transcript), Some(head)) = (&self.transcript, head) {
+            transcript.record(request, &head, &response.body);
+        }
+        Ok(response)
     }
 
-    /// Reads the raw response from the simulator.
+    /// Reads the raw response from the simulator, failing with
+    /// [BridgeError::Protocol] if its body exceeds
+    /// [Configuration::max_response_size]. The final status line and headers
+    /// are copied to `head` if given.
     fn read_response(
         &self,
-        stream: &mut BufReader<TcpStream>,
+        stream: &mut impl BufRead,
+        mut head: Option<&mut String>,
     ) -> Result<SoapResponse, BridgeError> {
-        let mut status_line = String::new();
-        stream.read_line(&mut status_line)?;
-
-        let status_code = parse_status_line(&status_line)?;
-
-        // Read headers
-        let mut content_length: Option<usize> = None;
-        loop {
-            let mut line = String::new();
-            stream.read_line(&mut line)?;
-            if line == "\r\n" {
-                break; // End of headers
+        // Skip interim responses, such as 100 Continue, to the final one
+        let (status_code, headers) = loop {
+            let mut status_line = String::new();
+            stream.read_line(&mut status_line)?;
+            let status_code = parse_status_line(&status_line)?;
+            if let Some(head) = head.as_deref_mut() {
+                head.clear();
+                head.push_str(&status_line);
+            }
+
+            // Read headers
+            let mut headers = ResponseHeaders::default();
+            loop {
+                let mut line = String::new();
+                stream.read_line(&mut line)?;
+                if let Some(head) = head.as_deref_mut() {
+                    head.push_str(&line);
+                }
+                if line == "\r\n" {
+                    break; // End of headers
+                }
+                headers.parse_line(&line)?;
             }
-            if let Some(length) = parse_content_length(&line) {
-                content_length = Some(length);
+
+            if !is_interim(status_code) {
+                break (status_code, headers);
             }
+            debug!("Skipping interim {} response", status_code);
+        };
+
+        // A chunked body takes precedence over any Content-Length
+        if headers.chunked {
+            let mut decoder = ChunkedDecoder::new(self.max_response_size);
+            let mut body = Vec::new();
+            while !decoder.is_done() {
+                let input = stream.fill_buf()?;
+                if input.is_empty() {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                }
+                let used = decoder.decode(input, &mut body)?;
+                stream.consume(used);
+            }
+            #[cfg(feature = "compression")]
+            let body = decode(headers.encoding, body, self.max_response_size)?;
+            return Ok(create_response(status_code, body));
         }
 
         // Read the body based on Content-Length
-        let length = content_length
+        let length = headers
+            .content_length
             .ok_or_else(|| BridgeError::SoapFault("Missing Content-Length header".into()))?;
+        check_body_size(length, self.max_response_size)?;
         let mut body = vec![0; length];
         stream.read_exact(&mut body)?;
 
+        #[cfg(feature = "compression")]
+        let body = decode(headers.encoding, body, self.max_response_size)?;
         Ok(create_response(status_code, body))
     }
+
+    /// Parses a complete response as read from the simulator, without any I/O.
+    #[cfg(any(test, feature = "bench-internals"))]
+    pub fn parse_response(&self, mut response: &[u8]) -> Result<SoapResponse, BridgeError> {
+        self.read_response(&mut response, None)
+    }
+}
+
+/// Decodes the simulator state from an `ExchangeData` response.
+#[cfg(any(test, feature = "bench-internals"))]
+fn decode_state(response: SoapResponse) -> Result<SimulatorState, BridgeError> {
+    match response.status_code {
+        200 => decode_simulator_state(&response.body),
+        _ => Err(BridgeError::SoapFault(response.fault_message())),
+    }
+}
+
+/// Converts socket timeouts into [BridgeError::Timeout].
+fn map_timeout(error: BridgeError, timeout: Option<Duration>) -> BridgeError {
+    match (error, timeout) {
+        (BridgeError::Connection(e), Some(timeout)) if is_timeout(&e) => {
+            BridgeError::Timeout(timeout)
+        }
+        (error, _) => error,
+    }
+}
+
+/// Whether `error` is an expired read/write timeout, which depending on the
+/// platform surfaces as either `WouldBlock` or `TimedOut`.
+fn is_timeout(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+/// Stream whose socket timeouts shrink to the time left before a deadline.
+struct DeadlineStream {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl DeadlineStream {
+    fn remaining(&self) -> io::Result<Duration> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        Ok(remaining)
+    }
+}
+
+impl Read for DeadlineStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.set_read_timeout(Some(self.remaining()?))?;
+        self.stream.read(buf)
+    }
+}
+
+impl Write for DeadlineStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.set_write_timeout(Some(self.remaining()?))?;
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+/// Whether the kept connection can carry the next request, logging when the
+/// simulator has closed it.
+fn kept_is_open(stream: &TcpStream) -> bool {
+    let open = is_open_blocking(stream);
+    if !open {
+        debug!("Kept connection closed by the simulator, using a fresh one");
+    }
+    open
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// A connection the simulator reset before the request was written.
+    struct ResetStream;
+
+    impl Read for ResetStream {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Ok(0)
+        }
+    }
+
+    impl Write for ResetStream {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::ConnectionReset.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn receives_chunked_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Configuration {
+            simulator_host: listener.local_addr().unwrap().to_string(),
+            ..Default::default()
+        };
+
+        // A proxy re-encoding the simulator's response
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf);
+
+            let response = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                            e\r\n<TestResponse>\r\n\
+                            3\r\nOK<\r\n\
+                            e\r\n/TestResponse>\r\n\
+                            0\r\nX-Trailer: done\r\n\r\n";
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        let client = TcpSoapClient::new(config, Arc::new(StatisticsEngine::new())).unwrap();
+
+        let response = client.send_action("TestAction", "").unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body, "<TestResponse>OK</TestResponse>");
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn rejects_oversized_declared_length() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Configuration {
+            simulator_host: listener.local_addr().unwrap().to_string(),
+            ..Default::default()
+        };
+
+        // A corrupted header announcing far more than is sent
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf);
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Length: 999999999\r\n\r\n<Small/>";
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        let client = TcpSoapClient::new(config, Arc::new(StatisticsEngine::new())).unwrap();
+
+        let result = client.send_action("TestAction", "");
+        assert!(
+            matches!(result, Err(BridgeError::Protocol(ref msg)) if msg.contains("999999999")),
+            "expected Protocol error, got {:?}",
+            result
+        );
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn write_failure_returns_connection_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Configuration {
+            simulator_host: listener.local_addr().unwrap().to_string(),
+            ..Default::default()
+        };
+        let client = TcpSoapClient::new(config, Arc::new(StatisticsEngine::new())).unwrap();
+
+        // The retry's fresh connection has been reset as well
+        let result = client.send_and_receive(ResetStream, "ResetAircraft", "", || Ok(ResetStream));
+
+        match result {
+            Err(BridgeError::Connection(e)) => {
+                assert_eq!(e.kind(), io::ErrorKind::ConnectionReset)
+            }
+            other => panic!("expected Connection error, got {:?}", other.map(|(r, _)| r)),
+        }
+        let statistics = client.statistics.snapshot();
+        assert_eq!(statistics.error_count, 2);
+        assert_eq!(statistics.request_count, 0);
+    }
+
+    #[test]
+    fn parses_response_bytes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Configuration {
+            simulator_host: listener.local_addr().unwrap().to_string(),
+            ..Default::default()
+        };
+        let client = TcpSoapClient::new(config, Arc::new(StatisticsEngine::new())).unwrap();
+
+        let response = client
+            .parse_response(b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\n<Small/>")
+            .unwrap();
+
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body, "<Small/>");
+        assert_eq!(client.statistics.snapshot().request_count, 0);
+    }
+
+    #[test]
+    fn truncated_headers_return_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Configuration {
+            simulator_host: listener.local_addr().unwrap().to_string(),
+            ..Default::default()
+        };
+        let client = TcpSoapClient::new(config, Arc::new(StatisticsEngine::new())).unwrap();
+
+        let result = client.parse_response(b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\n");
+
+        assert!(matches!(result, Err(BridgeError::SoapFault(_))));
+    }
+
+    mod pipelining {
+        use super::*;
+        use crate::bridge::local::PoolSize;
+        use crate::tests::soap_stub::Server;
+
+        fn create_client(port: u16) -> TcpSoapClient {
+            let config = Configuration {
+                simulator_host: format!("127.0.0.1:{}", port),
+                connect_timeout: Duration::from_millis(1000),
+                pool_size: PoolSize::Fixed(1),
+                ..Default::default()
+            };
+            TcpSoapClient::new(config, Arc::new(StatisticsEngine::new())).unwrap()
+        }
+
+        fn get_available_port() -> u16 {
+            TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+                .port()
+        }
+
+        #[test]
+        fn reads_both_responses_from_one_connection() {
+            let port = get_available_port();
+            let server = Server::keep_alive(port, vec!["return-data-200".to_string(); 2]);
+            let client = create_client(port);
+
+            let controls = [ControlInputs::default(), ControlInputs::default()];
+            let states = client.pipelined_exchange(&controls).unwrap();
+
+            for state in &states {
+                assert_eq!(state.current_physics_speed_multiplier, 1.0);
+            }
+            assert_eq!(server.request_count(), 2);
+            assert_eq!(server.connection_count(), 1);
+            assert_eq!(client.statistics.snapshot().request_count, 2);
+
+            drop(server);
+        }
+
+        #[test]
+        fn resends_second_when_simulator_closes_early() {
+            let port = get_available_port();
+            // Answers one request per connection, leaving the second unread
+            let server = Server::new(port, vec!["return-data-200".to_string(); 2]);
+            let client = create_client(port);
+
+            let controls = [ControlInputs::default(), ControlInputs::default()];
+            let states = client.pipelined_exchange(&controls).unwrap();
+
+            for state in &states {
+                assert_eq!(state.current_physics_speed_multiplier, 1.0);
+            }
+            assert_eq!(server.request_count(), 2);
+            assert_eq!(server.connection_count(), 2);
+            assert_eq!(client.statistics.snapshot().request_count, 2);
+
+            drop(server);
+        }
+
+        #[test]
+        fn fails_on_fault_response() {
+            let port = get_available_port();
+            let server = Server::keep_alive(
+                port,
+                vec!["return-data-200".to_string(), "return-data-500".to_string()],
+            );
+            let client = create_client(port);
+
+            let controls = [ControlInputs::default(), ControlInputs::default()];
+            let result = client.pipelined_exchange(&controls);
+
+            assert!(
+                matches!(result, Err(BridgeError::SoapFault(_))),
+                "expected SoapFault, got {:?}",
+                result.map(|_| ())
+            );
+
+            drop(server);
+        }
+    }
+
+    mod reuse {
+        use super::*;
+        use crate::bridge::local::PoolSize;
+
+        const RESPONSE: &str =
+            "HTTP/1.1 200 OK\r\nContent-Length: 31\r\n\r\n<TestResponse>OK</TestResponse>";
+
+        /// Client keeping one connection open to a simulator that answers
+        /// every request except the second, whose connection it closes after
+        /// reading it. Returns the requests the simulator received.
+        fn client_with_dropped_second() -> (TcpSoapClient, Arc<Mutex<Vec<String>>>) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let config = Configuration {
+                simulator_host: listener.local_addr().unwrap().to_string(),
+                pool_size: PoolSize::Fixed(1),
+                connection_mode: ConnectionMode::Reuse,
+                ..Default::default()
+            };
+
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let received = Arc::clone(&requests);
+            std::thread::spawn(move || {
+                for stream in listener.incoming().take(4) {
+                    let mut stream = stream.unwrap();
+                    let received = Arc::clone(&received);
+                    std::thread::spawn(move || {
+                        let mut buf = [0u8; 4096];
+                        while let Ok(n @ 1..) = stream.read(&mut buf) {
+                            let mut received = received.lock().unwrap();
+                            received.push(String::from_utf8_lossy(&buf[..n]).to_string());
+                            if received.len() == 2 {
+                                return;
+                            }
+                            stream.write_all(RESPONSE.as_bytes()).unwrap();
+                        }
+                    });
+                }
+            });
+
+            let client = TcpSoapClient::new(config, Arc::new(StatisticsEngine::new())).unwrap();
+            (client, requests)
+        }
+
+        #[test]
+        fn does_not_resend_reset_after_kept_connection_fails() {
+            let (client, requests) = client_with_dropped_second();
+            client.send_action("ResetAircraft", "").unwrap();
+
+            let result = client.send_action("ResetAircraft", "");
+
+            assert!(
+                matches!(result, Err(BridgeError::Connection(_))),
+                "expected Connection error, got {:?}",
+                result
+            );
+            assert_eq!(requests.lock().unwrap().len(), 2);
+        }
+
+        #[test]
+        fn resends_exchange_after_kept_connection_fails() {
+            let (client, requests) = client_with_dropped_second();
+            client.send_action("ExchangeData", "").unwrap();
+
+            let response = client.send_action("ExchangeData", "").unwrap();
+
+            assert_eq!(response.status_code, 200);
+            assert_eq!(requests.lock().unwrap().len(), 3);
+        }
+    }
 }
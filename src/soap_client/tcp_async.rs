@@ -1,91 +1,461 @@
 //! Async implementation of a SOAP client that uses the TCP protocol.
 
-use std::net::SocketAddr;
+use std::net::{Shutdown, SocketAddr};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use log::debug;
+use socket2::SockRef;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio::time::timeout;
 
 use crate::BridgeError;
 use crate::StatisticsEngine;
+use crate::bridge::local::{ConnectionMode, PoolSize};
+use crate::constants::DEFAULT_MAX_RESPONSE_SIZE;
 
+#[cfg(feature = "compression")]
+use super::compression::decode;
+use super::connect::{Pooled, is_open};
 use super::pool_async::AsyncConnectionPool;
-use super::xml::{build_http_request, create_response, parse_content_length, parse_status_line};
-use super::{AsyncSoapClient, SoapResponse, encode_envelope};
+use super::transcript::TranscriptRecorder;
+use super::xml::{
+    ChunkedDecoder, ResponseHeaders, build_http_request, check_body_size, create_response,
+    is_interim, parse_status_line,
+};
+use super::{AsyncSoapClient, RequestTap, SoapResponse, encode_envelope, is_resendable};
 
 /// Async implementation of a SOAP client for RealFlight Link that uses the TCP protocol.
-pub(crate) struct AsyncTcpSoapClient {
+///
+/// [AsyncLocalBridge](crate::AsyncLocalBridge) is built on this client. With the
+/// `raw-soap` feature, create one with
+/// [AsyncLocalBridgeBuilder::build_soap_client](crate::AsyncLocalBridgeBuilder::build_soap_client)
+/// to call actions the bridge does not wrap.
+pub struct AsyncTcpSoapClient {
     connection_pool: AsyncConnectionPool,
+    io_timeout: Option<Duration>,
+    tap: Option<RequestTap>,
+    /// Connection kept open between requests with [ConnectionMode::Reuse]
+    reused: Option<Mutex<Option<TcpStream>>>,
+    /// Largest response body accepted
+    max_response_size: usize,
+    /// Writer of [Configuration::transcript](crate::Configuration::transcript)
+    transcript: Option<TranscriptRecorder>,
 }
 
 impl AsyncSoapClient for AsyncTcpSoapClient {
     async fn send_action(&self, action: &str, body: &str) -> Result<SoapResponse, BridgeError> {
         let envelope = encode_envelope(action, body);
-        let mut stream = self.connection_pool.get_connection().await?;
+        let _in_flight = self.statistics().track_in_flight();
 
-        // Send request
-        let request = build_http_request(action, &envelope);
-        stream.write_all(request.as_bytes()).await?;
-        stream.flush().await?;
+        let response = match &self.reused {
+            Some(reused) => self.send_reused(reused, action, &envelope).await?,
+            None => self.send_pooled(action, &envelope).await?.0,
+        };
 
-        self.connection_pool.statistics().increment_request_count();
+        if let Some(tap) = &self.tap {
+            tap(action, body, &response.summary());
+        }
+        Ok(response)
+    }
+}
+
+impl AsyncTcpSoapClient {
+    /// Creates a new async TCP SOAP client.
+    pub(crate) async fn new(
+        addr: SocketAddr,
+        local_bind: Option<SocketAddr>,
+        connect_timeout: Duration,
+        io_timeout: Option<Duration>,
+        pool_size: PoolSize,
+        statistics: Arc<StatisticsEngine>,
+    ) -> Result<Self, BridgeError> {
+        let connection_pool =
+            AsyncConnectionPool::new(addr, local_bind, connect_timeout, pool_size, statistics)
+                .await?;
+        Ok(AsyncTcpSoapClient {
+            connection_pool,
+            io_timeout,
+            tap: None,
+            reused: None,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            transcript: None,
+        })
+    }
+
+    /// Creates a client that opens each connection on demand, without a background task.
+    pub(crate) fn inline(
+        addr: SocketAddr,
+        local_bind: Option<SocketAddr>,
+        connect_timeout: Duration,
+        io_timeout: Option<Duration>,
+        statistics: Arc<StatisticsEngine>,
+    ) -> Self {
+        AsyncTcpSoapClient {
+            connection_pool: AsyncConnectionPool::inline(
+                addr,
+                local_bind,
+                connect_timeout,
+                statistics,
+            ),
+            io_timeout,
+            tap: None,
+            reused: None,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            transcript: None,
+        }
+    }
+
+    /// Sets the callback observing each completed request.
+    pub(crate) fn with_tap(mut self, tap: Option<RequestTap>) -> Self {
+        self.tap = tap;
+        self
+    }
+
+    /// Sets the writer capturing each exchange.
+    pub(crate) fn with_transcript(mut self, transcript: Option<TranscriptRecorder>) -> Self {
+        self.transcript = transcript;
+        self
+    }
+
+    /// Sets the age after which a pooled connection is replaced instead of used.
+    pub(crate) fn with_max_idle(mut self, max_idle: Duration) -> Self {
+        self.connection_pool.set_max_idle(max_idle);
+        self
+    }
+
+    /// Sets the largest response body accepted.
+    pub(crate) fn with_max_response_size(mut self, max_response_size: usize) -> Self {
+        self.max_response_size = max_response_size;
+        self
+    }
+
+    /// Sets whether one connection is kept open across requests.
+    pub(crate) fn with_connection_mode(mut self, mode: ConnectionMode) -> Self {
+        self.reused = match mode {
+            ConnectionMode::Pooled => None,
+            ConnectionMode::Reuse => Some(Mutex::new(None)),
+        };
+        self
+    }
+
+    /// Ensures the connection pool is initialized.
+    pub(crate) async fn ensure_pool_initialized(
+        &self,
+        init_timeout: Duration,
+    ) -> Result<(), BridgeError> {
+        self.connection_pool.ensure_initialized(init_timeout).await
+    }
+
+    /// Waits for the connection pool, however long it takes to initialize.
+    pub(crate) async fn pool_initialized(&self) -> Result<(), BridgeError> {
+        self.connection_pool.initialized().await
+    }
+
+    /// Stops the connection pool's background task.
+    pub async fn shutdown(self) -> Result<(), BridgeError> {
+        self.connection_pool.shutdown().await
+    }
+
+    /// Returns a reference to the statistics engine.
+    pub(crate) fn statistics(&self) -> &Arc<StatisticsEngine> {
+        self.connection_pool.statistics()
+    }
+
+    /// Exchanges a request over a connection from the pool, returning the
+    /// connection with the response.
+    async fn send_pooled(
+        &self,
+        action: &str,
+        envelope: &str,
+    ) -> Result<(SoapResponse, TcpStream), BridgeError> {
+        let statistics = self.statistics();
+
+        let wait_start = Instant::now();
+        let stream = self.connection_pool.get_connection().await?;
+        statistics.add_pool_wait(wait_start.elapsed());
+        let guard = RequestGuard {
+            stream: Some(stream),
+            pool: &self.connection_pool,
+            sent: false,
+        };
+
+        let io_start = Instant::now();
+        let result = self
+            .with_io_timeout(self.exchange(guard, action, envelope))
+            .await;
+        statistics.add_io_time(io_start.elapsed());
+        result
+    }
+
+    /// Exchanges a request over the kept connection, falling back to a
+    /// connection from the pool, which is kept instead, if the simulator has
+    /// closed it.
+    ///
+    /// A kept connection found closed before the request is written is
+    /// replaced for any action. Once the request has been written, only
+    /// [resendable](is_resendable) actions are sent again; others return the
+    /// error, as the simulator may already have applied them.
+    async fn send_reused(
+        &self,
+        reused: &Mutex<Option<TcpStream>>,
+        action: &str,
+        envelope: &str,
+    ) -> Result<SoapResponse, BridgeError> {
+        let mut reused = reused.lock().await;
+        // Taken out for the exchange, so a failed or cancelled one leaves
+        // nothing behind
+        if let Some(mut stream) = reused.take().filter(kept_is_open) {
+            let io_start = Instant::now();
+            let result = self
+                .with_io_timeout(self.send_kept(&mut stream, action, envelope))
+                .await;
+            self.statistics().add_io_time(io_start.elapsed());
+            match result {
+                Ok(response) => {
+                    *reused = Some(stream);
+                    return Ok(response);
+                }
+                Err(BridgeError::Connection(e)) if is_resendable(action) => {
+                    debug!(
+                        "Kept connection closed, retrying {} on a fresh one: {}",
+                        action, e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let (response, stream) = self.send_pooled(action, envelope).await?;
+        *reused = Some(stream);
+        Ok(response)
+    }
+
+    /// Exchanges a request over a connection used before, failing with
+    /// [std::io::ErrorKind::UnexpectedEof] if the simulator closed it instead
+    /// of answering.
+    async fn send_kept(
+        &self,
+        stream: &mut TcpStream,
+        action: &str,
+        envelope: &str,
+    ) -> Result<SoapResponse, BridgeError> {
+        let request = build_http_request(action, envelope, true);
+        write_request(stream, &request).await?;
 
-        // Read response
         let mut reader = BufReader::new(stream);
+        if reader.fill_buf().await?.is_empty() {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        self.statistics().increment_request_count();
+        self.receive(&mut reader, request).await
+    }
+
+    /// Bounds `exchange` by the configured I/O timeout.
+    async fn with_io_timeout<T>(
+        &self,
+        exchange: impl Future<Output = Result<T, BridgeError>>,
+    ) -> Result<T, BridgeError> {
+        match self.io_timeout {
+            Some(io_timeout) => match timeout(io_timeout, exchange).await {
+                Ok(result) => result,
+                Err(_) => Err(BridgeError::Timeout(io_timeout)),
+            },
+            None => exchange.await,
+        }
+    }
+
+    /// Writes the request to the stream and reads the simulator's response,
+    /// returning the stream used.
+    async fn exchange(
+        &self,
+        mut guard: RequestGuard<'_>,
+        action: &str,
+        envelope: &str,
+    ) -> Result<(SoapResponse, TcpStream), BridgeError> {
+        let result = self.write_and_read(&mut guard, action, envelope).await;
+        let stream = guard.complete();
+        result.map(|response| (response, stream))
+    }
 
-        // Read status line
+    async fn write_and_read(
+        &self,
+        guard: &mut RequestGuard<'_>,
+        action: &str,
+        envelope: &str,
+    ) -> Result<SoapResponse, BridgeError> {
+        // Send request. The simulator may close a pooled connection after the
+        // pool checked it, in which case the request never reached it and can
+        // be resent on a fresh connection.
+        let request = build_http_request(action, envelope, self.reused.is_some());
+        if let Err(e) = write_request(guard.begin(), &request).await {
+            debug!("Retrying {} on a fresh connection: {}", action, e);
+            guard.replace(self.connection_pool.get_connection().await?);
+            write_request(guard.begin(), &request).await?;
+        }
+
+        self.connection_pool.statistics().increment_request_count();
+
+        self.receive(&mut BufReader::new(guard.begin()), request)
+            .await
+    }
+
+    /// Reads the response to `request`, capturing the exchange in the
+    /// transcript if one is written.
+    async fn receive(
+        &self,
+        reader: &mut (impl AsyncBufRead + Unpin),
+        request: String,
+    ) -> Result<SoapResponse, BridgeError> {
+        let mut head = self.transcript.as_ref().map(|_| String::new());
+        let response = read_response(reader, self.max_response_size, head.as_mut()).await?;
+        if let (Some(transcript), Some(head)) = (&self.transcript, head) {
+            transcript.record(request, &head, &response.body);
+        }
+        Ok(response)
+    }
+}
+
+async fn write_request(stream: &mut TcpStream, request: &str) -> std::io::Result<()> {
+    stream.write_all(request.as_bytes()).await?;
+    stream.flush().await
+}
+
+/// Reads the simulator's response, whose body may be at most
+/// `max_response_size` bytes. The final status line and headers are copied to
+/// `head` if given.
+async fn read_response(
+    reader: &mut (impl AsyncBufRead + Unpin),
+    max_response_size: usize,
+    mut head: Option<&mut String>,
+) -> Result<SoapResponse, BridgeError> {
+    // Skip interim responses, such as 100 Continue, to the final one
+    let (status_code, headers) = loop {
         let mut status_line = String::new();
         reader.read_line(&mut status_line).await?;
         let status_code = parse_status_line(&status_line)?;
+        if let Some(head) = head.as_deref_mut() {
+            head.clear();
+            head.push_str(&status_line);
+        }
 
         // Read headers
-        let mut content_length: Option<usize> = None;
+        let mut headers = ResponseHeaders::default();
         loop {
             let mut line = String::new();
             reader.read_line(&mut line).await?;
+            if let Some(head) = head.as_deref_mut() {
+                head.push_str(&line);
+            }
             if line == "\r\n" {
                 break; // End of headers
             }
-            if let Some(length) = parse_content_length(&line) {
-                content_length = Some(length);
-            }
+            headers.parse_line(&line)?;
         }
 
-        // Read the body based on Content-Length
-        let length = content_length
-            .ok_or_else(|| BridgeError::SoapFault("Missing Content-Length header".into()))?;
-        let mut body = vec![0; length];
-        reader.read_exact(&mut body).await?;
+        if !is_interim(status_code) {
+            break (status_code, headers);
+        }
+        debug!("Skipping interim {} response", status_code);
+    };
 
-        Ok(create_response(status_code, body))
+    // A chunked body takes precedence over any Content-Length
+    if headers.chunked {
+        let mut decoder = ChunkedDecoder::new(max_response_size);
+        let mut body = Vec::new();
+        while !decoder.is_done() {
+            let input = reader.fill_buf().await?;
+            if input.is_empty() {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            let used = decoder.decode(input, &mut body)?;
+            reader.consume(used);
+        }
+        #[cfg(feature = "compression")]
+        let body = decode(headers.encoding, body, max_response_size)?;
+        return Ok(create_response(status_code, body));
     }
+
+    // Read the body based on Content-Length
+    let length = headers
+        .content_length
+        .ok_or_else(|| BridgeError::SoapFault("Missing Content-Length header".into()))?;
+    check_body_size(length, max_response_size)?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+
+    #[cfg(feature = "compression")]
+    let body = decode(headers.encoding, body, max_response_size)?;
+    Ok(create_response(status_code, body))
 }
 
-impl AsyncTcpSoapClient {
-    /// Creates a new async TCP SOAP client.
-    pub async fn new(
-        addr: SocketAddr,
-        connect_timeout: Duration,
-        pool_size: usize,
-        statistics: Arc<StatisticsEngine>,
-    ) -> Result<Self, BridgeError> {
-        let connection_pool =
-            AsyncConnectionPool::new(addr, connect_timeout, pool_size, statistics).await?;
-        Ok(AsyncTcpSoapClient { connection_pool })
+/// Keeps the pool consistent when a request future is dropped.
+///
+/// A connection that was never written to goes back to the pool. Once the
+/// request is sent, the response is unusable to anyone else, so a cancelled
+/// request closes the connection and counts as cancelled.
+struct RequestGuard<'a> {
+    stream: Option<Pooled<TcpStream>>,
+    pool: &'a AsyncConnectionPool,
+    sent: bool,
+}
+
+impl RequestGuard<'_> {
+    /// Marks the connection as used and returns it for the request.
+    fn begin(&mut self) -> &mut TcpStream {
+        self.sent = true;
+        &mut self
+            .stream
+            .as_mut()
+            .expect("request already completed")
+            .stream
     }
 
-    /// Ensures the connection pool is initialized.
-    pub async fn ensure_pool_initialized(&self, init_timeout: Duration) -> Result<(), BridgeError> {
-        self.connection_pool.ensure_initialized(init_timeout).await
+    /// Swaps in a fresh connection after the current one failed, closing it.
+    fn replace(&mut self, pooled: Pooled<TcpStream>) {
+        self.stream = Some(pooled);
+        self.sent = false;
     }
 
-    /// Returns a reference to the statistics engine.
-    #[allow(dead_code)]
-    pub fn statistics(&self) -> &Arc<StatisticsEngine> {
-        self.connection_pool.statistics()
+    /// Releases the connection after the request finished, successfully or not.
+    fn complete(mut self) -> TcpStream {
+        self.stream
+            .take()
+            .expect("request already completed")
+            .stream
     }
 }
 
+impl Drop for RequestGuard<'_> {
+    fn drop(&mut self) {
+        let Some(pooled) = self.stream.take() else {
+            return;
+        };
+        if !self.sent {
+            self.pool.return_connection(pooled);
+            return;
+        }
+
+        debug!("Closing connection of cancelled request");
+        self.pool.statistics().increment_cancelled_count();
+        if let Ok(stream) = pooled.stream.into_std() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// Whether the kept connection can carry the next request, logging when the
+/// simulator has closed it.
+fn kept_is_open(stream: &TcpStream) -> bool {
+    let open = is_open(SockRef::from(stream));
+    if !open {
+        debug!("Kept connection closed by the simulator, using a fresh one");
+    }
+    open
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,9 +494,16 @@ mod tests {
             stream.flush().unwrap();
         });
 
-        let client = AsyncTcpSoapClient::new(addr, Duration::from_secs(5), 1, stats)
-            .await
-            .unwrap();
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         client
             .ensure_pool_initialized(Duration::from_secs(5))
@@ -140,6 +517,84 @@ mod tests {
         server_handle.join().unwrap();
     }
 
+    #[tokio::test]
+    async fn receives_chunked_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+
+        // A proxy re-encoding the simulator's response
+        let server_handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = std::io::Read::read(&mut stream, &mut buf);
+
+            let response = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                            e\r\n<TestResponse>\r\n\
+                            3\r\nOK<\r\n\
+                            e\r\n/TestResponse>\r\n\
+                            0\r\nX-Trailer: done\r\n\r\n";
+            stream.write_all(response.as_bytes()).unwrap();
+            stream.flush().unwrap();
+        });
+
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
+
+        let response = client.send_action("TestAction", "").await.unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body, "<TestResponse>OK</TestResponse>");
+
+        server_handle.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_declared_length() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+
+        // A corrupted header announcing far more than is sent
+        let server_handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = std::io::Read::read(&mut stream, &mut buf);
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Length: 999999999\r\n\r\n<Small/>";
+            stream.write_all(response.as_bytes()).unwrap();
+            stream.flush().unwrap();
+        });
+
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap()
+        .with_max_response_size(4096);
+
+        let result = client.send_action("TestAction", "").await;
+        assert!(
+            matches!(result, Err(BridgeError::Protocol(ref msg)) if msg.contains("4096")),
+            "expected Protocol error, got {:?}",
+            result
+        );
+
+        server_handle.join().unwrap();
+    }
+
     #[tokio::test]
     async fn parses_200_response_correctly() {
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
@@ -156,9 +611,16 @@ mod tests {
             stream.flush().unwrap();
         });
 
-        let client = AsyncTcpSoapClient::new(addr, Duration::from_secs(5), 1, stats)
-            .await
-            .unwrap();
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         client
             .ensure_pool_initialized(Duration::from_secs(5))
@@ -187,9 +649,16 @@ mod tests {
             stream.flush().unwrap();
         });
 
-        let client = AsyncTcpSoapClient::new(addr, Duration::from_secs(5), 1, stats)
-            .await
-            .unwrap();
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         client
             .ensure_pool_initialized(Duration::from_secs(5))
@@ -220,9 +689,16 @@ mod tests {
             stream.flush().unwrap();
         });
 
-        let client = AsyncTcpSoapClient::new(addr, Duration::from_secs(5), 1, stats)
-            .await
-            .unwrap();
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         client
             .ensure_pool_initialized(Duration::from_secs(5))
@@ -240,6 +716,15 @@ mod tests {
         server_handle.join().unwrap();
     }
 
+    #[tokio::test]
+    async fn truncated_headers_return_error() {
+        let mut response: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\n";
+
+        let result = read_response(&mut response, 1024, None).await;
+
+        assert!(matches!(result, Err(BridgeError::SoapFault(_))));
+    }
+
     #[tokio::test]
     async fn increments_request_count_on_success() {
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
@@ -257,9 +742,16 @@ mod tests {
             stream.flush().unwrap();
         });
 
-        let client = AsyncTcpSoapClient::new(addr, Duration::from_secs(5), 1, stats)
-            .await
-            .unwrap();
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         client
             .ensure_pool_initialized(Duration::from_secs(5))
@@ -296,13 +788,318 @@ mod tests {
             }
         });
 
-        let client = AsyncTcpSoapClient::new(addr, Duration::from_secs(5), 1, stats)
-            .await
-            .unwrap();
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
 
         assert!(Arc::ptr_eq(client.statistics(), &stats_clone));
 
         drop(client);
         let _ = accept_handle.join();
     }
+
+    #[tokio::test]
+    async fn stalled_response_returns_timeout() {
+        // Accepts connections (via the listen backlog) but never replies
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            Some(Duration::from_millis(100)),
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
+
+        client
+            .ensure_pool_initialized(Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        let result = client.send_action("TestAction", "").await;
+        let elapsed = start.elapsed();
+
+        match result {
+            Err(BridgeError::Timeout(timeout)) => {
+                assert_eq!(timeout, Duration::from_millis(100));
+            }
+            other => panic!("expected Timeout, got {:?}", other),
+        }
+        assert!(elapsed < Duration::from_secs(1), "took {:?}", elapsed);
+
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn cancelled_exchange_does_not_starve_pool() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+
+        // Never answers the first request, answers the second
+        let server_handle = std::thread::spawn(move || {
+            let (mut stalled, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = std::io::Read::read(&mut stalled, &mut buf);
+
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = std::io::Read::read(&mut stream, &mut buf);
+            let response = create_mock_response("<TestResponse>OK</TestResponse>");
+            stream.write_all(response.as_bytes()).unwrap();
+            stream.flush().unwrap();
+        });
+
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            Arc::clone(&stats),
+        )
+        .await
+        .unwrap();
+        client
+            .ensure_pool_initialized(Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        tokio::select! {
+            _ = client.send_action("TestAction", "") => panic!("stalled request completed"),
+            _ = tokio::time::sleep(Duration::from_millis(50)) => {}
+        }
+        assert_eq!(stats.snapshot().cancelled_count, 1);
+
+        let response =
+            tokio::time::timeout(Duration::from_secs(2), client.send_action("TestAction", ""))
+                .await
+                .expect("pool starved after cancellation")
+                .unwrap();
+        assert_eq!(response.status_code, 200);
+
+        server_handle.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn replaces_connection_closed_by_simulator() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
+        client
+            .ensure_pool_initialized(Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        // The simulator hangs up on the waiting connection
+        drop(listener.accept().unwrap());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let server_handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = std::io::Read::read(&mut stream, &mut buf);
+            let response = create_mock_response("<TestResponse>OK</TestResponse>");
+            stream.write_all(response.as_bytes()).unwrap();
+            stream.flush().unwrap();
+        });
+
+        let response = client.send_action("TestAction", "").await.unwrap();
+        assert_eq!(response.status_code, 200);
+
+        server_handle.join().unwrap();
+    }
+
+    async fn reusing_client(addr: SocketAddr) -> AsyncTcpSoapClient {
+        let stats = Arc::new(StatisticsEngine::new());
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap()
+        .with_connection_mode(ConnectionMode::Reuse);
+        client
+            .ensure_pool_initialized(Duration::from_secs(5))
+            .await
+            .unwrap();
+        client
+    }
+
+    /// Answers `requests` requests on one connection, returning each request.
+    fn serve_on(stream: &mut std::net::TcpStream, requests: usize) -> Vec<String> {
+        (0..requests)
+            .map(|_| {
+                let mut buf = [0u8; 1024];
+                let n = std::io::Read::read(stream, &mut buf).unwrap();
+                let response = create_mock_response("<TestResponse>OK</TestResponse>");
+                stream.write_all(response.as_bytes()).unwrap();
+                String::from_utf8_lossy(&buf[..n]).to_string()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn reuse_sends_requests_on_one_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = reusing_client(listener.local_addr().unwrap()).await;
+
+        let server_handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            serve_on(&mut stream, 3)
+        });
+
+        for _ in 0..3 {
+            let response = client.send_action("TestAction", "").await.unwrap();
+            assert_eq!(response.status_code, 200);
+        }
+
+        let requests = server_handle.join().unwrap();
+        assert!(
+            requests
+                .iter()
+                .all(|request| request.contains("Connection: keep-alive\r\n"))
+        );
+        assert_eq!(client.statistics().snapshot().request_count, 3);
+    }
+
+    #[tokio::test]
+    async fn reuse_falls_back_when_simulator_closes_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = reusing_client(listener.local_addr().unwrap()).await;
+
+        // Answers one request on each connection, then hangs up
+        let server_handle = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                serve_on(&mut stream, 1);
+            }
+        });
+
+        for _ in 0..2 {
+            let response = client.send_action("TestAction", "").await.unwrap();
+            assert_eq!(response.status_code, 200);
+            // Lets the hang-up arrive, so the kept connection is seen closed
+            // before the next request is written
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        server_handle.join().unwrap();
+        assert_eq!(client.statistics().snapshot().request_count, 2);
+    }
+
+    /// Answers one request on `listener`'s first connection, then reads the
+    /// next request on it and hangs up without answering. Returns the
+    /// requests read.
+    fn drop_second_request(listener: &TcpListener) -> Vec<String> {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut requests = serve_on(&mut stream, 1);
+        let mut buf = [0u8; 1024];
+        let n = std::io::Read::read(&mut stream, &mut buf).unwrap();
+        requests.push(String::from_utf8_lossy(&buf[..n]).to_string());
+        requests
+    }
+
+    #[tokio::test]
+    async fn reuse_does_not_resend_reset_after_kept_connection_fails() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = reusing_client(listener.local_addr().unwrap()).await;
+
+        let server_handle = std::thread::spawn(move || drop_second_request(&listener));
+
+        client.send_action("ResetAircraft", "").await.unwrap();
+        let result = timeout(
+            Duration::from_secs(5),
+            client.send_action("ResetAircraft", ""),
+        )
+        .await
+        .expect("the reset was resent");
+
+        assert!(
+            matches!(result, Err(BridgeError::Connection(_))),
+            "expected Connection error, got {:?}",
+            result
+        );
+        assert_eq!(server_handle.join().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn reuse_resends_exchange_after_kept_connection_fails() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = reusing_client(listener.local_addr().unwrap()).await;
+
+        let server_handle = std::thread::spawn(move || {
+            let mut requests = drop_second_request(&listener);
+            let (mut stream, _) = listener.accept().unwrap();
+            requests.extend(serve_on(&mut stream, 1));
+            requests
+        });
+
+        client.send_action("ExchangeData", "").await.unwrap();
+        let response = client.send_action("ExchangeData", "").await.unwrap();
+
+        assert_eq!(response.status_code, 200);
+        assert_eq!(server_handle.join().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn unsent_connection_returns_to_pool() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stats = Arc::new(StatisticsEngine::new());
+        let client = AsyncTcpSoapClient::new(
+            addr,
+            None,
+            Duration::from_secs(5),
+            None,
+            PoolSize::Fixed(1),
+            stats,
+        )
+        .await
+        .unwrap();
+        client
+            .ensure_pool_initialized(Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let pooled = client.connection_pool.get_connection().await.unwrap();
+        let local_addr = pooled.stream.local_addr().unwrap();
+        drop(RequestGuard {
+            stream: Some(pooled),
+            pool: &client.connection_pool,
+            sent: false,
+        });
+
+        let pooled = client.connection_pool.get_connection().await.unwrap();
+        assert_eq!(pooled.stream.local_addr().unwrap(), local_addr);
+        assert_eq!(client.statistics().snapshot().cancelled_count, 0);
+
+        drop(listener);
+    }
 }
@@ -0,0 +1,385 @@
+//! Capture of raw simulator traffic to disk, for protocol debugging.
+//!
+//! A [TranscriptRecorder] hands each request/response pair to a background
+//! thread over a bounded channel, so a request only pays for copying the
+//! traffic. The thread writes one file per exchange, named by sequence number:
+//!
+//! ```text
+//! sequence: 1
+//! timestamp: 1760000000.123456
+//! request: 312 bytes
+//! response: 3590 bytes
+//!
+//! POST / HTTP/1.1
+//! ...
+//!
+//! HTTP/1.1 200 OK
+//! ...
+//! ```
+//!
+//! [Transcript::open] reads a directory of these files back.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crossbeam_channel::{Sender, bounded};
+use log::warn;
+
+use crate::constants::TRANSCRIPT_CAPACITY;
+use crate::{BridgeError, StatisticsEngine};
+
+/// One exchange waiting to be written.
+struct Exchange {
+    sequence: u64,
+    timestamp: SystemTime,
+    request: String,
+    response: String,
+}
+
+/// Queues exchanges for the thread writing them to a transcript directory.
+///
+/// Dropping the recorder waits for the queued exchanges to be written.
+pub(crate) struct TranscriptRecorder {
+    /// Dropped before joining the writer, which then drains the queue and exits
+    tx: Option<Sender<Exchange>>,
+    writer: Option<JoinHandle<()>>,
+    sequence: AtomicU64,
+    statistics: Arc<StatisticsEngine>,
+}
+
+impl TranscriptRecorder {
+    /// Creates `dir` if needed and starts the writer thread.
+    pub(crate) fn start(
+        dir: &Path,
+        statistics: Arc<StatisticsEngine>,
+    ) -> Result<Self, BridgeError> {
+        let initialization = |e: io::Error| {
+            BridgeError::Initialization(format!(
+                "Failed to start transcript in {}: {}",
+                dir.display(),
+                e
+            ))
+        };
+        fs::create_dir_all(dir).map_err(initialization)?;
+
+        let (tx, rx) = bounded::<Exchange>(TRANSCRIPT_CAPACITY);
+        let dir = dir.to_path_buf();
+        let writer = thread::Builder::new()
+            .name(format!("transcript-{}", dir.display()))
+            .spawn(move || {
+                for exchange in rx {
+                    if let Err(e) = write_exchange(&dir, &exchange) {
+                        warn!("Failed to write transcript {}: {}", exchange.sequence, e);
+                    }
+                }
+            })
+            .map_err(initialization)?;
+
+        Ok(TranscriptRecorder {
+            tx: Some(tx),
+            writer: Some(writer),
+            sequence: AtomicU64::new(0),
+            statistics,
+        })
+    }
+
+    /// Queues a request and the head and body of its response, dropping the
+    /// pair and counting it in `Statistics::transcript_dropped_count` if the
+    /// writer has fallen behind.
+    pub(crate) fn record(&self, request: String, response_head: &str, response_body: &str) {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let exchange = Exchange {
+            sequence,
+            timestamp: SystemTime::now(),
+            request,
+            response: [response_head, response_body].concat(),
+        };
+        let tx = self.tx.as_ref().expect("sender is only taken on drop");
+        if tx.try_send(exchange).is_err() {
+            self.statistics.increment_transcript_dropped();
+        }
+    }
+}
+
+impl Drop for TranscriptRecorder {
+    fn drop(&mut self) {
+        self.tx = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+fn file_name(sequence: u64) -> String {
+    format!("{:08}.txt", sequence)
+}
+
+fn write_exchange(dir: &Path, exchange: &Exchange) -> io::Result<()> {
+    let timestamp = exchange
+        .timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let contents = format!(
+        "sequence: {}\ntimestamp: {}.{:06}\nrequest: {} bytes\nresponse: {} bytes\n\n{}\n\n{}",
+        exchange.sequence,
+        timestamp.as_secs(),
+        timestamp.subsec_micros(),
+        exchange.request.len(),
+        exchange.response.len(),
+        exchange.request,
+        exchange.response,
+    );
+    fs::write(dir.join(file_name(exchange.sequence)), contents)
+}
+
+/// Simulator traffic captured with
+/// [Configuration::transcript](crate::Configuration::transcript), read back
+/// from its directory.
+///
+/// ```no_run
+/// use realflight_bridge::Transcript;
+///
+/// let transcript = Transcript::open("rf-transcript")?;
+/// for entry in transcript.entries() {
+///     println!("#{} {:?} -> {}", entry.sequence, entry.action(), entry.status_code().unwrap_or(0));
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Transcript {
+    entries: Vec<TranscriptEntry>,
+}
+
+/// One request/response pair of a [Transcript].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptEntry {
+    /// Position of the exchange, starting at 1 for each client
+    pub sequence: u64,
+    /// When the response was read
+    pub timestamp: SystemTime,
+    /// The HTTP request as sent, headers and SOAP envelope
+    pub request: String,
+    /// The final HTTP response, status line, headers and decoded body
+    pub response: String,
+}
+
+impl Transcript {
+    /// Reads every transcript file in `dir`, ordered by sequence number.
+    /// Fails with [io::ErrorKind::InvalidData] on a file it cannot parse.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        for file in fs::read_dir(dir)? {
+            let path = file?.path();
+            if path.extension().is_some_and(|ext| ext == "txt") {
+                entries.push(parse_entry(&path)?);
+            }
+        }
+        entries.sort_by_key(|entry| entry.sequence);
+        Ok(Transcript { entries })
+    }
+
+    /// The captured exchanges.
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    /// The exchange with the given sequence number.
+    pub fn get(&self, sequence: u64) -> Option<&TranscriptEntry> {
+        self.entries
+            .binary_search_by_key(&sequence, |entry| entry.sequence)
+            .ok()
+            .map(|index| &self.entries[index])
+    }
+}
+
+impl TranscriptEntry {
+    /// The SOAP action named by the request's `Soapaction` header.
+    pub fn action(&self) -> Option<&str> {
+        self.request.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("soapaction")
+                .then(|| value.trim().trim_matches('\''))
+        })
+    }
+
+    /// The HTTP status code of the response.
+    pub fn status_code(&self) -> Option<u32> {
+        self.response.split_whitespace().nth(1)?.parse().ok()
+    }
+
+    /// The response body, after the blank line ending its headers.
+    pub fn response_body(&self) -> &str {
+        self.response
+            .split_once("\r\n\r\n")
+            .map_or("", |(_, body)| body)
+    }
+}
+
+fn parse_entry(path: &Path) -> io::Result<TranscriptEntry> {
+    let invalid = |what: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), what),
+        )
+    };
+    let contents = fs::read_to_string(path)?;
+    let (header, rest) = contents
+        .split_once("\n\n")
+        .ok_or_else(|| invalid("missing header"))?;
+
+    let field = |name: &str| {
+        header
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+            .ok_or_else(|| invalid(&format!("missing {}", name)))
+    };
+    let length = |name: &str| -> io::Result<usize> {
+        field(name)?
+            .strip_suffix(" bytes")
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| invalid(&format!("invalid {} length", name)))
+    };
+    let sequence = field("sequence")?
+        .parse()
+        .map_err(|_| invalid("invalid sequence"))?;
+    let timestamp = field("timestamp")?
+        .split_once('.')
+        .and_then(|(secs, micros)| Some((secs.parse().ok()?, micros.parse().ok()?)))
+        .map(|(secs, micros)| Duration::from_secs(secs) + Duration::from_micros(micros))
+        .ok_or_else(|| invalid("invalid timestamp"))?;
+    let (request_length, response_length) = (length("request")?, length("response")?);
+
+    let request = rest
+        .get(..request_length)
+        .ok_or_else(|| invalid("truncated request"))?;
+    let response = rest
+        .get(request_length..)
+        .and_then(|r| r.strip_prefix("\n\n"))
+        .filter(|r| r.len() == response_length)
+        .ok_or_else(|| invalid("truncated response"))?;
+
+    Ok(TranscriptEntry {
+        sequence,
+        timestamp: UNIX_EPOCH + timestamp,
+        request: request.to_string(),
+        response: response.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "realflight-bridge-{}-transcript-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn request(action: &str) -> String {
+        format!(
+            "POST / HTTP/1.1\r\nSoapaction: '{}'\r\nContent-Length: 4\r\n\r\nbody",
+            action
+        )
+    }
+
+    #[test]
+    fn writes_numbered_pairs() {
+        let dir = temp_dir("pairs");
+        let recorder = TranscriptRecorder::start(&dir, Arc::new(StatisticsEngine::new())).unwrap();
+
+        recorder.record(request("First"), "HTTP/1.1 200 OK\r\n\r\n", "one");
+        recorder.record(request("Second"), "HTTP/1.1 500 Error\r\n\r\n", "two");
+        drop(recorder);
+
+        let transcript = Transcript::open(&dir).unwrap();
+        let entries = transcript.entries();
+        assert_eq!(entries[0].sequence, 1);
+        assert_eq!(entries[0].action(), Some("First"));
+        assert_eq!(entries[0].status_code(), Some(200));
+        assert_eq!(entries[0].response_body(), "one");
+        assert_eq!(entries[1].sequence, 2);
+        assert_eq!(entries[1].action(), Some("Second"));
+        assert_eq!(entries[1].status_code(), Some(500));
+        assert_eq!(entries[1].response_body(), "two");
+        assert!(dir.join("00000001.txt").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn round_trips_timestamp() {
+        let dir = temp_dir("timestamp");
+        let recorder = TranscriptRecorder::start(&dir, Arc::new(StatisticsEngine::new())).unwrap();
+        let before = SystemTime::now() - Duration::from_millis(1);
+
+        recorder.record(request("Action"), "HTTP/1.1 200 OK\r\n\r\n", "");
+        drop(recorder);
+
+        let transcript = Transcript::open(&dir).unwrap();
+        let entry = &transcript.entries()[0];
+        assert!(entry.timestamp >= before);
+        assert!(entry.timestamp <= SystemTime::now());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn drops_and_counts_when_writer_is_behind() {
+        let statistics = Arc::new(StatisticsEngine::new());
+        let (tx, _rx) = bounded(1);
+        let recorder = TranscriptRecorder {
+            tx: Some(tx),
+            writer: None,
+            sequence: AtomicU64::new(0),
+            statistics: Arc::clone(&statistics),
+        };
+
+        recorder.record(request("Kept"), "HTTP/1.1 200 OK\r\n\r\n", "");
+        recorder.record(request("Dropped"), "HTTP/1.1 200 OK\r\n\r\n", "");
+
+        assert_eq!(statistics.snapshot().transcript_dropped_count, 1);
+    }
+
+    #[test]
+    fn open_rejects_malformed_file() {
+        let dir = temp_dir("malformed");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(file_name(1)), "sequence: 1\n\ngarbage").unwrap();
+
+        let err = Transcript::open(&dir).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn get_finds_entry_by_sequence() {
+        let dir = temp_dir("get");
+        let recorder = TranscriptRecorder::start(&dir, Arc::new(StatisticsEngine::new())).unwrap();
+        for action in ["A", "B", "C"] {
+            recorder.record(request(action), "HTTP/1.1 200 OK\r\n\r\n", action);
+        }
+        drop(recorder);
+
+        let transcript = Transcript::open(&dir).unwrap();
+        assert_eq!(
+            transcript.get(2).and_then(TranscriptEntry::action),
+            Some("B")
+        );
+        assert!(transcript.get(4).is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,13 +2,15 @@
 //! This module is runtime-agnostic (no I/O).
 
 use super::SoapResponse;
+#[cfg(feature = "compression")]
+use super::compression::ContentEncoding;
 use crate::BridgeError;
 
 /// Size of header for request body
 const HEADER_LEN: usize = 120;
 
 /// Encode a SOAP envelope for RealFlight
-pub(crate) fn encode_envelope(action: &str, body: &str) -> String {
+pub fn encode_envelope(action: &str, body: &str) -> String {
     let mut envelope = String::with_capacity(200 + body.len());
 
     envelope.push_str("<?xml version='1.0' encoding='UTF-8'?>");
@@ -21,14 +23,20 @@ pub(crate) fn encode_envelope(action: &str, body: &str) -> String {
     envelope
 }
 
-/// Build an HTTP request string for a SOAP action
-pub(crate) fn build_http_request(action: &str, envelope: &str) -> String {
+/// Build an HTTP request string for a SOAP action, asking the simulator to
+/// keep the connection open if `keep_alive` is set
+pub fn build_http_request(action: &str, envelope: &str, keep_alive: bool) -> String {
     let mut request = String::with_capacity(HEADER_LEN + envelope.len() + action.len());
 
     request.push_str("POST / HTTP/1.1\r\n");
     request.push_str(&format!("Soapaction: '{}'\r\n", action));
     request.push_str(&format!("Content-Length: {}\r\n", envelope.len()));
     request.push_str("Content-Type: text/xml;charset=utf-8\r\n");
+    #[cfg(feature = "compression")]
+    request.push_str("Accept-Encoding: gzip, deflate\r\n");
+    if keep_alive {
+        request.push_str("Connection: keep-alive\r\n");
+    }
     request.push_str("\r\n");
     request.push_str(envelope);
 
@@ -53,18 +61,198 @@ pub(crate) fn parse_status_line(status_line: &str) -> Result<u32, BridgeError> {
         .map_err(|e| BridgeError::SoapFault(format!("Invalid HTTP status code: {}", e)))
 }
 
-/// Extract Content-Length from a header line if present
-pub(crate) fn parse_content_length(line: &str) -> Option<usize> {
-    const PREFIX: &str = "content-length:";
-    if line.len() >= PREFIX.len() && line[..PREFIX.len()].eq_ignore_ascii_case(PREFIX) {
-        line.split_whitespace()
-            .nth(1)
-            .and_then(|s| s.trim().parse().ok())
-    } else {
-        None
+/// Whether a status code belongs to an interim 1xx response, which carries no
+/// body and precedes the final response
+pub(crate) fn is_interim(status_code: u32) -> bool {
+    (100..200).contains(&status_code)
+}
+
+/// Framing headers of a response, collected as its header lines are read.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ResponseHeaders {
+    pub content_length: Option<usize>,
+    /// Whether the body uses `Transfer-Encoding: chunked`
+    pub chunked: bool,
+    #[cfg(feature = "compression")]
+    pub encoding: ContentEncoding,
+}
+
+impl ResponseHeaders {
+    /// Records one header line. Names match case-insensitively and
+    /// whitespace around the name and value is optional. A malformed or
+    /// repeated Content-Length fails, as the body could not be delimited
+    /// reliably. An empty line is what reading returns at end of stream, so it
+    /// fails too rather than waiting forever for the blank line ending the
+    /// headers.
+    pub(crate) fn parse_line(&mut self, line: &str) -> Result<(), BridgeError> {
+        if line.is_empty() {
+            return Err(BridgeError::SoapFault(
+                "Connection closed before end of headers".into(),
+            ));
+        }
+        let Some((name, value)) = split_header(line) else {
+            return Ok(());
+        };
+        if name.eq_ignore_ascii_case("content-length") {
+            if self.content_length.is_some() {
+                return Err(BridgeError::SoapFault(
+                    "Duplicate Content-Length header".into(),
+                ));
+            }
+            let length = value.parse().map_err(|e| {
+                BridgeError::SoapFault(format!("Invalid Content-Length '{}': {}", value, e))
+            })?;
+            self.content_length = Some(length);
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            // Only a final chunked coding delimits the body
+            self.chunked = value
+                .rsplit(',')
+                .next()
+                .is_some_and(|coding| coding.trim().eq_ignore_ascii_case("chunked"));
+        }
+        #[cfg(feature = "compression")]
+        if name.eq_ignore_ascii_case("content-encoding") {
+            self.encoding = ContentEncoding::parse(value).ok_or_else(|| {
+                BridgeError::Protocol(format!("Unsupported Content-Encoding '{}'", value))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Fail if a response body of `length` bytes exceeds `limit`
+pub(crate) fn check_body_size(length: usize, limit: usize) -> Result<(), BridgeError> {
+    if length > limit {
+        return Err(BridgeError::Protocol(format!(
+            "Response body of {} bytes exceeds the {} byte limit",
+            length, limit
+        )));
+    }
+    Ok(())
+}
+
+/// Split a header line into its trimmed name and value
+fn split_header(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.split_once(':')?;
+    Some((name.trim(), value.trim()))
+}
+
+/// Decodes a `Transfer-Encoding: chunked` body fed to it in pieces, as they
+/// arrive from the connection.
+///
+/// Trailer headers after the last chunk are read and discarded, so a kept
+/// connection is left at the start of the next response.
+#[derive(Debug)]
+pub(crate) struct ChunkedDecoder {
+    state: ChunkState,
+    /// Partial line carried over from the previous piece
+    line: Vec<u8>,
+    /// Body bytes announced by the chunks so far
+    announced: usize,
+    /// Largest body accepted, see [check_body_size]
+    limit: usize,
+}
+
+#[derive(Debug, PartialEq)]
+enum ChunkState {
+    /// Reading a chunk-size line
+    Size,
+    /// Copying this many more chunk bytes into the body
+    Data(usize),
+    /// Reading the CRLF that ends a chunk
+    DataEnd,
+    /// Reading trailer headers up to the blank line ending the body
+    Trailers,
+    Done,
+}
+
+impl ChunkedDecoder {
+    /// Creates a decoder failing once the body would exceed `limit` bytes.
+    pub(crate) fn new(limit: usize) -> Self {
+        ChunkedDecoder {
+            state: ChunkState::Size,
+            line: Vec::new(),
+            announced: 0,
+            limit,
+        }
+    }
+
+    /// Decodes as much of `input` as belongs to the body into `body`,
+    /// returning the number of bytes consumed.
+    pub(crate) fn decode(
+        &mut self,
+        input: &[u8],
+        body: &mut Vec<u8>,
+    ) -> Result<usize, BridgeError> {
+        let mut used = 0;
+        while used < input.len() && !self.is_done() {
+            let rest = &input[used..];
+            if let ChunkState::Data(remaining) = self.state {
+                let n = remaining.min(rest.len());
+                body.extend_from_slice(&rest[..n]);
+                used += n;
+                self.state = match remaining - n {
+                    0 => ChunkState::DataEnd,
+                    remaining => ChunkState::Data(remaining),
+                };
+                continue;
+            }
+
+            match rest.iter().position(|&b| b == b'\n') {
+                Some(end) => {
+                    self.line.extend_from_slice(&rest[..=end]);
+                    used += end + 1;
+                    let line = std::mem::take(&mut self.line);
+                    self.end_line(&line)?;
+                }
+                None => {
+                    self.line.extend_from_slice(rest);
+                    used += rest.len();
+                }
+            }
+        }
+        Ok(used)
+    }
+
+    /// Whether the whole body, including trailers, has been decoded.
+    pub(crate) fn is_done(&self) -> bool {
+        self.state == ChunkState::Done
+    }
+
+    fn end_line(&mut self, line: &[u8]) -> Result<(), BridgeError> {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\r', '\n']);
+        self.state = match self.state {
+            ChunkState::Size => match parse_chunk_size(line)? {
+                0 => ChunkState::Trailers,
+                size => {
+                    self.announced = self.announced.saturating_add(size);
+                    check_body_size(self.announced, self.limit)?;
+                    ChunkState::Data(size)
+                }
+            },
+            ChunkState::DataEnd if line.is_empty() => ChunkState::Size,
+            ChunkState::DataEnd => {
+                return Err(BridgeError::SoapFault(
+                    "Malformed chunked body: chunk not followed by CRLF".into(),
+                ));
+            }
+            ChunkState::Trailers if line.is_empty() => ChunkState::Done,
+            ChunkState::Trailers => ChunkState::Trailers,
+            ChunkState::Data(_) | ChunkState::Done => unreachable!("not a line state"),
+        };
+        Ok(())
     }
 }
 
+/// Parse a chunk-size line, ignoring chunk extensions
+fn parse_chunk_size(line: &str) -> Result<usize, BridgeError> {
+    let size = line.split(';').next().unwrap_or_default().trim();
+    usize::from_str_radix(size, 16).map_err(|e| {
+        BridgeError::SoapFault(format!("Malformed chunked body: invalid chunk size: {}", e))
+    })
+}
+
 /// Create a SoapResponse from parsed components
 pub(crate) fn create_response(status_code: u32, body: Vec<u8>) -> SoapResponse {
     SoapResponse {
@@ -150,33 +338,100 @@ mod tests {
 
         #[test]
         fn includes_post_method() {
-            let request = build_http_request("Test", "body");
+            let request = build_http_request("Test", "body", false);
             assert!(request.starts_with("POST / HTTP/1.1\r\n"));
         }
 
         #[test]
         fn includes_soapaction_header() {
-            let request = build_http_request("MyAction", "body");
+            let request = build_http_request("MyAction", "body", false);
             assert!(request.contains("Soapaction: 'MyAction'\r\n"));
         }
 
         #[test]
         fn includes_content_length_header() {
-            let request = build_http_request("Test", "hello");
+            let request = build_http_request("Test", "hello", false);
             assert!(request.contains("Content-Length: 5\r\n"));
         }
 
         #[test]
         fn includes_content_type_header() {
-            let request = build_http_request("Test", "body");
+            let request = build_http_request("Test", "body", false);
             assert!(request.contains("Content-Type: text/xml;charset=utf-8\r\n"));
         }
 
         #[test]
         fn ends_with_envelope() {
-            let request = build_http_request("Test", "<envelope>data</envelope>");
+            let request = build_http_request("Test", "<envelope>data</envelope>", false);
             assert!(request.ends_with("<envelope>data</envelope>"));
         }
+
+        #[test]
+        fn keep_alive_adds_connection_header() {
+            let request = build_http_request("Test", "body", true);
+            assert!(request.contains("Connection: keep-alive\r\n"));
+
+            let request = build_http_request("Test", "body", false);
+            assert!(!request.contains("Connection:"));
+        }
+
+        #[cfg(feature = "compression")]
+        #[test]
+        fn compression_adds_accept_encoding_header() {
+            let request = build_http_request("Test", "body", false);
+            assert!(request.contains("Accept-Encoding: gzip, deflate\r\n"));
+        }
+    }
+
+    mod request_goldens {
+        use super::*;
+        use crate::encoders::encode_control_inputs;
+        use crate::tests::golden::assert_golden;
+
+        /// Golden file name; requests carry an extra header with compression
+        fn golden(name: &str) -> String {
+            if cfg!(feature = "compression") {
+                format!("{}.compression.http", name)
+            } else {
+                format!("{}.http", name)
+            }
+        }
+
+        fn request(action: &str, body: &str, keep_alive: bool) -> String {
+            build_http_request(action, &encode_envelope(action, body), keep_alive)
+        }
+
+        #[test]
+        fn reset_aircraft() {
+            let request = request("ResetAircraft", "", false);
+            assert_golden(&golden("reset-aircraft"), &request);
+        }
+
+        #[test]
+        fn inject_uav_controller_interface() {
+            let request = request("InjectUAVControllerInterface", "", false);
+            assert_golden(&golden("inject-uav-controller-interface"), &request);
+        }
+
+        #[test]
+        fn restore_original_controller_device() {
+            let request = request("RestoreOriginalControllerDevice", "", false);
+            assert_golden(&golden("restore-original-controller-device"), &request);
+        }
+
+        #[test]
+        fn exchange_data() {
+            let body = encode_control_inputs(&crate::ControlInputs::default());
+            let request = request("ExchangeData", &body, false);
+            assert_golden(&golden("exchange-data"), &request);
+        }
+
+        #[test]
+        fn exchange_data_keep_alive() {
+            let body = encode_control_inputs(&crate::ControlInputs::default());
+            let request = request("ExchangeData", &body, true);
+            assert_golden(&golden("exchange-data-keep-alive"), &request);
+        }
     }
 
     mod parse_status_line_tests {
@@ -219,25 +474,219 @@ mod tests {
         }
     }
 
-    mod parse_content_length_tests {
+    mod is_interim_tests {
         use super::*;
 
         #[test]
-        fn extracts_content_length() {
-            let length = parse_content_length("Content-Length: 1234");
-            assert_eq!(length, Some(1234));
+        fn informational_codes_are_interim() {
+            assert!(is_interim(100));
+            assert!(is_interim(103));
         }
 
         #[test]
-        fn case_insensitive() {
-            let length = parse_content_length("content-length: 567");
-            assert_eq!(length, Some(567));
+        fn final_codes_are_not_interim() {
+            assert!(!is_interim(200));
+            assert!(!is_interim(500));
+        }
+    }
+
+    mod response_headers_tests {
+        use super::*;
+
+        fn parse(lines: &[&str]) -> Result<ResponseHeaders, BridgeError> {
+            let mut headers = ResponseHeaders::default();
+            for line in lines {
+                headers.parse_line(line)?;
+            }
+            Ok(headers)
+        }
+
+        #[test]
+        fn parses_header_permutations() {
+            let cases: [(&[&str], Option<usize>, bool); 14] = [
+                (&["Content-Length: 1234"], Some(1234), false),
+                (&["content-length: 567"], Some(567), false),
+                (&["CONTENT-LENGTH: 89\r\n"], Some(89), false),
+                (&["Content-Length:123"], Some(123), false),
+                (&["Content-Length:   42   \r\n"], Some(42), false),
+                (&["Content-Length\t:\t7"], Some(7), false),
+                (&["cOnTeNt-LeNgTh: 0"], Some(0), false),
+                (&["Content-Type: text/xml"], None, false),
+                (
+                    &["Content-Type: text/xml", "Content-Length: 12"],
+                    Some(12),
+                    false,
+                ),
+                (&["Transfer-Encoding: chunked"], None, true),
+                (&["transfer-encoding:CHUNKED\r\n"], None, true),
+                (&["Transfer-Encoding: gzip, chunked"], None, true),
+                (&["Transfer-Encoding: chunked, gzip"], None, false),
+                (
+                    &["Server: RealFlight", "Content-Length: 5", "X-Info: a:b"],
+                    Some(5),
+                    false,
+                ),
+            ];
+
+            for (lines, content_length, chunked) in cases {
+                let headers = parse(lines).unwrap();
+                assert_eq!(
+                    (headers.content_length, headers.chunked),
+                    (content_length, chunked),
+                    "{:?}",
+                    lines
+                );
+            }
+        }
+
+        #[test]
+        fn rejects_truncated_headers() {
+            let cases: [&[&str]; 3] = [
+                &[""],
+                &["Content-Length: 5\r\n", ""],
+                &["Content-Type: text/xml\r\n", "Content-Length: 5\r\n", ""],
+            ];
+
+            for lines in cases {
+                let result = parse(lines);
+                assert!(
+                    matches!(result, Err(BridgeError::SoapFault(_))),
+                    "{:?}",
+                    lines
+                );
+            }
+        }
+
+        #[test]
+        fn rejects_malformed_content_length() {
+            let cases: [&[&str]; 4] = [
+                &["Content-Length: abc"],
+                &["Content-Length: -1"],
+                &["Content-Length:"],
+                &["Content-Length: 1 2"],
+            ];
+
+            for lines in cases {
+                let result = parse(lines);
+                assert!(
+                    matches!(result, Err(BridgeError::SoapFault(_))),
+                    "{:?}",
+                    lines
+                );
+            }
+        }
+
+        #[test]
+        fn rejects_duplicate_content_length() {
+            let cases: [&[&str]; 3] = [
+                &["Content-Length: 5", "Content-Length: 5"],
+                &["Content-Length: 5", "content-length: 6"],
+                &[
+                    "Content-Length:5",
+                    "Content-Type: text/xml",
+                    "CONTENT-LENGTH: 5",
+                ],
+            ];
+
+            for lines in cases {
+                match parse(lines) {
+                    Err(BridgeError::SoapFault(msg)) => {
+                        assert!(msg.contains("Duplicate"), "{}", msg)
+                    }
+                    other => panic!("expected duplicate error for {:?}, got {:?}", lines, other),
+                }
+            }
+        }
+
+        #[cfg(feature = "compression")]
+        #[test]
+        fn parses_content_encoding() {
+            let headers = parse(&["Content-Encoding: gzip", "Content-Length: 5"]).unwrap();
+            assert_eq!(headers.encoding, ContentEncoding::Gzip);
+
+            let result = parse(&["Content-Encoding: br"]);
+            assert!(matches!(result, Err(BridgeError::Protocol(_))));
+        }
+
+        #[test]
+        fn ignores_lines_without_colon() {
+            assert_eq!(parse(&["garbage\r\n"]).unwrap(), ResponseHeaders::default());
+        }
+    }
+
+    mod check_body_size_tests {
+        use super::*;
+
+        #[test]
+        fn accepts_body_up_to_limit() {
+            assert!(check_body_size(4096, 4096).is_ok());
+        }
+
+        #[test]
+        fn rejects_body_over_limit() {
+            let result = check_body_size(999_999_999, 4096);
+            assert!(matches!(result, Err(BridgeError::Protocol(_))));
+        }
+    }
+
+    mod chunked_decoder_tests {
+        use super::*;
+
+        const BODY: &[u8] = b"5\r\nHello\r\n7;ext=1\r\n, world\r\n0\r\nX-Trailer: yes\r\n\r\nNEXT";
+
+        #[test]
+        fn decodes_whole_body() {
+            let mut decoder = ChunkedDecoder::new(usize::MAX);
+            let mut body = Vec::new();
+
+            let used = decoder.decode(BODY, &mut body).unwrap();
+
+            assert!(decoder.is_done());
+            assert_eq!(body, b"Hello, world");
+            // Stops after the trailers, leaving the next response unread
+            assert_eq!(&BODY[used..], b"NEXT");
+        }
+
+        #[test]
+        fn decodes_body_split_into_single_bytes() {
+            let mut decoder = ChunkedDecoder::new(usize::MAX);
+            let mut body = Vec::new();
+
+            for byte in BODY.chunks(1) {
+                if decoder.is_done() {
+                    break;
+                }
+                assert_eq!(decoder.decode(byte, &mut body).unwrap(), 1);
+            }
+
+            assert!(decoder.is_done());
+            assert_eq!(body, b"Hello, world");
+        }
+
+        #[test]
+        fn errors_on_invalid_chunk_size() {
+            let mut decoder = ChunkedDecoder::new(usize::MAX);
+            let result = decoder.decode(b"zz\r\n", &mut Vec::new());
+            assert!(matches!(result, Err(BridgeError::SoapFault(_))));
+        }
+
+        #[test]
+        fn errors_when_chunks_exceed_limit() {
+            let mut decoder = ChunkedDecoder::new(10);
+            let mut body = Vec::new();
+
+            let result = decoder.decode(BODY, &mut body);
+
+            assert!(matches!(result, Err(BridgeError::Protocol(_))));
+            // The oversized chunk is refused before any of it is copied
+            assert_eq!(body, b"Hello");
         }
 
         #[test]
-        fn returns_none_for_other_headers() {
-            let length = parse_content_length("Content-Type: text/xml");
-            assert_eq!(length, None);
+        fn errors_on_missing_chunk_terminator() {
+            let mut decoder = ChunkedDecoder::new(usize::MAX);
+            let result = decoder.decode(b"2\r\nabcd\r\n", &mut Vec::new());
+            assert!(matches!(result, Err(BridgeError::SoapFault(_))));
         }
     }
 }
@@ -1,12 +1,13 @@
 //! Statistics tracking for RealFlight bridge operations.
 
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
 /// Represents a snapshot of performance metrics for a running `RealFlightBridge`.
 ///
-/// The `Statistics` struct is returned by [`RealFlightLocalBridge::statistics`](crate::RealFlightLocalBridge::statistics)
-/// and captures various counters and timings that can help diagnose performance issues
+/// The `Statistics` struct is returned by [`RealFlightLocalBridge::statistics`](crate::RealFlightLocalBridge::statistics),
+/// [`RealFlightRemoteBridge::statistics`](crate::RealFlightRemoteBridge::statistics) and their async
+/// counterparts, and captures various counters and timings that can help diagnose performance issues
 /// or monitor real-time operation.
 ///
 /// # Fields
@@ -15,6 +16,28 @@ use std::time::{Duration, Instant};
 /// - `error_count`: The number of errors (e.g., connection errors, SOAP faults) encountered so far.
 /// - `frequency`: An approximate request rate, calculated as `(request_count / runtime)`.
 /// - `request_count`: The total number of SOAP requests sent to the simulator. Loops back to 0 after `u32::MAX`.
+/// - `cancelled_count`: The number of async requests abandoned after being sent, either because
+///   the caller dropped the future or an I/O timeout expired. Their connections are closed.
+/// - `pool_wait_total`: Total time async requests spent waiting for a pooled connection.
+/// - `io_total`: Total time async requests spent writing requests and reading responses. For the
+///   remote bridges, the summed round-trip time through the proxy as seen by the client;
+///   `io_total / request_count` is the mean latency.
+/// - `in_flight`: The number of async requests currently between acquiring a connection and
+///   reading the response, including those still waiting for a connection.
+/// - `pool_target`: The number of connections the pool currently keeps ready. Only changes with
+///   [PoolSize::Adaptive](crate::PoolSize::Adaptive).
+/// - `pool_miss_rate`: The fraction of requests that found no connection ready in the pool and
+///   had to wait for one to be created.
+/// - `pool_starvation_count`: The number of times a [RealFlightLocalBridge](crate::RealFlightLocalBridge)
+///   request waited more than 1 ms for a pooled connection, including waits that timed out.
+/// - `transcript_dropped_count`: The number of exchanges left out of a
+///   [transcript](crate::Configuration::transcript) because its writer had fallen behind.
+/// - `channel_limit_count`: The number of exchanges whose inputs were changed by
+///   [channel limits](crate::Configuration::channel_limits).
+///
+/// Comparing `pool_wait_total` with `io_total` shows whether jitter comes from connection
+/// setup or from the simulator itself. The async-only fields stay zero for other bridges, and
+/// the pool fields stay zero for the remote bridges.
 ///
 /// ```no_run
 /// use realflight_bridge::{RealFlightLocalBridge, BridgeError};
@@ -42,6 +65,15 @@ pub struct Statistics {
     pub error_count: u32,
     pub frequency: f32,
     pub request_count: u32,
+    pub cancelled_count: u32,
+    pub pool_wait_total: Duration,
+    pub io_total: Duration,
+    pub in_flight: u32,
+    pub pool_target: u32,
+    pub pool_miss_rate: f32,
+    pub pool_starvation_count: u32,
+    pub transcript_dropped_count: u32,
+    pub channel_limit_count: u32,
 }
 
 /// Statistics engine for tracking bridge operations.
@@ -49,6 +81,16 @@ pub(crate) struct StatisticsEngine {
     start_time: Instant,
     error_count: AtomicU32,
     request_count: AtomicU32,
+    cancelled_count: AtomicU32,
+    pool_wait_nanos: AtomicU64,
+    io_nanos: AtomicU64,
+    in_flight: AtomicU32,
+    pool_target: AtomicU32,
+    pool_takes: AtomicU32,
+    pool_misses: AtomicU32,
+    pool_starvations: AtomicU32,
+    transcript_dropped: AtomicU32,
+    channel_limits_engaged: AtomicU32,
 }
 
 impl StatisticsEngine {
@@ -57,6 +99,16 @@ impl StatisticsEngine {
             start_time: Instant::now(),
             error_count: AtomicU32::new(0),
             request_count: AtomicU32::new(0),
+            cancelled_count: AtomicU32::new(0),
+            pool_wait_nanos: AtomicU64::new(0),
+            io_nanos: AtomicU64::new(0),
+            in_flight: AtomicU32::new(0),
+            pool_target: AtomicU32::new(0),
+            pool_takes: AtomicU32::new(0),
+            pool_misses: AtomicU32::new(0),
+            pool_starvations: AtomicU32::new(0),
+            transcript_dropped: AtomicU32::new(0),
+            channel_limits_engaged: AtomicU32::new(0),
         }
     }
 
@@ -66,6 +118,15 @@ impl StatisticsEngine {
             error_count: self.error_count(),
             frequency: self.frame_rate(),
             request_count: self.request_count(),
+            cancelled_count: self.cancelled_count.load(Ordering::Relaxed),
+            pool_wait_total: Duration::from_nanos(self.pool_wait_nanos.load(Ordering::Relaxed)),
+            io_total: Duration::from_nanos(self.io_nanos.load(Ordering::Relaxed)),
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            pool_target: self.pool_target.load(Ordering::Relaxed),
+            pool_miss_rate: self.pool_miss_rate(),
+            pool_starvation_count: self.pool_starvations.load(Ordering::Relaxed),
+            transcript_dropped_count: self.transcript_dropped.load(Ordering::Relaxed),
+            channel_limit_count: self.channel_limits_engaged.load(Ordering::Relaxed),
         }
     }
 
@@ -85,6 +146,73 @@ impl StatisticsEngine {
         self.error_count.fetch_add(1, Ordering::Relaxed);
     }
 
+    #[cfg(feature = "rt-tokio")]
+    pub(crate) fn increment_cancelled_count(&self) {
+        self.cancelled_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    pub(crate) fn add_pool_wait(&self, elapsed: Duration) {
+        self.pool_wait_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_io_time(&self, elapsed: Duration) {
+        self.io_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Counts a request as in flight until the returned guard is dropped.
+    #[cfg(feature = "rt-tokio")]
+    pub(crate) fn track_in_flight(&self) -> InFlight<'_> {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlight(self)
+    }
+
+    /// Counts a connection taken from the pool, and whether none was ready.
+    pub(crate) fn record_pool_take(&self, missed: bool) {
+        self.pool_takes.fetch_add(1, Ordering::Relaxed);
+        if missed {
+            self.pool_misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Connections taken from the pool so far, and how many of those found it
+    /// empty.
+    pub(crate) fn pool_takes(&self) -> (u32, u32) {
+        (
+            self.pool_takes.load(Ordering::Relaxed),
+            self.pool_misses.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Counts a request that waited too long for a pooled connection.
+    pub(crate) fn increment_pool_starvation(&self) {
+        self.pool_starvations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts an exchange the transcript writer had no room for.
+    pub(crate) fn increment_transcript_dropped(&self) {
+        self.transcript_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts an exchange whose inputs the channel limits changed.
+    pub(crate) fn increment_channel_limit_count(&self) {
+        self.channel_limits_engaged.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_pool_target(&self, target: usize) {
+        self.pool_target.store(target as u32, Ordering::Relaxed);
+    }
+
+    fn pool_miss_rate(&self) -> f32 {
+        let (takes, misses) = self.pool_takes();
+        if takes == 0 {
+            return 0.0;
+        }
+        misses as f32 / takes as f32
+    }
+
     fn frame_rate(&self) -> f32 {
         self.request_count() as f32 / self.start_time.elapsed().as_secs_f32()
     }
@@ -96,6 +224,17 @@ impl Default for StatisticsEngine {
     }
 }
 
+/// Decrements the in-flight gauge when dropped, including on cancellation.
+#[cfg(feature = "rt-tokio")]
+pub(crate) struct InFlight<'a>(&'a StatisticsEngine);
+
+#[cfg(feature = "rt-tokio")]
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,6 +295,61 @@ mod tests {
         assert!(snapshot.frequency < 100.0);
     }
 
+    #[cfg(feature = "rt-tokio")]
+    #[test]
+    fn in_flight_guard_tracks_requests() {
+        let engine = StatisticsEngine::new();
+
+        let first = engine.track_in_flight();
+        let second = engine.track_in_flight();
+        assert_eq!(engine.snapshot().in_flight, 2);
+
+        drop(first);
+        drop(second);
+        assert_eq!(engine.snapshot().in_flight, 0);
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    #[test]
+    fn timings_accumulate() {
+        let engine = StatisticsEngine::new();
+
+        engine.add_pool_wait(Duration::from_millis(2));
+        engine.add_pool_wait(Duration::from_millis(3));
+        engine.add_io_time(Duration::from_millis(7));
+
+        let snapshot = engine.snapshot();
+        assert_eq!(snapshot.pool_wait_total, Duration::from_millis(5));
+        assert_eq!(snapshot.io_total, Duration::from_millis(7));
+    }
+
+    #[test]
+    fn pool_miss_rate_counts_empty_takes() {
+        let engine = StatisticsEngine::new();
+        assert_eq!(engine.snapshot().pool_miss_rate, 0.0);
+
+        engine.record_pool_take(true);
+        engine.record_pool_take(false);
+        engine.record_pool_take(false);
+        engine.record_pool_take(false);
+        engine.set_pool_target(3);
+
+        let snapshot = engine.snapshot();
+        assert_eq!(snapshot.pool_miss_rate, 0.25);
+        assert_eq!(snapshot.pool_target, 3);
+    }
+
+    #[test]
+    fn increment_pool_starvation() {
+        let engine = StatisticsEngine::new();
+        assert_eq!(engine.snapshot().pool_starvation_count, 0);
+
+        engine.increment_pool_starvation();
+        engine.increment_pool_starvation();
+
+        assert_eq!(engine.snapshot().pool_starvation_count, 2);
+    }
+
     #[test]
     fn default_creates_new_engine() {
         let engine = StatisticsEngine::default();
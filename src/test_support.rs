@@ -0,0 +1,787 @@
+//! Test doubles for unit testing controllers without RealFlight.
+//!
+//! [StubSoapClient] answers the SOAP requests of a
+//! [RealFlightLocalBridge](crate::RealFlightLocalBridge)
+//! with recorded RealFlight responses from [fixtures], so the bridge's own
+//! encoding, decoding and fault handling run as they would against the
+//! simulator. [ScriptedBridge] skips the bridge entirely and hands out a
+//! sequence of [SimulatorState]s you construct.
+//!
+//! ```
+//! use realflight_bridge::test_support::ScriptedBridge;
+//! use realflight_bridge::{BridgeError, ControlInputs, RealFlightBridge, SimulatorState};
+//!
+//! /// Cuts the throttle once the aircraft is on the ground.
+//! fn landing_step(
+//!     bridge: &impl RealFlightBridge,
+//!     inputs: &mut ControlInputs,
+//! ) -> Result<(), BridgeError> {
+//!     let state = bridge.exchange_data(inputs)?;
+//!     if state.is_touching_ground {
+//!         inputs.channels[2] = 0.0;
+//!     }
+//!     Ok(())
+//! }
+//!
+//! let airborne = SimulatorState::default();
+//! let landed = SimulatorState {
+//!     is_touching_ground: true,
+//!     ..Default::default()
+//! };
+//! let bridge = ScriptedBridge::new([airborne, landed]);
+//! let mut inputs = ControlInputs::default();
+//! inputs.channels[2] = 0.4;
+//!
+//! landing_step(&bridge, &mut inputs)?;
+//! assert_eq!(inputs.channels[2], 0.4);
+//! landing_step(&bridge, &mut inputs)?;
+//! assert_eq!(inputs.channels[2], 0.0);
+//! assert_eq!(bridge.inputs().len(), 2);
+//! # Ok::<(), BridgeError>(())
+//! ```
+//!
+//! A bridge answering from recorded responses:
+//!
+//! ```
+//! use realflight_bridge::test_support::StubSoapClient;
+//! use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+//!
+//! let stub = StubSoapClient::new(vec![
+//!     "inject-uav-controller-interface-200".to_string(),
+//!     "return-data-200".to_string(),
+//! ]);
+//! let requests = stub.request_log();
+//! let bridge = RealFlightLocalBridge::stub(stub);
+//!
+//! bridge.disable_rc()?;
+//! let state = bridge.exchange_data(&ControlInputs::default())?;
+//! assert!(state.an_engine_is_running);
+//! assert_eq!(requests.lock().unwrap().len(), 2);
+//! # Ok::<(), realflight_bridge::BridgeError>(())
+//! ```
+//!
+//! This module requires the `test-support` feature, typically enabled for
+//! dev-dependencies only.
+
+#![cfg(feature = "test-support")]
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::{BridgeError, ControlInputs, RealFlightBridge, SimulatorState};
+
+pub use crate::soap_client::stub::{StubSoapClient, fixtures};
+
+/// Bridge serving a fixed sequence of states, one per exchange.
+///
+/// The inputs sent are kept for assertions. Once the states run out,
+/// exchanges fail with [BridgeError::InvalidState]. The other bridge methods
+/// succeed without effect.
+pub struct ScriptedBridge {
+    states: Vec<SimulatorState>,
+    inputs: Mutex<Vec<ControlInputs>>,
+}
+
+impl ScriptedBridge {
+    /// Serves `states` in order.
+    pub fn new(states: impl IntoIterator<Item = SimulatorState>) -> Self {
+        ScriptedBridge {
+            states: states.into_iter().collect(),
+            inputs: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Inputs sent so far, oldest first.
+    pub fn inputs(&self) -> Vec<ControlInputs> {
+        self.inputs.lock().unwrap().clone()
+    }
+
+    /// Number of states not yet served.
+    pub fn remaining(&self) -> usize {
+        self.states.len() - self.inputs.lock().unwrap().len()
+    }
+
+    fn next_state(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        let mut inputs = self.inputs.lock().unwrap();
+        let state = self.states.get(inputs.len()).ok_or_else(|| {
+            BridgeError::InvalidState(format!(
+                "Scripted states exhausted after {} states",
+                self.states.len()
+            ))
+        })?;
+        inputs.push(control.clone());
+        Ok(state.clone())
+    }
+}
+
+impl RealFlightBridge for ScriptedBridge {
+    fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        self.next_state(control)
+    }
+
+    fn enable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    fn disable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rt-tokio")]
+impl crate::AsyncBridge for ScriptedBridge {
+    async fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        self.next_state(control)
+    }
+
+    async fn enable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    async fn disable_rc(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+
+    async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        Ok(())
+    }
+}
+
+/// Which calls of a [FaultInjectingBridge] a fault is injected on.
+///
+/// Calls are counted from 0 across every bridge method.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FaultSchedule {
+    /// Every `n`th call: calls `n - 1`, `2n - 1` and so on
+    EveryNth(u64),
+    /// The listed calls
+    Calls(Vec<u64>),
+    /// Each call with the given probability, drawn from a generator seeded
+    /// with `seed`, so a run is repeated exactly by reusing the seed
+    Random { probability: f64, seed: u64 },
+}
+
+/// Fault a [FaultInjectingBridge] injects when its schedule fires.
+#[derive(Clone, Debug)]
+pub enum Fault {
+    /// Fails the call with the error returned by the function, without
+    /// calling the wrapped bridge
+    Error(fn() -> BridgeError),
+    /// Waits this long, then forwards the call to the wrapped bridge
+    Delay(Duration),
+}
+
+/// Faults injected by a [FaultInjectingBridge] so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FaultCounts {
+    /// Calls made through the bridge
+    pub calls: u64,
+    /// Calls failed with an injected error
+    pub errors: u64,
+    /// Calls delayed before being forwarded
+    pub delays: u64,
+}
+
+/// Bridge wrapping another and injecting faults on a deterministic schedule,
+/// for testing how a controller or supervisor handles failures.
+///
+/// Each [with_fault](Self::with_fault) adds a rule; on every call the first
+/// rule whose schedule fires decides the fault, and calls no rule fires on
+/// are forwarded unchanged. Wraps a [RealFlightBridge] and, with the
+/// `rt-tokio` feature, an [AsyncBridge](crate::AsyncBridge).
+///
+/// ```
+/// use realflight_bridge::test_support::{Fault, FaultInjectingBridge, FaultSchedule, ScriptedBridge};
+/// use realflight_bridge::{BridgeError, ControlInputs, RealFlightBridge, SimulatorState};
+/// use std::time::Duration;
+///
+/// let bridge = FaultInjectingBridge::new(ScriptedBridge::new(vec![SimulatorState::default(); 3]))
+///     .with_fault(FaultSchedule::Calls(vec![1]), Fault::Error(|| {
+///         BridgeError::Timeout(Duration::from_millis(20))
+///     }));
+///
+/// let inputs = ControlInputs::default();
+/// assert!(bridge.exchange_data(&inputs).is_ok());
+/// assert!(matches!(bridge.exchange_data(&inputs), Err(BridgeError::Timeout(_))));
+/// assert!(bridge.exchange_data(&inputs).is_ok());
+/// assert_eq!(bridge.counts().errors, 1);
+/// ```
+pub struct FaultInjectingBridge<B> {
+    inner: B,
+    rules: Vec<(FaultSchedule, Fault)>,
+    state: Mutex<FaultState>,
+}
+
+/// Call count, counters and one generator state per rule.
+#[derive(Default)]
+struct FaultState {
+    counts: FaultCounts,
+    generators: Vec<u64>,
+}
+
+impl<B> FaultInjectingBridge<B> {
+    /// Wraps `inner` without any faults.
+    pub fn new(inner: B) -> Self {
+        FaultInjectingBridge {
+            inner,
+            rules: Vec::new(),
+            state: Mutex::new(FaultState::default()),
+        }
+    }
+
+    /// Adds a rule injecting `fault` on the calls `schedule` selects.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an [EveryNth](FaultSchedule::EveryNth) interval is zero or
+    /// a [Random](FaultSchedule::Random) probability is outside 0 to 1.
+    pub fn with_fault(mut self, schedule: FaultSchedule, fault: Fault) -> Self {
+        match schedule {
+            FaultSchedule::EveryNth(n) => assert!(n > 0, "EveryNth interval must not be zero"),
+            FaultSchedule::Random { probability, seed } => {
+                assert!(
+                    (0.0..=1.0).contains(&probability),
+                    "probability must be between 0 and 1, got {}",
+                    probability
+                );
+                self.state.get_mut().unwrap().generators.push(seed);
+            }
+            FaultSchedule::Calls(_) => {}
+        }
+        self.rules.push((schedule, fault));
+        self
+    }
+
+    /// Faults injected so far.
+    pub fn counts(&self) -> FaultCounts {
+        self.state.lock().unwrap().counts
+    }
+
+    /// The wrapped bridge.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the wrapped bridge.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Counts a call and returns the fault to inject on it, if any.
+    fn next_fault(&self) -> Option<Fault> {
+        let mut state = self.state.lock().unwrap();
+        let call = state.counts.calls;
+        state.counts.calls += 1;
+
+        // Every generator advances on every call, so a rule's draws do not
+        // depend on the rules before it
+        let mut generators = state.generators.iter_mut();
+        let mut chosen = None;
+        for (schedule, fault) in &self.rules {
+            let fires = match schedule {
+                FaultSchedule::EveryNth(n) => (call + 1) % n == 0,
+                FaultSchedule::Calls(calls) => calls.contains(&call),
+                FaultSchedule::Random { probability, .. } => {
+                    let draw = next_unit(generators.next().unwrap());
+                    draw < *probability
+                }
+            };
+            if fires && chosen.is_none() {
+                chosen = Some(fault.clone());
+            }
+        }
+
+        match chosen {
+            Some(Fault::Error(_)) => state.counts.errors += 1,
+            Some(Fault::Delay(_)) => state.counts.delays += 1,
+            None => {}
+        }
+        chosen
+    }
+
+    /// Injects the next fault into a blocking call to `forward`.
+    fn call<T>(&self, forward: impl FnOnce() -> Result<T, BridgeError>) -> Result<T, BridgeError> {
+        match self.next_fault() {
+            Some(Fault::Error(error)) => Err(error()),
+            Some(Fault::Delay(delay)) => {
+                std::thread::sleep(delay);
+                forward()
+            }
+            None => forward(),
+        }
+    }
+
+    /// Injects the next fault into an async call to `forward`.
+    #[cfg(feature = "rt-tokio")]
+    async fn call_async<T>(
+        &self,
+        forward: impl Future<Output = Result<T, BridgeError>>,
+    ) -> Result<T, BridgeError> {
+        match self.next_fault() {
+            Some(Fault::Error(error)) => Err(error()),
+            Some(Fault::Delay(delay)) => {
+                tokio::time::sleep(delay).await;
+                forward.await
+            }
+            None => forward.await,
+        }
+    }
+}
+
+/// Advances a SplitMix64 generator and returns a value in `[0, 1)`.
+fn next_unit(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl<B: RealFlightBridge> RealFlightBridge for FaultInjectingBridge<B> {
+    fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        self.call(|| self.inner.exchange_data(control))
+    }
+
+    fn enable_rc(&self) -> Result<(), BridgeError> {
+        self.call(|| self.inner.enable_rc())
+    }
+
+    fn disable_rc(&self) -> Result<(), BridgeError> {
+        self.call(|| self.inner.disable_rc())
+    }
+
+    fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        self.call(|| self.inner.reset_aircraft())
+    }
+}
+
+#[cfg(feature = "rt-tokio")]
+impl<B: crate::AsyncBridge> crate::AsyncBridge for FaultInjectingBridge<B> {
+    async fn exchange_data(&self, control: &ControlInputs) -> Result<SimulatorState, BridgeError> {
+        self.call_async(self.inner.exchange_data(control)).await
+    }
+
+    async fn enable_rc(&self) -> Result<(), BridgeError> {
+        self.call_async(self.inner.enable_rc()).await
+    }
+
+    async fn disable_rc(&self) -> Result<(), BridgeError> {
+        self.call_async(self.inner.disable_rc()).await
+    }
+
+    async fn reset_aircraft(&self) -> Result<(), BridgeError> {
+        self.call_async(self.inner.reset_aircraft()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(prop_rpm: f32) -> SimulatorState {
+        SimulatorState {
+            prop_rpm,
+            ..Default::default()
+        }
+    }
+
+    mod scripted_bridge_tests {
+        use super::*;
+
+        #[test]
+        fn serves_states_in_order() {
+            let bridge = ScriptedBridge::new([state(1.0), state(2.0)]);
+
+            let first = bridge.exchange_data(&ControlInputs::default()).unwrap();
+            let second = bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert_eq!(first.prop_rpm, 1.0);
+            assert_eq!(second.prop_rpm, 2.0);
+            assert_eq!(bridge.remaining(), 0);
+        }
+
+        #[test]
+        fn records_inputs() {
+            let bridge = ScriptedBridge::new([state(1.0), state(2.0)]);
+            let mut inputs = ControlInputs::default();
+            inputs.channels[3] = 0.25;
+
+            bridge.exchange_data(&inputs).unwrap();
+
+            assert_eq!(bridge.inputs(), vec![inputs]);
+            assert_eq!(bridge.remaining(), 1);
+        }
+
+        #[test]
+        fn fails_once_exhausted() {
+            let bridge = ScriptedBridge::new([state(1.0)]);
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            match bridge.exchange_data(&ControlInputs::default()) {
+                Err(BridgeError::InvalidState(message)) => {
+                    assert_eq!(message, "Scripted states exhausted after 1 states")
+                }
+                other => panic!("expected InvalidState, got {:?}", other),
+            }
+            assert_eq!(bridge.inputs().len(), 1);
+        }
+
+        #[cfg(feature = "rt-tokio")]
+        #[tokio::test]
+        async fn serves_async_callers() {
+            use crate::AsyncBridge;
+
+            let bridge = ScriptedBridge::new([state(3.0)]);
+
+            let served = AsyncBridge::exchange_data(&bridge, &ControlInputs::default())
+                .await
+                .unwrap();
+
+            assert_eq!(served.prop_rpm, 3.0);
+        }
+    }
+
+    mod reset_and_wait_tests {
+        use super::*;
+        use crate::decoders::{to_length, to_velocity};
+
+        fn falling(altitude: f32, climb: f32) -> SimulatorState {
+            SimulatorState {
+                altitude_agl: to_length(altitude),
+                velocity_world_w: to_velocity(climb),
+                ..Default::default()
+            }
+        }
+
+        fn settling() -> Vec<SimulatorState> {
+            let mut states = vec![falling(3.0, -4.0), falling(1.5, -2.0), falling(0.2, -1.0)];
+            states.extend((0..5).map(|i| falling(0.02 * i as f32, 0.01)));
+            states
+        }
+
+        #[test]
+        fn returns_first_settled_state() {
+            let bridge = ScriptedBridge::new(settling());
+
+            let state = bridge
+                .reset_aircraft_and_wait(Duration::from_secs(1))
+                .unwrap();
+
+            assert_eq!(bridge.remaining(), 0);
+            assert_eq!(state.altitude_agl, to_length(0.08));
+            assert!(
+                bridge
+                    .inputs()
+                    .iter()
+                    .all(|inputs| *inputs == ControlInputs::neutral())
+            );
+        }
+
+        #[test]
+        fn times_out_when_state_keeps_changing() {
+            let bridge = ScriptedBridge::new((0..10).map(|i| falling(i as f32, -1.0)));
+
+            match bridge.reset_aircraft_and_wait(Duration::ZERO) {
+                Err(BridgeError::Timeout(settle)) => assert_eq!(settle, Duration::ZERO),
+                other => panic!("expected Timeout, got {:?}", other),
+            }
+            assert_eq!(bridge.inputs().len(), 1);
+        }
+
+        #[test]
+        fn propagates_exchange_errors() {
+            let bridge = ScriptedBridge::new([falling(1.0, 0.0)]);
+
+            assert!(matches!(
+                bridge.reset_aircraft_and_wait(Duration::from_secs(1)),
+                Err(BridgeError::InvalidState(_))
+            ));
+        }
+
+        #[cfg(feature = "rt-tokio")]
+        #[tokio::test]
+        async fn waits_for_async_callers() {
+            use crate::AsyncBridge;
+
+            let bridge = ScriptedBridge::new(settling());
+
+            let state = AsyncBridge::reset_aircraft_and_wait(&bridge, Duration::from_secs(1))
+                .await
+                .unwrap();
+
+            assert_eq!(state.altitude_agl, to_length(0.08));
+        }
+    }
+
+    mod fault_injecting_bridge_tests {
+        use super::*;
+        use crate::RealFlightLocalBridge;
+        use std::time::Instant;
+
+        fn stub_bridge() -> FaultInjectingBridge<RealFlightLocalBridge> {
+            let stub = StubSoapClient::new(vec!["return-data-200".to_string()]);
+            FaultInjectingBridge::new(RealFlightLocalBridge::stub(stub))
+        }
+
+        fn timeout() -> BridgeError {
+            BridgeError::Timeout(Duration::from_millis(20))
+        }
+
+        fn soap_fault() -> BridgeError {
+            BridgeError::SoapFault("injected".into())
+        }
+
+        /// Indices of the first `calls` exchanges that failed.
+        fn failed_calls<B: RealFlightBridge>(bridge: &B, calls: u64) -> Vec<u64> {
+            (0..calls)
+                .filter(|_| bridge.exchange_data(&ControlInputs::default()).is_err())
+                .collect()
+        }
+
+        #[test]
+        fn forwards_without_faults() {
+            let bridge = stub_bridge();
+
+            let state = bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert_eq!(
+                state,
+                crate::decode_simulator_state(fixtures::RETURN_DATA_200).unwrap()
+            );
+            assert_eq!(
+                bridge.counts(),
+                FaultCounts {
+                    calls: 1,
+                    ..Default::default()
+                }
+            );
+        }
+
+        #[test]
+        fn fails_every_nth_call() {
+            let bridge =
+                stub_bridge().with_fault(FaultSchedule::EveryNth(3), Fault::Error(timeout));
+
+            assert_eq!(failed_calls(&bridge, 9), [2, 5, 8]);
+            assert_eq!(bridge.counts().errors, 3);
+            assert_eq!(bridge.counts().calls, 9);
+        }
+
+        #[test]
+        fn fails_listed_calls() {
+            let bridge = stub_bridge()
+                .with_fault(FaultSchedule::Calls(vec![0, 4, 5]), Fault::Error(timeout));
+
+            assert_eq!(failed_calls(&bridge, 8), [0, 4, 5]);
+        }
+
+        #[test]
+        fn returns_configured_error() {
+            let bridge =
+                stub_bridge().with_fault(FaultSchedule::Calls(vec![0]), Fault::Error(soap_fault));
+
+            match bridge.exchange_data(&ControlInputs::default()) {
+                Err(BridgeError::SoapFault(message)) => assert_eq!(message, "injected"),
+                other => panic!("expected SoapFault, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn does_not_call_inner_bridge_on_error() {
+            let stub = StubSoapClient::new(vec!["return-data-200".to_string()]);
+            let requests = stub.request_log();
+            let bridge = FaultInjectingBridge::new(RealFlightLocalBridge::stub(stub))
+                .with_fault(FaultSchedule::Calls(vec![0]), Fault::Error(timeout));
+
+            assert!(bridge.exchange_data(&ControlInputs::default()).is_err());
+            assert!(bridge.exchange_data(&ControlInputs::default()).is_ok());
+
+            assert_eq!(requests.lock().unwrap().len(), 1);
+        }
+
+        #[test]
+        fn counts_every_bridge_method() {
+            let bridge = FaultInjectingBridge::new(ScriptedBridge::new([state(1.0)]))
+                .with_fault(FaultSchedule::Calls(vec![1]), Fault::Error(timeout));
+
+            bridge.disable_rc().unwrap();
+            assert!(bridge.exchange_data(&ControlInputs::default()).is_err());
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            bridge.enable_rc().unwrap();
+
+            assert_eq!(bridge.counts().calls, 4);
+            assert_eq!(bridge.inner().remaining(), 0);
+        }
+
+        #[test]
+        fn random_schedule_repeats_with_seed() {
+            let schedule = FaultSchedule::Random {
+                probability: 0.25,
+                seed: 7,
+            };
+            let first = stub_bridge().with_fault(schedule.clone(), Fault::Error(timeout));
+            let second = stub_bridge().with_fault(schedule, Fault::Error(timeout));
+            let other = stub_bridge().with_fault(
+                FaultSchedule::Random {
+                    probability: 0.25,
+                    seed: 8,
+                },
+                Fault::Error(timeout),
+            );
+
+            let failed = failed_calls(&first, 400);
+
+            assert_eq!(failed, failed_calls(&second, 400));
+            assert_ne!(failed, failed_calls(&other, 400));
+            assert!(
+                (60..140).contains(&failed.len()),
+                "{} failures",
+                failed.len()
+            );
+            assert_eq!(first.counts().errors, failed.len() as u64);
+        }
+
+        #[test]
+        fn random_schedule_bounds() {
+            let never = stub_bridge().with_fault(
+                FaultSchedule::Random {
+                    probability: 0.0,
+                    seed: 1,
+                },
+                Fault::Error(timeout),
+            );
+            let always = stub_bridge().with_fault(
+                FaultSchedule::Random {
+                    probability: 1.0,
+                    seed: 1,
+                },
+                Fault::Error(timeout),
+            );
+
+            assert!(failed_calls(&never, 50).is_empty());
+            assert_eq!(failed_calls(&always, 50).len(), 50);
+        }
+
+        #[test]
+        fn delays_scheduled_calls() {
+            let bridge = stub_bridge().with_fault(
+                FaultSchedule::Calls(vec![1]),
+                Fault::Delay(Duration::from_millis(50)),
+            );
+
+            let start = Instant::now();
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+            let undelayed = start.elapsed();
+            let start = Instant::now();
+            bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert!(start.elapsed() >= Duration::from_millis(50));
+            assert!(undelayed < Duration::from_millis(50));
+            assert_eq!(
+                bridge.counts(),
+                FaultCounts {
+                    calls: 2,
+                    errors: 0,
+                    delays: 1
+                }
+            );
+        }
+
+        #[test]
+        fn first_firing_rule_wins() {
+            let bridge = stub_bridge()
+                .with_fault(FaultSchedule::Calls(vec![2]), Fault::Error(soap_fault))
+                .with_fault(FaultSchedule::EveryNth(2), Fault::Error(timeout));
+
+            let errors: Vec<_> = (0..4)
+                .map(|_| bridge.exchange_data(&ControlInputs::default()).err())
+                .collect();
+
+            assert!(errors[0].is_none());
+            assert!(matches!(errors[1], Some(BridgeError::Timeout(_))));
+            assert!(matches!(errors[2], Some(BridgeError::SoapFault(_))));
+            assert!(matches!(errors[3], Some(BridgeError::Timeout(_))));
+            assert_eq!(bridge.counts().errors, 3);
+        }
+
+        #[test]
+        #[should_panic(expected = "EveryNth interval must not be zero")]
+        fn rejects_zero_interval() {
+            stub_bridge().with_fault(FaultSchedule::EveryNth(0), Fault::Error(timeout));
+        }
+
+        #[test]
+        #[should_panic(expected = "probability must be between 0 and 1")]
+        fn rejects_invalid_probability() {
+            stub_bridge().with_fault(
+                FaultSchedule::Random {
+                    probability: 1.5,
+                    seed: 1,
+                },
+                Fault::Error(timeout),
+            );
+        }
+
+        #[cfg(feature = "rt-tokio")]
+        #[tokio::test(start_paused = true)]
+        async fn injects_into_async_bridge() {
+            use crate::AsyncBridge;
+
+            let bridge = FaultInjectingBridge::new(ScriptedBridge::new([state(1.0), state(2.0)]))
+                .with_fault(FaultSchedule::Calls(vec![0]), Fault::Error(timeout))
+                .with_fault(
+                    FaultSchedule::Calls(vec![1]),
+                    Fault::Delay(Duration::from_secs(1)),
+                );
+            let inputs = ControlInputs::default();
+
+            let failed = AsyncBridge::exchange_data(&bridge, &inputs).await;
+            let start = tokio::time::Instant::now();
+            let delayed = AsyncBridge::exchange_data(&bridge, &inputs).await.unwrap();
+
+            assert!(matches!(failed, Err(BridgeError::Timeout(_))));
+            assert_eq!(delayed.prop_rpm, 1.0);
+            assert_eq!(start.elapsed(), Duration::from_secs(1));
+            assert_eq!(
+                bridge.counts(),
+                FaultCounts {
+                    calls: 2,
+                    errors: 1,
+                    delays: 1
+                }
+            );
+        }
+    }
+
+    mod fixture_tests {
+        use super::*;
+        use crate::RealFlightLocalBridge;
+
+        #[test]
+        fn every_fixture_is_named() {
+            for (name, body) in fixtures::ALL {
+                assert_eq!(fixtures::response(name), Some(body));
+                assert!(body.contains("Envelope"), "{} is not an envelope", name);
+            }
+            assert_eq!(fixtures::response("missing-200"), None);
+        }
+
+        #[test]
+        fn stub_bridge_decodes_fixture() {
+            let stub = StubSoapClient::new(vec!["return-data-200".to_string()]);
+            let bridge = RealFlightLocalBridge::stub(stub);
+
+            let state = bridge.exchange_data(&ControlInputs::default()).unwrap();
+
+            assert_eq!(
+                state,
+                crate::decode_simulator_state(fixtures::RETURN_DATA_200).unwrap()
+            );
+        }
+    }
+}
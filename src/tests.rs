@@ -6,5 +6,7 @@
 //! - `bridge::remote::tests` - RealFlightRemoteBridge tests
 //! - `decoders::tests` - XML decoder tests
 
+#[cfg(test)]
+pub mod golden;
 #[cfg(test)]
 pub mod soap_stub;
@@ -0,0 +1,73 @@
+//! Golden files for the requests the crate sends RealFlight.
+//!
+//! Each file under `testdata/requests/` holds the exact bytes of one request,
+//! as RealFlight receives them. A test encodes a request and compares it with
+//! its file; any change to the wire format fails until the file is updated.
+//!
+//! # Updating
+//!
+//! Run the tests with `UPDATE_GOLDEN=1` to rewrite the files from the current
+//! encoders, then review the change with `git diff testdata/requests`:
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test --all-features
+//! ```
+
+use std::path::PathBuf;
+
+/// Environment variable that rewrites golden files instead of comparing them.
+pub const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+/// Characters of context shown around the first difference
+const CONTEXT: usize = 60;
+
+/// Asserts that `actual` matches `testdata/requests/<name>` byte for byte.
+pub fn assert_golden(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "testdata", "requests", name]
+        .iter()
+        .collect();
+
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "cannot read {}: {}; run with {}=1 to create it",
+            path.display(),
+            e,
+            UPDATE_ENV
+        )
+    });
+    if expected == actual {
+        return;
+    }
+
+    let at = expected
+        .bytes()
+        .zip(actual.bytes())
+        .position(|(e, a)| e != a)
+        .unwrap_or(expected.len().min(actual.len()));
+    panic!(
+        "{} differs at byte {}\n  golden: {:?}\n  actual: {:?}\nrun with {}=1 to accept the new bytes",
+        path.display(),
+        at,
+        excerpt(&expected, at),
+        excerpt(actual, at),
+        UPDATE_ENV
+    );
+}
+
+/// Text around byte `at`, widened to character boundaries.
+fn excerpt(text: &str, at: usize) -> &str {
+    let mut start = at.saturating_sub(CONTEXT / 2);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (at + CONTEXT / 2).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+    &text[start.min(end)..end]
+}
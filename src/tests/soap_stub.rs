@@ -28,7 +28,7 @@ use std::{
     net::{TcpListener, TcpStream},
     sync::{
         Arc, Mutex,
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
     },
     thread,
 };
@@ -40,11 +40,27 @@ pub struct Server {
     handle: Option<thread::JoinHandle<()>>,
     running: Arc<AtomicBool>,
     requests: Arc<Mutex<Vec<String>>>,
+    framing: Framing,
+    connections: Arc<AtomicUsize>,
+}
+
+/// How the server frames its responses.
+#[derive(Clone, Copy, Default)]
+struct Framing {
+    /// Whether a connection stays open for further requests
+    keep_alive: bool,
+    /// Whether each response is preceded by an interim `100 Continue`
+    interim: bool,
+    /// Whether bodies are sent gzip compressed, from the `.xml.gz` fixtures
+    gzip: bool,
 }
 
 impl Drop for Server {
     fn drop(&mut self) {
         self.running.store(false, Ordering::Relaxed);
+        // Wake the worker if it is still waiting for a connection; it stops
+        // on a connection without a request
+        let _ = TcpStream::connect(("127.0.0.1", self.port));
 
         if let Some(handle) = self.handle.take() {
             if let Err(e) = handle.join() {
@@ -56,12 +72,49 @@ impl Drop for Server {
 
 impl Server {
     pub fn new(port: u16, responses: Vec<String>) -> Self {
+        Self::start(port, responses, Framing::default())
+    }
+
+    /// Serves every request a client sends on a connection, as a simulator
+    /// honoring keep-alive does, until the client hangs up.
+    pub fn keep_alive(port: u16, responses: Vec<String>) -> Self {
+        let framing = Framing {
+            keep_alive: true,
+            ..Framing::default()
+        };
+        Self::start(port, responses, framing)
+    }
+
+    /// Sends an interim `100 Continue` before each response, as some SOAP
+    /// middleboxes do.
+    pub fn with_continue(port: u16, responses: Vec<String>) -> Self {
+        let framing = Framing {
+            interim: true,
+            ..Framing::default()
+        };
+        Self::start(port, responses, framing)
+    }
+
+    /// Sends each body with `Content-Encoding: gzip`, as a compressing proxy
+    /// in front of the simulator would.
+    #[cfg(feature = "compression")]
+    pub fn gzip(port: u16, responses: Vec<String>) -> Self {
+        let framing = Framing {
+            gzip: true,
+            ..Framing::default()
+        };
+        Self::start(port, responses, framing)
+    }
+
+    fn start(port: u16, responses: Vec<String>, framing: Framing) -> Self {
         let mut server = Server {
             port,
             responses,
             handle: None,
             running: Arc::new(AtomicBool::new(true)),
             requests: Arc::new(Mutex::new(Vec::new())),
+            framing,
+            connections: Arc::new(AtomicUsize::new(0)),
         };
         server.start_worker();
         // allow server time to start
@@ -79,9 +132,16 @@ impl Server {
         requests.clone()
     }
 
+    /// Number of connections accepted.
+    pub fn connection_count(&self) -> usize {
+        self.connections.load(Ordering::Relaxed)
+    }
+
     fn start_worker(&mut self) {
         let mut responses = self.responses.clone();
         let requests = Arc::clone(&self.requests);
+        let connections = Arc::clone(&self.connections);
+        let framing = self.framing;
         let port = self.port;
 
         let handle = thread::spawn(move || {
@@ -89,7 +149,7 @@ impl Server {
 
             eprintln!("server listening on port {}", port);
 
-            for mut incoming in listener.incoming() {
+            'serve: for mut incoming in listener.incoming() {
                 eprintln!("incoming connection");
                 if responses.is_empty() {
                     break;
@@ -101,29 +161,42 @@ impl Server {
                 }
 
                 if let Ok(ref mut stream) = incoming {
+                    connections.fetch_add(1, Ordering::Relaxed);
                     let a = &mut stream.try_clone().unwrap();
                     let mut streamb = BufReader::new(a);
-                    let mut line = String::new();
-                    if let Err(e) = streamb.read_line(&mut line) {
-                        eprintln!("error reading line: {}", e);
-                        break;
-                    } else {
-                        eprintln!("status line: {}", line);
-                    }
-
-                    let request_body = read_request_body(&mut streamb);
-                    if request_body.is_empty() {
-                        eprintln!("empty request. try next.");
-                        thread::sleep(std::time::Duration::from_millis(100));
-                        break;
-                    }
-
-                    record_request(&requests, &request_body);
-
-                    if let Some(response_key) = responses.pop() {
-                        send_response(stream, &response_key);
-                    } else {
-                        eprintln!("no more responses to send");
+                    let mut served = 0;
+                    loop {
+                        let mut line = String::new();
+                        if let Err(e) = streamb.read_line(&mut line) {
+                            eprintln!("error reading line: {}", e);
+                            break 'serve;
+                        } else {
+                            eprintln!("status line: {}", line);
+                        }
+
+                        let request_body = read_request_body(&mut streamb);
+                        if request_body.is_empty() {
+                            if served > 0 && !responses.is_empty() {
+                                eprintln!("client closed kept connection");
+                                continue 'serve;
+                            }
+                            eprintln!("empty request. try next.");
+                            thread::sleep(std::time::Duration::from_millis(100));
+                            break 'serve;
+                        }
+
+                        record_request(&requests, &request_body);
+
+                        if let Some(response_key) = responses.pop() {
+                            send_response(stream, &response_key, framing);
+                        } else {
+                            eprintln!("no more responses to send");
+                        }
+                        served += 1;
+
+                        if !framing.keep_alive {
+                            break;
+                        }
                     }
                 }
             }
@@ -153,7 +226,8 @@ fn content_length(stream: &mut BufReader<&mut TcpStream>) -> usize {
     let mut content_length: Option<usize> = None;
     loop {
         let mut line = String::new();
-        if let Err(_) = stream.read_line(&mut line) {
+        // EOF before the end of the headers means the client hung up
+        if !matches!(stream.read_line(&mut line), Ok(n) if n > 0) {
             return 0;
         }
 
@@ -177,31 +251,43 @@ fn record_request(requests: &Arc<Mutex<Vec<String>>>, request: &str) {
     requests.push(request.to_string());
 }
 
-fn send_response(mut stream: &TcpStream, response_key: &str) {
+fn send_response(mut stream: &TcpStream, response_key: &str, framing: Framing) {
+    let extension = if framing.gzip { "xml.gz" } else { "xml" };
     let response_path: PathBuf = [
         env!("CARGO_MANIFEST_DIR"),
         "testdata",
         "responses",
-        &format!("{}.xml", response_key),
+        &format!("{}.{}", response_key, extension),
     ]
     .iter()
     .collect();
     eprintln!("Response path: {:?}", response_path);
-    let body = std::fs::read_to_string(response_path).unwrap();
+    let body = std::fs::read(response_path).unwrap();
 
     let mut buffer = String::new();
 
-    let code = response_key.split('-').last().unwrap();
+    if framing.interim {
+        buffer.push_str("HTTP/1.1 100 Continue\r\n\r\n");
+    }
+
+    let code = response_key.split('-').next_back().unwrap();
 
     buffer.push_str(&format!("HTTP/1.1 {} OK\r\n", code));
     buffer.push_str("Server: gSOAP/2.7\r\n");
     buffer.push_str("Content-Type: text/xml; charset=utf-8\r\n");
-    buffer.push_str(&format!("Content-Length: {}\r\n", body.as_bytes().len()));
-    buffer.push_str("Connection: close\r\n");
+    if framing.gzip {
+        buffer.push_str("Content-Encoding: gzip\r\n");
+    }
+    buffer.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    if framing.keep_alive {
+        buffer.push_str("Connection: keep-alive\r\n");
+    } else {
+        buffer.push_str("Connection: close\r\n");
+    }
     buffer.push_str("\r\n");
-    buffer.push_str(&body);
 
     //    eprintln!("sending response:\n{}", buffer);
     stream.write_all(buffer.as_bytes()).unwrap();
+    stream.write_all(&body).unwrap();
     stream.flush().unwrap();
 }
@@ -0,0 +1,399 @@
+//! Fixed-rate loop timing.
+//!
+//! Sleeping for one period per iteration drifts: the time spent in the
+//! iteration is added to every period, and on Windows, where RealFlight runs,
+//! sleeps are rounded up to the 15.6 ms timer tick. [RateLimiter] waits for
+//! absolute deadlines instead, so time spent in an iteration is taken out of
+//! the wait, and it sleeps to just short of each deadline and spins the rest
+//! of the way. [AsyncRateLimiter] is its counterpart for async loops, built
+//! on [tokio::time::Interval].
+//!
+//! ```no_run
+//! use realflight_bridge::timing::{RateLimiter, Tick};
+//! use realflight_bridge::{ControlInputs, RealFlightBridge, RealFlightLocalBridge};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let bridge = RealFlightLocalBridge::new()?;
+//! let mut limiter = RateLimiter::new(250.0);
+//! loop {
+//!     let state = bridge.exchange_data(&ControlInputs::default())?;
+//!     // ...
+//!     if let Tick::Missed { late } = limiter.wait() {
+//!         eprintln!("iteration overran its period by {:?}", late);
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+
+#[cfg(feature = "rt-tokio")]
+use tokio::time::{Interval, MissedTickBehavior};
+
+/// Time [RateLimiter] spins for before a deadline by default.
+pub const DEFAULT_SPIN: Duration = Duration::from_millis(1);
+
+/// Outcome of waiting for the end of a period.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tick {
+    /// The deadline was reached by waiting
+    OnTime,
+    /// The deadline had already passed by `late` when the wait began
+    Missed { late: Duration },
+}
+
+/// Paces a loop at a fixed rate with absolute deadlines.
+///
+/// The schedule starts when the limiter is created, and each [wait](Self::wait)
+/// returns at the end of the next period. When an iteration overruns its
+/// period, `wait` returns at once with [Tick::Missed] and the schedule
+/// restarts from the current time instead of bursting to catch up.
+///
+/// Waits sleep until [spin](Self::with_spin) before the deadline, then spin on
+/// the clock, which trades some CPU time for precision on coarse timers.
+#[derive(Debug)]
+pub struct RateLimiter<C: Clock = SystemClock> {
+    clock: C,
+    period: Duration,
+    spin: Duration,
+    deadline: Instant,
+    missed: u64,
+}
+
+impl RateLimiter {
+    /// Paces a loop at `rate_hz` on the system clock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate_hz` is not a positive, finite number.
+    pub fn new(rate_hz: f32) -> Self {
+        Self::with_clock(rate_hz, SystemClock)
+    }
+}
+
+impl<C: Clock> RateLimiter<C> {
+    /// Paces a loop at `rate_hz` on `clock`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate_hz` is not a positive, finite number.
+    pub fn with_clock(rate_hz: f32, clock: C) -> Self {
+        let period = period(rate_hz);
+        let deadline = clock.now();
+        RateLimiter {
+            clock,
+            period,
+            spin: DEFAULT_SPIN,
+            deadline,
+            missed: 0,
+        }
+    }
+
+    /// Spins for `spin` before each deadline instead of [DEFAULT_SPIN]. Zero
+    /// sleeps all the way.
+    pub fn with_spin(mut self, spin: Duration) -> Self {
+        self.spin = spin;
+        self
+    }
+
+    /// Time between deadlines.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Deadline of the last [wait](Self::wait), or the start of the schedule.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Number of deadlines missed so far.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    /// Restarts the schedule from the current time.
+    pub fn reset(&mut self) {
+        self.deadline = self.clock.now();
+    }
+
+    /// Waits until the end of the current period.
+    pub fn wait(&mut self) -> Tick {
+        self.deadline += self.period;
+        let now = self.clock.now();
+        if now >= self.deadline {
+            let late = now - self.deadline;
+            self.missed += 1;
+            self.deadline = now;
+            return Tick::Missed { late };
+        }
+
+        let remaining = self.deadline - now;
+        if remaining > self.spin {
+            self.clock.sleep(remaining - self.spin);
+        }
+        self.clock.spin_until(self.deadline);
+        Tick::OnTime
+    }
+}
+
+/// Paces an async loop at a fixed rate on a [tokio::time::Interval].
+///
+/// Like [RateLimiter], the schedule starts when the limiter is created and
+/// each [tick](Self::tick) completes at the end of the next period. What
+/// happens after an overrun follows the [MissedTickBehavior], which defaults
+/// to [MissedTickBehavior::Delay]: the schedule restarts from the late tick,
+/// as a [RateLimiter]'s does.
+///
+/// Requires the `rt-tokio` feature.
+#[cfg(feature = "rt-tokio")]
+#[derive(Debug)]
+pub struct AsyncRateLimiter {
+    interval: Interval,
+    missed: u64,
+}
+
+#[cfg(feature = "rt-tokio")]
+impl AsyncRateLimiter {
+    /// Paces a loop at `rate_hz`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate_hz` is not a positive, finite number.
+    pub fn new(rate_hz: f32) -> Self {
+        let period = period(rate_hz);
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        AsyncRateLimiter {
+            interval,
+            missed: 0,
+        }
+    }
+
+    /// Handles overruns with `behavior` instead of
+    /// [MissedTickBehavior::Delay].
+    pub fn with_missed_tick_behavior(mut self, behavior: MissedTickBehavior) -> Self {
+        self.interval.set_missed_tick_behavior(behavior);
+        self
+    }
+
+    /// How overruns are handled.
+    pub fn missed_tick_behavior(&self) -> MissedTickBehavior {
+        self.interval.missed_tick_behavior()
+    }
+
+    /// Time between deadlines.
+    pub fn period(&self) -> Duration {
+        self.interval.period()
+    }
+
+    /// Number of deadlines missed so far.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    /// Restarts the schedule one period from now.
+    pub fn reset(&mut self) {
+        self.interval.reset();
+    }
+
+    /// Waits until the end of the current period.
+    pub async fn tick(&mut self) -> Tick {
+        let started = tokio::time::Instant::now();
+        let deadline = self.interval.tick().await;
+        if started < deadline {
+            return Tick::OnTime;
+        }
+        self.missed += 1;
+        Tick::Missed {
+            late: started - deadline,
+        }
+    }
+}
+
+/// Period of `rate_hz`, checking it is usable.
+fn period(rate_hz: f32) -> Duration {
+    assert!(
+        rate_hz.is_finite() && rate_hz > 0.0,
+        "rate_hz must be positive and finite, got {}",
+        rate_hz
+    );
+    Duration::from_secs_f32(1.0 / rate_hz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// Rate achieved over `ticks` periods starting at `start`.
+    fn achieved_rate(start: Instant, ticks: u32) -> f64 {
+        ticks as f64 / start.elapsed().as_secs_f64()
+    }
+
+    mod rate_limiter_tests {
+        use super::*;
+
+        #[test]
+        fn waits_until_deadline() {
+            let clock = ManualClock::new();
+            let mut limiter = RateLimiter::with_clock(100.0, clock.clone());
+
+            assert_eq!(limiter.wait(), Tick::OnTime);
+            assert_eq!(limiter.wait(), Tick::OnTime);
+
+            assert_eq!(clock.elapsed(), limiter.period() * 2);
+            assert_eq!(limiter.deadline(), clock.now());
+        }
+
+        #[test]
+        fn subtracts_work_from_wait() {
+            let clock = ManualClock::new();
+            let mut limiter = RateLimiter::with_clock(100.0, clock.clone());
+
+            clock.advance(ms(4));
+            limiter.wait();
+
+            assert_eq!(clock.elapsed(), limiter.period());
+        }
+
+        #[test]
+        fn reports_missed_deadline() {
+            let clock = ManualClock::new();
+            let mut limiter = RateLimiter::with_clock(100.0, clock.clone());
+            let period = limiter.period();
+
+            clock.advance(period * 5 / 2);
+            let tick = limiter.wait();
+
+            assert_eq!(
+                tick,
+                Tick::Missed {
+                    late: period * 3 / 2
+                }
+            );
+            assert_eq!(limiter.missed(), 1);
+            // The schedule restarts from the overrun
+            assert_eq!(limiter.wait(), Tick::OnTime);
+            assert_eq!(clock.elapsed(), period * 5 / 2 + period);
+        }
+
+        #[test]
+        fn reset_restarts_schedule() {
+            let clock = ManualClock::new();
+            let mut limiter = RateLimiter::with_clock(100.0, clock.clone());
+            let period = limiter.period();
+
+            clock.advance(period * 3);
+            limiter.reset();
+
+            assert_eq!(limiter.wait(), Tick::OnTime);
+            assert_eq!(limiter.missed(), 0);
+            assert_eq!(clock.elapsed(), period * 4);
+        }
+
+        #[test]
+        fn holds_250_hz() {
+            let mut limiter = RateLimiter::new(250.0);
+            let start = Instant::now();
+
+            for _ in 0..100 {
+                limiter.wait();
+            }
+
+            let rate = achieved_rate(start, 100);
+            assert!((rate - 250.0).abs() < 12.5, "achieved {:.1} Hz", rate);
+        }
+
+        #[test]
+        fn holds_250_hz_without_spinning() {
+            let mut limiter = RateLimiter::new(250.0).with_spin(Duration::ZERO);
+            let start = Instant::now();
+
+            for _ in 0..100 {
+                limiter.wait();
+            }
+
+            let rate = achieved_rate(start, 100);
+            assert!((rate - 250.0).abs() < 12.5, "achieved {:.1} Hz", rate);
+        }
+
+        #[test]
+        #[should_panic(expected = "rate_hz must be positive")]
+        fn rejects_zero_rate() {
+            RateLimiter::new(0.0);
+        }
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    mod async_rate_limiter_tests {
+        use super::*;
+
+        #[tokio::test(start_paused = true)]
+        async fn ticks_at_end_of_period() {
+            let start = tokio::time::Instant::now();
+            let mut limiter = AsyncRateLimiter::new(100.0);
+
+            assert_eq!(limiter.tick().await, Tick::OnTime);
+            assert_eq!(limiter.tick().await, Tick::OnTime);
+
+            assert_eq!(start.elapsed(), limiter.period() * 2);
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn reports_missed_deadline() {
+            let start = tokio::time::Instant::now();
+            let mut limiter = AsyncRateLimiter::new(100.0);
+            let period = limiter.period();
+
+            tokio::time::advance(period * 5 / 2).await;
+            let tick = limiter.tick().await;
+
+            assert_eq!(
+                tick,
+                Tick::Missed {
+                    late: period * 3 / 2
+                }
+            );
+            assert_eq!(limiter.missed(), 1);
+            // Delay restarts the schedule from the late tick
+            assert_eq!(limiter.tick().await, Tick::OnTime);
+            assert_eq!(start.elapsed(), period * 5 / 2 + period);
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn bursts_when_asked() {
+            let start = tokio::time::Instant::now();
+            let mut limiter =
+                AsyncRateLimiter::new(100.0).with_missed_tick_behavior(MissedTickBehavior::Burst);
+            let period = limiter.period();
+            assert_eq!(limiter.missed_tick_behavior(), MissedTickBehavior::Burst);
+
+            tokio::time::advance(period * 5 / 2).await;
+            limiter.tick().await;
+            limiter.tick().await;
+
+            // The second tick catches up without waiting
+            assert_eq!(start.elapsed(), period * 5 / 2);
+            assert_eq!(limiter.missed(), 2);
+        }
+
+        #[tokio::test]
+        async fn holds_250_hz() {
+            let mut limiter = AsyncRateLimiter::new(250.0);
+            let start = Instant::now();
+
+            for _ in 0..100 {
+                limiter.tick().await;
+            }
+
+            let rate = achieved_rate(start, 100);
+            assert!((rate - 250.0).abs() < 12.5, "achieved {:.1} Hz", rate);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Conformance suite against a live RealFlight.
+//!
+//! Skipped unless `REALFLIGHT_CONFORMANCE=1` is set. The simulator is reached
+//! at `REALFLIGHT_SIMULATOR_HOST`, or the default RealFlight Link address:
+//!
+//! ```text
+//! REALFLIGHT_CONFORMANCE=1 cargo test --test conformance -- --nocapture
+//! ```
+
+use realflight_bridge::conformance::{self, ConformanceConfig};
+use realflight_bridge::{Configuration, DEFAULT_SIMULATOR_HOST, RealFlightLocalBridge};
+
+#[test]
+fn conforms_to_realflight() {
+    if std::env::var_os("REALFLIGHT_CONFORMANCE").is_none() {
+        eprintln!("skipping conformance suite; set REALFLIGHT_CONFORMANCE=1 to run it");
+        return;
+    }
+    let simulator_host = std::env::var("REALFLIGHT_SIMULATOR_HOST")
+        .unwrap_or_else(|_| DEFAULT_SIMULATOR_HOST.to_string());
+
+    let bridge = RealFlightLocalBridge::with_configuration(&Configuration {
+        simulator_host,
+        ..Default::default()
+    })
+    .expect("cannot connect to RealFlight");
+    let report = conformance::run(&bridge, &ConformanceConfig::default());
+
+    println!("{}", report);
+    assert!(report.passed(), "{}", report);
+}